@@ -0,0 +1,199 @@
+//! Experiment files, and running the experiments that they describe.
+use ecosystem::{
+    benchmarks::{BinarySolution, DeceptiveTrap, Function, NkLandscape, OneMax, Solution},
+    config::RunConfig,
+    history::History,
+    observer::Observer,
+    termination::StopReason,
+    Ecosystem, Individual, Objective, Organism,
+};
+use rand::{RngCore, SeedableRng};
+use rand_pcg::Pcg64;
+use serde::Deserialize;
+use std::{
+    error::Error,
+    fs,
+    ops::ControlFlow,
+    path::Path,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use toml::Value;
+
+/// An experiment file: the benchmark problem to solve, and how to run the
+/// ecosystem that solves it.
+///
+/// ```toml
+/// [problem]
+/// type = "Function"
+/// function = "Rastrigin"
+/// dimensions = 5
+///
+/// [run]
+/// population_size = 100
+/// selection = { type = "Tournament", size = 3 }
+/// ```
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Experiment {
+    pub problem: Problem,
+    #[serde(default)]
+    pub run: RunConfig,
+}
+
+/// A built-in benchmark problem.
+#[derive(Clone, Copy, Debug, Deserialize)]
+#[serde(tag = "type", deny_unknown_fields)]
+pub enum Problem {
+    /// A continuous test function, which is minimised.
+    Function {
+        function: Function,
+        dimensions: usize,
+    },
+    /// Maximising the number of ones in a bit string.
+    OneMax { bits: usize },
+    /// Concatenated deceptive traps of `order` bits.
+    DeceptiveTrap { bits: usize, order: usize },
+    /// A random NK landscape, generated from `seed` so that every run of an
+    /// experiment faces the same one.
+    NkLandscape {
+        n: usize,
+        k: usize,
+        #[serde(default)]
+        seed: u64,
+    },
+}
+
+impl Problem {
+    /// Returns the direction in which the problem's fitness is optimised.
+    fn objective(self) -> Objective {
+        match self {
+            Problem::Function { .. } => Objective::Minimize,
+            _ => Objective::Maximize,
+        }
+    }
+}
+
+/// The outcome of running an experiment.
+pub struct Outcome {
+    pub best: f64,
+    pub generations: u32,
+    pub evaluations: u64,
+    pub reason: StopReason,
+    pub elapsed: Duration,
+    pub history: Option<History>,
+}
+
+/// Reads an experiment file as a TOML value, so that its settings can be
+/// overridden before it is parsed.
+pub fn read(path: &Path) -> Result<Value, Box<dyn Error>> {
+    let text = fs::read_to_string(path)
+        .map_err(|error| format!("failed to read {}: {}", path.display(), error))?;
+    Ok(toml::from_str(&text)?)
+}
+
+/// Sets the setting at a dotted path, such as `run.mutation_rate`, creating
+/// any tables along the way.
+pub fn set(experiment: &mut Value, path: &str, value: Value) -> Result<(), Box<dyn Error>> {
+    let mut table = experiment;
+    let mut keys = path.split('.').peekable();
+    while let Some(key) = keys.next() {
+        let entries = table
+            .as_table_mut()
+            .ok_or_else(|| format!("{} is not within a table", path))?;
+        if keys.peek().is_none() {
+            entries.insert(key.to_string(), value);
+            return Ok(());
+        }
+        table = entries
+            .entry(key)
+            .or_insert_with(|| Value::Table(Default::default()));
+    }
+    Err("the setting's path is empty".into())
+}
+
+/// Parses a value given on the command line as TOML, treating anything that
+/// isn't valid TOML as a string.
+pub fn parse_value(text: &str) -> Value {
+    toml::from_str::<toml::Table>(&format!("value = {}", text))
+        .ok()
+        .and_then(|mut table| table.remove("value"))
+        .unwrap_or_else(|| Value::String(text.to_string()))
+}
+
+/// Parses and runs an experiment, printing the progress of each generation
+/// to standard error if `progress` is set.
+pub fn run(experiment: Value, progress: bool) -> Result<Outcome, Box<dyn Error>> {
+    let Experiment { problem, mut run } = experiment.try_into()?;
+    run.objective = problem.objective();
+
+    match problem {
+        Problem::Function {
+            function,
+            dimensions,
+        } => evolve(&run, progress, |mut rng| {
+            Solution::random(function, dimensions, &mut rng)
+        }),
+        Problem::OneMax { bits } => {
+            let problem = Arc::new(OneMax);
+            evolve(&run, progress, |mut rng| {
+                BinarySolution::random(bits, &problem, &mut rng)
+            })
+        }
+        Problem::DeceptiveTrap { bits, order } => {
+            let problem = Arc::new(DeceptiveTrap { order });
+            evolve(&run, progress, |mut rng| {
+                BinarySolution::random(bits, &problem, &mut rng)
+            })
+        }
+        Problem::NkLandscape { n, k, seed } => {
+            let problem = Arc::new(NkLandscape::random(n, k, &mut Pcg64::seed_from_u64(seed)));
+            evolve(&run, progress, |mut rng| {
+                BinarySolution::random(n, &problem, &mut rng)
+            })
+        }
+    }
+}
+
+/// Evolves a population of organisms made by `create` under the given
+/// configuration.
+fn evolve<O: Organism<Fitness = f64> + Clone + Send + Sync>(
+    config: &RunConfig,
+    progress: bool,
+    create: impl FnMut(&mut dyn RngCore) -> O,
+) -> Result<Outcome, Box<dyn Error>> {
+    let start = Instant::now();
+    let mut ecosystem = config.builder(create).record_history().build()?;
+    if progress {
+        ecosystem.add_observer(Progress);
+    }
+    let summary = ecosystem.run(config.termination())?;
+    Ok(Outcome {
+        best: ecosystem
+            .fittest_individual()
+            .map_or(f64::NAN, Individual::fitness),
+        generations: summary.generations,
+        evaluations: summary.evaluations,
+        reason: summary.reason,
+        elapsed: start.elapsed(),
+        history: ecosystem.history().cloned(),
+    })
+}
+
+/// Prints each generation's record as it is bred.
+struct Progress;
+
+impl<O: Organism + Send + Sync> Observer<O> for Progress {
+    fn on_generation_end(&mut self, ecosystem: &Ecosystem<O>) -> ControlFlow<()> {
+        if let Some(record) = ecosystem
+            .history()
+            .and_then(|history| history.records().last())
+        {
+            eprintln!(
+                "generation {:>6}  best {:>14.6}  mean {:>14.6}  std dev {:>12.6}",
+                record.generation, record.best, record.mean, record.fitness_std_dev
+            );
+        }
+        ControlFlow::Continue(())
+    }
+}
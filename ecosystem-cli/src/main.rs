@@ -0,0 +1,233 @@
+//! Runs experiments on the ecosystem crate's benchmark problems, described
+//! by TOML experiment files.
+mod experiment;
+
+use clap::{Parser, Subcommand};
+use experiment::Outcome;
+use std::{
+    error::Error,
+    fs::{self, File},
+    io::BufWriter,
+    path::{Path, PathBuf},
+    process,
+};
+use toml::Value;
+
+#[derive(Parser)]
+#[command(name = "ecosystem", version, about)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Runs an experiment, printing the progress of each generation.
+    Run {
+        /// The experiment file.
+        experiment: PathBuf,
+        /// Overrides a setting of the experiment, such as
+        /// `run.mutation_rate=0.1`.
+        #[arg(long = "set", value_name = "PATH=VALUE")]
+        overrides: Vec<String>,
+        /// Writes the history of the run to a CSV file.
+        #[arg(long, value_name = "FILE")]
+        history: Option<PathBuf>,
+        /// Doesn't print the progress of each generation.
+        #[arg(long, short)]
+        quiet: bool,
+    },
+    /// Runs an experiment once for each of several values of one setting.
+    Sweep {
+        /// The experiment file.
+        experiment: PathBuf,
+        /// The setting to vary, such as `run.mutation_rate`.
+        #[arg(long, value_name = "PATH")]
+        param: String,
+        /// The values to give the setting.
+        #[arg(long, value_delimiter = ',', required = true)]
+        values: Vec<String>,
+        #[command(flatten)]
+        repeats: Repeats,
+    },
+    /// Runs several experiments, to compare their results.
+    Compare {
+        /// The experiment files.
+        #[arg(required = true)]
+        experiments: Vec<PathBuf>,
+        #[command(flatten)]
+        repeats: Repeats,
+    },
+}
+
+/// How the runs of a sweep or comparison are repeated and recorded.
+#[derive(clap::Args)]
+struct Repeats {
+    /// Repeats each run with the seeds 0 to N - 1, rather than once with
+    /// the experiment's own seed.
+    #[arg(long, value_name = "N")]
+    seeds: Option<u64>,
+    /// Writes the history of every run to a CSV file in this directory.
+    #[arg(long, value_name = "DIR")]
+    history_dir: Option<PathBuf>,
+}
+
+impl Repeats {
+    /// Runs the given experiment once for each seed, naming the history
+    /// files after `label`.
+    fn run(&self, experiment: &Value, label: &str) -> Result<Vec<Outcome>, Box<dyn Error>> {
+        let seeds: Vec<Option<u64>> = match self.seeds {
+            Some(count) => (0..count).map(Some).collect(),
+            None => vec![None],
+        };
+        if let Some(directory) = &self.history_dir {
+            fs::create_dir_all(directory)?;
+        }
+
+        let mut outcomes = Vec::new();
+        for seed in seeds {
+            let mut experiment = experiment.clone();
+            let mut name = label.to_string();
+            if let Some(seed) = seed {
+                experiment::set(&mut experiment, "run.seed", Value::Integer(seed as i64))?;
+                name = format!("{}-seed{}", name, seed);
+            }
+            let outcome = experiment::run(experiment, false)?;
+            eprintln!(
+                "{}: best {} after {} generations",
+                name, outcome.best, outcome.generations
+            );
+            if let Some(directory) = &self.history_dir {
+                write_history(&outcome, &directory.join(format!("{}.csv", name)))?;
+            }
+            outcomes.push(outcome);
+        }
+        Ok(outcomes)
+    }
+}
+
+/// Writes the history of a run to a CSV file.
+fn write_history(outcome: &Outcome, path: &Path) -> Result<(), Box<dyn Error>> {
+    if let Some(history) = &outcome.history {
+        history
+            .write_csv(BufWriter::new(File::create(path)?))
+            .map_err(|error| format!("failed to write {}: {}", path.display(), error))?;
+    }
+    Ok(())
+}
+
+/// Prints a row of the summary table of a sweep or comparison.
+fn print_row(label: &str, outcomes: &[Outcome]) {
+    let count = outcomes.len() as f64;
+    let mean_best = outcomes.iter().map(|outcome| outcome.best).sum::<f64>() / count;
+    let std_dev = (outcomes
+        .iter()
+        .map(|outcome| (outcome.best - mean_best).powi(2))
+        .sum::<f64>()
+        / count)
+        .sqrt();
+    let mean_generations = outcomes
+        .iter()
+        .map(|outcome| outcome.generations as f64)
+        .sum::<f64>()
+        / count;
+    let mean_evaluations = outcomes
+        .iter()
+        .map(|outcome| outcome.evaluations as f64)
+        .sum::<f64>()
+        / count;
+    let mean_seconds = outcomes
+        .iter()
+        .map(|outcome| outcome.elapsed.as_secs_f64())
+        .sum::<f64>()
+        / count;
+    println!(
+        "{:<24} {:>14.6} {:>12.6} {:>12.1} {:>14.1} {:>10.3}",
+        label, mean_best, std_dev, mean_generations, mean_evaluations, mean_seconds
+    );
+}
+
+fn print_header(label: &str) {
+    println!(
+        "{:<24} {:>14} {:>12} {:>12} {:>14} {:>10}",
+        label, "mean best", "std dev", "generations", "evaluations", "seconds"
+    );
+}
+
+/// Names a run after its experiment file.
+fn file_label(path: &Path) -> String {
+    path.file_stem().map_or_else(
+        || path.display().to_string(),
+        |stem| stem.to_string_lossy().into_owned(),
+    )
+}
+
+fn main() {
+    if let Err(error) = execute(Cli::parse().command) {
+        eprintln!("error: {}", error);
+        process::exit(1);
+    }
+}
+
+fn execute(command: Command) -> Result<(), Box<dyn Error>> {
+    match command {
+        Command::Run {
+            experiment: path,
+            overrides,
+            history,
+            quiet,
+        } => {
+            let mut experiment = experiment::read(&path)?;
+            for setting in &overrides {
+                let (path, value) = setting
+                    .split_once('=')
+                    .ok_or_else(|| format!("expected PATH=VALUE, but found {}", setting))?;
+                experiment::set(&mut experiment, path, experiment::parse_value(value))?;
+            }
+            let outcome = experiment::run(experiment, !quiet)?;
+            println!("stopped: {:?}", outcome.reason);
+            println!("best fitness: {}", outcome.best);
+            println!("generations: {}", outcome.generations);
+            println!("evaluations: {}", outcome.evaluations);
+            println!("elapsed: {:.3}s", outcome.elapsed.as_secs_f64());
+            if let Some(path) = history {
+                write_history(&outcome, &path)?;
+            }
+        }
+        Command::Sweep {
+            experiment: path,
+            param,
+            values,
+            repeats,
+        } => {
+            let base = experiment::read(&path)?;
+            let mut rows = Vec::new();
+            for value in &values {
+                let mut experiment = base.clone();
+                experiment::set(&mut experiment, &param, experiment::parse_value(value))?;
+                let label = format!("{}={}", param, value);
+                rows.push((value, repeats.run(&experiment, &label)?));
+            }
+            print_header(&param);
+            for (value, outcomes) in &rows {
+                print_row(value, outcomes);
+            }
+        }
+        Command::Compare {
+            experiments,
+            repeats,
+        } => {
+            let mut rows = Vec::new();
+            for path in &experiments {
+                let label = file_label(path);
+                let outcomes = repeats.run(&experiment::read(path)?, &label)?;
+                rows.push((label, outcomes));
+            }
+            print_header("experiment");
+            for (label, outcomes) in &rows {
+                print_row(label, outcomes);
+            }
+        }
+    }
+    Ok(())
+}
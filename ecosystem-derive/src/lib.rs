@@ -0,0 +1,222 @@
+//! The `#[derive(Organism)]` macro for the [`ecosystem`] crate, which
+//! re-exports it when its `derive` feature is enabled.
+//!
+//! [`ecosystem`]: https://docs.rs/ecosystem
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{
+    parse_macro_input, parse_quote, Attribute, Data, DeriveInput, Error, Fields, Index, LitStr,
+    Member, Type,
+};
+
+/// Implements `Organism` for a struct by breeding and mutating each of its
+/// fields as a `Gene`, leaving only the fitness to be written by hand.
+///
+/// Every field must be a gene (a number, a boolean, a vector or array of
+/// genes, or an organism itself), unless it is marked with
+/// `#[organism(skip)]`, in which case children inherit it unchanged from
+/// their first parent and it is never mutated. Skipped fields must
+/// implement `Clone`.
+///
+/// The fitness is taken from an inherent method `fn fitness(&self)`, which
+/// must be defined alongside the struct. The type of the fitness value is
+/// `f64`, unless it is given by the struct's `#[organism(fitness = Type)]`
+/// attribute.
+///
+/// By default, each field of a child is crossed from its parents, taking
+/// one parent's value or the other's. With `#[organism(crossover =
+/// "blend")]`, the parents' values are blended instead, which averages
+/// numbers.
+///
+/// # Examples
+///
+/// ```rust
+/// use ecosystem::{Ecosystem, Organism};
+/// use rand::Rng;
+///
+/// #[derive(Organism)]
+/// #[organism(crossover = "blend")]
+/// struct Point {
+///     x: f64,
+///     y: f64,
+///     // The same for every point, so it is never bred or mutated
+///     #[organism(skip)]
+///     label: &'static str,
+/// }
+///
+/// impl Point {
+///     // Closest to (3, -1)
+///     fn fitness(&self) -> f64 {
+///         1.0 / (1.0 + (self.x - 3.0).powi(2) + (self.y + 1.0).powi(2))
+///     }
+/// }
+///
+/// let mut rng = rand::thread_rng();
+/// let points = (0..50).map(|_| Point {
+///     x: rng.gen_range(-10.0, 10.0),
+///     y: rng.gen_range(-10.0, 10.0),
+///     label: "point",
+/// });
+/// let mut ecosystem = Ecosystem::builder()
+///     .organisms(points)
+///     .mutation_rate(0.1)
+///     .elitism(1)
+///     .build()
+///     .unwrap();
+/// for _ in 0..100 {
+///     ecosystem.breed_next_generation()?;
+/// }
+///
+/// let fittest = ecosystem.fittest().unwrap();
+/// assert!((fittest.x - 3.0).abs() < 0.5 && (fittest.y + 1.0).abs() < 0.5);
+/// assert_eq!(fittest.label, "point");
+/// # Ok::<(), ecosystem::EcosystemError>(())
+/// ```
+#[proc_macro_derive(Organism, attributes(organism))]
+pub fn derive_organism(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+/// The options given by a struct's `#[organism(...)]` attributes.
+struct Options {
+    fitness: Type,
+    blend: bool,
+}
+
+impl Options {
+    fn parse(attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut options = Options {
+            fitness: parse_quote!(f64),
+            blend: false,
+        };
+        for attr in attrs.iter().filter(|attr| attr.path().is_ident("organism")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("fitness") {
+                    options.fitness = meta.value()?.parse()?;
+                } else if meta.path.is_ident("crossover") {
+                    let crossover: LitStr = meta.value()?.parse()?;
+                    options.blend = match crossover.value().as_str() {
+                        "uniform" => false,
+                        "blend" => true,
+                        _ => {
+                            return Err(Error::new_spanned(
+                                crossover,
+                                "expected \"uniform\" or \"blend\"",
+                            ))
+                        }
+                    };
+                } else {
+                    return Err(meta.error("expected `fitness` or `crossover`"));
+                }
+                Ok(())
+            })?;
+        }
+        Ok(options)
+    }
+}
+
+/// Returns whether a field is marked with `#[organism(skip)]`.
+fn is_skipped(attrs: &[Attribute]) -> syn::Result<bool> {
+    let mut skipped = false;
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("organism")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("skip") {
+                skipped = true;
+                Ok(())
+            } else {
+                Err(meta.error("expected `skip`"))
+            }
+        })?;
+    }
+    Ok(skipped)
+}
+
+fn expand(mut input: DeriveInput) -> syn::Result<TokenStream2> {
+    let options = Options::parse(&input.attrs)?;
+    let fields = match &input.data {
+        Data::Struct(data) => &data.fields,
+        _ => {
+            return Err(Error::new_spanned(
+                &input.ident,
+                "`Organism` can only be derived for structs",
+            ))
+        }
+    };
+    let members: Vec<Member> = match fields {
+        Fields::Named(fields) => fields
+            .named
+            .iter()
+            .map(|field| Member::Named(field.ident.clone().unwrap()))
+            .collect(),
+        _ => (0..fields.len())
+            .map(|index| Member::Unnamed(Index::from(index)))
+            .collect(),
+    };
+
+    let gene = quote!(::ecosystem::genome::Gene);
+    let cross = if options.blend {
+        quote!(blend)
+    } else {
+        quote!(cross)
+    };
+    let mut bounds = Vec::new();
+    let mut children = Vec::new();
+    let mut mutations = Vec::new();
+    for (field, member) in fields.iter().zip(&members) {
+        let ty = &field.ty;
+        if is_skipped(&field.attrs)? {
+            bounds.push(quote!(#ty: ::core::clone::Clone));
+            children.push(quote!(#member: ::core::clone::Clone::clone(&self.#member)));
+        } else {
+            bounds.push(quote!(#ty: #gene));
+            children.push(quote!(#member: #gene::#cross(&self.#member, &other.#member, rng)));
+            mutations.push(quote!(#gene::perturb(&mut self.#member, rate, rng);));
+        }
+    }
+
+    let where_clause = input.generics.make_where_clause();
+    for bound in bounds {
+        where_clause.predicates.push(syn::parse2(bound)?);
+    }
+    let name = &input.ident;
+    let fitness = &options.fitness;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::ecosystem::Organism for #name #ty_generics #where_clause {
+            type Fitness = #fitness;
+
+            fn fitness(&self) -> #fitness {
+                // Inherent methods take precedence over trait methods, so
+                // this calls the struct's own `fitness` method. Without one,
+                // the call is ambiguous or returns the wrong type, rather
+                // than recursing forever.
+                struct DefineAnInherentFitnessMethod;
+                trait MissingFitness {
+                    fn fitness(&self) -> DefineAnInherentFitnessMethod {
+                        DefineAnInherentFitnessMethod
+                    }
+                }
+                impl<T: ?Sized> MissingFitness for T {}
+                Self::fitness(self)
+            }
+
+            fn breed(
+                &self,
+                other: &Self,
+                rng: &mut impl ::ecosystem::__private::Rng,
+            ) -> Self {
+                Self {
+                    #(#children,)*
+                }
+            }
+
+            fn mutate(&mut self, rate: f64, rng: &mut impl ::ecosystem::__private::Rng) {
+                #(#mutations)*
+            }
+        }
+    })
+}
@@ -0,0 +1,83 @@
+/*
+ * Approximates π with an ecosystem whose genomes are owned by C.
+ *
+ * Build the library with `cargo build --release -p ecosystem-ffi`, then,
+ * from the repository root:
+ *
+ *     cc ecosystem-ffi/examples/pi_approx.c -Iecosystem-ffi/include \
+ *         -Ltarget/release -lecosystem_ffi -o pi_approx
+ *     LD_LIBRARY_PATH=target/release ./pi_approx
+ */
+#include <math.h>
+#include <stdio.h>
+#include <stdlib.h>
+
+#include "ecosystem.h"
+
+#define POPULATION_COUNT 10
+#define GENERATIONS 50
+
+/* Turns a seed from the ecosystem into a number in [-1, 1). */
+static double random_unit(uint64_t seed) {
+    /* The finalizer of SplitMix64 */
+    seed = (seed ^ (seed >> 30)) * 0xbf58476d1ce4e5b9ULL;
+    seed = (seed ^ (seed >> 27)) * 0x94d049bb133111ebULL;
+    seed ^= seed >> 31;
+    return (double)(seed >> 11) / (double)(1ULL << 52) - 1.0;
+}
+
+static double fitness(void *user_data, const void *genome) {
+    (void)user_data;
+    return 1.0 / fabs(M_PI - *(const double *)genome);
+}
+
+static void *breed(void *user_data, const void *a, const void *b, uint64_t seed) {
+    (void)user_data;
+    (void)seed;
+    double *child = malloc(sizeof(double));
+    *child = (*(const double *)a + *(const double *)b) / 2.0;
+    return child;
+}
+
+static void mutate(void *user_data, void *genome, double rate, uint64_t seed) {
+    (void)user_data;
+    *(double *)genome += rate * random_unit(seed);
+}
+
+static void free_genome(void *user_data, void *genome) {
+    (void)user_data;
+    free(genome);
+}
+
+int main(void) {
+    void *genomes[POPULATION_COUNT];
+    for (size_t i = 0; i < POPULATION_COUNT; i++) {
+        double *value = malloc(sizeof(double));
+        *value = random_unit(i) * 10.0;
+        genomes[i] = value;
+    }
+
+    EcosystemCallbacks callbacks = {NULL, fitness, breed, mutate, free_genome};
+    EcosystemConfig config = ecosystem_config_default();
+    config.mutation_rate = 0.1;
+    config.seeded = true;
+    config.seed = 42;
+
+    Ecosystem *ecosystem;
+    if (ecosystem_new(genomes, POPULATION_COUNT, callbacks, &config, &ecosystem) != ECOSYSTEM_OK) {
+        fprintf(stderr, "%s\n", ecosystem_last_error());
+        return 1;
+    }
+    for (int i = 0; i < GENERATIONS; i++) {
+        const void *best;
+        if (ecosystem_step(ecosystem) != ECOSYSTEM_OK ||
+            ecosystem_best(ecosystem, &best, NULL) != ECOSYSTEM_OK) {
+            fprintf(stderr, "%s\n", ecosystem_last_error());
+            ecosystem_free(ecosystem);
+            return 1;
+        }
+        printf("%f\n", *(const double *)best);
+    }
+    ecosystem_free(ecosystem);
+    return 0;
+}
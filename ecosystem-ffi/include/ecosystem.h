@@ -0,0 +1,134 @@
+/*
+ * A C interface to the ecosystem genetic algorithms library.
+ *
+ * The host program owns the genomes, which the ecosystem sees only as
+ * opaque pointers, and supplies the fitness, breeding and mutation
+ * functions as callbacks. Selection, elitism and the rest of the evolution
+ * loop run inside the library.
+ */
+#ifndef ECOSYSTEM_H
+#define ECOSYSTEM_H
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* The outcome of a call. On failure, `ecosystem_last_error` describes it. */
+typedef enum EcosystemStatus {
+    /* The call succeeded. */
+    ECOSYSTEM_OK = 0,
+    /* An argument was invalid, such as a null pointer or a configuration
+     * that the ecosystem can't be built with. */
+    ECOSYSTEM_INVALID_ARGUMENT = 1,
+    /* A generation couldn't be bred. */
+    ECOSYSTEM_BREEDING_FAILED = 2,
+    /* The ecosystem panicked. It should be freed without being used again. */
+    ECOSYSTEM_PANICKED = 3,
+} EcosystemStatus;
+
+/*
+ * The host program's genetic operators.
+ *
+ * Genomes are owned by the ecosystem once handed to it, which releases them
+ * with `free_genome`. `breed` and `mutate` are given a seed drawn from the
+ * ecosystem's random number generator, so a seeded run is reproducible if
+ * they make their random choices from it.
+ *
+ * Unless the ecosystem is configured to use a single thread, the callbacks
+ * are called from several threads at once, so they must be thread-safe.
+ */
+typedef struct EcosystemCallbacks {
+    /* Passed as the first argument to every callback. */
+    void *user_data;
+    /* Returns the fitness of a genome. */
+    double (*fitness)(void *user_data, const void *genome);
+    /* Returns a new genome bred from two parents. */
+    void *(*breed)(void *user_data, const void *a, const void *b, uint64_t seed);
+    /* Mutates a genome in place, by an amount determined by the rate. */
+    void (*mutate)(void *user_data, void *genome, double rate, uint64_t seed);
+    /* Releases a genome that the ecosystem has no more use for. Genomes are
+     * leaked if this is null. */
+    void (*free_genome)(void *user_data, void *genome);
+} EcosystemCallbacks;
+
+/* How an ecosystem evolves, with the defaults given by
+ * `ecosystem_config_default`. */
+typedef struct EcosystemConfig {
+    /* The mutation rate, between 0 and 1. */
+    double mutation_rate;
+    /* The number of the best genomes carried over to each new generation
+     * unchanged. */
+    size_t elitism;
+    /* The number of genomes competing in each tournament to become a
+     * parent, or zero to choose parents in proportion to their fitness. */
+    size_t tournament_size;
+    /* Whether lower fitness values are better. */
+    bool minimize;
+    /* Whether to seed the random number generator with `seed`, rather than
+     * from the operating system. */
+    bool seeded;
+    /* The seed for the random number generator, if `seeded` is set. */
+    uint64_t seed;
+    /* The number of threads to evaluate and breed genomes on, or zero to
+     * use the library's global thread pool. */
+    size_t threads;
+} EcosystemConfig;
+
+/* An opaque handle to an ecosystem of host genomes. */
+typedef struct EcosystemHandle Ecosystem;
+
+/* Returns the default configuration: the default mutation rate, no
+ * elitism, fitness-proportionate selection, maximised fitness, seeding from
+ * the operating system, and the global thread pool. */
+EcosystemConfig ecosystem_config_default(void);
+
+/* Creates an ecosystem from `count` genomes, storing its handle in `out`.
+ * The ecosystem takes ownership of the genomes, even if it can't be
+ * created. `config` may be null for the default configuration. */
+EcosystemStatus ecosystem_new(void *const *genomes, size_t count, EcosystemCallbacks callbacks,
+                              const EcosystemConfig *config, Ecosystem **out);
+
+/* Frees an ecosystem, along with all of its genomes. Does nothing if
+ * `ecosystem` is null. */
+void ecosystem_free(Ecosystem *ecosystem);
+
+/* Breeds the next generation, replacing the current one. */
+EcosystemStatus ecosystem_step(Ecosystem *ecosystem);
+
+/* Breeds generations until `generations` more have been bred or, if
+ * `target` isn't null, the best fitness reaches the target it points to.
+ * The number of generations bred is stored in `bred` unless it's null. */
+EcosystemStatus ecosystem_run(Ecosystem *ecosystem, uint32_t generations, const double *target,
+                              uint32_t *bred);
+
+/* Returns the number of generations bred so far. */
+uint32_t ecosystem_generation(const Ecosystem *ecosystem);
+
+/* Returns the number of genomes in the current generation. */
+size_t ecosystem_len(const Ecosystem *ecosystem);
+
+/* Returns the genome at `index` in the current generation, or null if the
+ * index is out of range. The genome still belongs to the ecosystem, and is
+ * only valid until the next generation is bred. */
+const void *ecosystem_genome(const Ecosystem *ecosystem, size_t index);
+
+/* Stores the genome with the best fitness in the current generation in
+ * `genome`, and its fitness in `fitness`, unless either is null. The genome
+ * still belongs to the ecosystem, and is only valid until the next
+ * generation is bred. */
+EcosystemStatus ecosystem_best(const Ecosystem *ecosystem, const void **genome, double *fitness);
+
+/* Returns a description of the most recent failure on the calling thread,
+ * or null if nothing has failed. The description is valid until the next
+ * failure on the same thread. */
+const char *ecosystem_last_error(void);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
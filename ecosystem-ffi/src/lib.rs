@@ -0,0 +1,428 @@
+//! A C interface to the ecosystem crate, for embedding the evolution loop in
+//! programs written in other languages.
+//!
+//! The host program owns the genomes, which the ecosystem sees only as
+//! opaque pointers, and supplies the fitness, breeding and mutation
+//! functions as function pointers in an [`EcosystemCallbacks`]. Everything
+//! else (selection, elitism and the rest of the loop) runs in Rust. The C
+//! declarations are in `include/ecosystem.h`.
+//!
+//! Every function reports failure through its [`EcosystemStatus`], with a
+//! description of the most recent failure on the calling thread available
+//! from [`ecosystem_last_error`].
+//!
+//! [`EcosystemCallbacks`]: struct.EcosystemCallbacks.html
+//! [`EcosystemStatus`]: enum.EcosystemStatus.html
+//! [`ecosystem_last_error`]: fn.ecosystem_last_error.html
+use ecosystem::{
+    selection::Tournament,
+    termination::{MaxGenerations, Search, StopReason, TargetFitness, Termination},
+    Ecosystem, Individual, Objective, Organism, DEFAULT_MUTATION_RATE,
+};
+use rand::Rng;
+use std::{
+    cell::RefCell,
+    ffi::CString,
+    os::raw::{c_char, c_void},
+    panic::{self, AssertUnwindSafe},
+    ptr,
+    sync::Arc,
+};
+
+/// The outcome of a call through the C interface.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EcosystemStatus {
+    /// The call succeeded.
+    Ok = 0,
+    /// An argument was invalid, such as a null pointer or a configuration
+    /// that the ecosystem can't be built with.
+    InvalidArgument = 1,
+    /// A generation couldn't be bred.
+    BreedingFailed = 2,
+    /// The ecosystem panicked. It should be freed without being used again.
+    Panicked = 3,
+}
+
+/// The host program's genetic operators.
+///
+/// Genomes are pointers owned by the ecosystem once handed to it, which
+/// releases them with `free_genome`. `breed` and `mutate` are given a seed
+/// drawn from the ecosystem's random number generator, so a seeded run is
+/// reproducible if they make their random choices from it.
+///
+/// Unless the ecosystem is configured to use a single thread, the callbacks
+/// are called from several threads at once, so they must be thread-safe.
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct EcosystemCallbacks {
+    /// Passed as the first argument to every callback.
+    pub user_data: *mut c_void,
+    /// Returns the fitness of a genome.
+    pub fitness: extern "C" fn(user_data: *mut c_void, genome: *const c_void) -> f64,
+    /// Returns a new genome bred from two parents.
+    pub breed: extern "C" fn(
+        user_data: *mut c_void,
+        a: *const c_void,
+        b: *const c_void,
+        seed: u64,
+    ) -> *mut c_void,
+    /// Mutates a genome in place, by an amount determined by the rate.
+    pub mutate: extern "C" fn(user_data: *mut c_void, genome: *mut c_void, rate: f64, seed: u64),
+    /// Releases a genome that the ecosystem has no more use for. Genomes are
+    /// leaked if this is null.
+    pub free_genome: Option<extern "C" fn(user_data: *mut c_void, genome: *mut c_void)>,
+}
+
+// The host promises that the callbacks and user data can be used from any
+// thread, as documented above
+unsafe impl Send for EcosystemCallbacks {}
+unsafe impl Sync for EcosystemCallbacks {}
+
+/// How an ecosystem evolves, with the defaults given by
+/// [`ecosystem_config_default`].
+///
+/// [`ecosystem_config_default`]: fn.ecosystem_config_default.html
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct EcosystemConfig {
+    /// The mutation rate, between 0 and 1.
+    pub mutation_rate: f64,
+    /// The number of the best genomes carried over to each new generation
+    /// unchanged.
+    pub elitism: usize,
+    /// The number of genomes competing in each tournament to become a
+    /// parent, or zero to choose parents in proportion to their fitness.
+    pub tournament_size: usize,
+    /// Whether lower fitness values are better.
+    pub minimize: bool,
+    /// Whether to seed the random number generator with `seed`, rather than
+    /// from the operating system.
+    pub seeded: bool,
+    /// The seed for the random number generator, if `seeded` is set.
+    pub seed: u64,
+    /// The number of threads to evaluate and breed genomes on, or zero to
+    /// use rayon's global thread pool.
+    pub threads: usize,
+}
+
+/// An opaque handle to an ecosystem of host genomes.
+pub struct EcosystemHandle {
+    ecosystem: Ecosystem<Genome>,
+}
+
+/// An organism whose genome belongs to the host program.
+struct Genome {
+    pointer: *mut c_void,
+    callbacks: Arc<EcosystemCallbacks>,
+}
+
+// Genomes are only touched through the callbacks, which must be thread-safe
+unsafe impl Send for Genome {}
+unsafe impl Sync for Genome {}
+
+impl Organism for Genome {
+    type Fitness = f64;
+
+    fn fitness(&self) -> f64 {
+        (self.callbacks.fitness)(self.callbacks.user_data, self.pointer)
+    }
+
+    fn breed(&self, other: &Self, rng: &mut impl Rng) -> Self {
+        let pointer = (self.callbacks.breed)(
+            self.callbacks.user_data,
+            self.pointer,
+            other.pointer,
+            rng.gen(),
+        );
+        Genome {
+            pointer,
+            callbacks: Arc::clone(&self.callbacks),
+        }
+    }
+
+    fn mutate(&mut self, rate: f64, rng: &mut impl Rng) {
+        (self.callbacks.mutate)(self.callbacks.user_data, self.pointer, rate, rng.gen());
+    }
+}
+
+impl Drop for Genome {
+    fn drop(&mut self) {
+        if let Some(free_genome) = self.callbacks.free_genome {
+            free_genome(self.callbacks.user_data, self.pointer);
+        }
+    }
+}
+
+/// Stops a run after a number of generations, or on reaching a target
+/// fitness if there is one.
+struct Until {
+    generations: MaxGenerations,
+    target: Option<TargetFitness<f64>>,
+}
+
+impl<S: Search<Fitness = f64>> Termination<S> for Until {
+    fn check(&mut self, search: &S) -> Option<StopReason> {
+        let target = self.target.as_mut().and_then(|target| target.check(search));
+        target.or_else(|| self.generations.check(search))
+    }
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/// Records the description of a failure for `ecosystem_last_error`.
+fn fail(status: EcosystemStatus, description: impl ToString) -> EcosystemStatus {
+    // Descriptions never contain nul bytes, as they come from this crate
+    let description = CString::new(description.to_string()).unwrap_or_default();
+    LAST_ERROR.with(|last| *last.borrow_mut() = Some(description));
+    status
+}
+
+/// Runs the body of an interface function, turning a panic into a status,
+/// as unwinding into the host program is undefined behaviour.
+fn guard(body: impl FnOnce() -> EcosystemStatus) -> EcosystemStatus {
+    panic::catch_unwind(AssertUnwindSafe(body))
+        .unwrap_or_else(|_| fail(EcosystemStatus::Panicked, "the ecosystem panicked"))
+}
+
+/// Returns the default configuration: the default mutation rate, no
+/// elitism, fitness-proportionate selection, maximised fitness, seeding
+/// from the operating system, and rayon's global thread pool.
+#[no_mangle]
+pub extern "C" fn ecosystem_config_default() -> EcosystemConfig {
+    EcosystemConfig {
+        mutation_rate: DEFAULT_MUTATION_RATE,
+        elitism: 0,
+        tournament_size: 0,
+        minimize: false,
+        seeded: false,
+        seed: 0,
+        threads: 0,
+    }
+}
+
+/// Creates an ecosystem from `count` genomes, storing its handle in `out`.
+///
+/// The ecosystem takes ownership of the genomes, even if it can't be
+/// created. `config` may be null for the default configuration.
+///
+/// # Safety
+///
+/// `genomes` must point to `count` genomes, `config` must be null or valid,
+/// and `out` must be valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn ecosystem_new(
+    genomes: *const *mut c_void,
+    count: usize,
+    callbacks: EcosystemCallbacks,
+    config: *const EcosystemConfig,
+    out: *mut *mut EcosystemHandle,
+) -> EcosystemStatus {
+    guard(|| {
+        if out.is_null() || (genomes.is_null() && count > 0) {
+            return fail(EcosystemStatus::InvalidArgument, "a pointer was null");
+        }
+        let callbacks = Arc::new(callbacks);
+        let genomes = if count > 0 {
+            std::slice::from_raw_parts(genomes, count)
+        } else {
+            &[]
+        };
+        let organisms = genomes.iter().map(|&pointer| Genome {
+            pointer,
+            callbacks: Arc::clone(&callbacks),
+        });
+        let config = config
+            .as_ref()
+            .copied()
+            .unwrap_or_else(|| ecosystem_config_default());
+
+        let mut builder = Ecosystem::builder()
+            .organisms(organisms)
+            .mutation_rate(config.mutation_rate)
+            .elitism(config.elitism);
+        if config.tournament_size > 0 {
+            builder = builder.selection(Tournament {
+                size: config.tournament_size,
+            });
+        }
+        if config.minimize {
+            builder = builder.objective(Objective::Minimize);
+        }
+        if config.seeded {
+            builder = builder.seed(config.seed);
+        }
+        if config.threads > 0 {
+            builder = builder.threads(config.threads);
+        }
+        match builder.build() {
+            Ok(ecosystem) => {
+                *out = Box::into_raw(Box::new(EcosystemHandle { ecosystem }));
+                EcosystemStatus::Ok
+            }
+            Err(error) => fail(EcosystemStatus::InvalidArgument, error),
+        }
+    })
+}
+
+/// Frees an ecosystem, along with all of its genomes. Does nothing if
+/// `ecosystem` is null.
+///
+/// # Safety
+///
+/// `ecosystem` must be null or a handle from `ecosystem_new` that hasn't
+/// already been freed.
+#[no_mangle]
+pub unsafe extern "C" fn ecosystem_free(ecosystem: *mut EcosystemHandle) {
+    if !ecosystem.is_null() {
+        drop(Box::from_raw(ecosystem));
+    }
+}
+
+/// Breeds the next generation, replacing the current one.
+///
+/// # Safety
+///
+/// `ecosystem` must be a valid handle.
+#[no_mangle]
+pub unsafe extern "C" fn ecosystem_step(ecosystem: *mut EcosystemHandle) -> EcosystemStatus {
+    guard(|| match ecosystem.as_mut() {
+        Some(handle) => match handle.ecosystem.breed_next_generation() {
+            Ok(()) => EcosystemStatus::Ok,
+            Err(error) => fail(EcosystemStatus::BreedingFailed, error),
+        },
+        None => fail(EcosystemStatus::InvalidArgument, "the ecosystem was null"),
+    })
+}
+
+/// Breeds generations until `generations` more have been bred or, if
+/// `target` isn't null, the best fitness reaches the target it points to.
+/// The number of generations bred is stored in `bred` unless it's null.
+///
+/// # Safety
+///
+/// `ecosystem` must be a valid handle, and `target` and `bred` must each be
+/// null or valid.
+#[no_mangle]
+pub unsafe extern "C" fn ecosystem_run(
+    ecosystem: *mut EcosystemHandle,
+    generations: u32,
+    target: *const f64,
+    bred: *mut u32,
+) -> EcosystemStatus {
+    guard(|| {
+        let handle = match ecosystem.as_mut() {
+            Some(handle) => handle,
+            None => return fail(EcosystemStatus::InvalidArgument, "the ecosystem was null"),
+        };
+        let until = Until {
+            generations: MaxGenerations(handle.ecosystem.generation().saturating_add(generations)),
+            target: target.as_ref().copied().map(TargetFitness),
+        };
+        match handle.ecosystem.run(until) {
+            Ok(summary) => {
+                if let Some(bred) = bred.as_mut() {
+                    *bred = summary.generations;
+                }
+                EcosystemStatus::Ok
+            }
+            Err(error) => fail(EcosystemStatus::BreedingFailed, error),
+        }
+    })
+}
+
+/// Returns the number of generations bred so far, or zero if `ecosystem` is
+/// null.
+///
+/// # Safety
+///
+/// `ecosystem` must be null or a valid handle.
+#[no_mangle]
+pub unsafe extern "C" fn ecosystem_generation(ecosystem: *const EcosystemHandle) -> u32 {
+    ecosystem
+        .as_ref()
+        .map_or(0, |handle| handle.ecosystem.generation())
+}
+
+/// Returns the number of genomes in the current generation, or zero if
+/// `ecosystem` is null.
+///
+/// # Safety
+///
+/// `ecosystem` must be null or a valid handle.
+#[no_mangle]
+pub unsafe extern "C" fn ecosystem_len(ecosystem: *const EcosystemHandle) -> usize {
+    ecosystem
+        .as_ref()
+        .map_or(0, |handle| handle.ecosystem.individuals().len())
+}
+
+/// Returns the genome at `index` in the current generation, or null if the
+/// index is out of range.
+///
+/// The genome still belongs to the ecosystem, and is only valid until the
+/// next generation is bred.
+///
+/// # Safety
+///
+/// `ecosystem` must be null or a valid handle.
+#[no_mangle]
+pub unsafe extern "C" fn ecosystem_genome(
+    ecosystem: *const EcosystemHandle,
+    index: usize,
+) -> *const c_void {
+    ecosystem
+        .as_ref()
+        .and_then(|handle| handle.ecosystem.individuals().get(index))
+        .map_or(ptr::null(), |individual| individual.organism().pointer)
+}
+
+/// Stores the genome with the best fitness in the current generation in
+/// `genome`, and its fitness in `fitness`, unless either is null.
+///
+/// The genome still belongs to the ecosystem, and is only valid until the
+/// next generation is bred.
+///
+/// # Safety
+///
+/// `ecosystem` must be a valid handle, and `genome` and `fitness` must each
+/// be null or valid.
+#[no_mangle]
+pub unsafe extern "C" fn ecosystem_best(
+    ecosystem: *const EcosystemHandle,
+    genome: *mut *const c_void,
+    fitness: *mut f64,
+) -> EcosystemStatus {
+    guard(|| {
+        let handle = match ecosystem.as_ref() {
+            Some(handle) => handle,
+            None => return fail(EcosystemStatus::InvalidArgument, "the ecosystem was null"),
+        };
+        let best = match handle.ecosystem.fittest_individual() {
+            Some(best) => best,
+            None => return fail(EcosystemStatus::InvalidArgument, "the ecosystem was empty"),
+        };
+        if let Some(genome) = genome.as_mut() {
+            *genome = best.organism().pointer;
+        }
+        if let Some(fitness) = fitness.as_mut() {
+            *fitness = Individual::fitness(best);
+        }
+        EcosystemStatus::Ok
+    })
+}
+
+/// Returns a description of the most recent failure on the calling thread,
+/// or null if nothing has failed.
+///
+/// The description is valid until the next failure on the same thread.
+#[no_mangle]
+pub extern "C" fn ecosystem_last_error() -> *const c_char {
+    LAST_ERROR.with(|last| {
+        last.borrow()
+            .as_ref()
+            .map_or(ptr::null(), |description| description.as_ptr())
+    })
+}
@@ -0,0 +1,355 @@
+//! Python bindings for the ecosystem crate.
+//!
+//! The `ecosystem` Python module exposes an `Ecosystem` class whose genomes
+//! can be any Python objects. Its fitness, breeding and mutation functions
+//! are Python callables, while selection, elitism and the rest of the
+//! evolution loop run in Rust:
+//!
+//! ```python
+//! import ecosystem
+//!
+//! def fitness(genome):
+//!     return -abs(genome - 3.14)
+//!
+//! def breed(a, b, rng):
+//!     return (a + b) / 2
+//!
+//! def mutate(genome, rate, rng):
+//!     return genome + rng.uniform(-rate, rate)
+//!
+//! population = ecosystem.Ecosystem(
+//!     [float(i) for i in range(20)],
+//!     fitness,
+//!     breed,
+//!     mutate,
+//!     mutation_rate=0.5,
+//!     tournament_size=3,
+//!     seed=7,
+//! )
+//! population.run(generations=50)
+//! print(population.fittest)
+//! ```
+//!
+//! Each call to `breed` and `mutate` is handed a `random.Random` seeded from
+//! the ecosystem's own random number generator, so a seeded run is
+//! reproducible from Python as well.
+use ecosystem::{
+    selection::Tournament,
+    termination::{MaxGenerations, Search, StopReason, TargetFitness, Termination},
+    BuildError, Ecosystem, EcosystemError, Individual, Objective, Organism, DEFAULT_MUTATION_RATE,
+};
+use pyo3::{
+    exceptions::{PyRuntimeError, PyValueError},
+    prelude::*,
+    types::PyList,
+};
+use rand::Rng;
+use std::sync::{Arc, Mutex};
+
+/// The Python callables shared by every organism in an ecosystem.
+struct Callbacks {
+    fitness: PyObject,
+    breed: PyObject,
+    mutate: PyObject,
+    /// The `random.Random` class, used to hand each callable a generator.
+    random: PyObject,
+    /// The first exception raised by a callable since it was last taken.
+    error: Mutex<Option<PyErr>>,
+}
+
+impl Callbacks {
+    /// Creates a `random.Random` seeded from the given generator.
+    fn random<'py>(&self, py: Python<'py>, rng: &mut impl Rng) -> PyResult<Bound<'py, PyAny>> {
+        self.random.bind(py).call1((rng.gen::<u64>(),))
+    }
+
+    /// Records an exception raised by a callable, keeping only the first.
+    ///
+    /// The evolution loop can't be interrupted partway through a generation,
+    /// so the exception is raised once the generation is over.
+    fn fail(&self, error: PyErr) {
+        let mut slot = self.error.lock().unwrap();
+        if slot.is_none() {
+            *slot = Some(error);
+        }
+    }
+
+    fn failed(&self) -> bool {
+        self.error.lock().unwrap().is_some()
+    }
+
+    /// Takes the recorded exception, if any callable has raised one.
+    fn take_error(&self) -> PyResult<()> {
+        match self.error.lock().unwrap().take() {
+            Some(error) => Err(error),
+            None => Ok(()),
+        }
+    }
+}
+
+/// An organism whose genome is a Python object.
+struct PyOrganism {
+    genome: PyObject,
+    callbacks: Arc<Callbacks>,
+}
+
+impl PyOrganism {
+    /// Wraps a genome produced by a callable, or this organism's own genome
+    /// if the callable raised an exception.
+    fn offspring(&self, py: Python, genome: PyResult<Bound<PyAny>>) -> Self {
+        let genome = genome.unwrap_or_else(|error| {
+            self.callbacks.fail(error);
+            self.genome.bind(py).clone()
+        });
+        PyOrganism {
+            genome: genome.unbind(),
+            callbacks: Arc::clone(&self.callbacks),
+        }
+    }
+}
+
+impl Organism for PyOrganism {
+    type Fitness = f64;
+
+    /// Calls the fitness function, scoring the organism as NaN (which is
+    /// never better than anything else) if it raises an exception or doesn't
+    /// return a number.
+    fn fitness(&self) -> f64 {
+        Python::with_gil(|py| {
+            self.callbacks
+                .fitness
+                .bind(py)
+                .call1((self.genome.bind(py),))
+                .and_then(|fitness| fitness.extract())
+                .unwrap_or_else(|error| {
+                    self.callbacks.fail(error);
+                    f64::NAN
+                })
+        })
+    }
+
+    fn breed(&self, other: &Self, rng: &mut impl Rng) -> Self {
+        Python::with_gil(|py| {
+            let child = self.callbacks.random(py, rng).and_then(|random| {
+                self.callbacks.breed.bind(py).call1((
+                    self.genome.bind(py),
+                    other.genome.bind(py),
+                    random,
+                ))
+            });
+            self.offspring(py, child)
+        })
+    }
+
+    /// Calls the mutation function, which may either return the mutated
+    /// genome or modify it in place and return `None`.
+    fn mutate(&mut self, rate: f64, rng: &mut impl Rng) {
+        Python::with_gil(|py| {
+            let mutated = self.callbacks.random(py, rng).and_then(|random| {
+                self.callbacks
+                    .mutate
+                    .bind(py)
+                    .call1((self.genome.bind(py), rate, random))
+            });
+            match mutated {
+                Ok(genome) if genome.is_none() => {}
+                Ok(genome) => self.genome = genome.unbind(),
+                Err(error) => self.callbacks.fail(error),
+            }
+        })
+    }
+}
+
+/// Stops a run after a number of generations or on reaching a target
+/// fitness, or as soon as a callable raises an exception.
+struct Until {
+    generations: Option<MaxGenerations>,
+    target: Option<TargetFitness<f64>>,
+    callbacks: Arc<Callbacks>,
+}
+
+impl<S: Search<Fitness = f64>> Termination<S> for Until {
+    fn check(&mut self, search: &S) -> Option<StopReason> {
+        if self.callbacks.failed() {
+            return Some(StopReason::Custom(
+                "a callback raised an exception".to_string(),
+            ));
+        }
+        let generations = self
+            .generations
+            .as_mut()
+            .and_then(|limit| limit.check(search));
+        generations.or_else(|| self.target.as_mut().and_then(|target| target.check(search)))
+    }
+}
+
+fn build_error(error: BuildError) -> PyErr {
+    PyValueError::new_err(error.to_string())
+}
+
+fn ecosystem_error(error: EcosystemError) -> PyErr {
+    PyRuntimeError::new_err(error.to_string())
+}
+
+/// A population of genomes that evolves under the given fitness, breeding
+/// and mutation functions.
+///
+/// `fitness(genome)` returns a number, `breed(a, b, rng)` returns a child
+/// genome, and `mutate(genome, rate, rng)` returns the mutated genome (or
+/// `None`, having mutated it in place). Parents are chosen by tournaments
+/// of `tournament_size` genomes if given, and in proportion to their
+/// fitness otherwise.
+#[pyclass(name = "Ecosystem", module = "ecosystem")]
+struct PyEcosystem {
+    ecosystem: Ecosystem<PyOrganism>,
+    callbacks: Arc<Callbacks>,
+}
+
+#[pymethods]
+impl PyEcosystem {
+    #[new]
+    #[pyo3(signature = (
+        genomes,
+        fitness,
+        breed,
+        mutate,
+        *,
+        mutation_rate = DEFAULT_MUTATION_RATE,
+        elitism = 0,
+        tournament_size = None,
+        minimize = false,
+        seed = None,
+    ))]
+    #[allow(clippy::too_many_arguments)]
+    fn new(
+        py: Python,
+        genomes: Vec<PyObject>,
+        fitness: PyObject,
+        breed: PyObject,
+        mutate: PyObject,
+        mutation_rate: f64,
+        elitism: usize,
+        tournament_size: Option<usize>,
+        minimize: bool,
+        seed: Option<u64>,
+    ) -> PyResult<Self> {
+        let callbacks = Arc::new(Callbacks {
+            fitness,
+            breed,
+            mutate,
+            random: py.import("random")?.getattr("Random")?.unbind(),
+            error: Mutex::new(None),
+        });
+        let organisms = genomes.into_iter().map(|genome| PyOrganism {
+            genome,
+            callbacks: Arc::clone(&callbacks),
+        });
+        let mut builder = Ecosystem::builder()
+            .organisms(organisms)
+            .mutation_rate(mutation_rate)
+            .elitism(elitism);
+        if let Some(size) = tournament_size {
+            builder = builder.selection(Tournament { size });
+        }
+        if minimize {
+            builder = builder.objective(Objective::Minimize);
+        }
+        if let Some(seed) = seed {
+            builder = builder.seed(seed);
+        }
+        // Building evaluates the initial population, which calls back into
+        // Python from the ecosystem's threads
+        let ecosystem = py.allow_threads(|| builder.build()).map_err(build_error)?;
+        callbacks.take_error()?;
+        Ok(PyEcosystem {
+            ecosystem,
+            callbacks,
+        })
+    }
+
+    /// Breeds the next generation, replacing the current one.
+    fn breed_next_generation(&mut self, py: Python) -> PyResult<()> {
+        let ecosystem = &mut self.ecosystem;
+        py.allow_threads(|| ecosystem.breed_next_generation())
+            .map_err(ecosystem_error)?;
+        self.callbacks.take_error()
+    }
+
+    /// Breeds generations until `generations` more have been bred or the
+    /// best fitness reaches `target`, whichever comes first, returning the
+    /// number bred.
+    #[pyo3(signature = (generations = None, target = None))]
+    fn run(&mut self, py: Python, generations: Option<u32>, target: Option<f64>) -> PyResult<u32> {
+        if generations.is_none() && target.is_none() {
+            return Err(PyValueError::new_err(
+                "a run needs a number of generations or a target fitness",
+            ));
+        }
+        let until = Until {
+            generations: generations
+                .map(|count| MaxGenerations(self.ecosystem.generation() + count)),
+            target: target.map(TargetFitness),
+            callbacks: Arc::clone(&self.callbacks),
+        };
+        let ecosystem = &mut self.ecosystem;
+        let summary = py
+            .allow_threads(|| ecosystem.run(until))
+            .map_err(ecosystem_error)?;
+        self.callbacks.take_error()?;
+        Ok(summary.generations)
+    }
+
+    /// The number of generations bred so far.
+    #[getter]
+    fn generation(&self) -> u32 {
+        self.ecosystem.generation()
+    }
+
+    /// The genomes of the current generation.
+    #[getter]
+    fn genomes<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyList>> {
+        let genomes = self.ecosystem.individuals().iter();
+        PyList::new(
+            py,
+            genomes.map(|individual| individual.organism().genome.bind(py)),
+        )
+    }
+
+    /// The genome with the best fitness in the current generation.
+    #[getter]
+    fn fittest(&self, py: Python) -> PyResult<Option<PyObject>> {
+        let fittest = self.fittest_individual(py)?;
+        Ok(fittest.map(|individual| individual.organism().genome.clone_ref(py)))
+    }
+
+    /// The fitness of the fittest genome.
+    #[getter]
+    fn best_fitness(&self, py: Python) -> PyResult<Option<f64>> {
+        Ok(self.fittest_individual(py)?.map(Individual::fitness))
+    }
+
+    fn __len__(&self) -> usize {
+        self.ecosystem.individuals().len()
+    }
+}
+
+impl PyEcosystem {
+    /// Finds the fittest individual, evaluating any organisms that haven't
+    /// been yet.
+    ///
+    /// The GIL is released meanwhile, as the ecosystem may evaluate them on
+    /// other threads, which need it to call the fitness function.
+    fn fittest_individual(&self, py: Python) -> PyResult<Option<&Individual<PyOrganism>>> {
+        let ecosystem = &self.ecosystem;
+        let fittest = py.allow_threads(|| ecosystem.fittest_individual());
+        self.callbacks.take_error()?;
+        Ok(fittest)
+    }
+}
+
+/// Genetic algorithms with the evolution loop in Rust.
+#[pymodule]
+#[pyo3(name = "ecosystem")]
+fn ecosystem_module(module: &Bound<PyModule>) -> PyResult<()> {
+    module.add_class::<PyEcosystem>()
+}
@@ -14,29 +14,30 @@ struct Monkey {
 impl Monkey {
     const TARGET_PHRASE: &'static str = "To be or not to be?";
 
-    fn new() -> Self {
-        let mut rng = rand::thread_rng();
+    fn new(rng: &mut impl Rng) -> Self {
         Self {
             phrase: (0..Self::TARGET_PHRASE.len())
-                .map(|_| *LETTERS.choose(&mut rng).unwrap())
+                .map(|_| *LETTERS.choose(rng).unwrap())
                 .collect(),
         }
     }
 }
 
 impl Organism for Monkey {
-    fn fitness(&self) -> f64 {
+    type Fitness = u32;
+
+    fn fitness(&self) -> u32 {
         let fitness: u32 = self
             .phrase
             .chars()
             .zip(Self::TARGET_PHRASE.chars())
             .map(|(guess, target)| if guess == target { 1 } else { 0 })
             .sum();
-        f64::from(fitness.pow(2))
+        fitness.pow(2)
     }
 
-    fn breed(&self, other: &Self) -> Self {
-        let partition = rand::thread_rng().gen_range(0, self.phrase.len());
+    fn breed(&self, other: &Self, rng: &mut impl Rng) -> Self {
+        let partition = rng.gen_range(0, self.phrase.len());
         Self {
             phrase: format!(
                 "{}{}",
@@ -46,14 +47,13 @@ impl Organism for Monkey {
         }
     }
 
-    fn mutate(&mut self, rate: f64) {
-        let mut rng = rand::thread_rng();
+    fn mutate(&mut self, rate: f64, rng: &mut impl Rng) {
         self.phrase = self
             .phrase
             .chars()
             .map(|letter| {
                 if rng.gen_bool(rate) {
-                    *LETTERS.choose(&mut rng).unwrap()
+                    *LETTERS.choose(rng).unwrap()
                 } else {
                     letter
                 }
@@ -62,15 +62,18 @@ impl Organism for Monkey {
     }
 }
 
-const POPULATION_COUNT: u32 = 500;
+const POPULATION_COUNT: usize = 500;
 const MUTATION_RATE: f64 = 0.01;
 
 fn main() {
-    let monkeys: Vec<Monkey> = (0..POPULATION_COUNT).map(|_| Monkey::new()).collect();
-    let mut ecosystem = Ecosystem::new(monkeys);
+    let mut ecosystem = Ecosystem::builder()
+        .generate(POPULATION_COUNT, |mut rng| Monkey::new(&mut rng))
+        .mutation_rate(MUTATION_RATE)
+        .build()
+        .unwrap();
 
-    while ecosystem.fittest().phrase != String::from(Monkey::TARGET_PHRASE) {
-        ecosystem.breed_next_generation(MUTATION_RATE);
-        println!("{}", ecosystem.fittest().phrase);
+    while ecosystem.fittest().unwrap().phrase != Monkey::TARGET_PHRASE {
+        ecosystem.breed_next_generation().unwrap();
+        println!("{}", ecosystem.fittest().unwrap().phrase);
     }
 }
@@ -6,19 +6,21 @@ struct PiApproximator {
 }
 
 impl Organism for PiApproximator {
+    type Fitness = f64;
+
     fn fitness(&self) -> f64 {
         let diff = (std::f64::consts::PI - self.value).abs();
         1.0 / diff
     }
 
-    fn breed(&self, other: &Self) -> Self {
+    fn breed(&self, other: &Self, _rng: &mut impl Rng) -> Self {
         Self {
             value: (self.value + other.value) / 2.0,
         }
     }
 
-    fn mutate(&mut self, rate: f64) {
-        let change = rand::thread_rng().gen_range(-rate, rate);
+    fn mutate(&mut self, rate: f64, rng: &mut impl Rng) {
+        let change = rng.gen_range(-rate, rate);
         self.value += change;
     }
 }
@@ -36,9 +38,13 @@ fn main() {
             value: rng.gen_range(-MAX_INITIAL_VALUE, MAX_INITIAL_VALUE),
         })
         .collect();
-    let mut ecosystem = Ecosystem::new(approximators);
+    let mut ecosystem = Ecosystem::builder()
+        .organisms(approximators)
+        .mutation_rate(MUTATION_RATE)
+        .build()
+        .unwrap();
     for _ in 0..GENERATIONS {
-        ecosystem.breed_next_generation(MUTATION_RATE);
-        println!("{}", ecosystem.fittest().value);
+        ecosystem.breed_next_generation().unwrap();
+        println!("{}", ecosystem.fittest().unwrap().value);
     }
 }
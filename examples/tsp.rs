@@ -0,0 +1,55 @@
+//! Evolves tours of a TSPLIB instance, then polishes the best with 2-opt.
+//!
+//! Run with `cargo run --release --example tsp [path/to/instance.tsp]`. The
+//! bundled instances, burma14 and ulysses16, have shortest tours of 3323 and
+//! 6859.
+use ecosystem::{
+    selection::Tournament,
+    tsp::{DistanceMatrix, Tour},
+    Ecosystem, Objective, Organism,
+};
+use std::{env, sync::Arc};
+
+const DEFAULT_INSTANCE: &str = "examples/data/burma14.tsp";
+const POPULATION_COUNT: usize = 200;
+const GENERATIONS: u32 = 300;
+const MUTATION_RATE: f64 = 0.3;
+
+fn main() {
+    let path = env::args()
+        .nth(1)
+        .unwrap_or_else(|| DEFAULT_INSTANCE.into());
+    let distances = Arc::new(DistanceMatrix::load(&path).unwrap());
+
+    let mut rng = rand::thread_rng();
+    let tours: Vec<Tour> = (0..POPULATION_COUNT)
+        .map(|_| Tour::random(&distances, &mut rng))
+        .collect();
+    let mut ecosystem = Ecosystem::builder()
+        .organisms(tours)
+        .objective(Objective::Minimize)
+        .selection(Tournament { size: 3 })
+        .mutation_rate(MUTATION_RATE)
+        .elitism(2)
+        .build()
+        .unwrap();
+    for generation in 0..GENERATIONS {
+        ecosystem.breed_next_generation().unwrap();
+        if generation % 50 == 0 {
+            let best = ecosystem.fittest().unwrap().fitness();
+            println!("generation {}: {}", generation, best);
+        }
+    }
+
+    let mut tour = ecosystem.fittest().unwrap().clone();
+    println!("evolved: {}", tour.length());
+    tour.two_opt();
+    println!("after 2-opt: {}", tour.length());
+    let order: Vec<_> = tour
+        .order()
+        .as_slice()
+        .iter()
+        .map(|city| city + 1)
+        .collect();
+    println!("{:?}", order);
+}
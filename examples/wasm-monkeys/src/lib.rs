@@ -0,0 +1,111 @@
+//! The monkeys example, compiled to WebAssembly so that it can evolve live
+//! in a web page. See `index.html` for the page that drives it.
+use ecosystem::{Ecosystem, Organism};
+use rand::{seq::SliceRandom, Rng};
+use wasm_bindgen::prelude::*;
+
+const LETTERS: &[char] = &[
+    ' ', '!', ',', '.', '?', 'A', 'B', 'C', 'D', 'E', 'F', 'G', 'H', 'I', 'J', 'K', 'L', 'M', 'N',
+    'O', 'P', 'Q', 'R', 'S', 'T', 'U', 'V', 'W', 'X', 'Y', 'Z', 'a', 'b', 'c', 'd', 'e', 'f', 'g',
+    'h', 'i', 'j', 'k', 'l', 'm', 'n', 'o', 'p', 'q', 'r', 's', 't', 'u', 'v', 'w', 'x', 'y', 'z',
+];
+
+const POPULATION_COUNT: usize = 500;
+const MUTATION_RATE: f64 = 0.01;
+
+struct Monkey {
+    phrase: String,
+}
+
+impl Monkey {
+    const TARGET_PHRASE: &'static str = "To be or not to be?";
+
+    fn new(rng: &mut impl Rng) -> Self {
+        Self {
+            phrase: (0..Self::TARGET_PHRASE.len())
+                .map(|_| *LETTERS.choose(rng).unwrap())
+                .collect(),
+        }
+    }
+}
+
+impl Organism for Monkey {
+    type Fitness = u32;
+
+    fn fitness(&self) -> u32 {
+        let fitness: u32 = self
+            .phrase
+            .chars()
+            .zip(Self::TARGET_PHRASE.chars())
+            .map(|(guess, target)| if guess == target { 1 } else { 0 })
+            .sum();
+        fitness.pow(2)
+    }
+
+    fn breed(&self, other: &Self, rng: &mut impl Rng) -> Self {
+        let partition = rng.gen_range(0, self.phrase.len());
+        Self {
+            phrase: format!(
+                "{}{}",
+                &self.phrase[..partition],
+                &other.phrase[partition..]
+            ),
+        }
+    }
+
+    fn mutate(&mut self, rate: f64, rng: &mut impl Rng) {
+        self.phrase = self
+            .phrase
+            .chars()
+            .map(|letter| {
+                if rng.gen_bool(rate) {
+                    *LETTERS.choose(rng).unwrap()
+                } else {
+                    letter
+                }
+            })
+            .collect();
+    }
+}
+
+/// A population of monkeys typing away in the browser.
+#[wasm_bindgen]
+pub struct Monkeys {
+    ecosystem: Ecosystem<Monkey>,
+}
+
+#[wasm_bindgen]
+impl Monkeys {
+    /// Creates a population of monkeys, all typing at random, from the given
+    /// seed. The browser has no operating system random number generator
+    /// for the ecosystem to seed itself from, so the page passes one in.
+    #[wasm_bindgen(constructor)]
+    pub fn new(seed: u64) -> Result<Monkeys, JsError> {
+        let ecosystem = Ecosystem::builder()
+            .seed(seed)
+            .generate(POPULATION_COUNT, |mut rng| Monkey::new(&mut rng))
+            .mutation_rate(MUTATION_RATE)
+            .build()?;
+        Ok(Self { ecosystem })
+    }
+
+    /// Breeds the next generation.
+    pub fn step(&mut self) -> Result<(), JsError> {
+        Ok(self.ecosystem.breed_next_generation()?)
+    }
+
+    /// Returns the current generation number.
+    pub fn generation(&self) -> u32 {
+        self.ecosystem.generation()
+    }
+
+    /// Returns the phrase typed by the fittest monkey.
+    pub fn best(&self) -> String {
+        self.ecosystem.fittest().unwrap().phrase.clone()
+    }
+
+    /// Returns whether a monkey has typed the target phrase.
+    pub fn solved(&self) -> bool {
+        self.best() == Monkey::TARGET_PHRASE
+    }
+}
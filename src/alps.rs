@@ -0,0 +1,294 @@
+use crate::parallel::*;
+use crate::{EcosystemError, Generator, Individual, Objective, Organism};
+use rand::{Rng, RngCore, SeedableRng};
+use rand_pcg::Pcg64;
+use std::cmp::Ordering;
+
+/// An Age-Layered Population Structure (ALPS), which keeps a search from
+/// converging prematurely by continually feeding fresh random organisms into
+/// it, and sheltering them from older, fitter organisms while they develop.
+///
+/// The population is divided into layers, each of which holds up to
+/// `layer_size` organisms. The age of an organism counts the generations
+/// since its line began: a child is as old as its older parent, and every
+/// organism ages by one in each generation. Each layer but the last has an
+/// age limit, which grows with the square of the layer's position:
+/// `age_gap`, `4 * age_gap`, `9 * age_gap` and so on. Organisms that exceed
+/// the limit of their layer move up to the next layer, where they take the
+/// place of less fit organisms (or are discarded if there are none).
+///
+/// Every `age_gap` generations, the bottom layer is replaced by newly
+/// generated random organisms, and the organisms it held move up to the next
+/// layer in the same way. In every generation, each layer that is not
+/// empty is replaced by its fittest `elitism` organisms and children bred
+/// from parents chosen by binary tournaments among that layer and the layer
+/// below it. Fresh organisms therefore only ever compete with those of a
+/// similar age, which gives them time to improve before meeting the best
+/// organisms found so far.
+///
+/// # Examples
+///
+/// ```rust
+/// use ecosystem::{Alps, Organism};
+/// use rand::Rng;
+/// # struct Guess(f64);
+/// # impl Organism for Guess {
+/// #     type Fitness = f64;
+/// #     fn fitness(&self) -> f64 { 1.0 / (42.0 - self.0).abs() }
+/// #     fn breed(&self, other: &Self, rng: &mut impl Rng) -> Self { Guess((self.0 + other.0) / 2.0) }
+/// #     fn mutate(&mut self, rate: f64, rng: &mut impl Rng) {
+/// #         self.0 += rng.gen_range(-rate, rate);
+/// #     }
+/// # }
+///
+/// // Four layers of twenty guesses, with fresh guesses every five generations
+/// let mut alps = Alps::new(4, 20, 5, |rng| Guess(rng.gen_range(0.0, 1000.0))).seed(0);
+/// for _ in 0..50 {
+///     alps.breed_next_generation()?;
+/// }
+///
+/// assert!((alps.fittest().unwrap().0 - 42.0).abs() < 1.0);
+/// // Organisms have moved up through the layers as they aged
+/// assert!(!alps.layer(3).is_empty());
+/// assert!(alps.layer(0).iter().all(|individual| individual.age() <= 5));
+/// # Ok::<(), ecosystem::EcosystemError>(())
+/// ```
+pub struct Alps<O: Organism> {
+    layers: Vec<Vec<Individual<O>>>,
+    layer_size: usize,
+    age_gap: u32,
+    generate: Generator<O>,
+    mutation_rate: f64,
+    objective: Objective,
+    elitism: usize,
+    rng: Pcg64,
+    generation: u32,
+    evaluations: u64,
+}
+
+impl<O: Organism + Send + Sync> Alps<O> {
+    /// Creates an age-layered population of `layer_count` layers, each
+    /// holding up to `layer_size` organisms, which are created by `generate`.
+    /// The layers are empty until the first generation is bred, when the
+    /// bottom layer is filled.
+    ///
+    /// Children are mutated at a rate of [`DEFAULT_MUTATION_RATE`], fitness
+    /// values are maximised, a single elite is kept in each layer, and the
+    /// random number generator is seeded from the operating system.
+    ///
+    /// [`DEFAULT_MUTATION_RATE`]: constant.DEFAULT_MUTATION_RATE.html
+    ///
+    /// # Panics
+    ///
+    /// Panics if `layer_count` or `age_gap` is zero.
+    pub fn new<G>(layer_count: usize, layer_size: usize, age_gap: u32, generate: G) -> Self
+    where
+        G: Fn(&mut dyn RngCore) -> O + Send + Sync + 'static,
+    {
+        assert!(layer_count > 0, "there must be at least one layer");
+        assert!(age_gap > 0, "the age gap must be non-zero");
+        Self {
+            layers: (0..layer_count).map(|_| Vec::new()).collect(),
+            layer_size,
+            age_gap,
+            generate: Box::new(generate),
+            mutation_rate: crate::DEFAULT_MUTATION_RATE,
+            objective: Objective::default(),
+            elitism: 1,
+            rng: Pcg64::from_entropy(),
+            generation: 0,
+            evaluations: 0,
+        }
+    }
+
+    /// Sets the rate at which children are mutated.
+    pub fn mutation_rate(mut self, rate: f64) -> Self {
+        self.mutation_rate = rate;
+        self
+    }
+
+    /// Sets the direction in which fitness values are optimised.
+    pub fn objective(mut self, objective: Objective) -> Self {
+        self.objective = objective;
+        self
+    }
+
+    /// Sets the number of the fittest organisms in each layer that are
+    /// carried over unchanged into the next generation.
+    pub fn elitism(mut self, count: usize) -> Self {
+        self.elitism = count;
+        self
+    }
+
+    /// Seeds the random number generator, making evolution reproducible.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.rng = Pcg64::seed_from_u64(seed);
+        self
+    }
+
+    /// Returns the number of layers.
+    pub fn layer_count(&self) -> usize {
+        self.layers.len()
+    }
+
+    /// Returns the individuals in the given layer, where the bottom layer is
+    /// layer zero.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no such layer.
+    pub fn layer(&self, layer: usize) -> &[Individual<O>] {
+        &self.layers[layer]
+    }
+
+    /// Returns the age limit of the given layer, or `None` for the top
+    /// layer, which has no limit.
+    pub fn age_limit(&self, layer: usize) -> Option<u32> {
+        if layer + 1 >= self.layers.len() {
+            return None;
+        }
+        let position = layer as u32 + 1;
+        Some(self.age_gap * position * position)
+    }
+
+    /// Returns the number of generations that have been bred.
+    pub fn generation(&self) -> u32 {
+        self.generation
+    }
+
+    /// Returns the number of organisms whose fitness has been evaluated.
+    pub fn evaluations(&self) -> u64 {
+        self.evaluations
+    }
+
+    /// Returns the fittest organism in any layer, or `None` if every layer
+    /// is empty.
+    pub fn fittest(&self) -> Option<&O> {
+        self.layers
+            .iter()
+            .flatten()
+            .max_by(|a, b| self.objective.compare(a.fitness(), b.fitness()))
+            .map(Individual::organism)
+    }
+
+    /// Breeds the next generation of every layer, then moves organisms that
+    /// have exceeded their layer's age limit up to the next layer. The bottom
+    /// layer is first replaced by random organisms if one is due.
+    ///
+    /// Children are bred and evaluated in parallel, with each given its own
+    /// random number generator seeded from the population's, so the result
+    /// is reproducible.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the layer size is zero, as there is then
+    /// nothing to breed.
+    pub fn breed_next_generation(&mut self) -> Result<(), EcosystemError> {
+        if self.layer_size == 0 {
+            return Err(EcosystemError::EmptyPopulation);
+        }
+
+        if self.generation.is_multiple_of(self.age_gap) {
+            let fresh: Vec<_> = (0..self.layer_size)
+                .map(|_| Individual::new((self.generate)(&mut self.rng)))
+                .collect();
+            fresh.par_iter().for_each(|individual| {
+                individual.evaluate();
+            });
+            self.evaluations += fresh.len() as u64;
+            let replaced = std::mem::replace(&mut self.layers[0], fresh);
+            if self.layers.len() > 1 {
+                self.offer(1, replaced);
+            }
+        }
+
+        let children: Vec<_> = (0..self.layers.len())
+            .map(|layer| self.breed_layer(layer))
+            .collect();
+        let objective = self.objective;
+        let elites = self.elitism.min(self.layer_size);
+        for (layer, children) in self.layers.iter_mut().zip(children) {
+            layer.sort_by(|a, b| objective.compare(b.fitness(), a.fitness()));
+            layer.truncate(elites);
+            layer.extend(children);
+            for individual in layer.iter_mut() {
+                individual.age += 1;
+            }
+        }
+        self.promote();
+
+        self.generation += 1;
+        Ok(())
+    }
+
+    /// Breeds the children of a layer, from parents in it and the layer
+    /// below, to make up its numbers alongside its elites. Nothing is bred
+    /// for empty layers.
+    fn breed_layer(&mut self, layer: usize) -> Vec<Individual<O>> {
+        let (layers, rng, objective) = (&self.layers, &mut self.rng, self.objective);
+        if layers[layer].is_empty() {
+            return Vec::new();
+        }
+
+        let below = if layer > 0 {
+            &layers[layer - 1][..]
+        } else {
+            &[]
+        };
+        let pool: Vec<_> = layers[layer].iter().chain(below).collect();
+        let select = |rng: &mut Pcg64| {
+            let a = pool[rng.gen_range(0, pool.len())];
+            let b = pool[rng.gen_range(0, pool.len())];
+            if objective.compare(a.fitness(), b.fitness()) == Ordering::Less {
+                b
+            } else {
+                a
+            }
+        };
+        let elites = self.elitism.min(self.layer_size).min(layers[layer].len());
+        let parents: Vec<_> = (elites..self.layer_size)
+            .map(|_| (select(rng), select(rng), rng.gen::<u64>()))
+            .collect();
+
+        let mutation_rate = self.mutation_rate;
+        let children: Vec<_> = parents
+            .into_par_iter()
+            .map(|(mother, father, seed)| {
+                let mut rng = Pcg64::seed_from_u64(seed);
+                let mut child = mother.organism().breed(father.organism(), &mut rng);
+                child.mutate(mutation_rate, &mut rng);
+
+                let mut individual = Individual::new(child);
+                individual.age = mother.age().max(father.age());
+                individual.evaluate();
+                individual
+            })
+            .collect();
+
+        self.evaluations += children.len() as u64;
+        children
+    }
+
+    /// Moves the organisms that have exceeded the age limit of their layer
+    /// up to the next layer.
+    fn promote(&mut self) {
+        for layer in 0..self.layers.len() - 1 {
+            let limit = self.age_limit(layer).unwrap();
+            let (aged, young) = std::mem::take(&mut self.layers[layer])
+                .into_iter()
+                .partition(|individual| individual.age() > limit);
+            self.layers[layer] = young;
+            self.offer(layer + 1, aged);
+        }
+    }
+
+    /// Adds the given individuals to a layer, keeping only the fittest if
+    /// the layer would then hold too many.
+    fn offer(&mut self, layer: usize, individuals: Vec<Individual<O>>) {
+        let objective = self.objective;
+        let layer = &mut self.layers[layer];
+        layer.extend(individuals);
+        layer.sort_by(|a, b| objective.compare(b.fitness(), a.fitness()));
+        layer.truncate(self.layer_size);
+    }
+}
@@ -0,0 +1,152 @@
+use crate::parallel::*;
+use crate::{Ecosystem, EcosystemError, Individual, Organism};
+
+/// A group of ecosystems (*islands*) that evolve independently, but
+/// periodically exchange their fittest organisms.
+///
+/// Isolating the islands lets each one explore a different region of the
+/// search space, while migration spreads good solutions between them. This
+/// keeps the overall population diverse, making it less likely to get stuck
+/// in a local optimum than a single ecosystem of the same total size.
+///
+/// The islands are arranged in a ring. Every `interval` generations, copies
+/// of the `migrants` fittest organisms on each island replace the least fit
+/// organisms on the next. Each island keeps its own configuration, but they
+/// should share an objective.
+///
+/// # Examples
+///
+/// ```rust
+/// use ecosystem::{Archipelago, Ecosystem, Organism};
+/// # use rand::Rng;
+/// # #[derive(Clone)]
+/// # struct Guess(f64);
+/// # impl Organism for Guess {
+/// #     type Fitness = f64;
+/// #     fn fitness(&self) -> f64 { 1.0 / (42.0 - self.0).abs() }
+/// #     fn breed(&self, other: &Self, rng: &mut impl Rng) -> Self { Guess((self.0 + other.0) / 2.0) }
+/// #     fn mutate(&mut self, rate: f64, rng: &mut impl Rng) {
+/// #         self.0 += rng.gen_range(-rate, rate);
+/// #     }
+/// # }
+///
+/// // Only the first island starts anywhere near the answer
+/// let islands = (0..4)
+///     .map(|island| {
+///         let offset = f64::from(island) * 1000.0;
+///         Ecosystem::builder()
+///             .organisms((0..20).map(|i| Guess(offset + f64::from(i) * 5.0 + 0.5)))
+///             .build()
+///             .unwrap()
+///     })
+///     .collect();
+/// let mut archipelago = Archipelago::new(islands, 5, 2);
+/// for _ in 0..20 {
+///     archipelago.breed_next_generation()?;
+/// }
+///
+/// // Migration has carried good solutions around the ring
+/// for island in archipelago.islands() {
+///     assert!((island.fittest().unwrap().0 - 42.0).abs() < 50.0);
+/// }
+/// # Ok::<(), ecosystem::EcosystemError>(())
+/// ```
+pub struct Archipelago<O: Organism> {
+    islands: Vec<Ecosystem<O>>,
+    interval: u32,
+    migrants: usize,
+    generation: u32,
+}
+
+impl<O: Organism + Clone + Send + Sync> Archipelago<O> {
+    /// Creates an archipelago from the given islands, which exchange
+    /// `migrants` organisms every `interval` generations. An interval of zero
+    /// disables migration.
+    pub fn new(islands: Vec<Ecosystem<O>>, interval: u32, migrants: usize) -> Self {
+        Self {
+            islands,
+            interval,
+            migrants,
+            generation: 0,
+        }
+    }
+
+    /// Returns the islands.
+    pub fn islands(&self) -> &[Ecosystem<O>] {
+        &self.islands
+    }
+
+    /// Returns the islands mutably, such as for reconfiguring them.
+    pub fn islands_mut(&mut self) -> &mut [Ecosystem<O>] {
+        &mut self.islands
+    }
+
+    /// Consumes the archipelago, returning its islands.
+    pub fn into_islands(self) -> Vec<Ecosystem<O>> {
+        self.islands
+    }
+
+    /// Returns the number of generations bred by the archipelago.
+    pub fn generation(&self) -> u32 {
+        self.generation
+    }
+
+    /// Returns the fittest organism across all of the islands, or `None` if
+    /// they contain no organisms.
+    ///
+    /// Fitness values are compared according to the first island's
+    /// objective.
+    pub fn fittest(&self) -> Option<&O> {
+        let objective = self.islands.first()?.objective();
+        self.islands
+            .iter()
+            .filter_map(Ecosystem::fittest_individual)
+            .max_by(|a, b| objective.compare(a.fitness(), b.fitness()))
+            .map(Individual::organism)
+    }
+
+    /// Breeds the next generation on every island in parallel, followed by a
+    /// round of migration when one is due.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if any island fails to breed, in which case no
+    /// migration takes place (though the other islands may have bred).
+    pub fn breed_next_generation(&mut self) -> Result<(), EcosystemError> {
+        self.islands
+            .par_iter_mut()
+            .try_for_each(Ecosystem::breed_next_generation)?;
+        self.generation += 1;
+
+        if self.interval > 0 && self.generation.is_multiple_of(self.interval) {
+            self.migrate();
+        }
+        Ok(())
+    }
+
+    /// Sends copies of the fittest organisms on each island to the next
+    /// island in the ring.
+    fn migrate(&mut self) {
+        if self.islands.len() < 2 || self.migrants == 0 {
+            return;
+        }
+
+        let emigrants: Vec<Vec<Individual<O>>> = self
+            .islands
+            .iter()
+            .map(|island| {
+                island
+                    .ranked_individuals()
+                    .into_iter()
+                    .take(self.migrants)
+                    .cloned()
+                    .collect()
+            })
+            .collect();
+
+        let count = self.islands.len();
+        for (source, emigrants) in emigrants.into_iter().enumerate() {
+            self.islands[(source + 1) % count].replace_worst(emigrants);
+        }
+    }
+}
@@ -0,0 +1,283 @@
+use crate::{diversity::Distance, genome::BitString, Organism};
+use rand::{seq::index, Rng};
+use std::{fmt, sync::Arc};
+
+/// A problem over bit strings of a fixed length, which gives the fitness of
+/// each. Fitness values are non-negative, and higher values are better.
+pub trait BinaryProblem {
+    /// Evaluates the bit string.
+    fn evaluate(&self, bits: &BitString) -> f64;
+}
+
+/// The 'OneMax' problem, whose fitness is the number of ones. It has no
+/// local optima, so it measures how quickly an algorithm climbs.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OneMax;
+
+impl BinaryProblem for OneMax {
+    fn evaluate(&self, bits: &BitString) -> f64 {
+        bits.count_ones() as f64
+    }
+}
+
+/// Concatenated deceptive traps, which divide the bits into consecutive
+/// blocks of `order` bits (the last of which may be shorter).
+///
+/// A block of all ones scores its length, while any other block scores one
+/// less than its number of zeros. Within each block, the slope of the
+/// fitness leads away from the optimum towards all zeros, so an algorithm
+/// can only find the optimum by combining whole blocks of ones, which
+/// crossover operators that respect the blocks do far better than those that
+/// disrupt them. The best fitness is the length of the bit string.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DeceptiveTrap {
+    /// The number of bits in each block, which is usually between 3 and 5.
+    pub order: usize,
+}
+
+impl BinaryProblem for DeceptiveTrap {
+    fn evaluate(&self, bits: &BitString) -> f64 {
+        let order = self.order.max(1);
+        (0..bits.len())
+            .step_by(order)
+            .map(|start| {
+                let end = (start + order).min(bits.len());
+                let ones = (start..end).filter(|&index| bits.get(index)).count();
+                let size = end - start;
+                if ones == size {
+                    size
+                } else {
+                    size - 1 - ones
+                }
+            })
+            .sum::<usize>() as f64
+    }
+}
+
+/// An NK landscape, whose ruggedness is tuned by the number of bits, `k`,
+/// that each bit interacts with.
+///
+/// Each of the `n` bits contributes a value that depends on its own state
+/// and the states of `k` other bits, chosen at random, looked up in a random
+/// table of values between zero and one. The fitness is the mean of the
+/// contributions. With `k` at zero, each bit can be optimised on its own and
+/// the landscape has a single peak; as `k` grows towards `n - 1`, the
+/// contributions conflict more and more, and the landscape becomes
+/// increasingly rugged, with many local optima.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NkLandscape {
+    neighbors: Vec<Vec<usize>>,
+    contributions: Vec<Vec<f64>>,
+}
+
+impl NkLandscape {
+    /// Generates a random landscape of `n` bits, each of which interacts
+    /// with `k` others.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `k` is not less than `n`, or is too large for the tables of
+    /// contributions to be held in memory.
+    pub fn random(n: usize, k: usize, rng: &mut impl Rng) -> Self {
+        assert!(k < n.max(1), "k must be less than n");
+        assert!(k < 32, "k is too large");
+        let neighbors = (0..n)
+            .map(|bit| {
+                index::sample(rng, n - 1, k)
+                    .into_iter()
+                    .map(|other| if other < bit { other } else { other + 1 })
+                    .collect()
+            })
+            .collect();
+        let contributions = (0..n)
+            .map(|_| (0..1 << (k + 1)).map(|_| rng.gen()).collect())
+            .collect();
+        Self {
+            neighbors,
+            contributions,
+        }
+    }
+
+    /// Returns the number of bits, `n`.
+    pub fn n(&self) -> usize {
+        self.neighbors.len()
+    }
+
+    /// Returns the number of bits that each bit interacts with, `k`.
+    pub fn k(&self) -> usize {
+        self.neighbors.first().map_or(0, Vec::len)
+    }
+
+    /// Returns the bits that each bit interacts with.
+    pub fn neighbors(&self, bit: usize) -> &[usize] {
+        &self.neighbors[bit]
+    }
+
+    /// Finds the best fitness on the landscape by evaluating every bit
+    /// string, which is only practical for small values of `n` (up to about
+    /// twenty).
+    pub fn global_optimum(&self) -> f64 {
+        let n = self.n();
+        assert!(n < 64, "n is too large to search exhaustively");
+        (0..1u64 << n)
+            .map(|value| {
+                let bits = (0..n).map(|bit| value >> bit & 1 == 1).collect();
+                self.evaluate(&bits)
+            })
+            .fold(0.0, f64::max)
+    }
+}
+
+impl BinaryProblem for NkLandscape {
+    /// # Panics
+    ///
+    /// Panics if the bit string's length is not `n`.
+    fn evaluate(&self, bits: &BitString) -> f64 {
+        assert_eq!(bits.len(), self.n(), "the bit string must have n bits");
+        if bits.is_empty() {
+            return 0.0;
+        }
+        let total: f64 = self
+            .neighbors
+            .iter()
+            .zip(&self.contributions)
+            .enumerate()
+            .map(|(bit, (neighbors, contributions))| {
+                let index = neighbors
+                    .iter()
+                    .fold(bits.get(bit) as usize, |index, &other| {
+                        index << 1 | bits.get(other) as usize
+                    });
+                contributions[index]
+            })
+            .sum();
+        total / self.n() as f64
+    }
+}
+
+/// The crossover operator that a [`BinarySolution`] is bred by.
+///
+/// [`BinarySolution`]: struct.BinarySolution.html
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Crossover {
+    /// One-point crossover.
+    OnePoint,
+    /// Two-point crossover, which is the default.
+    TwoPoint,
+    /// Uniform crossover.
+    Uniform,
+}
+
+/// A bit string evaluated on a [`BinaryProblem`], which is bred by
+/// two-point crossover unless set otherwise, and mutated by flipping each
+/// bit with the ecosystem's mutation rate.
+///
+/// [`BinaryProblem`]: trait.BinaryProblem.html
+pub struct BinarySolution<P> {
+    pub(super) bits: BitString,
+    problem: Arc<P>,
+    crossover: Crossover,
+}
+
+impl<P: BinaryProblem> BinarySolution<P> {
+    /// Creates a solution of the given bits to the given problem.
+    pub fn new(bits: BitString, problem: &Arc<P>) -> Self {
+        Self {
+            bits,
+            problem: Arc::clone(problem),
+            crossover: Crossover::TwoPoint,
+        }
+    }
+
+    /// Creates a solution of `len` random bits to the given problem.
+    pub fn random(len: usize, problem: &Arc<P>, rng: &mut impl Rng) -> Self {
+        Self::new(BitString::random(len, rng), problem)
+    }
+
+    /// Sets the crossover operator, which is inherited by the solution's
+    /// children.
+    pub fn crossover(mut self, crossover: Crossover) -> Self {
+        self.crossover = crossover;
+        self
+    }
+
+    /// Returns the bits.
+    pub fn bits(&self) -> &BitString {
+        &self.bits
+    }
+
+    /// Returns the problem.
+    pub fn problem(&self) -> &Arc<P> {
+        &self.problem
+    }
+
+    fn with_bits(&self, bits: BitString) -> Self {
+        Self {
+            bits,
+            problem: Arc::clone(&self.problem),
+            crossover: self.crossover,
+        }
+    }
+}
+
+impl<P: BinaryProblem> Organism for BinarySolution<P> {
+    type Fitness = f64;
+
+    fn fitness(&self) -> f64 {
+        self.problem.evaluate(&self.bits)
+    }
+
+    fn breed(&self, other: &Self, rng: &mut impl Rng) -> Self {
+        self.breed_pair(other, rng).0
+    }
+
+    fn breed_pair(&self, other: &Self, rng: &mut impl Rng) -> (Self, Self) {
+        let (first, second) = match self.crossover {
+            Crossover::OnePoint => self.bits.one_point_crossover(&other.bits, rng),
+            Crossover::TwoPoint => self.bits.two_point_crossover(&other.bits, rng),
+            Crossover::Uniform => self.bits.uniform_crossover(&other.bits, rng),
+        };
+        (self.with_bits(first), other.with_bits(second))
+    }
+
+    fn mutate(&mut self, rate: f64, rng: &mut impl Rng) {
+        self.bits.flip_bits(rate, rng);
+    }
+}
+
+impl<P> Distance for BinarySolution<P> {
+    fn distance(&self, other: &Self) -> f64 {
+        self.bits.hamming_distance(&other.bits) as f64
+    }
+}
+
+impl<P> Clone for BinarySolution<P> {
+    fn clone(&self) -> Self {
+        Self {
+            bits: self.bits.clone(),
+            problem: Arc::clone(&self.problem),
+            crossover: self.crossover,
+        }
+    }
+}
+
+impl<P> fmt::Debug for BinarySolution<P> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("BinarySolution")
+            .field("bits", &self.bits)
+            .field("crossover", &self.crossover)
+            .finish()
+    }
+}
+
+/// Solutions are equal if they have the same bits and crossover operator,
+/// whatever their problems.
+impl<P> PartialEq for BinarySolution<P> {
+    fn eq(&self, other: &Self) -> bool {
+        self.bits == other.bits && self.crossover == other.crossover
+    }
+}
@@ -0,0 +1,159 @@
+use crate::{diversity::Distance, genome::RealVector, Organism};
+use rand::Rng;
+use std::f64::consts::{E, PI};
+
+/// The distribution index of the crossover and mutation operators that
+/// solutions evolve by, which keeps children fairly close to their parents.
+const DISTRIBUTION_INDEX: f64 = 20.0;
+
+/// A continuous test function, to be minimised over its domain. Every
+/// function has a minimum of zero, at the point given by [`optimum`].
+///
+/// [`optimum`]: #method.optimum
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Function {
+    /// The sum of the squares of the coordinates: a smooth, unimodal bowl.
+    Sphere,
+    /// A sphere overlaid with a cosine wave, giving a regular grid of local
+    /// minima around the global one.
+    Rastrigin,
+    /// A long, curved, narrow valley whose floor is easy to find but
+    /// slow to follow to the minimum.
+    Rosenbrock,
+    /// A nearly flat outer region, riddled with shallow local minima, around
+    /// a deep hole at the origin.
+    Ackley,
+    /// A deceptive function whose best local minima lie far apart, near the
+    /// edges of the domain.
+    Schwefel,
+}
+
+impl Function {
+    /// Evaluates the function at the given point.
+    pub fn evaluate(self, point: &[f64]) -> f64 {
+        let n = point.len() as f64;
+        match self {
+            Function::Sphere => point.iter().map(|x| x * x).sum(),
+            Function::Rastrigin => {
+                10.0 * n
+                    + point
+                        .iter()
+                        .map(|x| x * x - 10.0 * (2.0 * PI * x).cos())
+                        .sum::<f64>()
+            }
+            Function::Rosenbrock => point
+                .windows(2)
+                .map(|pair| 100.0 * (pair[1] - pair[0] * pair[0]).powi(2) + (1.0 - pair[0]).powi(2))
+                .sum(),
+            Function::Ackley => {
+                if point.is_empty() {
+                    return 0.0;
+                }
+                let squares = point.iter().map(|x| x * x).sum::<f64>() / n;
+                let cosines = point.iter().map(|x| (2.0 * PI * x).cos()).sum::<f64>() / n;
+                -20.0 * (-0.2 * squares.sqrt()).exp() - cosines.exp() + 20.0 + E
+            }
+            Function::Schwefel => {
+                418.982_887_272_433_9 * n
+                    - point.iter().map(|x| x * x.abs().sqrt().sin()).sum::<f64>()
+            }
+        }
+    }
+
+    /// Returns the usual (min, max) bounds of every coordinate.
+    pub fn bounds(self) -> (f64, f64) {
+        match self {
+            Function::Sphere | Function::Rastrigin => (-5.12, 5.12),
+            Function::Rosenbrock => (-5.0, 10.0),
+            Function::Ackley => (-32.768, 32.768),
+            Function::Schwefel => (-500.0, 500.0),
+        }
+    }
+
+    /// Returns the point, with the given number of dimensions, at which the
+    /// function is minimised.
+    pub fn optimum(self, dimensions: usize) -> Vec<f64> {
+        let coordinate = match self {
+            Function::Sphere | Function::Rastrigin | Function::Ackley => 0.0,
+            Function::Rosenbrock => 1.0,
+            Function::Schwefel => 420.968_746_359_982,
+        };
+        vec![coordinate; dimensions]
+    }
+}
+
+/// A point in the domain of a test function, which evolves by simulated
+/// binary crossover and polynomial mutation. Its fitness is the value of
+/// the function at the point, which is to be minimised.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Solution {
+    function: Function,
+    point: RealVector,
+}
+
+impl Solution {
+    /// Creates a solution at the given point, which is kept within the
+    /// function's bounds.
+    pub fn new(function: Function, point: Vec<f64>) -> Self {
+        let bounds = vec![function.bounds(); point.len()];
+        Self {
+            function,
+            point: RealVector::with_bounds(point, bounds),
+        }
+    }
+
+    /// Creates a solution at a point with the given number of dimensions,
+    /// drawn uniformly from the function's domain.
+    pub fn random(function: Function, dimensions: usize, rng: &mut impl Rng) -> Self {
+        Self {
+            function,
+            point: RealVector::random(&vec![function.bounds(); dimensions], rng),
+        }
+    }
+
+    /// Returns the function that the solution is evaluated on.
+    pub fn function(&self) -> Function {
+        self.function
+    }
+
+    /// Returns the coordinates of the point.
+    pub fn point(&self) -> &[f64] {
+        self.point.genes()
+    }
+}
+
+impl Organism for Solution {
+    type Fitness = f64;
+
+    fn fitness(&self) -> f64 {
+        self.function.evaluate(self.point.genes())
+    }
+
+    fn breed(&self, other: &Self, rng: &mut impl Rng) -> Self {
+        self.breed_pair(other, rng).0
+    }
+
+    fn breed_pair(&self, other: &Self, rng: &mut impl Rng) -> (Self, Self) {
+        let (first, second) = self
+            .point
+            .sbx_crossover(&other.point, DISTRIBUTION_INDEX, rng);
+        let child = |point| Self {
+            function: self.function,
+            point,
+        };
+        (child(first), child(second))
+    }
+
+    fn mutate(&mut self, rate: f64, rng: &mut impl Rng) {
+        self.point
+            .polynomial_mutation(rate, DISTRIBUTION_INDEX, rng);
+    }
+}
+
+impl Distance for Solution {
+    fn distance(&self, other: &Self) -> f64 {
+        self.point.distance(&other.point)
+    }
+}
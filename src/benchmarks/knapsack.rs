@@ -0,0 +1,228 @@
+use super::{BinaryProblem, BinarySolution};
+use crate::{genome::BitString, Constrained};
+use rand::Rng;
+use std::sync::Arc;
+
+/// The 0/1 knapsack problem: choosing items, each with a value and a
+/// weight, to carry the greatest total value without their total weight
+/// exceeding the capacity.
+///
+/// As a [`BinaryProblem`], the fitness of a bit string is the total value
+/// of the items whose bits are set, whatever their weight. The capacity is
+/// a constraint, which [`BinarySolution`]s of the problem report through
+/// [`Constrained`]: their violation is the weight by which they exceed the
+/// capacity, and they are repaired by removing items in order of their
+/// value per unit of weight, the least valuable first, until they fit.
+///
+/// [`BinaryProblem`]: trait.BinaryProblem.html
+/// [`BinarySolution`]: struct.BinarySolution.html
+/// [`Constrained`]: ../trait.Constrained.html
+///
+/// # Examples
+///
+/// Overweight solutions can either be penalised, so that they lose out in
+/// selection, or repaired as they are bred:
+///
+/// ```rust
+/// use ecosystem::{
+///     benchmarks::{BinarySolution, Knapsack},
+///     selection::{ConstrainedTournament, Tournament},
+///     Constrained, ConstraintHandling, Ecosystem, Organism,
+/// };
+/// use rand::SeedableRng;
+/// use rand_pcg::Pcg64;
+/// use std::sync::Arc;
+///
+/// let mut rng = Pcg64::seed_from_u64(0);
+/// let knapsack = Arc::new(Knapsack::random(50, &mut rng));
+/// let optimum = knapsack.optimum() as f64;
+///
+/// // Each unit of excess weight costs ten units of value
+/// let mut penalised = Ecosystem::builder()
+///     .organisms((0..100).map(|_| BinarySolution::random(50, &knapsack, &mut rng)))
+///     .mutation_rate(0.02)
+///     .selection(ConstrainedTournament {
+///         size: 3,
+///         handling: ConstraintHandling::Penalty(10.0),
+///     })
+///     .elitism(1)
+///     .seed(0)
+///     .build()
+///     .unwrap();
+///
+/// // Every child is repaired until it fits
+/// let mut repaired = Ecosystem::builder()
+///     .organisms((0..100).map(|_| BinarySolution::random(50, &knapsack, &mut rng)))
+///     .mutation_rate(0.02)
+///     .selection(Tournament { size: 3 })
+///     .repair()
+///     .elitism(1)
+///     .seed(0)
+///     .build()
+///     .unwrap();
+///
+/// for _ in 0..100 {
+///     penalised.breed_next_generation()?;
+///     repaired.breed_next_generation()?;
+/// }
+///
+/// let best_feasible = |ecosystem: &Ecosystem<BinarySolution<Knapsack>>| {
+///     ecosystem
+///         .organisms()
+///         .filter(|solution| solution.violations() == 0.0)
+///         .map(|solution| solution.fitness())
+///         .fold(0.0, f64::max)
+/// };
+/// assert!(best_feasible(&penalised) > 0.9 * optimum);
+/// assert!(best_feasible(&repaired) > 0.95 * optimum);
+/// assert!(repaired.organisms().all(|solution| solution.violations() == 0.0));
+/// # Ok::<(), ecosystem::EcosystemError>(())
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Knapsack {
+    values: Vec<u64>,
+    weights: Vec<u64>,
+    capacity: u64,
+}
+
+impl Knapsack {
+    /// Creates a problem of the items with the given values and weights,
+    /// and a knapsack of the given capacity.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is not one weight for every value.
+    pub fn new(values: Vec<u64>, weights: Vec<u64>, capacity: u64) -> Self {
+        assert_eq!(
+            values.len(),
+            weights.len(),
+            "there must be one weight for every value"
+        );
+        Self {
+            values,
+            weights,
+            capacity,
+        }
+    }
+
+    /// Generates a problem of `len` items, whose values and weights are
+    /// drawn uniformly from 1 to 100, with a capacity of half their total
+    /// weight.
+    pub fn random(len: usize, rng: &mut impl Rng) -> Self {
+        let values = (0..len).map(|_| rng.gen_range(1, 101)).collect();
+        let weights: Vec<u64> = (0..len).map(|_| rng.gen_range(1, 101)).collect();
+        let capacity = weights.iter().sum::<u64>() / 2;
+        Self::new(values, weights, capacity)
+    }
+
+    /// Returns the number of items.
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Returns whether there are no items.
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Returns the values of the items.
+    pub fn values(&self) -> &[u64] {
+        &self.values
+    }
+
+    /// Returns the weights of the items.
+    pub fn weights(&self) -> &[u64] {
+        &self.weights
+    }
+
+    /// Returns the capacity of the knapsack.
+    pub fn capacity(&self) -> u64 {
+        self.capacity
+    }
+
+    /// Returns the total value of the items whose bits are set.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is not one bit for every item.
+    pub fn value(&self, bits: &BitString) -> u64 {
+        self.total(&self.values, bits)
+    }
+
+    /// Returns the total weight of the items whose bits are set.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is not one bit for every item.
+    pub fn weight(&self, bits: &BitString) -> u64 {
+        self.total(&self.weights, bits)
+    }
+
+    /// Finds the greatest total value that fits in the knapsack by dynamic
+    /// programming, in time proportional to the number of items multiplied
+    /// by the capacity.
+    pub fn optimum(&self) -> u64 {
+        let capacity = self.capacity as usize;
+        let mut best = vec![0; capacity + 1];
+        for (&value, &weight) in self.values.iter().zip(&self.weights) {
+            let weight = weight as usize;
+            for space in (weight..=capacity).rev() {
+                best[space] = best[space].max(best[space - weight] + value);
+            }
+        }
+        best[capacity]
+    }
+
+    fn total(&self, amounts: &[u64], bits: &BitString) -> u64 {
+        assert_eq!(
+            bits.len(),
+            self.len(),
+            "there must be one bit for every item"
+        );
+        amounts
+            .iter()
+            .enumerate()
+            .filter(|&(item, _)| bits.get(item))
+            .map(|(_, amount)| amount)
+            .sum()
+    }
+}
+
+impl BinaryProblem for Knapsack {
+    fn evaluate(&self, bits: &BitString) -> f64 {
+        self.value(bits) as f64
+    }
+}
+
+impl Constrained for BinarySolution<Knapsack> {
+    fn violations(&self) -> f64 {
+        let knapsack = self.problem();
+        knapsack
+            .weight(self.bits())
+            .saturating_sub(knapsack.capacity()) as f64
+    }
+
+    fn repair(&mut self) {
+        let knapsack = Arc::clone(self.problem());
+        let mut weight = knapsack.weight(self.bits());
+        let mut chosen: Vec<_> = (0..knapsack.len())
+            .filter(|&item| self.bits.get(item))
+            .collect();
+        // Least valuable per unit of weight first, comparing each v1 / w1
+        // with v2 / w2 as v1 * w2 with v2 * w1
+        chosen.sort_by(|&a, &b| {
+            let ratio = |item: usize, other: usize| {
+                u128::from(knapsack.values[item]) * u128::from(knapsack.weights[other])
+            };
+            ratio(a, b).cmp(&ratio(b, a))
+        });
+        for item in chosen {
+            if weight <= knapsack.capacity {
+                break;
+            }
+            self.bits.set(item, false);
+            weight -= knapsack.weights[item];
+        }
+    }
+}
@@ -0,0 +1,92 @@
+//! Standard benchmark problems, as ready-to-run organisms, for comparing
+//! operators and configurations on problems whose answers are known.
+//!
+//! [`Function`] gathers the classic continuous test functions, each of which
+//! has a minimum of zero, and a [`Solution`] is a point in a function's
+//! domain that evolves by simulated binary crossover and polynomial
+//! mutation. Its fitness is the function's value at the point, so
+//! ecosystems of solutions should minimise:
+//!
+//! ```rust
+//! use ecosystem::{
+//!     benchmarks::{Function, Solution},
+//!     selection::Tournament,
+//!     Ecosystem, Objective, Organism,
+//! };
+//! use rand::SeedableRng;
+//! use rand_pcg::Pcg64;
+//!
+//! let mut rng = Pcg64::seed_from_u64(0);
+//! let mut ecosystem = Ecosystem::builder()
+//!     .organisms((0..100).map(|_| Solution::random(Function::Rastrigin, 5, &mut rng)))
+//!     .objective(Objective::Minimize)
+//!     .selection(Tournament { size: 3 })
+//!     .mutation_rate(0.2)
+//!     .elitism(1)
+//!     .seed(0)
+//!     .build()
+//!     .unwrap();
+//! for _ in 0..100 {
+//!     ecosystem.breed_next_generation()?;
+//! }
+//!
+//! assert!(ecosystem.fittest().unwrap().fitness() < 0.1);
+//! # Ok::<(), ecosystem::EcosystemError>(())
+//! ```
+//!
+//! The classic binary problems implement [`BinaryProblem`], and a
+//! [`BinarySolution`] is a bit string that evolves on one of them, whose
+//! fitness is maximised. Deceptive traps reward crossover operators that
+//! keep blocks of bits together, and the ruggedness of NK landscapes can be
+//! tuned to test how well diversity is maintained:
+//!
+//! ```rust
+//! use ecosystem::{
+//!     benchmarks::{BinaryProblem, BinarySolution, DeceptiveTrap, NkLandscape},
+//!     genome::BitString,
+//!     Ecosystem, Organism,
+//! };
+//! use rand::SeedableRng;
+//! use rand_pcg::Pcg64;
+//! use std::sync::Arc;
+//!
+//! // Two traps of four bits: all ones is best, but fewer ones look better
+//! let trap = DeceptiveTrap { order: 4 };
+//! assert_eq!(trap.evaluate(&"11111111".parse::<BitString>().unwrap()), 8.0);
+//! assert_eq!(trap.evaluate(&"00000000".parse::<BitString>().unwrap()), 6.0);
+//! assert_eq!(trap.evaluate(&"11100000".parse::<BitString>().unwrap()), 3.0);
+//!
+//! let mut rng = Pcg64::seed_from_u64(0);
+//! // Fifteen bits, each interacting with two others
+//! let landscape = Arc::new(NkLandscape::random(15, 2, &mut rng));
+//! let mut ecosystem = Ecosystem::builder()
+//!     .organisms((0..50).map(|_| BinarySolution::random(15, &landscape, &mut rng)))
+//!     .mutation_rate(0.05)
+//!     .elitism(1)
+//!     .seed(0)
+//!     .build()
+//!     .unwrap();
+//! for _ in 0..50 {
+//!     ecosystem.breed_next_generation()?;
+//! }
+//!
+//! let best = ecosystem.fittest().unwrap().fitness();
+//! assert!(best > 0.95 * landscape.global_optimum());
+//! # Ok::<(), ecosystem::EcosystemError>(())
+//! ```
+//!
+//! The [`Knapsack`] problem adds a capacity constraint to a binary problem,
+//! which can be handled either by penalties or by repair.
+//!
+//! [`Function`]: enum.Function.html
+//! [`Solution`]: struct.Solution.html
+//! [`BinaryProblem`]: trait.BinaryProblem.html
+//! [`BinarySolution`]: struct.BinarySolution.html
+//! [`Knapsack`]: struct.Knapsack.html
+mod binary;
+mod continuous;
+mod knapsack;
+
+pub use binary::{BinaryProblem, BinarySolution, Crossover, DeceptiveTrap, NkLandscape, OneMax};
+pub use continuous::{Function, Solution};
+pub use knapsack::Knapsack;
@@ -0,0 +1,1657 @@
+#[cfg(feature = "std")]
+use crate::genome::RealGenome;
+use crate::{
+    diversity::Distance,
+    evaluation::BatchEvaluator,
+    genealogy::Genealogy,
+    history::History,
+    is_default_selection,
+    local_search::{Learning, LocalSearch},
+    mutation::MutationControl,
+    observer::Observer,
+    parallel::Parallelism,
+    population::PopulationControl,
+    progress::ProgressReporter,
+    replacement::Replacement,
+    selection::{FitnessProportionate, Selection},
+    stagnation::StagnationMonitor,
+    surrogate::Surrogate,
+    ArchiveOps, Constrained, Crossover, DuplicateKey, Ecosystem, Immigration, Individual,
+    Objective, Organism, PopulationModel, Screening, StagnationResponse, VarianceScaling,
+};
+use alloc::{
+    boxed::Box,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::{error::Error, fmt, hash::Hash};
+use portable_atomic::AtomicU64;
+use rand::{RngCore, SeedableRng};
+use rand_pcg::Pcg64;
+#[cfg(feature = "parallel")]
+use std::sync::Arc;
+
+/// The mutation rate used when none is specified.
+pub const DEFAULT_MUTATION_RATE: f64 = 0.01;
+
+/// A builder for configuring an [`Ecosystem`].
+///
+/// Every setting other than the organisms themselves is optional; the
+/// defaults match those of [`Ecosystem::new`].
+///
+/// [`Ecosystem`]: struct.Ecosystem.html
+/// [`Ecosystem::new`]: struct.Ecosystem.html#method.new
+///
+/// # Examples
+///
+/// ```rust
+/// use ecosystem::{selection::Tournament, Ecosystem, Objective, Organism};
+/// use rand::Rng;
+/// # struct Guess(f64);
+/// # impl Organism for Guess {
+/// #     type Fitness = f64;
+/// #     fn fitness(&self) -> f64 { (42.0 - self.0).abs() }
+/// #     fn breed(&self, other: &Self, rng: &mut impl Rng) -> Self { Guess((self.0 + other.0) / 2.0) }
+/// #     fn mutate(&mut self, rate: f64, rng: &mut impl Rng) {}
+/// # }
+///
+/// let ecosystem = Ecosystem::builder()
+///     .generate(100, |rng| Guess(rng.gen_range(0.0, 100.0)))
+///     .mutation_rate(0.05)
+///     .selection(Tournament { size: 3 })
+///     .objective(Objective::Minimize)
+///     .elitism(2)
+///     .build()
+///     .unwrap();
+/// ```
+pub struct EcosystemBuilder<O: Organism> {
+    organisms: Vec<O>,
+    mutation_rate: f64,
+    rng: Option<Pcg64>,
+    selection: Box<dyn Selection<O>>,
+    custom_selection: bool,
+    replacement: Option<Box<dyn Replacement<O>>>,
+    objective: Objective,
+    elitism: usize,
+    generation_gap: f64,
+    archive_capacity: usize,
+    archive_ops: Option<ArchiveOps<O>>,
+    record_history: bool,
+    track_genealogy: bool,
+    observers: Vec<Box<dyn Observer<O>>>,
+    progress: Option<Box<dyn ProgressReporter<O::Fitness>>>,
+    model: PopulationModel,
+    repair: Option<fn(&mut O)>,
+    crowding: Option<fn(&O, &O) -> f64>,
+    crossover: Option<Crossover<O>>,
+    variance_scaling: Option<VarianceScaling<O>>,
+    duplicate_key: Option<DuplicateKey<O>>,
+    immigration: Option<Immigration<O>>,
+    stagnation: Option<StagnationMonitor<O>>,
+    mutation_control: Option<Box<dyn MutationControl<O>>>,
+    population_control: Option<Box<dyn PopulationControl<O>>>,
+    local_search: Option<Box<dyn LocalSearch<O>>>,
+    baldwinian: Option<fn(&O) -> O>,
+    surrogate: Option<Screening<O>>,
+    batch_evaluator: Option<Box<dyn BatchEvaluator<O>>>,
+    parallelism: Parallelism,
+    #[cfg(feature = "parallel")]
+    threads: Option<usize>,
+    error: Option<BuildError>,
+}
+
+impl<O: Organism + Send + Sync> EcosystemBuilder<O> {
+    pub(crate) fn new() -> Self {
+        Self {
+            organisms: Vec::new(),
+            mutation_rate: DEFAULT_MUTATION_RATE,
+            rng: None,
+            selection: Box::new(FitnessProportionate),
+            custom_selection: false,
+            replacement: None,
+            objective: Objective::default(),
+            elitism: 0,
+            generation_gap: 1.0,
+            archive_capacity: 0,
+            archive_ops: None,
+            record_history: false,
+            track_genealogy: false,
+            observers: Vec::new(),
+            progress: None,
+            model: PopulationModel::default(),
+            repair: None,
+            crowding: None,
+            crossover: None,
+            variance_scaling: None,
+            duplicate_key: None,
+            immigration: None,
+            stagnation: None,
+            mutation_control: None,
+            population_control: None,
+            local_search: None,
+            baldwinian: None,
+            surrogate: None,
+            batch_evaluator: None,
+            parallelism: Parallelism::default(),
+            #[cfg(feature = "parallel")]
+            threads: None,
+            error: None,
+        }
+    }
+
+    /// Adds the given organisms to the initial population.
+    pub fn organisms(mut self, organisms: impl IntoIterator<Item = O>) -> Self {
+        self.organisms.extend(organisms);
+        self
+    }
+
+    /// Adds `count` organisms, created by the given function, to the initial
+    /// population.
+    ///
+    /// The function is passed the ecosystem's random number generator, so
+    /// an initial population drawn from it is reproducible once [`seed`] or
+    /// [`rng`] has been called. Either must be called before this method to
+    /// take effect on the organisms it creates.
+    ///
+    /// [`seed`]: #method.seed
+    /// [`rng`]: #method.rng
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ecosystem::{Ecosystem, Organism};
+    /// use rand::Rng;
+    /// # struct Guess(f64);
+    /// # impl Organism for Guess {
+    /// #     type Fitness = f64;
+    /// #     fn fitness(&self) -> f64 { 1.0 / (42.0 - self.0).abs() }
+    /// #     fn breed(&self, other: &Self, rng: &mut impl Rng) -> Self { Guess((self.0 + other.0) / 2.0) }
+    /// #     fn mutate(&mut self, rate: f64, rng: &mut impl Rng) {}
+    /// # }
+    ///
+    /// let initial = |seed| {
+    ///     let ecosystem = Ecosystem::builder()
+    ///         .seed(seed)
+    ///         .generate(20, |rng| Guess(rng.gen_range(0.0, 100.0)))
+    ///         .build()
+    ///         .unwrap();
+    ///     ecosystem.organisms().map(|guess| guess.0).collect::<Vec<_>>()
+    /// };
+    ///
+    /// assert_eq!(initial(7), initial(7));
+    /// ```
+    pub fn generate(mut self, count: usize, mut create: impl FnMut(&mut dyn RngCore) -> O) -> Self {
+        #[cfg(feature = "std")]
+        let rng = self.rng.get_or_insert_with(Pcg64::from_entropy);
+        #[cfg(not(feature = "std"))]
+        let rng = match &mut self.rng {
+            Some(rng) => rng,
+            None => {
+                if count > 0 {
+                    self.error.get_or_insert(BuildError::MissingRng);
+                }
+                return self;
+            }
+        };
+        self.organisms.extend((0..count).map(|_| create(rng)));
+        self
+    }
+
+    /// Adds organisms created by the given function until the initial
+    /// population holds `size` organisms, if it doesn't already.
+    ///
+    /// Together with [`organisms`], this seeds a population of a fixed size
+    /// with known good solutions, such as the best of an earlier run, and
+    /// makes up the rest at random.
+    ///
+    /// As with [`generate`], the function is passed the ecosystem's random
+    /// number generator.
+    ///
+    /// [`organisms`]: #method.organisms
+    /// [`generate`]: #method.generate
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ecosystem::{Ecosystem, Organism};
+    /// use rand::Rng;
+    /// # struct Guess(f64);
+    /// # impl Organism for Guess {
+    /// #     type Fitness = f64;
+    /// #     fn fitness(&self) -> f64 { 1.0 / (42.0 - self.0).abs() }
+    /// #     fn breed(&self, other: &Self, rng: &mut impl Rng) -> Self { Guess((self.0 + other.0) / 2.0) }
+    /// #     fn mutate(&mut self, rate: f64, rng: &mut impl Rng) {}
+    /// # }
+    ///
+    /// let yesterdays_best = vec![Guess(41.9), Guess(42.2)];
+    /// let ecosystem = Ecosystem::builder()
+    ///     .organisms(yesterdays_best)
+    ///     .fill(100, |rng| Guess(rng.gen_range(0.0, 1000.0)))
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// assert_eq!(ecosystem.individuals().len(), 100);
+    /// assert_eq!(ecosystem.fittest().unwrap().0, 41.9);
+    /// ```
+    pub fn fill(self, size: usize, create: impl FnMut(&mut dyn RngCore) -> O) -> Self {
+        let count = size.saturating_sub(self.organisms.len());
+        self.generate(count, create)
+    }
+
+    /// Sets the rate passed to [`Organism::mutate`] when breeding, which must
+    /// lie within [0, 1].
+    ///
+    /// [`Organism::mutate`]: trait.Organism.html#tymethod.mutate
+    pub fn mutation_rate(mut self, rate: f64) -> Self {
+        self.mutation_rate = rate;
+        self
+    }
+
+    /// Sets a [controller] that adjusts the mutation rate before each
+    /// generation is bred, starting from the rate given to
+    /// [`mutation_rate`].
+    ///
+    /// [controller]: mutation/index.html
+    /// [`mutation_rate`]: #method.mutation_rate
+    pub fn mutation_control<T: MutationControl<O> + 'static>(mut self, control: T) -> Self {
+        self.mutation_control = Some(Box::new(control));
+        self
+    }
+
+    /// Sets a [controller] that changes the population size before each
+    /// generation is bred, such as to explore with a large population early
+    /// on and refine with a small one later.
+    ///
+    /// [controller]: population/index.html
+    pub fn population_control<T: PopulationControl<O> + 'static>(mut self, control: T) -> Self {
+        self.population_control = Some(Box::new(control));
+        self
+    }
+
+    /// Sets a [local search] that improves every child after it is mutated
+    /// (and repaired, if enabled), making the ecosystem a memetic algorithm.
+    ///
+    /// [local search]: local_search/index.html
+    pub fn local_search<T: LocalSearch<O> + 'static>(mut self, search: T) -> Self {
+        self.local_search = Some(Box::new(search));
+        self
+    }
+
+    /// Sets a [batch evaluator], which evaluates every organism awaiting
+    /// evaluation in a single call, in place of their [`fitness`] methods.
+    ///
+    /// [batch evaluator]: evaluation/index.html
+    /// [`fitness`]: trait.Organism.html#tymethod.fitness
+    pub fn batch_evaluator<T: BatchEvaluator<O> + 'static>(mut self, evaluator: T) -> Self {
+        self.batch_evaluator = Some(Box::new(evaluator));
+        self
+    }
+
+    /// Has the ecosystem evaluate and breed organisms on the given thread
+    /// pool, rather than on rayon's global pool, replacing any number of
+    /// threads set with [`threads`].
+    ///
+    /// [`threads`]: #method.threads
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ecosystem::{Ecosystem, Organism};
+    /// use std::sync::Arc;
+    /// # use rand::Rng;
+    /// # struct Guess(f64);
+    /// # impl Organism for Guess {
+    /// #     type Fitness = f64;
+    /// #     fn fitness(&self) -> f64 { 1.0 / (42.0 - self.0).abs() }
+    /// #     fn breed(&self, other: &Self, rng: &mut impl Rng) -> Self { Guess((self.0 + other.0) / 2.0) }
+    /// #     fn mutate(&mut self, rate: f64, rng: &mut impl Rng) {}
+    /// # }
+    ///
+    /// let pool = Arc::new(rayon::ThreadPoolBuilder::new().num_threads(2).build().unwrap());
+    /// let mut ecosystem = Ecosystem::builder()
+    ///     .organisms((0..100).map(|i| Guess(f64::from(i) + 0.5)))
+    ///     .thread_pool(Arc::clone(&pool))
+    ///     .min_chunk_len(10)
+    ///     .build()
+    ///     .unwrap();
+    /// ecosystem.breed_next_generation()?;
+    /// # Ok::<(), ecosystem::EcosystemError>(())
+    /// ```
+    #[cfg(feature = "parallel")]
+    pub fn thread_pool(mut self, pool: Arc<rayon::ThreadPool>) -> Self {
+        self.parallelism.pool = Some(pool);
+        self.threads = None;
+        self
+    }
+
+    /// Has the ecosystem evaluate and breed organisms on a thread pool of its
+    /// own with `count` threads, rather than on rayon's global pool. A count
+    /// of zero chooses the number of threads as rayon does by default.
+    ///
+    /// The number of threads never changes the outcome of a [seeded] run:
+    /// every child is bred with its own generator, seeded in turn from the
+    /// ecosystem's, so the populations are identical to those bred on a
+    /// single thread, or without the `parallel` feature.
+    ///
+    /// [seeded]: #method.seed
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ecosystem::{Ecosystem, Neighborhood, Organism, PopulationModel, Replace};
+    /// use rand::Rng;
+    /// # struct Guess(f64);
+    /// # impl Organism for Guess {
+    /// #     type Fitness = f64;
+    /// #     fn fitness(&self) -> f64 { 1.0 / (42.0 - self.0).abs() }
+    /// #     fn breed(&self, other: &Self, rng: &mut impl Rng) -> Self {
+    /// #         Guess(self.0 + (other.0 - self.0) * rng.gen::<f64>())
+    /// #     }
+    /// #     fn mutate(&mut self, rate: f64, rng: &mut impl Rng) {
+    /// #         self.0 += rng.gen_range(-rate, rate);
+    /// #     }
+    /// # }
+    ///
+    /// let evolve = |model, seed, threads| {
+    ///     let mut ecosystem = Ecosystem::builder()
+    ///         .organisms((0..100).map(|i| Guess(f64::from(i) + 0.5)))
+    ///         .population_model(model)
+    ///         .mutation_rate(0.5)
+    ///         .threads(threads)
+    ///         .seed(seed)
+    ///         .build()
+    ///         .unwrap();
+    ///     for _ in 0..10 {
+    ///         ecosystem.breed_next_generation().unwrap();
+    ///     }
+    ///     ecosystem.organisms().map(|guess| guess.0.to_bits()).collect::<Vec<_>>()
+    /// };
+    ///
+    /// let models = [
+    ///     PopulationModel::Generational,
+    ///     PopulationModel::SteadyState { children: 10, replace: Replace::Worst },
+    ///     PopulationModel::Cellular {
+    ///         width: 10,
+    ///         neighborhood: Neighborhood::VonNeumann { radius: 1 },
+    ///     },
+    /// ];
+    /// for &model in &models {
+    ///     for seed in 0..5 {
+    ///         assert_eq!(evolve(model, seed, 1), evolve(model, seed, 4));
+    ///     }
+    /// }
+    /// ```
+    #[cfg(feature = "parallel")]
+    pub fn threads(mut self, count: usize) -> Self {
+        self.threads = Some(count);
+        self.parallelism.pool = None;
+        self
+    }
+
+    /// Sets the least number of organisms that each thread evaluates or
+    /// breeds at a time. Larger chunks cost less to schedule, which pays off
+    /// when organisms are cheap to evaluate and breed.
+    ///
+    /// By default, rayon divides the work as finely as it sees fit.
+    #[cfg(feature = "parallel")]
+    pub fn min_chunk_len(mut self, len: usize) -> Self {
+        self.parallelism.min_len = len;
+        self
+    }
+
+    /// Sets a [surrogate] model of the fitness function, by which `candidates`
+    /// children are bred for every child that is kept, and only the most
+    /// promising are kept and evaluated. At least one candidate is bred for
+    /// each child.
+    ///
+    /// Candidates are screened whenever children are bred from parents
+    /// chosen by the selection strategy, which is the case for every
+    /// population model except the cellular one.
+    ///
+    /// [surrogate]: surrogate/index.html
+    pub fn surrogate<T: Surrogate<O> + 'static>(mut self, surrogate: T, candidates: usize) -> Self {
+        self.surrogate = Some((Box::new(surrogate), candidates.max(1)));
+        self
+    }
+
+    /// Seeds the ecosystem's random number generator, making its evolution
+    /// reproducible.
+    ///
+    /// All of the randomness used by the ecosystem (such as in selection) is
+    /// drawn from this generator, including the generators passed to
+    /// [`Organism::breed`] and [`Organism::mutate`]. Runs with the same seed
+    /// and the same organisms follow the same trajectory, provided that the
+    /// organisms do not draw on other sources of randomness. This holds
+    /// however many [threads] the work is spread across, and with or
+    /// without the `parallel` feature.
+    ///
+    /// The generator is otherwise seeded from the operating system, except
+    /// without the `std` feature, when either this or [`rng`] must be called.
+    ///
+    /// [`rng`]: #method.rng
+    /// [threads]: #method.threads
+    /// [`Organism::breed`]: trait.Organism.html#tymethod.breed
+    /// [`Organism::mutate`]: trait.Organism.html#tymethod.mutate
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ecosystem::{Ecosystem, Organism};
+    /// # use rand::Rng;
+    /// # struct Number(u32);
+    /// # impl Organism for Number {
+    /// #     type Fitness = u32;
+    /// #     fn fitness(&self) -> u32 { self.0 }
+    /// #     fn breed(&self, other: &Self, rng: &mut impl Rng) -> Self { Number(self.0 + other.0) }
+    /// #     fn mutate(&mut self, rate: f64, rng: &mut impl Rng) {}
+    /// # }
+    ///
+    /// let evolve = |seed| {
+    ///     let mut ecosystem = Ecosystem::builder()
+    ///         .organisms((0..20).map(Number))
+    ///         .seed(seed)
+    ///         .build()
+    ///         .unwrap();
+    ///     for _ in 0..5 {
+    ///         ecosystem.breed_next_generation().unwrap();
+    ///     }
+    ///     ecosystem.organisms().map(|n| n.0).collect::<Vec<_>>()
+    /// };
+    ///
+    /// assert_eq!(evolve(7), evolve(7));
+    /// ```
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.rng = Some(Pcg64::seed_from_u64(seed));
+        self
+    }
+
+    /// Seeds the ecosystem's random number generator from the given one.
+    ///
+    /// # Errors
+    ///
+    /// If the given generator fails to produce a seed, [`build`] returns
+    /// [`BuildError::RngSeed`].
+    ///
+    /// [`build`]: #method.build
+    /// [`BuildError::RngSeed`]: enum.BuildError.html#variant.RngSeed
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ecosystem::{BuildError, Ecosystem, Organism};
+    /// use rand::{Rng, RngCore};
+    /// # struct Guess(f64);
+    /// # impl Organism for Guess {
+    /// #     type Fitness = f64;
+    /// #     fn fitness(&self) -> f64 { 1.0 / (42.0 - self.0).abs() }
+    /// #     fn breed(&self, other: &Self, rng: &mut impl Rng) -> Self { Guess((self.0 + other.0) / 2.0) }
+    /// #     fn mutate(&mut self, rate: f64, rng: &mut impl Rng) {}
+    /// # }
+    ///
+    /// // A hardware generator that has been unplugged
+    /// struct Unplugged;
+    ///
+    /// impl RngCore for Unplugged {
+    ///     fn next_u32(&mut self) -> u32 { unimplemented!() }
+    ///     fn next_u64(&mut self) -> u64 { unimplemented!() }
+    ///     fn fill_bytes(&mut self, dest: &mut [u8]) { unimplemented!() }
+    ///     fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+    ///         Err(rand::Error::new("the device is unplugged"))
+    ///     }
+    /// }
+    ///
+    /// let result = Ecosystem::builder()
+    ///     .rng(Unplugged)
+    ///     .organisms(vec![Guess(1.0), Guess(2.0)])
+    ///     .build();
+    ///
+    /// assert!(matches!(result, Err(BuildError::RngSeed(_))));
+    /// ```
+    pub fn rng<R: RngCore>(mut self, rng: R) -> Self {
+        match Pcg64::from_rng(rng) {
+            Ok(rng) => self.rng = Some(rng),
+            Err(error) => {
+                self.error
+                    .get_or_insert(BuildError::RngSeed(error.to_string()));
+            }
+        }
+        self
+    }
+
+    /// Sets the strategy used to select organisms for breeding.
+    ///
+    /// By default, organisms are selected in proportion to their fitness.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ecosystem::{
+    ///     selection::Selection, Ecosystem, EcosystemError, Individual, Objective, Organism,
+    /// };
+    /// use rand::{Rng, RngCore};
+    /// # struct Number(u32);
+    /// # impl Organism for Number {
+    /// #     type Fitness = f64;
+    /// #     fn fitness(&self) -> f64 { f64::from(self.0) }
+    /// #     fn breed(&self, other: &Self, rng: &mut impl Rng) -> Self { Number(self.0) }
+    /// #     fn mutate(&mut self, rate: f64, rng: &mut impl Rng) {}
+    /// # }
+    ///
+    /// /// Selects parents uniformly at random, ignoring fitness.
+    /// struct Uniform;
+    ///
+    /// impl<O: Organism> Selection<O> for Uniform {
+    ///     fn select(
+    ///         &self,
+    ///         population: &[Individual<O>],
+    ///         _objective: Objective,
+    ///         count: usize,
+    ///         rng: &mut dyn RngCore,
+    ///     ) -> Result<Vec<usize>, EcosystemError> {
+    ///         if population.is_empty() {
+    ///             return Err(EcosystemError::EmptyPopulation);
+    ///         }
+    ///         Ok((0..count).map(|_| rng.gen_range(0, population.len())).collect())
+    ///     }
+    /// }
+    ///
+    /// let mut ecosystem = Ecosystem::builder()
+    ///     .organisms((0..10).map(Number))
+    ///     .selection(Uniform)
+    ///     .build()
+    ///     .unwrap();
+    /// ecosystem.breed_next_generation()?;
+    /// # Ok::<(), EcosystemError>(())
+    /// ```
+    pub fn selection<S: Selection<O> + 'static>(mut self, selection: S) -> Self {
+        self.selection = Box::new(selection);
+        self.custom_selection = !is_default_selection::<S>();
+        self
+    }
+
+    /// Sets the [strategy] that chooses the organisms replaced by children
+    /// wherever only part of the population is replaced: in a steady-state
+    /// population, in place of the model's own `replace` policy, and under a
+    /// [generation gap], in place of random replacement.
+    ///
+    /// [strategy]: replacement/index.html
+    /// [generation gap]: #method.generation_gap
+    pub fn replacement<R: Replacement<O> + 'static>(mut self, replacement: R) -> Self {
+        self.replacement = Some(Box::new(replacement));
+        self
+    }
+
+    /// Sets the direction in which fitness values are optimised.
+    ///
+    /// By default, higher fitness values are better. Minimising allows cost or
+    /// error functions to be used directly as the fitness function, without
+    /// inverting them. This affects selection, elitism, the archive and
+    /// [`Ecosystem::fittest`].
+    ///
+    /// [`Ecosystem::fittest`]: struct.Ecosystem.html#method.fittest
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ecosystem::{Ecosystem, Objective, Organism};
+    /// # use rand::Rng;
+    /// # struct Guess(f64);
+    /// # impl Organism for Guess {
+    /// #     type Fitness = f64;
+    /// #     fn fitness(&self) -> f64 { (42.0 - self.0).abs() }
+    /// #     fn breed(&self, other: &Self, rng: &mut impl Rng) -> Self { Guess((self.0 + other.0) / 2.0) }
+    /// #     fn mutate(&mut self, rate: f64, rng: &mut impl Rng) {}
+    /// # }
+    ///
+    /// // The fitness of each guess is its error
+    /// let ecosystem = Ecosystem::builder()
+    ///     .organisms(vec![Guess(0.0), Guess(40.0), Guess(100.0)])
+    ///     .objective(Objective::Minimize)
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// assert_eq!(ecosystem.fittest().unwrap().0, 40.0);
+    /// ```
+    pub fn objective(mut self, objective: Objective) -> Self {
+        self.objective = objective;
+        self
+    }
+
+    /// Sets the number of the fittest organisms that are carried over,
+    /// unchanged, into each new generation. This must be smaller than the
+    /// population size.
+    ///
+    /// The rest of each generation is filled by breeding as usual, so the
+    /// population size stays the same. This guarantees that the best
+    /// organism found so far is never lost.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ecosystem::{Ecosystem, Organism};
+    /// # use rand::Rng;
+    /// # struct Guess(f64);
+    /// # impl Organism for Guess {
+    /// #     type Fitness = f64;
+    /// #     fn fitness(&self) -> f64 { 1.0 / (42.0 - self.0).abs() }
+    /// #     fn breed(&self, other: &Self, rng: &mut impl Rng) -> Self { Guess((self.0 + other.0) / 2.0) }
+    /// #     fn mutate(&mut self, rate: f64, rng: &mut impl Rng) {
+    /// #         self.0 += rng.gen_range(-rate, rate) * 100.0;
+    /// #     }
+    /// # }
+    ///
+    /// let mut ecosystem = Ecosystem::builder()
+    ///     .organisms((0..20).map(|i| Guess(f64::from(i) * 5.0 + 0.5)))
+    ///     .mutation_rate(1.0)
+    ///     .elitism(2)
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// let mut best = ecosystem.fittest().unwrap().fitness();
+    /// for _ in 0..20 {
+    ///     // Even wildly disruptive mutation cannot lose the best guess
+    ///     ecosystem.breed_next_generation()?;
+    ///     assert!(ecosystem.fittest().unwrap().fitness() >= best);
+    ///     best = ecosystem.fittest().unwrap().fitness();
+    /// }
+    /// # Ok::<(), ecosystem::EcosystemError>(())
+    /// ```
+    pub fn elitism(mut self, count: usize) -> Self {
+        self.elitism = count;
+        self
+    }
+
+    /// Sets the generation gap: the fraction of the population, within
+    /// (0, 1], that is replaced by children in each generation. Each child
+    /// takes the place of a randomly chosen organism other than the elites
+    /// (or one chosen by the [replacement] strategy, if set), and the rest of
+    /// the population survives unchanged. The default of 1 replaces every
+    /// organism other than the elites.
+    ///
+    /// A smaller gap makes for a gentler search, in which good organisms
+    /// survive to breed again. It applies to the generational population
+    /// model; the other models set how many organisms are replaced
+    /// themselves.
+    ///
+    /// [replacement]: #method.replacement
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ecosystem::{Ecosystem, Organism};
+    /// # use rand::Rng;
+    /// # struct Guess(f64);
+    /// # impl Organism for Guess {
+    /// #     type Fitness = f64;
+    /// #     fn fitness(&self) -> f64 { 1.0 / (42.0 - self.0).abs() }
+    /// #     fn breed(&self, other: &Self, rng: &mut impl Rng) -> Self { Guess((self.0 + other.0) / 2.0) }
+    /// #     fn mutate(&mut self, rate: f64, rng: &mut impl Rng) {}
+    /// # }
+    ///
+    /// let mut ecosystem = Ecosystem::builder()
+    ///     .organisms((0..20).map(|i| Guess(f64::from(i) + 0.5)))
+    ///     .generation_gap(0.25)
+    ///     .build()
+    ///     .unwrap();
+    /// ecosystem.breed_next_generation()?;
+    ///
+    /// // A quarter of the population was replaced
+    /// let survivors = ecosystem.individuals().iter().filter(|individual| individual.age() > 0);
+    /// assert_eq!(survivors.count(), 15);
+    /// # Ok::<(), ecosystem::EcosystemError>(())
+    /// ```
+    pub fn generation_gap(mut self, gap: f64) -> Self {
+        self.generation_gap = gap;
+        self
+    }
+
+    /// Sets how each new generation is formed.
+    ///
+    /// By default, the whole population (other than the elites) is replaced
+    /// in each generation. In a steady-state population, the number of
+    /// children bred in each step must be non-zero, and no more than the
+    /// number of organisms that are not elites. The evolution strategy
+    /// schemes must breed at least one child, and (μ,λ) must breed enough
+    /// children to refill the population. A cellular population must fill a
+    /// whole number of rows.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ecosystem::{Ecosystem, Organism, PopulationModel, Replace};
+    /// # use rand::Rng;
+    /// # struct Guess(f64);
+    /// # impl Organism for Guess {
+    /// #     type Fitness = f64;
+    /// #     fn fitness(&self) -> f64 { 1.0 / (42.0 - self.0).abs() }
+    /// #     fn breed(&self, other: &Self, rng: &mut impl Rng) -> Self { Guess((self.0 + other.0) / 2.0) }
+    /// #     fn mutate(&mut self, rate: f64, rng: &mut impl Rng) {
+    /// #         self.0 += rng.gen_range(-rate, rate);
+    /// #     }
+    /// # }
+    ///
+    /// let mut ecosystem = Ecosystem::builder()
+    ///     .organisms((0..20).map(|i| Guess(f64::from(i) * 5.0 + 0.5)))
+    ///     .population_model(PopulationModel::SteadyState {
+    ///         children: 2,
+    ///         replace: Replace::Worst,
+    ///     })
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// let mut best = ecosystem.fittest().unwrap().fitness();
+    /// for _ in 0..50 {
+    ///     ecosystem.breed_next_generation()?;
+    ///     // Only the worst organisms are ever replaced
+    ///     assert!(ecosystem.fittest().unwrap().fitness() >= best);
+    ///     best = ecosystem.fittest().unwrap().fitness();
+    /// }
+    /// assert_eq!(ecosystem.organisms().count(), 20);
+    /// # Ok::<(), ecosystem::EcosystemError>(())
+    /// ```
+    ///
+    /// The evolution strategy schemes keep the population size fixed, however
+    /// many children are bred:
+    ///
+    /// ```rust
+    /// use ecosystem::{Ecosystem, Organism, PopulationModel};
+    /// # use rand::Rng;
+    /// # struct Guess(f64);
+    /// # impl Organism for Guess {
+    /// #     type Fitness = f64;
+    /// #     fn fitness(&self) -> f64 { 1.0 / (42.0 - self.0).abs() }
+    /// #     fn breed(&self, other: &Self, rng: &mut impl Rng) -> Self { Guess((self.0 + other.0) / 2.0) }
+    /// #     fn mutate(&mut self, rate: f64, rng: &mut impl Rng) {
+    /// #         self.0 += rng.gen_range(-rate, rate);
+    /// #     }
+    /// # }
+    ///
+    /// // A (10+70) evolution strategy
+    /// let mut ecosystem = Ecosystem::builder()
+    ///     .organisms((0..10).map(|i| Guess(f64::from(i) * 10.0 + 0.5)))
+    ///     .population_model(PopulationModel::MuPlusLambda { offspring: 70 })
+    ///     .build()
+    ///     .unwrap();
+    /// for _ in 0..20 {
+    ///     ecosystem.breed_next_generation()?;
+    /// }
+    ///
+    /// assert_eq!(ecosystem.organisms().count(), 10);
+    /// assert!((ecosystem.fittest().unwrap().0 - 42.0).abs() < 2.0);
+    /// # Ok::<(), ecosystem::EcosystemError>(())
+    /// ```
+    pub fn population_model(mut self, model: PopulationModel) -> Self {
+        self.model = model;
+        self
+    }
+
+    /// Enables recording of the ecosystem's [`history`], which holds summary
+    /// statistics for every generation bred with
+    /// [`Ecosystem::breed_next_generation`].
+    ///
+    /// Recording reuses the fitness values cached for breeding, so it adds
+    /// little overhead.
+    ///
+    /// [`history`]: history/index.html
+    /// [`Ecosystem::breed_next_generation`]: struct.Ecosystem.html#method.breed_next_generation
+    pub fn record_history(mut self) -> Self {
+        self.record_history = true;
+        self
+    }
+
+    /// Enables tracking of the ecosystem's [genealogy], which records the
+    /// parents of every individual and the generation it was born into.
+    ///
+    /// [genealogy]: genealogy/index.html
+    pub fn track_genealogy(mut self) -> Self {
+        self.track_genealogy = true;
+        self
+    }
+
+    /// Has every child that duplicates an organism in the population (or
+    /// another child) mutated again until it is distinct, recognising
+    /// duplicates by the given key. This keeps a discrete search space from
+    /// filling up with copies of the same organism.
+    ///
+    /// Keys are looked up by their hashes, and those that hash alike are
+    /// then compared for equality, so a hash collision never makes distinct
+    /// organisms duplicates. A duplicate is kept as it is if it is still not
+    /// distinct after a hundred mutations.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ecosystem::{Ecosystem, Organism};
+    /// # use rand::Rng;
+    /// # struct Guess(f64);
+    /// # impl Organism for Guess {
+    /// #     type Fitness = f64;
+    /// #     fn fitness(&self) -> f64 { 1.0 / (42.0 - self.0).abs() }
+    /// #     fn breed(&self, other: &Self, rng: &mut impl Rng) -> Self { Guess((self.0 + other.0) / 2.0) }
+    /// #     fn mutate(&mut self, rate: f64, rng: &mut impl Rng) {
+    /// #         self.0 += rng.gen_range(-rate, rate);
+    /// #     }
+    /// # }
+    ///
+    /// // Guesses that round to the same tenth count as duplicates
+    /// let mut ecosystem = Ecosystem::builder()
+    ///     .organisms((0..20).map(|i| Guess(f64::from(i) * 5.0 + 0.5)))
+    ///     .mutation_rate(1.0)
+    ///     .deduplicate_by_key(|guess: &Guess| (guess.0 * 10.0).round() as i64)
+    ///     .seed(0)
+    ///     .build()
+    ///     .unwrap();
+    /// for _ in 0..10 {
+    ///     ecosystem.breed_next_generation()?;
+    /// }
+    ///
+    /// let mut rounded: Vec<_> = ecosystem
+    ///     .organisms()
+    ///     .map(|guess| (guess.0 * 10.0).round() as i64)
+    ///     .collect();
+    /// rounded.sort();
+    /// rounded.dedup();
+    /// assert_eq!(rounded.len(), 20);
+    /// # Ok::<(), ecosystem::EcosystemError>(())
+    /// ```
+    pub fn deduplicate_by_key<K, F>(mut self, key: F) -> Self
+    where
+        K: Hash + Eq,
+        F: Fn(&O) -> K + Send + Sync + 'static,
+    {
+        self.duplicate_key = Some(Box::new(crate::KeyFunction(key)));
+        self
+    }
+
+    /// Has a fraction of the population, within [0, 1], replaced in each
+    /// generation by immigrants created by `generate`. The least fit
+    /// organisms are replaced, other than the elites.
+    ///
+    /// A steady trickle of random organisms keeps the search exploring,
+    /// which helps in deceptive problems and in ones whose fitness function
+    /// changes over time.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ecosystem::{Ecosystem, Organism};
+    /// use rand::Rng;
+    /// # struct Guess(f64);
+    /// # impl Organism for Guess {
+    /// #     type Fitness = f64;
+    /// #     fn fitness(&self) -> f64 { 1.0 / (42.0 - self.0).abs() }
+    /// #     fn breed(&self, other: &Self, rng: &mut impl Rng) -> Self { Guess((self.0 + other.0) / 2.0) }
+    /// #     fn mutate(&mut self, rate: f64, rng: &mut impl Rng) {}
+    /// # }
+    ///
+    /// // Every guess starts out far from the answer, and none are mutated
+    /// let mut ecosystem = Ecosystem::builder()
+    ///     .organisms((0..20).map(|i| Guess(f64::from(i) + 500.5)))
+    ///     .immigrants(0.1, |rng| Guess(rng.gen_range(0.0, 1000.0)))
+    ///     .build()
+    ///     .unwrap();
+    /// for _ in 0..20 {
+    ///     ecosystem.breed_next_generation()?;
+    /// }
+    ///
+    /// // Immigrants have brought in guesses from elsewhere
+    /// assert!(ecosystem.organisms().any(|guess| guess.0 < 500.0));
+    /// # Ok::<(), ecosystem::EcosystemError>(())
+    /// ```
+    pub fn immigrants<G>(mut self, fraction: f64, generate: G) -> Self
+    where
+        G: Fn(&mut dyn RngCore) -> O + Send + Sync + 'static,
+    {
+        self.immigration = Some((fraction, Box::new(generate)));
+        self
+    }
+
+    /// Sets how the ecosystem responds once its best fitness has not
+    /// improved for the given number of generations, such as by restarting
+    /// the search. After responding, the ecosystem waits for the same number
+    /// of generations without improvement before responding again.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ecosystem::{
+    ///     termination::{MaxGenerations, StopReason},
+    ///     Ecosystem, Organism, StagnationResponse,
+    /// };
+    /// # use rand::Rng;
+    /// # struct Guess(f64);
+    /// # impl Organism for Guess {
+    /// #     type Fitness = f64;
+    /// #     fn fitness(&self) -> f64 { 1.0 / (42.0 - self.0).abs() }
+    /// #     fn breed(&self, other: &Self, rng: &mut impl Rng) -> Self { Guess((self.0 + other.0) / 2.0) }
+    /// #     fn mutate(&mut self, rate: f64, rng: &mut impl Rng) {}
+    /// # }
+    ///
+    /// let mut ecosystem = Ecosystem::builder()
+    ///     .organisms((0..20).map(|_| Guess(100.5)))
+    ///     .on_stagnation(10, StagnationResponse::Stop)
+    ///     .build()
+    ///     .unwrap();
+    /// let summary = ecosystem.run(MaxGenerations(1000))?;
+    ///
+    /// assert_eq!(summary.reason, StopReason::Stagnation);
+    /// assert_eq!(summary.generations, 10);
+    /// # Ok::<(), ecosystem::EcosystemError>(())
+    /// ```
+    pub fn on_stagnation(mut self, generations: u32, response: StagnationResponse<O>) -> Self {
+        self.stagnation = Some(StagnationMonitor::new(generations, response));
+        self
+    }
+
+    /// Registers an [observer], which is notified of the progress of every
+    /// [`Ecosystem::run`]. Any number of observers can be registered; they
+    /// are notified in the order of registration.
+    ///
+    /// [observer]: observer/index.html
+    /// [`Ecosystem::run`]: struct.Ecosystem.html#method.run
+    pub fn observer<T: Observer<O> + 'static>(mut self, observer: T) -> Self {
+        self.observers.push(Box::new(observer));
+        self
+    }
+
+    /// Sets a [progress reporter], which is told how every
+    /// [`Ecosystem::run`] is going after each generation: the generation
+    /// number, the best fitness and, if the termination condition can tell,
+    /// the fraction of the run completed and the estimated time remaining.
+    ///
+    /// [progress reporter]: progress/index.html
+    /// [`Ecosystem::run`]: struct.Ecosystem.html#method.run
+    pub fn progress<T: ProgressReporter<O::Fitness> + 'static>(mut self, reporter: T) -> Self {
+        self.progress = Some(Box::new(reporter));
+        self
+    }
+
+    /// Validates the configuration, producing the configured ecosystem.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if there are no organisms, if the mutation rate,
+    /// crossover rate or fraction of immigrants lies outside of [0, 1] or the
+    /// generation gap outside of (0, 1], if the
+    /// elitism count is not smaller than the population size, if the
+    /// population model is misconfigured, if the ecosystem's own thread pool
+    /// cannot be created, if the given random number generator failed to
+    /// produce a seed, or if no random number generator was given without
+    /// the `std` feature.
+    pub fn build(self) -> Result<Ecosystem<O>, BuildError> {
+        if let Some(error) = self.error {
+            return Err(error);
+        }
+        if self.organisms.is_empty() {
+            return Err(BuildError::EmptyPopulation);
+        }
+        if !(0.0..=1.0).contains(&self.mutation_rate) {
+            return Err(BuildError::InvalidMutationRate(self.mutation_rate));
+        }
+        if let Some((rate, _)) = self.crossover {
+            if !(0.0..=1.0).contains(&rate) {
+                return Err(BuildError::InvalidCrossoverRate(rate));
+            }
+        }
+        if !(self.generation_gap > 0.0 && self.generation_gap <= 1.0) {
+            return Err(BuildError::InvalidGenerationGap(self.generation_gap));
+        }
+        if let Some((fraction, _)) = self.immigration {
+            if !(0.0..=1.0).contains(&fraction) {
+                return Err(BuildError::InvalidImmigrantFraction(fraction));
+            }
+        }
+        if self.elitism >= self.organisms.len() {
+            return Err(BuildError::InvalidElitism {
+                elitism: self.elitism,
+                population_size: self.organisms.len(),
+            });
+        }
+        match self.model {
+            PopulationModel::Generational => {}
+            PopulationModel::SteadyState { children, .. } => {
+                let replaceable = self.organisms.len() - self.elitism;
+                if children == 0 || children > replaceable {
+                    return Err(BuildError::InvalidChildCount {
+                        children,
+                        replaceable,
+                    });
+                }
+            }
+            PopulationModel::MuPlusLambda { offspring } => {
+                if offspring == 0 {
+                    return Err(BuildError::TooFewOffspring {
+                        offspring,
+                        required: 1,
+                    });
+                }
+            }
+            PopulationModel::MuCommaLambda { offspring } => {
+                let required = (self.organisms.len() - self.elitism).max(1);
+                if offspring < required {
+                    return Err(BuildError::TooFewOffspring {
+                        offspring,
+                        required,
+                    });
+                }
+            }
+            PopulationModel::Cellular { width, .. } => {
+                if width == 0 || !self.organisms.len().is_multiple_of(width) {
+                    return Err(BuildError::InvalidGridWidth {
+                        width,
+                        population_size: self.organisms.len(),
+                    });
+                }
+            }
+        }
+
+        #[cfg(feature = "std")]
+        let rng = self.rng.unwrap_or_else(Pcg64::from_entropy);
+        #[cfg(not(feature = "std"))]
+        let rng = self.rng.ok_or(BuildError::MissingRng)?;
+        #[cfg(feature = "parallel")]
+        let parallelism = match self.threads {
+            Some(count) => Parallelism {
+                pool: Some(Arc::new(
+                    rayon::ThreadPoolBuilder::new()
+                        .num_threads(count)
+                        .build()
+                        .map_err(|error| BuildError::ThreadPool(error.to_string()))?,
+                )),
+                ..self.parallelism
+            },
+            None => self.parallelism,
+        };
+        #[cfg(not(feature = "parallel"))]
+        let parallelism = self.parallelism;
+
+        let population: Vec<_> = self.organisms.into_iter().map(Individual::new).collect();
+        let genealogy = if self.track_genealogy {
+            let mut genealogy = Genealogy::new();
+            for individual in &population {
+                genealogy.record(individual.id(), Vec::new(), 0);
+            }
+            Some(genealogy)
+        } else {
+            None
+        };
+
+        Ok(Ecosystem {
+            population,
+            generation: 0,
+            archive: Vec::new(),
+            archive_capacity: self.archive_capacity,
+            archive_ops: self.archive_ops,
+            evaluations: AtomicU64::new(0),
+            mutation_rate: self.mutation_rate,
+            rng,
+            selection: self.selection,
+            replacement: self.replacement,
+            elitism: self.elitism,
+            generation_gap: self.generation_gap,
+            objective: self.objective,
+            history: if self.record_history {
+                Some(History::new())
+            } else {
+                None
+            },
+            observers: self.observers,
+            progress: self.progress,
+            model: self.model,
+            repair: self.repair,
+            crowding: self.crowding,
+            crossover: self.crossover,
+            variance_scaling: self.variance_scaling,
+            gene_variances: Vec::new(),
+            duplicate_key: self.duplicate_key,
+            immigration: self.immigration,
+            stagnation: self.stagnation,
+            mutation_control: self.mutation_control,
+            population_control: self.population_control,
+            local_search: self.local_search,
+            baldwinian: self.baldwinian,
+            surrogate: self.surrogate,
+            batch_evaluator: self.batch_evaluator,
+            hypermutation: None,
+            genealogy,
+            parallelism,
+            custom_selection: self.custom_selection,
+            unrestored: Vec::new(),
+        })
+    }
+}
+
+impl<O: Organism + Constrained + Send + Sync> EcosystemBuilder<O> {
+    /// Has every child [repaired] after it is bred and mutated.
+    ///
+    /// [repaired]: trait.Constrained.html#method.repair
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ecosystem::{Constrained, Ecosystem, Organism};
+    /// # use rand::Rng;
+    /// # struct Guess(f64);
+    /// # impl Organism for Guess {
+    /// #     type Fitness = f64;
+    /// #     fn fitness(&self) -> f64 { self.0 }
+    /// #     fn breed(&self, other: &Self, rng: &mut impl Rng) -> Self { Guess((self.0 + other.0) / 2.0) }
+    /// #     fn mutate(&mut self, rate: f64, rng: &mut impl Rng) {
+    /// #         self.0 += rng.gen_range(-rate, rate);
+    /// #     }
+    /// # }
+    ///
+    /// impl Constrained for Guess {
+    ///     fn violations(&self) -> f64 {
+    ///         (self.0 - 42.0).max(0.0)
+    ///     }
+    ///
+    ///     fn repair(&mut self) {
+    ///         self.0 = self.0.min(42.0);
+    ///     }
+    /// }
+    ///
+    /// let mut ecosystem = Ecosystem::builder()
+    ///     .organisms((0..100).map(|i| Guess(f64::from(i))))
+    ///     .repair()
+    ///     .build()
+    ///     .unwrap();
+    /// ecosystem.breed_next_generation()?;
+    ///
+    /// assert!(ecosystem.organisms().all(|guess| guess.violations() == 0.0));
+    /// # Ok::<(), ecosystem::EcosystemError>(())
+    /// ```
+    pub fn repair(mut self) -> Self {
+        self.repair = Some(O::repair);
+        self
+    }
+}
+
+#[cfg(feature = "std")]
+impl<O: Organism + RealGenome + Send + Sync> EcosystemBuilder<O> {
+    /// Has every child mutated by [variance-scaled mutation] in place of
+    /// [`Organism::mutate`], with each gene's standard deviation `scale`
+    /// times its standard deviation across the current population.
+    ///
+    /// The variances are measured before each generation is bred, so genes
+    /// on which the population has converged mutate less while the others
+    /// keep exploring, without any tuning beyond `scale`.
+    ///
+    /// [variance-scaled mutation]: genome/struct.RealVector.html#method.variance_scaled_mutation
+    /// [`Organism::mutate`]: trait.Organism.html#tymethod.mutate
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ecosystem::{
+    ///     genome::{RealGenome, RealVector},
+    ///     selection::Tournament,
+    ///     Ecosystem, Objective, Organism,
+    /// };
+    /// use rand::Rng;
+    ///
+    /// struct Point(RealVector);
+    ///
+    /// impl Organism for Point {
+    ///     type Fitness = f64;
+    ///
+    ///     fn fitness(&self) -> f64 {
+    ///         self.0.genes().iter().map(|x| x * x).sum()
+    ///     }
+    ///
+    ///     fn breed(&self, other: &Self, rng: &mut impl Rng) -> Self {
+    ///         Point(self.0.arithmetic_crossover(&other.0, rng).0)
+    ///     }
+    ///
+    ///     // Never called once variance-scaled mutation is enabled
+    ///     fn mutate(&mut self, rate: f64, rng: &mut impl Rng) {
+    ///         self.0.gaussian_mutation(rate, 1.0, rng);
+    ///     }
+    /// }
+    ///
+    /// impl RealGenome for Point {
+    ///     fn real_vector(&self) -> &RealVector {
+    ///         &self.0
+    ///     }
+    ///
+    ///     fn real_vector_mut(&mut self) -> &mut RealVector {
+    ///         &mut self.0
+    ///     }
+    /// }
+    ///
+    /// // The first gene starts out converged, and the second spread out
+    /// let mut ecosystem = Ecosystem::builder()
+    ///     .organisms((0..50).map(|i| Point(RealVector::new(vec![0.0, f64::from(i) - 25.0]))))
+    ///     .objective(Objective::Minimize)
+    ///     .selection(Tournament { size: 3 })
+    ///     .mutation_rate(0.5)
+    ///     .variance_scaled_mutation(0.5)
+    ///     .build()
+    ///     .unwrap();
+    /// for _ in 0..50 {
+    ///     ecosystem.breed_next_generation()?;
+    /// }
+    ///
+    /// // The converged gene was left alone, while the other was optimised
+    /// assert!(ecosystem.organisms().all(|point| point.0.genes()[0] == 0.0));
+    /// assert!(ecosystem.fittest().unwrap().fitness() < 0.01);
+    /// # Ok::<(), ecosystem::EcosystemError>(())
+    /// ```
+    pub fn variance_scaled_mutation(mut self, scale: f64) -> Self {
+        self.variance_scaling = Some(crate::genome::variance_scaling(scale));
+        self
+    }
+}
+
+impl<O: Organism + Distance + Send + Sync> EcosystemBuilder<O> {
+    /// Has each generation bred by *deterministic crowding*, a niching
+    /// method in which offspring compete only with their parents.
+    ///
+    /// In each generation, the population is paired off at random, and each
+    /// pair breeds two children. Each child is matched with the parent more
+    /// similar to it, as measured by [`Distance`], and takes that parent's
+    /// place if it is fitter. As children only ever replace organisms of
+    /// their own niche, several optima can be held at once, and the best
+    /// organism is never lost. This works best when the two children share
+    /// out their parents' genes between them, as [`Organism::breed_pair`]
+    /// can arrange.
+    ///
+    /// Deterministic crowding takes the place of the population model, and
+    /// the selection strategy and elitism are not used.
+    ///
+    /// [`Distance`]: diversity/trait.Distance.html
+    /// [`Organism::breed_pair`]: trait.Organism.html#method.breed_pair
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ecosystem::{diversity::Distance, Ecosystem, Organism};
+    /// use rand::Rng;
+    /// # struct Guess(f64);
+    ///
+    /// impl Organism for Guess {
+    /// #   type Fitness = f64;
+    /// #   fn fitness(&self) -> f64 { -(42.0 - self.0).abs() }
+    /// #   fn breed(&self, other: &Self, rng: &mut impl Rng) -> Self { Guess(self.0) }
+    /// #   fn mutate(&mut self, rate: f64, rng: &mut impl Rng) {
+    /// #       self.0 += rng.gen_range(-rate, rate);
+    /// #   }
+    ///     // Each child takes the guess of a different parent
+    ///     fn breed_pair(&self, other: &Self, rng: &mut impl Rng) -> (Self, Self) {
+    ///         let (first, second) = if rng.gen() { (self, other) } else { (other, self) };
+    ///         (Guess(first.0), Guess(second.0))
+    ///     }
+    /// }
+    ///
+    /// impl Distance for Guess {
+    ///     fn distance(&self, other: &Self) -> f64 {
+    ///         (self.0 - other.0).abs()
+    ///     }
+    /// }
+    ///
+    /// let mut ecosystem = Ecosystem::builder()
+    ///     .organisms((0..40).map(|i| Guess(f64::from(i) + 0.5)))
+    ///     .mutation_rate(0.5)
+    ///     .deterministic_crowding()
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// // Children only replace parents they beat, so the best is never lost
+    /// let mut best = ecosystem.fittest_individual().unwrap().fitness();
+    /// for _ in 0..50 {
+    ///     ecosystem.breed_next_generation()?;
+    ///     let fittest = ecosystem.fittest_individual().unwrap().fitness();
+    ///     assert!(fittest >= best);
+    ///     best = fittest;
+    /// }
+    /// # Ok::<(), ecosystem::EcosystemError>(())
+    /// ```
+    pub fn deterministic_crowding(mut self) -> Self {
+        self.crowding = Some(O::distance);
+        self
+    }
+}
+
+impl<O: Organism + Clone + Send + Sync> EcosystemBuilder<O> {
+    /// Sets the probability, within [0, 1], that each child is bred by
+    /// [recombining] its parents. Otherwise, the child starts out as a clone
+    /// of one parent, before being mutated as usual.
+    ///
+    /// By default, every child is bred by recombination.
+    ///
+    /// [recombining]: trait.Organism.html#tymethod.breed
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ecosystem::{Ecosystem, Organism};
+    /// # use rand::Rng;
+    /// #[derive(Clone)]
+    /// struct Guess(f64);
+    /// # impl Organism for Guess {
+    /// #     type Fitness = f64;
+    /// #     fn fitness(&self) -> f64 { 1.0 / (42.0 - self.0).abs() }
+    /// #     fn breed(&self, other: &Self, rng: &mut impl Rng) -> Self { Guess((self.0 + other.0) / 2.0) }
+    /// #     fn mutate(&mut self, rate: f64, rng: &mut impl Rng) {}
+    /// # }
+    ///
+    /// // Without crossover or mutation, every child is a copy of a parent
+    /// let mut ecosystem = Ecosystem::builder()
+    ///     .organisms((0..10).map(|i| Guess(f64::from(i) + 0.5)))
+    ///     .crossover_rate(0.0)
+    ///     .build()
+    ///     .unwrap();
+    /// ecosystem.breed_next_generation()?;
+    ///
+    /// assert!(ecosystem.organisms().all(|guess| guess.0.fract() == 0.5));
+    /// # Ok::<(), ecosystem::EcosystemError>(())
+    /// ```
+    pub fn crossover_rate(mut self, rate: f64) -> Self {
+        self.crossover = Some((rate, O::clone));
+        self
+    }
+
+    /// Sets whether children keep the improvements made by the [local
+    /// search], or only the fitness that they reach through it. Children
+    /// keep them by default.
+    ///
+    /// [local search]: #method.local_search
+    pub fn learning(mut self, learning: Learning) -> Self {
+        self.baldwinian = learning.clone_fn();
+        self
+    }
+}
+
+impl<O: Organism + Clone + PartialEq + Send + Sync> EcosystemBuilder<O> {
+    /// Enables an archive holding up to `capacity` of the fittest distinct
+    /// organisms seen over the course of the run.
+    ///
+    /// The archive is kept separately from the live population: after each
+    /// generation is bred, copies of its fittest organisms compete for a place
+    /// in the archive, as do any organisms that were replaced, so good
+    /// solutions are not lost to breeding. Organisms are distinct if they are
+    /// unequal, so a plateau of equally fit solutions can fill the archive,
+    /// while copies of one solution take up a single place.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ecosystem::{Ecosystem, Organism};
+    /// # use rand::Rng;
+    /// #[derive(Clone, PartialEq)]
+    /// struct PiApproximator {
+    ///     value: f64,
+    /// }
+    /// # impl Organism for PiApproximator {
+    /// #     type Fitness = f64;
+    /// #     fn fitness(&self) -> f64 {
+    /// #         1.0 / (std::f64::consts::PI - self.value).abs()
+    /// #     }
+    /// #     fn breed(&self, other: &Self, rng: &mut impl Rng) -> Self {
+    /// #         Self { value: (self.value + other.value) / 2.0 }
+    /// #     }
+    /// #     fn mutate(&mut self, rate: f64, rng: &mut impl Rng) {
+    /// #         self.value += rng.gen_range(-rate, rate);
+    /// #     }
+    /// # }
+    ///
+    /// let mut ecosystem = Ecosystem::builder()
+    ///     .organisms((0..10).map(|i| PiApproximator { value: f64::from(i) }))
+    ///     .mutation_rate(0.1)
+    ///     .archive(5)
+    ///     .build()
+    ///     .unwrap();
+    /// for _ in 0..20 {
+    ///     ecosystem.breed_next_generation()?;
+    /// }
+    ///
+    /// let archive = ecosystem.archived_individuals();
+    /// assert!(archive.len() > 1 && archive.len() <= 5);
+    /// for pair in archive.windows(2) {
+    ///     assert!(pair[0].fitness() >= pair[1].fitness());
+    ///     assert!(pair[0].organism() != pair[1].organism());
+    /// }
+    ///
+    /// // The archive holds the best organism found, even while it is alive
+    /// let fittest = ecosystem.fittest_individual().unwrap().fitness();
+    /// assert!(archive[0].fitness() >= fittest);
+    /// assert!(ecosystem.archive().next().unwrap() == archive[0].organism());
+    /// # Ok::<(), ecosystem::EcosystemError>(())
+    /// ```
+    pub fn archive(mut self, capacity: usize) -> Self {
+        self.archive_capacity = capacity;
+        self.archive_ops = Some((Individual::clone, O::eq));
+        self
+    }
+}
+
+impl<O: Organism + Hash + Eq + Send + Sync + 'static> EcosystemBuilder<O> {
+    /// Has every child that equals an organism in the population (or another
+    /// child) mutated again until it is distinct, as with
+    /// [`deduplicate_by_key`] with the organism itself as the key.
+    ///
+    /// [`deduplicate_by_key`]: #method.deduplicate_by_key
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ecosystem::{Ecosystem, Organism};
+    /// use std::collections::HashSet;
+    /// # use rand::Rng;
+    /// #[derive(Hash, PartialEq, Eq)]
+    /// struct Number(u32);
+    /// # impl Organism for Number {
+    /// #     type Fitness = u32;
+    /// #     fn fitness(&self) -> u32 { self.0 }
+    /// #     fn breed(&self, other: &Self, rng: &mut impl Rng) -> Self { Number(self.0.max(other.0)) }
+    /// #     fn mutate(&mut self, rate: f64, rng: &mut impl Rng) {
+    /// #         if rng.gen_bool(rate) {
+    /// #             self.0 = rng.gen_range(0, 1000);
+    /// #         }
+    /// #     }
+    /// # }
+    ///
+    /// // Breeding alone would soon fill the population with copies
+    /// let mut ecosystem = Ecosystem::builder()
+    ///     .organisms((0..20).map(Number))
+    ///     .mutation_rate(0.1)
+    ///     .deduplicate()
+    ///     .seed(0)
+    ///     .build()
+    ///     .unwrap();
+    /// for _ in 0..20 {
+    ///     ecosystem.breed_next_generation()?;
+    /// }
+    ///
+    /// let distinct: HashSet<_> = ecosystem.organisms().collect();
+    /// assert_eq!(distinct.len(), 20);
+    /// # Ok::<(), ecosystem::EcosystemError>(())
+    /// ```
+    ///
+    /// Organisms whose hashes collide are only duplicates if they are also
+    /// equal:
+    ///
+    /// ```rust
+    /// use ecosystem::{Ecosystem, Organism};
+    /// use rand::Rng;
+    /// use std::{
+    ///     hash::{Hash, Hasher},
+    ///     sync::atomic::{AtomicU32, Ordering},
+    /// };
+    ///
+    /// static NEXT: AtomicU32 = AtomicU32::new(100);
+    /// static MUTATIONS: AtomicU32 = AtomicU32::new(0);
+    ///
+    /// #[derive(PartialEq, Eq)]
+    /// struct Number(u32);
+    ///
+    /// // Every number hashes alike
+    /// impl Hash for Number {
+    ///     fn hash<H: Hasher>(&self, _state: &mut H) {}
+    /// }
+    ///
+    /// impl Organism for Number {
+    /// #   type Fitness = u32;
+    /// #   fn fitness(&self) -> u32 { self.0 }
+    ///     // Every child is a new number
+    ///     fn breed(&self, _other: &Self, _rng: &mut impl Rng) -> Self {
+    ///         Number(NEXT.fetch_add(1, Ordering::Relaxed))
+    ///     }
+    ///
+    ///     fn mutate(&mut self, _rate: f64, _rng: &mut impl Rng) {
+    ///         MUTATIONS.fetch_add(1, Ordering::Relaxed);
+    ///     }
+    /// }
+    ///
+    /// let mut ecosystem = Ecosystem::builder()
+    ///     .organisms((0..20).map(Number))
+    ///     .deduplicate()
+    ///     .build()
+    ///     .unwrap();
+    /// ecosystem.breed_next_generation()?;
+    ///
+    /// // Each child was mutated once as it was bred, and never again
+    /// assert_eq!(MUTATIONS.load(Ordering::Relaxed), 20);
+    /// # Ok::<(), ecosystem::EcosystemError>(())
+    /// ```
+    pub fn deduplicate(mut self) -> Self {
+        self.duplicate_key = Some(Box::new(crate::Equality));
+        self
+    }
+}
+
+/// An error arising from an invalid ecosystem configuration.
+#[derive(Clone, Debug, PartialEq)]
+pub enum BuildError {
+    /// No organisms were provided.
+    EmptyPopulation,
+    /// The mutation rate was outside of [0, 1].
+    InvalidMutationRate(f64),
+    /// The crossover rate was outside of [0, 1].
+    InvalidCrossoverRate(f64),
+    /// The fraction of the population replaced by immigrants was outside of
+    /// [0, 1].
+    InvalidImmigrantFraction(f64),
+    /// The generation gap was outside of (0, 1].
+    InvalidGenerationGap(f64),
+    /// The number of elite organisms was not smaller than the population size.
+    InvalidElitism {
+        /// The requested number of elite organisms.
+        elitism: usize,
+        /// The size of the population.
+        population_size: usize,
+    },
+    /// The number of children bred in each step of a steady-state population
+    /// was zero, or more than the number of organisms that can be replaced.
+    InvalidChildCount {
+        /// The requested number of children.
+        children: usize,
+        /// The number of organisms that are not elites.
+        replaceable: usize,
+    },
+    /// An evolution strategy scheme bred too few children in each
+    /// generation.
+    TooFewOffspring {
+        /// The requested number of children.
+        offspring: usize,
+        /// The minimum number of children required.
+        required: usize,
+    },
+    /// The population of a cellular model could not be arranged into rows
+    /// of the given width.
+    InvalidGridWidth {
+        /// The requested width of the grid.
+        width: usize,
+        /// The size of the population.
+        population_size: usize,
+    },
+    /// No random number generator was given with [`EcosystemBuilder::seed`]
+    /// or [`EcosystemBuilder::rng`], which is required without the `std`
+    /// feature, and before organisms are created with
+    /// [`EcosystemBuilder::generate`] or [`EcosystemBuilder::fill`].
+    ///
+    /// [`EcosystemBuilder::seed`]: struct.EcosystemBuilder.html#method.seed
+    /// [`EcosystemBuilder::rng`]: struct.EcosystemBuilder.html#method.rng
+    /// [`EcosystemBuilder::generate`]: struct.EcosystemBuilder.html#method.generate
+    /// [`EcosystemBuilder::fill`]: struct.EcosystemBuilder.html#method.fill
+    MissingRng,
+    /// The random number generator given with [`EcosystemBuilder::rng`]
+    /// failed to produce a seed, for the given reason.
+    ///
+    /// [`EcosystemBuilder::rng`]: struct.EcosystemBuilder.html#method.rng
+    RngSeed(String),
+    /// The thread pool requested with [`EcosystemBuilder::threads`] could
+    /// not be created, for the given reason. This is never returned without
+    /// the `parallel` feature.
+    ///
+    /// [`EcosystemBuilder::threads`]: struct.EcosystemBuilder.html#method.threads
+    ThreadPool(String),
+}
+
+impl fmt::Display for BuildError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BuildError::EmptyPopulation => write!(f, "there are no organisms in the ecosystem"),
+            BuildError::InvalidMutationRate(rate) => {
+                write!(f, "the mutation rate {} does not lie within [0, 1]", rate)
+            }
+            BuildError::InvalidCrossoverRate(rate) => {
+                write!(f, "the crossover rate {} does not lie within [0, 1]", rate)
+            }
+            BuildError::InvalidImmigrantFraction(fraction) => write!(
+                f,
+                "the fraction of immigrants {} does not lie within [0, 1]",
+                fraction
+            ),
+            BuildError::InvalidGenerationGap(gap) => {
+                write!(f, "the generation gap {} does not lie within (0, 1]", gap)
+            }
+            BuildError::InvalidElitism {
+                elitism,
+                population_size,
+            } => write!(
+                f,
+                "the elitism count {} is not smaller than the population size {}",
+                elitism, population_size
+            ),
+            BuildError::InvalidChildCount {
+                children,
+                replaceable,
+            } => write!(
+                f,
+                "the number of children {} does not lie within [1, {}]",
+                children, replaceable
+            ),
+            BuildError::TooFewOffspring {
+                offspring,
+                required,
+            } => write!(
+                f,
+                "{} offspring were requested, but at least {} are required",
+                offspring, required
+            ),
+            BuildError::InvalidGridWidth {
+                width,
+                population_size,
+            } => write!(
+                f,
+                "the population size {} is not a multiple of the grid width {}",
+                population_size, width
+            ),
+            BuildError::MissingRng => write!(
+                f,
+                "no random number generator was given, and none can be seeded without `std`"
+            ),
+            BuildError::RngSeed(reason) => write!(
+                f,
+                "the random number generator could not be seeded: {}",
+                reason
+            ),
+            BuildError::ThreadPool(reason) => {
+                write!(f, "the thread pool could not be created: {}", reason)
+            }
+        }
+    }
+}
+
+impl Error for BuildError {}
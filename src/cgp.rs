@@ -0,0 +1,435 @@
+//! Cartesian genetic programming (CGP), in which the organisms are programs
+//! represented as graphs of function nodes laid out on a fixed grid.
+//!
+//! Each node of a [`Program`] has a gene choosing its function from a
+//! [`Grid`]'s function set and a gene for each of its inputs, which connect
+//! it to a program input or to a node in one of the columns before it. The
+//! program's outputs are genes connecting to nodes or inputs in the same
+//! way. Many nodes are usually inactive, as no output depends on them; they
+//! drift freely under mutation, which lets the search explore without
+//! affecting fitness. Only the active nodes are evaluated.
+//!
+//! The functions are user-defined, in the same way as the [primitives] of
+//! tree-based genetic programming. CGP is conventionally evolved by mutation
+//! alone, with a (1+4) evolution strategy.
+//!
+//! ```rust
+//! use ecosystem::{
+//!     cgp::{Grid, Program},
+//!     gp::Primitive,
+//!     selection::Tournament,
+//!     Ecosystem, Organism, PopulationModel,
+//! };
+//! use rand::{Rng, SeedableRng};
+//! use rand_pcg::Pcg64;
+//! use std::sync::Arc;
+//!
+//! #[derive(Clone, Debug)]
+//! enum Gate {
+//!     And,
+//!     Or,
+//!     Nand,
+//! }
+//!
+//! impl Primitive for Gate {
+//!     fn arity(&self) -> usize {
+//!         2
+//!     }
+//! }
+//!
+//! // Evolve a circuit for exclusive or
+//! struct Circuit(Program<Gate>);
+//!
+//! impl Organism for Circuit {
+//!     type Fitness = u32;
+//!
+//!     fn fitness(&self) -> u32 {
+//!         let mut correct = 0;
+//!         for case in 0..4u32 {
+//!             let inputs = [case & 1 == 1, case & 2 == 2];
+//!             let outputs = self.0.evaluate(&inputs, |gate, inputs| match gate {
+//!                 Gate::And => inputs[0] && inputs[1],
+//!                 Gate::Or => inputs[0] || inputs[1],
+//!                 Gate::Nand => !(inputs[0] && inputs[1]),
+//!             });
+//!             correct += (outputs[0] == (inputs[0] != inputs[1])) as u32;
+//!         }
+//!         correct
+//!     }
+//!
+//!     fn breed(&self, _: &Self, _: &mut impl Rng) -> Self {
+//!         Circuit(self.0.clone())
+//!     }
+//!
+//!     fn mutate(&mut self, _: f64, rng: &mut impl Rng) {
+//!         self.0.single_active_mutation(rng);
+//!     }
+//! }
+//!
+//! // Two inputs, one output, and a single row of 30 nodes
+//! let grid = Arc::new(Grid::new(2, 1, 1, 30, vec![Gate::And, Gate::Or, Gate::Nand]));
+//! let mut rng = Pcg64::seed_from_u64(0);
+//! let mut ecosystem = Ecosystem::builder()
+//!     .organisms(vec![Circuit(Program::random(&grid, &mut rng))])
+//!     .population_model(PopulationModel::MuPlusLambda { offspring: 4 })
+//!     .selection(Tournament { size: 1 })
+//!     .seed(0)
+//!     .build()
+//!     .unwrap();
+//! while ecosystem.fittest().unwrap().fitness() < 4 {
+//!     ecosystem.breed_next_generation()?;
+//! }
+//!
+//! // Only the nodes that the output depends on are active
+//! assert!(ecosystem.fittest().unwrap().0.active_nodes().len() < 30);
+//! # Ok::<(), ecosystem::EcosystemError>(())
+//! ```
+//!
+//! [`Program`]: struct.Program.html
+//! [`Grid`]: struct.Grid.html
+//! [primitives]: ../gp/trait.Primitive.html
+use crate::gp::Primitive;
+use rand::Rng;
+use std::{fmt, sync::Arc};
+
+/// The layout of the nodes of CGP programs, and the functions they can
+/// compute.
+///
+/// Programs keep a shared reference to their grid, so that mutation can draw
+/// new functions and connections from it.
+pub struct Grid<F> {
+    inputs: usize,
+    outputs: usize,
+    rows: usize,
+    columns: usize,
+    levels_back: usize,
+    functions: Vec<F>,
+    max_arity: usize,
+}
+
+impl<F: Primitive> Grid<F> {
+    /// Creates a grid of `rows` by `columns` nodes, for programs with the
+    /// given numbers of inputs and outputs, whose nodes compute the given
+    /// functions.
+    ///
+    /// Nodes can connect to a node in any earlier column; this can be
+    /// restricted with [`levels_back`].
+    ///
+    /// [`levels_back`]: #method.levels_back
+    ///
+    /// # Panics
+    ///
+    /// Panics if there are no functions, or no inputs, as nodes would then
+    /// have nothing to compute.
+    pub fn new(
+        inputs: usize,
+        outputs: usize,
+        rows: usize,
+        columns: usize,
+        functions: Vec<F>,
+    ) -> Self {
+        assert!(!functions.is_empty(), "there must be at least one function");
+        assert!(inputs > 0, "there must be at least one input");
+        let max_arity = functions.iter().map(F::arity).max().unwrap();
+        Self {
+            inputs,
+            outputs,
+            rows,
+            columns,
+            levels_back: columns,
+            functions,
+            max_arity,
+        }
+    }
+
+    /// Limits the connections of each node to nodes in at most the given
+    /// number of columns before it. The program inputs can always be
+    /// connected to.
+    pub fn levels_back(mut self, levels: usize) -> Self {
+        self.levels_back = levels;
+        self
+    }
+
+    /// Returns the number of program inputs.
+    pub fn inputs(&self) -> usize {
+        self.inputs
+    }
+
+    /// Returns the number of program outputs.
+    pub fn outputs(&self) -> usize {
+        self.outputs
+    }
+
+    /// Returns the number of nodes.
+    pub fn nodes(&self) -> usize {
+        self.rows * self.columns
+    }
+
+    /// Returns the functions that nodes can compute.
+    pub fn functions(&self) -> &[F] {
+        &self.functions
+    }
+
+    /// Chooses a random address that the given node can connect to, or that
+    /// an output can connect to if `node` is `None`. Addresses below the
+    /// number of inputs refer to the program inputs, and the rest to nodes.
+    fn random_source(&self, node: Option<usize>, rng: &mut impl Rng) -> usize {
+        let (first, end) = match node {
+            Some(node) => {
+                let column = node / self.rows;
+                (
+                    column.saturating_sub(self.levels_back) * self.rows,
+                    column * self.rows,
+                )
+            }
+            None => (0, self.nodes()),
+        };
+        let choice = rng.gen_range(0, self.inputs + end - first);
+        if choice < self.inputs {
+            choice
+        } else {
+            self.inputs + first + (choice - self.inputs)
+        }
+    }
+}
+
+/// A CGP program: a function gene and connection genes for each node of a
+/// [`Grid`], and a connection gene for each output.
+///
+/// [`Grid`]: struct.Grid.html
+pub struct Program<F> {
+    functions: Vec<usize>,
+    connections: Vec<usize>,
+    outputs: Vec<usize>,
+    grid: Arc<Grid<F>>,
+}
+
+impl<F: Primitive> Program<F> {
+    /// Creates a program with random genes.
+    pub fn random(grid: &Arc<Grid<F>>, rng: &mut impl Rng) -> Self {
+        let nodes = grid.nodes();
+        Self {
+            functions: (0..nodes)
+                .map(|_| rng.gen_range(0, grid.functions.len()))
+                .collect(),
+            connections: (0..nodes * grid.max_arity)
+                .map(|gene| grid.random_source(Some(gene / grid.max_arity), rng))
+                .collect(),
+            outputs: (0..grid.outputs)
+                .map(|_| grid.random_source(None, rng))
+                .collect(),
+            grid: Arc::clone(grid),
+        }
+    }
+
+    /// Returns the grid that the program is laid out on.
+    pub fn grid(&self) -> &Arc<Grid<F>> {
+        &self.grid
+    }
+
+    /// Returns the function computed by the given node.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no such node.
+    pub fn function(&self, node: usize) -> &F {
+        &self.grid.functions[self.functions[node]]
+    }
+
+    /// Returns the addresses that the given node takes its inputs from, one
+    /// for each input of its function. Addresses below the number of program
+    /// inputs refer to those inputs, and the rest to nodes, offset by the
+    /// number of inputs.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no such node.
+    pub fn sources(&self, node: usize) -> &[usize] {
+        let start = node * self.grid.max_arity;
+        &self.connections[start..start + self.function(node).arity()]
+    }
+
+    /// Returns the addresses that the outputs are taken from.
+    pub fn outputs(&self) -> &[usize] {
+        &self.outputs
+    }
+
+    /// Returns the nodes that the outputs depend on, in ascending order.
+    pub fn active_nodes(&self) -> Vec<usize> {
+        self.active()
+            .iter()
+            .enumerate()
+            .filter(|&(_, &active)| active)
+            .map(|(node, _)| node)
+            .collect()
+    }
+
+    /// Evaluates the program on the given inputs, calling `apply` with each
+    /// active node's function and the values of its inputs to find its
+    /// value, and returning the values of the outputs.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of inputs does not match the grid.
+    pub fn evaluate<V: Clone>(&self, inputs: &[V], mut apply: impl FnMut(&F, &[V]) -> V) -> Vec<V> {
+        assert_eq!(
+            inputs.len(),
+            self.grid.inputs,
+            "the program takes {} inputs",
+            self.grid.inputs
+        );
+        let mut values: Vec<Option<V>> = inputs.iter().cloned().map(Some).collect();
+        values.resize(inputs.len() + self.grid.nodes(), None);
+
+        // Nodes only connect to earlier nodes, so each active node's inputs
+        // are ready by the time it is reached
+        let mut arguments = Vec::with_capacity(self.grid.max_arity);
+        for node in self.active_nodes() {
+            arguments.clear();
+            arguments.extend(
+                self.sources(node)
+                    .iter()
+                    .map(|&source| values[source].clone().unwrap()),
+            );
+            values[inputs.len() + node] = Some(apply(self.function(node), &arguments));
+        }
+        self.outputs
+            .iter()
+            .map(|&source| values[source].clone().unwrap())
+            .collect()
+    }
+
+    /// Point mutation, which replaces each gene, independently with a
+    /// probability of `rate`, with a random valid value.
+    pub fn point_mutation(&mut self, rate: f64, rng: &mut impl Rng) {
+        let rate = rate.clamp(0.0, 1.0);
+        for gene in 0..self.gene_count() {
+            if rng.gen_bool(rate) {
+                self.mutate_gene(gene, rng);
+            }
+        }
+    }
+
+    /// Mutates randomly chosen genes until one that the outputs depend on
+    /// has changed, so that every mutation has an effect on the program's
+    /// behavior, but no more than necessary. Changes to inactive genes along
+    /// the way are kept.
+    pub fn single_active_mutation(&mut self, rng: &mut impl Rng) {
+        // Gives up eventually in case no active gene can take another value
+        for _ in 0..self.gene_count().saturating_mul(10) {
+            let gene = rng.gen_range(0, self.gene_count());
+            let active = match self.gene_node(gene) {
+                Some(node) => self.active()[node] && self.is_expressed(gene),
+                None => true,
+            };
+            if self.mutate_gene(gene, rng) && active {
+                return;
+            }
+        }
+    }
+
+    /// Returns which nodes the outputs depend on.
+    fn active(&self) -> Vec<bool> {
+        let inputs = self.grid.inputs;
+        let mut active = vec![false; self.grid.nodes()];
+        let mut pending: Vec<_> = self.outputs.clone();
+        while let Some(source) = pending.pop() {
+            if source < inputs || active[source - inputs] {
+                continue;
+            }
+            let node = source - inputs;
+            active[node] = true;
+            pending.extend_from_slice(self.sources(node));
+        }
+        active
+    }
+
+    /// Returns the total number of genes: a function gene and connection
+    /// genes for each node, followed by the output genes.
+    fn gene_count(&self) -> usize {
+        self.functions.len() + self.connections.len() + self.outputs.len()
+    }
+
+    /// Returns the node that the gene belongs to, or `None` for an output
+    /// gene.
+    fn gene_node(&self, gene: usize) -> Option<usize> {
+        let nodes = self.functions.len();
+        if gene < nodes {
+            Some(gene)
+        } else if gene < nodes + self.connections.len() {
+            Some((gene - nodes) / self.grid.max_arity)
+        } else {
+            None
+        }
+    }
+
+    /// Returns whether a node's gene affects it, which is not the case for
+    /// connections beyond the arity of its function.
+    fn is_expressed(&self, gene: usize) -> bool {
+        let nodes = self.functions.len();
+        if gene < nodes {
+            return true;
+        }
+        let connection = gene - nodes;
+        let node = connection / self.grid.max_arity;
+        connection % self.grid.max_arity < self.function(node).arity()
+    }
+
+    /// Replaces the gene with a random valid value, returning whether it
+    /// changed.
+    fn mutate_gene(&mut self, gene: usize, rng: &mut impl Rng) -> bool {
+        let grid = &self.grid;
+        let nodes = self.functions.len();
+        let (slot, value) = if gene < nodes {
+            (
+                &mut self.functions[gene],
+                rng.gen_range(0, grid.functions.len()),
+            )
+        } else if gene < nodes + self.connections.len() {
+            let connection = gene - nodes;
+            let node = connection / grid.max_arity;
+            (
+                &mut self.connections[connection],
+                grid.random_source(Some(node), rng),
+            )
+        } else {
+            (
+                &mut self.outputs[gene - nodes - self.connections.len()],
+                grid.random_source(None, rng),
+            )
+        };
+        let changed = *slot != value;
+        *slot = value;
+        changed
+    }
+}
+
+impl<F> Clone for Program<F> {
+    fn clone(&self) -> Self {
+        Self {
+            functions: self.functions.clone(),
+            connections: self.connections.clone(),
+            outputs: self.outputs.clone(),
+            grid: Arc::clone(&self.grid),
+        }
+    }
+}
+
+impl<F> fmt::Debug for Program<F> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Program")
+            .field("functions", &self.functions)
+            .field("connections", &self.connections)
+            .field("outputs", &self.outputs)
+            .finish()
+    }
+}
+
+/// Programs are equal if they have the same genes, whatever their grids.
+impl<F> PartialEq for Program<F> {
+    fn eq(&self, other: &Self) -> bool {
+        self.functions == other.functions
+            && self.connections == other.connections
+            && self.outputs == other.outputs
+    }
+}
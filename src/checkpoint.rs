@@ -0,0 +1,510 @@
+use crate::{
+    history::History, mutation::Hypermutation, parallel::Parallelism,
+    selection::FitnessProportionate, stagnation::StagnationMonitor, Ecosystem, Individual,
+    Objective, Organism, PopulationModel, Setting, StagnationResponse,
+};
+use portable_atomic::AtomicU64;
+use rand_pcg::Pcg64;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::{
+    error::Error,
+    ffi::OsString,
+    fmt,
+    fs::{self, File},
+    io::{self, BufReader, BufWriter, Write},
+    path::{Path, PathBuf},
+    sync::atomic,
+};
+
+/// The serialised form of an ecosystem, borrowing from the original.
+#[derive(Serialize)]
+struct StateRef<'a, O, F> {
+    population: Vec<(&'a O, Option<&'a F>, u32)>,
+    generation: u32,
+    archive: Vec<(&'a O, Option<&'a F>, u32)>,
+    archive_capacity: usize,
+    evaluations: u64,
+    mutation_rate: f64,
+    hypermutation: Option<Hypermutation>,
+    rng: &'a Pcg64,
+    elitism: usize,
+    generation_gap: f64,
+    objective: Objective,
+    history: Option<&'a History>,
+    model: PopulationModel,
+    stagnation: Option<SavedStagnation<&'a F>>,
+    min_chunk_len: usize,
+    unrestored: Vec<Setting>,
+}
+
+/// The serialised form of an ecosystem, as it is read back.
+#[derive(Deserialize)]
+struct State<O, F> {
+    population: Vec<(O, Option<F>, u32)>,
+    generation: u32,
+    archive: Vec<(O, Option<F>, u32)>,
+    archive_capacity: usize,
+    evaluations: u64,
+    mutation_rate: f64,
+    hypermutation: Option<Hypermutation>,
+    rng: Pcg64,
+    elitism: usize,
+    generation_gap: f64,
+    objective: Objective,
+    history: Option<History>,
+    model: PopulationModel,
+    stagnation: Option<SavedStagnation<F>>,
+    min_chunk_len: usize,
+    unrestored: Vec<Setting>,
+}
+
+/// The serialised form of a stagnation monitor whose response can be saved.
+#[derive(Serialize, Deserialize)]
+struct SavedStagnation<F> {
+    generations: u32,
+    response: SavedResponse,
+    best: Option<F>,
+    last_improvement: u32,
+    stalled: bool,
+}
+
+/// The stagnation responses that can be saved, as they hold no functions.
+#[derive(Serialize, Deserialize)]
+enum SavedResponse {
+    RaiseMutation(f64),
+    Hypermutate(Hypermutation),
+    Stop,
+}
+
+fn borrow_stagnation<O: Organism>(
+    monitor: &StagnationMonitor<O>,
+) -> Option<SavedStagnation<&O::Fitness>> {
+    let response = match monitor.response {
+        StagnationResponse::Restart(_) => return None,
+        StagnationResponse::RaiseMutation(factor) => SavedResponse::RaiseMutation(factor),
+        StagnationResponse::Hypermutate(hypermutation) => SavedResponse::Hypermutate(hypermutation),
+        StagnationResponse::Stop => SavedResponse::Stop,
+    };
+    Some(SavedStagnation {
+        generations: monitor.generations,
+        response,
+        best: monitor.best.as_ref(),
+        last_improvement: monitor.last_improvement,
+        stalled: monitor.stalled,
+    })
+}
+
+fn restore_stagnation<O: Organism>(saved: SavedStagnation<O::Fitness>) -> StagnationMonitor<O> {
+    let response = match saved.response {
+        SavedResponse::RaiseMutation(factor) => StagnationResponse::RaiseMutation(factor),
+        SavedResponse::Hypermutate(hypermutation) => StagnationResponse::Hypermutate(hypermutation),
+        SavedResponse::Stop => StagnationResponse::Stop,
+    };
+    let mut monitor = StagnationMonitor::new(saved.generations, response);
+    monitor.best = saved.best;
+    monitor.last_improvement = saved.last_improvement;
+    monitor.stalled = saved.stalled;
+    monitor
+}
+
+fn borrow_individuals<O: Organism>(
+    individuals: &[Individual<O>],
+) -> Vec<(&O, Option<&O::Fitness>, u32)> {
+    individuals
+        .iter()
+        .map(|individual| {
+            (
+                individual.organism(),
+                individual.fitness.get(),
+                individual.age(),
+            )
+        })
+        .collect()
+}
+
+fn restore_individuals<O: Organism>(
+    individuals: Vec<(O, Option<O::Fitness>, u32)>,
+) -> Vec<Individual<O>> {
+    individuals
+        .into_iter()
+        .map(|(organism, fitness, age)| {
+            let mut individual = Individual::new(organism);
+            individual.age = age;
+            if let Some(fitness) = fitness {
+                let _ = individual.fitness.set(fitness);
+            }
+            individual
+        })
+        .collect()
+}
+
+/// Ecosystems serialise everything needed to continue evolving exactly where
+/// they left off, including cached fitness values, the ages of individuals
+/// and the state of the random number generator. The exceptions are the
+/// settings made up of functions or trait objects, which cannot be
+/// serialised in general; which of them were configured is saved instead,
+/// as described on the `Deserialize` implementation. The genealogy is not
+/// saved either, as individuals' identifiers are not kept.
+impl<O> Serialize for Ecosystem<O>
+where
+    O: Organism + Serialize,
+    O::Fitness: Serialize,
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        StateRef {
+            population: borrow_individuals(&self.population),
+            generation: self.generation,
+            archive: borrow_individuals(&self.archive),
+            archive_capacity: self.archive_capacity,
+            evaluations: self.evaluations.load(atomic::Ordering::Relaxed),
+            mutation_rate: self.mutation_rate,
+            hypermutation: self.hypermutation,
+            rng: &self.rng,
+            elitism: self.elitism,
+            generation_gap: self.generation_gap,
+            objective: self.objective,
+            history: self.history.as_ref(),
+            model: self.model,
+            stagnation: self.stagnation.as_ref().and_then(borrow_stagnation),
+            min_chunk_len: self.parallelism.min_len(),
+            unrestored: self.lost_settings(),
+        }
+        .serialize(serializer)
+    }
+}
+
+/// Deserialised ecosystems lose the settings that cannot be serialised. Each
+/// one that was configured is listed, as a [`Setting`], by
+/// [`Ecosystem::unrestored_settings`], and the ecosystem refuses to breed
+/// until each is restored, by the method named in its documentation, or
+/// they are discarded with [`Ecosystem::discard_unrestored_settings`].
+/// Discarded settings revert to their defaults: fitness-proportionate
+/// selection, no observers, and so on. The genealogy is not tracked either.
+///
+/// [`Setting`]: enum.Setting.html
+/// [`Ecosystem::unrestored_settings`]: struct.Ecosystem.html#method.unrestored_settings
+/// [`Ecosystem::discard_unrestored_settings`]: struct.Ecosystem.html#method.discard_unrestored_settings
+impl<'de, O> Deserialize<'de> for Ecosystem<O>
+where
+    O: Organism + Deserialize<'de>,
+    O::Fitness: Deserialize<'de>,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let state = State::<O, O::Fitness>::deserialize(deserializer)?;
+        Ok(Ecosystem {
+            population: restore_individuals(state.population),
+            generation: state.generation,
+            archive: restore_individuals(state.archive),
+            archive_capacity: state.archive_capacity,
+            archive_ops: None,
+            evaluations: AtomicU64::new(state.evaluations),
+            mutation_rate: state.mutation_rate,
+            rng: state.rng,
+            selection: Box::new(FitnessProportionate),
+            replacement: None,
+            elitism: state.elitism,
+            generation_gap: state.generation_gap,
+            objective: state.objective,
+            history: state.history,
+            observers: Vec::new(),
+            progress: None,
+            model: state.model,
+            repair: None,
+            crowding: None,
+            crossover: None,
+            variance_scaling: None,
+            gene_variances: Vec::new(),
+            duplicate_key: None,
+            immigration: None,
+            stagnation: state.stagnation.map(restore_stagnation),
+            mutation_control: None,
+            population_control: None,
+            local_search: None,
+            baldwinian: None,
+            surrogate: None,
+            batch_evaluator: None,
+            hypermutation: state.hypermutation,
+            genealogy: None,
+            parallelism: Parallelism::with_min_len(state.min_chunk_len),
+            custom_selection: false,
+            unrestored: state.unrestored,
+        })
+    }
+}
+
+impl<O: Organism> Ecosystem<O> {
+    /// Returns the settings that were configured but lost when the ecosystem
+    /// was resumed from a checkpoint, and have been neither restored nor
+    /// discarded since. The ecosystem will not breed until there are none.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ecosystem::{selection::Tournament, Ecosystem, EcosystemError, Organism, Setting};
+    /// use rand::Rng;
+    /// use serde::{Deserialize, Serialize};
+    ///
+    /// #[derive(Serialize, Deserialize)]
+    /// struct Guess(f64);
+    /// # impl Organism for Guess {
+    /// #     type Fitness = f64;
+    /// #     fn fitness(&self) -> f64 { 1.0 / (42.0 - self.0).abs() }
+    /// #     fn breed(&self, other: &Self, rng: &mut impl Rng) -> Self {
+    /// #         Guess(if rng.gen() { self.0 } else { other.0 })
+    /// #     }
+    /// #     fn mutate(&mut self, rate: f64, rng: &mut impl Rng) {
+    /// #         self.0 += rng.gen_range(-rate, rate);
+    /// #     }
+    /// # }
+    ///
+    /// let path = std::env::temp_dir().join("ecosystem-unrestored-example.bin");
+    ///
+    /// let ecosystem = Ecosystem::builder()
+    ///     .organisms((0..50).map(|i| Guess(f64::from(i) + 0.5)))
+    ///     .selection(Tournament { size: 3 })
+    ///     .immigrants(0.1, |rng| Guess(rng.gen_range(0.0, 100.0)))
+    ///     .build()
+    ///     .unwrap();
+    /// ecosystem.save_checkpoint(&path)?;
+    ///
+    /// // The resumed ecosystem refuses to breed without its settings
+    /// let mut resumed = Ecosystem::<Guess>::resume(&path)?;
+    /// assert_eq!(
+    ///     resumed.unrestored_settings(),
+    ///     &[Setting::Selection, Setting::Immigrants]
+    /// );
+    /// assert_eq!(
+    ///     resumed.breed_next_generation(),
+    ///     Err(EcosystemError::UnrestoredSettings(vec![
+    ///         Setting::Selection,
+    ///         Setting::Immigrants,
+    ///     ]))
+    /// );
+    ///
+    /// // Restoring one and discarding the rest lets it carry on
+    /// resumed.set_selection(Tournament { size: 3 });
+    /// assert_eq!(resumed.unrestored_settings(), &[Setting::Immigrants]);
+    /// resumed.discard_unrestored_settings();
+    /// resumed.breed_next_generation()?;
+    /// # std::fs::remove_file(path)?;
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn unrestored_settings(&self) -> &[Setting] {
+        &self.unrestored
+    }
+
+    /// Gives up on restoring the settings lost when the ecosystem was
+    /// resumed from a checkpoint, leaving them at their defaults, so that it
+    /// can breed without them.
+    pub fn discard_unrestored_settings(&mut self) {
+        self.unrestored.clear();
+    }
+
+    /// Returns the settings that a checkpoint of the ecosystem loses: those
+    /// that are configured but cannot be serialised, along with any already
+    /// lost and not yet restored.
+    fn lost_settings(&self) -> Vec<Setting> {
+        #[cfg(feature = "parallel")]
+        let thread_pool = self.parallelism.pool.is_some();
+        #[cfg(not(feature = "parallel"))]
+        let thread_pool = false;
+        let restarts = self
+            .stagnation
+            .as_ref()
+            .is_some_and(|monitor| matches!(monitor.response, StagnationResponse::Restart(_)));
+        let configured = [
+            (Setting::Selection, self.custom_selection),
+            (Setting::Replacement, self.replacement.is_some()),
+            (Setting::Archive, self.archive_ops.is_some()),
+            (Setting::Observers, !self.observers.is_empty()),
+            (Setting::ProgressReporter, self.progress.is_some()),
+            (Setting::MutationControl, self.mutation_control.is_some()),
+            (
+                Setting::PopulationControl,
+                self.population_control.is_some(),
+            ),
+            (Setting::CrossoverRate, self.crossover.is_some()),
+            (
+                Setting::VarianceScaledMutation,
+                self.variance_scaling.is_some(),
+            ),
+            (Setting::Deduplication, self.duplicate_key.is_some()),
+            (Setting::Immigrants, self.immigration.is_some()),
+            (Setting::StagnationResponse, restarts),
+            (Setting::Repair, self.repair.is_some()),
+            (Setting::DeterministicCrowding, self.crowding.is_some()),
+            (Setting::LocalSearch, self.local_search.is_some()),
+            (Setting::Learning, self.baldwinian.is_some()),
+            (Setting::Surrogate, self.surrogate.is_some()),
+            (Setting::BatchEvaluator, self.batch_evaluator.is_some()),
+            (Setting::ThreadPool, thread_pool),
+        ];
+        let mut lost = self.unrestored.clone();
+        for &(setting, configured) in &configured {
+            if configured && !lost.contains(&setting) {
+                lost.push(setting);
+            }
+        }
+        lost
+    }
+}
+
+impl<O> Ecosystem<O>
+where
+    O: Organism + Serialize + Send + Sync,
+    O::Fitness: Serialize,
+{
+    /// Saves the state of the ecosystem to the file at the given path, from
+    /// which it can later be restored with [`resume`].
+    ///
+    /// The checkpoint is written to a temporary file alongside the target,
+    /// which then replaces it, so an interrupted save never leaves a
+    /// truncated checkpoint behind.
+    ///
+    /// [`resume`]: #method.resume
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ecosystem::{Ecosystem, Organism};
+    /// use rand::Rng;
+    /// use serde::{Deserialize, Serialize};
+    ///
+    /// #[derive(Serialize, Deserialize)]
+    /// struct Guess(f64);
+    /// # impl Organism for Guess {
+    /// #     type Fitness = f64;
+    /// #     fn fitness(&self) -> f64 { 1.0 / (42.0 - self.0).abs() }
+    /// #     fn breed(&self, other: &Self, rng: &mut impl Rng) -> Self {
+    /// #         Guess(if rng.gen() { self.0 } else { other.0 })
+    /// #     }
+    /// #     fn mutate(&mut self, rate: f64, rng: &mut impl Rng) {
+    /// #         self.0 += rng.gen_range(-rate, rate);
+    /// #     }
+    /// # }
+    ///
+    /// let path = std::env::temp_dir().join("ecosystem-checkpoint-example.bin");
+    ///
+    /// let mut ecosystem = Ecosystem::builder()
+    ///     .organisms((0..50).map(|i| Guess(f64::from(i) + 0.5)))
+    ///     .mutation_rate(0.5)
+    ///     .build()
+    ///     .unwrap();
+    /// for _ in 0..5 {
+    ///     ecosystem.breed_next_generation()?;
+    /// }
+    /// ecosystem.save_checkpoint(&path)?;
+    ///
+    /// let mut resumed = Ecosystem::<Guess>::resume(&path)?;
+    /// assert_eq!(resumed.generation(), 5);
+    ///
+    /// // The resumed run continues exactly as the original does
+    /// for _ in 0..5 {
+    ///     ecosystem.breed_next_generation()?;
+    ///     resumed.breed_next_generation()?;
+    /// }
+    /// let values = |ecosystem: &Ecosystem<Guess>| {
+    ///     ecosystem.organisms().map(|guess| guess.0).collect::<Vec<_>>()
+    /// };
+    /// assert_eq!(values(&ecosystem), values(&resumed));
+    /// # std::fs::remove_file(path)?;
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the file cannot be written, or if an organism
+    /// fails to serialise.
+    pub fn save_checkpoint<P: AsRef<Path>>(&self, path: P) -> Result<(), CheckpointError> {
+        let path = path.as_ref();
+        let temporary = temporary_path(path);
+
+        let mut writer = BufWriter::new(File::create(&temporary)?);
+        bincode::serialize_into(&mut writer, self)?;
+        writer.flush()?;
+        writer
+            .into_inner()
+            .map_err(io::IntoInnerError::into_error)?
+            .sync_all()?;
+
+        fs::rename(&temporary, path)?;
+        Ok(())
+    }
+}
+
+impl<O> Ecosystem<O>
+where
+    O: Organism + for<'de> Deserialize<'de> + Send + Sync,
+    O::Fitness: for<'de> Deserialize<'de>,
+{
+    /// Restores an ecosystem from a checkpoint saved with
+    /// [`save_checkpoint`].
+    ///
+    /// Settings that could not be saved, such as a selection strategy other
+    /// than fitness-proportionate selection, are listed by
+    /// [`unrestored_settings`], and must be restored or discarded before
+    /// the ecosystem breeds again.
+    ///
+    /// [`save_checkpoint`]: #method.save_checkpoint
+    /// [`unrestored_settings`]: #method.unrestored_settings
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the file cannot be read, or if it does not
+    /// hold a valid checkpoint.
+    pub fn resume<P: AsRef<Path>>(path: P) -> Result<Self, CheckpointError> {
+        let reader = BufReader::new(File::open(path)?);
+        Ok(bincode::deserialize_from(reader)?)
+    }
+}
+
+/// Returns the path of the temporary file that a checkpoint is first written
+/// to.
+fn temporary_path(path: &Path) -> PathBuf {
+    let mut temporary = OsString::from(path.as_os_str());
+    temporary.push(".tmp");
+    PathBuf::from(temporary)
+}
+
+/// An error arising from saving or resuming a checkpoint.
+#[derive(Debug)]
+pub enum CheckpointError {
+    /// The checkpoint file could not be read or written.
+    Io(io::Error),
+    /// The ecosystem could not be encoded, or the file did not hold a valid
+    /// checkpoint.
+    Format(Box<dyn Error + Send + Sync>),
+}
+
+impl fmt::Display for CheckpointError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CheckpointError::Io(error) => write!(f, "failed to access the checkpoint: {}", error),
+            CheckpointError::Format(error) => write!(f, "invalid checkpoint: {}", error),
+        }
+    }
+}
+
+impl Error for CheckpointError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            CheckpointError::Io(error) => Some(error),
+            CheckpointError::Format(error) => Some(error.as_ref()),
+        }
+    }
+}
+
+impl From<io::Error> for CheckpointError {
+    fn from(error: io::Error) -> Self {
+        CheckpointError::Io(error)
+    }
+}
+
+impl From<bincode::Error> for CheckpointError {
+    fn from(error: bincode::Error) -> Self {
+        match *error {
+            bincode::ErrorKind::Io(error) => CheckpointError::Io(error),
+            error => CheckpointError::Format(Box::new(error)),
+        }
+    }
+}
@@ -0,0 +1,494 @@
+use crate::parallel::*;
+use crate::{
+    genome::standard_normal,
+    history::History,
+    termination::{RunSummary, Search, Termination},
+    Objective, RealFunction, Statistics,
+};
+use rand::SeedableRng;
+use rand_pcg::Pcg64;
+use std::cmp::Ordering;
+
+/// The Covariance Matrix Adaptation Evolution Strategy (CMA-ES), which
+/// optimises functions of real-valued vectors by sampling each generation
+/// from a multivariate normal distribution, and adapting the distribution
+/// towards the best samples.
+///
+/// The mean of the distribution moves to a weighted average of the best half
+/// of each generation. The covariance matrix learns the shape of the
+/// landscape (including correlations between variables, which a GA's
+/// mutation operators cannot follow), and the step size grows or shrinks
+/// depending on how far the mean has travelled recently. As a result, CMA-ES
+/// needs no tuning beyond an initial mean and step size, and on smooth
+/// real-valued problems it usually needs far fewer evaluations than a
+/// genetic algorithm.
+///
+/// The defaults follow Hansen's tutorial: each generation holds
+/// `4 + floor(3 ln n)` samples for `n` variables, fitness values are
+/// maximised (as everywhere else in this crate), and the random number
+/// generator is seeded from the operating system. Samples are evaluated in
+/// parallel.
+///
+/// A run can be ended by the same [termination conditions] as an ecosystem's,
+/// and the fitness values of each generation can be summarised and recorded
+/// in the same way.
+///
+/// [termination conditions]: termination/index.html
+///
+/// # Examples
+///
+/// ```rust
+/// use ecosystem::{
+///     benchmarks::Function,
+///     termination::{MaxGenerations, StopReason, TargetFitness},
+///     Cmaes, Objective,
+/// };
+///
+/// let function = Function::Rosenbrock;
+/// let mut cmaes = Cmaes::new(vec![0.0; 10], 0.5, move |point| function.evaluate(point))
+///     .objective(Objective::Minimize)
+///     .record_history()
+///     .seed(0);
+/// let summary = cmaes.run(TargetFitness(1e-10).or(MaxGenerations(2000)));
+///
+/// assert_eq!(summary.reason, StopReason::TargetFitness);
+/// assert!(cmaes.best().unwrap().iter().all(|x| (x - 1.0).abs() < 1e-4));
+/// assert_eq!(cmaes.history().unwrap().records().len() as u32, cmaes.generation());
+/// ```
+pub struct Cmaes {
+    fitness: RealFunction,
+    objective: Objective,
+    rng: Pcg64,
+    population_size: usize,
+    weights: Vec<f64>,
+    mean: Vec<f64>,
+    step_size: f64,
+    // The evolution paths of the covariance matrix and the step size
+    path_c: Vec<f64>,
+    path_sigma: Vec<f64>,
+    // The covariance matrix, and its eigendecomposition `B D^2 B^T`, all
+    // stored row by row
+    covariance: Vec<f64>,
+    basis: Vec<f64>,
+    scales: Vec<f64>,
+    inverse_sqrt: Vec<f64>,
+    decomposed_at: u64,
+    best: Option<(Vec<f64>, f64)>,
+    statistics: Option<Statistics>,
+    history: Option<History>,
+    generation: u32,
+    evaluations: u64,
+}
+
+impl Cmaes {
+    /// Creates a search for the best input to `fitness`, starting from a
+    /// normal distribution around `mean`, with a standard deviation of
+    /// `step_size` in every direction.
+    ///
+    /// The step size should be about a third of the distance to the optimum,
+    /// or of the width of the region in which it is expected to lie.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `mean` is empty, or if `step_size` is not positive.
+    pub fn new<F>(mean: Vec<f64>, step_size: f64, fitness: F) -> Self
+    where
+        F: Fn(&[f64]) -> f64 + Send + Sync + 'static,
+    {
+        assert!(!mean.is_empty(), "there must be at least one variable");
+        assert!(step_size > 0.0, "the step size must be positive");
+        let n = mean.len();
+        let population_size = 4 + (3.0 * (n as f64).ln()) as usize;
+        let mut identity = vec![0.0; n * n];
+        for i in 0..n {
+            identity[i * n + i] = 1.0;
+        }
+        Self {
+            fitness: Box::new(fitness),
+            objective: Objective::default(),
+            rng: Pcg64::from_entropy(),
+            population_size,
+            weights: recombination_weights(population_size),
+            mean,
+            step_size,
+            path_c: vec![0.0; n],
+            path_sigma: vec![0.0; n],
+            covariance: identity.clone(),
+            basis: identity.clone(),
+            scales: vec![1.0; n],
+            inverse_sqrt: identity,
+            decomposed_at: 0,
+            best: None,
+            statistics: None,
+            history: None,
+            generation: 0,
+            evaluations: 0,
+        }
+    }
+
+    /// Sets the direction in which fitness values are optimised.
+    pub fn objective(mut self, objective: Objective) -> Self {
+        self.objective = objective;
+        self
+    }
+
+    /// Sets the number of samples in each generation. Larger generations
+    /// search more globally, which helps on multimodal functions such as
+    /// Rastrigin's.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `size` is less than two.
+    pub fn population_size(mut self, size: usize) -> Self {
+        assert!(
+            size >= 2,
+            "there must be at least two samples per generation"
+        );
+        self.population_size = size;
+        self.weights = recombination_weights(size);
+        self
+    }
+
+    /// Records the statistics of every generation, which can then be
+    /// inspected with [`history`].
+    ///
+    /// [`history`]: #method.history
+    pub fn record_history(mut self) -> Self {
+        self.history = Some(History::new());
+        self
+    }
+
+    /// Seeds the random number generator, making the search reproducible.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.rng = Pcg64::seed_from_u64(seed);
+        self
+    }
+
+    /// Returns the number of variables.
+    pub fn dimensions(&self) -> usize {
+        self.mean.len()
+    }
+
+    /// Returns the mean of the distribution, which is the search's current
+    /// estimate of the optimum.
+    pub fn mean(&self) -> &[f64] {
+        &self.mean
+    }
+
+    /// Returns the current step size, by which the distribution is scaled.
+    pub fn step_size(&self) -> f64 {
+        self.step_size
+    }
+
+    /// Returns the current generation number.
+    pub fn generation(&self) -> u32 {
+        self.generation
+    }
+
+    /// Returns the total number of fitness evaluations performed so far.
+    pub fn evaluations(&self) -> u64 {
+        self.evaluations
+    }
+
+    /// Returns the best point sampled so far, or `None` before the first
+    /// generation.
+    pub fn best(&self) -> Option<&[f64]> {
+        self.best.as_ref().map(|(point, _)| point.as_slice())
+    }
+
+    /// Returns the fitness of the best point sampled so far, or `None`
+    /// before the first generation.
+    pub fn best_fitness(&self) -> Option<f64> {
+        self.best.as_ref().map(|&(_, fitness)| fitness)
+    }
+
+    /// Summarises the fitness values of the latest generation, or returns
+    /// `None` before the first generation.
+    pub fn statistics(&self) -> Option<Statistics> {
+        self.statistics
+    }
+
+    /// Returns the recorded history of the search, if recording was enabled
+    /// with [`record_history`].
+    ///
+    /// [`record_history`]: #method.record_history
+    pub fn history(&self) -> Option<&History> {
+        self.history.as_ref()
+    }
+
+    /// Samples and evaluates a generation, then adapts the distribution
+    /// towards its best samples.
+    pub fn next_generation(&mut self) {
+        let n = self.dimensions();
+        let nf = n as f64;
+        let mu_eff = 1.0 / self.weights.iter().map(|w| w * w).sum::<f64>();
+
+        // Learning rates for the evolution paths and the covariance matrix,
+        // and the damping of the step size
+        let c_c = (4.0 + mu_eff / nf) / (nf + 4.0 + 2.0 * mu_eff / nf);
+        let c_sigma = (mu_eff + 2.0) / (nf + mu_eff + 5.0);
+        let c_1 = 2.0 / ((nf + 1.3).powi(2) + mu_eff);
+        let c_mu =
+            (1.0 - c_1).min(2.0 * (mu_eff - 2.0 + 1.0 / mu_eff) / ((nf + 2.0).powi(2) + mu_eff));
+        let damping = 1.0 + 2.0 * (((mu_eff - 1.0) / (nf + 1.0)).sqrt() - 1.0).max(0.0) + c_sigma;
+        let expected_norm = nf.sqrt() * (1.0 - 1.0 / (4.0 * nf) + 1.0 / (21.0 * nf * nf));
+
+        // Sample from N(0, C) by scaling and rotating standard normal vectors
+        let (rng, scales, basis) = (&mut self.rng, &self.scales, &self.basis);
+        let steps: Vec<Vec<f64>> = (0..self.population_size)
+            .map(|_| {
+                let scaled: Vec<f64> = scales
+                    .iter()
+                    .map(|scale| scale * standard_normal(rng))
+                    .collect();
+                multiply(basis, &scaled)
+            })
+            .collect();
+        let points: Vec<Vec<f64>> = steps
+            .iter()
+            .map(|step| {
+                self.mean
+                    .iter()
+                    .zip(step)
+                    .map(|(m, y)| m + self.step_size * y)
+                    .collect()
+            })
+            .collect();
+        let fitnesses: Vec<f64> = points
+            .par_iter()
+            .map(|point| (self.fitness)(point))
+            .collect();
+        self.evaluations += points.len() as u64;
+        self.generation += 1;
+
+        let objective = self.objective;
+        let mut ranking: Vec<usize> = (0..points.len()).collect();
+        ranking.sort_by(|&a, &b| objective.compare(fitnesses[b], fitnesses[a]));
+        let fittest = ranking[0];
+        let improved = match &self.best {
+            Some((_, best)) => objective.compare(fitnesses[fittest], *best) == Ordering::Greater,
+            None => true,
+        };
+        if improved {
+            self.best = Some((points[fittest].clone(), fitnesses[fittest]));
+        }
+        self.statistics = Statistics::from_fitnesses(fitnesses);
+        if let (Some(history), Some(statistics)) = (&mut self.history, self.statistics) {
+            history.record(self.generation, statistics, objective);
+        }
+
+        // Move the mean to the weighted average of the best samples
+        let mut step = vec![0.0; n];
+        for (weight, &index) in self.weights.iter().zip(&ranking) {
+            for (s, y) in step.iter_mut().zip(&steps[index]) {
+                *s += weight * y;
+            }
+        }
+        for (m, s) in self.mean.iter_mut().zip(&step) {
+            *m += self.step_size * s;
+        }
+
+        // Update the evolution paths, stalling the covariance path while the
+        // step size path is unusually long, as the step size is then about
+        // to grow
+        let whitened = multiply(&self.inverse_sqrt, &step);
+        let factor = (c_sigma * (2.0 - c_sigma) * mu_eff).sqrt();
+        for (p, w) in self.path_sigma.iter_mut().zip(&whitened) {
+            *p = (1.0 - c_sigma) * *p + factor * w;
+        }
+        let path_norm = norm(&self.path_sigma);
+        let correction = (1.0 - (1.0 - c_sigma).powi(2 * self.generation as i32)).sqrt();
+        let stalled = path_norm / correction / expected_norm >= 1.4 + 2.0 / (nf + 1.0);
+        let factor = if stalled {
+            0.0
+        } else {
+            (c_c * (2.0 - c_c) * mu_eff).sqrt()
+        };
+        for (p, s) in self.path_c.iter_mut().zip(&step) {
+            *p = (1.0 - c_c) * *p + factor * s;
+        }
+
+        // Adapt the covariance matrix with the rank-one update from the
+        // evolution path and the rank-mu update from the best samples
+        let lost_variance = if stalled { c_c * (2.0 - c_c) } else { 0.0 };
+        for i in 0..n {
+            for j in 0..=i {
+                let rank_mu: f64 = self
+                    .weights
+                    .iter()
+                    .zip(&ranking)
+                    .map(|(weight, &index)| weight * steps[index][i] * steps[index][j])
+                    .sum();
+                let value = (1.0 - c_1 - c_mu + c_1 * lost_variance) * self.covariance[i * n + j]
+                    + c_1 * self.path_c[i] * self.path_c[j]
+                    + c_mu * rank_mu;
+                self.covariance[i * n + j] = value;
+                self.covariance[j * n + i] = value;
+            }
+        }
+
+        self.step_size *= ((c_sigma / damping) * (path_norm / expected_norm - 1.0)).exp();
+
+        // Decomposing the matrix takes O(n^3) time, so it is only done often
+        // enough to keep up with how quickly the matrix changes
+        let interval = self.population_size as f64 / (c_1 + c_mu) / nf / 10.0;
+        if (self.evaluations - self.decomposed_at) as f64 > interval {
+            self.decomposed_at = self.evaluations;
+            self.decompose();
+        }
+    }
+
+    /// Runs the search until the given termination condition is met, which
+    /// is checked before the first generation and after each generation.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ecosystem::{
+    ///     benchmarks::Function,
+    ///     termination::{MaxGenerations, StopReason, Stagnation},
+    ///     Cmaes, Objective,
+    /// };
+    ///
+    /// let function = Function::Sphere;
+    /// let mut cmaes = Cmaes::new(vec![3.0; 5], 1.0, move |point| function.evaluate(point))
+    ///     .objective(Objective::Minimize)
+    ///     .seed(0);
+    /// let summary = cmaes.run(MaxGenerations(100).or(Stagnation::new(20)));
+    ///
+    /// assert_eq!(summary.reason, StopReason::MaxGenerations);
+    /// assert_eq!(summary.evaluations, cmaes.evaluations());
+    /// assert!(cmaes.best_fitness().unwrap() < 1e-6);
+    /// ```
+    pub fn run<T: Termination<Self>>(&mut self, mut termination: T) -> RunSummary {
+        let (start_generation, start_evaluations) = (self.generation, self.evaluations);
+        let reason = loop {
+            if let Some(reason) = termination.check(self) {
+                break reason;
+            }
+            self.next_generation();
+        };
+        RunSummary {
+            reason,
+            generations: self.generation - start_generation,
+            evaluations: self.evaluations - start_evaluations,
+        }
+    }
+
+    /// Recomputes the eigendecomposition of the covariance matrix, and the
+    /// inverse of its square root.
+    fn decompose(&mut self) {
+        let n = self.dimensions();
+        let (eigenvalues, basis) = symmetric_eigen(&self.covariance, n);
+        // Rounding errors can leave tiny eigenvalues negative
+        self.scales = eigenvalues
+            .iter()
+            .map(|&value| value.max(f64::MIN_POSITIVE).sqrt())
+            .collect();
+        for i in 0..n {
+            for j in 0..n {
+                self.inverse_sqrt[i * n + j] = (0..n)
+                    .map(|k| basis[i * n + k] * basis[j * n + k] / self.scales[k])
+                    .sum();
+            }
+        }
+        self.basis = basis;
+    }
+}
+
+impl Search for Cmaes {
+    type Fitness = f64;
+
+    fn generation(&self) -> u32 {
+        self.generation
+    }
+
+    fn evaluations(&self) -> u64 {
+        self.evaluations
+    }
+
+    fn objective(&self) -> Objective {
+        self.objective
+    }
+
+    /// Returns the fitness of the best point sampled so far.
+    fn best_fitness(&self) -> Option<f64> {
+        self.best_fitness()
+    }
+}
+
+/// Returns the weights with which the best half of a generation of the given
+/// size is averaged, which decrease logarithmically with rank and sum to one.
+fn recombination_weights(population_size: usize) -> Vec<f64> {
+    let mu = population_size / 2;
+    let weights: Vec<f64> = (1..=mu)
+        .map(|rank| (mu as f64 + 0.5).ln() - (rank as f64).ln())
+        .collect();
+    let total: f64 = weights.iter().sum();
+    weights.iter().map(|weight| weight / total).collect()
+}
+
+/// Multiplies a square matrix, stored row by row, by a vector.
+fn multiply(matrix: &[f64], vector: &[f64]) -> Vec<f64> {
+    matrix
+        .chunks(vector.len())
+        .map(|row| row.iter().zip(vector).map(|(a, b)| a * b).sum())
+        .collect()
+}
+
+fn norm(vector: &[f64]) -> f64 {
+    vector.iter().map(|x| x * x).sum::<f64>().sqrt()
+}
+
+/// Finds the eigenvalues and eigenvectors of a symmetric matrix, stored row
+/// by row, with the cyclic Jacobi method. The eigenvectors are returned as
+/// the columns of a matrix, in the same order as the eigenvalues.
+fn symmetric_eigen(matrix: &[f64], n: usize) -> (Vec<f64>, Vec<f64>) {
+    let mut a = matrix.to_vec();
+    let mut vectors = vec![0.0; n * n];
+    for i in 0..n {
+        vectors[i * n + i] = 1.0;
+    }
+
+    for _ in 0..100 {
+        let off_diagonal: f64 = (0..n)
+            .flat_map(|i| (0..n).filter(move |&j| j != i).map(move |j| (i, j)))
+            .map(|(i, j)| a[i * n + j].powi(2))
+            .sum();
+        let diagonal: f64 = (0..n).map(|i| a[i * n + i].powi(2)).sum();
+        if off_diagonal <= f64::EPSILON * f64::EPSILON * diagonal {
+            break;
+        }
+
+        for p in 0..n {
+            for q in p + 1..n {
+                if a[p * n + q] == 0.0 {
+                    continue;
+                }
+                // Choose the rotation that zeroes a[p][q]
+                let theta = (a[q * n + q] - a[p * n + p]) / (2.0 * a[p * n + q]);
+                let t = theta.signum() / (theta.abs() + (theta * theta + 1.0).sqrt());
+                let c = 1.0 / (t * t + 1.0).sqrt();
+                let s = t * c;
+
+                for k in 0..n {
+                    let (kp, kq) = (a[k * n + p], a[k * n + q]);
+                    a[k * n + p] = c * kp - s * kq;
+                    a[k * n + q] = s * kp + c * kq;
+                }
+                for k in 0..n {
+                    let (pk, qk) = (a[p * n + k], a[q * n + k]);
+                    a[p * n + k] = c * pk - s * qk;
+                    a[q * n + k] = s * pk + c * qk;
+                }
+                for k in 0..n {
+                    let (kp, kq) = (vectors[k * n + p], vectors[k * n + q]);
+                    vectors[k * n + p] = c * kp - s * kq;
+                    vectors[k * n + q] = s * kp + c * kq;
+                }
+            }
+        }
+    }
+
+    ((0..n).map(|i| a[i * n + i]).collect(), vectors)
+}
@@ -0,0 +1,326 @@
+use crate::parallel::*;
+use crate::{EcosystemError, Organism};
+use rand::{seq::index, Rng, SeedableRng};
+use rand_pcg::Pcg64;
+
+/// A function that plays an encounter between members of the two
+/// populations, returning the score of each.
+type Encounter<A, B> = Box<dyn Fn(&A, &B) -> (f64, f64) + Send + Sync>;
+
+/// Competitive coevolution between two populations, such as programs and
+/// the test cases that try to break them, in which the fitness of each
+/// organism depends on how it fares against the other population.
+///
+/// In every generation, each organism meets a sample of `opponents`
+/// organisms from the other population, drawn afresh, and its score is the
+/// mean of its scores from those encounters, which are played in parallel.
+/// Higher scores are better. Each population is then replaced by its
+/// `elitism` highest-scoring organisms and children bred from parents chosen
+/// by tournaments on the scores. As each population improves, the other
+/// faces stronger opponents, which can drive both towards solutions that no
+/// fixed fitness function would have found.
+///
+/// The organisms' own [`fitness`] is never evaluated. By default, each
+/// organism meets ten opponents, parents are chosen by binary tournaments,
+/// a single elite is kept in each population, children are mutated at a
+/// rate of [`DEFAULT_MUTATION_RATE`], and the random number generator is
+/// seeded from the operating system.
+///
+/// [`fitness`]: trait.Organism.html#tymethod.fitness
+/// [`DEFAULT_MUTATION_RATE`]: constant.DEFAULT_MUTATION_RATE.html
+///
+/// # Examples
+///
+/// ```rust
+/// use ecosystem::{Coevolution, Organism};
+/// use rand::Rng;
+///
+/// struct Height(f64);
+///
+/// impl Organism for Height {
+///     type Fitness = f64;
+///
+///     fn fitness(&self) -> f64 {
+///         unreachable!("fitness comes from encounters")
+///     }
+///
+///     fn breed(&self, other: &Self, _rng: &mut impl Rng) -> Self {
+///         Height((self.0 + other.0) / 2.0)
+///     }
+///
+///     fn mutate(&mut self, rate: f64, rng: &mut impl Rng) {
+///         self.0 += rng.gen_range(-rate, rate);
+///     }
+/// }
+///
+/// // An arms race, in which the taller of the two wins each encounter
+/// let heights = |range: std::ops::Range<i32>| range.map(|i| Height(f64::from(i))).collect();
+/// let mut coevolution = Coevolution::new(heights(0..20), heights(0..20), |a, b| {
+///     if a.0 > b.0 {
+///         (1.0, 0.0)
+///     } else {
+///         (0.0, 1.0)
+///     }
+/// })
+/// .opponents(5)
+/// .mutation_rate(1.0)
+/// .seed(0);
+/// for _ in 0..100 {
+///     coevolution.breed_next_generation()?;
+/// }
+///
+/// // Each population has pushed the other far beyond where they started
+/// assert!(coevolution.fittest_first().unwrap().0 > 30.0);
+/// assert!(coevolution.fittest_second().unwrap().0 > 30.0);
+/// // Every organism meets five opponents in the initial populations and in
+/// // each of the hundred generations bred from them
+/// assert_eq!(coevolution.encounters(), 101 * 40 * 5);
+/// # Ok::<(), ecosystem::EcosystemError>(())
+/// ```
+pub struct Coevolution<A: Organism, B: Organism> {
+    first: Vec<A>,
+    second: Vec<B>,
+    first_scores: Vec<f64>,
+    second_scores: Vec<f64>,
+    play: Encounter<A, B>,
+    opponents: usize,
+    tournament_size: usize,
+    elitism: usize,
+    mutation_rate: f64,
+    rng: Pcg64,
+    generation: u32,
+    encounters: u64,
+}
+
+impl<A: Organism + Send + Sync, B: Organism + Send + Sync> Coevolution<A, B> {
+    /// Creates a coevolution of the two given populations, whose encounters
+    /// are played by `play`, which returns the score of each organism.
+    pub fn new<P>(first: Vec<A>, second: Vec<B>, play: P) -> Self
+    where
+        P: Fn(&A, &B) -> (f64, f64) + Send + Sync + 'static,
+    {
+        Self {
+            first,
+            second,
+            first_scores: Vec::new(),
+            second_scores: Vec::new(),
+            play: Box::new(play),
+            opponents: 10,
+            tournament_size: 2,
+            elitism: 1,
+            mutation_rate: crate::DEFAULT_MUTATION_RATE,
+            rng: Pcg64::from_entropy(),
+            generation: 0,
+            encounters: 0,
+        }
+    }
+
+    /// Sets the number of opponents that each organism meets in each
+    /// generation. Organisms meet every member of the other population if it
+    /// is smaller than this.
+    pub fn opponents(mut self, count: usize) -> Self {
+        self.opponents = count;
+        self
+    }
+
+    /// Sets the number of organisms in the tournaments that choose parents.
+    pub fn tournament_size(mut self, size: usize) -> Self {
+        self.tournament_size = size;
+        self
+    }
+
+    /// Sets the number of the highest-scoring organisms in each population
+    /// that are carried over unchanged into the next generation.
+    pub fn elitism(mut self, count: usize) -> Self {
+        self.elitism = count;
+        self
+    }
+
+    /// Sets the rate at which children are mutated.
+    pub fn mutation_rate(mut self, rate: f64) -> Self {
+        self.mutation_rate = rate;
+        self
+    }
+
+    /// Seeds the random number generator, making evolution reproducible.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.rng = Pcg64::seed_from_u64(seed);
+        self
+    }
+
+    /// Returns the first population.
+    pub fn first(&self) -> &[A] {
+        &self.first
+    }
+
+    /// Returns the second population.
+    pub fn second(&self) -> &[B] {
+        &self.second
+    }
+
+    /// Returns the scores of the first population, in the same order as the
+    /// organisms, or an empty slice before the first generation is bred.
+    pub fn first_scores(&self) -> &[f64] {
+        &self.first_scores
+    }
+
+    /// Returns the scores of the second population, in the same order as
+    /// the organisms, or an empty slice before the first generation is bred.
+    pub fn second_scores(&self) -> &[f64] {
+        &self.second_scores
+    }
+
+    /// Returns the highest-scoring organism of the first population, or
+    /// `None` before the first generation is bred.
+    pub fn fittest_first(&self) -> Option<&A> {
+        highest(&self.first_scores).map(|index| &self.first[index])
+    }
+
+    /// Returns the highest-scoring organism of the second population, or
+    /// `None` before the first generation is bred.
+    pub fn fittest_second(&self) -> Option<&B> {
+        highest(&self.second_scores).map(|index| &self.second[index])
+    }
+
+    /// Returns the number of generations that have been bred.
+    pub fn generation(&self) -> u32 {
+        self.generation
+    }
+
+    /// Returns the number of encounters that have been played.
+    pub fn encounters(&self) -> u64 {
+        self.encounters
+    }
+
+    /// Breeds the next generation of both populations from the scores of
+    /// the current generation, then scores the new generation.
+    ///
+    /// Encounters are played in parallel, as are children bred, with each
+    /// child given its own random number generator seeded from the
+    /// coevolution's, so the result is reproducible.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if either population is empty, as nothing can
+    /// then be scored, or if the tournament size is zero.
+    pub fn breed_next_generation(&mut self) -> Result<(), EcosystemError> {
+        if self.first.is_empty() || self.second.is_empty() {
+            return Err(EcosystemError::EmptyPopulation);
+        }
+        if self.tournament_size == 0 {
+            return Err(EcosystemError::InvalidTournamentSize);
+        }
+
+        if self.first_scores.is_empty() {
+            self.score();
+        }
+        let (elitism, tournament_size, mutation_rate) =
+            (self.elitism, self.tournament_size, self.mutation_rate);
+        let first = std::mem::take(&mut self.first);
+        self.first = next_population(
+            first,
+            &self.first_scores,
+            (elitism, tournament_size, mutation_rate),
+            &mut self.rng,
+        );
+        let second = std::mem::take(&mut self.second);
+        self.second = next_population(
+            second,
+            &self.second_scores,
+            (elitism, tournament_size, mutation_rate),
+            &mut self.rng,
+        );
+        self.score();
+
+        self.generation += 1;
+        Ok(())
+    }
+
+    /// Scores both populations, each organism against a fresh sample of
+    /// opponents.
+    fn score(&mut self) {
+        let (first, second, rng) = (&self.first, &self.second, &mut self.rng);
+        let first_opponents = sample_opponents(first.len(), second.len(), self.opponents, rng);
+        let second_opponents = sample_opponents(second.len(), first.len(), self.opponents, rng);
+
+        let play = &self.play;
+        self.first_scores = first
+            .par_iter()
+            .zip(&first_opponents)
+            .map(|(a, opponents)| mean(opponents.iter().map(|&b| play(a, &second[b]).0)))
+            .collect();
+        self.second_scores = second
+            .par_iter()
+            .zip(&second_opponents)
+            .map(|(b, opponents)| mean(opponents.iter().map(|&a| play(&first[a], b).1)))
+            .collect();
+        self.encounters += first_opponents
+            .iter()
+            .chain(&second_opponents)
+            .map(|opponents| opponents.len() as u64)
+            .sum::<u64>();
+    }
+}
+
+/// Chooses the opponents of each of `count` organisms, from a population of
+/// `population_size`.
+fn sample_opponents(
+    count: usize,
+    population_size: usize,
+    opponents: usize,
+    rng: &mut Pcg64,
+) -> Vec<Vec<usize>> {
+    let opponents = opponents.min(population_size);
+    (0..count)
+        .map(|_| index::sample(rng, population_size, opponents).into_vec())
+        .collect()
+}
+
+/// Replaces a population with its elites and children bred from parents
+/// chosen by tournaments on the given scores.
+fn next_population<O: Organism + Send + Sync>(
+    organisms: Vec<O>,
+    scores: &[f64],
+    (elitism, tournament_size, mutation_rate): (usize, usize, f64),
+    rng: &mut Pcg64,
+) -> Vec<O> {
+    let select = |rng: &mut Pcg64| {
+        (0..tournament_size)
+            .map(|_| rng.gen_range(0, organisms.len()))
+            .max_by(|&a, &b| scores[a].total_cmp(&scores[b]))
+            .unwrap()
+    };
+    let elites = elitism.min(organisms.len());
+    let parents: Vec<_> = (elites..organisms.len())
+        .map(|_| (select(rng), select(rng), rng.gen::<u64>()))
+        .collect();
+    let children: Vec<O> = parents
+        .into_par_iter()
+        .map(|(mother, father, seed)| {
+            let mut rng = Pcg64::seed_from_u64(seed);
+            let mut child = organisms[mother].breed(&organisms[father], &mut rng);
+            child.mutate(mutation_rate, &mut rng);
+            child
+        })
+        .collect();
+
+    let mut ranking: Vec<usize> = (0..organisms.len()).collect();
+    ranking.sort_by(|&a, &b| scores[b].total_cmp(&scores[a]));
+    ranking.truncate(elites);
+    let mut organisms: Vec<_> = organisms.into_iter().map(Some).collect();
+    ranking
+        .into_iter()
+        .map(|index| organisms[index].take().unwrap())
+        .chain(children)
+        .collect()
+}
+
+/// Returns the index of the highest score, if there are any.
+fn highest(scores: &[f64]) -> Option<usize> {
+    (0..scores.len()).max_by(|&a, &b| scores[a].total_cmp(&scores[b]))
+}
+
+fn mean(values: impl Iterator<Item = f64>) -> f64 {
+    let (sum, count) = values.fold((0.0, 0), |(sum, count), value| (sum + value, count + 1));
+    sum / count as f64
+}
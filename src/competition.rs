@@ -0,0 +1,291 @@
+//! Relative fitness, for organisms that can only be judged against each
+//! other.
+//!
+//! The strength of a game-playing organism has no absolute measure: it
+//! depends on who it plays. A [`RoundRobin`] instead plays the members of
+//! the population against each other, as described by [`Competitor`], and
+//! takes each organism's fitness to be its win rate, so the population
+//! improves by beating itself.
+//!
+//! [`RoundRobin`]: struct.RoundRobin.html
+//! [`Competitor`]: trait.Competitor.html
+use crate::parallel::*;
+use crate::{Ecosystem, EcosystemError, Organism};
+use rand::seq::index;
+use std::sync::OnceLock;
+
+/// The result of a game, from the point of view of the organism that
+/// played it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Outcome {
+    /// The organism won, scoring one point.
+    Win,
+    /// Neither organism won, and each scores half a point.
+    Draw,
+    /// The organism lost, scoring nothing.
+    Loss,
+}
+
+impl Outcome {
+    /// Returns the same result from the point of view of the opponent.
+    pub fn reverse(self) -> Self {
+        match self {
+            Outcome::Win => Outcome::Loss,
+            Outcome::Draw => Outcome::Draw,
+            Outcome::Loss => Outcome::Win,
+        }
+    }
+
+    /// Returns the points scored for this result.
+    fn points(self) -> f64 {
+        match self {
+            Outcome::Win => 1.0,
+            Outcome::Draw => 0.5,
+            Outcome::Loss => 0.0,
+        }
+    }
+}
+
+/// An interface for organisms that play games against each other.
+pub trait Competitor {
+    /// Plays a game against `other`, returning the result for `self`.
+    ///
+    /// Each pairing is played once, with the result counting towards both
+    /// organisms' win rates, so games in which the first player has an
+    /// advantage should alternate sides themselves.
+    fn play(&self, other: &Self) -> Outcome;
+}
+
+/// An ecosystem whose fitness values are win rates in games played between
+/// the members of its population.
+///
+/// In a full round robin, every organism plays every other, which takes
+/// `n(n-1)/2` games for a population of `n`. With [`set_opponents`], each
+/// organism instead challenges a fresh sample of opponents, which scales to
+/// larger populations; organisms are then also credited with the games in
+/// which they were challenged. An organism's win rate is its points (one for
+/// each win and half for each draw) divided by its games played. Games are
+/// played in parallel.
+///
+/// Before each generation is bred, the current population's win rates are
+/// set as its fitness values, which the ecosystem's selection strategy and
+/// elitism then use as usual (with the ecosystem's objective left as
+/// maximisation). Children are bred generationally, regardless of the
+/// ecosystem's population model, and the new generation is then played
+/// against itself, elites included, so that the ecosystem's [`fittest`]
+/// organism and statistics always reflect the current population. The
+/// organisms' own [`fitness`] is never evaluated.
+///
+/// [`set_opponents`]: #method.set_opponents
+/// [`fittest`]: ../struct.Ecosystem.html#method.fittest
+/// [`fitness`]: ../trait.Organism.html#tymethod.fitness
+///
+/// # Examples
+///
+/// ```rust
+/// use ecosystem::{
+///     competition::{Competitor, Outcome, RoundRobin},
+///     Ecosystem, Organism,
+/// };
+/// # use rand::Rng;
+/// # struct Guess(f64);
+/// # impl Organism for Guess {
+/// #     type Fitness = f64;
+/// #     fn fitness(&self) -> f64 { unreachable!("fitness comes from games") }
+/// #     fn breed(&self, other: &Self, rng: &mut impl Rng) -> Self { Guess((self.0 + other.0) / 2.0) }
+/// #     fn mutate(&mut self, rate: f64, rng: &mut impl Rng) {
+/// #         self.0 += rng.gen_range(-rate, rate);
+/// #     }
+/// # }
+///
+/// // The higher guess wins
+/// impl Competitor for Guess {
+///     fn play(&self, other: &Self) -> Outcome {
+///         if self.0 > other.0 {
+///             Outcome::Win
+///         } else if self.0 < other.0 {
+///             Outcome::Loss
+///         } else {
+///             Outcome::Draw
+///         }
+///     }
+/// }
+///
+/// let ecosystem = Ecosystem::builder()
+///     .organisms((0..20).map(|i| Guess(f64::from(i))))
+///     .elitism(1)
+///     .mutation_rate(1.0)
+///     .seed(0)
+///     .build()
+///     .unwrap();
+/// let mut round_robin = RoundRobin::new(ecosystem);
+/// for _ in 0..20 {
+///     round_robin.breed_next_generation()?;
+/// }
+///
+/// // Every guess has outgrown the highest of the initial population
+/// assert!(round_robin.ecosystem().organisms().all(|guess| guess.0 > 19.0));
+/// // The best guess beat every other
+/// let best = round_robin.ecosystem().fittest_individual().unwrap();
+/// assert_eq!(best.fitness(), 1.0);
+/// // 190 games were played in the initial population and in each of the
+/// // twenty generations bred from it
+/// assert_eq!(round_robin.games(), 21 * 190);
+/// # Ok::<(), ecosystem::EcosystemError>(())
+/// ```
+pub struct RoundRobin<O: Organism> {
+    ecosystem: Ecosystem<O>,
+    opponents: Option<usize>,
+    games: u64,
+}
+
+impl<O: Organism<Fitness = f64> + Competitor + Send + Sync> RoundRobin<O> {
+    /// Creates a full round robin over the given ecosystem.
+    pub fn new(ecosystem: Ecosystem<O>) -> Self {
+        Self {
+            ecosystem,
+            opponents: None,
+            games: 0,
+        }
+    }
+
+    /// Samples the round robin, with each organism challenging `count`
+    /// opponents chosen at random from the rest of the population, rather
+    /// than every one of them.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ecosystem::{
+    ///     competition::{Competitor, Outcome, RoundRobin},
+    ///     Ecosystem, Organism,
+    /// };
+    /// # use rand::Rng;
+    /// # struct Guess(f64);
+    /// # impl Organism for Guess {
+    /// #     type Fitness = f64;
+    /// #     fn fitness(&self) -> f64 { unreachable!("fitness comes from games") }
+    /// #     fn breed(&self, other: &Self, rng: &mut impl Rng) -> Self { Guess((self.0 + other.0) / 2.0) }
+    /// #     fn mutate(&mut self, rate: f64, rng: &mut impl Rng) {
+    /// #         self.0 += rng.gen_range(-rate, rate);
+    /// #     }
+    /// # }
+    /// # impl Competitor for Guess {
+    /// #     fn play(&self, other: &Self) -> Outcome {
+    /// #         if self.0 > other.0 { Outcome::Win } else { Outcome::Loss }
+    /// #     }
+    /// # }
+    ///
+    /// let ecosystem = Ecosystem::new((0..100).map(|i| Guess(f64::from(i))).collect());
+    /// let mut round_robin = RoundRobin::new(ecosystem);
+    /// round_robin.set_opponents(5);
+    /// round_robin.breed_next_generation()?;
+    ///
+    /// // Five challenges from each organism, in the initial population and
+    /// // the generation bred from it, rather than 4950 games in each
+    /// assert_eq!(round_robin.games(), 2 * 100 * 5);
+    /// # Ok::<(), ecosystem::EcosystemError>(())
+    /// ```
+    pub fn set_opponents(&mut self, count: usize) {
+        self.opponents = Some(count);
+    }
+
+    /// Returns the ecosystem being evolved.
+    pub fn ecosystem(&self) -> &Ecosystem<O> {
+        &self.ecosystem
+    }
+
+    /// Consumes the round robin, returning the ecosystem.
+    pub fn into_ecosystem(self) -> Ecosystem<O> {
+        self.ecosystem
+    }
+
+    /// Returns the number of games that have been played.
+    pub fn games(&self) -> u64 {
+        self.games
+    }
+
+    /// Breeds the next generation from parents chosen by their win rates,
+    /// then plays the new generation against itself. The current population
+    /// is played first, if it has not been already.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the ecosystem contains no organisms, or if
+    /// its selection strategy fails.
+    pub fn breed_next_generation(&mut self) -> Result<(), EcosystemError> {
+        if self.ecosystem.population.is_empty() {
+            return Err(EcosystemError::EmptyPopulation);
+        }
+        if self
+            .ecosystem
+            .population
+            .iter()
+            .any(|individual| individual.fitness.get().is_none())
+        {
+            self.play();
+        }
+
+        let ecosystem = &mut self.ecosystem;
+        let next_generation = ecosystem.select_and_breed(ecosystem.offspring_count())?;
+        ecosystem.replace_generation(next_generation, |a, b| b.fitness().total_cmp(&a.fitness()));
+        self.play();
+        if self.ecosystem.history.is_some() {
+            self.ecosystem.record_history();
+        }
+        Ok(())
+    }
+
+    /// Plays the games of a round robin in the current population, setting
+    /// each individual's fitness to its win rate.
+    fn play(&mut self) {
+        let ecosystem = &mut self.ecosystem;
+        let size = ecosystem.population.len();
+        let pairings: Vec<(usize, usize)> = match self.opponents {
+            None => (0..size)
+                .flat_map(|a| (a + 1..size).map(move |b| (a, b)))
+                .collect(),
+            Some(count) => {
+                let count = count.min(size - 1);
+                let rng = &mut ecosystem.rng;
+                (0..size)
+                    .flat_map(|a| {
+                        index::sample(rng, size - 1, count)
+                            .into_iter()
+                            .map(move |b| (a, if b >= a { b + 1 } else { b }))
+                            .collect::<Vec<_>>()
+                    })
+                    .collect()
+            }
+        };
+
+        let population = &ecosystem.population;
+        let outcomes: Vec<Outcome> = pairings
+            .par_iter()
+            .map(|&(a, b)| population[a].organism().play(population[b].organism()))
+            .collect();
+        let mut points = vec![0.0; size];
+        let mut games = vec![0_u32; size];
+        for (&(a, b), outcome) in pairings.iter().zip(outcomes) {
+            points[a] += outcome.points();
+            points[b] += outcome.reverse().points();
+            games[a] += 1;
+            games[b] += 1;
+        }
+
+        for (individual, (points, games)) in ecosystem
+            .population
+            .iter_mut()
+            .zip(points.into_iter().zip(games))
+        {
+            let rate = if games > 0 {
+                points / f64::from(games)
+            } else {
+                0.0
+            };
+            individual.fitness = OnceLock::from(rate);
+        }
+        self.games += pairings.len() as u64;
+    }
+}
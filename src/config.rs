@@ -0,0 +1,372 @@
+//! Run configurations that can be loaded from files.
+//!
+//! A [`RunConfig`] describes how an ecosystem evolves and when its run
+//! stops, so that a set of experiments can be kept as configuration files
+//! rather than as constants in the program. It can be deserialised from any
+//! format supported by serde, and read directly from TOML or JSON with the
+//! `toml` and `json` features. Every field has a default, so a file only
+//! needs to give the settings that differ:
+//!
+//! ```rust
+//! use ecosystem::config::{RunConfig, SelectionConfig, TerminationConfig};
+//! # use ecosystem::Organism;
+//! # use rand::Rng;
+//! # #[derive(Clone)]
+//! # struct Guess(f64);
+//! # impl Organism for Guess {
+//! #     type Fitness = f64;
+//! #     fn fitness(&self) -> f64 { -(42.0 - self.0).abs() }
+//! #     fn breed(&self, other: &Self, rng: &mut impl Rng) -> Self { Guess((self.0 + other.0) / 2.0) }
+//! #     fn mutate(&mut self, rate: f64, rng: &mut impl Rng) {
+//! #         self.0 += rng.gen_range(-rate, rate);
+//! #     }
+//! # }
+//!
+//! let config = RunConfig {
+//!     population_size: 20,
+//!     mutation_rate: 0.5,
+//!     selection: SelectionConfig::Tournament { size: 3 },
+//!     termination: TerminationConfig {
+//!         max_generations: Some(200),
+//!         target_fitness: Some(-0.01),
+//!         ..TerminationConfig::default()
+//!     },
+//!     ..RunConfig::default()
+//! };
+//!
+//! let mut ecosystem = config.build(|rng| Guess(rng.gen_range(0.0, 100.0)))?;
+//! let summary = ecosystem.run(config.termination())?;
+//!
+//! assert!(summary.generations <= 200);
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+//!
+//! [`RunConfig`]: struct.RunConfig.html
+use crate::{
+    selection::{
+        ExponentialRank, FitnessProportionate, LinearRank, StochasticUniversal, Tournament,
+    },
+    termination::{MaxEvaluations, MaxGenerations, Search, Stagnation, StopReason, Termination},
+    BuildError, Ecosystem, EcosystemBuilder, Fitness, Objective, Organism, DEFAULT_MUTATION_RATE,
+};
+use core::cmp::Ordering;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+#[cfg(any(feature = "toml", feature = "json"))]
+use std::fs;
+use std::{error::Error, fmt, io, path::Path};
+
+/// How an ecosystem evolves, and when its run stops.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RunConfig {
+    /// The number of organisms in the population. Defaults to 100.
+    pub population_size: usize,
+    /// The mutation rate, which must lie within [0, 1]. Defaults to
+    /// [`DEFAULT_MUTATION_RATE`].
+    ///
+    /// [`DEFAULT_MUTATION_RATE`]: ../constant.DEFAULT_MUTATION_RATE.html
+    pub mutation_rate: f64,
+    /// The probability that a child is bred by recombining its parents,
+    /// rather than cloning one of them. Defaults to 1.
+    pub crossover_rate: f64,
+    /// How parents are chosen. Defaults to fitness-proportionate selection.
+    pub selection: SelectionConfig,
+    /// The number of the fittest organisms carried over to each new
+    /// generation unchanged. Defaults to none.
+    pub elitism: usize,
+    /// The fraction of the population replaced by children in each
+    /// generation, within (0, 1]. Defaults to 1.
+    pub generation_gap: f64,
+    /// Whether higher or lower fitness values are better. Defaults to
+    /// higher.
+    pub objective: Objective,
+    /// When the run stops. Defaults to after 100 generations, which giving
+    /// any conditions replaces.
+    pub termination: TerminationConfig,
+    /// The seed for the random number generator, or `None` (the default)
+    /// to seed it from the operating system.
+    pub seed: Option<u64>,
+}
+
+impl Default for RunConfig {
+    fn default() -> Self {
+        RunConfig {
+            population_size: 100,
+            mutation_rate: DEFAULT_MUTATION_RATE,
+            crossover_rate: 1.0,
+            selection: SelectionConfig::default(),
+            elitism: 0,
+            generation_gap: 1.0,
+            objective: Objective::default(),
+            termination: TerminationConfig {
+                max_generations: Some(100),
+                ..TerminationConfig::default()
+            },
+            seed: None,
+        }
+    }
+}
+
+/// A built-in selection strategy, as named in a configuration file by its
+/// `type`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", deny_unknown_fields)]
+pub enum SelectionConfig {
+    /// [`FitnessProportionate`](../selection/struct.FitnessProportionate.html)
+    /// selection.
+    #[default]
+    FitnessProportionate,
+    /// [`Tournament`](../selection/struct.Tournament.html) selection.
+    Tournament {
+        /// The number of organisms competing in each tournament.
+        size: usize,
+    },
+    /// [`LinearRank`](../selection/struct.LinearRank.html) selection.
+    LinearRank {
+        /// The selection pressure, within [1, 2].
+        pressure: f64,
+    },
+    /// [`ExponentialRank`](../selection/struct.ExponentialRank.html)
+    /// selection.
+    ExponentialRank {
+        /// The ratio between the weights of adjacent ranks, within (0, 1].
+        base: f64,
+    },
+    /// [`StochasticUniversal`](../selection/struct.StochasticUniversal.html)
+    /// sampling.
+    StochasticUniversal,
+}
+
+/// The conditions that stop a run, which stops as soon as any of the given
+/// conditions is met.
+///
+/// At least one condition should be given, as the run never stops
+/// otherwise.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TerminationConfig {
+    /// The generation number at which to stop.
+    pub max_generations: Option<u32>,
+    /// The number of fitness evaluations after which to stop.
+    pub max_evaluations: Option<u64>,
+    /// The fitness at which to stop, compared with the scalar view of the
+    /// best fitness given by [`Fitness::to_f64`].
+    ///
+    /// [`Fitness::to_f64`]: ../trait.Fitness.html#tymethod.to_f64
+    pub target_fitness: Option<f64>,
+    /// The number of generations without improvement after which to stop.
+    pub stagnation: Option<u32>,
+}
+
+impl RunConfig {
+    /// Parses a configuration from TOML.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ecosystem::config::{RunConfig, SelectionConfig};
+    ///
+    /// let config = RunConfig::from_toml(
+    ///     r#"
+    ///     population_size = 20
+    ///     mutation_rate = 0.5
+    ///     seed = 7
+    ///     selection = { type = "Tournament", size = 3 }
+    ///
+    ///     [termination]
+    ///     max_generations = 200
+    ///     target_fitness = -0.01
+    ///     "#,
+    /// )?;
+    ///
+    /// assert_eq!(config.selection, SelectionConfig::Tournament { size: 3 });
+    /// assert_eq!(config.termination.max_generations, Some(200));
+    /// // Anything left out keeps its default
+    /// assert_eq!(config.elitism, 0);
+    /// # Ok::<(), ecosystem::config::ConfigError>(())
+    /// ```
+    #[cfg(feature = "toml")]
+    pub fn from_toml(text: &str) -> Result<Self, ConfigError> {
+        toml::from_str(text).map_err(|error| ConfigError::Format(Box::new(error)))
+    }
+
+    /// Parses a configuration from JSON.
+    #[cfg(feature = "json")]
+    pub fn from_json(text: &str) -> Result<Self, ConfigError> {
+        serde_json::from_str(text).map_err(|error| ConfigError::Format(Box::new(error)))
+    }
+
+    /// Reads a configuration from a file, in TOML or JSON as given by its
+    /// `.toml` or `.json` extension (each needing the feature of the same
+    /// name).
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ConfigError> {
+        let path = path.as_ref();
+        match path.extension().and_then(|extension| extension.to_str()) {
+            #[cfg(feature = "toml")]
+            Some("toml") => Self::from_toml(&fs::read_to_string(path)?),
+            #[cfg(feature = "json")]
+            Some("json") => Self::from_json(&fs::read_to_string(path)?),
+            _ => Err(ConfigError::Format(
+                format!("unsupported configuration file {}", path.display()).into(),
+            )),
+        }
+    }
+
+    /// Returns an ecosystem builder configured by everything but the
+    /// termination conditions, with a population of `population_size`
+    /// organisms made by `create`. The organisms are drawn from the
+    /// ecosystem's random number generator, so they are reproducible
+    /// whenever the run is seeded.
+    pub fn builder<O: Organism + Clone + Send + Sync>(
+        &self,
+        create: impl FnMut(&mut dyn RngCore) -> O,
+    ) -> EcosystemBuilder<O> {
+        let mut builder = Ecosystem::builder();
+        if let Some(seed) = self.seed {
+            builder = builder.seed(seed);
+        }
+        builder = builder
+            .generate(self.population_size, create)
+            .mutation_rate(self.mutation_rate)
+            .elitism(self.elitism)
+            .generation_gap(self.generation_gap)
+            .objective(self.objective);
+        if self.crossover_rate < 1.0 {
+            builder = builder.crossover_rate(self.crossover_rate);
+        }
+        builder = match self.selection {
+            SelectionConfig::FitnessProportionate => builder.selection(FitnessProportionate),
+            SelectionConfig::Tournament { size } => builder.selection(Tournament { size }),
+            SelectionConfig::LinearRank { pressure } => builder.selection(LinearRank { pressure }),
+            SelectionConfig::ExponentialRank { base } => {
+                builder.selection(ExponentialRank { base })
+            }
+            SelectionConfig::StochasticUniversal => builder.selection(StochasticUniversal),
+        };
+        builder
+    }
+
+    /// Builds an ecosystem from this configuration, with a population of
+    /// `population_size` organisms made by `create`.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the configuration is invalid, as with
+    /// [`EcosystemBuilder::build`].
+    ///
+    /// [`EcosystemBuilder::build`]: ../struct.EcosystemBuilder.html#method.build
+    pub fn build<O: Organism + Clone + Send + Sync>(
+        &self,
+        create: impl FnMut(&mut dyn RngCore) -> O,
+    ) -> Result<Ecosystem<O>, BuildError> {
+        self.builder(create).build()
+    }
+
+    /// Returns the termination conditions, to be passed to
+    /// [`Ecosystem::run`].
+    ///
+    /// [`Ecosystem::run`]: ../struct.Ecosystem.html#method.run
+    pub fn termination<F>(&self) -> RunTermination<F> {
+        self.termination.build()
+    }
+}
+
+impl TerminationConfig {
+    /// Returns the configured conditions as a [`Termination`].
+    ///
+    /// [`Termination`]: ../termination/trait.Termination.html
+    pub fn build<F>(&self) -> RunTermination<F> {
+        RunTermination {
+            max_generations: self.max_generations.map(MaxGenerations),
+            max_evaluations: self.max_evaluations.map(MaxEvaluations),
+            target_fitness: self.target_fitness,
+            stagnation: self.stagnation.map(Stagnation::new),
+        }
+    }
+}
+
+/// The termination conditions of a [`TerminationConfig`].
+///
+/// [`TerminationConfig`]: struct.TerminationConfig.html
+#[derive(Clone, Debug)]
+pub struct RunTermination<F> {
+    max_generations: Option<MaxGenerations>,
+    max_evaluations: Option<MaxEvaluations>,
+    target_fitness: Option<f64>,
+    stagnation: Option<Stagnation<F>>,
+}
+
+impl<S: Search<Fitness = F> + ?Sized, F: Fitness> Termination<S> for RunTermination<F> {
+    fn check(&mut self, search: &S) -> Option<StopReason> {
+        if let Some(target) = self.target_fitness {
+            let best = search.best_fitness().map(Fitness::to_f64);
+            if best.is_some_and(|best| search.objective().compare(best, target) != Ordering::Less) {
+                return Some(StopReason::TargetFitness);
+            }
+        }
+        // The stagnation condition tracks the best fitness, so it is checked
+        // every time, even if another condition is met first
+        let stagnation = self
+            .stagnation
+            .as_mut()
+            .and_then(|stagnation| stagnation.check(search));
+        self.max_generations
+            .as_mut()
+            .and_then(|limit| limit.check(search))
+            .or_else(|| {
+                self.max_evaluations
+                    .as_mut()
+                    .and_then(|limit| limit.check(search))
+            })
+            .or(stagnation)
+    }
+
+    fn progress(&self, search: &S) -> Option<f64> {
+        let generations = self
+            .max_generations
+            .as_ref()
+            .and_then(|limit| limit.progress(search));
+        let evaluations = self
+            .max_evaluations
+            .as_ref()
+            .and_then(|limit| limit.progress(search));
+        match (generations, evaluations) {
+            (Some(generations), Some(evaluations)) => Some(generations.max(evaluations)),
+            (generations, evaluations) => generations.or(evaluations),
+        }
+    }
+}
+
+/// An error arising from loading a run configuration.
+#[derive(Debug)]
+pub enum ConfigError {
+    /// The configuration file could not be read.
+    Io(io::Error),
+    /// The configuration was not valid, or was in an unsupported format.
+    Format(Box<dyn Error + Send + Sync>),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ConfigError::Io(error) => write!(f, "failed to read the configuration: {}", error),
+            ConfigError::Format(error) => write!(f, "invalid configuration: {}", error),
+        }
+    }
+}
+
+impl Error for ConfigError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ConfigError::Io(error) => Some(error),
+            ConfigError::Format(error) => Some(error.as_ref()),
+        }
+    }
+}
+
+impl From<io::Error> for ConfigError {
+    fn from(error: io::Error) -> Self {
+        ConfigError::Io(error)
+    }
+}
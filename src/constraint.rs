@@ -0,0 +1,80 @@
+use crate::{Ecosystem, Fitness, Objective, Organism, Setting};
+use core::cmp::Ordering;
+
+/// An interface for organisms that must satisfy constraints, which are kept
+/// separate from their fitness.
+///
+/// Constraints are taken into account by the [`ConstrainedTournament`]
+/// selection strategy, and infeasible children can be repaired as they are
+/// bred by enabling [`EcosystemBuilder::repair`].
+///
+/// [`ConstrainedTournament`]: selection/struct.ConstrainedTournament.html
+/// [`EcosystemBuilder::repair`]: struct.EcosystemBuilder.html#method.repair
+pub trait Constrained {
+    /// Returns how badly the organism violates its constraints, such as the
+    /// sum of the amounts by which each is exceeded. Feasible organisms
+    /// return zero.
+    fn violations(&self) -> f64;
+
+    /// Modifies the organism so that it violates its constraints less, if
+    /// possible. This does nothing by default.
+    fn repair(&mut self) {}
+}
+
+/// How constraint violations affect the comparison of organisms.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ConstraintHandling {
+    /// The organism's violations, multiplied by the given weight, are
+    /// deducted from its fitness (or added to it, when minimising). Fitness
+    /// values are compared through their scalar view.
+    Penalty(f64),
+    /// Feasible organisms always beat infeasible ones; feasible organisms
+    /// are then compared by fitness, and infeasible ones by their
+    /// violations.
+    FeasibilityFirst,
+}
+
+impl ConstraintHandling {
+    /// Compares two organisms by their fitness values and violations, with
+    /// `Ordering::Greater` meaning that `a` is better than `b`.
+    pub fn compare<F: Fitness>(
+        self,
+        objective: Objective,
+        (fitness_a, violations_a): (F, f64),
+        (fitness_b, violations_b): (F, f64),
+    ) -> Ordering {
+        match self {
+            ConstraintHandling::Penalty(weight) => {
+                let penalized = |fitness: F, violations: f64| match objective {
+                    Objective::Maximize => fitness.to_f64() - weight * violations,
+                    Objective::Minimize => fitness.to_f64() + weight * violations,
+                };
+                objective.compare(
+                    penalized(fitness_a, violations_a),
+                    penalized(fitness_b, violations_b),
+                )
+            }
+            ConstraintHandling::FeasibilityFirst => {
+                match (violations_a > 0.0, violations_b > 0.0) {
+                    (false, false) => objective.compare(fitness_a, fitness_b),
+                    (false, true) => Ordering::Greater,
+                    (true, false) => Ordering::Less,
+                    (true, true) => Objective::Minimize.compare(violations_a, violations_b),
+                }
+            }
+        }
+    }
+}
+
+impl<O: Organism + Constrained + Send + Sync> Ecosystem<O> {
+    /// Has every child [repaired] after it is bred and mutated, as with
+    /// [`EcosystemBuilder::repair`]. This is useful for restoring the setting
+    /// after resuming from a checkpoint.
+    ///
+    /// [repaired]: trait.Constrained.html#method.repair
+    /// [`EcosystemBuilder::repair`]: struct.EcosystemBuilder.html#method.repair
+    pub fn enable_repair(&mut self) {
+        self.repair = Some(O::repair);
+        self.restored(Setting::Repair);
+    }
+}
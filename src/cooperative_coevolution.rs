@@ -0,0 +1,414 @@
+use crate::parallel::*;
+use crate::{
+    genome::RealVector,
+    history::History,
+    termination::{RunSummary, Search, Termination},
+    Objective, RealFunction, Statistics,
+};
+use rand::{seq::index, Rng, SeedableRng};
+use rand_pcg::Pcg64;
+use std::{cmp::Ordering, ops::Range};
+
+/// Cooperative coevolution, which optimises functions of many real-valued
+/// variables by splitting them into subcomponents, each evolved in its own
+/// subpopulation.
+///
+/// The variables are divided into contiguous groups of (nearly) equal size.
+/// A member of a subpopulation only holds the values of its own group, so it
+/// is evaluated by inserting those values into the context vector: the best
+/// complete solution found so far, assembled from the representatives of
+/// every subpopulation. Whenever a member beats the context vector, it
+/// becomes its subpopulation's representative.
+///
+/// In every generation, the subpopulations take turns to evolve for a few
+/// generations of differential evolution (DE/rand/1/bin), each against the
+/// context vector as left by the ones before it. A subpopulation is
+/// evaluated again at the start of its turn if the context vector has
+/// changed since its last one. As each subpopulation only searches a
+/// handful of dimensions, this makes problems with hundreds or thousands of
+/// variables tractable, particularly where the variables interact little.
+///
+/// The context vector and subpopulations are created within the given
+/// bounds when the first generation is bred. By default, each subpopulation
+/// holds twenty members and evolves for five generations in each turn, the
+/// differential weight (F) and crossover rate (CR) are both 0.5, fitness
+/// values are maximised, and the random number generator is seeded from the
+/// operating system. The members of each subpopulation are evaluated in
+/// parallel.
+///
+/// # Examples
+///
+/// ```rust
+/// use ecosystem::{benchmarks::Function, CooperativeCoevolution, Objective};
+///
+/// // A thousand variables, in a hundred subcomponents of ten
+/// let function = Function::Sphere;
+/// let bounds = vec![function.bounds(); 1000];
+/// let mut cc = CooperativeCoevolution::new(bounds, 100, move |point| function.evaluate(point))
+///     .objective(Objective::Minimize)
+///     .seed(0);
+/// cc.next_generation();
+/// let initial = cc.best_fitness().unwrap();
+/// for _ in 0..19 {
+///     cc.next_generation();
+/// }
+///
+/// assert_eq!(cc.best().unwrap().len(), 1000);
+/// assert!(cc.best_fitness().unwrap() < initial / 1e4);
+/// ```
+pub struct CooperativeCoevolution {
+    fitness: RealFunction,
+    bounds: Vec<(f64, f64)>,
+    groups: Vec<Range<usize>>,
+    subpopulation_size: usize,
+    generations_per_turn: usize,
+    differential_weight: f64,
+    crossover_rate: f64,
+    objective: Objective,
+    rng: Pcg64,
+    context: Vec<f64>,
+    context_fitness: f64,
+    revision: u64,
+    subpopulations: Vec<Vec<Vec<f64>>>,
+    fitnesses: Vec<Vec<f64>>,
+    evaluated_at: Vec<u64>,
+    history: Option<History>,
+    generation: u32,
+    evaluations: u64,
+}
+
+impl CooperativeCoevolution {
+    /// Creates a search for the best input to `fitness` among vectors within
+    /// `bounds`, given as a (lower, upper) pair for each variable, which are
+    /// split into the given number of subcomponents.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bounds` is empty, if any bound is infinite or any lower
+    /// bound is greater than its upper bound, or if `subcomponents` is zero
+    /// or greater than the number of variables.
+    pub fn new<F>(bounds: Vec<(f64, f64)>, subcomponents: usize, fitness: F) -> Self
+    where
+        F: Fn(&[f64]) -> f64 + Send + Sync + 'static,
+    {
+        assert!(!bounds.is_empty(), "there must be at least one variable");
+        assert!(
+            bounds
+                .iter()
+                .all(|(lower, upper)| lower.is_finite() && upper.is_finite() && lower <= upper),
+            "the bounds must be finite, with no lower bound greater than its upper bound"
+        );
+        assert!(
+            subcomponents >= 1 && subcomponents <= bounds.len(),
+            "there must be between one subcomponent and one per variable"
+        );
+        let dimensions = bounds.len();
+        let groups = (0..subcomponents)
+            .map(|group| {
+                group * dimensions / subcomponents..(group + 1) * dimensions / subcomponents
+            })
+            .collect();
+        Self {
+            fitness: Box::new(fitness),
+            bounds,
+            groups,
+            subpopulation_size: 20,
+            generations_per_turn: 5,
+            differential_weight: 0.5,
+            crossover_rate: 0.5,
+            objective: Objective::default(),
+            rng: Pcg64::from_entropy(),
+            context: Vec::new(),
+            context_fitness: 0.0,
+            revision: 0,
+            subpopulations: Vec::new(),
+            fitnesses: Vec::new(),
+            evaluated_at: Vec::new(),
+            history: None,
+            generation: 0,
+            evaluations: 0,
+        }
+    }
+
+    /// Sets the number of members in each subpopulation.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `size` is less than four, the fewest that differential
+    /// evolution can work with.
+    pub fn subpopulation_size(mut self, size: usize) -> Self {
+        assert!(
+            size >= 4,
+            "each subpopulation must hold at least four members"
+        );
+        self.subpopulation_size = size;
+        self
+    }
+
+    /// Sets the number of generations that each subpopulation evolves for
+    /// in its turn.
+    pub fn generations_per_turn(mut self, generations: usize) -> Self {
+        self.generations_per_turn = generations;
+        self
+    }
+
+    /// Sets the differential weight (F), by which the difference between two
+    /// members is scaled when building a mutant.
+    pub fn differential_weight(mut self, weight: f64) -> Self {
+        self.differential_weight = weight;
+        self
+    }
+
+    /// Sets the crossover rate (CR), the probability that each value of a
+    /// trial member is taken from the mutant.
+    pub fn crossover_rate(mut self, rate: f64) -> Self {
+        self.crossover_rate = rate;
+        self
+    }
+
+    /// Sets the direction in which fitness values are optimised.
+    pub fn objective(mut self, objective: Objective) -> Self {
+        self.objective = objective;
+        self
+    }
+
+    /// Records the statistics of every generation, which can then be
+    /// inspected with [`history`].
+    ///
+    /// [`history`]: #method.history
+    pub fn record_history(mut self) -> Self {
+        self.history = Some(History::new());
+        self
+    }
+
+    /// Seeds the random number generator, making the search reproducible.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.rng = Pcg64::seed_from_u64(seed);
+        self
+    }
+
+    /// Returns the number of variables being optimised.
+    pub fn dimensions(&self) -> usize {
+        self.bounds.len()
+    }
+
+    /// Returns the ranges of the variables that make up each subcomponent.
+    pub fn subcomponents(&self) -> &[Range<usize>] {
+        &self.groups
+    }
+
+    /// Returns the current generation number.
+    pub fn generation(&self) -> u32 {
+        self.generation
+    }
+
+    /// Returns the total number of fitness evaluations performed so far.
+    pub fn evaluations(&self) -> u64 {
+        self.evaluations
+    }
+
+    /// Returns the context vector, the best solution found so far, or `None`
+    /// before the first generation.
+    pub fn best(&self) -> Option<&[f64]> {
+        if self.context.is_empty() {
+            None
+        } else {
+            Some(&self.context)
+        }
+    }
+
+    /// Returns the fitness of the context vector, or `None` before the first
+    /// generation.
+    pub fn best_fitness(&self) -> Option<f64> {
+        self.best().map(|_| self.context_fitness)
+    }
+
+    /// Summarises the fitness values of the members of every subpopulation,
+    /// or returns `None` before the first generation.
+    pub fn statistics(&self) -> Option<Statistics> {
+        Statistics::from_fitnesses(self.fitnesses.concat())
+    }
+
+    /// Returns the recorded history of the search, if recording was enabled
+    /// with [`record_history`].
+    ///
+    /// [`record_history`]: #method.record_history
+    pub fn history(&self) -> Option<&History> {
+        self.history.as_ref()
+    }
+
+    /// Breeds the next generation of each subpopulation in turn. The context
+    /// vector and subpopulations are created first, if they have not been
+    /// already.
+    pub fn next_generation(&mut self) {
+        if self.context.is_empty() {
+            self.context = RealVector::random(&self.bounds, &mut self.rng)
+                .genes()
+                .to_vec();
+            self.context_fitness = (self.fitness)(&self.context);
+            self.evaluations += 1;
+            for component in 0..self.groups.len() {
+                let bounds = &self.bounds[self.groups[component].clone()];
+                // The representative is a member of its subpopulation from
+                // the start
+                let mut members = vec![self.context[self.groups[component].clone()].to_vec()];
+                for _ in 1..self.subpopulation_size {
+                    members.push(RealVector::random(bounds, &mut self.rng).genes().to_vec());
+                }
+                let mut fitnesses = vec![self.context_fitness];
+                fitnesses.extend(self.evaluate(component, &members[1..]));
+                self.subpopulations.push(members);
+                self.fitnesses.push(fitnesses);
+                self.evaluated_at.push(self.revision);
+            }
+        }
+
+        for component in 0..self.groups.len() {
+            // Fitness values only compare fairly against the same context
+            // vector, so the subpopulation is evaluated afresh if the others
+            // have changed it since its last turn
+            if self.evaluated_at[component] != self.revision {
+                let members = std::mem::take(&mut self.subpopulations[component]);
+                self.fitnesses[component] = self.evaluate(component, &members);
+                self.subpopulations[component] = members;
+            }
+            for _ in 0..self.generations_per_turn {
+                let trials: Vec<_> = (0..self.subpopulation_size)
+                    .map(|target| self.trial(component, target))
+                    .collect();
+                let fitnesses = self.evaluate(component, &trials);
+                let objective = self.objective;
+                for (target, (trial, fitness)) in trials.into_iter().zip(fitnesses).enumerate() {
+                    if objective.compare(fitness, self.fitnesses[component][target])
+                        != Ordering::Less
+                    {
+                        self.subpopulations[component][target] = trial;
+                        self.fitnesses[component][target] = fitness;
+                    }
+                }
+            }
+            self.evaluated_at[component] = self.revision;
+        }
+
+        self.generation += 1;
+        if let Some(statistics) = self.statistics() {
+            if let Some(history) = &mut self.history {
+                history.record(self.generation, statistics, self.objective);
+            }
+        }
+    }
+
+    /// Runs the search until the given termination condition is met, which
+    /// is checked before the first generation and after each generation.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ecosystem::{
+    ///     benchmarks::Function,
+    ///     termination::{MaxGenerations, StopReason, TargetFitness},
+    ///     CooperativeCoevolution, Objective,
+    /// };
+    ///
+    /// // Rastrigin's variables are independent, so they can be evolved in
+    /// // small groups
+    /// let function = Function::Rastrigin;
+    /// let bounds = vec![function.bounds(); 100];
+    /// let mut cc = CooperativeCoevolution::new(bounds, 20, move |point| function.evaluate(point))
+    ///     .objective(Objective::Minimize)
+    ///     .seed(0);
+    /// let summary = cc.run(TargetFitness(1e-6).or(MaxGenerations(200)));
+    ///
+    /// assert_eq!(summary.reason, StopReason::TargetFitness);
+    /// assert!(cc.best().unwrap().iter().all(|x| x.abs() < 1e-3));
+    /// ```
+    pub fn run<T: Termination<Self>>(&mut self, mut termination: T) -> RunSummary {
+        let (start_generation, start_evaluations) = (self.generation, self.evaluations);
+        let reason = loop {
+            if let Some(reason) = termination.check(self) {
+                break reason;
+            }
+            self.next_generation();
+        };
+        RunSummary {
+            reason,
+            generations: self.generation - start_generation,
+            evaluations: self.evaluations - start_evaluations,
+        }
+    }
+
+    /// Builds the trial member for the given target, by crossing it with a
+    /// mutant built from three other members of its subpopulation.
+    fn trial(&mut self, component: usize, target: usize) -> Vec<f64> {
+        let (members, rng) = (&self.subpopulations[component], &mut self.rng);
+        let mut others = index::sample(rng, members.len() - 1, 3)
+            .into_iter()
+            .map(|index| if index >= target { index + 1 } else { index });
+        let (base, first, second) = (
+            &members[others.next().unwrap()],
+            &members[others.next().unwrap()],
+            &members[others.next().unwrap()],
+        );
+
+        let bounds = &self.bounds[self.groups[component].clone()];
+        let mut trial = members[target].clone();
+        let forced = rng.gen_range(0, trial.len());
+        let rate = self.crossover_rate.clamp(0.0, 1.0);
+        for (value, (&(lower, upper), gene)) in trial.iter_mut().zip(bounds.iter().zip(0..)) {
+            if gene == forced || rng.gen_bool(rate) {
+                let mutant = base[gene] + self.differential_weight * (first[gene] - second[gene]);
+                *value = mutant.clamp(lower, upper);
+            }
+        }
+        trial
+    }
+
+    /// Evaluates the given members of a subpopulation in parallel, each
+    /// inserted into the context vector, which the best of them replaces if
+    /// it is fitter.
+    fn evaluate(&mut self, component: usize, members: &[Vec<f64>]) -> Vec<f64> {
+        self.evaluations += members.len() as u64;
+        let group = self.groups[component].clone();
+        let (fitness, context) = (&self.fitness, &self.context);
+        let fitnesses: Vec<f64> = members
+            .par_iter()
+            .map(|member| {
+                let mut point = context.clone();
+                point[group.clone()].copy_from_slice(member);
+                fitness(&point)
+            })
+            .collect();
+
+        let objective = self.objective;
+        if let Some(best) =
+            (0..fitnesses.len()).max_by(|&a, &b| objective.compare(fitnesses[a], fitnesses[b]))
+        {
+            if objective.compare(fitnesses[best], self.context_fitness) == Ordering::Greater {
+                self.context[group].copy_from_slice(&members[best]);
+                self.context_fitness = fitnesses[best];
+                self.revision += 1;
+            }
+        }
+        fitnesses
+    }
+}
+
+impl Search for CooperativeCoevolution {
+    type Fitness = f64;
+
+    fn generation(&self) -> u32 {
+        self.generation
+    }
+
+    fn evaluations(&self) -> u64 {
+        self.evaluations
+    }
+
+    fn objective(&self) -> Objective {
+        self.objective
+    }
+
+    fn best_fitness(&self) -> Option<f64> {
+        self.best_fitness()
+    }
+}
@@ -0,0 +1,371 @@
+use crate::parallel::*;
+use crate::{
+    genome::RealVector,
+    history::History,
+    termination::{RunSummary, Search, Termination},
+    Objective, RealFunction, Statistics,
+};
+use rand::{seq::index, Rng, SeedableRng};
+use rand_pcg::Pcg64;
+use std::cmp::Ordering;
+
+/// The way in which differential evolution builds the mutant vector that each
+/// trial vector is crossed with, written in the usual `DE/x/y/z` notation.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DeStrategy {
+    /// DE/rand/1/bin: a random vector plus the scaled difference between two
+    /// others, with binomial crossover. This explores widely and is the
+    /// default.
+    #[default]
+    Rand1Bin,
+    /// DE/best/1/bin: the best vector of the generation plus the scaled
+    /// difference between two others, with binomial crossover. This
+    /// converges faster, but is more likely to get stuck in a local optimum,
+    /// and needs a larger differential weight (such as 0.8) to keep the
+    /// population from collapsing onto the best vector.
+    Best1Bin,
+}
+
+/// Differential evolution (DE), which optimises functions of real-valued
+/// vectors by adding scaled differences between members of the population to
+/// other members.
+///
+/// In every generation, each member of the population (the target) competes
+/// with a trial vector. The trial vector takes each of its genes from a
+/// mutant vector, built according to the [`DeStrategy`], with a probability
+/// of the crossover rate, and from the target otherwise (though at least one
+/// gene always comes from the mutant). Whichever of the two vectors is
+/// fitter survives into the next generation, the trial vector winning ties.
+/// As the population converges, the differences between its members shrink,
+/// so the search narrows by itself.
+///
+/// The population is created within the given bounds, which every vector
+/// then keeps to, when the first generation is bred. By default, the
+/// strategy is DE/rand/1/bin with a differential weight (F) of 0.5 and a
+/// crossover rate (CR) of 0.9, fitness values are maximised, and the random
+/// number generator is seeded from the operating system. Trial vectors are
+/// evaluated in parallel.
+///
+/// [`DeStrategy`]: enum.DeStrategy.html
+///
+/// # Examples
+///
+/// ```rust
+/// use ecosystem::{
+///     benchmarks::Function,
+///     termination::{MaxGenerations, StopReason, TargetFitness},
+///     DeStrategy, DifferentialEvolution, Objective,
+/// };
+///
+/// let function = Function::Rastrigin;
+/// let bounds = vec![function.bounds(); 5];
+/// let mut de = DifferentialEvolution::new(bounds, 50, move |point| function.evaluate(point))
+///     .strategy(DeStrategy::Rand1Bin)
+///     .differential_weight(0.5)
+///     .crossover_rate(0.2)
+///     .objective(Objective::Minimize)
+///     .seed(0);
+/// let summary = de.run(TargetFitness(1e-6).or(MaxGenerations(2000)));
+///
+/// assert_eq!(summary.reason, StopReason::TargetFitness);
+/// assert!(de.best().unwrap().genes().iter().all(|x| x.abs() < 1e-3));
+/// ```
+pub struct DifferentialEvolution {
+    fitness: RealFunction,
+    bounds: Vec<(f64, f64)>,
+    population_size: usize,
+    strategy: DeStrategy,
+    differential_weight: f64,
+    crossover_rate: f64,
+    objective: Objective,
+    rng: Pcg64,
+    population: Vec<RealVector>,
+    fitnesses: Vec<f64>,
+    history: Option<History>,
+    generation: u32,
+    evaluations: u64,
+}
+
+impl DifferentialEvolution {
+    /// Creates a search for the best input to `fitness` among vectors within
+    /// `bounds`, given as a (lower, upper) pair for each gene, with a
+    /// population of `population_size` vectors.
+    ///
+    /// A population of five to ten times the number of genes is a common
+    /// choice.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bounds` is empty, if any bound is infinite or any lower
+    /// bound is greater than its upper bound, or if `population_size` is
+    /// less than four.
+    pub fn new<F>(bounds: Vec<(f64, f64)>, population_size: usize, fitness: F) -> Self
+    where
+        F: Fn(&[f64]) -> f64 + Send + Sync + 'static,
+    {
+        assert!(!bounds.is_empty(), "there must be at least one gene");
+        assert!(
+            bounds
+                .iter()
+                .all(|(lower, upper)| lower.is_finite() && upper.is_finite() && lower <= upper),
+            "the bounds must be finite, with no lower bound greater than its upper bound"
+        );
+        assert!(
+            population_size >= 4,
+            "the population must hold at least four vectors"
+        );
+        Self {
+            fitness: Box::new(fitness),
+            bounds,
+            population_size,
+            strategy: DeStrategy::default(),
+            differential_weight: 0.5,
+            crossover_rate: 0.9,
+            objective: Objective::default(),
+            rng: Pcg64::from_entropy(),
+            population: Vec::new(),
+            fitnesses: Vec::new(),
+            history: None,
+            generation: 0,
+            evaluations: 0,
+        }
+    }
+
+    /// Sets the strategy by which mutant vectors are built.
+    pub fn strategy(mut self, strategy: DeStrategy) -> Self {
+        self.strategy = strategy;
+        self
+    }
+
+    /// Sets the differential weight (F), by which the difference between two
+    /// vectors is scaled when building a mutant vector. Values usually lie
+    /// between 0.4 and 1.
+    pub fn differential_weight(mut self, weight: f64) -> Self {
+        self.differential_weight = weight;
+        self
+    }
+
+    /// Sets the crossover rate (CR), the probability that each gene of a
+    /// trial vector is taken from the mutant vector. Low values suit
+    /// functions whose variables are independent, such as Rastrigin's, and
+    /// high values suit functions whose variables interact.
+    pub fn crossover_rate(mut self, rate: f64) -> Self {
+        self.crossover_rate = rate;
+        self
+    }
+
+    /// Sets the direction in which fitness values are optimised.
+    pub fn objective(mut self, objective: Objective) -> Self {
+        self.objective = objective;
+        self
+    }
+
+    /// Records the statistics of every generation, which can then be
+    /// inspected with [`history`].
+    ///
+    /// [`history`]: #method.history
+    pub fn record_history(mut self) -> Self {
+        self.history = Some(History::new());
+        self
+    }
+
+    /// Seeds the random number generator, making the search reproducible.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.rng = Pcg64::seed_from_u64(seed);
+        self
+    }
+
+    /// Returns the vectors of the current generation, which is empty until
+    /// the first generation is bred.
+    pub fn population(&self) -> &[RealVector] {
+        &self.population
+    }
+
+    /// Returns the current generation number.
+    pub fn generation(&self) -> u32 {
+        self.generation
+    }
+
+    /// Returns the total number of fitness evaluations performed so far.
+    pub fn evaluations(&self) -> u64 {
+        self.evaluations
+    }
+
+    /// Returns the fittest vector in the population, or `None` before the
+    /// first generation.
+    ///
+    /// As a vector only ever gives way to a fitter one, this is also the
+    /// fittest vector found so far.
+    pub fn best(&self) -> Option<&RealVector> {
+        self.fittest_index().map(|index| &self.population[index])
+    }
+
+    /// Returns the fitness of the fittest vector in the population, or
+    /// `None` before the first generation.
+    pub fn best_fitness(&self) -> Option<f64> {
+        self.fittest_index().map(|index| self.fitnesses[index])
+    }
+
+    /// Summarises the fitness values of the current generation, or returns
+    /// `None` before the first generation.
+    pub fn statistics(&self) -> Option<Statistics> {
+        Statistics::from_fitnesses(self.fitnesses.clone())
+    }
+
+    /// Returns the recorded history of the search, if recording was enabled
+    /// with [`record_history`].
+    ///
+    /// [`record_history`]: #method.record_history
+    pub fn history(&self) -> Option<&History> {
+        self.history.as_ref()
+    }
+
+    /// Breeds the next generation, pitting each vector against a trial
+    /// vector. The population is created first, if it has not been already.
+    pub fn next_generation(&mut self) {
+        if self.population.is_empty() {
+            let (bounds, rng) = (&self.bounds, &mut self.rng);
+            let population: Vec<_> = (0..self.population_size)
+                .map(|_| RealVector::random(bounds, rng))
+                .collect();
+            self.fitnesses = self.evaluate(&population);
+            self.population = population;
+        }
+
+        let best = self.fittest_index().unwrap_or(0);
+        let trials: Vec<RealVector> = (0..self.population.len())
+            .map(|target| self.trial(target, best))
+            .collect();
+        let fitnesses = self.evaluate(&trials);
+
+        let objective = self.objective;
+        for (target, (trial, fitness)) in trials.into_iter().zip(fitnesses).enumerate() {
+            if objective.compare(fitness, self.fitnesses[target]) != Ordering::Less {
+                self.population[target] = trial;
+                self.fitnesses[target] = fitness;
+            }
+        }
+
+        self.generation += 1;
+        if let Some(statistics) = self.statistics() {
+            if let Some(history) = &mut self.history {
+                history.record(self.generation, statistics, objective);
+            }
+        }
+    }
+
+    /// Runs the search until the given termination condition is met, which
+    /// is checked before the first generation and after each generation.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ecosystem::{
+    ///     benchmarks::Function,
+    ///     termination::{MaxGenerations, StopReason},
+    ///     DeStrategy, DifferentialEvolution, Objective,
+    /// };
+    ///
+    /// let function = Function::Rosenbrock;
+    /// let bounds = vec![function.bounds(); 4];
+    /// let mut de = DifferentialEvolution::new(bounds, 40, move |point| function.evaluate(point))
+    ///     .strategy(DeStrategy::Best1Bin)
+    ///     .differential_weight(0.8)
+    ///     .objective(Objective::Minimize)
+    ///     .record_history()
+    ///     .seed(0);
+    /// let summary = de.run(MaxGenerations(500));
+    ///
+    /// assert_eq!(summary.reason, StopReason::MaxGenerations);
+    /// // The initial population is evaluated along with every trial vector
+    /// assert_eq!(summary.evaluations, 40 + 500 * 40);
+    /// assert_eq!(de.history().unwrap().records().len(), 500);
+    /// assert!(de.best_fitness().unwrap() < 1e-6);
+    /// ```
+    pub fn run<T: Termination<Self>>(&mut self, mut termination: T) -> RunSummary {
+        let (start_generation, start_evaluations) = (self.generation, self.evaluations);
+        let reason = loop {
+            if let Some(reason) = termination.check(self) {
+                break reason;
+            }
+            self.next_generation();
+        };
+        RunSummary {
+            reason,
+            generations: self.generation - start_generation,
+            evaluations: self.evaluations - start_evaluations,
+        }
+    }
+
+    /// Builds the trial vector for the given target, by crossing it with a
+    /// mutant vector.
+    fn trial(&mut self, target: usize, best: usize) -> RealVector {
+        // Three distinct vectors other than the target, of which the
+        // best/1 strategy only needs two
+        let mut others = index::sample(&mut self.rng, self.population.len() - 1, 3)
+            .into_iter()
+            .map(|index| if index >= target { index + 1 } else { index });
+        let (base, first, second) = match self.strategy {
+            DeStrategy::Rand1Bin => (
+                others.next().unwrap(),
+                others.next().unwrap(),
+                others.next().unwrap(),
+            ),
+            DeStrategy::Best1Bin => (best, others.next().unwrap(), others.next().unwrap()),
+        };
+        let (base, first, second) = (
+            self.population[base].genes(),
+            self.population[first].genes(),
+            self.population[second].genes(),
+        );
+
+        let mut trial = self.population[target].clone();
+        let forced = self.rng.gen_range(0, trial.len());
+        let rate = self.crossover_rate.clamp(0.0, 1.0);
+        for gene in 0..trial.len() {
+            if gene == forced || self.rng.gen_bool(rate) {
+                let mutant = base[gene] + self.differential_weight * (first[gene] - second[gene]);
+                trial.set(gene, mutant);
+            }
+        }
+        trial
+    }
+
+    /// Evaluates the given vectors in parallel, keeping count of the number
+    /// of evaluations performed.
+    fn evaluate(&mut self, vectors: &[RealVector]) -> Vec<f64> {
+        self.evaluations += vectors.len() as u64;
+        let fitness = &self.fitness;
+        vectors
+            .par_iter()
+            .map(|vector| fitness(vector.genes()))
+            .collect()
+    }
+
+    fn fittest_index(&self) -> Option<usize> {
+        let objective = self.objective;
+        (0..self.fitnesses.len())
+            .max_by(|&a, &b| objective.compare(self.fitnesses[a], self.fitnesses[b]))
+    }
+}
+
+impl Search for DifferentialEvolution {
+    type Fitness = f64;
+
+    fn generation(&self) -> u32 {
+        self.generation
+    }
+
+    fn evaluations(&self) -> u64 {
+        self.evaluations
+    }
+
+    fn objective(&self) -> Objective {
+        self.objective
+    }
+
+    fn best_fitness(&self) -> Option<f64> {
+        self.best_fitness()
+    }
+}
@@ -0,0 +1,325 @@
+//! Fitness evaluation farmed out to remote worker processes.
+//!
+//! A [`Worker`] listens for batches of organisms over TCP, evaluates them
+//! with their [`fitness`] methods, and sends back the results. A
+//! [`WorkerPool`] is a [`BatchEvaluator`] that splits each batch between a
+//! set of workers, so that an ecosystem on one machine can evaluate its
+//! population on many.
+//!
+//! Every message, in either direction, is a four-byte big-endian length
+//! followed by that many bytes of [bincode]: a list of organisms from the
+//! pool, and a list of their fitness values in the same order from the
+//! worker. A connection may carry any number of such exchanges.
+//!
+//! [`Worker`]: struct.Worker.html
+//! [`WorkerPool`]: struct.WorkerPool.html
+//! [`BatchEvaluator`]: ../evaluation/trait.BatchEvaluator.html
+//! [`fitness`]: ../trait.Organism.html#tymethod.fitness
+//! [bincode]: https://docs.rs/bincode
+use crate::parallel::*;
+use crate::{evaluation::BatchEvaluator, Organism};
+use serde::{de::DeserializeOwned, Serialize};
+use std::{
+    convert::TryFrom,
+    io::{self, BufReader, BufWriter, Read, Write},
+    net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs},
+    thread,
+    time::Duration,
+};
+
+/// The largest message that will be read, to guard against a corrupt length
+/// prefix.
+const MAX_MESSAGE_LEN: u32 = 1 << 30;
+
+/// A worker process, serving evaluation requests from [`WorkerPool`]s.
+///
+/// [`WorkerPool`]: struct.WorkerPool.html
+#[derive(Debug)]
+pub struct Worker {
+    listener: TcpListener,
+}
+
+impl Worker {
+    /// Creates a worker listening on the given address.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the address cannot be bound.
+    pub fn bind(address: impl ToSocketAddrs) -> io::Result<Self> {
+        Ok(Self {
+            listener: TcpListener::bind(address)?,
+        })
+    }
+
+    /// Returns the address that the worker is listening on.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the address cannot be read from the
+    /// underlying socket.
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// Serves evaluation requests until accepting a connection fails,
+    /// handling each connection on its own thread and evaluating the
+    /// organisms of each request in parallel.
+    ///
+    /// A connection that sends a malformed request is closed, without
+    /// affecting the others.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if accepting a connection fails.
+    pub fn serve<O>(&self) -> io::Result<()>
+    where
+        O: Organism + DeserializeOwned + Send + Sync + 'static,
+        O::Fitness: Serialize,
+    {
+        for stream in self.listener.incoming() {
+            let stream = stream?;
+            thread::spawn(move || {
+                let _ = handle_connection::<O>(stream);
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Answers requests on a connection until it is closed.
+fn handle_connection<O>(stream: TcpStream) -> io::Result<()>
+where
+    O: Organism + DeserializeOwned + Send + Sync,
+    O::Fitness: Serialize,
+{
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut writer = BufWriter::new(stream);
+    loop {
+        let organisms: Vec<O> = match read_message(&mut reader) {
+            Ok(organisms) => organisms,
+            Err(error) if error.kind() == io::ErrorKind::UnexpectedEof => return Ok(()),
+            Err(error) => return Err(error),
+        };
+        let fitnesses: Vec<O::Fitness> = organisms.par_iter().map(O::fitness).collect();
+        write_message(&mut writer, &fitnesses)?;
+    }
+}
+
+/// A pool of remote [`Worker`]s, used to evaluate fitness with
+/// [`EcosystemBuilder::batch_evaluator`].
+///
+/// Each batch is split into one request per worker, which are sent
+/// concurrently. A request that fails (because the worker cannot be reached,
+/// does not answer within the timeout, or answers with something other than
+/// the right number of fitness values) is retried on the next worker in the
+/// pool, up to the configured number of retries.
+///
+/// [`Worker`]: struct.Worker.html
+/// [`EcosystemBuilder::batch_evaluator`]: ../struct.EcosystemBuilder.html#method.batch_evaluator
+///
+/// # Examples
+///
+/// ```rust
+/// use ecosystem::{
+///     distributed::{Worker, WorkerPool},
+///     Ecosystem, Organism,
+/// };
+/// use serde::{Deserialize, Serialize};
+/// use std::thread;
+/// # use rand::Rng;
+///
+/// #[derive(Serialize, Deserialize)]
+/// struct Guess(f64);
+/// # impl Organism for Guess {
+/// #     type Fitness = f64;
+/// #     fn fitness(&self) -> f64 { 1.0 / (42.0 - self.0).abs() }
+/// #     fn breed(&self, other: &Self, rng: &mut impl Rng) -> Self { Guess((self.0 + other.0) / 2.0) }
+/// #     fn mutate(&mut self, rate: f64, rng: &mut impl Rng) {
+/// #         self.0 += rng.gen_range(-rate, rate);
+/// #     }
+/// # }
+///
+/// // Each worker would usually be a separate process on another machine
+/// let mut addresses = Vec::new();
+/// for _ in 0..2 {
+///     let worker = Worker::bind("127.0.0.1:0")?;
+///     addresses.push(worker.local_addr()?);
+///     thread::spawn(move || worker.serve::<Guess>());
+/// }
+/// // A worker that has gone down, whose share is retried on another
+/// let dead = Worker::bind("127.0.0.1:0")?;
+/// addresses.push(dead.local_addr()?);
+/// drop(dead);
+///
+/// let mut ecosystem = Ecosystem::builder()
+///     .organisms((0..20).map(|i| Guess(f64::from(i) * 5.0 + 0.5)))
+///     .batch_evaluator(WorkerPool::new(addresses).retries(2))
+///     .build()
+///     .unwrap();
+/// for _ in 0..10 {
+///     ecosystem.breed_next_generation()?;
+/// }
+///
+/// let best = ecosystem.fittest().unwrap();
+/// assert!((42.0 - best.0).abs() < 2.5);
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Clone, Debug)]
+pub struct WorkerPool {
+    addresses: Vec<SocketAddr>,
+    timeout: Duration,
+    retries: usize,
+}
+
+impl WorkerPool {
+    /// Creates a pool of the workers at the given addresses, with a timeout
+    /// of a minute and a single retry.
+    ///
+    /// # Panics
+    ///
+    /// Panics if no addresses are given.
+    pub fn new(addresses: Vec<SocketAddr>) -> Self {
+        assert!(
+            !addresses.is_empty(),
+            "a worker pool needs at least one worker"
+        );
+        Self {
+            addresses,
+            timeout: Duration::from_secs(60),
+            retries: 1,
+        }
+    }
+
+    /// Sets how long to wait for a worker to connect, accept a request, or
+    /// answer one, before the request is treated as failed.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Sets how many times a failed request is retried, each time on the
+    /// next worker in the pool.
+    pub fn retries(mut self, retries: usize) -> Self {
+        self.retries = retries;
+        self
+    }
+
+    /// Returns the addresses of the workers in the pool.
+    pub fn addresses(&self) -> &[SocketAddr] {
+        &self.addresses
+    }
+
+    /// Sends a request to the worker at the given index, retrying on the
+    /// workers that follow it if it fails.
+    fn request<O>(&self, worker: usize, organisms: &[&O]) -> io::Result<Vec<O::Fitness>>
+    where
+        O: Organism + Serialize,
+        O::Fitness: DeserializeOwned,
+    {
+        let mut attempt = 0;
+        loop {
+            let address = self.addresses[(worker + attempt) % self.addresses.len()];
+            match self.exchange(address, organisms) {
+                Err(_) if attempt < self.retries => attempt += 1,
+                result => return result,
+            }
+        }
+    }
+
+    /// Sends a single request to the worker at the given address, and waits
+    /// for its answer.
+    fn exchange<O>(&self, address: SocketAddr, organisms: &[&O]) -> io::Result<Vec<O::Fitness>>
+    where
+        O: Organism + Serialize,
+        O::Fitness: DeserializeOwned,
+    {
+        let stream = TcpStream::connect_timeout(&address, self.timeout)?;
+        stream.set_read_timeout(Some(self.timeout))?;
+        stream.set_write_timeout(Some(self.timeout))?;
+        stream.set_nodelay(true)?;
+        write_message(&mut BufWriter::new(stream.try_clone()?), &organisms)?;
+        let fitnesses: Vec<O::Fitness> = read_message(&mut BufReader::new(stream))?;
+        if fitnesses.len() != organisms.len() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "the worker returned the wrong number of fitness values",
+            ));
+        }
+        Ok(fitnesses)
+    }
+}
+
+impl<O> BatchEvaluator<O> for WorkerPool
+where
+    O: Organism + Serialize + Sync,
+    O::Fitness: DeserializeOwned,
+{
+    /// Evaluates the organisms on the pool's workers.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a request still fails once its retries have been used up.
+    fn evaluate_all(&self, organisms: &[&O]) -> Vec<O::Fitness> {
+        if organisms.is_empty() {
+            return Vec::new();
+        }
+        let share = organisms.len().div_ceil(self.addresses.len());
+        let results: Vec<_> = thread::scope(|scope| {
+            let requests: Vec<_> = organisms
+                .chunks(share)
+                .enumerate()
+                .map(|(worker, chunk)| scope.spawn(move || self.request(worker, chunk)))
+                .collect();
+            requests
+                .into_iter()
+                .map(|request| {
+                    request
+                        .join()
+                        .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+                })
+                .collect()
+        });
+        results
+            .into_iter()
+            .flat_map(|result| {
+                result.unwrap_or_else(|error| panic!("distributed evaluation failed: {}", error))
+            })
+            .collect()
+    }
+}
+
+/// Writes a length-prefixed message.
+fn write_message<W: Write, T: Serialize + ?Sized>(writer: &mut W, message: &T) -> io::Result<()> {
+    let bytes = bincode::serialize(message).map_err(|error| into_io_error(*error))?;
+    let len = u32::try_from(bytes.len())
+        .ok()
+        .filter(|&len| len <= MAX_MESSAGE_LEN)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "the message is too long"))?;
+    writer.write_all(&len.to_be_bytes())?;
+    writer.write_all(&bytes)?;
+    writer.flush()
+}
+
+/// Reads a length-prefixed message.
+fn read_message<R: Read, T: DeserializeOwned>(reader: &mut R) -> io::Result<T> {
+    let mut len = [0; 4];
+    reader.read_exact(&mut len)?;
+    let len = u32::from_be_bytes(len);
+    if len > MAX_MESSAGE_LEN {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "the message is too long",
+        ));
+    }
+    let mut bytes = vec![0; len as usize];
+    reader.read_exact(&mut bytes)?;
+    bincode::deserialize(&bytes).map_err(|error| into_io_error(*error))
+}
+
+/// Converts a bincode error into the I/O error that it wraps, if any.
+fn into_io_error(error: bincode::ErrorKind) -> io::Error {
+    match error {
+        bincode::ErrorKind::Io(error) => error,
+        error => io::Error::new(io::ErrorKind::InvalidData, error),
+    }
+}
@@ -0,0 +1,218 @@
+//! Measures of how varied a population is.
+//!
+//! A population that has lost its diversity has stopped exploring, so these
+//! measures are useful for monitoring a run, for deciding when to end it
+//! (as with the [`Convergence`] condition), and for adapting operators as it
+//! progresses. Most are based on the [`Distance`] between organisms; as a
+//! `Distance` implementation provides a function of the right shape, it can
+//! also be passed wherever a strategy asks for a distance, such as to
+//! [`FitnessSharing`] or [`DiversityControl`].
+//!
+//! ```rust
+//! use ecosystem::{diversity::Distance, Ecosystem, Organism};
+//! # use rand::Rng;
+//! # struct Guess(f64);
+//! # impl Organism for Guess {
+//! #     type Fitness = f64;
+//! #     fn fitness(&self) -> f64 { 1.0 / (42.0 - self.0).abs() }
+//! #     fn breed(&self, other: &Self, rng: &mut impl Rng) -> Self { Guess((self.0 + other.0) / 2.0) }
+//! #     fn mutate(&mut self, rate: f64, rng: &mut impl Rng) {
+//! #         self.0 += rng.gen_range(-rate, rate);
+//! #     }
+//! # }
+//!
+//! impl Distance for Guess {
+//!     fn distance(&self, other: &Self) -> f64 {
+//!         (self.0 - other.0).abs()
+//!     }
+//! }
+//!
+//! let mut ecosystem = Ecosystem::builder()
+//!     .organisms((0..20).map(|i| Guess(f64::from(i) * 5.0 + 0.5)))
+//!     .build()
+//!     .unwrap();
+//! let initial = ecosystem.mean_distance();
+//! for _ in 0..10 {
+//!     ecosystem.breed_next_generation()?;
+//! }
+//!
+//! // The population has converged on the answer
+//! assert!(ecosystem.mean_distance() < initial / 10.0);
+//! assert!(ecosystem.nearest_neighbor_distances().iter().all(|&distance| distance < 1.0));
+//! # Ok::<(), ecosystem::EcosystemError>(())
+//! ```
+//!
+//! [`Convergence`]: ../termination/struct.Convergence.html
+//! [`Distance`]: trait.Distance.html
+//! [`FitnessSharing`]: ../selection/struct.FitnessSharing.html
+//! [`DiversityControl`]: ../mutation/struct.DiversityControl.html
+use crate::parallel::*;
+use crate::{Ecosystem, Individual, Organism};
+use alloc::vec::Vec;
+use rand::Rng;
+#[cfg(feature = "std")]
+use std::{collections::HashMap, hash::Hash};
+
+/// An interface for organisms that can be compared by how different they
+/// are.
+pub trait Distance {
+    /// Returns the distance between this organism and another, which should
+    /// be zero for identical organisms and never negative.
+    fn distance(&self, other: &Self) -> f64;
+}
+
+/// Returns the mean distance between every pair of the given organisms, or
+/// zero if there are fewer than two.
+///
+/// This takes a number of distance calculations that grows with the square
+/// of the number of organisms, though they are spread across threads.
+pub fn mean_distance<O: Distance + Sync>(organisms: &[O]) -> f64 {
+    mean_pairwise_distance(organisms, O::distance)
+}
+
+/// Estimates the mean distance between every pair of the given organisms
+/// from `samples` randomly chosen pairs, returning zero if there are fewer
+/// than two organisms.
+pub fn sampled_mean_distance<O: Distance>(
+    organisms: &[O],
+    samples: usize,
+    rng: &mut impl Rng,
+) -> f64 {
+    sampled_pairwise_distance(organisms, samples, rng, O::distance)
+}
+
+/// Returns the distance from each of the given organisms to its nearest
+/// neighbor among the others, or an empty list if there are fewer than two.
+pub fn nearest_neighbor_distances<O: Distance + Sync>(organisms: &[O]) -> Vec<f64> {
+    nearest_distances(organisms, O::distance)
+}
+
+/// Returns the Shannon entropy, in bits, of the distribution of the given
+/// keys: zero if every key is the same, rising to the base-2 logarithm of
+/// the number of keys if they are all different.
+///
+/// The keys might be the organisms themselves, or a simpler description of
+/// them, such as the value of one gene.
+///
+/// # Examples
+///
+/// ```rust
+/// use ecosystem::diversity::entropy;
+///
+/// assert_eq!(entropy(vec![1, 1, 1, 1]), 0.0);
+/// assert_eq!(entropy(vec![1, 1, 2, 2]), 1.0);
+/// assert_eq!(entropy(vec![1, 2, 3, 4]), 2.0);
+/// ```
+#[cfg(feature = "std")]
+pub fn entropy<K: Hash + Eq>(keys: impl IntoIterator<Item = K>) -> f64 {
+    let mut counts = HashMap::new();
+    let mut total = 0;
+    for key in keys {
+        *counts.entry(key).or_insert(0) += 1;
+        total += 1;
+    }
+    counts
+        .values()
+        .map(|&count| {
+            let p = f64::from(count) / f64::from(total);
+            -p * p.log2()
+        })
+        .sum::<f64>()
+        .max(0.0)
+}
+
+/// Returns the mean `distance` between every pair of the given organisms.
+pub(crate) fn mean_pairwise_distance<T: Sync>(
+    organisms: &[T],
+    distance: impl Fn(&T, &T) -> f64 + Sync,
+) -> f64 {
+    let pairs = organisms.len() * organisms.len().saturating_sub(1) / 2;
+    if pairs == 0 {
+        return 0.0;
+    }
+    let total: f64 = (0..organisms.len())
+        .into_par_iter()
+        .map(|a| {
+            organisms[a + 1..]
+                .iter()
+                .map(|b| distance(&organisms[a], b))
+                .sum::<f64>()
+        })
+        .sum();
+    total / pairs as f64
+}
+
+/// Estimates the mean `distance` between every pair of the given organisms
+/// from randomly chosen pairs.
+fn sampled_pairwise_distance<T>(
+    organisms: &[T],
+    samples: usize,
+    rng: &mut impl Rng,
+    distance: impl Fn(&T, &T) -> f64,
+) -> f64 {
+    if organisms.len() < 2 || samples == 0 {
+        return 0.0;
+    }
+    let total: f64 = (0..samples)
+        .map(|_| {
+            let a = rng.gen_range(0, organisms.len());
+            // Skipping over `a` keeps the pair distinct
+            let b = (a + rng.gen_range(1, organisms.len())) % organisms.len();
+            distance(&organisms[a], &organisms[b])
+        })
+        .sum();
+    total / samples as f64
+}
+
+/// Returns the `distance` from each organism to its nearest neighbor.
+fn nearest_distances<T: Sync>(
+    organisms: &[T],
+    distance: impl Fn(&T, &T) -> f64 + Sync,
+) -> Vec<f64> {
+    if organisms.len() < 2 {
+        return Vec::new();
+    }
+    (0..organisms.len())
+        .into_par_iter()
+        .map(|a| {
+            organisms
+                .iter()
+                .enumerate()
+                .filter(|&(b, _)| b != a)
+                .map(|(_, other)| distance(&organisms[a], other))
+                .fold(f64::INFINITY, f64::min)
+        })
+        .collect()
+}
+
+/// Measures the distance between the organisms of two individuals.
+fn individual_distance<O: Organism + Distance>(a: &Individual<O>, b: &Individual<O>) -> f64 {
+    a.organism().distance(b.organism())
+}
+
+impl<O: Organism + Distance + Send + Sync> Ecosystem<O> {
+    /// Returns the mean distance between every pair of organisms in the
+    /// population, as with [`diversity::mean_distance`].
+    ///
+    /// [`diversity::mean_distance`]: diversity/fn.mean_distance.html
+    pub fn mean_distance(&self) -> f64 {
+        mean_pairwise_distance(self.individuals(), individual_distance)
+    }
+
+    /// Estimates the mean distance between every pair of organisms in the
+    /// population from `samples` randomly chosen pairs, as with
+    /// [`diversity::sampled_mean_distance`].
+    ///
+    /// [`diversity::sampled_mean_distance`]: diversity/fn.sampled_mean_distance.html
+    pub fn sampled_mean_distance(&self, samples: usize, rng: &mut impl Rng) -> f64 {
+        sampled_pairwise_distance(self.individuals(), samples, rng, individual_distance)
+    }
+
+    /// Returns the distance from each organism in the population to its
+    /// nearest neighbor, in the same order as [`individuals`].
+    ///
+    /// [`individuals`]: #method.individuals
+    pub fn nearest_neighbor_distances(&self) -> Vec<f64> {
+        nearest_distances(self.individuals(), individual_distance)
+    }
+}
@@ -0,0 +1,199 @@
+use alloc::{string::String, vec::Vec};
+use core::{error::Error, fmt};
+
+/// An error arising while evolving an ecosystem.
+#[derive(Clone, Debug, PartialEq)]
+pub enum EcosystemError {
+    /// There are no organisms in the ecosystem.
+    EmptyPopulation,
+    /// The fitness values were unsuitable for the selection strategy, such as
+    /// negative values under fitness-proportionate selection.
+    UnsuitableFitness(String),
+    /// The tournament size was zero.
+    InvalidTournamentSize,
+    /// The selection pressure of linear ranking was outside of [1, 2].
+    InvalidSelectionPressure(f64),
+    /// The base of exponential ranking was outside of (0, 1].
+    InvalidRankBase(f64),
+    /// The sharing radius of fitness sharing was not positive.
+    InvalidSharingRadius(f64),
+    /// The tolerance of lexicase selection was negative or NaN.
+    InvalidLexicaseEpsilon(f64),
+    /// The ecosystem was resumed from a checkpoint, and the given settings,
+    /// which could not be saved in it, have been neither restored nor
+    /// discarded.
+    UnrestoredSettings(Vec<Setting>),
+}
+
+impl fmt::Display for EcosystemError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EcosystemError::EmptyPopulation => write!(f, "there are no organisms in the ecosystem"),
+            EcosystemError::UnsuitableFitness(reason) => {
+                write!(f, "unsuitable fitness values for selection: {}", reason)
+            }
+            EcosystemError::InvalidTournamentSize => {
+                write!(f, "the tournament size must be non-zero")
+            }
+            EcosystemError::InvalidSelectionPressure(pressure) => write!(
+                f,
+                "the selection pressure {} does not lie within [1, 2]",
+                pressure
+            ),
+            EcosystemError::InvalidRankBase(base) => {
+                write!(f, "the base {} does not lie within (0, 1]", base)
+            }
+            EcosystemError::InvalidSharingRadius(radius) => {
+                write!(f, "the sharing radius {} must be positive", radius)
+            }
+            EcosystemError::InvalidLexicaseEpsilon(epsilon) => {
+                write!(f, "the lexicase tolerance {} must not be negative", epsilon)
+            }
+            EcosystemError::UnrestoredSettings(settings) => {
+                write!(f, "settings lost in the checkpoint must be restored: ")?;
+                for (index, setting) in settings.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", setting)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl Error for EcosystemError {}
+
+/// A setting of an ecosystem that is made up of functions or trait objects,
+/// and so cannot be saved in a [checkpoint].
+///
+/// An ecosystem resumed from a checkpoint lists the settings it was
+/// configured with but lost in [`Ecosystem::unrestored_settings`], and
+/// refuses to breed until each has been restored by the method named below
+/// or they are all discarded with
+/// [`Ecosystem::discard_unrestored_settings`].
+///
+/// [checkpoint]: struct.Ecosystem.html#method.save_checkpoint
+/// [`Ecosystem::unrestored_settings`]: struct.Ecosystem.html#method.unrestored_settings
+/// [`Ecosystem::discard_unrestored_settings`]: struct.Ecosystem.html#method.discard_unrestored_settings
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Setting {
+    /// A selection strategy other than fitness-proportionate selection,
+    /// restored with [`Ecosystem::set_selection`].
+    ///
+    /// [`Ecosystem::set_selection`]: struct.Ecosystem.html#method.set_selection
+    Selection,
+    /// The replacement strategy, restored with [`Ecosystem::set_replacement`].
+    ///
+    /// [`Ecosystem::set_replacement`]: struct.Ecosystem.html#method.set_replacement
+    Replacement,
+    /// The archive, which keeps its organisms but takes in no more until it
+    /// is restored with [`Ecosystem::set_archive_capacity`].
+    ///
+    /// [`Ecosystem::set_archive_capacity`]: struct.Ecosystem.html#method.set_archive_capacity
+    Archive,
+    /// The observers, restored with [`Ecosystem::add_observer`].
+    ///
+    /// [`Ecosystem::add_observer`]: struct.Ecosystem.html#method.add_observer
+    Observers,
+    /// The progress reporter, restored with
+    /// [`Ecosystem::set_progress_reporter`].
+    ///
+    /// [`Ecosystem::set_progress_reporter`]: struct.Ecosystem.html#method.set_progress_reporter
+    ProgressReporter,
+    /// The mutation controller, restored with
+    /// [`Ecosystem::set_mutation_control`].
+    ///
+    /// [`Ecosystem::set_mutation_control`]: struct.Ecosystem.html#method.set_mutation_control
+    MutationControl,
+    /// The population controller, restored with
+    /// [`Ecosystem::set_population_control`].
+    ///
+    /// [`Ecosystem::set_population_control`]: struct.Ecosystem.html#method.set_population_control
+    PopulationControl,
+    /// The crossover rate, restored with [`Ecosystem::set_crossover_rate`].
+    ///
+    /// [`Ecosystem::set_crossover_rate`]: struct.Ecosystem.html#method.set_crossover_rate
+    CrossoverRate,
+    /// Variance-scaled mutation, restored with
+    /// [`Ecosystem::enable_variance_scaled_mutation`].
+    ///
+    /// [`Ecosystem::enable_variance_scaled_mutation`]: struct.Ecosystem.html#method.enable_variance_scaled_mutation
+    VarianceScaledMutation,
+    /// Deduplication of children, restored with
+    /// [`Ecosystem::enable_deduplication`] or
+    /// [`Ecosystem::set_duplicate_key`].
+    ///
+    /// [`Ecosystem::enable_deduplication`]: struct.Ecosystem.html#method.enable_deduplication
+    /// [`Ecosystem::set_duplicate_key`]: struct.Ecosystem.html#method.set_duplicate_key
+    Deduplication,
+    /// Immigration, restored with [`Ecosystem::set_immigrants`].
+    ///
+    /// [`Ecosystem::set_immigrants`]: struct.Ecosystem.html#method.set_immigrants
+    Immigrants,
+    /// A stagnation response that restarts the search, restored with
+    /// [`Ecosystem::set_stagnation_response`]. Other responses are saved.
+    ///
+    /// [`Ecosystem::set_stagnation_response`]: struct.Ecosystem.html#method.set_stagnation_response
+    StagnationResponse,
+    /// Repair of children, restored with [`Ecosystem::enable_repair`].
+    ///
+    /// [`Ecosystem::enable_repair`]: struct.Ecosystem.html#method.enable_repair
+    Repair,
+    /// Deterministic crowding, restored with
+    /// [`Ecosystem::enable_deterministic_crowding`].
+    ///
+    /// [`Ecosystem::enable_deterministic_crowding`]: struct.Ecosystem.html#method.enable_deterministic_crowding
+    DeterministicCrowding,
+    /// The local search, restored with [`Ecosystem::set_local_search`].
+    ///
+    /// [`Ecosystem::set_local_search`]: struct.Ecosystem.html#method.set_local_search
+    LocalSearch,
+    /// Baldwinian learning, restored with [`Ecosystem::set_learning`].
+    ///
+    /// [`Ecosystem::set_learning`]: struct.Ecosystem.html#method.set_learning
+    Learning,
+    /// The surrogate model, restored with [`Ecosystem::set_surrogate`].
+    ///
+    /// [`Ecosystem::set_surrogate`]: struct.Ecosystem.html#method.set_surrogate
+    Surrogate,
+    /// The batch evaluator, restored with
+    /// [`Ecosystem::set_batch_evaluator`].
+    ///
+    /// [`Ecosystem::set_batch_evaluator`]: struct.Ecosystem.html#method.set_batch_evaluator
+    BatchEvaluator,
+    /// The ecosystem's own thread pool, restored with
+    /// [`Ecosystem::set_thread_pool`].
+    ///
+    /// [`Ecosystem::set_thread_pool`]: struct.Ecosystem.html#method.set_thread_pool
+    ThreadPool,
+}
+
+impl fmt::Display for Setting {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Setting::Selection => "selection strategy",
+            Setting::Replacement => "replacement strategy",
+            Setting::Archive => "archive",
+            Setting::Observers => "observers",
+            Setting::ProgressReporter => "progress reporter",
+            Setting::MutationControl => "mutation controller",
+            Setting::PopulationControl => "population controller",
+            Setting::CrossoverRate => "crossover rate",
+            Setting::VarianceScaledMutation => "variance-scaled mutation",
+            Setting::Deduplication => "deduplication",
+            Setting::Immigrants => "immigrants",
+            Setting::StagnationResponse => "stagnation response",
+            Setting::Repair => "repair",
+            Setting::DeterministicCrowding => "deterministic crowding",
+            Setting::LocalSearch => "local search",
+            Setting::Learning => "learning",
+            Setting::Surrogate => "surrogate",
+            Setting::BatchEvaluator => "batch evaluator",
+            Setting::ThreadPool => "thread pool",
+        };
+        f.write_str(name)
+    }
+}
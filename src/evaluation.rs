@@ -0,0 +1,246 @@
+//! Alternative ways of evaluating fitness.
+//!
+//! By default, an ecosystem evaluates each organism by calling its
+//! [`fitness`] method, in parallel across the population. Some fitness
+//! functions are much faster when given many organisms at once, such as
+//! those built on vectorised numerical libraries or run on a GPU. A
+//! [`BatchEvaluator`] registered with [`EcosystemBuilder::batch_evaluator`]
+//! is then used instead, and is handed every organism awaiting evaluation
+//! together.
+//!
+//! With the `tokio` feature, fitness functions that wait on something else,
+//! such as a simulation service reached over the network, can instead be
+//! written as an [`AsyncEvaluator`], whose evaluations run concurrently on
+//! the Tokio runtime rather than each holding up a thread.
+//!
+//! [`AsyncEvaluator`]: trait.AsyncEvaluator.html
+//! [`fitness`]: ../trait.Organism.html#tymethod.fitness
+//! [`BatchEvaluator`]: trait.BatchEvaluator.html
+//! [`EcosystemBuilder::batch_evaluator`]: ../struct.EcosystemBuilder.html#method.batch_evaluator
+use crate::Organism;
+#[cfg(feature = "tokio")]
+use crate::{Ecosystem, EcosystemError};
+use alloc::vec::Vec;
+#[cfg(feature = "tokio")]
+use std::{future::Future, sync::Arc};
+
+/// An interface for evaluating the fitness of many organisms at once.
+///
+/// Closures that take a slice of organisms and return their fitness values
+/// are batch evaluators too.
+///
+/// # Examples
+///
+/// ```rust
+/// use ecosystem::{Ecosystem, Organism};
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+/// use std::sync::Arc;
+/// # use rand::Rng;
+/// # struct Guess(f64);
+/// # impl Organism for Guess {
+/// #     type Fitness = f64;
+/// #     fn fitness(&self) -> f64 { unreachable!("guesses are evaluated in batches") }
+/// #     fn breed(&self, other: &Self, rng: &mut impl Rng) -> Self { Guess((self.0 + other.0) / 2.0) }
+/// #     fn mutate(&mut self, rate: f64, rng: &mut impl Rng) {
+/// #         self.0 += rng.gen_range(-rate, rate);
+/// #     }
+/// # }
+///
+/// let batches = Arc::new(AtomicUsize::new(0));
+/// let counter = Arc::clone(&batches);
+/// let mut ecosystem = Ecosystem::builder()
+///     .organisms((0..20).map(|i| Guess(f64::from(i) * 5.0 + 0.5)))
+///     // Evaluates the whole population in a single call
+///     .batch_evaluator(move |guesses: &[&Guess]| {
+///         counter.fetch_add(1, Ordering::Relaxed);
+///         guesses
+///             .iter()
+///             .map(|guess| 1.0 / (42.0 - guess.0).abs())
+///             .collect()
+///     })
+///     .build()
+///     .unwrap();
+/// for _ in 0..10 {
+///     ecosystem.breed_next_generation()?;
+/// }
+/// ecosystem.statistics();
+///
+/// // One batch for each generation, including the newest
+/// assert_eq!(batches.load(Ordering::Relaxed), 11);
+/// assert_eq!(ecosystem.evaluations(), 11 * 20);
+/// # Ok::<(), ecosystem::EcosystemError>(())
+/// ```
+pub trait BatchEvaluator<O: Organism>: Send + Sync {
+    /// Evaluates the fitness of each of the given organisms, returning the
+    /// values in the same order.
+    ///
+    /// The ecosystem panics if the number of values returned differs from
+    /// the number of organisms.
+    fn evaluate_all(&self, organisms: &[&O]) -> Vec<O::Fitness>;
+}
+
+impl<O, F> BatchEvaluator<O> for F
+where
+    O: Organism,
+    F: Fn(&[&O]) -> Vec<O::Fitness> + Send + Sync,
+{
+    fn evaluate_all(&self, organisms: &[&O]) -> Vec<O::Fitness> {
+        self(organisms)
+    }
+}
+
+/// An interface for evaluating fitness asynchronously, for use with
+/// [`Ecosystem::breed_next_generation_async`].
+///
+/// Each evaluation is given its own copy of the organism, so that it can run
+/// as a separate task. Closures that take an organism and return a future of
+/// its fitness are asynchronous evaluators too.
+///
+/// [`Ecosystem::breed_next_generation_async`]: ../struct.Ecosystem.html#method.breed_next_generation_async
+#[cfg(feature = "tokio")]
+pub trait AsyncEvaluator<O: Organism>: Send + Sync + 'static {
+    /// The future that resolves to an organism's fitness.
+    type Future: Future<Output = O::Fitness> + Send + 'static;
+
+    /// Starts evaluating the fitness of the given organism.
+    fn evaluate(&self, organism: O) -> Self::Future;
+}
+
+#[cfg(feature = "tokio")]
+impl<O, F, Fut> AsyncEvaluator<O> for F
+where
+    O: Organism,
+    F: Fn(O) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = O::Fitness> + Send + 'static,
+{
+    type Future = Fut;
+
+    fn evaluate(&self, organism: O) -> Fut {
+        self(organism)
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl<O> Ecosystem<O>
+where
+    O: Organism + Clone + Send + Sync + 'static,
+    O::Fitness: 'static,
+{
+    /// Breeds the next generation as [`breed_next_generation`] does, but
+    /// evaluates fitness with the given asynchronous evaluator, running up to
+    /// `concurrency` evaluations at once as tasks on the current Tokio
+    /// runtime. The current generation is evaluated before breeding, and the
+    /// new one (including any immigrants) once it has been bred.
+    ///
+    /// Population models other than the generational one evaluate children
+    /// while breeding them, which they still do with the organisms' own
+    /// [`fitness`] methods (or the batch evaluator, if one is set).
+    ///
+    /// [`breed_next_generation`]: #method.breed_next_generation
+    /// [`fitness`]: trait.Organism.html#tymethod.fitness
+    ///
+    /// # Panics
+    ///
+    /// Panics if called outside of a Tokio runtime, or if an evaluation
+    /// panics.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the ecosystem contains no organisms, or if
+    /// the selection strategy fails.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ecosystem::{Ecosystem, Organism};
+    /// use std::{sync::Arc, time::Duration};
+    /// # use rand::Rng;
+    /// # #[derive(Clone)]
+    /// # struct Guess(f64);
+    /// # impl Organism for Guess {
+    /// #     type Fitness = f64;
+    /// #     fn fitness(&self) -> f64 { unreachable!("guesses are evaluated remotely") }
+    /// #     fn breed(&self, other: &Self, rng: &mut impl Rng) -> Self { Guess((self.0 + other.0) / 2.0) }
+    /// #     fn mutate(&mut self, rate: f64, rng: &mut impl Rng) {
+    /// #         self.0 += rng.gen_range(-rate, rate);
+    /// #     }
+    /// # }
+    ///
+    /// // Stands in for a request to a simulation service
+    /// async fn simulate(guess: Guess) -> f64 {
+    ///     tokio::time::sleep(Duration::from_millis(10)).await;
+    ///     1.0 / (42.0 - guess.0).abs()
+    /// }
+    ///
+    /// let runtime = tokio::runtime::Runtime::new().unwrap();
+    /// let mut ecosystem = Ecosystem::new((0..20).map(|i| Guess(f64::from(i) * 5.0 + 0.5)).collect());
+    /// let evaluator = Arc::new(simulate);
+    /// runtime.block_on(async {
+    ///     for _ in 0..5 {
+    ///         ecosystem.breed_next_generation_async(&evaluator, 20).await?;
+    ///     }
+    ///     Ok::<(), ecosystem::EcosystemError>(())
+    /// })?;
+    ///
+    /// // Every generation has been evaluated, including the newest
+    /// assert_eq!(ecosystem.evaluations(), 6 * 20);
+    /// # Ok::<(), ecosystem::EcosystemError>(())
+    /// ```
+    pub async fn breed_next_generation_async<E: AsyncEvaluator<O>>(
+        &mut self,
+        evaluator: &Arc<E>,
+        concurrency: usize,
+    ) -> Result<(), EcosystemError> {
+        if self.population.is_empty() {
+            return Err(EcosystemError::EmptyPopulation);
+        }
+
+        self.evaluate_async(evaluator, concurrency).await;
+        self.begin_generation();
+        self.breed_generation()?;
+        self.evaluate_async(evaluator, concurrency).await;
+        self.admit_immigrants();
+        self.evaluate_async(evaluator, concurrency).await;
+        self.end_generation();
+        Ok(())
+    }
+
+    /// Evaluates every individual in the population that has not been
+    /// evaluated already, with up to `concurrency` evaluations running at
+    /// once.
+    async fn evaluate_async<E: AsyncEvaluator<O>>(&self, evaluator: &Arc<E>, concurrency: usize) {
+        let pending: Vec<_> = self
+            .population
+            .iter()
+            .filter(|individual| individual.fitness.get().is_none())
+            .collect();
+        let mut fitnesses = Vec::with_capacity(pending.len());
+        let mut tasks = tokio::task::JoinSet::new();
+        for (index, individual) in pending.iter().enumerate() {
+            if tasks.len() >= concurrency.max(1) {
+                fitnesses.push(join(&mut tasks).await);
+            }
+            let (evaluator, organism) = (Arc::clone(evaluator), individual.organism().clone());
+            tasks.spawn(async move { (index, evaluator.evaluate(organism).await) });
+        }
+        while !tasks.is_empty() {
+            fitnesses.push(join(&mut tasks).await);
+        }
+
+        self.evaluations
+            .fetch_add(pending.len() as u64, std::sync::atomic::Ordering::Relaxed);
+        for (index, fitness) in fitnesses {
+            let _ = pending[index].fitness.set(fitness);
+        }
+    }
+}
+
+/// Waits for the next of the given tasks to finish, passing on any panic.
+#[cfg(feature = "tokio")]
+async fn join<T: 'static>(tasks: &mut tokio::task::JoinSet<T>) -> T {
+    match tasks.join_next().await {
+        Some(Ok(value)) => value,
+        Some(Err(error)) => std::panic::resume_unwind(error.into_panic()),
+        None => unreachable!("there are tasks left to join"),
+    }
+}
@@ -0,0 +1,79 @@
+/// A value describing how well an organism performs.
+///
+/// Fitness values only need to be ordered for most purposes, so any
+/// `PartialOrd` type can be used, such as an integer score or a tuple that
+/// compares lexicographically. Strategies that work with magnitudes (such as
+/// fitness-proportionate selection) use the scalar view given by [`to_f64`].
+///
+/// [`to_f64`]: #tymethod.to_f64
+///
+/// # Examples
+///
+/// ```rust
+/// use ecosystem::{selection::Tournament, Ecosystem, Organism};
+/// # use rand::Rng;
+///
+/// struct Design {
+///     working: bool,
+///     cost: i32,
+/// }
+///
+/// impl Organism for Design {
+///     // Working designs always beat broken ones, and cheaper designs beat
+///     // more expensive ones
+///     type Fitness = (u8, i32);
+///
+///     fn fitness(&self) -> (u8, i32) {
+///         (self.working as u8, -self.cost)
+///     }
+/// #   fn breed(&self, other: &Self, rng: &mut impl Rng) -> Self { Design { ..*self } }
+/// #   fn mutate(&mut self, rate: f64, rng: &mut impl Rng) {}
+/// }
+///
+/// let designs = vec![
+///     Design { working: false, cost: 1 },
+///     Design { working: true, cost: 8 },
+///     Design { working: true, cost: 5 },
+/// ];
+/// let ecosystem = Ecosystem::builder()
+///     .organisms(designs)
+///     .selection(Tournament { size: 2 })
+///     .build()
+///     .unwrap();
+///
+/// assert_eq!(ecosystem.fittest().unwrap().cost, 5);
+/// ```
+pub trait Fitness: PartialOrd + Copy + Send + Sync {
+    /// Converts the fitness value into a single floating-point number.
+    fn to_f64(self) -> f64;
+}
+
+macro_rules! impl_fitness_for_primitives {
+    ($($primitive:ty),*) => {
+        $(
+            impl Fitness for $primitive {
+                fn to_f64(self) -> f64 {
+                    self as f64
+                }
+            }
+        )*
+    };
+}
+
+impl_fitness_for_primitives!(f64, f32, i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+
+/// Tuples compare lexicographically; their scalar view is that of the first
+/// (most significant) element.
+impl<A: Fitness, B: Fitness> Fitness for (A, B) {
+    fn to_f64(self) -> f64 {
+        self.0.to_f64()
+    }
+}
+
+/// Tuples compare lexicographically; their scalar view is that of the first
+/// (most significant) element.
+impl<A: Fitness, B: Fitness, C: Fitness> Fitness for (A, B, C) {
+    fn to_f64(self) -> f64 {
+        self.0.to_f64()
+    }
+}
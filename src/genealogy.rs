@@ -0,0 +1,154 @@
+//! Records of where each organism came from.
+//!
+//! When enabled with [`EcosystemBuilder::track_genealogy`], the ecosystem
+//! records the birth of every individual: its parents and the generation it
+//! was born into. Individuals are identified by their [`id`]. The records
+//! make it possible to trace the ancestry of any organism, and can be
+//! exported to the DOT format for viewing with Graphviz.
+//!
+//! Every birth is kept for the rest of the run, so long runs with large
+//! populations will use a good deal of memory.
+//!
+//! [`EcosystemBuilder::track_genealogy`]: ../struct.EcosystemBuilder.html#method.track_genealogy
+//! [`id`]: ../struct.Individual.html#method.id
+use alloc::{
+    collections::{BTreeMap, BTreeSet},
+    vec::Vec,
+};
+#[cfg(feature = "std")]
+use std::io::{self, Write};
+
+/// The record of an individual's birth.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Birth {
+    /// The [identifier] of the individual.
+    ///
+    /// [identifier]: ../struct.Individual.html#method.id
+    pub id: u64,
+    /// The identifiers of the individual's parents. This is empty for the
+    /// initial population, and holds a single parent for a child that was
+    /// cloned from its parent rather than bred.
+    pub parents: Vec<u64>,
+    /// The generation that the individual was born into.
+    pub generation: u32,
+}
+
+/// The births of every individual in an ecosystem.
+///
+/// # Examples
+///
+/// ```rust
+/// use ecosystem::{Ecosystem, Organism};
+/// # use rand::Rng;
+/// # struct Guess(f64);
+/// # impl Organism for Guess {
+/// #     type Fitness = f64;
+/// #     fn fitness(&self) -> f64 { 1.0 / (42.0 - self.0).abs() }
+/// #     fn breed(&self, other: &Self, rng: &mut impl Rng) -> Self { Guess((self.0 + other.0) / 2.0) }
+/// #     fn mutate(&mut self, rate: f64, rng: &mut impl Rng) {
+/// #         self.0 += rng.gen_range(-rate, rate);
+/// #     }
+/// # }
+///
+/// let mut ecosystem = Ecosystem::builder()
+///     .organisms((0..10).map(|i| Guess(f64::from(i) * 10.0 + 0.5)))
+///     .track_genealogy()
+///     .build()
+///     .unwrap();
+/// for _ in 0..3 {
+///     ecosystem.breed_next_generation()?;
+/// }
+///
+/// let genealogy = ecosystem.genealogy().unwrap();
+/// let fittest = ecosystem.fittest_individual().unwrap();
+/// assert_eq!(genealogy.get(fittest.id()).unwrap().generation, 3);
+///
+/// // Every line of ancestry leads back to the initial population
+/// let founders: Vec<_> = genealogy
+///     .ancestors(fittest.id())
+///     .into_iter()
+///     .filter(|&id| genealogy.get(id).unwrap().parents.is_empty())
+///     .collect();
+/// assert!(!founders.is_empty());
+///
+/// let mut dot = Vec::new();
+/// genealogy.write_dot(&mut dot)?;
+/// assert!(String::from_utf8(dot).unwrap().starts_with("digraph genealogy {"));
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Genealogy {
+    births: Vec<Birth>,
+    index: BTreeMap<u64, usize>,
+}
+
+impl Genealogy {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns every recorded birth, in the order they were recorded.
+    pub fn births(&self) -> &[Birth] {
+        &self.births
+    }
+
+    /// Returns the birth of the individual with the given identifier, if it
+    /// was recorded.
+    pub fn get(&self, id: u64) -> Option<&Birth> {
+        self.index.get(&id).map(|&index| &self.births[index])
+    }
+
+    /// Returns the identifiers of every recorded ancestor of the individual
+    /// with the given identifier, nearest first.
+    pub fn ancestors(&self, id: u64) -> Vec<u64> {
+        let mut ancestors = Vec::new();
+        let mut seen = BTreeSet::new();
+        let mut next = 0;
+        let mut current = id;
+        loop {
+            if let Some(birth) = self.get(current) {
+                for &parent in &birth.parents {
+                    if seen.insert(parent) {
+                        ancestors.push(parent);
+                    }
+                }
+            }
+            match ancestors.get(next) {
+                Some(&ancestor) => current = ancestor,
+                None => return ancestors,
+            }
+            next += 1;
+        }
+    }
+
+    /// Writes the genealogy as a Graphviz graph in the DOT format, with an
+    /// edge from each parent to each of its children. Each individual is
+    /// labelled with its identifier and the generation it was born into.
+    #[cfg(feature = "std")]
+    pub fn write_dot<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writeln!(writer, "digraph genealogy {{")?;
+        for birth in &self.births {
+            writeln!(
+                writer,
+                "    {} [label=\"{} (generation {})\"];",
+                birth.id, birth.id, birth.generation
+            )?;
+        }
+        for birth in &self.births {
+            for parent in &birth.parents {
+                writeln!(writer, "    {} -> {};", parent, birth.id)?;
+            }
+        }
+        writeln!(writer, "}}")
+    }
+
+    /// Records the birth of an individual.
+    pub(crate) fn record(&mut self, id: u64, parents: Vec<u64>, generation: u32) {
+        self.index.insert(id, self.births.len());
+        self.births.push(Birth {
+            id,
+            parents,
+            generation,
+        });
+    }
+}
@@ -0,0 +1,125 @@
+use crate::{
+    termination::{StopReason, Termination},
+    Ecosystem, EcosystemError, Individual, Organism, Statistics,
+};
+
+/// A report on a generation bred by [`Ecosystem::generations`].
+///
+/// [`Ecosystem::generations`]: struct.Ecosystem.html#method.generations
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GenerationReport<F> {
+    /// The generation number.
+    pub generation: u32,
+    /// The fitness of the generation's fittest organism.
+    pub best: F,
+    /// A summary of the generation's fitness values.
+    pub statistics: Statistics,
+    /// The total number of fitness evaluations performed by the ecosystem.
+    pub evaluations: u64,
+}
+
+/// An iterator that breeds a generation each time it is advanced, created by
+/// [`Ecosystem::generations`].
+///
+/// [`Ecosystem::generations`]: struct.Ecosystem.html#method.generations
+pub struct Generations<'a, O: Organism, T> {
+    ecosystem: &'a mut Ecosystem<O>,
+    termination: T,
+    reason: Option<StopReason>,
+    failed: bool,
+}
+
+impl<O: Organism + Send + Sync, T: Termination<Ecosystem<O>>> Generations<'_, O, T> {
+    /// Returns the reason that the iteration stopped, or `None` if it hasn't
+    /// stopped yet or stopped because breeding failed.
+    pub fn stop_reason(&self) -> Option<&StopReason> {
+        self.reason.as_ref()
+    }
+}
+
+impl<O: Organism + Send + Sync, T: Termination<Ecosystem<O>>> Iterator for Generations<'_, O, T> {
+    type Item = Result<GenerationReport<O::Fitness>, EcosystemError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.reason.is_some() || self.failed {
+            return None;
+        }
+        let ecosystem = &mut *self.ecosystem;
+        self.reason = self.termination.check(ecosystem).or_else(|| {
+            let monitor = ecosystem.stagnation.as_mut()?;
+            core::mem::take(&mut monitor.stalled).then_some(StopReason::Stagnation)
+        });
+        if self.reason.is_some() {
+            return None;
+        }
+
+        if let Err(error) = ecosystem.breed_next_generation() {
+            self.failed = true;
+            return Some(Err(error));
+        }
+        let best = ecosystem.fittest_individual().map(Individual::fitness);
+        let statistics = ecosystem.statistics();
+        // A generation that bred successfully is never empty
+        let (best, statistics) = best.zip(statistics)?;
+        Some(Ok(GenerationReport {
+            generation: ecosystem.generation(),
+            best,
+            statistics,
+            evaluations: ecosystem.evaluations(),
+        }))
+    }
+}
+
+impl<O: Organism + Send + Sync> Ecosystem<O> {
+    /// Returns an iterator that breeds a new generation each time it is
+    /// advanced, yielding a report on it, until the given termination
+    /// condition is met.
+    ///
+    /// This is a lazy alternative to [`run`], for use with iterator
+    /// adapters. Unlike [`run`], it doesn't notify the ecosystem's observers
+    /// or progress reporter. If a generation fails to breed, its error is
+    /// yielded, and the iteration ends.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ecosystem::{termination::MaxGenerations, Ecosystem, Organism};
+    /// # use rand::Rng;
+    /// # struct Guess(f64);
+    /// # impl Organism for Guess {
+    /// #     type Fitness = f64;
+    /// #     fn fitness(&self) -> f64 { 1.0 / (42.0 - self.0).abs() }
+    /// #     fn breed(&self, other: &Self, rng: &mut impl Rng) -> Self { Guess((self.0 + other.0) / 2.0) }
+    /// #     fn mutate(&mut self, rate: f64, rng: &mut impl Rng) {
+    /// #         self.0 += rng.gen_range(-rate, rate);
+    /// #     }
+    /// # }
+    ///
+    /// let guesses = (0..20).map(|i| Guess(f64::from(i) * 5.0 + 0.5)).collect();
+    /// let mut ecosystem = Ecosystem::new(guesses);
+    ///
+    /// let mut bred = 0;
+    /// for report in ecosystem
+    ///     .generations(MaxGenerations(100))
+    ///     .inspect(|_| bred += 1)
+    ///     .take_while(|report| report.as_ref().map_or(true, |report| report.best < 10.0))
+    /// {
+    ///     let report = report?;
+    ///     println!("{}: {}", report.generation, report.best);
+    /// }
+    ///
+    /// assert!(bred <= 100);
+    /// assert_eq!(ecosystem.generation(), bred);
+    /// # Ok::<(), ecosystem::EcosystemError>(())
+    /// ```
+    ///
+    /// [`run`]: #method.run
+    pub fn generations<T: Termination<Self>>(&mut self, termination: T) -> Generations<'_, O, T> {
+        Generations {
+            ecosystem: self,
+            termination,
+            reason: None,
+            failed: false,
+        }
+    }
+}
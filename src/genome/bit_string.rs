@@ -0,0 +1,369 @@
+use crate::diversity::Distance;
+use rand::Rng;
+use std::{fmt, iter::FromIterator};
+
+const WORD_BITS: usize = 64;
+
+/// A fixed-length string of bits, packed 64 to a word.
+///
+/// Crossover between two bit strings produces a pair of children, as
+/// [`Organism::breed_pair`] expects, and panics if the parents differ in
+/// length. The distance between two bit strings is the number of bits in
+/// which they differ (the Hamming distance).
+///
+/// [`Organism::breed_pair`]: ../trait.Organism.html#method.breed_pair
+///
+/// # Examples
+///
+/// ```rust
+/// use ecosystem::genome::BitString;
+///
+/// let mut bits: BitString = "10110".parse().unwrap();
+/// bits.flip(1);
+///
+/// assert_eq!(bits.to_string(), "11110");
+/// assert_eq!(bits.count_ones(), 4);
+/// assert!(bits.get(3));
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BitString {
+    // Bits beyond `len` in the last word are always zero, so that equal bit
+    // strings have equal words
+    words: Vec<u64>,
+    len: usize,
+}
+
+impl BitString {
+    /// Creates a bit string of the given length, with every bit unset.
+    pub fn zeros(len: usize) -> Self {
+        Self {
+            words: vec![0; words_for(len)],
+            len,
+        }
+    }
+
+    /// Creates a bit string of the given length, with every bit set.
+    pub fn ones(len: usize) -> Self {
+        let mut bits = Self {
+            words: vec![!0; words_for(len)],
+            len,
+        };
+        bits.clear_tail();
+        bits
+    }
+
+    /// Creates a bit string of the given length, with each bit set at random.
+    pub fn random(len: usize, rng: &mut impl Rng) -> Self {
+        let mut bits = Self {
+            words: (0..words_for(len)).map(|_| rng.gen()).collect(),
+            len,
+        };
+        bits.clear_tail();
+        bits
+    }
+
+    /// Returns the number of bits.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns whether there are no bits.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the bit at the given index.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index is out of bounds.
+    pub fn get(&self, index: usize) -> bool {
+        self.check_index(index);
+        self.words[index / WORD_BITS] & (1 << (index % WORD_BITS)) != 0
+    }
+
+    /// Sets the bit at the given index.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index is out of bounds.
+    pub fn set(&mut self, index: usize, bit: bool) {
+        self.check_index(index);
+        let mask = 1 << (index % WORD_BITS);
+        if bit {
+            self.words[index / WORD_BITS] |= mask;
+        } else {
+            self.words[index / WORD_BITS] &= !mask;
+        }
+    }
+
+    /// Inverts the bit at the given index.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index is out of bounds.
+    pub fn flip(&mut self, index: usize) {
+        self.check_index(index);
+        self.words[index / WORD_BITS] ^= 1 << (index % WORD_BITS);
+    }
+
+    /// Returns the number of set bits.
+    pub fn count_ones(&self) -> usize {
+        self.words
+            .iter()
+            .map(|word| word.count_ones() as usize)
+            .sum()
+    }
+
+    /// Returns the number of unset bits.
+    pub fn count_zeros(&self) -> usize {
+        self.len - self.count_ones()
+    }
+
+    /// Returns an iterator over the bits, from the first to the last.
+    pub fn iter(&self) -> impl Iterator<Item = bool> + '_ {
+        (0..self.len).map(move |index| self.get(index))
+    }
+
+    /// Returns the bits packed into words, with the first bit in the least
+    /// significant bit of the first word. Any bits of the last word beyond
+    /// the length are unset.
+    pub fn as_words(&self) -> &[u64] {
+        &self.words
+    }
+
+    /// Interprets the bits in the given range as an unsigned integer, with
+    /// the first bit of the range as the most significant. This is useful for
+    /// decoding numeric parameters from a bit string.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is out of bounds or longer than 64 bits.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ecosystem::genome::BitString;
+    ///
+    /// let bits: BitString = "0011011".parse().unwrap();
+    ///
+    /// assert_eq!(bits.to_u64(2, 7), 0b11011);
+    /// ```
+    pub fn to_u64(&self, start: usize, end: usize) -> u64 {
+        assert!(
+            start <= end && end <= self.len,
+            "range out of bounds for a bit string of length {}",
+            self.len
+        );
+        assert!(end - start <= WORD_BITS, "range is longer than 64 bits");
+        (start..end).fold(0, |value, index| value << 1 | self.get(index) as u64)
+    }
+
+    /// One-point crossover, which swaps the bits after a random point
+    /// between the two parents.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the bit strings differ in length.
+    pub fn one_point_crossover(&self, other: &Self, rng: &mut impl Rng) -> (Self, Self) {
+        self.check_len(other);
+        let point = rng.gen_range(0, self.len + 1);
+        self.swap_range(other, point, self.len)
+    }
+
+    /// Two-point crossover, which swaps the bits between two random points
+    /// between the two parents.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the bit strings differ in length.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ecosystem::genome::BitString;
+    ///
+    /// let mut rng = rand::thread_rng();
+    /// let (zeros, ones) = (BitString::zeros(100), BitString::ones(100));
+    /// let (first, second) = zeros.two_point_crossover(&ones, &mut rng);
+    ///
+    /// // Each child takes every bit from one parent or the other
+    /// assert_eq!(first.count_ones() + second.count_ones(), 100);
+    /// assert_eq!(first.hamming_distance(&second), 100);
+    /// ```
+    pub fn two_point_crossover(&self, other: &Self, rng: &mut impl Rng) -> (Self, Self) {
+        self.check_len(other);
+        let a = rng.gen_range(0, self.len + 1);
+        let b = rng.gen_range(0, self.len + 1);
+        self.swap_range(other, a.min(b), a.max(b))
+    }
+
+    /// Uniform crossover, in which each bit is swapped between the two
+    /// parents with a probability of one half.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the bit strings differ in length.
+    pub fn uniform_crossover(&self, other: &Self, rng: &mut impl Rng) -> (Self, Self) {
+        self.check_len(other);
+        let mut first = self.clone();
+        let mut second = other.clone();
+        for (a, b) in first.words.iter_mut().zip(&mut second.words) {
+            // The tail bits are unset in both parents, so swapping them
+            // leaves them unset
+            let swap = (*a ^ *b) & rng.gen::<u64>();
+            *a ^= swap;
+            *b ^= swap;
+        }
+        (first, second)
+    }
+
+    /// Flips each bit independently with a probability of `rate`, which is
+    /// clamped to [0, 1].
+    pub fn flip_bits(&mut self, rate: f64, rng: &mut impl Rng) {
+        let rate = rate.clamp(0.0, 1.0);
+        for index in 0..self.len {
+            if rng.gen_bool(rate) {
+                self.flip(index);
+            }
+        }
+    }
+
+    /// Returns the number of bits in which the two bit strings differ.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the bit strings differ in length.
+    pub fn hamming_distance(&self, other: &Self) -> usize {
+        self.check_len(other);
+        self.words
+            .iter()
+            .zip(&other.words)
+            .map(|(a, b)| (a ^ b).count_ones() as usize)
+            .sum()
+    }
+
+    /// Returns copies of the two bit strings with the bits in `start..end`
+    /// swapped between them.
+    fn swap_range(&self, other: &Self, start: usize, end: usize) -> (Self, Self) {
+        let mut first = self.clone();
+        let mut second = other.clone();
+        for (word, (a, b)) in first.words.iter_mut().zip(&mut second.words).enumerate() {
+            let swap = (*a ^ *b) & range_mask(word, start, end);
+            *a ^= swap;
+            *b ^= swap;
+        }
+        (first, second)
+    }
+
+    /// Unsets the bits of the last word beyond the length.
+    fn clear_tail(&mut self) {
+        let last = self.words.len().saturating_sub(1);
+        if let Some(word) = self.words.get_mut(last) {
+            *word &= range_mask(last, 0, self.len);
+        }
+    }
+
+    fn check_index(&self, index: usize) {
+        assert!(
+            index < self.len,
+            "index {} out of bounds for a bit string of length {}",
+            index,
+            self.len
+        );
+    }
+
+    fn check_len(&self, other: &Self) {
+        assert_eq!(
+            self.len, other.len,
+            "bit strings of different lengths cannot be combined"
+        );
+    }
+}
+
+/// Returns the number of words needed to hold the given number of bits.
+fn words_for(len: usize) -> usize {
+    len.div_ceil(WORD_BITS)
+}
+
+/// Returns a mask of the bits of the given word that fall within
+/// `start..end`.
+fn range_mask(word: usize, start: usize, end: usize) -> u64 {
+    let first = word * WORD_BITS;
+    let low = start.saturating_sub(first).min(WORD_BITS);
+    let high = end.saturating_sub(first).min(WORD_BITS);
+    if low >= high {
+        return 0;
+    }
+    let below_high = if high == WORD_BITS {
+        !0
+    } else {
+        (1 << high) - 1
+    };
+    below_high & !((1 << low) - 1)
+}
+
+impl Distance for BitString {
+    fn distance(&self, other: &Self) -> f64 {
+        self.hamming_distance(other) as f64
+    }
+}
+
+impl FromIterator<bool> for BitString {
+    fn from_iter<I: IntoIterator<Item = bool>>(iter: I) -> Self {
+        let mut bits = BitString::zeros(0);
+        for bit in iter {
+            if bits.len.is_multiple_of(WORD_BITS) {
+                bits.words.push(0);
+            }
+            bits.len += 1;
+            bits.set(bits.len - 1, bit);
+        }
+        bits
+    }
+}
+
+impl fmt::Display for BitString {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for bit in self.iter() {
+            f.write_str(if bit { "1" } else { "0" })?;
+        }
+        Ok(())
+    }
+}
+
+/// An error returned when parsing a bit string from text containing
+/// characters other than `0` and `1`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ParseBitStringError {
+    /// The position of the first invalid character.
+    pub position: usize,
+}
+
+impl fmt::Display for ParseBitStringError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "invalid character at position {} of a bit string",
+            self.position
+        )
+    }
+}
+
+impl std::error::Error for ParseBitStringError {}
+
+impl std::str::FromStr for BitString {
+    type Err = ParseBitStringError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.chars()
+            .enumerate()
+            .map(|(position, c)| match c {
+                '0' => Ok(false),
+                '1' => Ok(true),
+                _ => Err(ParseBitStringError { position }),
+            })
+            .collect()
+    }
+}
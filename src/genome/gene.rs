@@ -0,0 +1,168 @@
+use super::standard_normal;
+use crate::Organism;
+use rand::Rng;
+
+/// A value that can be bred and mutated on its own, as each field of an
+/// organism is by `#[derive(Organism)]`.
+///
+/// Numbers, booleans, vectors and arrays of genes, and organisms themselves
+/// are all genes:
+///
+/// - Floating-point numbers cross by taking either parent's value, blend to
+///   the mean of the two, and are perturbed by Gaussian noise with a
+///   standard deviation of the mutation rate.
+/// - Integers cross by taking either parent's value, blend to the mean of
+///   the two (rounded towards zero), and are perturbed by a step of one up
+///   or down with a probability of the mutation rate.
+/// - Booleans cross and blend by taking either parent's value, and are
+///   flipped with a probability of the mutation rate.
+/// - Vectors and arrays cross, blend and are perturbed element by element.
+///   Vectors of different lengths are cut down to the shorter length.
+/// - Organisms cross and blend by [`breed`]ing, and are perturbed by
+///   [`mutate`].
+///
+/// [`breed`]: ../trait.Organism.html#tymethod.breed
+/// [`mutate`]: ../trait.Organism.html#tymethod.mutate
+///
+/// # Examples
+///
+/// ```rust
+/// use ecosystem::genome::Gene;
+///
+/// let mut rng = rand::thread_rng();
+/// assert_eq!(2.0.blend(&4.0, &mut rng), 3.0);
+/// assert!([1, 2].contains(&1.cross(&2, &mut rng)));
+///
+/// let mut weights = vec![0.5, -0.5];
+/// weights.perturb(0.1, &mut rng);
+/// assert_eq!(weights.len(), 2);
+/// ```
+pub trait Gene: Sized {
+    /// Creates a child gene by taking the value of one parent or the other.
+    fn cross(&self, other: &Self, rng: &mut impl Rng) -> Self;
+
+    /// Creates a child gene by combining the values of both parents. By
+    /// default, this is the same as [`cross`].
+    ///
+    /// [`cross`]: #tymethod.cross
+    fn blend(&self, other: &Self, rng: &mut impl Rng) -> Self {
+        self.cross(other, rng)
+    }
+
+    /// Modifies the gene, based on the given mutation rate.
+    fn perturb(&mut self, rate: f64, rng: &mut impl Rng);
+}
+
+impl<O: Organism> Gene for O {
+    fn cross(&self, other: &Self, rng: &mut impl Rng) -> Self {
+        self.breed(other, rng)
+    }
+
+    fn perturb(&mut self, rate: f64, rng: &mut impl Rng) {
+        self.mutate(rate, rng);
+    }
+}
+
+macro_rules! impl_float_gene {
+    ($($float:ty),*) => {
+        $(
+            impl Gene for $float {
+                fn cross(&self, other: &Self, rng: &mut impl Rng) -> Self {
+                    if rng.gen_bool(0.5) { *self } else { *other }
+                }
+
+                fn blend(&self, other: &Self, _rng: &mut impl Rng) -> Self {
+                    (*self + *other) / 2.0
+                }
+
+                fn perturb(&mut self, rate: f64, rng: &mut impl Rng) {
+                    *self += (rate * standard_normal(rng)) as $float;
+                }
+            }
+        )*
+    };
+}
+
+impl_float_gene!(f32, f64);
+
+macro_rules! impl_integer_gene {
+    ($($integer:ty),*) => {
+        $(
+            impl Gene for $integer {
+                fn cross(&self, other: &Self, rng: &mut impl Rng) -> Self {
+                    if rng.gen_bool(0.5) { *self } else { *other }
+                }
+
+                fn blend(&self, other: &Self, _rng: &mut impl Rng) -> Self {
+                    ((*self as i128 + *other as i128) / 2) as $integer
+                }
+
+                fn perturb(&mut self, rate: f64, rng: &mut impl Rng) {
+                    if rng.gen_bool(rate.clamp(0.0, 1.0)) {
+                        *self = if rng.gen_bool(0.5) {
+                            self.saturating_add(1)
+                        } else {
+                            self.saturating_sub(1)
+                        };
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_integer_gene!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+
+impl Gene for bool {
+    fn cross(&self, other: &Self, rng: &mut impl Rng) -> Self {
+        if rng.gen_bool(0.5) {
+            *self
+        } else {
+            *other
+        }
+    }
+
+    fn perturb(&mut self, rate: f64, rng: &mut impl Rng) {
+        if rng.gen_bool(rate.clamp(0.0, 1.0)) {
+            *self = !*self;
+        }
+    }
+}
+
+impl<T: Gene> Gene for Vec<T> {
+    fn cross(&self, other: &Self, rng: &mut impl Rng) -> Self {
+        self.iter()
+            .zip(other)
+            .map(|(a, b)| a.cross(b, rng))
+            .collect()
+    }
+
+    fn blend(&self, other: &Self, rng: &mut impl Rng) -> Self {
+        self.iter()
+            .zip(other)
+            .map(|(a, b)| a.blend(b, rng))
+            .collect()
+    }
+
+    fn perturb(&mut self, rate: f64, rng: &mut impl Rng) {
+        for gene in self {
+            gene.perturb(rate, rng);
+        }
+    }
+}
+
+impl<T: Gene, const N: usize> Gene for [T; N] {
+    fn cross(&self, other: &Self, rng: &mut impl Rng) -> Self {
+        std::array::from_fn(|index| self[index].cross(&other[index], rng))
+    }
+
+    fn blend(&self, other: &Self, rng: &mut impl Rng) -> Self {
+        std::array::from_fn(|index| self[index].blend(&other[index], rng))
+    }
+
+    fn perturb(&mut self, rate: f64, rng: &mut impl Rng) {
+        for gene in self {
+            gene.perturb(rate, rng);
+        }
+    }
+}
@@ -0,0 +1,256 @@
+use crate::diversity::Distance;
+use rand::{distributions::Uniform, Rng};
+
+/// A vector of integers, each kept within its own inclusive bounds.
+///
+/// Every operator keeps the genes within their bounds, clamping any value
+/// that would fall outside them. Crossover between two vectors produces a
+/// pair of children, as [`Organism::breed_pair`] expects, and panics if the
+/// parents differ in length. The distance between two vectors is the sum of
+/// the absolute differences between their genes (the Manhattan distance).
+///
+/// [`Organism::breed_pair`]: ../trait.Organism.html#method.breed_pair
+///
+/// # Examples
+///
+/// ```rust
+/// use ecosystem::{genome::IntVector, selection::Tournament, Ecosystem, Organism};
+/// use rand::Rng;
+///
+/// // Tune a layer count and a kernel size, which work best at 4 and 7
+/// struct Hyperparameters(IntVector);
+///
+/// impl Organism for Hyperparameters {
+///     type Fitness = f64;
+///
+///     fn fitness(&self) -> f64 {
+///         let genes = self.0.genes();
+///         let error = (genes[0] - 4).abs() + (genes[1] - 7).abs();
+///         1.0 / (error as f64 + 1.0)
+///     }
+///
+///     fn breed(&self, other: &Self, rng: &mut impl Rng) -> Self {
+///         self.breed_pair(other, rng).0
+///     }
+///
+///     fn breed_pair(&self, other: &Self, rng: &mut impl Rng) -> (Self, Self) {
+///         let (first, second) = self.0.uniform_crossover(&other.0, rng);
+///         (Hyperparameters(first), Hyperparameters(second))
+///     }
+///
+///     fn mutate(&mut self, rate: f64, rng: &mut impl Rng) {
+///         self.0.creep_mutation(rate, 2, rng);
+///     }
+/// }
+///
+/// // Between 1 and 8 layers, and kernels of between 1 and 11
+/// let bounds = vec![(1, 8), (1, 11)];
+/// let mut rng = rand::thread_rng();
+/// let mut ecosystem = Ecosystem::builder()
+///     .organisms((0..20).map(|_| Hyperparameters(IntVector::random(&bounds, &mut rng))))
+///     .mutation_rate(0.5)
+///     .selection(Tournament { size: 3 })
+///     .elitism(1)
+///     .build()
+///     .unwrap();
+/// for _ in 0..50 {
+///     ecosystem.breed_next_generation()?;
+/// }
+///
+/// assert_eq!(ecosystem.fittest().unwrap().0.genes(), &[4, 7]);
+/// # Ok::<(), ecosystem::EcosystemError>(())
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct IntVector {
+    genes: Vec<i64>,
+    bounds: Vec<(i64, i64)>,
+}
+
+impl IntVector {
+    /// Creates a vector of the given genes, bounded only by the range of
+    /// `i64`.
+    pub fn new(genes: Vec<i64>) -> Self {
+        let bounds = vec![(i64::MIN, i64::MAX); genes.len()];
+        Self { genes, bounds }
+    }
+
+    /// Creates a vector of the given genes, each kept within the inclusive
+    /// bounds in the same position, given as (min, max) pairs. Genes outside
+    /// their bounds are clamped.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is not one pair of bounds for every gene, or if any
+    /// minimum is greater than its maximum.
+    pub fn with_bounds(genes: Vec<i64>, bounds: Vec<(i64, i64)>) -> Self {
+        assert_eq!(
+            genes.len(),
+            bounds.len(),
+            "there must be one pair of bounds for every gene"
+        );
+        assert!(
+            bounds.iter().all(|(min, max)| min <= max),
+            "minimums must not be greater than maximums"
+        );
+        let mut vector = Self { genes, bounds };
+        for index in 0..vector.genes.len() {
+            vector.set(index, vector.genes[index]);
+        }
+        vector
+    }
+
+    /// Creates a vector with a gene for every pair of bounds, each drawn
+    /// uniformly from within its bounds.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any minimum is greater than its maximum.
+    pub fn random(bounds: &[(i64, i64)], rng: &mut impl Rng) -> Self {
+        assert!(
+            bounds.iter().all(|(min, max)| min <= max),
+            "minimums must not be greater than maximums"
+        );
+        let genes = bounds
+            .iter()
+            .map(|&(min, max)| rng.sample(Uniform::new_inclusive(min, max)))
+            .collect();
+        Self::with_bounds(genes, bounds.to_vec())
+    }
+
+    /// Returns the genes.
+    pub fn genes(&self) -> &[i64] {
+        &self.genes
+    }
+
+    /// Returns the inclusive (min, max) bounds of each gene.
+    pub fn bounds(&self) -> &[(i64, i64)] {
+        &self.bounds
+    }
+
+    /// Returns the number of genes.
+    pub fn len(&self) -> usize {
+        self.genes.len()
+    }
+
+    /// Returns whether there are no genes.
+    pub fn is_empty(&self) -> bool {
+        self.genes.is_empty()
+    }
+
+    /// Sets the gene at the given index, clamping the value to its bounds.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index is out of bounds.
+    pub fn set(&mut self, index: usize, value: i64) {
+        let (min, max) = self.bounds[index];
+        self.genes[index] = value.clamp(min, max);
+    }
+
+    /// One-point crossover, which swaps the genes after a random point
+    /// between the two parents.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the vectors differ in length.
+    pub fn one_point_crossover(&self, other: &Self, rng: &mut impl Rng) -> (Self, Self) {
+        self.check_len(other);
+        let point = rng.gen_range(0, self.len() + 1);
+        self.swap_range(other, point, self.len())
+    }
+
+    /// Two-point crossover, which swaps the genes between two random points
+    /// between the two parents.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the vectors differ in length.
+    pub fn two_point_crossover(&self, other: &Self, rng: &mut impl Rng) -> (Self, Self) {
+        self.check_len(other);
+        let a = rng.gen_range(0, self.len() + 1);
+        let b = rng.gen_range(0, self.len() + 1);
+        self.swap_range(other, a.min(b), a.max(b))
+    }
+
+    /// Uniform crossover, in which each gene is swapped between the two
+    /// parents with a probability of one half.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the vectors differ in length.
+    pub fn uniform_crossover(&self, other: &Self, rng: &mut impl Rng) -> (Self, Self) {
+        self.check_len(other);
+        let mut first = self.clone();
+        let mut second = other.clone();
+        for index in 0..self.len() {
+            if rng.gen_bool(0.5) {
+                first.set(index, other.genes[index]);
+                second.set(index, self.genes[index]);
+            }
+        }
+        (first, second)
+    }
+
+    /// Replaces each gene, independently with a probability of `rate`, with
+    /// a value drawn uniformly from within its bounds.
+    pub fn uniform_mutation(&mut self, rate: f64, rng: &mut impl Rng) {
+        let rate = rate.clamp(0.0, 1.0);
+        for index in 0..self.len() {
+            if rng.gen_bool(rate) {
+                let (min, max) = self.bounds[index];
+                self.genes[index] = rng.sample(Uniform::new_inclusive(min, max));
+            }
+        }
+    }
+
+    /// Creep mutation, which nudges each gene, independently with a
+    /// probability of `rate`, up or down by between one and `max_step`. This
+    /// suits genes whose neighboring values behave similarly, such as counts
+    /// and sizes.
+    pub fn creep_mutation(&mut self, rate: f64, max_step: i64, rng: &mut impl Rng) {
+        let rate = rate.clamp(0.0, 1.0);
+        let max_step = max_step.max(1);
+        for index in 0..self.len() {
+            if rng.gen_bool(rate) {
+                let step = rng.sample(Uniform::new_inclusive(1, max_step));
+                let value = if rng.gen_bool(0.5) {
+                    self.genes[index].saturating_add(step)
+                } else {
+                    self.genes[index].saturating_sub(step)
+                };
+                self.set(index, value);
+            }
+        }
+    }
+
+    /// Returns copies of the two vectors with the genes in `start..end`
+    /// swapped between them.
+    fn swap_range(&self, other: &Self, start: usize, end: usize) -> (Self, Self) {
+        let mut first = self.clone();
+        let mut second = other.clone();
+        for index in start..end {
+            first.set(index, other.genes[index]);
+            second.set(index, self.genes[index]);
+        }
+        (first, second)
+    }
+
+    fn check_len(&self, other: &Self) {
+        assert_eq!(
+            self.len(),
+            other.len(),
+            "vectors of different lengths cannot be combined"
+        );
+    }
+}
+
+impl Distance for IntVector {
+    fn distance(&self, other: &Self) -> f64 {
+        self.genes
+            .iter()
+            .zip(&other.genes)
+            .map(|(&a, &b)| (a as f64 - b as f64).abs())
+            .sum()
+    }
+}
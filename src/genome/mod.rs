@@ -0,0 +1,66 @@
+//! Ready-made genomes, with the crossover and mutation operators commonly
+//! used with them.
+//!
+//! Each genome is a plain value rather than an organism, as its fitness
+//! depends on the problem being solved. An organism wraps a genome and
+//! forwards [`breed`], [`breed_pair`] and [`mutate`] to its operators:
+//!
+//! ```rust
+//! use ecosystem::{genome::BitString, Ecosystem, Organism};
+//! use rand::Rng;
+//!
+//! // The 'OneMax' problem: find the bit string with as many ones as possible
+//! struct OneMax(BitString);
+//!
+//! impl Organism for OneMax {
+//!     type Fitness = usize;
+//!
+//!     fn fitness(&self) -> usize {
+//!         self.0.count_ones()
+//!     }
+//!
+//!     fn breed(&self, other: &Self, rng: &mut impl Rng) -> Self {
+//!         self.breed_pair(other, rng).0
+//!     }
+//!
+//!     fn breed_pair(&self, other: &Self, rng: &mut impl Rng) -> (Self, Self) {
+//!         let (first, second) = self.0.uniform_crossover(&other.0, rng);
+//!         (OneMax(first), OneMax(second))
+//!     }
+//!
+//!     fn mutate(&mut self, rate: f64, rng: &mut impl Rng) {
+//!         self.0.flip_bits(rate, rng);
+//!     }
+//! }
+//!
+//! let mut rng = rand::thread_rng();
+//! let mut ecosystem = Ecosystem::builder()
+//!     .organisms((0..50).map(|_| OneMax(BitString::random(100, &mut rng))))
+//!     .mutation_rate(0.01)
+//!     .elitism(1)
+//!     .build()
+//!     .unwrap();
+//! for _ in 0..100 {
+//!     ecosystem.breed_next_generation()?;
+//! }
+//!
+//! assert!(ecosystem.fittest().unwrap().fitness() > 70);
+//! # Ok::<(), ecosystem::EcosystemError>(())
+//! ```
+//!
+//! [`breed`]: ../trait.Organism.html#tymethod.breed
+//! [`breed_pair`]: ../trait.Organism.html#method.breed_pair
+//! [`mutate`]: ../trait.Organism.html#tymethod.mutate
+mod bit_string;
+mod gene;
+mod int_vector;
+mod permutation;
+mod real_vector;
+
+pub use bit_string::{BitString, ParseBitStringError};
+pub use gene::Gene;
+pub use int_vector::IntVector;
+pub use permutation::Permutation;
+pub(crate) use real_vector::standard_normal;
+pub(crate) use real_vector::variance_scaling;
+pub use real_vector::{RealGenome, RealVector};
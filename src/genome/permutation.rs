@@ -0,0 +1,323 @@
+use crate::diversity::Distance;
+use rand::{seq::SliceRandom, Rng};
+
+/// An ordering of the numbers `0..n`, as used for routing and scheduling
+/// problems, in which each number might stand for a city or a job.
+///
+/// Every operator produces another valid permutation, in which each number
+/// appears exactly once. Crossover between two permutations produces a pair
+/// of children, as [`Organism::breed_pair`] expects, and panics if the
+/// parents differ in length. The distance between two permutations is the
+/// number of positions at which they differ.
+///
+/// [`Organism::breed_pair`]: ../trait.Organism.html#method.breed_pair
+///
+/// # Examples
+///
+/// ```rust
+/// use ecosystem::{genome::Permutation, selection::Tournament, Ecosystem, Organism};
+/// use rand::Rng;
+///
+/// // Sort the numbers by counting the adjacent pairs that are out of order
+/// struct Sorting(Permutation);
+///
+/// impl Organism for Sorting {
+///     type Fitness = f64;
+///
+///     fn fitness(&self) -> f64 {
+///         let order = self.0.as_slice();
+///         let unsorted = order.windows(2).filter(|pair| pair[0] > pair[1]).count();
+///         1.0 / (unsorted as f64 + 1.0)
+///     }
+///
+///     fn breed(&self, other: &Self, rng: &mut impl Rng) -> Self {
+///         self.breed_pair(other, rng).0
+///     }
+///
+///     fn breed_pair(&self, other: &Self, rng: &mut impl Rng) -> (Self, Self) {
+///         let (first, second) = self.0.order_crossover(&other.0, rng);
+///         (Sorting(first), Sorting(second))
+///     }
+///
+///     fn mutate(&mut self, rate: f64, rng: &mut impl Rng) {
+///         self.0.inversion_mutation(rate, rng);
+///     }
+/// }
+///
+/// let mut rng = rand::thread_rng();
+/// let mut ecosystem = Ecosystem::builder()
+///     .organisms((0..50).map(|_| Sorting(Permutation::random(10, &mut rng))))
+///     .mutation_rate(0.5)
+///     .selection(Tournament { size: 3 })
+///     .elitism(1)
+///     .build()
+///     .unwrap();
+/// for _ in 0..200 {
+///     ecosystem.breed_next_generation()?;
+/// }
+///
+/// assert!(ecosystem.fittest().unwrap().fitness() >= 0.5);
+/// # Ok::<(), ecosystem::EcosystemError>(())
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Permutation {
+    order: Vec<usize>,
+}
+
+impl Permutation {
+    /// Creates a permutation from the given order.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the order does not hold each of the numbers `0..n` exactly
+    /// once, where `n` is its length.
+    pub fn new(order: Vec<usize>) -> Self {
+        let mut seen = vec![false; order.len()];
+        for &value in &order {
+            assert!(
+                value < order.len() && !seen[value],
+                "the order must hold each of the numbers 0..{} exactly once",
+                order.len()
+            );
+            seen[value] = true;
+        }
+        Self { order }
+    }
+
+    /// Creates the permutation `0, 1, ..., n - 1`.
+    pub fn identity(n: usize) -> Self {
+        Self {
+            order: (0..n).collect(),
+        }
+    }
+
+    /// Creates a random permutation of the numbers `0..n`.
+    pub fn random(n: usize, rng: &mut impl Rng) -> Self {
+        let mut permutation = Self::identity(n);
+        permutation.order.shuffle(rng);
+        permutation
+    }
+
+    /// Returns the order as a slice.
+    pub fn as_slice(&self) -> &[usize] {
+        &self.order
+    }
+
+    /// Returns the number of elements.
+    pub fn len(&self) -> usize {
+        self.order.len()
+    }
+
+    /// Returns whether there are no elements.
+    pub fn is_empty(&self) -> bool {
+        self.order.is_empty()
+    }
+
+    /// Swaps the elements at the given positions.
+    ///
+    /// # Panics
+    ///
+    /// Panics if either position is out of bounds.
+    pub fn swap(&mut self, a: usize, b: usize) {
+        self.order.swap(a, b);
+    }
+
+    /// Reverses the order of the elements at positions `start..end`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `start` is greater than `end`, or if `end` is greater than
+    /// the length.
+    pub fn reverse(&mut self, start: usize, end: usize) {
+        self.order[start..end].reverse();
+    }
+
+    /// Partially mapped crossover (PMX), which gives each child a random
+    /// segment of one parent in place, and fills the rest of it from the
+    /// other parent, following the mapping between the two segments to
+    /// resolve any element that appears twice. This tends to keep elements in
+    /// the absolute positions they hold in the parents.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the permutations differ in length.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ecosystem::genome::Permutation;
+    ///
+    /// let mut rng = rand::thread_rng();
+    /// let mother = Permutation::random(20, &mut rng);
+    /// let father = Permutation::random(20, &mut rng);
+    /// let (first, second) = mother.pmx_crossover(&father, &mut rng);
+    ///
+    /// // Each child is still a permutation of the same elements
+    /// for child in vec![first, second] {
+    ///     let mut sorted = child.as_slice().to_vec();
+    ///     sorted.sort();
+    ///     assert_eq!(sorted, (0..20).collect::<Vec<_>>());
+    /// }
+    /// ```
+    pub fn pmx_crossover(&self, other: &Self, rng: &mut impl Rng) -> (Self, Self) {
+        self.check_len(other);
+        let (start, end) = self.cut_points(rng);
+        (
+            Self::pmx_child(self, other, start, end),
+            Self::pmx_child(other, self, start, end),
+        )
+    }
+
+    /// Order crossover (OX), which gives each child a random segment of one
+    /// parent in place, and fills the rest of it with the remaining elements
+    /// in the order they appear in the other parent, starting after the
+    /// segment. This tends to keep the relative order of the elements, which
+    /// suits problems such as the travelling salesman problem.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the permutations differ in length.
+    pub fn order_crossover(&self, other: &Self, rng: &mut impl Rng) -> (Self, Self) {
+        self.check_len(other);
+        let (start, end) = self.cut_points(rng);
+        (
+            Self::order_child(self, other, start, end),
+            Self::order_child(other, self, start, end),
+        )
+    }
+
+    /// Cycle crossover (CX), which divides the positions into cycles (sets of
+    /// positions that hold the same elements in both parents) and fills them
+    /// from each parent in turn. Every element of each child keeps the
+    /// position it holds in one of the parents.
+    ///
+    /// Cycle crossover is deterministic, so it takes no random number
+    /// generator.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the permutations differ in length.
+    pub fn cycle_crossover(&self, other: &Self) -> (Self, Self) {
+        self.check_len(other);
+        let positions = self.positions();
+        let mut first = self.order.clone();
+        let mut second = other.order.clone();
+        let mut visited = vec![false; self.len()];
+        let mut swap = false;
+        for start in 0..self.len() {
+            if visited[start] {
+                continue;
+            }
+            let mut position = start;
+            while !visited[position] {
+                visited[position] = true;
+                if swap {
+                    first[position] = other.order[position];
+                    second[position] = self.order[position];
+                }
+                position = positions[other.order[position]];
+            }
+            swap = !swap;
+        }
+        (Self { order: first }, Self { order: second })
+    }
+
+    /// Swaps each element with another at a random position, independently
+    /// with a probability of `rate`.
+    pub fn swap_mutation(&mut self, rate: f64, rng: &mut impl Rng) {
+        let rate = rate.clamp(0.0, 1.0);
+        for position in 0..self.len() {
+            if rng.gen_bool(rate) {
+                let other = rng.gen_range(0, self.len());
+                self.order.swap(position, other);
+            }
+        }
+    }
+
+    /// Reverses the order of a random segment, with a probability of `rate`.
+    /// For routing problems, this reverses part of the route while keeping
+    /// all but two of its links.
+    pub fn inversion_mutation(&mut self, rate: f64, rng: &mut impl Rng) {
+        if rng.gen_bool(rate.clamp(0.0, 1.0)) {
+            let (start, end) = self.cut_points(rng);
+            self.order[start..end].reverse();
+        }
+    }
+
+    /// Shuffles a random segment, with a probability of `rate`.
+    pub fn scramble_mutation(&mut self, rate: f64, rng: &mut impl Rng) {
+        if rng.gen_bool(rate.clamp(0.0, 1.0)) {
+            let (start, end) = self.cut_points(rng);
+            self.order[start..end].shuffle(rng);
+        }
+    }
+
+    /// Chooses the bounds of a random segment.
+    fn cut_points(&self, rng: &mut impl Rng) -> (usize, usize) {
+        let a = rng.gen_range(0, self.len() + 1);
+        let b = rng.gen_range(0, self.len() + 1);
+        (a.min(b), a.max(b))
+    }
+
+    /// Returns the position of each element.
+    fn positions(&self) -> Vec<usize> {
+        let mut positions = vec![0; self.len()];
+        for (position, &value) in self.order.iter().enumerate() {
+            positions[value] = position;
+        }
+        positions
+    }
+
+    /// Breeds a child with `segment`'s elements in `start..end` and the
+    /// rest placed as in `rest`, mapped through the segment where needed.
+    fn pmx_child(segment: &Self, rest: &Self, start: usize, end: usize) -> Self {
+        let positions = segment.positions();
+        let mut order = rest.order.clone();
+        order[start..end].copy_from_slice(&segment.order[start..end]);
+        for position in (0..start).chain(end..segment.len()) {
+            let mut value = rest.order[position];
+            while (start..end).contains(&positions[value]) {
+                value = rest.order[positions[value]];
+            }
+            order[position] = value;
+        }
+        Self { order }
+    }
+
+    /// Breeds a child with `segment`'s elements in `start..end` and the rest
+    /// in the order they appear in `rest`, starting after the segment.
+    fn order_child(segment: &Self, rest: &Self, start: usize, end: usize) -> Self {
+        let len = segment.len();
+        let mut in_segment = vec![false; len];
+        for &value in &segment.order[start..end] {
+            in_segment[value] = true;
+        }
+        let mut order = segment.order.clone();
+        let remaining = (0..len)
+            .map(|offset| rest.order[(end + offset) % len])
+            .filter(|&value| !in_segment[value]);
+        for (offset, value) in remaining.enumerate() {
+            order[(end + offset) % len] = value;
+        }
+        Self { order }
+    }
+
+    fn check_len(&self, other: &Self) {
+        assert_eq!(
+            self.len(),
+            other.len(),
+            "permutations of different lengths cannot be combined"
+        );
+    }
+}
+
+impl Distance for Permutation {
+    fn distance(&self, other: &Self) -> f64 {
+        self.order
+            .iter()
+            .zip(&other.order)
+            .filter(|(a, b)| a != b)
+            .count() as f64
+    }
+}
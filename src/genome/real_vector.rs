@@ -0,0 +1,465 @@
+use crate::{diversity::Distance, Individual, Organism, VarianceScaling};
+use rand::Rng;
+use std::f64::consts::PI;
+
+/// A vector of real numbers, each kept within its own bounds.
+///
+/// Every operator keeps the genes within their bounds, clamping any value
+/// that would fall outside them. Crossover between two vectors produces a
+/// pair of children, as [`Organism::breed_pair`] expects, and panics if the
+/// parents differ in length. The distance between two vectors is the
+/// Euclidean distance.
+///
+/// [`Organism::breed_pair`]: ../trait.Organism.html#method.breed_pair
+///
+/// # Examples
+///
+/// ```rust
+/// use ecosystem::{genome::RealVector, selection::Tournament, Ecosystem, Organism};
+/// use rand::Rng;
+///
+/// // Minimise the sphere function, whose minimum is at the origin
+/// struct Point(RealVector);
+///
+/// impl Organism for Point {
+///     type Fitness = f64;
+///
+///     fn fitness(&self) -> f64 {
+///         -self.0.genes().iter().map(|x| x * x).sum::<f64>()
+///     }
+///
+///     fn breed(&self, other: &Self, rng: &mut impl Rng) -> Self {
+///         self.breed_pair(other, rng).0
+///     }
+///
+///     fn breed_pair(&self, other: &Self, rng: &mut impl Rng) -> (Self, Self) {
+///         let (first, second) = self.0.sbx_crossover(&other.0, 15.0, rng);
+///         (Point(first), Point(second))
+///     }
+///
+///     fn mutate(&mut self, rate: f64, rng: &mut impl Rng) {
+///         self.0.polynomial_mutation(rate, 20.0, rng);
+///     }
+/// }
+///
+/// let bounds = vec![(-5.0, 5.0); 4];
+/// let mut rng = rand::thread_rng();
+/// let mut ecosystem = Ecosystem::builder()
+///     .organisms((0..50).map(|_| Point(RealVector::random(&bounds, &mut rng))))
+///     .mutation_rate(0.25)
+///     .selection(Tournament { size: 3 })
+///     .elitism(1)
+///     .build()
+///     .unwrap();
+/// for _ in 0..100 {
+///     ecosystem.breed_next_generation()?;
+/// }
+///
+/// let best = ecosystem.fittest().unwrap();
+/// assert!(best.fitness() > -0.1);
+/// assert!(best.0.genes().iter().all(|x| (-5.0..=5.0).contains(x)));
+/// # Ok::<(), ecosystem::EcosystemError>(())
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RealVector {
+    genes: Vec<f64>,
+    bounds: Vec<(f64, f64)>,
+}
+
+impl RealVector {
+    /// Creates a vector of the given genes, with no bounds on their values.
+    pub fn new(genes: Vec<f64>) -> Self {
+        let bounds = vec![(f64::NEG_INFINITY, f64::INFINITY); genes.len()];
+        Self { genes, bounds }
+    }
+
+    /// Creates a vector of the given genes, each kept within the bounds in
+    /// the same position, given as (lower, upper) pairs. Genes outside their
+    /// bounds are clamped.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is not one pair of bounds for every gene, or if any
+    /// lower bound is greater than its upper bound.
+    pub fn with_bounds(genes: Vec<f64>, bounds: Vec<(f64, f64)>) -> Self {
+        assert_eq!(
+            genes.len(),
+            bounds.len(),
+            "there must be one pair of bounds for every gene"
+        );
+        assert!(
+            bounds.iter().all(|(lower, upper)| lower <= upper),
+            "lower bounds must not be greater than upper bounds"
+        );
+        let mut vector = Self { genes, bounds };
+        for index in 0..vector.genes.len() {
+            vector.set(index, vector.genes[index]);
+        }
+        vector
+    }
+
+    /// Creates a vector with a gene for every pair of bounds, each drawn
+    /// uniformly from within its bounds.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any bound is infinite, or if any lower bound is greater than
+    /// its upper bound.
+    pub fn random(bounds: &[(f64, f64)], rng: &mut impl Rng) -> Self {
+        assert!(
+            bounds
+                .iter()
+                .all(|(lower, upper)| lower.is_finite() && upper.is_finite()),
+            "random genes need finite bounds"
+        );
+        let genes = bounds
+            .iter()
+            .map(|&(lower, upper)| uniform(lower, upper, rng))
+            .collect();
+        Self::with_bounds(genes, bounds.to_vec())
+    }
+
+    /// Returns the genes.
+    pub fn genes(&self) -> &[f64] {
+        &self.genes
+    }
+
+    /// Returns the (lower, upper) bounds of each gene.
+    pub fn bounds(&self) -> &[(f64, f64)] {
+        &self.bounds
+    }
+
+    /// Returns the number of genes.
+    pub fn len(&self) -> usize {
+        self.genes.len()
+    }
+
+    /// Returns whether there are no genes.
+    pub fn is_empty(&self) -> bool {
+        self.genes.is_empty()
+    }
+
+    /// Sets the gene at the given index, clamping the value to its bounds.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index is out of bounds.
+    pub fn set(&mut self, index: usize, value: f64) {
+        let (lower, upper) = self.bounds[index];
+        self.genes[index] = value.clamp(lower, upper);
+    }
+
+    /// Simulated binary crossover (SBX), which spreads the children around
+    /// the parents much as one-point crossover spreads binary-encoded
+    /// children. Each gene is crossed with a probability of one half.
+    ///
+    /// The distribution index `eta` controls the spread: large values (such
+    /// as 20) give children close to their parents, and small values (such
+    /// as 2) give children further away. Children are drawn from a
+    /// distribution that is truncated at the bounds, rather than clamped to
+    /// them.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the vectors differ in length.
+    pub fn sbx_crossover(&self, other: &Self, eta: f64, rng: &mut impl Rng) -> (Self, Self) {
+        self.check_len(other);
+        let mut first = self.clone();
+        let mut second = other.clone();
+        for index in 0..self.len() {
+            let (a, b) = (self.genes[index], other.genes[index]);
+            if !rng.gen_bool(0.5) || (a - b).abs() < f64::EPSILON {
+                continue;
+            }
+
+            let (lower, upper) = self.bounds[index];
+            let (low, high) = (a.min(b), a.max(b));
+            let spread = high - low;
+            let u: f64 = rng.gen();
+            let spread_factor = |distance: f64| {
+                // The probability mass that lies within the bounds
+                let beta = 1.0 + 2.0 * distance / spread;
+                let alpha = 2.0 - beta.powf(-(eta + 1.0));
+                if u <= 1.0 / alpha {
+                    (u * alpha).powf(1.0 / (eta + 1.0))
+                } else {
+                    (1.0 / (2.0 - u * alpha)).powf(1.0 / (eta + 1.0))
+                }
+            };
+            let mut children = (
+                0.5 * (low + high - spread_factor(low - lower) * spread),
+                0.5 * (low + high + spread_factor(upper - high) * spread),
+            );
+            if rng.gen_bool(0.5) {
+                children = (children.1, children.0);
+            }
+            first.set(index, children.0);
+            second.set(index, children.1);
+        }
+        (first, second)
+    }
+
+    /// Blend crossover (BLX-α), which draws each gene of each child uniformly
+    /// from the range between the parents' genes, extended on each side by
+    /// `alpha` times the range's width. An `alpha` of 0.5 is common.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the vectors differ in length.
+    pub fn blx_crossover(&self, other: &Self, alpha: f64, rng: &mut impl Rng) -> (Self, Self) {
+        self.check_len(other);
+        let mut first = self.clone();
+        let mut second = other.clone();
+        for index in 0..self.len() {
+            let (a, b) = (self.genes[index], other.genes[index]);
+            let extension = alpha * (a - b).abs();
+            let (low, high) = (a.min(b) - extension, a.max(b) + extension);
+            first.set(index, uniform(low, high, rng));
+            second.set(index, uniform(low, high, rng));
+        }
+        (first, second)
+    }
+
+    /// Whole arithmetic crossover, which takes a random weighted average of
+    /// the parents: with a weight `w` drawn from [0, 1], the first child is
+    /// `w * self + (1 - w) * other` and the second is the reverse. The
+    /// children always lie between their parents, so they stay within the
+    /// bounds.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the vectors differ in length.
+    pub fn arithmetic_crossover(&self, other: &Self, rng: &mut impl Rng) -> (Self, Self) {
+        self.check_len(other);
+        let weight: f64 = rng.gen();
+        let mut first = self.clone();
+        let mut second = other.clone();
+        for index in 0..self.len() {
+            let (a, b) = (self.genes[index], other.genes[index]);
+            first.set(index, weight * a + (1.0 - weight) * b);
+            second.set(index, weight * b + (1.0 - weight) * a);
+        }
+        (first, second)
+    }
+
+    /// Adds normally distributed noise, with the given standard deviation, to
+    /// each gene independently with a probability of `rate`.
+    pub fn gaussian_mutation(&mut self, rate: f64, std_dev: f64, rng: &mut impl Rng) {
+        let rate = rate.clamp(0.0, 1.0);
+        for index in 0..self.len() {
+            if rng.gen_bool(rate) {
+                let value = self.genes[index] + std_dev * standard_normal(rng);
+                self.set(index, value);
+            }
+        }
+    }
+
+    /// Returns the variance of each gene across the given vectors, for use
+    /// with [variance-scaled mutation]. Genes missing from shorter vectors
+    /// are left out, and the variance of a gene that no vector has is zero.
+    ///
+    /// [variance-scaled mutation]: #method.variance_scaled_mutation
+    pub fn gene_variances<'a>(vectors: impl IntoIterator<Item = &'a Self>) -> Vec<f64> {
+        // Welford's algorithm, which stays accurate for genes that have
+        // nearly converged
+        let mut counts = Vec::new();
+        let mut means = Vec::new();
+        let mut sums_of_squares = Vec::new();
+        for vector in vectors {
+            if vector.len() > counts.len() {
+                counts.resize(vector.len(), 0.0);
+                means.resize(vector.len(), 0.0);
+                sums_of_squares.resize(vector.len(), 0.0);
+            }
+            for (index, &gene) in vector.genes.iter().enumerate() {
+                counts[index] += 1.0;
+                let delta = gene - means[index];
+                means[index] += delta / counts[index];
+                sums_of_squares[index] += delta * (gene - means[index]);
+            }
+        }
+        sums_of_squares
+            .into_iter()
+            .zip(counts)
+            .map(|(sum, count)| sum / count)
+            .collect()
+    }
+
+    /// Adds normally distributed noise to each gene independently with a
+    /// probability of `rate`, with a standard deviation of `scale` times the
+    /// gene's standard deviation across the population, as given by its
+    /// variance in `variances`.
+    ///
+    /// Genes on which the population has converged are barely mutated,
+    /// while those on which it still differs keep exploring, so the step
+    /// sizes adapt to the search as it progresses without any tuning
+    /// beyond `scale`. The variances are computed with [`gene_variances`].
+    /// Genes without a variance are left unchanged.
+    ///
+    /// An ecosystem of organisms that implement [`RealGenome`] mutates them
+    /// this way, with the variances of its current population, once
+    /// [`EcosystemBuilder::variance_scaled_mutation`] is enabled.
+    ///
+    /// [`gene_variances`]: #method.gene_variances
+    /// [`RealGenome`]: trait.RealGenome.html
+    /// [`EcosystemBuilder::variance_scaled_mutation`]: ../struct.EcosystemBuilder.html#method.variance_scaled_mutation
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ecosystem::genome::RealVector;
+    ///
+    /// // The first gene has converged, while the second is spread out
+    /// let population: Vec<_> = (0..20)
+    ///     .map(|i| RealVector::new(vec![1.0, f64::from(i) - 10.0]))
+    ///     .collect();
+    /// let variances = RealVector::gene_variances(&population);
+    /// assert_eq!(variances[0], 0.0);
+    /// assert!(variances[1] > 30.0);
+    ///
+    /// let mut rng = rand::thread_rng();
+    /// let (mut first_shift, mut second_shift) = (0.0, 0.0);
+    /// for _ in 0..100 {
+    ///     let mut vector = RealVector::new(vec![1.0, 0.0]);
+    ///     vector.variance_scaled_mutation(1.0, &variances, 0.5, &mut rng);
+    ///     first_shift += (vector.genes()[0] - 1.0).abs() / 100.0;
+    ///     second_shift += vector.genes()[1].abs() / 100.0;
+    /// }
+    ///
+    /// assert!(first_shift < 1e-9);
+    /// assert!(second_shift > 1.0);
+    /// ```
+    pub fn variance_scaled_mutation(
+        &mut self,
+        rate: f64,
+        variances: &[f64],
+        scale: f64,
+        rng: &mut impl Rng,
+    ) {
+        let rate = rate.clamp(0.0, 1.0);
+        for (index, &variance) in variances.iter().enumerate().take(self.len()) {
+            if rng.gen_bool(rate) {
+                let std_dev = scale * variance.max(0.0).sqrt();
+                let value = self.genes[index] + std_dev * standard_normal(rng);
+                self.set(index, value);
+            }
+        }
+    }
+
+    /// Polynomial mutation, which perturbs each gene independently with a
+    /// probability of `rate` by an amount drawn from a polynomial
+    /// distribution scaled to the width of its bounds.
+    ///
+    /// As with [simulated binary crossover], the distribution index `eta`
+    /// controls the spread, with larger values giving smaller perturbations.
+    ///
+    /// [simulated binary crossover]: #method.sbx_crossover
+    ///
+    /// # Panics
+    ///
+    /// Panics if a gene to be mutated has an infinite bound.
+    pub fn polynomial_mutation(&mut self, rate: f64, eta: f64, rng: &mut impl Rng) {
+        let rate = rate.clamp(0.0, 1.0);
+        for index in 0..self.len() {
+            if !rng.gen_bool(rate) {
+                continue;
+            }
+            let (lower, upper) = self.bounds[index];
+            assert!(
+                lower.is_finite() && upper.is_finite(),
+                "polynomial mutation needs finite bounds"
+            );
+            let width = upper - lower;
+            if width == 0.0 {
+                continue;
+            }
+
+            let value = self.genes[index];
+            let u: f64 = rng.gen();
+            let power = 1.0 / (eta + 1.0);
+            let shift = if u < 0.5 {
+                let reach = 1.0 - (value - lower) / width;
+                (2.0 * u + (1.0 - 2.0 * u) * reach.powf(eta + 1.0)).powf(power) - 1.0
+            } else {
+                let reach = 1.0 - (upper - value) / width;
+                1.0 - (2.0 * (1.0 - u) + 2.0 * (u - 0.5) * reach.powf(eta + 1.0)).powf(power)
+            };
+            self.set(index, value + shift * width);
+        }
+    }
+
+    fn check_len(&self, other: &Self) {
+        assert_eq!(
+            self.len(),
+            other.len(),
+            "vectors of different lengths cannot be combined"
+        );
+    }
+}
+
+impl Distance for RealVector {
+    fn distance(&self, other: &Self) -> f64 {
+        self.genes
+            .iter()
+            .zip(&other.genes)
+            .map(|(a, b)| (a - b) * (a - b))
+            .sum::<f64>()
+            .sqrt()
+    }
+}
+
+/// An interface for organisms whose genome is a [`RealVector`], which lets
+/// the ecosystem mutate them by [variance-scaled mutation] once it is
+/// enabled with [`EcosystemBuilder::variance_scaled_mutation`].
+///
+/// [`RealVector`]: struct.RealVector.html
+/// [variance-scaled mutation]: struct.RealVector.html#method.variance_scaled_mutation
+/// [`EcosystemBuilder::variance_scaled_mutation`]: ../struct.EcosystemBuilder.html#method.variance_scaled_mutation
+pub trait RealGenome {
+    /// Returns the organism's genome.
+    fn real_vector(&self) -> &RealVector;
+
+    /// Returns the organism's genome, to be mutated.
+    fn real_vector_mut(&mut self) -> &mut RealVector;
+}
+
+/// Returns the functions by which the ecosystem measures the variance of
+/// each gene across its population, and mutates a child with them, scaling
+/// each gene's standard deviation by `scale`.
+pub(crate) fn variance_scaling<O: Organism + RealGenome>(scale: f64) -> VarianceScaling<O> {
+    (
+        scale,
+        |population| {
+            RealVector::gene_variances(
+                population
+                    .iter()
+                    .map(|individual: &Individual<O>| individual.organism().real_vector()),
+            )
+        },
+        |organism, rate, variances, scale, rng| {
+            organism
+                .real_vector_mut()
+                .variance_scaled_mutation(rate, variances, scale, rng)
+        },
+    )
+}
+
+/// Draws a value uniformly from [low, high), or returns `low` if the range
+/// is empty.
+fn uniform(low: f64, high: f64, rng: &mut impl Rng) -> f64 {
+    if low < high {
+        rng.gen_range(low, high)
+    } else {
+        low
+    }
+}
+
+/// Draws a value from the standard normal distribution, using the
+/// Box-Muller transform.
+pub(crate) fn standard_normal(rng: &mut impl Rng) -> f64 {
+    // Avoids taking the logarithm of zero
+    let u = 1.0 - rng.gen::<f64>();
+    let v: f64 = rng.gen();
+    (-2.0 * u.ln()).sqrt() * (2.0 * PI * v).cos()
+}
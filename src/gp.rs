@@ -0,0 +1,488 @@
+//! Genetic programming, in which the organisms are programs represented as
+//! expression trees.
+//!
+//! The programs are built from a [`PrimitiveSet`] of user-defined
+//! [primitives]: functions, which take the values of one or more child
+//! subtrees, and terminals, such as inputs and constants, which take none.
+//! A [`Tree`] holds no meaning of its own; it is run by [evaluating] it with
+//! a function that gives the value of each primitive from the values of its
+//! children.
+//!
+//! Trees tend to grow without improving as a run goes on, which is known as
+//! bloat. The primitive set limits the depth and size of the trees, and
+//! crossover and mutation give up on any child that would exceed them. To
+//! favor smaller trees further, an organism can break ties in favor of
+//! smaller trees with a lexicographic fitness, as in the example below.
+//!
+//! ```rust
+//! use ecosystem::{
+//!     gp::{Primitive, PrimitiveSet, Tree},
+//!     selection::Tournament,
+//!     Ecosystem, Organism,
+//! };
+//! use rand::{Rng, SeedableRng};
+//! use rand_pcg::Pcg64;
+//! use std::sync::Arc;
+//!
+//! #[derive(Clone, Debug)]
+//! enum Node {
+//!     Add,
+//!     Multiply,
+//!     X,
+//!     One,
+//! }
+//!
+//! impl Primitive for Node {
+//!     fn arity(&self) -> usize {
+//!         match self {
+//!             Node::Add | Node::Multiply => 2,
+//!             Node::X | Node::One => 0,
+//!         }
+//!     }
+//! }
+//!
+//! // Symbolic regression: find a program that computes x² + x + 1
+//! struct Program(Tree<Node>);
+//!
+//! impl Program {
+//!     fn run(&self, x: f64) -> f64 {
+//!         self.0.evaluate(|node, inputs| match node {
+//!             Node::Add => inputs[0] + inputs[1],
+//!             Node::Multiply => inputs[0] * inputs[1],
+//!             Node::X => x,
+//!             Node::One => 1.0,
+//!         })
+//!     }
+//! }
+//!
+//! impl Organism for Program {
+//!     // The error comes first, so smaller programs only win ties
+//!     type Fitness = (f64, i64);
+//!
+//!     fn fitness(&self) -> (f64, i64) {
+//!         let error: f64 = (-5..=5)
+//!             .map(|x| f64::from(x))
+//!             .map(|x| (self.run(x) - (x * x + x + 1.0)).abs())
+//!             .sum();
+//!         (-error, -(self.0.size() as i64))
+//!     }
+//!
+//!     fn breed(&self, other: &Self, rng: &mut impl Rng) -> Self {
+//!         self.breed_pair(other, rng).0
+//!     }
+//!
+//!     fn breed_pair(&self, other: &Self, rng: &mut impl Rng) -> (Self, Self) {
+//!         let (first, second) = self.0.subtree_crossover(&other.0, rng);
+//!         (Program(first), Program(second))
+//!     }
+//!
+//!     fn mutate(&mut self, rate: f64, rng: &mut impl Rng) {
+//!         self.0.subtree_mutation(rate, 3, rng);
+//!     }
+//! }
+//!
+//! let primitives = Arc::new(
+//!     PrimitiveSet::new(vec![Node::Add, Node::Multiply, Node::X, Node::One]).max_depth(6),
+//! );
+//! let mut rng = Pcg64::seed_from_u64(0);
+//! let trees = Tree::ramped_half_and_half(&primitives, 100, 1, 4, &mut rng);
+//! let mut ecosystem = Ecosystem::builder()
+//!     .organisms(trees.into_iter().map(Program))
+//!     .mutation_rate(0.1)
+//!     .selection(Tournament { size: 4 })
+//!     .elitism(1)
+//!     .seed(0)
+//!     .build()
+//!     .unwrap();
+//! for _ in 0..30 {
+//!     ecosystem.breed_next_generation()?;
+//! }
+//!
+//! let best = ecosystem.fittest().unwrap();
+//! assert_eq!(best.fitness().0, 0.0);
+//! assert!(best.0.depth() <= 6);
+//! # Ok::<(), ecosystem::EcosystemError>(())
+//! ```
+//!
+//! [`PrimitiveSet`]: struct.PrimitiveSet.html
+//! [primitives]: trait.Primitive.html
+//! [`Tree`]: struct.Tree.html
+//! [evaluating]: struct.Tree.html#method.evaluate
+use crate::Generator;
+use rand::{seq::SliceRandom, Rng, RngCore};
+use std::{fmt, sync::Arc};
+
+/// The proportion of crossover and mutation points that are chosen among a
+/// tree's functions rather than its terminals, as recommended by Koza.
+const FUNCTION_POINT_PROBABILITY: f64 = 0.9;
+
+/// An interface for the functions and terminals from which trees are built.
+pub trait Primitive: Clone {
+    /// Returns the number of children the primitive takes, which is zero for
+    /// terminals.
+    fn arity(&self) -> usize;
+}
+
+/// The primitives from which trees are built, along with the limits on their
+/// depth and size.
+///
+/// Trees keep a shared reference to their primitive set, so that crossover
+/// and mutation can draw new primitives from it and respect its limits.
+pub struct PrimitiveSet<P> {
+    functions: Vec<P>,
+    terminals: Vec<P>,
+    constants: Option<Generator<P>>,
+    max_depth: usize,
+    max_size: usize,
+}
+
+impl<P: Primitive> PrimitiveSet<P> {
+    /// Creates a set of the given primitives, which are divided into
+    /// functions and terminals by their arity.
+    ///
+    /// Trees are limited to a depth of 17, as recommended by Koza, and are
+    /// not limited in size.
+    pub fn new(primitives: impl IntoIterator<Item = P>) -> Self {
+        let (terminals, functions) = primitives
+            .into_iter()
+            .partition(|primitive| primitive.arity() == 0);
+        Self {
+            functions,
+            terminals,
+            constants: None,
+            max_depth: 17,
+            max_size: usize::MAX,
+        }
+    }
+
+    /// Adds ephemeral random constants: terminals created by the given
+    /// function whenever a terminal is chosen, with the same chance as any
+    /// one of the other terminals.
+    pub fn constants<G>(mut self, generate: G) -> Self
+    where
+        G: Fn(&mut dyn RngCore) -> P + Send + Sync + 'static,
+    {
+        self.constants = Some(Box::new(generate));
+        self
+    }
+
+    /// Sets the maximum depth of a tree, where a tree of a single terminal
+    /// has a depth of zero.
+    pub fn max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = depth;
+        self
+    }
+
+    /// Sets the maximum number of nodes in a tree.
+    pub fn max_size(mut self, size: usize) -> Self {
+        self.max_size = size;
+        self
+    }
+
+    /// Returns the functions.
+    pub fn functions(&self) -> &[P] {
+        &self.functions
+    }
+
+    /// Returns the terminals, not including any ephemeral random constants.
+    pub fn terminals(&self) -> &[P] {
+        &self.terminals
+    }
+
+    /// Chooses a random terminal, which may be a new constant.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there are no terminals or constants.
+    fn random_terminal(&self, rng: &mut impl RngCore) -> P {
+        let choices = self.terminals.len() + self.constants.is_some() as usize;
+        assert!(choices > 0, "the primitive set has no terminals");
+        let choice = rng.gen_range(0, choices);
+        match self.terminals.get(choice) {
+            Some(terminal) => terminal.clone(),
+            None => (self.constants.as_ref().unwrap())(rng),
+        }
+    }
+
+    /// Chooses a random primitive with the given arity, or `None` if there
+    /// is none.
+    fn random_with_arity(&self, arity: usize, rng: &mut impl RngCore) -> Option<P> {
+        if arity == 0 {
+            return Some(self.random_terminal(rng));
+        }
+        let candidates: Vec<_> = self
+            .functions
+            .iter()
+            .filter(|function| function.arity() == arity)
+            .collect();
+        candidates.choose(rng).map(|&function| function.clone())
+    }
+
+    /// Appends a random tree to `nodes`, in prefix order. Full trees have
+    /// every terminal at exactly the given depth; grown trees have terminals
+    /// at any depth up to it.
+    fn generate(&self, depth: usize, full: bool, rng: &mut impl RngCore, nodes: &mut Vec<P>) {
+        let terminal_choices = self.terminals.len() + self.constants.is_some() as usize;
+        let choose_terminal = depth == 0
+            || self.functions.is_empty()
+            || (!full
+                && rng.gen_range(0, terminal_choices + self.functions.len()) < terminal_choices);
+        if choose_terminal {
+            nodes.push(self.random_terminal(rng));
+        } else {
+            let function = self.functions.choose(rng).unwrap().clone();
+            let arity = function.arity();
+            nodes.push(function);
+            for _ in 0..arity {
+                self.generate(depth - 1, full, rng, nodes);
+            }
+        }
+    }
+}
+
+/// An expression tree, stored as a list of primitives in prefix order (each
+/// function followed by the subtrees of its children).
+///
+/// Crossover between two trees produces a pair of children, as
+/// [`Organism::breed_pair`] expects. Where a child would exceed the limits
+/// of the primitive set, the corresponding parent is returned in its place.
+///
+/// [`Organism::breed_pair`]: ../trait.Organism.html#method.breed_pair
+pub struct Tree<P> {
+    nodes: Vec<P>,
+    primitives: Arc<PrimitiveSet<P>>,
+}
+
+impl<P: Primitive> Tree<P> {
+    /// Creates a tree from primitives in prefix order.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the primitives do not form exactly one complete tree.
+    pub fn new(primitives: &Arc<PrimitiveSet<P>>, nodes: Vec<P>) -> Self {
+        assert!(
+            !nodes.is_empty() && subtree_end(&nodes, 0) == Some(nodes.len()),
+            "the primitives must form exactly one complete tree"
+        );
+        Self {
+            nodes,
+            primitives: Arc::clone(primitives),
+        }
+    }
+
+    /// Creates a random tree with every terminal at the given depth.
+    pub fn full(primitives: &Arc<PrimitiveSet<P>>, depth: usize, rng: &mut impl RngCore) -> Self {
+        Self::generate(primitives, depth, true, rng)
+    }
+
+    /// Creates a random tree with terminals at any depth up to the given
+    /// depth.
+    pub fn grow(primitives: &Arc<PrimitiveSet<P>>, depth: usize, rng: &mut impl RngCore) -> Self {
+        Self::generate(primitives, depth, false, rng)
+    }
+
+    /// Creates `count` random trees by the 'ramped half-and-half' method,
+    /// which spreads them evenly over the depths from `min_depth` to
+    /// `max_depth`, creating half of those at each depth by the [full] method
+    /// and half by the [grow] method. This gives a diverse initial population.
+    ///
+    /// [full]: #method.full
+    /// [grow]: #method.grow
+    pub fn ramped_half_and_half(
+        primitives: &Arc<PrimitiveSet<P>>,
+        count: usize,
+        min_depth: usize,
+        max_depth: usize,
+        rng: &mut impl RngCore,
+    ) -> Vec<Self> {
+        let depths = max_depth.saturating_sub(min_depth) + 1;
+        (0..count)
+            .map(|i| {
+                let depth = min_depth + i % depths;
+                Self::generate(primitives, depth, (i / depths).is_multiple_of(2), rng)
+            })
+            .collect()
+    }
+
+    fn generate(
+        primitives: &Arc<PrimitiveSet<P>>,
+        depth: usize,
+        full: bool,
+        rng: &mut impl RngCore,
+    ) -> Self {
+        let mut nodes = Vec::new();
+        primitives.generate(depth, full, rng, &mut nodes);
+        Self {
+            nodes,
+            primitives: Arc::clone(primitives),
+        }
+    }
+
+    /// Returns the primitives in prefix order.
+    pub fn nodes(&self) -> &[P] {
+        &self.nodes
+    }
+
+    /// Returns the primitive set that the tree is built from.
+    pub fn primitives(&self) -> &Arc<PrimitiveSet<P>> {
+        &self.primitives
+    }
+
+    /// Returns the number of nodes.
+    pub fn size(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Returns the length of the longest path from the root to a terminal,
+    /// which is zero for a tree of a single terminal.
+    pub fn depth(&self) -> usize {
+        self.evaluate(|_, depths: &[usize]| depths.iter().max().map_or(0, |&deepest| deepest + 1))
+    }
+
+    /// Evaluates the tree from its terminals up, calling `evaluate` with each
+    /// primitive and the values of its children (in order) to find its own
+    /// value, and returning the value of the root.
+    pub fn evaluate<V>(&self, mut evaluate: impl FnMut(&P, &[V]) -> V) -> V {
+        let mut values = Vec::new();
+        for node in self.nodes.iter().rev() {
+            // The values of the children are on top of the stack, with the
+            // first child on the very top
+            let mut children = values.split_off(values.len() - node.arity());
+            children.reverse();
+            values.push(evaluate(node, &children));
+        }
+        values.pop().unwrap()
+    }
+
+    /// Subtree crossover, which swaps a randomly chosen subtree of each
+    /// parent with one of the other. Either child that would exceed the
+    /// limits of the primitive set is replaced by a copy of its parent.
+    pub fn subtree_crossover(&self, other: &Self, rng: &mut impl Rng) -> (Self, Self) {
+        let (start, end) = self.random_subtree(rng);
+        let (other_start, other_end) = other.random_subtree(rng);
+
+        let first = self.replace(start, end, &other.nodes[other_start..other_end]);
+        let second = other.replace(other_start, other_end, &self.nodes[start..end]);
+        (
+            first.unwrap_or_else(|| self.clone()),
+            second.unwrap_or_else(|| other.clone()),
+        )
+    }
+
+    /// Replaces each node, independently with a probability of `rate`, with
+    /// a random primitive of the same arity. This changes the tree without
+    /// changing its shape.
+    pub fn point_mutation(&mut self, rate: f64, rng: &mut impl Rng) {
+        let rate = rate.clamp(0.0, 1.0);
+        for index in 0..self.nodes.len() {
+            if rng.gen_bool(rate) {
+                let arity = self.nodes[index].arity();
+                if let Some(primitive) = self.primitives.random_with_arity(arity, rng) {
+                    self.nodes[index] = primitive;
+                }
+            }
+        }
+    }
+
+    /// Replaces a randomly chosen subtree, with a probability of `rate`, with
+    /// a new random tree grown to at most the given depth. The tree is left
+    /// unchanged if the result would exceed the limits of the primitive set.
+    pub fn subtree_mutation(&mut self, rate: f64, depth: usize, rng: &mut impl Rng) {
+        if !rng.gen_bool(rate.clamp(0.0, 1.0)) {
+            return;
+        }
+        let (start, end) = self.random_subtree(rng);
+        let mut subtree = Vec::new();
+        self.primitives.generate(depth, false, rng, &mut subtree);
+        if let Some(mutated) = self.replace(start, end, &subtree) {
+            *self = mutated;
+        }
+    }
+
+    /// Chooses a random subtree, returning the range of its nodes. Points
+    /// are usually chosen among the functions, so that crossover and
+    /// mutation don't mostly exchange single terminals.
+    fn random_subtree(&self, rng: &mut impl Rng) -> (usize, usize) {
+        let functions: Vec<_> = (0..self.nodes.len())
+            .filter(|&index| self.nodes[index].arity() > 0)
+            .collect();
+        let start = if !functions.is_empty() && rng.gen_bool(FUNCTION_POINT_PROBABILITY) {
+            *functions.choose(rng).unwrap()
+        } else {
+            rng.gen_range(0, self.nodes.len())
+        };
+        (start, subtree_end(&self.nodes, start).unwrap())
+    }
+
+    /// Returns a copy of the tree with the nodes in `start..end` replaced by
+    /// the given subtree, or `None` if it would exceed the limits of the
+    /// primitive set.
+    fn replace(&self, start: usize, end: usize, subtree: &[P]) -> Option<Self> {
+        let size = self.nodes.len() - (end - start) + subtree.len();
+        if size > self.primitives.max_size {
+            return None;
+        }
+        let mut nodes = Vec::with_capacity(size);
+        nodes.extend_from_slice(&self.nodes[..start]);
+        nodes.extend_from_slice(subtree);
+        nodes.extend_from_slice(&self.nodes[end..]);
+        let tree = Self {
+            nodes,
+            primitives: Arc::clone(&self.primitives),
+        };
+        if tree.depth() > self.primitives.max_depth {
+            return None;
+        }
+        Some(tree)
+    }
+}
+
+/// Returns the end of the subtree that starts at the given index, or `None`
+/// if the nodes run out before it is complete.
+fn subtree_end<P: Primitive>(nodes: &[P], start: usize) -> Option<usize> {
+    let mut open = 1;
+    let mut index = start;
+    while open > 0 {
+        open += nodes.get(index)?.arity();
+        open -= 1;
+        index += 1;
+    }
+    Some(index)
+}
+
+impl<P: Clone> Clone for Tree<P> {
+    fn clone(&self) -> Self {
+        Self {
+            nodes: self.nodes.clone(),
+            primitives: Arc::clone(&self.primitives),
+        }
+    }
+}
+
+impl<P: fmt::Debug> fmt::Debug for Tree<P> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Tree").field("nodes", &self.nodes).finish()
+    }
+}
+
+/// Trees are equal if they have the same nodes, whatever their primitive
+/// sets.
+impl<P: PartialEq> PartialEq for Tree<P> {
+    fn eq(&self, other: &Self) -> bool {
+        self.nodes == other.nodes
+    }
+}
+
+/// Trees are displayed as S-expressions, such as `(add x (mul x 2))`.
+impl<P: Primitive + fmt::Display> fmt::Display for Tree<P> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let text = self.evaluate(|node, children: &[String]| {
+            if children.is_empty() {
+                node.to_string()
+            } else {
+                format!("({} {})", node, children.join(" "))
+            }
+        });
+        f.write_str(&text)
+    }
+}
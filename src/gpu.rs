@@ -0,0 +1,467 @@
+//! Fitness evaluation of real-valued populations on the GPU.
+//!
+//! A [`GpuEvaluator`] is a [`BatchEvaluator`] that runs a WGSL compute
+//! kernel over the genes of every organism awaiting evaluation at once,
+//! through [wgpu]. For large populations of cheap-to-evaluate vectors, this
+//! is far faster than evaluating them one at a time on the CPU.
+//!
+//! A kernel is a WGSL function named `fitness`, taking the index of an
+//! organism and returning its fitness as an `f32`. The evaluator places the
+//! following declarations before it, so that it can read the organism's
+//! genes:
+//!
+//! ```wgsl
+//! struct Params {
+//!     count: u32,
+//!     dimensions: u32,
+//! }
+//!
+//! @group(0) @binding(2) var<uniform> params: Params;
+//!
+//! // Returns the gene at `index` in the vector of the given organism
+//! fn gene(organism: u32, index: u32) -> f32 { ... }
+//! ```
+//!
+//! Genes and fitness values are passed to and from the GPU as 32-bit floats,
+//! so they are only as precise as an `f32`.
+//!
+//! [`GpuEvaluator`]: struct.GpuEvaluator.html
+//! [`BatchEvaluator`]: ../evaluation/trait.BatchEvaluator.html
+//! [wgpu]: https://wgpu.rs
+use crate::{benchmarks::Function, evaluation::BatchEvaluator, Organism};
+use std::{borrow::Cow, error::Error, fmt, sync::mpsc};
+use wgpu::util::DeviceExt;
+
+/// The number of organisms evaluated by each workgroup, as declared in
+/// [`ENTRY_POINT`].
+const WORKGROUP_SIZE: u32 = 64;
+
+/// The largest number of workgroups that can be dispatched along one
+/// dimension.
+const MAX_WORKGROUPS: u32 = 65535;
+
+/// The declarations placed before every kernel.
+const PRELUDE: &str = "
+struct Params {
+    count: u32,
+    dimensions: u32,
+}
+
+@group(0) @binding(0) var<storage, read> genes: array<f32>;
+@group(0) @binding(1) var<storage, read_write> fitnesses: array<f32>;
+@group(0) @binding(2) var<uniform> params: Params;
+
+fn gene(organism: u32, index: u32) -> f32 {
+    return genes[organism * params.dimensions + index];
+}
+";
+
+/// The entry point placed after every kernel, which calls its `fitness`
+/// function once for each organism.
+const ENTRY_POINT: &str = "
+@compute @workgroup_size(64)
+fn main(
+    @builtin(global_invocation_id) id: vec3<u32>,
+    @builtin(num_workgroups) workgroups: vec3<u32>,
+) {
+    let organism = id.y * workgroups.x * 64u + id.x;
+    if organism < params.count {
+        fitnesses[organism] = fitness(organism);
+    }
+}
+";
+
+/// An error in setting up a [`GpuEvaluator`].
+///
+/// [`GpuEvaluator`]: struct.GpuEvaluator.html
+#[derive(Debug)]
+pub enum GpuError {
+    /// No suitable GPU adapter was found.
+    NoAdapter,
+    /// The adapter could not provide a device.
+    Device(wgpu::RequestDeviceError),
+    /// The kernel failed to compile, with the given message.
+    Kernel(String),
+}
+
+impl fmt::Display for GpuError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GpuError::NoAdapter => write!(f, "no suitable GPU adapter was found"),
+            GpuError::Device(error) => write!(f, "failed to request a GPU device: {}", error),
+            GpuError::Kernel(message) => write!(f, "failed to compile the kernel: {}", message),
+        }
+    }
+}
+
+impl Error for GpuError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            GpuError::Device(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+/// A batch evaluator that computes the fitness of real-valued organisms on
+/// the GPU.
+///
+/// The evaluator is given a function that returns an organism's genes, all
+/// of which must have the same number of dimensions. The built-in kernels of
+/// [`benchmark`] evaluate the standard test functions.
+///
+/// [`benchmark`]: #method.benchmark
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use ecosystem::{
+///     benchmarks::{Function, Solution},
+///     gpu::GpuEvaluator,
+///     selection::Tournament,
+///     Ecosystem, Objective, Organism,
+/// };
+/// use rand::SeedableRng;
+/// use rand_pcg::Pcg64;
+///
+/// let evaluator = GpuEvaluator::benchmark(Function::Rastrigin, 30, Solution::point)?;
+/// let mut rng = Pcg64::seed_from_u64(0);
+/// let mut ecosystem = Ecosystem::builder()
+///     .organisms((0..100_000).map(|_| Solution::random(Function::Rastrigin, 30, &mut rng)))
+///     .objective(Objective::Minimize)
+///     .selection(Tournament { size: 3 })
+///     .batch_evaluator(evaluator)
+///     .build()
+///     .unwrap();
+/// for _ in 0..100 {
+///     ecosystem.breed_next_generation()?;
+/// }
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub struct GpuEvaluator<O> {
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+    layout: wgpu::BindGroupLayout,
+    pipeline: wgpu::ComputePipeline,
+    dimensions: usize,
+    genes: fn(&O) -> &[f64],
+}
+
+impl<O> GpuEvaluator<O> {
+    /// Creates an evaluator that runs the given kernel on the first
+    /// high-performance adapter available, over organisms whose genes have
+    /// the given number of dimensions.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use ecosystem::{benchmarks::Solution, gpu::GpuEvaluator};
+    ///
+    /// // The sum of the absolute values of the genes
+    /// let kernel = "
+    ///     fn fitness(organism: u32) -> f32 {
+    ///         var sum = 0.0;
+    ///         for (var i = 0u; i < params.dimensions; i++) {
+    ///             sum += abs(gene(organism, i));
+    ///         }
+    ///         return sum;
+    ///     }
+    /// ";
+    /// let evaluator = GpuEvaluator::new(kernel, 10, Solution::point)?;
+    /// # Ok::<(), ecosystem::gpu::GpuError>(())
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of dimensions is zero.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if no adapter or device can be found, or if the
+    /// kernel fails to compile.
+    pub fn new(kernel: &str, dimensions: usize, genes: fn(&O) -> &[f64]) -> Result<Self, GpuError> {
+        assert!(dimensions > 0, "organisms must have at least one gene");
+        let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor::default());
+        let adapter = pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions {
+            power_preference: wgpu::PowerPreference::HighPerformance,
+            ..Default::default()
+        }))
+        .ok_or(GpuError::NoAdapter)?;
+        // The adapter's own limits, so that large populations fit in a
+        // single storage buffer
+        let (device, queue) = pollster::block_on(adapter.request_device(
+            &wgpu::DeviceDescriptor {
+                label: Some("ecosystem"),
+                required_limits: adapter.limits(),
+                ..Default::default()
+            },
+            None,
+        ))
+        .map_err(GpuError::Device)?;
+
+        device.push_error_scope(wgpu::ErrorFilter::Validation);
+        let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("fitness kernel"),
+            source: wgpu::ShaderSource::Wgsl(Cow::Owned(format!(
+                "{}\n{}\n{}",
+                PRELUDE, kernel, ENTRY_POINT
+            ))),
+        });
+        let storage = |binding, read_only| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::COMPUTE,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Storage { read_only },
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        };
+        let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("fitness bindings"),
+            entries: &[
+                storage(0, true),
+                storage(1, false),
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+            ],
+        });
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("fitness pipeline layout"),
+            bind_group_layouts: &[&layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("fitness pipeline"),
+            layout: Some(&pipeline_layout),
+            module: &module,
+            entry_point: Some("main"),
+            compilation_options: Default::default(),
+            cache: None,
+        });
+        if let Some(error) = pollster::block_on(device.pop_error_scope()) {
+            return Err(GpuError::Kernel(error.to_string()));
+        }
+
+        Ok(Self {
+            device,
+            queue,
+            layout,
+            pipeline,
+            dimensions,
+            genes,
+        })
+    }
+
+    /// Creates an evaluator that runs a built-in kernel for the given test
+    /// function.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of dimensions is zero.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if no adapter or device can be found.
+    pub fn benchmark(
+        function: Function,
+        dimensions: usize,
+        genes: fn(&O) -> &[f64],
+    ) -> Result<Self, GpuError> {
+        Self::new(benchmark_kernel(function), dimensions, genes)
+    }
+
+    /// Returns the number of dimensions of the organisms' genes.
+    pub fn dimensions(&self) -> usize {
+        self.dimensions
+    }
+}
+
+impl<O> fmt::Debug for GpuEvaluator<O> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("GpuEvaluator")
+            .field("dimensions", &self.dimensions)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<O: Organism<Fitness = f64>> BatchEvaluator<O> for GpuEvaluator<O> {
+    /// Evaluates the organisms on the GPU.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any organism's genes have the wrong number of dimensions,
+    /// or if the results cannot be read back from the GPU.
+    fn evaluate_all(&self, organisms: &[&O]) -> Vec<f64> {
+        if organisms.is_empty() {
+            return Vec::new();
+        }
+
+        let mut genes = Vec::with_capacity(organisms.len() * self.dimensions);
+        for organism in organisms {
+            let organism_genes = (self.genes)(organism);
+            assert_eq!(
+                organism_genes.len(),
+                self.dimensions,
+                "every organism must have {} genes",
+                self.dimensions
+            );
+            genes.extend(organism_genes.iter().map(|&gene| gene as f32));
+        }
+        let count = organisms.len() as u32;
+        let params = [count, self.dimensions as u32];
+        let output_size = u64::from(count) * std::mem::size_of::<f32>() as u64;
+
+        let device = &self.device;
+        let gene_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("genes"),
+            contents: bytemuck::cast_slice(&genes),
+            usage: wgpu::BufferUsages::STORAGE,
+        });
+        let output_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("fitnesses"),
+            size: output_size,
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        });
+        let params_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("params"),
+            contents: bytemuck::cast_slice(&params),
+            usage: wgpu::BufferUsages::UNIFORM,
+        });
+        let staging_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("staging"),
+            size: output_size,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("fitness bindings"),
+            layout: &self.layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: gene_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: output_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: params_buffer.as_entire_binding(),
+                },
+            ],
+        });
+
+        // Workgroups are laid out in rows, as there is a limit on how many
+        // can be dispatched along one dimension
+        let workgroups = count.div_ceil(WORKGROUP_SIZE);
+        let columns = workgroups.min(MAX_WORKGROUPS);
+        let rows = workgroups.div_ceil(columns);
+        let mut encoder = device.create_command_encoder(&Default::default());
+        {
+            let mut pass = encoder.begin_compute_pass(&Default::default());
+            pass.set_pipeline(&self.pipeline);
+            pass.set_bind_group(0, &bind_group, &[]);
+            pass.dispatch_workgroups(columns, rows, 1);
+        }
+        encoder.copy_buffer_to_buffer(&output_buffer, 0, &staging_buffer, 0, output_size);
+        self.queue.submit(Some(encoder.finish()));
+
+        let slice = staging_buffer.slice(..);
+        let (sender, receiver) = mpsc::channel();
+        slice.map_async(wgpu::MapMode::Read, move |result| {
+            let _ = sender.send(result);
+        });
+        device.poll(wgpu::Maintain::Wait);
+        receiver
+            .recv()
+            .expect("the GPU device was lost")
+            .expect("failed to read fitness values from the GPU");
+        let fitnesses = bytemuck::cast_slice::<u8, f32>(&slice.get_mapped_range())
+            .iter()
+            .map(|&fitness| f64::from(fitness))
+            .collect();
+        staging_buffer.unmap();
+        fitnesses
+    }
+}
+
+/// Returns the kernel that evaluates the given test function.
+fn benchmark_kernel(function: Function) -> &'static str {
+    match function {
+        Function::Sphere => {
+            "
+            fn fitness(organism: u32) -> f32 {
+                var sum = 0.0;
+                for (var i = 0u; i < params.dimensions; i++) {
+                    let x = gene(organism, i);
+                    sum += x * x;
+                }
+                return sum;
+            }
+            "
+        }
+        Function::Rastrigin => {
+            "
+            fn fitness(organism: u32) -> f32 {
+                var sum = 10.0 * f32(params.dimensions);
+                for (var i = 0u; i < params.dimensions; i++) {
+                    let x = gene(organism, i);
+                    sum += x * x - 10.0 * cos(6.283185307 * x);
+                }
+                return sum;
+            }
+            "
+        }
+        Function::Rosenbrock => {
+            "
+            fn fitness(organism: u32) -> f32 {
+                var sum = 0.0;
+                for (var i = 1u; i < params.dimensions; i++) {
+                    let x = gene(organism, i - 1u);
+                    let y = gene(organism, i);
+                    sum += 100.0 * (y - x * x) * (y - x * x) + (1.0 - x) * (1.0 - x);
+                }
+                return sum;
+            }
+            "
+        }
+        Function::Ackley => {
+            "
+            fn fitness(organism: u32) -> f32 {
+                var squares = 0.0;
+                var cosines = 0.0;
+                for (var i = 0u; i < params.dimensions; i++) {
+                    let x = gene(organism, i);
+                    squares += x * x;
+                    cosines += cos(6.283185307 * x);
+                }
+                let n = f32(params.dimensions);
+                return -20.0 * exp(-0.2 * sqrt(squares / n)) - exp(cosines / n) + 20.0 + 2.718281828;
+            }
+            "
+        }
+        Function::Schwefel => {
+            "
+            fn fitness(organism: u32) -> f32 {
+                var sum = 418.9828873 * f32(params.dimensions);
+                for (var i = 0u; i < params.dimensions; i++) {
+                    let x = gene(organism, i);
+                    sum -= x * sin(sqrt(abs(x)));
+                }
+                return sum;
+            }
+            "
+        }
+    }
+}
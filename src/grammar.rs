@@ -0,0 +1,406 @@
+//! Grammatical evolution, in which the organisms are lists of integer
+//! codons that are mapped into programs (or any other sentences) through a
+//! grammar.
+//!
+//! A [`Grammar`] is written in Backus-Naur form, with one or more rules of
+//! the form `<name> ::= first | second | ...`, where each choice is a
+//! sequence of symbols separated by whitespace. Symbols in angle brackets are
+//! nonterminals, which must have a rule of their own, and all other symbols
+//! are terminals; terminals containing whitespace, `|` or `<` can be written
+//! in double quotes. The first rule gives the start symbol.
+//!
+//! Mapping a genome starts from the start symbol and repeatedly expands the
+//! leftmost nonterminal, using the next codon (modulo the number of choices)
+//! to pick among the choices of its rule. Nonterminals with a single choice
+//! use no codon. If the codons run out before the sentence is complete, the
+//! mapping wraps around to the first codon again, up to a given number of
+//! times; after that, the genome is invalid, and should be given the worst
+//! possible fitness.
+//!
+//! The codons can be held in an [`IntVector`], whose crossover and mutation
+//! operators then evolve them.
+//!
+//! ```rust
+//! use ecosystem::{
+//!     genome::IntVector, grammar::Grammar, selection::Tournament, Ecosystem, Organism,
+//! };
+//! use rand::{Rng, SeedableRng};
+//! use rand_pcg::Pcg64;
+//! use std::sync::OnceLock;
+//!
+//! fn grammar() -> &'static Grammar {
+//!     static GRAMMAR: OnceLock<Grammar> = OnceLock::new();
+//!     GRAMMAR.get_or_init(|| {
+//!         "<word> ::= <letter> | <letter> <word>
+//!          <letter> ::= a | b | c | d"
+//!             .parse()
+//!             .unwrap()
+//!     })
+//! }
+//!
+//! // Spell out the word 'dabcab'
+//! struct Speller(IntVector);
+//!
+//! impl Organism for Speller {
+//!     type Fitness = i32;
+//!
+//!     fn fitness(&self) -> i32 {
+//!         match grammar().map(self.0.genes(), 2) {
+//!             Ok(letters) => {
+//!                 let word = letters.concat();
+//!                 let matching = word.chars().zip("dabcab".chars()).filter(|(a, b)| a == b);
+//!                 2 * matching.count() as i32 - (word.len() as i32 - 6).abs()
+//!             }
+//!             // Invalid genomes get the worst possible fitness
+//!             Err(_) => i32::MIN,
+//!         }
+//!     }
+//!
+//!     fn breed(&self, other: &Self, rng: &mut impl Rng) -> Self {
+//!         self.breed_pair(other, rng).0
+//!     }
+//!
+//!     fn breed_pair(&self, other: &Self, rng: &mut impl Rng) -> (Self, Self) {
+//!         let (first, second) = self.0.one_point_crossover(&other.0, rng);
+//!         (Speller(first), Speller(second))
+//!     }
+//!
+//!     fn mutate(&mut self, rate: f64, rng: &mut impl Rng) {
+//!         self.0.uniform_mutation(rate, rng);
+//!     }
+//! }
+//!
+//! let bounds = vec![(0, 255); 20];
+//! let mut rng = Pcg64::seed_from_u64(0);
+//! let mut ecosystem = Ecosystem::builder()
+//!     .organisms((0..100).map(|_| Speller(IntVector::random(&bounds, &mut rng))))
+//!     .mutation_rate(0.05)
+//!     .selection(Tournament { size: 3 })
+//!     .elitism(1)
+//!     .seed(0)
+//!     .build()
+//!     .unwrap();
+//! for _ in 0..50 {
+//!     ecosystem.breed_next_generation()?;
+//! }
+//!
+//! let best = ecosystem.fittest().unwrap();
+//! assert_eq!(grammar().map(best.0.genes(), 2).unwrap().concat(), "dabcab");
+//! # Ok::<(), ecosystem::EcosystemError>(())
+//! ```
+//!
+//! [`Grammar`]: struct.Grammar.html
+//! [`IntVector`]: ../genome/struct.IntVector.html
+use std::{error::Error, fmt, str::FromStr};
+
+/// A symbol in a choice of a grammar rule.
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Symbol {
+    Terminal(String),
+    /// The index of the nonterminal's rule.
+    Nonterminal(usize),
+}
+
+/// A context-free grammar in Backus-Naur form, which maps codons into
+/// sentences.
+///
+/// Grammars are parsed from text with [`str::parse`], as described in the
+/// [module documentation]. Every nonterminal must be able to expand into a
+/// finite sentence, so that mapping always uses up codons as it goes.
+///
+/// [`str::parse`]: https://doc.rust-lang.org/std/primitive.str.html#method.parse
+/// [module documentation]: index.html
+///
+/// # Examples
+///
+/// ```rust
+/// use ecosystem::grammar::{Grammar, MappingError};
+///
+/// let grammar: Grammar = "
+///     <expr> ::= <expr> <op> <expr> | x | 1
+///     <op> ::= + | *
+/// "
+/// .parse()
+/// .unwrap();
+///
+/// // 0 picks `<expr> <op> <expr>`, then 1 picks `x`, 1 picks `*` and 2
+/// // picks `1`
+/// assert_eq!(grammar.map(&[0, 1, 1, 2], 0).unwrap().concat(), "x*1");
+/// // Without wrapping, two codons are too few
+/// assert_eq!(grammar.map(&[0, 1], 0), Err(MappingError::OutOfCodons));
+/// // Wrapping reuses them, so 0 picks `+` and 1 picks `x`
+/// assert_eq!(grammar.map(&[0, 1], 1).unwrap().concat(), "x+x");
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Grammar {
+    nonterminals: Vec<String>,
+    rules: Vec<Vec<Vec<Symbol>>>,
+}
+
+impl Grammar {
+    /// Returns the names of the nonterminals, without their angle brackets,
+    /// in the order their rules appear. The first is the start symbol.
+    pub fn nonterminals(&self) -> impl Iterator<Item = &str> {
+        self.nonterminals.iter().map(String::as_str)
+    }
+
+    /// Maps the given codons into a sentence, returning its terminals in
+    /// order.
+    ///
+    /// The codons are reused from the start up to `max_wraps` times if they
+    /// run out. Negative codons count from the end of the list of choices.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the codons run out before the sentence is
+    /// complete, in which case the genome is invalid.
+    pub fn map(&self, codons: &[i64], max_wraps: usize) -> Result<Vec<&str>, MappingError> {
+        let available = codons.len().saturating_mul(max_wraps.saturating_add(1));
+        let mut used = 0;
+        let mut sentence = Vec::new();
+        let mut pending = vec![&Symbol::Nonterminal(0)];
+        while let Some(symbol) = pending.pop() {
+            let rule = match symbol {
+                Symbol::Terminal(terminal) => {
+                    sentence.push(terminal.as_str());
+                    continue;
+                }
+                Symbol::Nonterminal(rule) => &self.rules[*rule],
+            };
+            let choice = if rule.len() == 1 {
+                0
+            } else if used < available {
+                let codon = codons[used % codons.len()];
+                used += 1;
+                codon.rem_euclid(rule.len() as i64) as usize
+            } else {
+                return Err(MappingError::OutOfCodons);
+            };
+            pending.extend(rule[choice].iter().rev());
+        }
+        Ok(sentence)
+    }
+}
+
+impl FromStr for Grammar {
+    type Err = GrammarError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let tokens = tokenize(s)?;
+
+        // The nonterminals are named by the tokens before each `::=`
+        let mut nonterminals = Vec::new();
+        for (index, token) in tokens.iter().enumerate() {
+            if token.text == "::=" && !token.quoted {
+                match index.checked_sub(1).map(|index| &tokens[index]) {
+                    Some(name) if name.is_nonterminal() => {
+                        let name = name.nonterminal_name().to_string();
+                        if nonterminals.contains(&name) {
+                            return Err(GrammarError::DuplicateRule(name));
+                        }
+                        nonterminals.push(name);
+                    }
+                    _ => return Err(GrammarError::Syntax("a rule has no name".to_string())),
+                }
+            }
+        }
+        if nonterminals.is_empty() {
+            return Err(GrammarError::Empty);
+        }
+        if tokens.len() < 2 || tokens[1].text != "::=" {
+            return Err(GrammarError::Syntax(format!(
+                "expected a rule, found `{}`",
+                tokens[0].text
+            )));
+        }
+
+        let mut rules: Vec<Vec<Vec<Symbol>>> = Vec::new();
+        let mut index = 0;
+        while index < tokens.len() {
+            let starts_rule = tokens.get(index + 1).is_some_and(|next| {
+                next.text == "::=" && !next.quoted && tokens[index].is_nonterminal()
+            });
+            if starts_rule {
+                rules.push(vec![Vec::new()]);
+                index += 2;
+                continue;
+            }
+
+            let token = &tokens[index];
+            let choices = rules.last_mut().unwrap();
+            if token.text == "|" && !token.quoted {
+                choices.push(Vec::new());
+            } else if token.is_nonterminal() {
+                let name = token.nonterminal_name();
+                let rule = nonterminals
+                    .iter()
+                    .position(|nonterminal| nonterminal == name)
+                    .ok_or_else(|| GrammarError::UndefinedNonterminal(name.to_string()))?;
+                choices.last_mut().unwrap().push(Symbol::Nonterminal(rule));
+            } else {
+                let terminal = token.text.clone();
+                choices.last_mut().unwrap().push(Symbol::Terminal(terminal));
+            }
+            index += 1;
+        }
+
+        let grammar = Grammar {
+            nonterminals,
+            rules,
+        };
+        grammar.check_terminating()?;
+        Ok(grammar)
+    }
+}
+
+impl Grammar {
+    /// Checks that every nonterminal can expand into a finite sentence.
+    fn check_terminating(&self) -> Result<(), GrammarError> {
+        let mut terminating = vec![false; self.rules.len()];
+        loop {
+            let mut changed = false;
+            for (rule, choices) in self.rules.iter().enumerate() {
+                if terminating[rule] {
+                    continue;
+                }
+                let terminates = choices.iter().any(|choice| {
+                    choice.iter().all(|symbol| match symbol {
+                        Symbol::Terminal(_) => true,
+                        Symbol::Nonterminal(rule) => terminating[*rule],
+                    })
+                });
+                if terminates {
+                    terminating[rule] = true;
+                    changed = true;
+                }
+            }
+            if !changed {
+                break;
+            }
+        }
+        match terminating.iter().position(|&terminates| !terminates) {
+            Some(rule) => Err(GrammarError::NonTerminating(
+                self.nonterminals[rule].clone(),
+            )),
+            None => Ok(()),
+        }
+    }
+}
+
+/// A token of a grammar's text.
+struct Token {
+    text: String,
+    quoted: bool,
+}
+
+impl Token {
+    fn is_nonterminal(&self) -> bool {
+        !self.quoted
+            && self.text.len() > 2
+            && self.text.starts_with('<')
+            && self.text.ends_with('>')
+    }
+
+    fn nonterminal_name(&self) -> &str {
+        &self.text[1..self.text.len() - 1]
+    }
+}
+
+/// Splits a grammar's text into tokens at whitespace, keeping quoted
+/// terminals whole.
+fn tokenize(s: &str) -> Result<Vec<Token>, GrammarError> {
+    let mut tokens = Vec::new();
+    let mut chars = s.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c == '"' {
+            chars.next();
+            let mut text = String::new();
+            loop {
+                match chars.next() {
+                    Some('"') => break,
+                    Some(c) => text.push(c),
+                    None => {
+                        return Err(GrammarError::Syntax(
+                            "a quoted terminal is not closed".to_string(),
+                        ))
+                    }
+                }
+            }
+            tokens.push(Token { text, quoted: true });
+        } else {
+            let mut text = String::new();
+            while let Some(&c) = chars.peek() {
+                if c.is_whitespace() {
+                    break;
+                }
+                text.push(c);
+                chars.next();
+            }
+            tokens.push(Token {
+                text,
+                quoted: false,
+            });
+        }
+    }
+    Ok(tokens)
+}
+
+/// An error arising while parsing a grammar.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum GrammarError {
+    /// The grammar had no rules.
+    Empty,
+    /// The grammar's text was malformed.
+    Syntax(String),
+    /// A nonterminal had more than one rule.
+    DuplicateRule(String),
+    /// A nonterminal was used without a rule of its own.
+    UndefinedNonterminal(String),
+    /// A nonterminal could never expand into a finite sentence.
+    NonTerminating(String),
+}
+
+impl fmt::Display for GrammarError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GrammarError::Empty => write!(f, "the grammar has no rules"),
+            GrammarError::Syntax(reason) => write!(f, "malformed grammar: {}", reason),
+            GrammarError::DuplicateRule(name) => {
+                write!(f, "the nonterminal <{}> has more than one rule", name)
+            }
+            GrammarError::UndefinedNonterminal(name) => {
+                write!(f, "the nonterminal <{}> has no rule", name)
+            }
+            GrammarError::NonTerminating(name) => write!(
+                f,
+                "the nonterminal <{}> can never expand into a finite sentence",
+                name
+            ),
+        }
+    }
+}
+
+impl Error for GrammarError {}
+
+/// An error arising while mapping codons through a grammar, which makes the
+/// genome invalid.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MappingError {
+    /// The codons ran out, even after wrapping, before the sentence was
+    /// complete.
+    OutOfCodons,
+}
+
+impl fmt::Display for MappingError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MappingError::OutOfCodons => {
+                write!(f, "the codons ran out before the sentence was complete")
+            }
+        }
+    }
+}
+
+impl Error for MappingError {}
@@ -0,0 +1,125 @@
+use crate::{observer::Observer, Ecosystem, Individual, Organism};
+use alloc::vec::Vec;
+use core::{cmp::Ordering, ops::ControlFlow};
+
+/// A record of the fittest distinct organisms ever seen, kept as copies that
+/// are unaffected by further evolution.
+///
+/// Unlike the ecosystem's [archive], which only takes organisms as they are
+/// replaced, the hall of fame copies the fittest organisms of the population
+/// whenever it is updated, so it includes organisms that are still alive. It
+/// can also be shared by several ecosystems, such as the islands of an
+/// [`Archipelago`]. As with the archive, two organisms are considered
+/// distinct if their fitness values differ.
+///
+/// The hall of fame is updated by calling [`update`], or automatically
+/// before and after each generation of a run when it is registered as an
+/// [observer]. Wrapping it in an `Arc<Mutex<_>>` allows it to be inspected
+/// while it is registered.
+///
+/// [archive]: struct.EcosystemBuilder.html#method.archive
+/// [`Archipelago`]: struct.Archipelago.html
+/// [`update`]: #method.update
+/// [observer]: observer/index.html
+///
+/// # Examples
+///
+/// ```rust
+/// use ecosystem::{termination::MaxGenerations, Ecosystem, HallOfFame, Organism};
+/// use std::sync::{Arc, Mutex};
+/// # use rand::Rng;
+/// # #[derive(Clone)]
+/// # struct Guess(f64);
+/// # impl Organism for Guess {
+/// #     type Fitness = f64;
+/// #     fn fitness(&self) -> f64 { 1.0 / (42.0 - self.0).abs() }
+/// #     fn breed(&self, other: &Self, rng: &mut impl Rng) -> Self { Guess((self.0 + other.0) / 2.0) }
+/// #     fn mutate(&mut self, rate: f64, rng: &mut impl Rng) {
+/// #         self.0 += rng.gen_range(-rate, rate);
+/// #     }
+/// # }
+///
+/// let hall_of_fame = Arc::new(Mutex::new(HallOfFame::new(5)));
+/// let mut ecosystem = Ecosystem::builder()
+///     .organisms((0..20).map(|i| Guess(f64::from(i) * 5.0 + 0.5)))
+///     .observer(Arc::clone(&hall_of_fame))
+///     .build()
+///     .unwrap();
+/// ecosystem.run(MaxGenerations(20))?;
+///
+/// let hall_of_fame = hall_of_fame.lock().unwrap();
+/// assert_eq!(hall_of_fame.members().len(), 5);
+/// // Nothing in the population beats the best organism ever seen
+/// let best = hall_of_fame.members()[0].fitness();
+/// assert!(ecosystem.individuals().iter().all(|individual| individual.fitness() <= best));
+/// # Ok::<(), ecosystem::EcosystemError>(())
+/// ```
+pub struct HallOfFame<O: Organism> {
+    members: Vec<Individual<O>>,
+    capacity: usize,
+}
+
+impl<O: Organism + Clone + Send + Sync> HallOfFame<O> {
+    /// Creates an empty hall of fame holding up to `capacity` organisms.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            members: Vec::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    /// Returns the members, ordered from fittest to least fit.
+    pub fn members(&self) -> &[Individual<O>] {
+        &self.members
+    }
+
+    /// Returns the fittest organism ever seen, or `None` if the hall of fame
+    /// is empty.
+    pub fn fittest(&self) -> Option<&O> {
+        self.members.first().map(Individual::organism)
+    }
+
+    /// Considers the current population of the given ecosystem for
+    /// membership, evaluating any organisms that have not yet been
+    /// evaluated. Organisms are ranked according to the ecosystem's
+    /// objective.
+    pub fn update(&mut self, ecosystem: &Ecosystem<O>) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        let objective = ecosystem.objective();
+        let worst = match self.members.last() {
+            Some(worst) if self.members.len() == self.capacity => Some(worst.fitness()),
+            _ => None,
+        };
+        let candidates = ecosystem
+            .ranked_individuals()
+            .into_iter()
+            .take(self.capacity)
+            .take_while(|candidate| {
+                worst.is_none_or(|worst| {
+                    objective.compare(candidate.fitness(), worst) == Ordering::Greater
+                })
+            })
+            .cloned();
+
+        self.members.extend(candidates);
+        self.members
+            .sort_by(|a, b| objective.compare(b.fitness(), a.fitness()));
+        self.members.dedup_by(|a, b| a.fitness() == b.fitness());
+        self.members.truncate(self.capacity);
+    }
+}
+
+impl<O: Organism + Clone + Send + Sync> Observer<O> for HallOfFame<O> {
+    fn on_generation_start(&mut self, ecosystem: &Ecosystem<O>) -> ControlFlow<()> {
+        self.update(ecosystem);
+        ControlFlow::Continue(())
+    }
+
+    fn on_generation_end(&mut self, ecosystem: &Ecosystem<O>) -> ControlFlow<()> {
+        self.update(ecosystem);
+        ControlFlow::Continue(())
+    }
+}
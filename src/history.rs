@@ -0,0 +1,205 @@
+//! Recording how a run progresses, generation by generation.
+//!
+//! Recording is enabled with [`EcosystemBuilder::record_history`], after which
+//! the ecosystem's [`history`] can be inspected at any point, exported as CSV
+//! for plotting elsewhere, or, with the `plot` feature, plotted as an image
+//! with [`History::plot`]:
+//!
+//! ```rust
+//! use ecosystem::{termination::MaxGenerations, Ecosystem, Organism};
+//! # use rand::Rng;
+//! # struct Guess(f64);
+//! # impl Organism for Guess {
+//! #     type Fitness = f64;
+//! #     fn fitness(&self) -> f64 { 1.0 / (42.0 - self.0).abs() }
+//! #     fn breed(&self, other: &Self, rng: &mut impl Rng) -> Self { Guess((self.0 + other.0) / 2.0) }
+//! #     fn mutate(&mut self, rate: f64, rng: &mut impl Rng) {
+//! #         self.0 += rng.gen_range(-rate, rate);
+//! #     }
+//! # }
+//!
+//! let mut ecosystem = Ecosystem::builder()
+//!     .organisms((0..20).map(|i| Guess(f64::from(i) * 5.0 + 0.5)))
+//!     .record_history()
+//!     .build()
+//!     .unwrap();
+//! ecosystem.run(MaxGenerations(10))?;
+//!
+//! let history = ecosystem.history().unwrap();
+//! // The initial generation is recorded, along with every one bred since
+//! assert_eq!(history.records().len(), 11);
+//!
+//! let mut csv = Vec::new();
+//! history.write_csv(&mut csv).unwrap();
+//! let csv = String::from_utf8(csv).unwrap();
+//! assert!(csv.starts_with("generation,best,mean,fitness_std_dev,elapsed_seconds\n0,"));
+//! # Ok::<(), ecosystem::EcosystemError>(())
+//! ```
+//!
+//! [`EcosystemBuilder::record_history`]: ../struct.EcosystemBuilder.html#method.record_history
+//! [`history`]: ../struct.Ecosystem.html#method.history
+//! [`History::plot`]: struct.History.html#method.plot
+use crate::{Objective, Statistics};
+use alloc::vec::Vec;
+use core::time::Duration;
+#[cfg(feature = "std")]
+use std::io::{self, Write};
+#[cfg(all(
+    feature = "std",
+    not(all(target_arch = "wasm32", target_os = "unknown"))
+))]
+use std::time::Instant;
+
+/// A record of a single generation.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Record {
+    /// The generation number.
+    pub generation: u32,
+    /// The best fitness value in the generation, with respect to the
+    /// ecosystem's objective.
+    pub best: f64,
+    /// The mean fitness value of the generation.
+    pub mean: f64,
+    /// The standard deviation of the generation's fitness values. A
+    /// population whose fitness has stopped varying has usually converged,
+    /// though organisms of equal fitness may still differ: measuring their
+    /// genetic diversity takes a [`Distance`] between them.
+    ///
+    /// [`Distance`]: ../diversity/trait.Distance.html
+    pub fitness_std_dev: f64,
+    /// The wall-clock time between the ecosystem being built and the
+    /// generation being recorded. Without the `std` feature, or on
+    /// `wasm32-unknown-unknown`, there is no clock to measure it by, and it
+    /// is always zero.
+    pub elapsed: Duration,
+}
+
+/// The records of every generation since recording began.
+#[derive(Clone, Debug)]
+pub struct History {
+    records: Vec<Record>,
+    clock: Clock,
+}
+
+impl History {
+    pub(crate) fn new() -> Self {
+        Self {
+            records: Vec::new(),
+            clock: Clock::starting_at(Duration::ZERO),
+        }
+    }
+
+    /// Returns the records, in the order in which the generations were bred.
+    pub fn records(&self) -> &[Record] {
+        &self.records
+    }
+
+    /// Writes the records as CSV, with a header row, to the given writer.
+    ///
+    /// The elapsed time is written as a (fractional) number of seconds.
+    ///
+    /// # Errors
+    ///
+    /// Any error arising from writing is returned.
+    #[cfg(feature = "std")]
+    pub fn write_csv<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writeln!(
+            writer,
+            "generation,best,mean,fitness_std_dev,elapsed_seconds"
+        )?;
+        for record in &self.records {
+            writeln!(
+                writer,
+                "{},{},{},{},{}",
+                record.generation,
+                record.best,
+                record.mean,
+                record.fitness_std_dev,
+                record.elapsed.as_secs_f64()
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Records the given generation's statistics.
+    pub(crate) fn record(&mut self, generation: u32, statistics: Statistics, objective: Objective) {
+        self.records.push(Record {
+            generation,
+            best: match objective {
+                Objective::Maximize => statistics.max,
+                Objective::Minimize => statistics.min,
+            },
+            mean: statistics.mean,
+            fitness_std_dev: statistics.std_dev,
+            elapsed: self.clock.elapsed(),
+        });
+    }
+}
+
+/// The clock by which the time elapsed since recording began is measured.
+/// Reading the time is impossible without the standard library, and panics
+/// on `wasm32-unknown-unknown`, so no time ever elapses there.
+#[derive(Clone, Debug)]
+pub(crate) struct Clock {
+    #[cfg(all(
+        feature = "std",
+        not(all(target_arch = "wasm32", target_os = "unknown"))
+    ))]
+    start: Instant,
+}
+
+#[cfg(all(
+    feature = "std",
+    not(all(target_arch = "wasm32", target_os = "unknown"))
+))]
+impl Clock {
+    /// Starts a clock that reads the given time already.
+    pub(crate) fn starting_at(elapsed: Duration) -> Self {
+        Self {
+            start: Instant::now()
+                .checked_sub(elapsed)
+                .unwrap_or_else(Instant::now),
+        }
+    }
+
+    pub(crate) fn elapsed(&self) -> Duration {
+        self.start.elapsed()
+    }
+}
+
+#[cfg(not(all(
+    feature = "std",
+    not(all(target_arch = "wasm32", target_os = "unknown"))
+)))]
+impl Clock {
+    pub(crate) fn starting_at(_elapsed: Duration) -> Self {
+        Self {}
+    }
+
+    pub(crate) fn elapsed(&self) -> Duration {
+        Duration::ZERO
+    }
+}
+
+/// Only the records are serialised. When a history is deserialised, its
+/// elapsed times carry on from that of its last record.
+#[cfg(feature = "serde")]
+impl serde::Serialize for History {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.records.serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for History {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let records = Vec::<Record>::deserialize(deserializer)?;
+        let clock = Clock::starting_at(
+            records
+                .last()
+                .map_or(Duration::ZERO, |record| record.elapsed),
+        );
+        Ok(Self { records, clock })
+    }
+}
@@ -0,0 +1,206 @@
+use crate::{pareto::MultiObjective, Organism};
+use alloc::vec::Vec;
+use core::sync::atomic::Ordering;
+#[cfg(not(feature = "std"))]
+pub(crate) use once::OnceLock;
+use portable_atomic::AtomicU64;
+#[cfg(feature = "std")]
+pub(crate) use std::sync::OnceLock;
+
+/// The identifier given to the next individual to be created.
+static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+
+/// An organism paired with its cached fitness value.
+///
+/// The fitness of an individual is evaluated at most once, by the ecosystem
+/// that holds it, and the stored value is reused from then on. This keeps
+/// expensive fitness functions from being run repeatedly during selection
+/// and reporting, and lets the ecosystem count every evaluation and hand
+/// them to its [batch evaluator], if any. Every individual that an ecosystem
+/// hands out, such as to a selection strategy or an observer, has been
+/// evaluated already.
+///
+/// [batch evaluator]: evaluation/index.html
+///
+/// Under [Baldwinian learning], an individual's fitness is that of its
+/// organism after local search, while the organism itself is left as it was
+/// bred; its [`raw_fitness`] is then the fitness of the organism itself.
+///
+/// [Baldwinian learning]: local_search/enum.Learning.html#variant.Baldwinian
+/// [`raw_fitness`]: #method.raw_fitness
+#[derive(Clone)]
+pub struct Individual<O: Organism> {
+    organism: O,
+    id: u64,
+    pub(crate) age: u32,
+    pub(crate) fitness: OnceLock<O::Fitness>,
+    /// The fitness of the organism itself, if it differs from the learned
+    /// fitness above.
+    raw_fitness: Option<OnceLock<O::Fitness>>,
+    pub(crate) objectives: OnceLock<Vec<f64>>,
+}
+
+impl<O: Organism> Individual<O> {
+    /// Wraps the given organism, without evaluating its fitness.
+    pub fn new(organism: O) -> Self {
+        Self {
+            organism,
+            id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+            age: 0,
+            fitness: OnceLock::new(),
+            raw_fitness: None,
+            objectives: OnceLock::new(),
+        }
+    }
+
+    /// Wraps the given organism, evaluating its fitness straight away with
+    /// its own fitness function.
+    ///
+    /// This is useful for trying out strategies on individuals outside of an
+    /// ecosystem; individuals in an ecosystem are evaluated by it.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ecosystem::{Individual, Organism};
+    /// # use rand::Rng;
+    /// # struct Number(u32);
+    /// # impl Organism for Number {
+    /// #     type Fitness = u32;
+    /// #     fn fitness(&self) -> u32 { self.0 }
+    /// #     fn breed(&self, other: &Self, rng: &mut impl Rng) -> Self { Number(self.0) }
+    /// #     fn mutate(&mut self, rate: f64, rng: &mut impl Rng) {}
+    /// # }
+    ///
+    /// assert!(!Individual::new(Number(3)).is_evaluated());
+    ///
+    /// let individual = Individual::evaluated(Number(3));
+    /// assert!(individual.is_evaluated());
+    /// assert_eq!(individual.fitness(), 3);
+    /// ```
+    pub fn evaluated(organism: O) -> Self {
+        let individual = Self::new(organism);
+        individual.evaluate();
+        individual
+    }
+
+    /// Wraps the given organism with the fitness that it learned by local
+    /// search, under Baldwinian learning.
+    pub(crate) fn learned(organism: O, fitness: O::Fitness) -> Self {
+        let individual = Self {
+            raw_fitness: Some(OnceLock::new()),
+            ..Self::new(organism)
+        };
+        let _ = individual.fitness.set(fitness);
+        individual
+    }
+
+    /// Returns the individual's identifier, which is unique among the
+    /// individuals created by this process (though clones of an individual
+    /// share its identifier). Identifiers are not preserved by checkpoints.
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// Returns the individual's age: the number of generations it has
+    /// survived since it was bred (or since the ecosystem was created, for
+    /// the initial population).
+    pub fn age(&self) -> u32 {
+        self.age
+    }
+
+    /// Returns a reference to the underlying organism.
+    pub fn organism(&self) -> &O {
+        &self.organism
+    }
+
+    /// Consumes the individual, returning the underlying organism.
+    pub fn into_organism(self) -> O {
+        self.organism
+    }
+
+    /// Returns the organism's fitness.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the individual has not been evaluated, which is only
+    /// possible for individuals created with [`new`] that are not held by
+    /// an ecosystem.
+    ///
+    /// [`new`]: #method.new
+    pub fn fitness(&self) -> O::Fitness {
+        *self
+            .fitness
+            .get()
+            .expect("the individual's fitness has not been evaluated")
+    }
+
+    /// Returns whether the individual's fitness has been evaluated.
+    pub fn is_evaluated(&self) -> bool {
+        self.fitness.get().is_some()
+    }
+
+    /// Returns the organism's fitness, evaluating it with the organism's own
+    /// fitness function if this has not already been done. Any evaluation
+    /// is left to the caller to count.
+    pub(crate) fn evaluate(&self) -> O::Fitness {
+        *self.fitness.get_or_init(|| self.organism.fitness())
+    }
+
+    /// Returns the fitness of the organism itself, evaluating it if this has
+    /// not already been done. This is the same as [`fitness`], unless the
+    /// individual's fitness was learned under Baldwinian learning, in which
+    /// case the organism's own fitness function is run the first time this
+    /// is called, outside of the ecosystem's evaluation count.
+    ///
+    /// [`fitness`]: #method.fitness
+    pub fn raw_fitness(&self) -> O::Fitness {
+        match &self.raw_fitness {
+            Some(raw_fitness) => *raw_fitness.get_or_init(|| self.organism.fitness()),
+            None => self.fitness(),
+        }
+    }
+}
+
+impl<O: Organism + MultiObjective> Individual<O> {
+    /// Returns the organism's objective values, evaluating them if this has
+    /// not already been done.
+    pub fn objectives(&self) -> &[f64] {
+        self.objectives.get_or_init(|| self.organism.objectives())
+    }
+}
+
+/// A stand-in for the standard library's `OnceLock`, for `no_std` targets.
+#[cfg(not(feature = "std"))]
+mod once {
+    pub(crate) struct OnceLock<T>(spin::Once<T>);
+
+    impl<T> OnceLock<T> {
+        pub(crate) const fn new() -> Self {
+            Self(spin::Once::new())
+        }
+
+        pub(crate) fn get(&self) -> Option<&T> {
+            self.0.get()
+        }
+
+        pub(crate) fn get_or_init(&self, init: impl FnOnce() -> T) -> &T {
+            self.0.call_once(init)
+        }
+
+        pub(crate) fn set(&self, value: T) -> Result<(), T> {
+            let mut value = Some(value);
+            self.0.call_once(|| value.take().unwrap());
+            value.map_or(Ok(()), Err)
+        }
+    }
+
+    impl<T: Clone> Clone for OnceLock<T> {
+        fn clone(&self) -> Self {
+            match self.get() {
+                Some(value) => Self(spin::Once::initialized(value.clone())),
+                None => Self::new(),
+            }
+        }
+    }
+}
@@ -1,124 +1,2548 @@
+#![cfg_attr(not(feature = "std"), no_std)]
 #![warn(missing_docs)]
 //! A small genetic algorithms library.
-use rand::{seq::SliceRandom, Rng};
-use rayon::prelude::*;
+//!
+//! Without the default `std` feature, the crate builds for `no_std` targets
+//! that provide an allocator. The core [`Ecosystem`] machinery remains,
+//! but the ecosystem must be given a random number generator with
+//! [`EcosystemBuilder::seed`] or [`EcosystemBuilder::rng`], and the other
+//! search algorithms, which need the standard library, are left out.
+//!
+//! [`Ecosystem`]: struct.Ecosystem.html
+//! [`EcosystemBuilder::seed`]: struct.EcosystemBuilder.html#method.seed
+//! [`EcosystemBuilder::rng`]: struct.EcosystemBuilder.html#method.rng
+extern crate alloc;
+
+use crate::parallel::*;
+use alloc::{boxed::Box, collections::BTreeMap, vec, vec::Vec};
+use core::{
+    any::TypeId,
+    cmp::Ordering,
+    hash::{Hash, Hasher},
+    ops::ControlFlow,
+    sync::atomic,
+    time::Duration,
+};
+#[cfg(not(feature = "std"))]
+#[allow(unused_imports)]
+use num_traits::Float;
+use portable_atomic::AtomicU64;
+use rand::{seq::SliceRandom, Rng, RngCore, SeedableRng};
+use rand_pcg::Pcg64;
+
+#[cfg(feature = "std")]
+mod alps;
+#[cfg(feature = "std")]
+mod archipelago;
+#[cfg(feature = "std")]
+pub mod benchmarks;
+mod builder;
+#[cfg(feature = "std")]
+pub mod cgp;
+#[cfg(feature = "serde")]
+mod checkpoint;
+#[cfg(feature = "std")]
+mod cmaes;
+#[cfg(feature = "std")]
+mod coevolution;
+#[cfg(feature = "std")]
+pub mod competition;
+#[cfg(feature = "serde")]
+pub mod config;
+mod constraint;
+#[cfg(feature = "std")]
+mod cooperative_coevolution;
+#[cfg(feature = "std")]
+mod differential_evolution;
+#[cfg(feature = "distributed")]
+pub mod distributed;
+pub mod diversity;
+mod error;
+pub mod evaluation;
+mod fitness;
+pub mod genealogy;
+mod generations;
+#[cfg(feature = "std")]
+pub mod genome;
+#[cfg(feature = "std")]
+pub mod gp;
+#[cfg(feature = "gpu")]
+pub mod gpu;
+#[cfg(feature = "std")]
+pub mod grammar;
+mod hall_of_fame;
+pub mod history;
+mod individual;
+pub mod local_search;
+#[cfg(feature = "std")]
+mod map_elites;
+#[cfg(feature = "metrics")]
+pub mod metrics;
+mod model;
+#[cfg(feature = "tui")]
+pub mod monitor;
+pub mod mutation;
+#[cfg(feature = "std")]
+pub mod neat;
+#[cfg(feature = "std")]
+pub mod neuro;
+#[cfg(feature = "std")]
+pub mod novelty;
+mod objective;
+pub mod observer;
+mod parallel;
+pub mod pareto;
+#[cfg(feature = "std")]
+mod particle_swarm;
+#[cfg(feature = "plot")]
+mod plot;
+pub mod population;
+pub mod progress;
+pub mod replacement;
+pub mod selection;
+mod stagnation;
+mod statistics;
+pub mod surrogate;
+#[cfg(feature = "websocket")]
+pub mod telemetry;
+pub mod termination;
+mod trace;
+#[cfg(feature = "std")]
+pub mod tsp;
+
+#[cfg(feature = "std")]
+pub use alps::Alps;
+#[cfg(feature = "std")]
+pub use archipelago::Archipelago;
+pub use builder::{BuildError, EcosystemBuilder, DEFAULT_MUTATION_RATE};
+#[cfg(feature = "serde")]
+pub use checkpoint::CheckpointError;
+#[cfg(feature = "std")]
+pub use cmaes::Cmaes;
+#[cfg(feature = "std")]
+pub use coevolution::Coevolution;
+pub use constraint::{Constrained, ConstraintHandling};
+#[cfg(feature = "std")]
+pub use cooperative_coevolution::CooperativeCoevolution;
+#[cfg(feature = "std")]
+pub use differential_evolution::{DeStrategy, DifferentialEvolution};
+use diversity::Distance;
+#[cfg(feature = "derive")]
+pub use ecosystem_derive::Organism;
+pub use error::{EcosystemError, Setting};
+use evaluation::BatchEvaluator;
+pub use fitness::Fitness;
+use genealogy::Genealogy;
+pub use generations::{GenerationReport, Generations};
+pub use hall_of_fame::HallOfFame;
+use history::{Clock, History};
+pub use individual::Individual;
+use local_search::{Learning, LocalSearch};
+#[cfg(feature = "std")]
+pub use map_elites::MapElites;
+pub use model::{Neighborhood, PopulationModel, Replace};
+use mutation::{Hypermutation, MutationControl};
+pub use objective::Objective;
+use observer::Observer;
+#[cfg(feature = "std")]
+pub use particle_swarm::{Particle, ParticleSwarm, Topology};
+#[cfg(feature = "plot")]
+pub use plot::PlotError;
+use population::PopulationControl;
+use progress::{ProgressReporter, Tracker};
+use replacement::Replacement;
+#[cfg(feature = "std")]
+use selection::FitnessProportionate;
+use selection::Selection;
+use stagnation::StagnationMonitor;
+pub use stagnation::StagnationResponse;
+pub use statistics::Statistics;
+use surrogate::Surrogate;
+use termination::{RunSummary, StopReason, Termination};
+
+/// Items used by the code that `#[derive(Organism)]` generates.
+#[cfg(feature = "derive")]
+#[doc(hidden)]
+pub mod __private {
+    pub use rand::Rng;
+}
 
 /// An interface for breeding, mutation, and fitness evaluation functionality.
 ///
+/// The ecosystem passes its own random number generator into [`breed`] and
+/// [`mutate`]. Organisms that draw all of their randomness from it evolve
+/// reproducibly when the ecosystem is seeded.
+///
+/// [`breed`]: #tymethod.breed
+/// [`mutate`]: #tymethod.mutate
+///
 /// The example code in this trait's method documentation is drawn from the
 /// 'π approximator' example of this crate's repository (https://github.com/thfm/ecosystem/).
 pub trait Organism {
+    /// The type of the organism's fitness value, such as `f64`.
+    type Fitness: Fitness;
+
     /// Evaluates the organism's fitness.
     ///
     /// # Examples
     ///
     /// ```rust
+    /// # use ecosystem::Organism;
+    /// # use rand::Rng;
+    /// # struct PiApproximator {
+    /// #     value: f64,
+    /// # }
     /// impl Organism for PiApproximator {
+    ///     type Fitness = f64;
+    ///
     ///     fn fitness(&self) -> f64 {
     ///         let diff = (std::f64::consts::PI - self.value).abs();
     ///         1.0 / diff
     ///     }
+    /// #   fn breed(&self, other: &Self, rng: &mut impl Rng) -> Self { unimplemented!() }
+    /// #   fn mutate(&mut self, rate: f64, rng: &mut impl Rng) {}
     /// }
     /// ```
-    fn fitness(&self) -> f64;
+    fn fitness(&self) -> Self::Fitness;
 
-    /// Creates a new child by breeding the organism with another.
+    /// Creates a new child by breeding the organism with another, using the
+    /// given random number generator for any random choices.
     ///
     /// # Examples
     ///
     /// ```rust
+    /// # use ecosystem::Organism;
+    /// # use rand::Rng;
+    /// # struct PiApproximator {
+    /// #     value: f64,
+    /// # }
     /// impl Organism for PiApproximator {
-    ///     fn breed(&self, other: &Self) -> Self {
+    /// #   type Fitness = f64;
+    /// #   fn fitness(&self) -> f64 { 0.0 }
+    ///     fn breed(&self, other: &Self, _rng: &mut impl Rng) -> Self {
     ///         Self {
     ///             value: (self.value + other.value) / 2.0,
     ///         }
     ///     }
+    /// #   fn mutate(&mut self, rate: f64, rng: &mut impl Rng) {}
     /// }
     /// ```
-    fn breed(&self, other: &Self) -> Self;
+    fn breed(&self, other: &Self, rng: &mut impl Rng) -> Self;
 
-    /// Modifies (or *mutates*) the organism, based on the given rate.
+    /// Modifies (or *mutates*) the organism, based on the given rate and
+    /// using the given random number generator.
     ///
     /// # Examples
     ///
     /// ```rust
     /// use rand::Rng;
+    /// # use ecosystem::Organism;
+    /// # struct PiApproximator {
+    /// #     value: f64,
+    /// # }
     ///
     /// impl Organism for PiApproximator {
-    ///     fn mutate(&mut self, rate: f64) {
-    ///         let change = rand::thread_rng().gen_range(-rate, rate);
+    /// #   type Fitness = f64;
+    /// #   fn fitness(&self) -> f64 { 0.0 }
+    /// #   fn breed(&self, other: &Self, rng: &mut impl Rng) -> Self { unimplemented!() }
+    ///     fn mutate(&mut self, rate: f64, rng: &mut impl Rng) {
+    ///         let change = rng.gen_range(-rate, rate);
     ///         self.value += change;
     ///     }
     /// }
     /// ```
-    fn mutate(&mut self, rate: f64);
+    fn mutate(&mut self, rate: f64, rng: &mut impl Rng);
+
+    /// Creates two children by breeding the organism with another, as many
+    /// crossover operators naturally do. The ecosystem breeds children in
+    /// pairs wherever it can, so implementing this avoids wasting half of the
+    /// work of such operators.
+    ///
+    /// By default, this breeds each organism with the other using
+    /// [`breed`].
+    ///
+    /// [`breed`]: #tymethod.breed
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use ecosystem::Organism;
+    /// # use rand::Rng;
+    /// struct Genome(Vec<bool>);
+    ///
+    /// impl Organism for Genome {
+    /// #   type Fitness = usize;
+    /// #   fn fitness(&self) -> usize { self.0.iter().filter(|&&bit| bit).count() }
+    ///     fn breed(&self, other: &Self, rng: &mut impl Rng) -> Self {
+    ///         self.breed_pair(other, rng).0
+    ///     }
+    ///
+    ///     // One-point crossover, which swaps the tails of the two genomes
+    ///     fn breed_pair(&self, other: &Self, rng: &mut impl Rng) -> (Self, Self) {
+    ///         let point = rng.gen_range(0, self.0.len() + 1);
+    ///         let mut first = self.0.clone();
+    ///         let mut second = other.0.clone();
+    ///         first[point..].swap_with_slice(&mut second[point..]);
+    ///         (Genome(first), Genome(second))
+    ///     }
+    /// #   fn mutate(&mut self, rate: f64, rng: &mut impl Rng) {}
+    /// }
+    /// ```
+    fn breed_pair(&self, other: &Self, rng: &mut impl Rng) -> (Self, Self)
+    where
+        Self: Sized,
+    {
+        (self.breed(other, rng), other.breed(self, rng))
+    }
+}
+
+/// An interface for comparing organisms directly, as an alternative to
+/// numeric fitness.
+///
+/// This is useful for problems in which it is easier to say which of two
+/// organisms is better than to score each one on an absolute scale (for
+/// example, when a person ranks pairs of organisms interactively). Closures of
+/// the form `Fn(&O, &O) -> Ordering` implement this trait.
+pub trait Comparator<O> {
+    /// Compares two organisms, with `Ordering::Greater` meaning that `a` is
+    /// fitter than `b`.
+    fn compare(&self, a: &O, b: &O) -> Ordering;
+}
+
+impl<O, F: Fn(&O, &O) -> Ordering> Comparator<O> for F {
+    fn compare(&self, a: &O, b: &O) -> Ordering {
+        self(a, b)
+    }
+}
+
+/// Returns the number of parents needed to breed `children` children in
+/// pairs.
+fn parent_count(children: usize) -> usize {
+    2 * children.div_ceil(2)
+}
+
+/// The probability that parents are recombined, along with the function used
+/// to clone a parent when they are not.
+type Crossover<O> = (f64, fn(&O) -> O);
+
+/// The functions by which the archive copies the survivors of each
+/// generation, and tells whether two organisms are the same.
+type ArchiveOps<O> = (fn(&Individual<O>) -> Individual<O>, fn(&O, &O) -> bool);
+
+/// The scale of variance-scaled mutation, along with the functions that
+/// measure the variance of each gene across a population and mutate an
+/// organism given those variances, a mutation rate and the scale.
+type VarianceScaling<O> = (
+    f64,
+    fn(&[Individual<O>]) -> Vec<f64>,
+    fn(&mut O, f64, &[f64], f64, &mut Pcg64),
+);
+
+/// A function that creates a random organism.
+type Generator<O> = Box<dyn Fn(&mut dyn RngCore) -> O + Send + Sync>;
+
+/// The fraction of the population replaced by immigrants in each generation,
+/// along with the function that creates them.
+type Immigration<O> = (f64, Generator<O>);
+
+/// A surrogate model of the fitness function, along with the number of
+/// candidate children bred for each child that is kept.
+type Screening<O> = (Box<dyn Surrogate<O>>, usize);
+
+/// The key by which duplicate organisms are recognised.
+type DuplicateKey<O> = Box<dyn Duplicates<O>>;
+
+/// A function giving the fitness of a point in a real-valued search space.
+#[cfg(feature = "std")]
+type RealFunction = Box<dyn Fn(&[f64]) -> f64 + Send + Sync>;
+
+/// Mutates an organism at the given rate: by variance-scaled mutation with
+/// the given variances, if enabled, or by the organism's own operator.
+fn mutate<O: Organism>(
+    organism: &mut O,
+    rate: f64,
+    variance_scaling: Option<VarianceScaling<O>>,
+    gene_variances: &[f64],
+    rng: &mut Pcg64,
+) {
+    match variance_scaling {
+        Some((scale, _, mutate)) => mutate(organism, rate, gene_variances, scale, rng),
+        None => organism.mutate(rate, rng),
+    }
+}
+
+/// The number of times a duplicate child is mutated in search of a distinct
+/// organism, before it is kept regardless.
+const DUPLICATE_MUTATION_ATTEMPTS: usize = 100;
+
+/// Evaluates the given organisms with a batch evaluator, checking that it
+/// returned a fitness value for each.
+fn evaluate_batch<O: Organism>(
+    evaluator: &dyn BatchEvaluator<O>,
+    organisms: &[&O],
+) -> Vec<O::Fitness> {
+    let fitnesses = evaluator.evaluate_all(organisms);
+    assert_eq!(
+        fitnesses.len(),
+        organisms.len(),
+        "the batch evaluator must return a fitness value for each organism"
+    );
+    fitnesses
+}
+
+/// Hashes a key by which duplicate organisms are recognised.
+fn hash_key<K: Hash + ?Sized>(key: &K) -> u64 {
+    #[cfg(feature = "std")]
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    // The standard library's hasher is SipHash as well, but only `core`'s
+    // own (deprecated) implementation is available without it
+    #[cfg(not(feature = "std"))]
+    #[allow(deprecated)]
+    let mut hasher = core::hash::SipHasher::new();
+    key.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// A way of recognising duplicate organisms by a key. Organisms whose keys
+/// hash alike are only duplicates if the keys are also equal, so that hash
+/// collisions do not count.
+trait Duplicates<O>: Send + Sync {
+    /// Returns the hash of the organism's key.
+    fn hash(&self, organism: &O) -> u64;
+
+    /// Returns whether two organisms have equal keys.
+    fn same(&self, a: &O, b: &O) -> bool;
+}
+
+/// Recognises duplicate organisms as those that are equal.
+struct Equality;
+
+impl<O: Hash + Eq> Duplicates<O> for Equality {
+    fn hash(&self, organism: &O) -> u64 {
+        hash_key(organism)
+    }
+
+    fn same(&self, a: &O, b: &O) -> bool {
+        a == b
+    }
+}
+
+/// Recognises duplicate organisms as those whose keys, as given by a
+/// function, are equal.
+struct KeyFunction<F>(F);
+
+impl<O, F, K> Duplicates<O> for KeyFunction<F>
+where
+    F: Fn(&O) -> K + Send + Sync,
+    K: Hash + Eq,
+{
+    fn hash(&self, organism: &O) -> u64 {
+        hash_key(&(self.0)(organism))
+    }
+
+    fn same(&self, a: &O, b: &O) -> bool {
+        (self.0)(a) == (self.0)(b)
+    }
+}
+
+/// Returns whether `S` is the default selection strategy, which an
+/// ecosystem resumed from a checkpoint falls back on.
+fn is_default_selection<S: 'static>() -> bool {
+    TypeId::of::<S>() == TypeId::of::<selection::FitnessProportionate>()
 }
 
 /// A collection of organisms.
 pub struct Ecosystem<O: Organism> {
-    /// A vector containing the organisms.
-    pub organisms: Vec<O>,
-    /// The current generation number.
-    pub generation: u32,
+    population: Vec<Individual<O>>,
+    generation: u32,
+    archive: Vec<Individual<O>>,
+    archive_capacity: usize,
+    archive_ops: Option<ArchiveOps<O>>,
+    evaluations: AtomicU64,
+    mutation_rate: f64,
+    rng: Pcg64,
+    selection: Box<dyn Selection<O>>,
+    replacement: Option<Box<dyn Replacement<O>>>,
+    elitism: usize,
+    generation_gap: f64,
+    objective: Objective,
+    history: Option<History>,
+    observers: Vec<Box<dyn Observer<O>>>,
+    progress: Option<Box<dyn ProgressReporter<O::Fitness>>>,
+    model: PopulationModel,
+    repair: Option<fn(&mut O)>,
+    crowding: Option<fn(&O, &O) -> f64>,
+    crossover: Option<Crossover<O>>,
+    variance_scaling: Option<VarianceScaling<O>>,
+    gene_variances: Vec<f64>,
+    duplicate_key: Option<DuplicateKey<O>>,
+    immigration: Option<Immigration<O>>,
+    stagnation: Option<StagnationMonitor<O>>,
+    mutation_control: Option<Box<dyn MutationControl<O>>>,
+    population_control: Option<Box<dyn PopulationControl<O>>>,
+    local_search: Option<Box<dyn LocalSearch<O>>>,
+    baldwinian: Option<fn(&O) -> O>,
+    surrogate: Option<Screening<O>>,
+    batch_evaluator: Option<Box<dyn BatchEvaluator<O>>>,
+    hypermutation: Option<Hypermutation>,
+    genealogy: Option<Genealogy>,
+    parallelism: Parallelism,
+    custom_selection: bool,
+    unrestored: Vec<Setting>,
 }
 
-impl<O: Organism + std::marker::Send + std::marker::Sync> Ecosystem<O> {
-    /// Creates a new ecosystem with the given organisms.
+impl<O: Organism + Send + Sync> Ecosystem<O> {
+    /// Creates a new ecosystem with the given organisms, using the default
+    /// configuration.
+    ///
+    /// Organisms are selected for breeding in proportion to their fitness,
+    /// and mutated at a rate of [`DEFAULT_MUTATION_RATE`]. The random number
+    /// generator is seeded from the operating system. Use [`builder`] to
+    /// configure the ecosystem differently.
+    ///
+    /// [`DEFAULT_MUTATION_RATE`]: constant.DEFAULT_MUTATION_RATE.html
+    /// [`builder`]: #method.builder
+    #[cfg(feature = "std")]
     pub fn new(organisms: Vec<O>) -> Self {
         Self {
-            organisms,
+            population: organisms.into_iter().map(Individual::new).collect(),
             generation: 0,
+            archive: Vec::new(),
+            archive_capacity: 0,
+            archive_ops: None,
+            evaluations: AtomicU64::new(0),
+            mutation_rate: DEFAULT_MUTATION_RATE,
+            rng: Pcg64::from_entropy(),
+            selection: Box::new(FitnessProportionate),
+            replacement: None,
+            elitism: 0,
+            generation_gap: 1.0,
+            objective: Objective::default(),
+            history: None,
+            observers: Vec::new(),
+            progress: None,
+            model: PopulationModel::default(),
+            repair: None,
+            crowding: None,
+            crossover: None,
+            variance_scaling: None,
+            gene_variances: Vec::new(),
+            duplicate_key: None,
+            immigration: None,
+            stagnation: None,
+            mutation_control: None,
+            population_control: None,
+            local_search: None,
+            baldwinian: None,
+            surrogate: None,
+            batch_evaluator: None,
+            hypermutation: None,
+            genealogy: None,
+            parallelism: Parallelism::default(),
+            custom_selection: false,
+            unrestored: Vec::new(),
         }
     }
 
-    /// Returns the organism in the ecosystem with the highest fitness.
-    pub fn fittest(&self) -> &O {
-        self.organisms
+    /// Returns a builder for configuring a new ecosystem.
+    pub fn builder() -> EcosystemBuilder<O> {
+        EcosystemBuilder::new()
+    }
+
+    /// Returns an iterator over the organisms in the archive, ordered from
+    /// fittest to least fit.
+    ///
+    /// The archive is empty unless it was enabled with
+    /// [`EcosystemBuilder::archive`].
+    ///
+    /// [`EcosystemBuilder::archive`]: struct.EcosystemBuilder.html#method.archive
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ecosystem::{Ecosystem, Organism};
+    /// # use rand::Rng;
+    ///
+    /// #[derive(Clone, PartialEq)]
+    /// struct Number(u32);
+    ///
+    /// // Every number from 10 upwards is equally good
+    /// impl Organism for Number {
+    ///     type Fitness = u32;
+    ///
+    ///     fn fitness(&self) -> u32 {
+    ///         self.0.min(10)
+    ///     }
+    /// #   fn breed(&self, other: &Self, rng: &mut impl Rng) -> Self { Number(self.0.max(other.0)) }
+    /// #   fn mutate(&mut self, rate: f64, rng: &mut impl Rng) {}
+    /// }
+    ///
+    /// let mut ecosystem = Ecosystem::builder()
+    ///     .organisms((0..20).map(Number))
+    ///     .archive(5)
+    ///     .build()
+    ///     .unwrap();
+    /// ecosystem.breed_next_generation()?;
+    ///
+    /// // The plateau fills the archive with distinct organisms
+    /// let mut archived: Vec<_> = ecosystem.archive().map(|n| n.0).collect();
+    /// archived.sort();
+    /// archived.dedup();
+    /// assert_eq!(archived.len(), 5);
+    /// assert!(archived.iter().all(|&n| n >= 10));
+    /// # Ok::<(), ecosystem::EcosystemError>(())
+    /// ```
+    pub fn archive(&self) -> impl Iterator<Item = &O> {
+        self.archive.iter().map(Individual::organism)
+    }
+
+    /// Returns the individuals in the archive, ordered from fittest to least
+    /// fit, along with their fitness.
+    pub fn archived_individuals(&self) -> &[Individual<O>] {
+        &self.archive
+    }
+
+    /// Returns the history of the run, if recording was enabled with
+    /// [`EcosystemBuilder::record_history`].
+    ///
+    /// [`EcosystemBuilder::record_history`]: struct.EcosystemBuilder.html#method.record_history
+    pub fn history(&self) -> Option<&History> {
+        self.history.as_ref()
+    }
+
+    /// Returns the genealogy of the ecosystem's individuals, if tracking was
+    /// enabled with [`EcosystemBuilder::track_genealogy`].
+    ///
+    /// [`EcosystemBuilder::track_genealogy`]: struct.EcosystemBuilder.html#method.track_genealogy
+    pub fn genealogy(&self) -> Option<&Genealogy> {
+        self.genealogy.as_ref()
+    }
+
+    /// Returns the individuals making up the current generation, evaluating
+    /// any that have not yet been evaluated.
+    pub fn individuals(&self) -> &[Individual<O>] {
+        self.evaluate_all(&self.population);
+        &self.population
+    }
+
+    /// Returns an iterator over the organisms in the current generation.
+    pub fn organisms(&self) -> impl Iterator<Item = &O> {
+        self.population.iter().map(Individual::organism)
+    }
+
+    /// Replaces the strategy used to select organisms for breeding.
+    ///
+    /// This is mainly useful for restoring the strategy of an ecosystem that
+    /// was deserialised, as strategies are not saved along with it.
+    pub fn set_selection<S: Selection<O> + 'static>(&mut self, selection: S) {
+        self.selection = Box::new(selection);
+        self.custom_selection = !is_default_selection::<S>();
+        self.restored(Setting::Selection);
+    }
+
+    /// Replaces the strategy that chooses the organisms replaced by
+    /// children, as set with [`EcosystemBuilder::replacement`].
+    ///
+    /// [`EcosystemBuilder::replacement`]: struct.EcosystemBuilder.html#method.replacement
+    pub fn set_replacement<R: Replacement<O> + 'static>(&mut self, replacement: R) {
+        self.replacement = Some(Box::new(replacement));
+        self.restored(Setting::Replacement);
+    }
+
+    /// Registers an [observer], in addition to any registered with the
+    /// builder.
+    ///
+    /// [observer]: observer/index.html
+    pub fn add_observer<T: Observer<O> + 'static>(&mut self, observer: T) {
+        self.observers.push(Box::new(observer));
+        self.restored(Setting::Observers);
+    }
+
+    /// Sets the [progress reporter] that is told how each run is going,
+    /// replacing any set with the builder.
+    ///
+    /// [progress reporter]: progress/index.html
+    pub fn set_progress_reporter<T: ProgressReporter<O::Fitness> + 'static>(
+        &mut self,
+        reporter: T,
+    ) {
+        self.progress = Some(Box::new(reporter));
+        self.restored(Setting::ProgressReporter);
+    }
+
+    /// Replaces the controller that adjusts the mutation rate before each
+    /// generation, as set with [`EcosystemBuilder::mutation_control`].
+    ///
+    /// [`EcosystemBuilder::mutation_control`]: struct.EcosystemBuilder.html#method.mutation_control
+    pub fn set_mutation_control<T: MutationControl<O> + 'static>(&mut self, control: T) {
+        self.mutation_control = Some(Box::new(control));
+        self.restored(Setting::MutationControl);
+    }
+
+    /// Replaces the controller that adjusts the population size before each
+    /// generation, as set with [`EcosystemBuilder::population_control`].
+    ///
+    /// [`EcosystemBuilder::population_control`]: struct.EcosystemBuilder.html#method.population_control
+    pub fn set_population_control<T: PopulationControl<O> + 'static>(&mut self, control: T) {
+        self.population_control = Some(Box::new(control));
+        self.restored(Setting::PopulationControl);
+    }
+
+    /// Replaces the local search that improves each child after it is bred,
+    /// as set with [`EcosystemBuilder::local_search`].
+    ///
+    /// [`EcosystemBuilder::local_search`]: struct.EcosystemBuilder.html#method.local_search
+    pub fn set_local_search<T: LocalSearch<O> + 'static>(&mut self, search: T) {
+        self.local_search = Some(Box::new(search));
+        self.restored(Setting::LocalSearch);
+    }
+
+    /// Replaces the evaluator used to evaluate many organisms at once, as set
+    /// with [`EcosystemBuilder::batch_evaluator`].
+    ///
+    /// [`EcosystemBuilder::batch_evaluator`]: struct.EcosystemBuilder.html#method.batch_evaluator
+    pub fn set_batch_evaluator<T: BatchEvaluator<O> + 'static>(&mut self, evaluator: T) {
+        self.batch_evaluator = Some(Box::new(evaluator));
+        self.restored(Setting::BatchEvaluator);
+    }
+
+    /// Replaces the thread pool on which the ecosystem evaluates and breeds
+    /// organisms, as set with [`EcosystemBuilder::thread_pool`].
+    ///
+    /// [`EcosystemBuilder::thread_pool`]: struct.EcosystemBuilder.html#method.thread_pool
+    #[cfg(feature = "parallel")]
+    pub fn set_thread_pool(&mut self, pool: std::sync::Arc<rayon::ThreadPool>) {
+        self.parallelism.pool = Some(pool);
+        self.restored(Setting::ThreadPool);
+    }
+
+    /// Replaces the least number of organisms that each thread evaluates or
+    /// breeds at a time, as set with [`EcosystemBuilder::min_chunk_len`].
+    ///
+    /// [`EcosystemBuilder::min_chunk_len`]: struct.EcosystemBuilder.html#method.min_chunk_len
+    #[cfg(feature = "parallel")]
+    pub fn set_min_chunk_len(&mut self, len: usize) {
+        self.parallelism.min_len = len;
+    }
+
+    /// Replaces the surrogate model by which candidate children are screened,
+    /// as set with [`EcosystemBuilder::surrogate`]. At least one candidate is
+    /// bred for each child.
+    ///
+    /// [`EcosystemBuilder::surrogate`]: struct.EcosystemBuilder.html#method.surrogate
+    pub fn set_surrogate<T: Surrogate<O> + 'static>(&mut self, surrogate: T, candidates: usize) {
+        self.surrogate = Some((Box::new(surrogate), candidates.max(1)));
+        self.restored(Setting::Surrogate);
+    }
+
+    /// Has children that duplicate another organism mutated until they are
+    /// distinct, recognising duplicates by the given key, as with
+    /// [`EcosystemBuilder::deduplicate_by_key`].
+    ///
+    /// [`EcosystemBuilder::deduplicate_by_key`]: struct.EcosystemBuilder.html#method.deduplicate_by_key
+    pub fn set_duplicate_key<K, F>(&mut self, key: F)
+    where
+        K: Hash + Eq,
+        F: Fn(&O) -> K + Send + Sync + 'static,
+    {
+        self.duplicate_key = Some(Box::new(KeyFunction(key)));
+        self.restored(Setting::Deduplication);
+    }
+
+    /// Has a fraction of the population replaced by newly generated
+    /// immigrants in each generation, as with [`EcosystemBuilder::immigrants`].
+    /// The fraction is clamped to [0, 1].
+    ///
+    /// [`EcosystemBuilder::immigrants`]: struct.EcosystemBuilder.html#method.immigrants
+    pub fn set_immigrants<G>(&mut self, fraction: f64, generate: G)
+    where
+        G: Fn(&mut dyn RngCore) -> O + Send + Sync + 'static,
+    {
+        self.immigration = Some((fraction.clamp(0.0, 1.0), Box::new(generate)));
+        self.restored(Setting::Immigrants);
+    }
+
+    /// Sets the fraction of the population replaced by children in each
+    /// generation, as with [`EcosystemBuilder::generation_gap`]. The
+    /// fraction is clamped to [0, 1], though at least one organism is always
+    /// replaced.
+    ///
+    /// [`EcosystemBuilder::generation_gap`]: struct.EcosystemBuilder.html#method.generation_gap
+    pub fn set_generation_gap(&mut self, gap: f64) {
+        self.generation_gap = gap.clamp(0.0, 1.0);
+    }
+
+    /// Returns the rate at which new organisms are mutated, not including
+    /// any [hypermutation].
+    ///
+    /// [hypermutation]: #method.hypermutate
+    pub fn mutation_rate(&self) -> f64 {
+        self.mutation_rate
+    }
+
+    /// Multiplies the mutation rate by `hypermutation.factor` (keeping it
+    /// within [0, 1]) for the next `hypermutation.generations` generations,
+    /// replacing any hypermutation already under way.
+    ///
+    /// A burst of hypermutation is the usual response to a change in the
+    /// fitness landscape, scattering a converged population so that it can
+    /// find the new optimum. It can also be triggered automatically when the
+    /// population stagnates, with [`StagnationResponse::Hypermutate`].
+    ///
+    /// [`StagnationResponse::Hypermutate`]: enum.StagnationResponse.html#variant.Hypermutate
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ecosystem::{mutation::Hypermutation, Ecosystem, Organism};
+    /// # use rand::Rng;
+    /// # struct Guess(f64);
+    /// # impl Organism for Guess {
+    /// #     type Fitness = f64;
+    /// #     fn fitness(&self) -> f64 { 1.0 / (42.0 - self.0).abs() }
+    /// #     fn breed(&self, other: &Self, rng: &mut impl Rng) -> Self { Guess((self.0 + other.0) / 2.0) }
+    /// #     fn mutate(&mut self, rate: f64, rng: &mut impl Rng) {
+    /// #         self.0 += rng.gen_range(-rate, rate) * 100.0;
+    /// #     }
+    /// # }
+    ///
+    /// let mut ecosystem = Ecosystem::builder()
+    ///     .organisms((0..20).map(|_| Guess(42.5)))
+    ///     .mutation_rate(0.01)
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// // The environment has changed, so scatter the population
+    /// ecosystem.hypermutate(Hypermutation { factor: 50.0, generations: 2 });
+    /// ecosystem.breed_next_generation()?;
+    /// assert!(ecosystem.organisms().any(|guess| (guess.0 - 42.5).abs() > 5.0));
+    /// assert_eq!(ecosystem.hypermutation().unwrap().generations, 1);
+    /// ecosystem.breed_next_generation()?;
+    /// assert_eq!(ecosystem.hypermutation(), None);
+    /// assert_eq!(ecosystem.mutation_rate(), 0.01);
+    /// # Ok::<(), ecosystem::EcosystemError>(())
+    /// ```
+    pub fn hypermutate(&mut self, hypermutation: Hypermutation) {
+        self.hypermutation = Some(hypermutation).filter(|h| h.generations > 0);
+    }
+
+    /// Returns the hypermutation under way, with the number of generations
+    /// it has left to run, if there is one.
+    pub fn hypermutation(&self) -> Option<Hypermutation> {
+        self.hypermutation
+    }
+
+    /// Returns the direction in which fitness values are optimised.
+    pub fn objective(&self) -> Objective {
+        self.objective
+    }
+
+    /// Returns the current generation number.
+    pub fn generation(&self) -> u32 {
+        self.generation
+    }
+
+    /// Returns the total number of fitness evaluations performed so far.
+    ///
+    /// Unlike the generation number, this reflects the actual amount of work
+    /// done, which makes it a fairer basis for comparing different runs. As
+    /// fitness values are cached, each organism is evaluated at most once.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ecosystem::{Ecosystem, Organism};
+    /// # use rand::Rng;
+    /// # struct Number(u32);
+    /// # impl Organism for Number {
+    /// #     type Fitness = f64;
+    /// #     fn fitness(&self) -> f64 { f64::from(self.0) }
+    /// #     fn breed(&self, other: &Self, rng: &mut impl Rng) -> Self { Number(self.0) }
+    /// #     fn mutate(&mut self, rate: f64, rng: &mut impl Rng) {}
+    /// # }
+    ///
+    /// let mut ecosystem = Ecosystem::new((1..=10).map(Number).collect());
+    /// assert_eq!(ecosystem.evaluations(), 0);
+    ///
+    /// ecosystem.fittest();
+    /// assert_eq!(ecosystem.evaluations(), 10);
+    ///
+    /// // The cached values are reused
+    /// ecosystem.fittest();
+    /// assert_eq!(ecosystem.evaluations(), 10);
+    ///
+    /// ecosystem.breed_next_generation()?;
+    /// ecosystem.fittest();
+    /// assert_eq!(ecosystem.evaluations(), 20);
+    /// # Ok::<(), ecosystem::EcosystemError>(())
+    /// ```
+    ///
+    /// The count matches the number of calls to [`Organism::fitness`], and
+    /// the cached values match those the organisms give when evaluated
+    /// afresh:
+    ///
+    /// [`Organism::fitness`]: trait.Organism.html#tymethod.fitness
+    ///
+    /// ```rust
+    /// use ecosystem::{Ecosystem, Organism};
+    /// use rand::Rng;
+    /// use std::sync::atomic::{AtomicU64, Ordering};
+    ///
+    /// static CALLS: AtomicU64 = AtomicU64::new(0);
+    ///
+    /// struct Guess(f64);
+    ///
+    /// impl Organism for Guess {
+    ///     type Fitness = f64;
+    ///
+    ///     fn fitness(&self) -> f64 {
+    ///         CALLS.fetch_add(1, Ordering::Relaxed);
+    ///         1.0 / (1.0 + (42.0 - self.0).abs())
+    ///     }
+    /// #   fn breed(&self, other: &Self, rng: &mut impl Rng) -> Self {
+    /// #       Guess(if rng.gen() { self.0 } else { other.0 })
+    /// #   }
+    /// #   fn mutate(&mut self, rate: f64, rng: &mut impl Rng) {
+    /// #       self.0 += rng.gen_range(-rate, rate);
+    /// #   }
+    /// }
+    ///
+    /// let mut ecosystem = Ecosystem::builder()
+    ///     .organisms((0..20).map(|i| Guess(f64::from(i) * 5.0 + 0.5)))
+    ///     .mutation_rate(0.5)
+    ///     .elitism(2)
+    ///     .build()
+    ///     .unwrap();
+    /// ecosystem.breed_next_generation()?;
+    /// for _ in 0..10 {
+    ///     let before = CALLS.load(Ordering::Relaxed);
+    ///     ecosystem.breed_next_generation()?;
+    ///     // The parents were evaluated as they were bred, and the elites
+    ///     // carried over keep their cached values, so only the 18 children
+    ///     // of the previous generation are evaluated
+    ///     assert_eq!(CALLS.load(Ordering::Relaxed) - before, 18);
+    ///     assert_eq!(CALLS.load(Ordering::Relaxed), ecosystem.evaluations());
+    /// }
+    ///
+    /// let cached: Vec<_> = ecosystem.individuals().iter().map(|i| i.fitness()).collect();
+    /// let uncached: Vec<_> = ecosystem.organisms().map(Organism::fitness).collect();
+    /// assert_eq!(cached, uncached);
+    /// # Ok::<(), ecosystem::EcosystemError>(())
+    /// ```
+    pub fn evaluations(&self) -> u64 {
+        self.evaluations.load(atomic::Ordering::Relaxed)
+    }
+
+    /// Summarises the fitness values of the current generation, or returns
+    /// `None` if the ecosystem contains no organisms.
+    ///
+    /// Any organisms that have not yet been evaluated are evaluated in
+    /// parallel; the cached values are used for the rest.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ecosystem::{Ecosystem, Organism};
+    /// # use rand::Rng;
+    /// # struct Number(u32);
+    /// # impl Organism for Number {
+    /// #     type Fitness = u32;
+    /// #     fn fitness(&self) -> u32 { self.0 }
+    /// #     fn breed(&self, other: &Self, rng: &mut impl Rng) -> Self { Number(self.0) }
+    /// #     fn mutate(&mut self, rate: f64, rng: &mut impl Rng) {}
+    /// # }
+    ///
+    /// let numbers = vec![2, 4, 4, 4, 5, 5, 7, 9];
+    /// let ecosystem = Ecosystem::new(numbers.into_iter().map(Number).collect());
+    /// let statistics = ecosystem.statistics().unwrap();
+    ///
+    /// assert_eq!(statistics.min, 2.0);
+    /// assert_eq!(statistics.max, 9.0);
+    /// assert_eq!(statistics.mean, 5.0);
+    /// assert_eq!(statistics.median, 4.5);
+    /// assert_eq!(statistics.std_dev, 2.0);
+    /// ```
+    pub fn statistics(&self) -> Option<Statistics> {
+        self.evaluate_all(&self.population);
+        Statistics::from_fitnesses(
+            self.population
+                .par_iter()
+                .map(|individual| self.evaluate(individual).to_f64())
+                .collect(),
+        )
+    }
+
+    /// Returns the organism in the ecosystem with the best fitness (the
+    /// highest, unless the ecosystem's objective is to minimise), or `None`
+    /// if the ecosystem contains no organisms.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ecosystem::{Ecosystem, EcosystemError, Organism};
+    /// # use rand::Rng;
+    /// # struct Number(u32);
+    /// # impl Organism for Number {
+    /// #     type Fitness = u32;
+    /// #     fn fitness(&self) -> u32 { self.0 }
+    /// #     fn breed(&self, other: &Self, rng: &mut impl Rng) -> Self { Number(self.0) }
+    /// #     fn mutate(&mut self, rate: f64, rng: &mut impl Rng) {}
+    /// # }
+    ///
+    /// let mut ecosystem = Ecosystem::new(vec![Number(3), Number(7), Number(5)]);
+    /// assert_eq!(ecosystem.fittest().unwrap().0, 7);
+    ///
+    /// // Degenerate states are reported rather than causing a panic
+    /// let mut empty = Ecosystem::<Number>::new(Vec::new());
+    /// assert!(empty.fittest().is_none());
+    /// assert_eq!(empty.breed_next_generation(), Err(EcosystemError::EmptyPopulation));
+    /// ```
+    pub fn fittest(&self) -> Option<&O> {
+        self.fittest_individual().map(Individual::organism)
+    }
+
+    /// Returns the individual in the ecosystem with the best fitness, along
+    /// with its cached fitness value, or `None` if the ecosystem contains no
+    /// organisms.
+    pub fn fittest_individual(&self) -> Option<&Individual<O>> {
+        self.evaluate_all(&self.population);
+        self.population
             .iter()
-            .fold(&self.organisms[0], |fittest, organism| {
-                if organism.fitness() > fittest.fitness() {
-                    organism
-                } else {
-                    fittest
-                }
-            })
+            .map(|individual| (self.evaluate(individual), individual))
+            .fold(
+                None,
+                |fittest: Option<(O::Fitness, &Individual<O>)>, (fitness, individual)| match fittest
+                {
+                    Some((best, _)) if self.objective.compare(best, fitness) != Ordering::Less => {
+                        fittest
+                    }
+                    _ => Some((fitness, individual)),
+                },
+            )
+            .map(|(_, individual)| individual)
     }
 
-    /// Creates the next generation of organisms through the breeding
-    /// of suitable organisms.
-    pub fn breed_next_generation(&mut self, mutation_rate: f64) {
-        let next_generation: Vec<_> = (0..self.organisms.len())
-            .into_par_iter()
-            .map(|_| {
-                let mother = self.select_suitable_organism();
-                let father = self.select_suitable_organism();
+    /// Returns the fittest organism in the ecosystem according to the given
+    /// comparator, without evaluating any fitness values, or `None` if the
+    /// ecosystem contains no organisms.
+    pub fn fittest_by<C: Comparator<O>>(&self, comparator: &C) -> Option<&O> {
+        self.organisms().max_by(|a, b| comparator.compare(a, b))
+    }
 
-                let mut child = mother.breed(father);
-                child.mutate(mutation_rate);
-                child
-            })
-            .collect();
+    /// Returns the `k` fittest organisms in the ecosystem, fittest first, or
+    /// every organism if there are fewer than `k`.
+    ///
+    /// The organisms are ranked by their cached fitness values, and only the
+    /// `k` fittest are sorted, so this is cheaper than [`ranked`] when `k` is
+    /// small.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ecosystem::{Ecosystem, Objective, Organism};
+    /// # use rand::Rng;
+    /// # struct Number(u32);
+    /// # impl Organism for Number {
+    /// #     type Fitness = u32;
+    /// #     fn fitness(&self) -> u32 { self.0 }
+    /// #     fn breed(&self, other: &Self, rng: &mut impl Rng) -> Self { Number(self.0) }
+    /// #     fn mutate(&mut self, rate: f64, rng: &mut impl Rng) {}
+    /// # }
+    ///
+    /// let numbers = [3, 9, 1, 7, 5].iter().map(|&n| Number(n));
+    /// let ecosystem = Ecosystem::builder().organisms(numbers).build().unwrap();
+    /// let best: Vec<u32> = ecosystem.top_k(3).iter().map(|number| number.0).collect();
+    /// assert_eq!(best, [9, 7, 5]);
+    ///
+    /// let numbers = [3, 9, 1, 7, 5].iter().map(|&n| Number(n));
+    /// let ecosystem = Ecosystem::builder()
+    ///     .organisms(numbers)
+    ///     .objective(Objective::Minimize)
+    ///     .build()
+    ///     .unwrap();
+    /// let best: Vec<u32> = ecosystem.top_k(10).iter().map(|number| number.0).collect();
+    /// assert_eq!(best, [1, 3, 5, 7, 9]);
+    /// ```
+    ///
+    /// [`ranked`]: #method.ranked
+    pub fn top_k(&self, k: usize) -> Vec<&O> {
+        self.rank(k)
+            .into_iter()
+            .map(|index| self.population[index].organism())
+            .collect()
+    }
 
-        self.organisms = next_generation;
-        self.generation += 1;
+    /// Returns every organism in the ecosystem, ordered by their cached
+    /// fitness values, fittest first.
+    pub fn ranked(&self) -> Vec<&O> {
+        self.top_k(self.population.len())
     }
 
-    /// Selects an organism in the ecosystem that is suitable for breeding,
-    /// based on fitness values.
+    /// Consumes the ecosystem, returning the organism with the best fitness,
+    /// or `None` if the ecosystem contains no organisms.
     ///
-    /// # Panics
+    /// Unlike [`fittest`], this hands over the organism itself, so it can be
+    /// kept after a run without being cloned.
     ///
-    /// This method panics if the ecosystem contains no organisms.
-    fn select_suitable_organism(&self) -> &O {
-        let mut rng = rand::thread_rng();
-        loop {
-            let organism = self
-                .organisms
-                .choose(&mut rng)
-                .unwrap_or_else(|| panic!("there are no organisms in the ecosystem"));
-            if organism.fitness() > rng.gen_range(0.0, self.fittest().fitness()) {
-                break &organism;
-            }
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ecosystem::{Ecosystem, Organism};
+    /// # use rand::Rng;
+    /// # struct Number(u32);
+    /// # impl Organism for Number {
+    /// #     type Fitness = u32;
+    /// #     fn fitness(&self) -> u32 { self.0 }
+    /// #     fn breed(&self, other: &Self, rng: &mut impl Rng) -> Self { Number(self.0) }
+    /// #     fn mutate(&mut self, rate: f64, rng: &mut impl Rng) {}
+    /// # }
+    ///
+    /// let ecosystem = Ecosystem::new(vec![Number(3), Number(7), Number(5)]);
+    /// let winner: Number = ecosystem.into_fittest().unwrap();
+    /// assert_eq!(winner.0, 7);
+    /// ```
+    ///
+    /// [`fittest`]: #method.fittest
+    pub fn into_fittest(self) -> Option<O> {
+        let index = self.rank(1).pop()?;
+        let mut population = self.population;
+        Some(population.swap_remove(index).into_organism())
+    }
+
+    /// Consumes the ecosystem, returning every organism, ordered by their
+    /// cached fitness values, fittest first.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ecosystem::{Ecosystem, Organism};
+    /// # use rand::Rng;
+    /// # struct Number(u32);
+    /// # impl Organism for Number {
+    /// #     type Fitness = u32;
+    /// #     fn fitness(&self) -> u32 { self.0 }
+    /// #     fn breed(&self, other: &Self, rng: &mut impl Rng) -> Self { Number(self.0) }
+    /// #     fn mutate(&mut self, rate: f64, rng: &mut impl Rng) {}
+    /// # }
+    ///
+    /// let ecosystem = Ecosystem::new(vec![Number(3), Number(7), Number(5)]);
+    /// let ranked: Vec<u32> = ecosystem.into_ranked().into_iter().map(|n| n.0).collect();
+    /// assert_eq!(ranked, [7, 5, 3]);
+    /// ```
+    pub fn into_ranked(self) -> Vec<O> {
+        let order = self.rank(self.population.len());
+        let mut population: Vec<_> = self.population.into_iter().map(Some).collect();
+        order
+            .into_iter()
+            .filter_map(|index| population[index].take())
+            .map(Individual::into_organism)
+            .collect()
+    }
+
+    /// Returns the indices of the `k` fittest individuals (or of all of
+    /// them, if there are fewer than `k`), fittest first. Only those `k` are
+    /// sorted.
+    fn rank(&self, k: usize) -> Vec<usize> {
+        self.evaluate_all(&self.population);
+        let mut ranking: Vec<_> = self
+            .population
+            .iter()
+            .map(|individual| self.evaluate(individual))
+            .enumerate()
+            .collect();
+        let objective = self.objective;
+        let fitter_first =
+            |a: &(usize, O::Fitness), b: &(usize, O::Fitness)| objective.compare(b.1, a.1);
+        let k = k.min(ranking.len());
+        if k == 0 {
+            return Vec::new();
+        }
+        if k < ranking.len() {
+            ranking.select_nth_unstable_by(k - 1, fitter_first);
+            ranking.truncate(k);
         }
+        ranking.sort_by(fitter_first);
+        ranking.into_iter().map(|(index, _)| index).collect()
+    }
+
+    /// Randomly reorders the organisms in the ecosystem.
+    ///
+    /// This removes any bias that might arise from the order in which the
+    /// organisms were supplied (e.g. when loading them from a file). The
+    /// ecosystem's own random number generator is used, so the resulting order
+    /// is reproducible when the ecosystem is seeded.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ecosystem::{Ecosystem, Organism};
+    /// # use rand::Rng;
+    /// # struct Number(u32);
+    /// # impl Organism for Number {
+    /// #     type Fitness = f64;
+    /// #     fn fitness(&self) -> f64 { 1.0 }
+    /// #     fn breed(&self, other: &Self, rng: &mut impl Rng) -> Self { Number(self.0) }
+    /// #     fn mutate(&mut self, rate: f64, rng: &mut impl Rng) {}
+    /// # }
+    ///
+    /// let order = |seed| {
+    ///     let mut ecosystem = Ecosystem::builder()
+    ///         .organisms((0..20).map(Number))
+    ///         .seed(seed)
+    ///         .build()
+    ///         .unwrap();
+    ///     ecosystem.shuffle();
+    ///     ecosystem.organisms().map(|n| n.0).collect::<Vec<_>>()
+    /// };
+    ///
+    /// assert_eq!(order(1), order(1));
+    /// assert_ne!(order(1), order(2));
+    /// ```
+    pub fn shuffle(&mut self) {
+        self.population.shuffle(&mut self.rng);
+    }
+
+    /// Injects the given organisms into the current generation, in place of
+    /// its least fit individuals, and returns how many were injected. The
+    /// population size stays the same, and the elites are never replaced, so
+    /// any organisms beyond the number of individuals that may be replaced
+    /// are left out.
+    ///
+    /// This is useful for warm-starting a search from known good solutions,
+    /// or for bringing in organisms found elsewhere partway through a run.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ecosystem::{Ecosystem, Organism};
+    /// # use rand::Rng;
+    /// # #[derive(Clone, Copy, Debug, PartialEq)]
+    /// # struct Guess(f64);
+    /// # impl Organism for Guess {
+    /// #     type Fitness = f64;
+    /// #     fn fitness(&self) -> f64 { 1.0 / (42.0 - self.0).abs() }
+    /// #     fn breed(&self, other: &Self, rng: &mut impl Rng) -> Self { Guess((self.0 + other.0) / 2.0) }
+    /// #     fn mutate(&mut self, rate: f64, rng: &mut impl Rng) {}
+    /// # }
+    ///
+    /// let mut ecosystem = Ecosystem::builder()
+    ///     .organisms((0..10).map(|i| Guess(f64::from(i) + 100.5)))
+    ///     .elitism(8)
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// // Only the two least fit guesses may be replaced
+    /// let injected = ecosystem.inject(vec![Guess(41.5), Guess(42.5), Guess(43.5)]);
+    ///
+    /// assert_eq!(injected, 2);
+    /// assert_eq!(ecosystem.individuals().len(), 10);
+    /// assert!(ecosystem.organisms().any(|&guess| guess == Guess(41.5)));
+    /// assert!(ecosystem.organisms().all(|guess| guess.0 < 108.0));
+    /// ```
+    pub fn inject(&mut self, organisms: impl IntoIterator<Item = O>) -> usize {
+        self.replace_least_fit_with(organisms.into_iter().collect())
+    }
+
+    /// Creates the next generation of organisms through the breeding
+    /// of suitable organisms, chosen by the ecosystem's selection strategy.
+    ///
+    /// How much of the population is replaced depends on the ecosystem's
+    /// [`PopulationModel`]; in a steady-state population, each step counts
+    /// as a generation.
+    ///
+    /// [`PopulationModel`]: enum.PopulationModel.html
+    ///
+    /// The fitness of each organism in the current generation is evaluated
+    /// once, up front, and reused throughout selection. Children are bred in
+    /// parallel, but each is given its own random number generator, seeded in
+    /// turn from the ecosystem's; the result is therefore the same however
+    /// the work is scheduled across threads.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ecosystem::{Ecosystem, Organism};
+    /// use rand::Rng;
+    /// # struct Guess(f64);
+    /// # impl Organism for Guess {
+    /// #     type Fitness = f64;
+    /// #     fn fitness(&self) -> f64 { 1.0 / (42.0 - self.0).abs() }
+    /// #     fn breed(&self, other: &Self, rng: &mut impl Rng) -> Self {
+    /// #         Guess(if rng.gen() { self.0 } else { other.0 })
+    /// #     }
+    /// #     fn mutate(&mut self, rate: f64, rng: &mut impl Rng) {
+    /// #         self.0 += rng.gen_range(-rate, rate);
+    /// #     }
+    /// # }
+    ///
+    /// let evolve = |seed| {
+    ///     let mut ecosystem = Ecosystem::builder()
+    ///         .organisms((0..100).map(|i| Guess(f64::from(i) + 0.5)))
+    ///         .mutation_rate(0.5)
+    ///         .seed(seed)
+    ///         .build()
+    ///         .unwrap();
+    ///     for _ in 0..10 {
+    ///         ecosystem.breed_next_generation().unwrap();
+    ///     }
+    ///     ecosystem.organisms().map(|guess| guess.0).collect::<Vec<_>>()
+    /// };
+    ///
+    /// assert_eq!(evolve(3), evolve(3));
+    /// assert_ne!(evolve(3), evolve(4));
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the ecosystem contains no organisms, if the
+    /// selection strategy fails, or if settings lost in a [checkpoint] have
+    /// not been restored. The ecosystem is left unchanged in each case.
+    ///
+    /// [checkpoint]: #method.save_checkpoint
+    pub fn breed_next_generation(&mut self) -> Result<(), EcosystemError> {
+        if self.population.is_empty() {
+            return Err(EcosystemError::EmptyPopulation);
+        }
+        self.check_restored()?;
+
+        let _span = trace::generation(self.generation);
+        let clock = Clock::starting_at(Duration::ZERO);
+        let parallelism = self.parallelism.clone();
+        parallelism.install(|| {
+            self.evaluate_all(&self.population);
+            trace::evaluated(self.generation, self.objective, || {
+                Statistics::from_fitnesses(
+                    self.population
+                        .iter()
+                        .map(|individual| self.evaluate(individual).to_f64())
+                        .collect(),
+                )
+            });
+            self.begin_generation();
+            self.resize_population()?;
+            self.breed_generation()?;
+            self.admit_immigrants();
+            self.end_generation();
+            Ok(())
+        })?;
+        trace::bred(self.generation, self.evaluations(), clock.elapsed());
+        Ok(())
+    }
+
+    /// Prepares to breed a generation, once the current one has been
+    /// evaluated.
+    fn begin_generation(&mut self) {
+        self.teach_surrogate();
+        if self
+            .history
+            .as_ref()
+            .is_some_and(|history| history.records().is_empty())
+        {
+            self.record_history();
+        }
+        self.note_best_fitness();
+        if let Some(mut control) = self.mutation_control.take() {
+            self.mutation_rate = control.mutation_rate(self).clamp(0.0, 1.0);
+            self.mutation_control = Some(control);
+        }
+        if let Some((_, gene_variances, _)) = self.variance_scaling {
+            self.gene_variances = gene_variances(&self.population);
+        }
+    }
+
+    /// Shrinks or grows the population to the size chosen by the population
+    /// controller, if any, within the bounds that the population model
+    /// allows. The least fit individuals are retired to shrink it, and extra
+    /// children are bred to grow it.
+    fn resize_population(&mut self) -> Result<(), EcosystemError> {
+        let Some(mut control) = self.population_control.take() else {
+            return Ok(());
+        };
+        let size = control.population_size(self);
+        self.population_control = Some(control);
+
+        let size = self.model.valid_population_size(size, self.elitism);
+        let current = self.population.len();
+        if size > current {
+            let children = self.select_and_breed(size - current)?;
+            self.evaluate_all(&children);
+            self.population.extend(children);
+        } else if size < current {
+            let population = &self.population;
+            let mut worst_first: Vec<_> = (0..current).collect();
+            worst_first.sort_by(|&a, &b| {
+                self.objective
+                    .compare(population[a].fitness(), population[b].fitness())
+            });
+            let mut retiring = vec![false; current];
+            for index in worst_first.into_iter().take(current - size) {
+                retiring[index] = true;
+            }
+            // The survivors keep their order, which matters to a grid
+            let (retired, kept) = core::mem::take(&mut self.population)
+                .into_iter()
+                .zip(retiring)
+                .partition::<Vec<_>, _>(|&(_, retiring)| retiring);
+            self.population = kept.into_iter().map(|(individual, _)| individual).collect();
+            self.update_archive(
+                retired
+                    .into_iter()
+                    .map(|(individual, _)| individual)
+                    .collect(),
+            );
+        }
+        Ok(())
+    }
+
+    /// Breeds the next generation according to the population model, or by
+    /// deterministic crowding if it is enabled.
+    fn breed_generation(&mut self) -> Result<(), EcosystemError> {
+        if let Some(distance) = self.crowding {
+            self.breed_crowded(distance);
+            return Ok(());
+        }
+        match self.model {
+            PopulationModel::Generational if self.generation_gap < 1.0 => {
+                let count = (self.generation_gap * self.offspring_count() as f64).round() as usize;
+                let children = self.select_and_breed(count.max(1))?;
+                self.replace_individuals(children, Replace::Random);
+            }
+            PopulationModel::Generational => {
+                let next_generation = self.select_and_breed(self.offspring_count())?;
+                let objective = self.objective;
+                self.replace_generation(next_generation, |a, b| {
+                    objective.compare(b.fitness(), a.fitness())
+                });
+            }
+            PopulationModel::SteadyState { children, replace } => {
+                let children = self.select_and_breed(children)?;
+                self.replace_individuals(children, replace);
+            }
+            PopulationModel::MuPlusLambda { offspring } => {
+                let offspring = self.select_and_breed(offspring)?;
+                self.select_survivors(offspring, true);
+            }
+            PopulationModel::MuCommaLambda { offspring } => {
+                let offspring = self.select_and_breed(offspring)?;
+                self.select_survivors(offspring, false);
+            }
+            PopulationModel::Cellular {
+                width,
+                neighborhood,
+            } => self.breed_cellular(width, neighborhood),
+        }
+        Ok(())
+    }
+
+    /// Responds to stagnation and records the history, once a generation
+    /// has been bred.
+    fn end_generation(&mut self) {
+        self.archive_survivors();
+        self.respond_to_stagnation();
+        if self.history.is_some() {
+            self.record_history();
+        }
+    }
+
+    /// Breeds new generations until the given termination condition is met,
+    /// returning a summary of the run.
+    ///
+    /// The condition is checked before the first generation is bred, so no
+    /// breeding takes place if it is already met. The ecosystem's
+    /// [observers] are notified as each generation is bred, and may also end
+    /// the run, and its [progress reporter], if any, is told how the run is
+    /// going after each generation.
+    ///
+    /// [observers]: observer/index.html
+    /// [progress reporter]: progress/index.html
+    ///
+    /// # Errors
+    ///
+    /// An error is returned, ending the run early, if any generation fails to
+    /// breed. No generation is bred if settings lost in a [checkpoint] have
+    /// not been restored.
+    ///
+    /// [checkpoint]: #method.save_checkpoint
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ecosystem::{
+    ///     termination::{MaxGenerations, StopReason, TargetFitness},
+    ///     Ecosystem, Organism,
+    /// };
+    /// # use rand::Rng;
+    /// # struct Guess(f64);
+    /// # impl Organism for Guess {
+    /// #     type Fitness = f64;
+    /// #     fn fitness(&self) -> f64 { 1.0 / (42.0 - self.0).abs() }
+    /// #     fn breed(&self, other: &Self, rng: &mut impl Rng) -> Self { Guess((self.0 + other.0) / 2.0) }
+    /// #     fn mutate(&mut self, rate: f64, rng: &mut impl Rng) {
+    /// #         self.0 += rng.gen_range(-rate, rate);
+    /// #     }
+    /// # }
+    ///
+    /// let guesses = (0..20).map(|i| Guess(f64::from(i) * 5.0 + 0.5)).collect();
+    /// let mut ecosystem = Ecosystem::new(guesses);
+    ///
+    /// // An unreachable target, so the run is bounded by the generation limit
+    /// let summary = ecosystem.run(TargetFitness(f64::INFINITY).or(MaxGenerations(25)))?;
+    ///
+    /// assert_eq!(summary.reason, StopReason::MaxGenerations);
+    /// assert_eq!(summary.generations, 25);
+    /// assert_eq!(ecosystem.generation(), 25);
+    /// # Ok::<(), ecosystem::EcosystemError>(())
+    /// ```
+    pub fn run<T: Termination<Self>>(
+        &mut self,
+        mut termination: T,
+    ) -> Result<RunSummary, EcosystemError> {
+        self.check_restored()?;
+        let (start_generation, start_evaluations) = (self.generation, self.evaluations());
+        // The reporter is taken out of the ecosystem for the run, so that it
+        // can be told about the ecosystem while it is borrowed
+        let mut reporter = self.progress.take();
+        let tracker = Tracker::start(self.generation, termination.progress(self));
+        if let Some(reporter) = &mut reporter {
+            reporter.start(&self.progress_of(&tracker, &termination));
+        }
+        let result = self.run_until(&mut termination, |ecosystem, termination| {
+            if let Some(reporter) = &mut reporter {
+                reporter.update(&ecosystem.progress_of(&tracker, termination));
+            }
+        });
+        let _ = self.notify(|observer, ecosystem| {
+            observer.on_run_end(ecosystem);
+            ControlFlow::Continue(())
+        });
+        let summary = result.map(|reason| RunSummary {
+            reason,
+            generations: self.generation - start_generation,
+            evaluations: self.evaluations() - start_evaluations,
+        });
+        if let Some(mut reporter) = reporter {
+            if let Ok(summary) = &summary {
+                reporter.finish(summary);
+            }
+            self.progress = Some(reporter);
+        }
+        summary
+    }
+
+    /// Describes the progress of a run towards the given termination
+    /// condition.
+    fn progress_of<T: Termination<Self>>(
+        &self,
+        tracker: &Tracker,
+        termination: &T,
+    ) -> progress::Progress<O::Fitness> {
+        tracker.progress(
+            self.generation,
+            self.fittest_individual().map(Individual::fitness),
+            termination.progress(self),
+        )
+    }
+
+    /// Breeds new generations until the termination condition or an
+    /// observer ends the run, calling `bred` after each generation.
+    fn run_until<T: Termination<Self>>(
+        &mut self,
+        termination: &mut T,
+        mut bred: impl FnMut(&Self, &T),
+    ) -> Result<StopReason, EcosystemError> {
+        let mut best = None;
+        let reason = loop {
+            if let Some(reason) = termination.check(self) {
+                break reason;
+            }
+            if let Some(monitor) = &mut self.stagnation {
+                if core::mem::take(&mut monitor.stalled) {
+                    break StopReason::Stagnation;
+                }
+            }
+            if self.observers.is_empty() {
+                self.breed_next_generation()?;
+                bred(self, termination);
+                continue;
+            }
+
+            if best.is_none() {
+                best = self.fittest_individual().map(Individual::fitness);
+            }
+            if self
+                .notify(|observer, ecosystem| observer.on_generation_start(ecosystem))
+                .is_break()
+            {
+                break StopReason::Observer;
+            }
+            self.breed_next_generation()?;
+            bred(self, termination);
+
+            let mut flow = self.notify(|observer, ecosystem| observer.on_generation_end(ecosystem));
+            let fitness = self.fittest_individual().map(Individual::fitness);
+            let improved = match (fitness, best) {
+                (Some(fitness), Some(best)) => {
+                    self.objective.compare(fitness, best) == Ordering::Greater
+                }
+                (fitness, _) => fitness.is_some(),
+            };
+            if improved {
+                best = fitness;
+                if self
+                    .notify(|observer, ecosystem| observer.on_new_best(ecosystem))
+                    .is_break()
+                {
+                    flow = ControlFlow::Break(());
+                }
+            }
+            if flow.is_break() {
+                break StopReason::Observer;
+            }
+        };
+        Ok(reason)
+    }
+
+    /// Creates the next generation of organisms using tournament selection
+    /// driven by the given comparator, rather than by fitness values.
+    ///
+    /// Each parent is the winner of a tournament between `tournament_size`
+    /// randomly chosen organisms.
+    ///
+    /// The rest of the generation goes as in [`breed_next_generation`]: the
+    /// mutation controller, immigrants, stagnation response, archive and
+    /// history all take effect if enabled. These rank organisms by their
+    /// fitness values, so they are best left disabled unless the fitness
+    /// agrees with the comparator.
+    ///
+    /// [`breed_next_generation`]: #method.breed_next_generation
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ecosystem::{Ecosystem, Organism};
+    /// # use rand::Rng;
+    /// # struct Guess(f64);
+    /// # impl Organism for Guess {
+    /// #     type Fitness = f64;
+    /// #     fn fitness(&self) -> f64 {
+    /// #         unreachable!("evolution is driven by the comparator")
+    /// #     }
+    /// #     fn breed(&self, other: &Self, rng: &mut impl Rng) -> Self {
+    /// #         Guess((self.0 + other.0) / 2.0)
+    /// #     }
+    /// #     fn mutate(&mut self, rate: f64, rng: &mut impl Rng) {
+    /// #         self.0 += rng.gen_range(-rate, rate);
+    /// #     }
+    /// # }
+    ///
+    /// // Prefers whichever guess is closer to 42
+    /// let closer = |a: &Guess, b: &Guess| {
+    ///     let error = |guess: &Guess| (guess.0 - 42.0).abs();
+    ///     error(b).partial_cmp(&error(a)).unwrap()
+    /// };
+    ///
+    /// let mut ecosystem = Ecosystem::builder()
+    ///     .organisms((0..100).map(|i| Guess(f64::from(i))))
+    ///     .mutation_rate(1.0)
+    ///     .build()
+    ///     .unwrap();
+    /// for _ in 0..30 {
+    ///     ecosystem.breed_next_generation_by(&closer, 3)?;
+    /// }
+    ///
+    /// assert!((ecosystem.fittest_by(&closer).unwrap().0 - 42.0).abs() < 5.0);
+    /// # Ok::<(), ecosystem::EcosystemError>(())
+    /// ```
+    ///
+    /// Immigrants and history work as they do with fitness-driven breeding:
+    ///
+    /// ```rust
+    /// use ecosystem::{Ecosystem, Organism};
+    /// use rand::Rng;
+    /// # struct Guess(f64);
+    /// # impl Organism for Guess {
+    /// #     type Fitness = f64;
+    /// #     fn fitness(&self) -> f64 { -(self.0 - 42.0).abs() }
+    /// #     fn breed(&self, other: &Self, rng: &mut impl Rng) -> Self {
+    /// #         Guess((self.0 + other.0) / 2.0)
+    /// #     }
+    /// #     fn mutate(&mut self, rate: f64, rng: &mut impl Rng) {}
+    /// # }
+    ///
+    /// let closer = |a: &Guess, b: &Guess| a.fitness().partial_cmp(&b.fitness()).unwrap();
+    ///
+    /// // Every guess starts out far from the answer, and none are mutated
+    /// let mut ecosystem = Ecosystem::builder()
+    ///     .organisms((0..20).map(|i| Guess(f64::from(i) + 500.5)))
+    ///     .immigrants(0.1, |rng| Guess(rng.gen_range(0.0, 1000.0)))
+    ///     .record_history()
+    ///     .build()
+    ///     .unwrap();
+    /// for _ in 0..10 {
+    ///     ecosystem.breed_next_generation_by(&closer, 3)?;
+    /// }
+    ///
+    /// assert!(ecosystem.organisms().any(|guess| guess.0 < 500.0));
+    /// assert_eq!(ecosystem.history().unwrap().records().len(), 11);
+    /// # Ok::<(), ecosystem::EcosystemError>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the ecosystem contains no organisms, if
+    /// `tournament_size` is zero, or if settings lost in a [checkpoint] have
+    /// not been restored.
+    ///
+    /// [checkpoint]: #method.save_checkpoint
+    pub fn breed_next_generation_by<C: Comparator<O> + Sync>(
+        &mut self,
+        comparator: &C,
+        tournament_size: usize,
+    ) -> Result<(), EcosystemError> {
+        if self.population.is_empty() {
+            return Err(EcosystemError::EmptyPopulation);
+        }
+        if tournament_size == 0 {
+            return Err(EcosystemError::InvalidTournamentSize);
+        }
+        self.check_restored()?;
+
+        let _span = trace::generation(self.generation);
+        let clock = Clock::starting_at(Duration::ZERO);
+        let parallelism = self.parallelism.clone();
+        parallelism.install(|| {
+            self.begin_generation();
+            let count = self.offspring_count();
+            let parents: Vec<_> = {
+                let _span = trace::select(parent_count(count));
+                (0..parent_count(count))
+                    .map(|_| {
+                        Self::select_tournament_winner(
+                            &self.population,
+                            comparator,
+                            tournament_size,
+                            &mut self.rng,
+                        )
+                    })
+                    .collect()
+            };
+            let next_generation = self.breed_offspring(&parents, count);
+
+            self.replace_generation(next_generation, |a, b| {
+                comparator.compare(b.organism(), a.organism())
+            });
+            self.admit_immigrants();
+            self.end_generation();
+        });
+        trace::bred(self.generation, self.evaluations(), clock.elapsed());
+        Ok(())
+    }
+
+    /// Delivers an event to every observer, returning `ControlFlow::Break` if
+    /// any of them requested that the run stop.
+    fn notify(
+        &mut self,
+        event: impl Fn(&mut dyn Observer<O>, &Self) -> ControlFlow<()>,
+    ) -> ControlFlow<()> {
+        let mut observers = core::mem::take(&mut self.observers);
+        let mut flow = ControlFlow::Continue(());
+        for observer in &mut observers {
+            if event(observer.as_mut(), self).is_break() {
+                flow = ControlFlow::Break(());
+            }
+        }
+        self.observers = observers;
+        flow
+    }
+
+    /// Marks a setting lost in a checkpoint as restored.
+    fn restored(&mut self, setting: Setting) {
+        self.unrestored.retain(|&unrestored| unrestored != setting);
+    }
+
+    /// Returns an error if any setting lost in a checkpoint has been neither
+    /// restored nor discarded.
+    fn check_restored(&self) -> Result<(), EcosystemError> {
+        if self.unrestored.is_empty() {
+            Ok(())
+        } else {
+            Err(EcosystemError::UnrestoredSettings(self.unrestored.clone()))
+        }
+    }
+
+    /// Records the statistics of the current generation in the history.
+    fn record_history(&mut self) {
+        if let Some(statistics) = self.statistics() {
+            let (generation, objective) = (self.generation, self.objective);
+            if let Some(history) = &mut self.history {
+                history.record(generation, statistics, objective);
+            }
+        }
+    }
+
+    /// Evaluates the fitness of an individual if it has not been evaluated
+    /// already, keeping count of the number of evaluations performed.
+    fn evaluate(&self, individual: &Individual<O>) -> O::Fitness {
+        *individual
+            .fitness
+            .get_or_init(|| self.evaluate_organism(individual.organism()))
+    }
+
+    /// Evaluates the fitness of an organism, with the batch evaluator if one
+    /// is set, keeping count of the evaluation.
+    fn evaluate_organism(&self, organism: &O) -> O::Fitness {
+        self.evaluations.fetch_add(1, atomic::Ordering::Relaxed);
+        match &self.batch_evaluator {
+            Some(evaluator) => evaluate_batch(evaluator.as_ref(), &[organism])[0],
+            None => organism.fitness(),
+        }
+    }
+
+    /// Evaluates every one of the given individuals that has not been
+    /// evaluated already: together, if a batch evaluator is set, and in
+    /// parallel otherwise.
+    fn evaluate_all(&self, individuals: &[Individual<O>]) {
+        let _span = trace::evaluate(individuals.len());
+        let evaluator = match &self.batch_evaluator {
+            Some(evaluator) => evaluator,
+            None => {
+                self.parallelism.install(|| {
+                    individuals
+                        .par_iter()
+                        .with_min_len(self.parallelism.min_len())
+                        .for_each(|individual| {
+                            self.evaluate(individual);
+                        })
+                });
+                return;
+            }
+        };
+        let pending: Vec<_> = individuals
+            .iter()
+            .filter(|individual| individual.fitness.get().is_none())
+            .collect();
+        if pending.is_empty() {
+            return;
+        }
+        let organisms: Vec<_> = pending
+            .iter()
+            .map(|individual| individual.organism())
+            .collect();
+        let fitnesses = evaluate_batch(evaluator.as_ref(), &organisms);
+        self.evaluations
+            .fetch_add(pending.len() as u64, atomic::Ordering::Relaxed);
+        for (individual, fitness) in pending.into_iter().zip(fitnesses) {
+            let _ = individual.fitness.set(fitness);
+        }
+    }
+
+    /// Breeds `count` children from parents chosen by the selection strategy.
+    /// If a surrogate is set, more candidates are bred, and the children are
+    /// those that it estimates to be fittest.
+    fn select_and_breed(&mut self, count: usize) -> Result<Vec<Individual<O>>, EcosystemError> {
+        let candidates = match &self.surrogate {
+            Some((_, candidates)) => count * candidates,
+            None => count,
+        };
+        let parents = {
+            let _span = trace::select(parent_count(candidates));
+            self.selection.select(
+                &self.population,
+                self.objective,
+                parent_count(candidates),
+                &mut self.rng,
+            )?
+        };
+        let children = self.breed_children(&parents, candidates);
+        let children = self.screen(children, count);
+        Ok(self.record_births(children))
+    }
+
+    /// Breeds a generation by deterministic crowding: the population is
+    /// paired off at random, each pair breeds two children, and each child
+    /// takes the place of the parent more similar to it (matching the
+    /// children to the parents so as to minimise the total distance) if it
+    /// is fitter.
+    fn breed_crowded(&mut self, distance: fn(&O, &O) -> f64) {
+        let mut parents: Vec<_> = (0..self.population.len()).collect();
+        parents.shuffle(&mut self.rng);
+        // With an odd population, the last organism sits the generation out
+        parents.truncate(parents.len() / 2 * 2);
+        let children = self.breed_children(&parents, parents.len());
+        let children = self.record_births(children);
+        self.evaluate_all(&children);
+
+        self.pass_generation();
+        let objective = self.objective;
+        let mut retired = Vec::new();
+        let mut children = children.into_iter();
+        for pair in parents.chunks_exact(2) {
+            let (Some(first), Some(second)) = (children.next(), children.next()) else {
+                break;
+            };
+            let population = &self.population;
+            let apart = |parent: usize, child: &Individual<O>| {
+                distance(population[parent].organism(), child.organism())
+            };
+            let (a, b) = (pair[0], pair[1]);
+            let matches =
+                if apart(a, &first) + apart(b, &second) <= apart(a, &second) + apart(b, &first) {
+                    [(a, first), (b, second)]
+                } else {
+                    [(a, second), (b, first)]
+                };
+            for (parent, child) in matches {
+                if objective.compare(child.fitness(), self.population[parent].fitness())
+                    == Ordering::Greater
+                {
+                    retired.push(core::mem::replace(&mut self.population[parent], child));
+                } else {
+                    retired.push(child);
+                }
+            }
+        }
+
+        self.update_archive(retired);
+        self.generation += 1;
+    }
+
+    /// Breeds a child for every cell of a grid-shaped population, from
+    /// parents found nearby, with each child taking its cell if it is at
+    /// least as fit as the current occupant. Every individual must already
+    /// have been evaluated.
+    fn breed_cellular(&mut self, width: usize, neighborhood: Neighborhood) {
+        let height = self.population.len() / width;
+        let offsets = neighborhood.offsets();
+        let seeds: Vec<u64> = (0..self.population.len()).map(|_| self.rng.gen()).collect();
+        let (population, objective) = (&self.population, self.objective);
+
+        let children: Vec<_> = seeds
+            .into_par_iter()
+            .with_min_len(self.parallelism.min_len())
+            .enumerate()
+            .map(|(cell, seed)| {
+                let mut rng = Pcg64::seed_from_u64(seed);
+                let (x, y) = ((cell % width) as isize, (cell / width) as isize);
+                let neighbors: Vec<_> = offsets
+                    .iter()
+                    .map(|&(dx, dy)| {
+                        let x = (x + dx).rem_euclid(width as isize) as usize;
+                        let y = (y + dy).rem_euclid(height as isize) as usize;
+                        y * width + x
+                    })
+                    .collect();
+
+                // Each parent is the winner of a binary tournament between
+                // neighbors
+                let select = |rng: &mut Pcg64| {
+                    let a = *neighbors.choose(rng).unwrap();
+                    let b = *neighbors.choose(rng).unwrap();
+                    if objective.compare(population[a].fitness(), population[b].fitness())
+                        == Ordering::Less
+                    {
+                        b
+                    } else {
+                        a
+                    }
+                };
+                let (mother, father) = (select(&mut rng), select(&mut rng));
+                let (child, recombined) = self.reproduce(
+                    population[mother].organism(),
+                    population[father].organism(),
+                    &mut rng,
+                );
+                let parents = if recombined {
+                    vec![mother, father]
+                } else {
+                    vec![mother]
+                };
+                (child, parents)
+            })
+            .collect();
+        let children = self.replace_duplicates(children);
+        let children = self.record_births(children);
+        self.evaluate_all(&children);
+
+        self.pass_generation();
+        let retired = children
+            .into_iter()
+            .enumerate()
+            .map(|(cell, child)| {
+                let occupant = &mut self.population[cell];
+                if objective.compare(child.fitness(), occupant.fitness()) == Ordering::Less {
+                    child
+                } else {
+                    core::mem::replace(occupant, child)
+                }
+            })
+            .collect();
+
+        self.update_archive(retired);
+        self.generation += 1;
+    }
+
+    /// Breeds `count` children, two from each pair of parent indices, of
+    /// which there must be at least [`parent_count`]`(count)`.
+    ///
+    /// A seed for each pair is drawn from the ecosystem's generator before
+    /// breeding begins, so the children do not depend on the order in which
+    /// they are bred.
+    fn breed_offspring(&mut self, parents: &[usize], count: usize) -> Vec<Individual<O>> {
+        let children = self.breed_children(parents, count);
+        self.record_births(children)
+    }
+
+    /// Breeds `count` children as in [`breed_offspring`], each paired with
+    /// the indices of its parents, without recording their births.
+    ///
+    /// [`breed_offspring`]: #method.breed_offspring
+    fn breed_children(
+        &mut self,
+        parents: &[usize],
+        count: usize,
+    ) -> Vec<(Individual<O>, Vec<usize>)> {
+        let _span = trace::breed(count);
+        let pair_count = count.div_ceil(2);
+        let seeds: Vec<u64> = (0..pair_count).map(|_| self.rng.gen()).collect();
+        let population = &self.population;
+
+        let pairs: Vec<_> = parents[..2 * pair_count]
+            .par_chunks(2)
+            .with_min_len(self.parallelism.min_len().div_ceil(2))
+            .zip(seeds)
+            .map(|(pair, seed)| {
+                let mut rng = Pcg64::seed_from_u64(seed);
+                let (mother, father) = (pair[0], pair[1]);
+                let (first, second, parents) = match self.crossover {
+                    Some((rate, clone)) if !rng.gen_bool(rate) => (
+                        clone(population[mother].organism()),
+                        clone(population[father].organism()),
+                        [vec![mother], vec![father]],
+                    ),
+                    _ => {
+                        let (first, second) = population[mother]
+                            .organism()
+                            .breed_pair(population[father].organism(), &mut rng);
+                        (first, second, [vec![mother, father], vec![mother, father]])
+                    }
+                };
+                let [first_parents, second_parents] = parents;
+                [
+                    (self.finish_child(first, &mut rng), first_parents),
+                    (self.finish_child(second, &mut rng), second_parents),
+                ]
+            })
+            .collect();
+        let children = pairs.into_iter().flatten().take(count).collect();
+        self.replace_duplicates(children)
+    }
+
+    /// Keeps the `count` children that the surrogate estimates to be
+    /// fittest, in the order they were bred, or all of them if no surrogate
+    /// is set.
+    fn screen(
+        &self,
+        children: Vec<(Individual<O>, Vec<usize>)>,
+        count: usize,
+    ) -> Vec<(Individual<O>, Vec<usize>)> {
+        let surrogate = match &self.surrogate {
+            Some((surrogate, _)) if children.len() > count => surrogate,
+            _ => return children,
+        };
+        let estimates: Vec<f64> = children
+            .par_iter()
+            .map(|(child, _)| surrogate.estimate(child.organism()))
+            .collect();
+        let mut kept: Vec<_> = (0..children.len()).collect();
+        kept.sort_by(|&a, &b| self.objective.compare(estimates[b], estimates[a]));
+        kept.truncate(count);
+        kept.sort_unstable();
+
+        let mut children: Vec<_> = children.into_iter().map(Some).collect();
+        kept.into_iter()
+            .map(|index| children[index].take().unwrap())
+            .collect()
+    }
+
+    /// Shows the surrogate, if one is set, the true fitness of every
+    /// individual bred (or added) since the last generation.
+    fn teach_surrogate(&mut self) {
+        if let Some((mut surrogate, candidates)) = self.surrogate.take() {
+            for individual in self
+                .population
+                .iter()
+                .filter(|individual| individual.age == 0)
+            {
+                surrogate.learn(individual.organism(), self.evaluate(individual).to_f64());
+            }
+            self.surrogate = Some((surrogate, candidates));
+        }
+    }
+
+    /// Mutates any of the given children that duplicate an organism in the
+    /// population or an earlier child, if deduplication is enabled, until
+    /// they are distinct (or the attempts run out).
+    fn replace_duplicates(
+        &mut self,
+        children: Vec<(Individual<O>, Vec<usize>)>,
+    ) -> Vec<(Individual<O>, Vec<usize>)> {
+        let key = match &self.duplicate_key {
+            Some(key) => key,
+            None => return children,
+        };
+        let mutation_rate = self.effective_mutation_rate();
+        let (rng, repair) = (&mut self.rng, self.repair);
+        let (variance_scaling, gene_variances) = (self.variance_scaling, &self.gene_variances);
+        let population = &self.population;
+
+        // The organisms seen so far, by the hash of their keys: indices below
+        // the population size are in the population, and the rest are kept
+        // children
+        let mut seen: BTreeMap<u64, Vec<usize>> = BTreeMap::new();
+        for (index, individual) in population.iter().enumerate() {
+            seen.entry(key.hash(individual.organism()))
+                .or_default()
+                .push(index);
+        }
+
+        let mut kept: Vec<(Individual<O>, Vec<usize>)> = Vec::with_capacity(children.len());
+        for (mut child, parents) in children {
+            let is_duplicate = |organism: &O, hash: u64| {
+                seen.get(&hash).is_some_and(|indices| {
+                    indices.iter().any(|&index| {
+                        let other = match population.get(index) {
+                            Some(individual) => individual.organism(),
+                            None => kept[index - population.len()].0.organism(),
+                        };
+                        key.same(organism, other)
+                    })
+                })
+            };
+
+            let mut hash = key.hash(child.organism());
+            if is_duplicate(child.organism(), hash) {
+                let mut organism = child.into_organism();
+                for _ in 0..DUPLICATE_MUTATION_ATTEMPTS {
+                    mutate(
+                        &mut organism,
+                        mutation_rate,
+                        variance_scaling,
+                        gene_variances,
+                        rng,
+                    );
+                    if let Some(repair) = repair {
+                        repair(&mut organism);
+                    }
+                    hash = key.hash(&organism);
+                    if !is_duplicate(&organism, hash) {
+                        break;
+                    }
+                }
+                child = Individual::new(organism);
+            }
+            seen.entry(hash)
+                .or_default()
+                .push(population.len() + kept.len());
+            kept.push((child, parents));
+        }
+        kept
+    }
+
+    /// Records the births of the given children, each paired with the
+    /// indices of its parents, if genealogy tracking is enabled.
+    fn record_births(&mut self, children: Vec<(Individual<O>, Vec<usize>)>) -> Vec<Individual<O>> {
+        let generation = self.generation + 1;
+        let population = &self.population;
+        match &mut self.genealogy {
+            Some(genealogy) => children
+                .into_iter()
+                .map(|(child, parents)| {
+                    let parents = parents
+                        .iter()
+                        .map(|&parent| population[parent].id())
+                        .collect();
+                    genealogy.record(child.id(), parents, generation);
+                    child
+                })
+                .collect(),
+            None => children.into_iter().map(|(child, _)| child).collect(),
+        }
+    }
+
+    /// Produces a single child from two parents: recombining them (or, if
+    /// crossover is skipped, cloning the mother), then mutating, repairing
+    /// and improving the result. Also returns whether the parents were
+    /// recombined.
+    fn reproduce(&self, mother: &O, father: &O, rng: &mut Pcg64) -> (Individual<O>, bool) {
+        let (child, recombined) = match self.crossover {
+            Some((rate, clone)) if !rng.gen_bool(rate) => (clone(mother), false),
+            _ => (mother.breed(father, rng), true),
+        };
+        (self.finish_child(child, rng), recombined)
+    }
+
+    /// Mutates a newly bred child (by variance-scaled mutation, if enabled),
+    /// then repairs it and improves it by local
+    /// search, if enabled. Under Baldwinian learning, a copy of the child is
+    /// improved instead, and only its fitness is kept.
+    fn finish_child(&self, mut child: O, rng: &mut Pcg64) -> Individual<O> {
+        mutate(
+            &mut child,
+            self.effective_mutation_rate(),
+            self.variance_scaling,
+            &self.gene_variances,
+            rng,
+        );
+        if let Some(repair) = self.repair {
+            repair(&mut child);
+        }
+        let local_search = match &self.local_search {
+            Some(local_search) => local_search,
+            None => return Individual::new(child),
+        };
+        match self.baldwinian {
+            None => {
+                let evaluations = local_search.improve(&mut child, self.objective, rng);
+                self.evaluations
+                    .fetch_add(evaluations, atomic::Ordering::Relaxed);
+                Individual::new(child)
+            }
+            Some(clone) => {
+                let mut learner = clone(&child);
+                let evaluations = local_search.improve(&mut learner, self.objective, rng);
+                self.evaluations
+                    .fetch_add(evaluations, atomic::Ordering::Relaxed);
+                let fitness = self.evaluate_organism(&learner);
+                Individual::learned(child, fitness)
+            }
+        }
+    }
+
+    /// Returns the number of children bred for each new generation, which
+    /// accounts for the elite organisms that are carried over.
+    fn offspring_count(&self) -> usize {
+        self.population.len() - self.elitism.min(self.population.len())
+    }
+
+    /// Replaces the current organisms with the next generation, carrying
+    /// over the elite organisms as determined by the given ordering (which
+    /// places fitter individuals first).
+    fn replace_generation(
+        &mut self,
+        offspring: Vec<Individual<O>>,
+        fitter_first: impl Fn(&Individual<O>, &Individual<O>) -> Ordering,
+    ) {
+        self.pass_generation();
+        let mut elites = core::mem::take(&mut self.population);
+        let elite_count = self.elitism.min(elites.len());
+        if elite_count > 0 && elite_count < elites.len() {
+            elites.select_nth_unstable_by(elite_count - 1, &fitter_first);
+        }
+        let retired = elites.split_off(elite_count);
+
+        self.population = elites;
+        self.population.extend(offspring);
+        self.update_archive(retired);
+        self.generation += 1;
+    }
+
+    /// Replaces existing individuals with the given children, as in a
+    /// steady-state population, according to the ecosystem's replacement
+    /// strategy if it has one, or the given policy otherwise. Every
+    /// individual must already have been evaluated.
+    fn replace_individuals(&mut self, children: Vec<Individual<O>>, replace: Replace) {
+        self.pass_generation();
+        self.evaluate_all(&children);
+        let population = &self.population;
+        let mut worst_first: Vec<_> = (0..population.len()).collect();
+        worst_first.sort_by(|&a, &b| {
+            self.objective
+                .compare(population[a].fitness(), population[b].fitness())
+        });
+        worst_first.truncate(population.len() - self.elitism.min(population.len()));
+
+        let replacement: &dyn Replacement<O> = match &self.replacement {
+            Some(replacement) => replacement.as_ref(),
+            None => &replace,
+        };
+        let retired = replacement.replace(
+            &mut self.population,
+            &worst_first,
+            children,
+            self.objective,
+            &mut self.rng,
+        );
+
+        self.update_archive(retired);
+        self.generation += 1;
+    }
+
+    /// Forms the next generation from the fittest of the given children and
+    /// either all of the current individuals (when `keep_parents` is true) or
+    /// just the elites, keeping the population size the same.
+    fn select_survivors(&mut self, offspring: Vec<Individual<O>>, keep_parents: bool) {
+        let size = self.population.len();
+        let objective = self.objective;
+        let fitter_first =
+            |a: &Individual<O>, b: &Individual<O>| objective.compare(b.fitness(), a.fitness());
+
+        self.pass_generation();
+        let mut candidates = core::mem::take(&mut self.population);
+        let mut retired = Vec::new();
+        if !keep_parents {
+            let elite_count = self.elitism.min(candidates.len());
+            if elite_count > 0 && elite_count < candidates.len() {
+                candidates.select_nth_unstable_by(elite_count - 1, fitter_first);
+            }
+            retired = candidates.split_off(elite_count);
+        }
+
+        candidates.extend(offspring);
+        self.evaluate_all(&candidates);
+        candidates.sort_by(fitter_first);
+        retired.extend(candidates.split_off(size.min(candidates.len())));
+
+        self.population = candidates;
+        self.update_archive(retired);
+        self.generation += 1;
+    }
+
+    /// Replaces the least fit individuals, other than the elites, with newly
+    /// generated immigrants, if immigration is enabled.
+    fn admit_immigrants(&mut self) {
+        if let Some((fraction, generate)) = self.immigration.take() {
+            let count = (fraction * self.population.len() as f64).round() as usize;
+            self.replace_least_fit(count, &generate);
+            self.immigration = Some((fraction, generate));
+        }
+    }
+
+    /// Replaces up to `count` of the least fit individuals, other than the
+    /// elites, with organisms created by `generate`. The replacements take
+    /// the places of the individuals they replace.
+    pub(crate) fn replace_least_fit(&mut self, count: usize, generate: &Generator<O>) {
+        let size = self.population.len();
+        let count = count.min(size - self.elitism.min(size));
+        let rng = &mut self.rng;
+        let newcomers = (0..count).map(|_| generate(rng)).collect();
+        self.replace_least_fit_with(newcomers);
+    }
+
+    /// Replaces the least fit individuals, other than the elites, with the
+    /// given organisms, returning how many were admitted. Organisms beyond
+    /// the number of individuals that may be replaced are dropped.
+    fn replace_least_fit_with(&mut self, mut organisms: Vec<O>) -> usize {
+        let size = self.population.len();
+        organisms.truncate(size - self.elitism.min(size));
+        if organisms.is_empty() {
+            return 0;
+        }
+
+        self.evaluate_all(&self.population);
+        let population = &self.population;
+        let mut worst_first: Vec<_> = (0..size).collect();
+        worst_first.sort_by(|&a, &b| {
+            self.objective
+                .compare(population[a].fitness(), population[b].fitness())
+        });
+
+        let newcomers: Vec<_> = organisms.into_iter().map(Individual::new).collect();
+        let count = newcomers.len();
+        if let Some(genealogy) = &mut self.genealogy {
+            for newcomer in &newcomers {
+                genealogy.record(newcomer.id(), Vec::new(), self.generation);
+            }
+        }
+        let retired = worst_first
+            .into_iter()
+            .zip(newcomers)
+            .map(|(victim, newcomer)| core::mem::replace(&mut self.population[victim], newcomer))
+            .collect();
+        self.update_archive(retired);
+        count
+    }
+
+    /// Marks the passing of a generation, once its children have been bred:
+    /// every current individual ages by one, as they survive into the next
+    /// generation (unless they are then replaced), and any hypermutation
+    /// counts down.
+    pub(crate) fn pass_generation(&mut self) {
+        for individual in &mut self.population {
+            individual.age += 1;
+        }
+        if let Some(hypermutation) = &mut self.hypermutation {
+            hypermutation.generations = hypermutation.generations.saturating_sub(1);
+            if hypermutation.generations == 0 {
+                self.hypermutation = None;
+            }
+        }
+    }
+
+    /// Returns the rate at which children are currently mutated, taking
+    /// any hypermutation into account.
+    fn effective_mutation_rate(&self) -> f64 {
+        match self.hypermutation {
+            Some(hypermutation) => (self.mutation_rate * hypermutation.factor).clamp(0.0, 1.0),
+            None => self.mutation_rate,
+        }
+    }
+
+    /// Returns the individuals ordered from fittest to least fit, evaluating
+    /// any that have not yet been evaluated.
+    pub(crate) fn ranked_individuals(&self) -> Vec<&Individual<O>> {
+        self.evaluate_all(&self.population);
+        let mut ranked: Vec<_> = self.population.iter().collect();
+        ranked.sort_by(|a, b| self.objective.compare(b.fitness(), a.fitness()));
+        ranked
+    }
+
+    /// Replaces the least fit individuals with the given ones, keeping the
+    /// population size the same.
+    #[cfg(feature = "std")]
+    pub(crate) fn replace_worst(&mut self, individuals: Vec<Individual<O>>) {
+        self.evaluate_all(&self.population);
+        let objective = self.objective;
+        self.population
+            .sort_by(|a, b| objective.compare(b.fitness(), a.fitness()));
+
+        let count = individuals.len().min(self.population.len());
+        let retired = self.population.split_off(self.population.len() - count);
+        self.population.extend(individuals.into_iter().take(count));
+        self.update_archive(retired);
+    }
+
+    /// Merges copies of the fittest survivors into the archive, so that it
+    /// holds the best organisms found so far, whether or not they are still
+    /// alive.
+    fn archive_survivors(&mut self) {
+        let Some((copy, _)) = self.archive_ops else {
+            return;
+        };
+        let survivors = self
+            .ranked_individuals()
+            .into_iter()
+            .take(self.archive_capacity)
+            .map(copy)
+            .collect();
+        self.update_archive(survivors);
+    }
+
+    /// Merges the given individuals into the archive, keeping only the
+    /// fittest distinct individuals up to the archive's capacity.
+    fn update_archive(&mut self, candidates: Vec<Individual<O>>) {
+        let Some((_, same)) = self.archive_ops else {
+            return;
+        };
+        if self.archive_capacity == 0 {
+            return;
+        }
+
+        self.evaluate_all(&candidates);
+
+        // The sort is stable, so an archived organism keeps its place ahead
+        // of an equally fit newcomer
+        let mut ranked = core::mem::take(&mut self.archive);
+        ranked.extend(candidates);
+        ranked.sort_by(|a, b| self.objective.compare(b.fitness(), a.fitness()));
+        for individual in ranked {
+            if self.archive.len() == self.archive_capacity {
+                break;
+            }
+            if !self
+                .archive
+                .iter()
+                .any(|archived| same(archived.organism(), individual.organism()))
+            {
+                self.archive.push(individual);
+            }
+        }
+    }
+
+    /// Selects the fittest of `size` randomly chosen individuals, according to
+    /// the given comparator, returning its index. The population and `size`
+    /// must both be non-empty.
+    fn select_tournament_winner<C: Comparator<O>>(
+        population: &[Individual<O>],
+        comparator: &C,
+        size: usize,
+        rng: &mut Pcg64,
+    ) -> usize {
+        (0..size)
+            .map(|_| rng.gen_range(0, population.len()))
+            .max_by(|&a, &b| comparator.compare(population[a].organism(), population[b].organism()))
+            .unwrap()
+    }
+}
+
+impl<O: Organism + Clone + Send + Sync> Ecosystem<O> {
+    /// Sets the probability that each child is bred by recombining its
+    /// parents, as with [`EcosystemBuilder::crossover_rate`]. The rate is
+    /// clamped to [0, 1].
+    ///
+    /// [`EcosystemBuilder::crossover_rate`]: struct.EcosystemBuilder.html#method.crossover_rate
+    pub fn set_crossover_rate(&mut self, rate: f64) {
+        self.crossover = Some((rate.clamp(0.0, 1.0), O::clone));
+        self.restored(Setting::CrossoverRate);
+    }
+
+    /// Sets whether children keep the improvements made by local search, as
+    /// with [`EcosystemBuilder::learning`].
+    ///
+    /// [`EcosystemBuilder::learning`]: struct.EcosystemBuilder.html#method.learning
+    pub fn set_learning(&mut self, learning: Learning) {
+        self.baldwinian = learning.clone_fn();
+        self.restored(Setting::Learning);
+    }
+}
+
+#[cfg(feature = "std")]
+impl<O: Organism + genome::RealGenome + Send + Sync> Ecosystem<O> {
+    /// Has every child mutated by variance-scaled mutation, as with
+    /// [`EcosystemBuilder::variance_scaled_mutation`]. This is useful for
+    /// restoring the setting after resuming from a checkpoint.
+    ///
+    /// [`EcosystemBuilder::variance_scaled_mutation`]: struct.EcosystemBuilder.html#method.variance_scaled_mutation
+    pub fn enable_variance_scaled_mutation(&mut self, scale: f64) {
+        self.variance_scaling = Some(genome::variance_scaling(scale));
+        self.restored(Setting::VarianceScaledMutation);
+    }
+}
+
+impl<O: Organism + Distance + Send + Sync> Ecosystem<O> {
+    /// Has each generation bred by deterministic crowding, as with
+    /// [`EcosystemBuilder::deterministic_crowding`]. This is useful for
+    /// restoring the setting after resuming from a checkpoint.
+    ///
+    /// [`EcosystemBuilder::deterministic_crowding`]: struct.EcosystemBuilder.html#method.deterministic_crowding
+    pub fn enable_deterministic_crowding(&mut self) {
+        self.crowding = Some(O::distance);
+        self.restored(Setting::DeterministicCrowding);
+    }
+}
+
+impl<O: Organism + Clone + PartialEq + Send + Sync> Ecosystem<O> {
+    /// Keeps an archive of up to `capacity` of the fittest distinct
+    /// organisms, as with [`EcosystemBuilder::archive`], keeping the
+    /// organisms already archived. This is useful for restoring the setting
+    /// after resuming from a checkpoint.
+    ///
+    /// [`EcosystemBuilder::archive`]: struct.EcosystemBuilder.html#method.archive
+    pub fn set_archive_capacity(&mut self, capacity: usize) {
+        self.archive_capacity = capacity;
+        self.archive_ops = Some((Individual::clone, O::eq));
+        self.archive.truncate(capacity);
+        self.restored(Setting::Archive);
+    }
+}
+
+impl<O: Organism + Hash + Eq + Send + Sync + 'static> Ecosystem<O> {
+    /// Has children that duplicate another organism mutated until they are
+    /// distinct, as with [`EcosystemBuilder::deduplicate`]. This is useful
+    /// for restoring the setting after resuming from a checkpoint.
+    ///
+    /// [`EcosystemBuilder::deduplicate`]: struct.EcosystemBuilder.html#method.deduplicate
+    pub fn enable_deduplication(&mut self) {
+        self.duplicate_key = Some(Box::new(Equality));
+        self.restored(Setting::Deduplication);
     }
 }
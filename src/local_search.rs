@@ -0,0 +1,262 @@
+//! Local search, which improves each child after it is bred, turning an
+//! ecosystem into a memetic algorithm.
+//!
+//! A [`LocalSearch`] registered with [`EcosystemBuilder::local_search`] is
+//! applied to every child once it has been mutated (and repaired, if repair
+//! is enabled), so the population is made up of locally optimal organisms.
+//! The genetic algorithm then only has to find the right region of the
+//! search space, which on combinatorial problems in particular saves far
+//! more generations than the local search costs.
+//!
+//! By default, the improvements are written back into the children's
+//! genomes ([Lamarckian learning]). With [Baldwinian learning], children are
+//! instead left as they were bred, and only the fitness that they reach
+//! through local search is used for selection, which preserves more of the
+//! population's diversity.
+//!
+//! Closures that take the organism, the ecosystem's [`Objective`] and a
+//! random number generator, and return the number of fitness evaluations
+//! that they performed, are local searches too:
+//!
+//! ```rust
+//! use ecosystem::{
+//!     selection::Tournament,
+//!     tsp::{DistanceMatrix, Tour},
+//!     Ecosystem, Objective,
+//! };
+//! use rand::SeedableRng;
+//! use rand_pcg::Pcg64;
+//! use std::{f64::consts::PI, sync::Arc};
+//!
+//! // Fifty cities on a circle, whose shortest tour goes around it
+//! let cities: Vec<_> = (0..50)
+//!     .map(|city| {
+//!         let angle = 2.0 * PI * city as f64 / 50.0;
+//!         (angle.cos(), angle.sin())
+//!     })
+//!     .collect();
+//! let distances = Arc::new(DistanceMatrix::from_coordinates(&cities));
+//! let shortest = 50.0 * distances.distance(0, 1);
+//!
+//! let mut rng = Pcg64::seed_from_u64(0);
+//! let mut ecosystem = Ecosystem::builder()
+//!     .organisms((0..20).map(|_| Tour::random(&distances, &mut rng)))
+//!     .objective(Objective::Minimize)
+//!     .selection(Tournament { size: 3 })
+//!     .mutation_rate(0.3)
+//!     // 2-opt measures the changes it makes itself, without evaluating
+//!     // whole tours
+//!     .local_search(|tour: &mut Tour, _, _: &mut dyn rand::RngCore| {
+//!         tour.two_opt();
+//!         0
+//!     })
+//!     .seed(0)
+//!     .build()
+//!     .unwrap();
+//! ecosystem.breed_next_generation()?;
+//!
+//! // Every child of the first generation is free of crossings
+//! assert!(ecosystem
+//!     .individuals()
+//!     .iter()
+//!     .all(|individual| (individual.organism().length() - shortest).abs() < 1e-9));
+//! # Ok::<(), ecosystem::EcosystemError>(())
+//! ```
+//!
+//! [Lamarckian learning]: enum.Learning.html#variant.Lamarckian
+//! [Baldwinian learning]: enum.Learning.html#variant.Baldwinian
+//! [`LocalSearch`]: trait.LocalSearch.html
+//! [`EcosystemBuilder::local_search`]: ../struct.EcosystemBuilder.html#method.local_search
+//! [`Objective`]: ../enum.Objective.html
+use crate::{Objective, Organism};
+use core::cmp::Ordering;
+use rand::RngCore;
+
+/// An interface for improving newly bred children.
+pub trait LocalSearch<O: Organism>: Send + Sync {
+    /// Improves the given organism in place, with respect to the given
+    /// objective, returning the number of fitness evaluations performed,
+    /// which are added to the ecosystem's count.
+    fn improve(&self, organism: &mut O, objective: Objective, rng: &mut dyn RngCore) -> u64;
+}
+
+impl<O, F> LocalSearch<O> for F
+where
+    O: Organism,
+    F: Fn(&mut O, Objective, &mut dyn RngCore) -> u64 + Send + Sync,
+{
+    fn improve(&self, organism: &mut O, objective: Objective, rng: &mut dyn RngCore) -> u64 {
+        self(organism, objective, rng)
+    }
+}
+
+/// Whether children keep the improvements that local search makes to them.
+///
+/// # Examples
+///
+/// ```rust
+/// use ecosystem::{local_search::Learning, Ecosystem, Organism};
+/// use rand::{Rng, RngCore};
+/// # #[derive(Clone)]
+/// # struct Guess(f64);
+/// # impl Organism for Guess {
+/// #     type Fitness = f64;
+/// #     fn fitness(&self) -> f64 { 1.0 / (42.0 - self.0).abs() }
+/// #     fn breed(&self, other: &Self, rng: &mut impl Rng) -> Self { Guess((self.0 + other.0) / 2.0) }
+/// #     fn mutate(&mut self, rate: f64, rng: &mut impl Rng) {
+/// #         self.0 += rng.gen_range(-rate, rate);
+/// #     }
+/// # }
+///
+/// let mut ecosystem = Ecosystem::builder()
+///     .organisms((0..20).map(|i| Guess(f64::from(i) * 5.0 + 0.5)))
+///     // Moves each guess a quarter of the way towards 42
+///     .local_search(|guess: &mut Guess, _, _: &mut dyn RngCore| {
+///         guess.0 += (42.0 - guess.0) / 4.0;
+///         0
+///     })
+///     .learning(Learning::Baldwinian)
+///     .build()
+///     .unwrap();
+/// ecosystem.breed_next_generation()?;
+///
+/// // Each child is selected by the fitness of its improved copy, but keeps
+/// // its own genome
+/// for individual in ecosystem.individuals() {
+///     let guess = individual.organism().0;
+///     let learned = Guess(guess + (42.0 - guess) / 4.0);
+///     assert_eq!(individual.fitness(), learned.fitness());
+///     assert_eq!(individual.raw_fitness(), individual.organism().fitness());
+/// }
+/// // The improved copies are evaluated, along with the parents
+/// assert_eq!(ecosystem.evaluations(), 20 + 20);
+/// # Ok::<(), ecosystem::EcosystemError>(())
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Learning {
+    /// Children are replaced by their improved versions, so what they learn
+    /// is inherited by their own children. This is the default.
+    #[default]
+    Lamarckian,
+    /// Children are left as they were bred, but take the fitness of their
+    /// improved versions. What they learn only guides selection, towards
+    /// organisms that local search can improve the most.
+    ///
+    /// The fitness of each child's own genome remains available as the
+    /// individual's [raw fitness]. The improved versions are evaluated like
+    /// any other organism, by the [batch evaluator] if one is set.
+    ///
+    /// [raw fitness]: ../struct.Individual.html#method.raw_fitness
+    /// [batch evaluator]: ../evaluation/trait.BatchEvaluator.html
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ecosystem::{local_search::Learning, Ecosystem, Organism};
+    /// use rand::{Rng, RngCore};
+    /// # #[derive(Clone)]
+    /// # struct Guess(f64);
+    /// # impl Organism for Guess {
+    /// #     type Fitness = f64;
+    /// #     fn fitness(&self) -> f64 { unreachable!("guesses are evaluated in batches") }
+    /// #     fn breed(&self, other: &Self, rng: &mut impl Rng) -> Self { Guess((self.0 + other.0) / 2.0) }
+    /// #     fn mutate(&mut self, rate: f64, rng: &mut impl Rng) {
+    /// #         self.0 += rng.gen_range(-rate, rate);
+    /// #     }
+    /// # }
+    ///
+    /// let mut ecosystem = Ecosystem::builder()
+    ///     .organisms((0..20).map(|i| Guess(f64::from(i) * 5.0 + 0.5)))
+    ///     .batch_evaluator(|guesses: &[&Guess]| {
+    ///         guesses.iter().map(|guess| 1.0 / (42.0 - guess.0).abs()).collect()
+    ///     })
+    ///     .local_search(|guess: &mut Guess, _, _: &mut dyn RngCore| {
+    ///         guess.0 += (42.0 - guess.0) / 4.0;
+    ///         0
+    ///     })
+    ///     .learning(Learning::Baldwinian)
+    ///     .build()
+    ///     .unwrap();
+    /// ecosystem.breed_next_generation()?;
+    ///
+    /// // The improved copies were evaluated in batches and counted
+    /// for individual in ecosystem.individuals() {
+    ///     let guess = individual.organism().0;
+    ///     let learned = 1.0 / (0.75 * (42.0 - guess).abs());
+    ///     assert!((individual.fitness() / learned - 1.0).abs() < 1e-9);
+    /// }
+    /// assert_eq!(ecosystem.evaluations(), 20 + 20);
+    /// # Ok::<(), ecosystem::EcosystemError>(())
+    /// ```
+    Baldwinian,
+}
+
+impl Learning {
+    /// Returns the function used to copy a child for local search under
+    /// this kind of learning, or `None` if the child itself is improved.
+    pub(crate) fn clone_fn<O: Clone>(self) -> Option<fn(&O) -> O> {
+        match self {
+            Learning::Lamarckian => None,
+            Learning::Baldwinian => Some(O::clone),
+        }
+    }
+}
+
+/// Hill climbing, which makes `steps` attempts to improve an organism, each
+/// mutating a copy of it at the given `rate` and keeping the copy if it is
+/// at least as fit.
+///
+/// Each attempt takes a fitness evaluation, as does the organism itself
+/// beforehand.
+///
+/// # Examples
+///
+/// ```rust
+/// use ecosystem::{local_search::HillClimbing, Ecosystem, Organism};
+/// use rand::Rng;
+/// # #[derive(Clone)]
+/// # struct Guess(f64);
+/// # impl Organism for Guess {
+/// #     type Fitness = f64;
+/// #     fn fitness(&self) -> f64 { 1.0 / (42.0 - self.0).abs() }
+/// #     fn breed(&self, other: &Self, rng: &mut impl Rng) -> Self { Guess((self.0 + other.0) / 2.0) }
+/// #     fn mutate(&mut self, rate: f64, rng: &mut impl Rng) {
+/// #         self.0 += rng.gen_range(-rate, rate);
+/// #     }
+/// # }
+///
+/// let mut ecosystem = Ecosystem::builder()
+///     .organisms((0..20).map(|i| Guess(f64::from(i) * 5.0 + 0.5)))
+///     .local_search(HillClimbing { steps: 5, rate: 1.0 })
+///     .build()
+///     .unwrap();
+/// ecosystem.breed_next_generation()?;
+///
+/// // The parents, then each child and its five neighbours, are evaluated
+/// assert_eq!(ecosystem.evaluations(), 20 + 20 * 6);
+/// # Ok::<(), ecosystem::EcosystemError>(())
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct HillClimbing {
+    /// The number of attempts to improve each organism.
+    pub steps: usize,
+    /// The rate at which the copies are mutated.
+    pub rate: f64,
+}
+
+impl<O: Organism + Clone> LocalSearch<O> for HillClimbing {
+    fn improve(&self, organism: &mut O, objective: Objective, mut rng: &mut dyn RngCore) -> u64 {
+        let mut fitness = organism.fitness();
+        for _ in 0..self.steps {
+            let mut neighbour = organism.clone();
+            neighbour.mutate(self.rate, &mut rng);
+            let neighbour_fitness = neighbour.fitness();
+            if objective.compare(neighbour_fitness, fitness) != Ordering::Less {
+                *organism = neighbour;
+                fitness = neighbour_fitness;
+            }
+        }
+        self.steps as u64 + 1
+    }
+}
@@ -0,0 +1,255 @@
+use crate::parallel::*;
+use crate::{novelty::Behavior, EcosystemError, Individual, Objective, Organism};
+use rand::{Rng, SeedableRng};
+use rand_pcg::Pcg64;
+use std::cmp::Ordering;
+
+/// A MAP-Elites container, which searches for the fittest organism with
+/// each kind of behavior, rather than for a single fittest organism.
+///
+/// The space of behaviors (as given by each organism's [`Behavior`]) is
+/// divided into a grid of bins, with `resolution` bins along each dimension.
+/// Each bin holds the fittest organism found so far whose behavior falls
+/// within it (the bin's *elite*). New organisms are bred from randomly
+/// chosen elites, and take the place of the elite in their own bin if they
+/// are fitter, or fill it if it is empty. The result is a map of high-quality
+/// solutions covering the whole range of behaviors.
+///
+/// Behaviors outside of the given bounds are placed in the nearest bin.
+///
+/// [`Behavior`]: novelty/trait.Behavior.html
+///
+/// # Examples
+///
+/// ```rust
+/// use ecosystem::{novelty::Behavior, MapElites, Organism};
+/// # use rand::Rng;
+/// # struct Guess(f64);
+/// # impl Organism for Guess {
+/// #     type Fitness = f64;
+/// #     fn fitness(&self) -> f64 { 1.0 / (42.0 - self.0).abs() }
+/// #     fn breed(&self, other: &Self, rng: &mut impl Rng) -> Self {
+/// #         Guess(if rng.gen() { self.0 } else { other.0 })
+/// #     }
+/// #     fn mutate(&mut self, rate: f64, rng: &mut impl Rng) {
+/// #         self.0 += rng.gen_range(-rate, rate);
+/// #     }
+/// # }
+///
+/// impl Behavior for Guess {
+///     fn behavior(&self) -> Vec<f64> {
+///         vec![self.0]
+///     }
+/// }
+///
+/// // Ten bins, each covering a range of ten guesses
+/// let mut map = MapElites::new(vec![(0.0, 100.0)], 10).mutation_rate(20.0).seed(0);
+/// map.insert(Guess(50.5));
+/// for _ in 0..20 {
+///     map.breed(50)?;
+/// }
+///
+/// assert_eq!(map.coverage(), 1.0);
+/// assert!((map.fittest().unwrap().0 - 42.0).abs() < 1.0);
+/// // Every bin holds its own best guess
+/// assert!(map.get(&[0]).unwrap().0 < 10.0);
+/// # Ok::<(), ecosystem::EcosystemError>(())
+/// ```
+pub struct MapElites<O: Organism> {
+    bins: Vec<Option<Individual<O>>>,
+    bounds: Vec<(f64, f64)>,
+    resolution: usize,
+    mutation_rate: f64,
+    objective: Objective,
+    rng: Pcg64,
+    evaluations: u64,
+}
+
+impl<O: Organism + Behavior + Send + Sync> MapElites<O> {
+    /// Creates an empty container whose behaviors lie within the given
+    /// (lower, upper) bounds in each dimension, each of which is divided into
+    /// `resolution` bins.
+    ///
+    /// Children are mutated at a rate of [`DEFAULT_MUTATION_RATE`], fitness
+    /// values are maximised, and the random number generator is seeded from
+    /// the operating system.
+    ///
+    /// [`DEFAULT_MUTATION_RATE`]: constant.DEFAULT_MUTATION_RATE.html
+    ///
+    /// # Panics
+    ///
+    /// Panics if `resolution` is zero.
+    pub fn new(bounds: Vec<(f64, f64)>, resolution: usize) -> Self {
+        assert!(resolution > 0, "the resolution must be non-zero");
+        let bin_count = (0..bounds.len()).fold(1, |count, _| count * resolution);
+        Self {
+            bins: (0..bin_count).map(|_| None).collect(),
+            bounds,
+            resolution,
+            mutation_rate: crate::DEFAULT_MUTATION_RATE,
+            objective: Objective::default(),
+            rng: Pcg64::from_entropy(),
+            evaluations: 0,
+        }
+    }
+
+    /// Sets the rate at which children are mutated.
+    pub fn mutation_rate(mut self, rate: f64) -> Self {
+        self.mutation_rate = rate;
+        self
+    }
+
+    /// Sets the direction in which fitness values are optimised.
+    pub fn objective(mut self, objective: Objective) -> Self {
+        self.objective = objective;
+        self
+    }
+
+    /// Seeds the random number generator, making breeding reproducible.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.rng = Pcg64::seed_from_u64(seed);
+        self
+    }
+
+    /// Returns the number of bins that hold an elite.
+    pub fn len(&self) -> usize {
+        self.bins.iter().filter(|bin| bin.is_some()).count()
+    }
+
+    /// Returns whether every bin is empty.
+    pub fn is_empty(&self) -> bool {
+        self.bins.iter().all(Option::is_none)
+    }
+
+    /// Returns the fraction of the bins that hold an elite.
+    pub fn coverage(&self) -> f64 {
+        self.len() as f64 / self.bins.len() as f64
+    }
+
+    /// Returns the number of organisms whose fitness has been evaluated.
+    pub fn evaluations(&self) -> u64 {
+        self.evaluations
+    }
+
+    /// Returns the elite in the bin at the given coordinates (one per
+    /// dimension), if there is one.
+    pub fn get(&self, coordinates: &[usize]) -> Option<&O> {
+        if coordinates.len() != self.bounds.len()
+            || coordinates
+                .iter()
+                .any(|&coordinate| coordinate >= self.resolution)
+        {
+            return None;
+        }
+        let index = coordinates
+            .iter()
+            .fold(0, |index, &coordinate| index * self.resolution + coordinate);
+        self.bins[index].as_ref().map(Individual::organism)
+    }
+
+    /// Returns every elite, along with the coordinates of its bin.
+    pub fn elites(&self) -> impl Iterator<Item = (Vec<usize>, &Individual<O>)> {
+        self.bins
+            .iter()
+            .enumerate()
+            .filter_map(move |(index, bin)| {
+                let mut coordinates = vec![0; self.bounds.len()];
+                let mut remainder = index;
+                for coordinate in coordinates.iter_mut().rev() {
+                    *coordinate = remainder % self.resolution;
+                    remainder /= self.resolution;
+                }
+                bin.as_ref().map(|individual| (coordinates, individual))
+            })
+    }
+
+    /// Returns the fittest elite, or `None` if the container is empty.
+    pub fn fittest(&self) -> Option<&O> {
+        self.bins
+            .iter()
+            .flatten()
+            .max_by(|a, b| self.objective.compare(a.fitness(), b.fitness()))
+            .map(Individual::organism)
+    }
+
+    /// Offers an organism to the container, returning whether it became the
+    /// elite of its bin.
+    pub fn insert(&mut self, organism: O) -> bool {
+        let behavior = organism.behavior();
+        let individual = Individual::new(organism);
+        individual.evaluate();
+        self.evaluations += 1;
+        self.place(individual, &behavior)
+    }
+
+    /// Breeds `count` children from randomly chosen elites, offering each
+    /// to the container in turn.
+    ///
+    /// Children are bred and evaluated in parallel, with each given its own
+    /// random number generator seeded from the container's, so the result is
+    /// reproducible.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the container is empty, as there is nothing
+    /// to breed from.
+    pub fn breed(&mut self, count: usize) -> Result<(), EcosystemError> {
+        let (bins, rng) = (&self.bins, &mut self.rng);
+        let occupied: Vec<_> = bins.iter().flatten().map(Individual::organism).collect();
+        if occupied.is_empty() {
+            return Err(EcosystemError::EmptyPopulation);
+        }
+
+        let parents: Vec<_> = (0..count)
+            .map(|_| {
+                let mother = rng.gen_range(0, occupied.len());
+                let father = rng.gen_range(0, occupied.len());
+                (mother, father, rng.gen::<u64>())
+            })
+            .collect();
+        let mutation_rate = self.mutation_rate;
+        let children: Vec<_> = parents
+            .into_par_iter()
+            .map(|(mother, father, seed)| {
+                let mut rng = Pcg64::seed_from_u64(seed);
+                let mut child = occupied[mother].breed(occupied[father], &mut rng);
+                child.mutate(mutation_rate, &mut rng);
+
+                let behavior = child.behavior();
+                let individual = Individual::new(child);
+                individual.evaluate();
+                (individual, behavior)
+            })
+            .collect();
+
+        self.evaluations += children.len() as u64;
+        for (individual, behavior) in children {
+            self.place(individual, &behavior);
+        }
+        Ok(())
+    }
+
+    /// Places an evaluated individual in its bin if it is fitter than the
+    /// current elite, returning whether it was placed.
+    fn place(&mut self, individual: Individual<O>, behavior: &[f64]) -> bool {
+        let index = self
+            .bounds
+            .iter()
+            .zip(behavior)
+            .fold(0, |index, (&(lower, upper), &value)| {
+                let position = (value - lower) / (upper - lower) * self.resolution as f64;
+                let coordinate = (position.max(0.0) as usize).min(self.resolution - 1);
+                index * self.resolution + coordinate
+            });
+
+        let objective = self.objective;
+        let bin = &mut self.bins[index];
+        let fitter = bin.as_ref().is_none_or(|elite| {
+            objective.compare(individual.fitness(), elite.fitness()) == Ordering::Greater
+        });
+        if fitter {
+            *bin = Some(individual);
+        }
+        fitter
+    }
+}
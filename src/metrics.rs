@@ -0,0 +1,121 @@
+//! Exporting the state of a run through the [metrics] facade.
+//!
+//! The [`Metrics`] observer records each generation as it is bred, so that
+//! an ecosystem running as part of a long-lived service can be monitored
+//! with whichever metrics recorder the service installs, such as
+//! [metrics-exporter-prometheus] for scraping by Prometheus. It records:
+//!
+//! | Metric | Type | |
+//! |---|---|---|
+//! | `ecosystem_generation` | gauge | The current generation number |
+//! | `ecosystem_best_fitness` | gauge | The best fitness of the current generation |
+//! | `ecosystem_population_size` | gauge | The number of organisms |
+//! | `ecosystem_evaluations_total` | counter | The fitness evaluations performed |
+//! | `ecosystem_generation_duration_seconds` | histogram | The time taken to breed each generation |
+//!
+//! Without a recorder installed, recording does nothing.
+//!
+//! ```rust
+//! use ecosystem::{metrics::Metrics, termination::MaxGenerations, Ecosystem, Organism};
+//! # use rand::Rng;
+//! # struct Guess(f64);
+//! # impl Organism for Guess {
+//! #     type Fitness = f64;
+//! #     fn fitness(&self) -> f64 { 1.0 / (42.0 - self.0).abs() }
+//! #     fn breed(&self, other: &Self, rng: &mut impl Rng) -> Self { Guess((self.0 + other.0) / 2.0) }
+//! #     fn mutate(&mut self, rate: f64, rng: &mut impl Rng) {
+//! #         self.0 += rng.gen_range(-rate, rate);
+//! #     }
+//! # }
+//!
+//! // metrics_exporter_prometheus::PrometheusBuilder::new().install()?;
+//! let mut ecosystem = Ecosystem::builder()
+//!     .organisms((0..20).map(|i| Guess(f64::from(i) * 5.0 + 0.5)))
+//!     .observer(Metrics::new().label("problem", "guess"))
+//!     .build()
+//!     .unwrap();
+//! ecosystem.run(MaxGenerations(50))?;
+//! # Ok::<(), ecosystem::EcosystemError>(())
+//! ```
+//!
+//! [metrics]: https://docs.rs/metrics
+//! [`Metrics`]: struct.Metrics.html
+//! [metrics-exporter-prometheus]: https://docs.rs/metrics-exporter-prometheus
+use crate::{history::Clock, observer::Observer, Ecosystem, Fitness, Individual, Organism};
+use core::{ops::ControlFlow, time::Duration};
+use metrics::{Label, SharedString, Unit};
+
+const GENERATION: &str = "ecosystem_generation";
+const BEST_FITNESS: &str = "ecosystem_best_fitness";
+const POPULATION_SIZE: &str = "ecosystem_population_size";
+const EVALUATIONS: &str = "ecosystem_evaluations_total";
+const GENERATION_DURATION: &str = "ecosystem_generation_duration_seconds";
+
+/// An [observer] that records the state of an ecosystem with the installed
+/// metrics recorder after each generation is bred.
+///
+/// [observer]: ../observer/index.html
+#[derive(Default)]
+pub struct Metrics {
+    labels: Vec<Label>,
+    described: bool,
+    clock: Option<Clock>,
+}
+
+impl Metrics {
+    /// Creates an observer that records metrics without labels.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a label to every metric recorded, such as to tell apart several
+    /// ecosystems running in the same process.
+    pub fn label(mut self, key: impl Into<SharedString>, value: impl Into<SharedString>) -> Self {
+        self.labels.push(Label::new(key, value));
+        self
+    }
+
+    /// Describes the metrics to the recorder, which must be done once it has
+    /// been installed.
+    fn describe(&mut self) {
+        if self.described {
+            return;
+        }
+        metrics::describe_gauge!(GENERATION, "The current generation number");
+        metrics::describe_gauge!(BEST_FITNESS, "The best fitness of the current generation");
+        metrics::describe_gauge!(POPULATION_SIZE, Unit::Count, "The number of organisms");
+        metrics::describe_counter!(
+            EVALUATIONS,
+            Unit::Count,
+            "The fitness evaluations performed"
+        );
+        metrics::describe_histogram!(
+            GENERATION_DURATION,
+            Unit::Seconds,
+            "The time taken to breed each generation"
+        );
+        self.described = true;
+    }
+}
+
+impl<O: Organism + Send + Sync> Observer<O> for Metrics {
+    fn on_generation_start(&mut self, _ecosystem: &Ecosystem<O>) -> ControlFlow<()> {
+        self.describe();
+        self.clock = Some(Clock::starting_at(Duration::ZERO));
+        ControlFlow::Continue(())
+    }
+
+    fn on_generation_end(&mut self, ecosystem: &Ecosystem<O>) -> ControlFlow<()> {
+        let labels = self.labels.iter();
+        metrics::gauge!(GENERATION, labels.clone()).set(ecosystem.generation());
+        if let Some(best) = ecosystem.fittest_individual().map(Individual::fitness) {
+            metrics::gauge!(BEST_FITNESS, labels.clone()).set(best.to_f64());
+        }
+        metrics::gauge!(POPULATION_SIZE, labels.clone()).set(ecosystem.individuals().len() as f64);
+        metrics::counter!(EVALUATIONS, labels.clone()).absolute(ecosystem.evaluations());
+        if let Some(clock) = self.clock.take() {
+            metrics::histogram!(GENERATION_DURATION, labels).record(clock.elapsed());
+        }
+        ControlFlow::Continue(())
+    }
+}
@@ -0,0 +1,200 @@
+use alloc::vec::Vec;
+
+/// How each new generation is formed from the previous one.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PopulationModel {
+    /// Every organism (other than the elites) is replaced by a child in each
+    /// generation. This is the default.
+    #[default]
+    Generational,
+    /// Only a few children are bred in each step, each taking the place of
+    /// an existing organism. The rest of the population carries over
+    /// unchanged, so good organisms can breed as soon as they appear.
+    SteadyState {
+        /// The number of children bred in each step.
+        children: usize,
+        /// Which organisms the children replace, unless a replacement
+        /// strategy is set with [`EcosystemBuilder::replacement`].
+        ///
+        /// [`EcosystemBuilder::replacement`]: struct.EcosystemBuilder.html#method.replacement
+        replace: Replace,
+    },
+    /// The (μ+λ) scheme of evolution strategies, in which `offspring` (λ)
+    /// children are bred from the population of μ parents, and the fittest μ
+    /// of the parents and children together survive.
+    ///
+    /// As parents survive for as long as they are among the fittest, this
+    /// scheme is inherently elitist.
+    MuPlusLambda {
+        /// The number of children (λ) bred in each generation.
+        offspring: usize,
+    },
+    /// The (μ,λ) scheme of evolution strategies, in which `offspring` (λ)
+    /// children are bred from the population of μ parents, and the fittest μ
+    /// children survive (alongside any elites).
+    ///
+    /// As parents (other than any elites) are discarded, the population can
+    /// escape local optima more easily than under (μ+λ). The number of
+    /// children and elites together must be at least the population size.
+    MuCommaLambda {
+        /// The number of children (λ) bred in each generation.
+        offspring: usize,
+    },
+    /// A cellular model, in which the organisms live on a grid and only breed
+    /// with their neighbors.
+    ///
+    /// The population fills the grid row by row, and the grid wraps around
+    /// at its edges. In each generation, a child is bred for every cell from
+    /// two parents, each the winner of a binary tournament within the cell's
+    /// neighborhood. The child takes the cell if it is at least as fit as the
+    /// organism already there. Good solutions therefore spread only gradually
+    /// across the grid, which preserves diversity for far longer than
+    /// breeding across the whole population.
+    ///
+    /// The ecosystem's selection strategy and elitism are not used, and the
+    /// population size must be a multiple of the grid's width.
+    Cellular {
+        /// The number of cells in each row of the grid.
+        width: usize,
+        /// The cells considered to be neighbors of each cell.
+        neighborhood: Neighborhood,
+    },
+}
+
+impl PopulationModel {
+    /// Returns the population size nearest to `size` that the model can
+    /// breed from with the given number of elites.
+    pub(crate) fn valid_population_size(self, size: usize, elitism: usize) -> usize {
+        let min = elitism + 1;
+        match self {
+            PopulationModel::Generational | PopulationModel::MuPlusLambda { .. } => size.max(min),
+            PopulationModel::SteadyState { children, .. } => size.max(min.max(elitism + children)),
+            PopulationModel::MuCommaLambda { offspring } => size.clamp(min, elitism + offspring),
+            PopulationModel::Cellular { width, .. } => {
+                let rows = ((size + width / 2) / width).max(min.div_ceil(width));
+                rows * width
+            }
+        }
+    }
+}
+
+/// The organisms that are replaced by children in a steady-state population.
+///
+/// # Examples
+///
+/// ```rust
+/// use ecosystem::{Ecosystem, Organism, PopulationModel, Replace};
+/// # use rand::Rng;
+/// # struct Guess(f64);
+/// # impl Organism for Guess {
+/// #     type Fitness = f64;
+/// #     fn fitness(&self) -> f64 { 1.0 / (42.0 - self.0).abs() }
+/// #     fn breed(&self, other: &Self, rng: &mut impl Rng) -> Self { Guess((self.0 + other.0) / 2.0) }
+/// #     fn mutate(&mut self, rate: f64, rng: &mut impl Rng) {
+/// #         self.0 += rng.gen_range(-rate, rate);
+/// #     }
+/// # }
+///
+/// let mut ecosystem = Ecosystem::builder()
+///     .organisms((0..10).map(|i| Guess(f64::from(i) * 10.0 + 0.5)))
+///     .population_model(PopulationModel::SteadyState {
+///         children: 2,
+///         replace: Replace::Oldest,
+///     })
+///     .build()
+///     .unwrap();
+/// for _ in 0..20 {
+///     ecosystem.breed_next_generation()?;
+/// }
+///
+/// // Two organisms are replaced in each step, so none survives for more
+/// // than five steps
+/// assert!(ecosystem.individuals().iter().all(|individual| individual.age() < 5));
+/// # Ok::<(), ecosystem::EcosystemError>(())
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Replace {
+    /// The least fit organisms are replaced.
+    Worst,
+    /// Randomly chosen organisms are replaced, other than the elites.
+    Random,
+    /// The oldest organisms are replaced, other than the elites, with the
+    /// least fit replaced first among organisms of the same age. This keeps
+    /// any one organism from dominating the population for too long.
+    Oldest,
+}
+
+/// The neighborhood of each cell in a [cellular] population, which always
+/// includes the cell itself.
+///
+/// [cellular]: enum.PopulationModel.html#variant.Cellular
+///
+/// # Examples
+///
+/// ```rust
+/// use ecosystem::{Ecosystem, Neighborhood, Organism, PopulationModel};
+/// # use rand::Rng;
+/// # struct Guess(f64);
+/// # impl Organism for Guess {
+/// #     type Fitness = f64;
+/// #     fn fitness(&self) -> f64 { 1.0 / (42.0 - self.0).abs() }
+/// #     fn breed(&self, other: &Self, rng: &mut impl Rng) -> Self { Guess((self.0 + other.0) / 2.0) }
+/// #     fn mutate(&mut self, rate: f64, rng: &mut impl Rng) {
+/// #         self.0 += rng.gen_range(-rate, rate);
+/// #     }
+/// # }
+///
+/// // A 10x10 grid, in which each organism breeds with its four adjacent
+/// // neighbors
+/// let mut ecosystem = Ecosystem::builder()
+///     .organisms((0..100).map(|i| Guess(f64::from(i) + 0.5)))
+///     .population_model(PopulationModel::Cellular {
+///         width: 10,
+///         neighborhood: Neighborhood::VonNeumann { radius: 1 },
+///     })
+///     .build()
+///     .unwrap();
+/// for _ in 0..10 {
+///     ecosystem.breed_next_generation()?;
+/// }
+///
+/// assert!((ecosystem.fittest().unwrap().0 - 42.0).abs() < 1.0);
+/// # Ok::<(), ecosystem::EcosystemError>(())
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Neighborhood {
+    /// The cells within the given number of steps horizontally or vertically
+    /// (the Manhattan distance). A radius of 1 gives the four adjacent cells.
+    VonNeumann {
+        /// The maximum number of steps away from the cell.
+        radius: usize,
+    },
+    /// The cells within the given number of steps in any direction,
+    /// including diagonally. A radius of 1 gives the eight surrounding cells.
+    Moore {
+        /// The maximum number of steps away from the cell.
+        radius: usize,
+    },
+}
+
+impl Neighborhood {
+    /// Returns the offsets of the neighboring cells, as (x, y) pairs.
+    pub(crate) fn offsets(self) -> Vec<(isize, isize)> {
+        let (radius, within) = match self {
+            Neighborhood::VonNeumann { radius } => (radius as isize, true),
+            Neighborhood::Moore { radius } => (radius as isize, false),
+        };
+        let mut offsets = Vec::new();
+        for dy in -radius..=radius {
+            for dx in -radius..=radius {
+                if !within || dx.abs() + dy.abs() <= radius {
+                    offsets.push((dx, dy));
+                }
+            }
+        }
+        offsets
+    }
+}
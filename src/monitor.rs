@@ -0,0 +1,344 @@
+//! A live terminal monitor for evolutionary runs.
+//!
+//! The [`Monitor`] observer takes over the terminal while an ecosystem runs,
+//! drawing a chart of the best and mean fitness of each generation, the
+//! standard deviation of the population's fitness and the current best
+//! organism, as shown by its `Display` implementation.
+//! Pressing `q` or `Esc` ends the run early, with the reason
+//! [`StopReason::Observer`]; the terminal is restored once the run ends.
+//!
+//! ```rust,no_run
+//! use ecosystem::{monitor::Monitor, termination::MaxGenerations, Ecosystem, Organism};
+//! use std::fmt;
+//! # use rand::Rng;
+//! # struct Guess(f64);
+//! # impl Organism for Guess {
+//! #     type Fitness = f64;
+//! #     fn fitness(&self) -> f64 { 1.0 / (42.0 - self.0).abs() }
+//! #     fn breed(&self, other: &Self, rng: &mut impl Rng) -> Self { Guess((self.0 + other.0) / 2.0) }
+//! #     fn mutate(&mut self, rate: f64, rng: &mut impl Rng) {
+//! #         self.0 += rng.gen_range(-rate, rate);
+//! #     }
+//! # }
+//!
+//! impl fmt::Display for Guess {
+//!     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+//!         write!(f, "a guess of {}", self.0)
+//!     }
+//! }
+//!
+//! let mut ecosystem = Ecosystem::builder()
+//!     .organisms((0..20).map(|i| Guess(f64::from(i) * 5.0 + 0.5)))
+//!     .observer(Monitor::new())
+//!     .build()
+//!     .unwrap();
+//! ecosystem.run(MaxGenerations(10_000))?;
+//! # Ok::<(), ecosystem::EcosystemError>(())
+//! ```
+//!
+//! [`Monitor`]: struct.Monitor.html
+//! [`StopReason::Observer`]: ../termination/enum.StopReason.html#variant.Observer
+use crate::{observer::Observer, Ecosystem, Fitness, Objective, Organism};
+use core::{fmt::Display, ops::ControlFlow, time::Duration};
+use ratatui::{
+    backend::CrosstermBackend,
+    crossterm::{
+        event::{self, Event, KeyCode, KeyEventKind, KeyModifiers},
+        execute,
+        terminal::{self, EnterAlternateScreen, LeaveAlternateScreen},
+    },
+    layout::{Constraint, Layout},
+    style::{Color, Style},
+    symbols::Marker,
+    text::Line,
+    widgets::{Axis, Block, Chart, Dataset, GraphType, Paragraph, Sparkline, Wrap},
+    Frame, Terminal,
+};
+use std::{
+    io::{self, Stdout},
+    time::Instant,
+};
+
+/// An [observer] that shows the progress of a run in the terminal.
+///
+/// [observer]: ../observer/index.html
+pub struct Monitor {
+    terminal: Option<Terminal<CrosstermBackend<Stdout>>>,
+    refresh_interval: Duration,
+    last_drawn: Option<Instant>,
+    started: Option<Instant>,
+    best: Vec<(f64, f64)>,
+    mean: Vec<(f64, f64)>,
+    fitness_std_dev: Vec<f64>,
+}
+
+impl Monitor {
+    /// Creates a monitor, which takes over the terminal once a run begins.
+    pub fn new() -> Self {
+        Self {
+            terminal: None,
+            refresh_interval: Duration::from_millis(50),
+            last_drawn: None,
+            started: None,
+            best: Vec::new(),
+            mean: Vec::new(),
+            fitness_std_dev: Vec::new(),
+        }
+    }
+
+    /// Sets the least time between redraws of the terminal, which is 50
+    /// milliseconds by default. Generations bred in between are still
+    /// recorded, and appear at the next redraw.
+    pub fn refresh_interval(mut self, interval: Duration) -> Self {
+        self.refresh_interval = interval;
+        self
+    }
+
+    /// Switches the terminal to the alternate screen, in raw mode, so that
+    /// it can be drawn on and key presses read.
+    fn enter(&mut self) -> io::Result<()> {
+        terminal::enable_raw_mode()?;
+        execute!(io::stdout(), EnterAlternateScreen)?;
+        self.terminal = Some(Terminal::new(CrosstermBackend::new(io::stdout()))?);
+        Ok(())
+    }
+
+    /// Restores the terminal to how it was before the run, if it was taken
+    /// over.
+    fn leave(&mut self) {
+        if self.terminal.take().is_some() {
+            restore();
+        }
+    }
+
+    /// Records the statistics of the ecosystem's current generation.
+    fn record<O: Organism + Send + Sync>(&mut self, ecosystem: &Ecosystem<O>) {
+        if let Some(statistics) = ecosystem.statistics() {
+            let generation = f64::from(ecosystem.generation());
+            let best = match ecosystem.objective() {
+                Objective::Maximize => statistics.max,
+                Objective::Minimize => statistics.min,
+            };
+            self.best.push((generation, best));
+            self.mean.push((generation, statistics.mean));
+            self.fitness_std_dev.push(statistics.std_dev);
+        }
+    }
+
+    /// Redraws the terminal, unless it was drawn too recently.
+    fn draw<O: Organism + Display + Send + Sync>(&mut self, ecosystem: &Ecosystem<O>) {
+        let now = Instant::now();
+        if self
+            .last_drawn
+            .is_some_and(|drawn| now.duration_since(drawn) < self.refresh_interval)
+        {
+            return;
+        }
+        self.last_drawn = Some(now);
+
+        let Self {
+            terminal,
+            started,
+            best,
+            mean,
+            fitness_std_dev,
+            ..
+        } = self;
+        let elapsed = started.map_or(Duration::ZERO, |started| now.duration_since(started));
+        let fittest = ecosystem.fittest_individual().map(|individual| {
+            (
+                individual.fitness().to_f64(),
+                individual.organism().to_string(),
+            )
+        });
+        let title = format!(
+            " generation {} · {} evaluations · {:.1}s ",
+            ecosystem.generation(),
+            ecosystem.evaluations(),
+            elapsed.as_secs_f64()
+        );
+        if let Some(terminal) = terminal {
+            // A failed redraw is skipped; the next may well succeed
+            let _ = terminal.draw(|frame| {
+                render(frame, &title, best, mean, fitness_std_dev, fittest.as_ref());
+            });
+        }
+    }
+}
+
+impl Default for Monitor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<O: Organism + Display + Send + Sync> Observer<O> for Monitor {
+    fn on_generation_start(&mut self, ecosystem: &Ecosystem<O>) -> ControlFlow<()> {
+        if self.started.is_none() {
+            self.started = Some(Instant::now());
+            if self.enter().is_err() {
+                // Without a terminal to draw on, the run carries on unmonitored
+                self.terminal = None;
+                restore();
+            }
+            if self.best.is_empty() {
+                self.record(ecosystem);
+            }
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn on_generation_end(&mut self, ecosystem: &Ecosystem<O>) -> ControlFlow<()> {
+        self.record(ecosystem);
+        self.draw(ecosystem);
+        if self.terminal.is_some() && quit_requested() {
+            ControlFlow::Break(())
+        } else {
+            ControlFlow::Continue(())
+        }
+    }
+
+    fn on_run_end(&mut self, _ecosystem: &Ecosystem<O>) {
+        self.leave();
+        self.started = None;
+        self.last_drawn = None;
+    }
+}
+
+impl Drop for Monitor {
+    fn drop(&mut self) {
+        self.leave();
+    }
+}
+
+/// Leaves the alternate screen and raw mode.
+fn restore() {
+    // There is nowhere to report a failure to restore the terminal
+    let _ = terminal::disable_raw_mode();
+    let _ = execute!(io::stdout(), LeaveAlternateScreen);
+}
+
+/// Reads the pending key presses, returning whether any of them asked to
+/// end the run.
+fn quit_requested() -> bool {
+    let mut quit = false;
+    while event::poll(Duration::ZERO).unwrap_or(false) {
+        if let Ok(Event::Key(key)) = event::read() {
+            let ctrl_c =
+                key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL);
+            if key.kind == KeyEventKind::Press
+                && (matches!(key.code, KeyCode::Char('q') | KeyCode::Esc) || ctrl_c)
+            {
+                quit = true;
+            }
+        }
+    }
+    quit
+}
+
+/// Draws the monitor: the fitness chart above, and the standard deviation
+/// of the fitness and the best organism below.
+fn render(
+    frame: &mut Frame,
+    title: &str,
+    best: &[(f64, f64)],
+    mean: &[(f64, f64)],
+    fitness_std_dev: &[f64],
+    fittest: Option<&(f64, String)>,
+) {
+    let [chart_area, lower_area, help_area] = Layout::vertical([
+        Constraint::Percentage(65),
+        Constraint::Fill(1),
+        Constraint::Length(1),
+    ])
+    .areas(frame.area());
+    let [std_dev_area, fittest_area] =
+        Layout::horizontal([Constraint::Percentage(40), Constraint::Fill(1)]).areas(lower_area);
+
+    let generations = bounds(best.iter().map(|&(generation, _)| generation));
+    let fitness = bounds(best.iter().chain(mean).map(|&(_, fitness)| fitness));
+    let chart = Chart::new(vec![
+        Dataset::default()
+            .name("best")
+            .marker(Marker::Braille)
+            .graph_type(GraphType::Line)
+            .style(Style::default().fg(Color::Green))
+            .data(best),
+        Dataset::default()
+            .name("mean")
+            .marker(Marker::Braille)
+            .graph_type(GraphType::Line)
+            .style(Style::default().fg(Color::Cyan))
+            .data(mean),
+    ])
+    .block(Block::bordered().title(title))
+    .x_axis(
+        Axis::default()
+            .title("generation")
+            .bounds(generations)
+            .labels([format!("{}", generations[0]), format!("{}", generations[1])]),
+    )
+    .y_axis(
+        Axis::default()
+            .title("fitness")
+            .bounds(fitness)
+            .labels([format!("{:.4}", fitness[0]), format!("{:.4}", fitness[1])]),
+    );
+    frame.render_widget(chart, chart_area);
+
+    // The sparkline shows as many of the latest generations as fit, scaled
+    // to the largest standard deviation among them
+    let shown = &fitness_std_dev[fitness_std_dev
+        .len()
+        .saturating_sub(std_dev_area.width as usize)..];
+    let largest = shown.iter().copied().fold(0.0, f64::max);
+    let scaled: Vec<u64> = shown
+        .iter()
+        .map(|&value| {
+            if largest > 0.0 {
+                (value / largest * 1000.0) as u64
+            } else {
+                0
+            }
+        })
+        .collect();
+    let current = fitness_std_dev.last().copied().unwrap_or(0.0);
+    frame.render_widget(
+        Sparkline::default()
+            .block(Block::bordered().title(format!(" fitness std dev {:.4} ", current)))
+            .style(Style::default().fg(Color::Yellow))
+            .max(1000)
+            .data(&scaled),
+        std_dev_area,
+    );
+
+    let (fittest_title, fittest_text) = match fittest {
+        Some((fitness, organism)) => (format!(" best organism ({}) ", fitness), organism.as_str()),
+        None => (String::from(" best organism "), ""),
+    };
+    frame.render_widget(
+        Paragraph::new(fittest_text)
+            .wrap(Wrap { trim: false })
+            .block(Block::bordered().title(fittest_title)),
+        fittest_area,
+    );
+
+    frame.render_widget(Line::from(" q: end the run"), help_area);
+}
+
+/// Returns the range of the given values, widened if they are all equal so
+/// that the range is never empty.
+fn bounds(values: impl Iterator<Item = f64>) -> [f64; 2] {
+    let (low, high) = values
+        .filter(|value| value.is_finite())
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(low, high), value| {
+            (low.min(value), high.max(value))
+        });
+    if low > high {
+        [0.0, 1.0]
+    } else if low == high {
+        [low - 0.5, high + 0.5]
+    } else {
+        [low, high]
+    }
+}
@@ -0,0 +1,240 @@
+//! Controllers that adjust the mutation rate as a run progresses.
+//!
+//! A fixed mutation rate is a compromise between exploring early on and
+//! fine-tuning later. A [`MutationControl`] registered with
+//! [`EcosystemBuilder::mutation_control`] instead chooses the rate before
+//! each generation is bred by [`Ecosystem::breed_next_generation`]. The rate
+//! can also be raised for a few generations at a time with a burst of
+//! [hypermutation].
+//!
+//! [`MutationControl`]: trait.MutationControl.html
+//! [`EcosystemBuilder::mutation_control`]: ../struct.EcosystemBuilder.html#method.mutation_control
+//! [`Ecosystem::breed_next_generation`]: ../struct.Ecosystem.html#method.breed_next_generation
+//! [hypermutation]: struct.Hypermutation.html
+use crate::{diversity::mean_pairwise_distance, Ecosystem, Individual, Organism};
+use alloc::boxed::Box;
+#[cfg(not(feature = "std"))]
+#[allow(unused_imports)]
+use num_traits::Float;
+
+/// An interface for choosing the mutation rate of each generation.
+pub trait MutationControl<O: Organism>: Send + Sync {
+    /// Returns the mutation rate to breed the next generation with, given the
+    /// ecosystem as it stands (with every organism evaluated). The rate is
+    /// clamped to [0, 1].
+    fn mutation_rate(&mut self, ecosystem: &Ecosystem<O>) -> f64;
+}
+
+/// Adapts the mutation rate to the diversity of the population, raising it
+/// when the population has converged and lowering it when the population is
+/// diverse.
+///
+/// Diversity is measured as the mean `distance` between every pair of
+/// organisms. Before each generation, the current mutation rate is
+/// multiplied by `factor` if the diversity is below the `target`, or divided
+/// by it otherwise, and then kept within [`min_rate`, `max_rate`].
+///
+/// Measuring diversity takes a number of distance calculations that grows
+/// with the square of the population size, though they are spread across
+/// threads. Organisms that implement [`Distance`] can pass `O::distance` as
+/// the distance.
+///
+/// [`Distance`]: ../diversity/trait.Distance.html
+///
+/// # Examples
+///
+/// ```rust
+/// use ecosystem::{mutation::DiversityControl, Ecosystem, Organism};
+/// # use rand::Rng;
+/// # struct Guess(f64);
+/// # impl Organism for Guess {
+/// #     type Fitness = f64;
+/// #     fn fitness(&self) -> f64 { 1.0 / (42.0 - self.0).abs() }
+/// #     fn breed(&self, other: &Self, rng: &mut impl Rng) -> Self { Guess((self.0 + other.0) / 2.0) }
+/// #     fn mutate(&mut self, rate: f64, rng: &mut impl Rng) {
+/// #         self.0 += rng.gen_range(-rate, rate);
+/// #     }
+/// # }
+///
+/// // Every guess starts out the same
+/// let mut ecosystem = Ecosystem::builder()
+///     .organisms((0..20).map(|_| Guess(10.5)))
+///     .mutation_rate(0.01)
+///     .mutation_control(DiversityControl {
+///         distance: |a: &Guess, b: &Guess| (a.0 - b.0).abs(),
+///         target: 1.0,
+///         factor: 2.0,
+///         min_rate: 0.001,
+///         max_rate: 1.0,
+///     })
+///     .build()
+///     .unwrap();
+/// for _ in 0..5 {
+///     ecosystem.breed_next_generation()?;
+/// }
+///
+/// // The lack of diversity has driven the mutation rate up
+/// assert!(ecosystem.mutation_rate() > 0.1);
+/// # Ok::<(), ecosystem::EcosystemError>(())
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct DiversityControl<D> {
+    /// Measures the distance between two organisms.
+    pub distance: D,
+    /// The diversity below which the mutation rate is raised.
+    pub target: f64,
+    /// The factor by which the mutation rate is changed in each generation.
+    pub factor: f64,
+    /// The lowest mutation rate allowed.
+    pub min_rate: f64,
+    /// The highest mutation rate allowed.
+    pub max_rate: f64,
+}
+
+impl<D> DiversityControl<D> {
+    /// Returns the diversity of the given individuals: the mean distance
+    /// between every pair of them, or zero if there are fewer than two.
+    pub fn diversity<O>(&self, individuals: &[Individual<O>]) -> f64
+    where
+        O: Organism + Sync,
+        D: Fn(&O, &O) -> f64 + Sync,
+    {
+        mean_pairwise_distance(individuals, |a, b| {
+            (self.distance)(a.organism(), b.organism())
+        })
+    }
+}
+
+impl<O, D> MutationControl<O> for DiversityControl<D>
+where
+    O: Organism + Send + Sync,
+    D: Fn(&O, &O) -> f64 + Send + Sync,
+{
+    fn mutation_rate(&mut self, ecosystem: &Ecosystem<O>) -> f64 {
+        let rate = if self.diversity(ecosystem.individuals()) < self.target {
+            ecosystem.mutation_rate() * self.factor
+        } else {
+            ecosystem.mutation_rate() / self.factor
+        };
+        rate.max(self.min_rate).min(self.max_rate)
+    }
+}
+
+/// A mutation rate that changes according to the generation number, such as
+/// one that starts high to explore widely and falls to fine-tune the best
+/// solutions.
+///
+/// # Examples
+///
+/// ```rust
+/// use ecosystem::{mutation::Schedule, Ecosystem, Organism};
+/// # use rand::Rng;
+/// # struct Guess(f64);
+/// # impl Organism for Guess {
+/// #     type Fitness = f64;
+/// #     fn fitness(&self) -> f64 { 1.0 / (42.0 - self.0).abs() }
+/// #     fn breed(&self, other: &Self, rng: &mut impl Rng) -> Self { Guess((self.0 + other.0) / 2.0) }
+/// #     fn mutate(&mut self, rate: f64, rng: &mut impl Rng) {
+/// #         self.0 += rng.gen_range(-rate, rate);
+/// #     }
+/// # }
+///
+/// let schedule = Schedule::Linear { start: 0.5, end: 0.1, generations: 10 };
+/// assert_eq!(schedule.rate(0), 0.5);
+/// assert!((schedule.rate(5) - 0.3).abs() < 1e-9);
+/// assert_eq!(schedule.rate(20), 0.1);
+///
+/// let mut ecosystem = Ecosystem::builder()
+///     .organisms((0..20).map(|i| Guess(f64::from(i) * 5.0 + 0.5)))
+///     .mutation_control(schedule)
+///     .build()
+///     .unwrap();
+/// for _ in 0..20 {
+///     ecosystem.breed_next_generation()?;
+/// }
+///
+/// assert_eq!(ecosystem.mutation_rate(), 0.1);
+/// # Ok::<(), ecosystem::EcosystemError>(())
+/// ```
+pub enum Schedule {
+    /// The same rate in every generation.
+    Constant(f64),
+    /// A rate that changes linearly from `start` to `end` over the given
+    /// number of generations, and stays at `end` from then on.
+    Linear {
+        /// The rate in the first generation.
+        start: f64,
+        /// The rate once the given number of generations have been bred.
+        end: f64,
+        /// The number of generations over which the rate changes.
+        generations: u32,
+    },
+    /// A rate that is multiplied by `decay` in each generation.
+    Exponential {
+        /// The rate in the first generation.
+        start: f64,
+        /// The factor by which the rate changes in each generation.
+        decay: f64,
+    },
+    /// A rate that is multiplied by `factor` every `interval` generations.
+    Step {
+        /// The rate in the first generation.
+        start: f64,
+        /// The factor by which the rate changes at each step.
+        factor: f64,
+        /// The number of generations between steps.
+        interval: u32,
+    },
+    /// A rate given by a function of the generation number.
+    Custom(Box<dyn Fn(u32) -> f64 + Send + Sync>),
+}
+
+impl Schedule {
+    /// Returns the mutation rate for the given generation.
+    pub fn rate(&self, generation: u32) -> f64 {
+        match *self {
+            Schedule::Constant(rate) => rate,
+            Schedule::Linear {
+                start,
+                end,
+                generations,
+            } => {
+                let progress = if generations == 0 {
+                    1.0
+                } else {
+                    (f64::from(generation) / f64::from(generations)).min(1.0)
+                };
+                start * (1.0 - progress) + end * progress
+            }
+            Schedule::Exponential { start, decay } => start * decay.powf(f64::from(generation)),
+            Schedule::Step {
+                start,
+                factor,
+                interval,
+            } => match generation.checked_div(interval) {
+                Some(steps) => start * factor.powf(f64::from(steps)),
+                None => start,
+            },
+            Schedule::Custom(ref rate) => rate(generation),
+        }
+    }
+}
+
+impl<O: Organism + Send + Sync> MutationControl<O> for Schedule {
+    fn mutation_rate(&mut self, ecosystem: &Ecosystem<O>) -> f64 {
+        self.rate(ecosystem.generation())
+    }
+}
+
+/// A temporary increase in the mutation rate, started with
+/// [`Ecosystem::hypermutate`].
+///
+/// [`Ecosystem::hypermutate`]: ../struct.Ecosystem.html#method.hypermutate
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Hypermutation {
+    /// The factor by which the mutation rate is multiplied.
+    pub factor: f64,
+    /// The number of generations for which the increase lasts.
+    pub generations: u32,
+}
@@ -0,0 +1,808 @@
+//! NeuroEvolution of Augmenting Topologies (NEAT), which evolves the
+//! structure of neural networks alongside their weights.
+//!
+//! Every network starts out minimal, with its inputs (and a bias) connected
+//! directly to its outputs, and grows through structural mutations that add
+//! connections and split existing connections with new hidden nodes. Each
+//! new structure is given an innovation number by the population's
+//! [`Innovations`], so the same structure arising in two networks is marked
+//! the same way. Crossover lines the parents' connection genes up by these
+//! historical markings, and the same markings measure how far apart two
+//! networks are, which divides the population into species. Organisms share
+//! fitness with the rest of their species, which protects new structures
+//! while their weights are tuned.
+//!
+//! [`Neat`] drives the whole process, given the number of inputs and outputs
+//! and a fitness function for [`Genome`]s, which are maximised.
+//!
+//! ```rust
+//! use ecosystem::neat::Neat;
+//!
+//! // Learn exclusive or, which needs a hidden node
+//! let cases = [
+//!     ([0.0, 0.0], 0.0),
+//!     ([0.0, 1.0], 1.0),
+//!     ([1.0, 0.0], 1.0),
+//!     ([1.0, 1.0], 0.0),
+//! ];
+//! let mut neat = Neat::new(2, 1, 150, move |genome| {
+//!     let error: f64 = cases
+//!         .iter()
+//!         .map(|(inputs, target)| (genome.activate(inputs)[0] - target).abs())
+//!         .sum();
+//!     (4.0 - error).powi(2)
+//! })
+//! .seed(0);
+//! while neat.best_fitness().map_or(true, |fitness| fitness < 15.0) {
+//!     neat.breed_next_generation()?;
+//! }
+//!
+//! let network = neat.fittest().unwrap();
+//! for (inputs, target) in &cases {
+//!     assert_eq!(network.activate(inputs)[0].round(), *target);
+//! }
+//! assert!(!network.hidden_nodes().is_empty());
+//! # Ok::<(), ecosystem::EcosystemError>(())
+//! ```
+//!
+//! [`Innovations`]: struct.Innovations.html
+//! [`Neat`]: struct.Neat.html
+//! [`Genome`]: struct.Genome.html
+use crate::parallel::*;
+use crate::{genome::standard_normal, selection::divide_into_species, EcosystemError};
+use rand::{Rng, SeedableRng};
+use rand_pcg::Pcg64;
+use std::{cmp::Ordering, collections::HashMap};
+
+/// The probability that a gene disabled in either parent is disabled in
+/// their child.
+const DISABLED_INHERITANCE: f64 = 0.75;
+/// The probability that a weight being mutated is replaced outright, rather
+/// than perturbed.
+const WEIGHT_REPLACEMENT: f64 = 0.1;
+/// The standard deviation of the noise that weights are perturbed by.
+const WEIGHT_PERTURBATION: f64 = 0.5;
+/// The proportion of children bred by crossover, rather than by mutation
+/// alone.
+const CROSSOVER_RATE: f64 = 0.75;
+/// The probability that a child bred by crossover has a father from outside
+/// its mother's species.
+const INTERSPECIES_RATE: f64 = 0.001;
+/// The proportion of the fittest members of each species that are allowed
+/// to breed.
+const SURVIVAL_RATE: f64 = 0.2;
+/// The size a species must reach for its champion to be carried over
+/// unchanged.
+const CHAMPION_SPECIES_SIZE: usize = 5;
+/// The number of the fittest species that are never culled for stagnating.
+const SPARED_SPECIES: usize = 2;
+
+/// The record of the structural innovations made across a population, which
+/// gives the same innovation number to every connection between the same
+/// pair of nodes, and the same node to every split of the same connection.
+///
+/// Nodes are numbered with the inputs first, then a bias node whose value is
+/// always one, then the outputs, followed by any hidden nodes.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Innovations {
+    inputs: usize,
+    outputs: usize,
+    connections: HashMap<(usize, usize), u64>,
+    splits: HashMap<u64, usize>,
+    next_node: usize,
+}
+
+impl Innovations {
+    /// Creates an empty record for networks with the given numbers of inputs
+    /// and outputs.
+    pub fn new(inputs: usize, outputs: usize) -> Self {
+        Self {
+            inputs,
+            outputs,
+            connections: HashMap::new(),
+            splits: HashMap::new(),
+            next_node: inputs + 1 + outputs,
+        }
+    }
+
+    /// Returns the number of inputs.
+    pub fn inputs(&self) -> usize {
+        self.inputs
+    }
+
+    /// Returns the number of outputs.
+    pub fn outputs(&self) -> usize {
+        self.outputs
+    }
+
+    /// Returns the number of distinct connections that have been made.
+    pub fn connection_count(&self) -> usize {
+        self.connections.len()
+    }
+
+    /// Returns the number of distinct nodes, including the inputs, bias and
+    /// outputs.
+    pub fn node_count(&self) -> usize {
+        self.next_node
+    }
+
+    /// Returns the innovation number of the connection between the given
+    /// nodes, assigning the next number if the connection is new.
+    fn connection(&mut self, from: usize, to: usize) -> u64 {
+        let next = self.connections.len() as u64;
+        *self.connections.entry((from, to)).or_insert(next)
+    }
+
+    /// Returns the node that splits the connection with the given innovation
+    /// number, assigning the next node if it has not been split before.
+    fn split(&mut self, innovation: u64) -> usize {
+        let next_node = &mut self.next_node;
+        *self.splits.entry(innovation).or_insert_with(|| {
+            *next_node += 1;
+            *next_node - 1
+        })
+    }
+}
+
+/// A weighted connection between two nodes, marked with the innovation
+/// number of its structure.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ConnectionGene {
+    /// The innovation number of the connection.
+    pub innovation: u64,
+    /// The node the connection leads from.
+    pub from: usize,
+    /// The node the connection leads to.
+    pub to: usize,
+    /// The weight of the connection.
+    pub weight: f64,
+    /// Whether the connection is expressed in the network.
+    pub enabled: bool,
+}
+
+/// A network encoded as a list of connection genes, ordered by innovation
+/// number.
+///
+/// Networks are kept feed-forward: connections never lead from an output,
+/// and are never added where they would form a cycle. Hidden and output
+/// nodes apply a steepened sigmoid to the weighted sum of their inputs.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Genome {
+    inputs: usize,
+    outputs: usize,
+    hidden: Vec<usize>,
+    connections: Vec<ConnectionGene>,
+}
+
+impl Genome {
+    /// Creates a network that connects every input, and the bias, directly
+    /// to every output, with weights drawn from the standard normal
+    /// distribution.
+    pub fn new(innovations: &mut Innovations, rng: &mut impl Rng) -> Self {
+        let (inputs, outputs) = (innovations.inputs, innovations.outputs);
+        let mut connections = Vec::with_capacity((inputs + 1) * outputs);
+        for to in inputs + 1..inputs + 1 + outputs {
+            for from in 0..=inputs {
+                connections.push(ConnectionGene {
+                    innovation: innovations.connection(from, to),
+                    from,
+                    to,
+                    weight: standard_normal(rng),
+                    enabled: true,
+                });
+            }
+        }
+        connections.sort_by_key(|gene| gene.innovation);
+        Self {
+            inputs,
+            outputs,
+            hidden: Vec::new(),
+            connections,
+        }
+    }
+
+    /// Returns the number of inputs.
+    pub fn inputs(&self) -> usize {
+        self.inputs
+    }
+
+    /// Returns the number of outputs.
+    pub fn outputs(&self) -> usize {
+        self.outputs
+    }
+
+    /// Returns the hidden nodes.
+    pub fn hidden_nodes(&self) -> &[usize] {
+        &self.hidden
+    }
+
+    /// Returns the connection genes, ordered by innovation number.
+    pub fn connections(&self) -> &[ConnectionGene] {
+        &self.connections
+    }
+
+    /// Feeds the inputs through the network, returning the value of each
+    /// output. Outputs that nothing is connected to give one half.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of inputs does not match the network.
+    pub fn activate(&self, inputs: &[f64]) -> Vec<f64> {
+        assert_eq!(
+            inputs.len(),
+            self.inputs,
+            "the number of inputs must match the network"
+        );
+        let mut values: HashMap<_, _> = inputs.iter().copied().enumerate().collect();
+        values.insert(self.inputs, 1.0);
+        let mut incoming: HashMap<_, Vec<_>> = HashMap::new();
+        for gene in self.connections.iter().filter(|gene| gene.enabled) {
+            incoming.entry(gene.to).or_default().push(gene);
+        }
+        (self.inputs + 1..self.inputs + 1 + self.outputs)
+            .map(|node| node_value(node, &incoming, &mut values))
+            .collect()
+    }
+
+    /// Connects two previously unconnected nodes with a random weight,
+    /// returning whether there were any that could be connected without
+    /// forming a cycle.
+    pub fn add_connection(&mut self, innovations: &mut Innovations, rng: &mut impl Rng) -> bool {
+        let sources = (0..=self.inputs).chain(self.hidden.iter().copied());
+        let targets: Vec<_> = self
+            .hidden
+            .iter()
+            .copied()
+            .chain(self.inputs + 1..self.inputs + 1 + self.outputs)
+            .collect();
+        let candidates: Vec<_> = sources
+            .flat_map(|from| targets.iter().map(move |&to| (from, to)))
+            .filter(|&(from, to)| {
+                from != to
+                    && !self
+                        .connections
+                        .iter()
+                        .any(|gene| gene.from == from && gene.to == to)
+                    && !self.reaches(to, from)
+            })
+            .collect();
+        if candidates.is_empty() {
+            return false;
+        }
+
+        let (from, to) = candidates[rng.gen_range(0, candidates.len())];
+        self.insert(ConnectionGene {
+            innovation: innovations.connection(from, to),
+            from,
+            to,
+            weight: standard_normal(rng),
+            enabled: true,
+        });
+        true
+    }
+
+    /// Splits a random enabled connection with a new hidden node, returning
+    /// whether one was split. The connection is disabled and replaced by one
+    /// into the new node, with a weight of one, and one out of it, with the
+    /// old weight, so the network behaves much as it did.
+    pub fn add_node(&mut self, innovations: &mut Innovations, rng: &mut impl Rng) -> bool {
+        let enabled: Vec<_> = (0..self.connections.len())
+            .filter(|&index| self.connections[index].enabled)
+            .collect();
+        if enabled.is_empty() {
+            return false;
+        }
+
+        let index = enabled[rng.gen_range(0, enabled.len())];
+        let split = self.connections[index];
+        let node = innovations.split(split.innovation);
+        // The connection has already been split, before being re-enabled
+        if self.hidden.contains(&node) {
+            return false;
+        }
+
+        self.connections[index].enabled = false;
+        self.hidden.push(node);
+        self.insert(ConnectionGene {
+            innovation: innovations.connection(split.from, node),
+            from: split.from,
+            to: node,
+            weight: 1.0,
+            enabled: true,
+        });
+        self.insert(ConnectionGene {
+            innovation: innovations.connection(node, split.to),
+            from: node,
+            to: split.to,
+            weight: split.weight,
+            enabled: true,
+        });
+        true
+    }
+
+    /// Mutates every weight, either perturbing it with Gaussian noise of the
+    /// given standard deviation or, occasionally, replacing it with a value
+    /// drawn from the standard normal distribution.
+    pub fn perturb_weights(&mut self, std_dev: f64, rng: &mut impl Rng) {
+        for gene in &mut self.connections {
+            if rng.gen_bool(WEIGHT_REPLACEMENT) {
+                gene.weight = standard_normal(rng);
+            } else {
+                gene.weight += std_dev * standard_normal(rng);
+            }
+        }
+    }
+
+    /// Crosses this network with another, where this is the fitter of the
+    /// two. Genes with the same innovation number take their weight from
+    /// either parent at random, while the rest are inherited from this
+    /// parent alone, so the child has its structure. A gene disabled in
+    /// either parent is usually disabled in the child.
+    pub fn crossover(&self, other: &Self, rng: &mut impl Rng) -> Self {
+        let mut child = self.clone();
+        for gene in &mut child.connections {
+            let matching = other
+                .connections
+                .binary_search_by_key(&gene.innovation, |gene| gene.innovation)
+                .map(|index| other.connections[index]);
+            if let Ok(matching) = matching {
+                if rng.gen_bool(0.5) {
+                    gene.weight = matching.weight;
+                }
+                gene.enabled = if gene.enabled && matching.enabled {
+                    true
+                } else {
+                    !rng.gen_bool(DISABLED_INHERITANCE)
+                };
+            }
+        }
+        child
+    }
+
+    /// Measures how far apart two networks are, as a weighted sum of the
+    /// number of excess genes (those beyond the other's latest innovation),
+    /// the number of disjoint genes (the other unmatched genes) and the mean
+    /// difference between the weights of matching genes. The counts are
+    /// divided by the number of genes in the larger network, unless it has
+    /// fewer than twenty.
+    pub fn compatibility(&self, other: &Self, excess: f64, disjoint: f64, weight: f64) -> f64 {
+        let (a, b) = (&self.connections, &other.connections);
+        let (mut i, mut j) = (0, 0);
+        let (mut disjoint_count, mut matching, mut weight_difference) = (0, 0, 0.0);
+        while i < a.len() && j < b.len() {
+            match a[i].innovation.cmp(&b[j].innovation) {
+                Ordering::Equal => {
+                    matching += 1;
+                    weight_difference += (a[i].weight - b[j].weight).abs();
+                    i += 1;
+                    j += 1;
+                }
+                Ordering::Less => {
+                    disjoint_count += 1;
+                    i += 1;
+                }
+                Ordering::Greater => {
+                    disjoint_count += 1;
+                    j += 1;
+                }
+            }
+        }
+        let excess_count = (a.len() - i) + (b.len() - j);
+        let size = a.len().max(b.len());
+        let size = if size < 20 { 1.0 } else { size as f64 };
+        let mean_weight_difference = if matching > 0 {
+            weight_difference / matching as f64
+        } else {
+            0.0
+        };
+        excess * excess_count as f64 / size
+            + disjoint * disjoint_count as f64 / size
+            + weight * mean_weight_difference
+    }
+
+    /// Returns whether there is a path from one node to another.
+    fn reaches(&self, from: usize, to: usize) -> bool {
+        let mut stack = vec![from];
+        let mut visited = vec![from];
+        while let Some(node) = stack.pop() {
+            if node == to {
+                return true;
+            }
+            for gene in self.connections.iter().filter(|gene| gene.from == node) {
+                if !visited.contains(&gene.to) {
+                    visited.push(gene.to);
+                    stack.push(gene.to);
+                }
+            }
+        }
+        false
+    }
+
+    /// Inserts a gene, keeping the genes ordered by innovation number.
+    fn insert(&mut self, gene: ConnectionGene) {
+        let index = self
+            .connections
+            .partition_point(|existing| existing.innovation < gene.innovation);
+        self.connections.insert(index, gene);
+    }
+}
+
+/// Returns the value of a node, computing and recording the values of the
+/// nodes that feed into it as needed.
+fn node_value(
+    node: usize,
+    incoming: &HashMap<usize, Vec<&ConnectionGene>>,
+    values: &mut HashMap<usize, f64>,
+) -> f64 {
+    if let Some(&value) = values.get(&node) {
+        return value;
+    }
+    let sum: f64 = incoming.get(&node).map_or(0.0, |genes| {
+        genes
+            .iter()
+            .map(|gene| gene.weight * node_value(gene.from, incoming, values))
+            .sum()
+    });
+    let value = 1.0 / (1.0 + (-4.9 * sum).exp());
+    values.insert(node, value);
+    value
+}
+
+/// A species of genomes, remembered between generations.
+struct Species {
+    representative: Genome,
+    members: Vec<usize>,
+    best_fitness: f64,
+    last_improvement: u32,
+}
+
+/// A population of [`Genome`]s evolved by NEAT, as described in the
+/// [module documentation].
+///
+/// In each generation, the population is divided into species, each
+/// represented by a random member of the previous generation: a genome
+/// joins the first species whose representative is within the compatibility
+/// threshold of it, or else founds a new species. Species whose best fitness
+/// has not improved for the stagnation limit are culled, sparing the two
+/// fittest. The rest are allotted children in proportion to their mean
+/// fitness (shifted so that the least fit genome has none), and breed them
+/// from their fittest fifth. The champion of each species with at least five
+/// members is carried over unchanged, a quarter of the children are copies
+/// of a single parent, and the rest are bred by crossover; every child but
+/// the champions is then mutated.
+///
+/// [`Genome`]: struct.Genome.html
+/// [module documentation]: index.html
+pub struct Neat {
+    population_size: usize,
+    evaluate: Box<dyn Fn(&Genome) -> f64 + Send + Sync>,
+    innovations: Innovations,
+    population: Vec<Genome>,
+    fitnesses: Vec<f64>,
+    species: Vec<Species>,
+    compatibility_threshold: f64,
+    coefficients: (f64, f64, f64),
+    add_node_rate: f64,
+    add_connection_rate: f64,
+    weight_mutation_rate: f64,
+    stagnation: u32,
+    rng: Pcg64,
+    generation: u32,
+    evaluations: u64,
+}
+
+impl Neat {
+    /// Creates a population of `population_size` networks with the given
+    /// numbers of inputs and outputs, whose fitness is measured by
+    /// `evaluate`. The population is empty until the first generation is
+    /// bred, when it is filled with minimal networks.
+    ///
+    /// By default, the compatibility threshold is 3.0, with coefficients of
+    /// 1.0 for excess and disjoint genes and 0.4 for weights. Children gain
+    /// a node with a probability of 0.03 and a connection with a probability
+    /// of 0.05, and have their weights mutated with a probability of 0.8.
+    /// Species are culled after 15 generations without improving, and the
+    /// random number generator is seeded from the operating system.
+    pub fn new<F>(inputs: usize, outputs: usize, population_size: usize, evaluate: F) -> Self
+    where
+        F: Fn(&Genome) -> f64 + Send + Sync + 'static,
+    {
+        Self {
+            population_size,
+            evaluate: Box::new(evaluate),
+            innovations: Innovations::new(inputs, outputs),
+            population: Vec::new(),
+            fitnesses: Vec::new(),
+            species: Vec::new(),
+            compatibility_threshold: 3.0,
+            coefficients: (1.0, 1.0, 0.4),
+            add_node_rate: 0.03,
+            add_connection_rate: 0.05,
+            weight_mutation_rate: 0.8,
+            stagnation: 15,
+            rng: Pcg64::from_entropy(),
+            generation: 0,
+            evaluations: 0,
+        }
+    }
+
+    /// Sets the compatibility distance below which genomes belong to the
+    /// same species.
+    pub fn compatibility_threshold(mut self, threshold: f64) -> Self {
+        self.compatibility_threshold = threshold;
+        self
+    }
+
+    /// Sets the coefficients of the excess genes, disjoint genes and weight
+    /// differences in the compatibility distance.
+    pub fn compatibility_coefficients(mut self, excess: f64, disjoint: f64, weight: f64) -> Self {
+        self.coefficients = (excess, disjoint, weight);
+        self
+    }
+
+    /// Sets the probability that a child gains a hidden node.
+    pub fn add_node_rate(mut self, rate: f64) -> Self {
+        self.add_node_rate = rate;
+        self
+    }
+
+    /// Sets the probability that a child gains a connection.
+    pub fn add_connection_rate(mut self, rate: f64) -> Self {
+        self.add_connection_rate = rate;
+        self
+    }
+
+    /// Sets the probability that a child's weights are mutated.
+    pub fn weight_mutation_rate(mut self, rate: f64) -> Self {
+        self.weight_mutation_rate = rate;
+        self
+    }
+
+    /// Sets the number of generations a species may go without improving
+    /// before it is culled.
+    pub fn stagnation(mut self, generations: u32) -> Self {
+        self.stagnation = generations;
+        self
+    }
+
+    /// Seeds the random number generator, making evolution reproducible.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.rng = Pcg64::seed_from_u64(seed);
+        self
+    }
+
+    /// Returns the number of generations that have been bred.
+    pub fn generation(&self) -> u32 {
+        self.generation
+    }
+
+    /// Returns the number of genomes whose fitness has been evaluated.
+    pub fn evaluations(&self) -> u64 {
+        self.evaluations
+    }
+
+    /// Returns the current population.
+    pub fn population(&self) -> &[Genome] {
+        &self.population
+    }
+
+    /// Returns the record of the population's structural innovations.
+    pub fn innovations(&self) -> &Innovations {
+        &self.innovations
+    }
+
+    /// Returns the number of species in the current population.
+    pub fn species_count(&self) -> usize {
+        self.species.len()
+    }
+
+    /// Returns the fittest genome, or `None` if the population is empty.
+    pub fn fittest(&self) -> Option<&Genome> {
+        self.fittest_index().map(|index| &self.population[index])
+    }
+
+    /// Returns the fitness of the fittest genome, or `None` if the
+    /// population is empty.
+    pub fn best_fitness(&self) -> Option<f64> {
+        self.fittest_index().map(|index| self.fitnesses[index])
+    }
+
+    /// Breeds and evaluates the next generation, then divides it into
+    /// species. The population is first filled with minimal networks if it
+    /// is empty.
+    ///
+    /// Children are bred sequentially and evaluated in parallel, so the
+    /// result is reproducible.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the population size is zero, as there is then
+    /// nothing to breed.
+    pub fn breed_next_generation(&mut self) -> Result<(), EcosystemError> {
+        if self.population_size == 0 {
+            return Err(EcosystemError::EmptyPopulation);
+        }
+
+        if self.population.is_empty() {
+            let (innovations, rng) = (&mut self.innovations, &mut self.rng);
+            let population = (0..self.population_size)
+                .map(|_| Genome::new(innovations, rng))
+                .collect();
+            self.replace_population(population);
+        }
+
+        let children = self.reproduce();
+        self.generation += 1;
+        self.replace_population(children);
+        Ok(())
+    }
+
+    /// Evaluates and speciates a new population.
+    fn replace_population(&mut self, population: Vec<Genome>) {
+        let evaluate = &self.evaluate;
+        self.fitnesses = population.par_iter().map(evaluate).collect();
+        self.evaluations += population.len() as u64;
+        self.population = population;
+        self.speciate();
+    }
+
+    /// Divides the population into species, starting from the species of
+    /// the previous generation and dropping any that are left empty.
+    fn speciate(&mut self) {
+        let founders: Vec<_> = self
+            .species
+            .iter()
+            .map(|species| &species.representative)
+            .collect();
+        let (excess, disjoint, weight) = self.coefficients;
+        let groups = divide_into_species(
+            &founders,
+            &self.population,
+            |a, b| a.compatibility(b, excess, disjoint, weight),
+            self.compatibility_threshold,
+        );
+
+        let previous = std::mem::take(&mut self.species);
+        for (id, members) in groups.into_iter().enumerate() {
+            if members.is_empty() {
+                continue;
+            }
+            let best_fitness = members
+                .iter()
+                .map(|&index| self.fitnesses[index])
+                .fold(f64::NEG_INFINITY, f64::max);
+            let (best_fitness, last_improvement) = match previous.get(id) {
+                Some(species) if species.best_fitness >= best_fitness => {
+                    (species.best_fitness, species.last_improvement)
+                }
+                _ => (best_fitness, self.generation),
+            };
+            let representative = members[self.rng.gen_range(0, members.len())];
+            self.species.push(Species {
+                representative: self.population[representative].clone(),
+                members,
+                best_fitness,
+                last_improvement,
+            });
+        }
+    }
+
+    /// Culls stagnant species and breeds the next generation from the rest.
+    fn reproduce(&mut self) -> Vec<Genome> {
+        let (generation, stagnation) = (self.generation, self.stagnation);
+        self.species.sort_by(|a, b| {
+            b.best_fitness
+                .partial_cmp(&a.best_fitness)
+                .unwrap_or(Ordering::Equal)
+        });
+        let mut rank = 0;
+        self.species.retain(|species| {
+            rank += 1;
+            rank <= SPARED_SPECIES || generation - species.last_improvement < stagnation
+        });
+
+        let mut children = Vec::with_capacity(self.population_size);
+        for (species, count) in self.species.iter().zip(self.allot_children()) {
+            let mut members = species.members.clone();
+            members.sort_by(|&a, &b| {
+                self.fitnesses[b]
+                    .partial_cmp(&self.fitnesses[a])
+                    .unwrap_or(Ordering::Equal)
+            });
+            let mut count = count;
+            if count > 0 && members.len() >= CHAMPION_SPECIES_SIZE {
+                children.push(self.population[members[0]].clone());
+                count -= 1;
+            }
+            let survivors = (members.len() as f64 * SURVIVAL_RATE).ceil() as usize;
+            members.truncate(survivors.max(1));
+
+            let rng = &mut self.rng;
+            for _ in 0..count {
+                let mother = members[rng.gen_range(0, members.len())];
+                let mut child = if rng.gen_bool(CROSSOVER_RATE) {
+                    let father = if rng.gen_bool(INTERSPECIES_RATE) {
+                        rng.gen_range(0, self.population.len())
+                    } else {
+                        members[rng.gen_range(0, members.len())]
+                    };
+                    let (fitter, other) = if self.fitnesses[father] > self.fitnesses[mother] {
+                        (father, mother)
+                    } else {
+                        (mother, father)
+                    };
+                    self.population[fitter].crossover(&self.population[other], rng)
+                } else {
+                    self.population[mother].clone()
+                };
+
+                if rng.gen_bool(self.add_node_rate.clamp(0.0, 1.0)) {
+                    child.add_node(&mut self.innovations, rng);
+                }
+                if rng.gen_bool(self.add_connection_rate.clamp(0.0, 1.0)) {
+                    child.add_connection(&mut self.innovations, rng);
+                }
+                if rng.gen_bool(self.weight_mutation_rate.clamp(0.0, 1.0)) {
+                    child.perturb_weights(WEIGHT_PERTURBATION, rng);
+                }
+                children.push(child);
+            }
+        }
+        children
+    }
+
+    /// Divides the population size between the species in proportion to
+    /// their mean fitness, giving any remainder to those with the largest
+    /// fractional shares.
+    fn allot_children(&self) -> Vec<usize> {
+        let least = self.fitnesses.iter().copied().fold(f64::INFINITY, f64::min);
+        let mut shares: Vec<f64> = self
+            .species
+            .iter()
+            .map(|species| {
+                let total: f64 = species
+                    .members
+                    .iter()
+                    .map(|&index| self.fitnesses[index] - least)
+                    .sum();
+                total / species.members.len() as f64
+            })
+            .collect();
+        let total: f64 = shares.iter().sum();
+        if total <= 0.0 || total.is_nan() {
+            shares.iter_mut().for_each(|share| *share = 1.0);
+        }
+
+        let total: f64 = shares.iter().sum();
+        let quotas: Vec<_> = shares
+            .iter()
+            .map(|share| share / total * self.population_size as f64)
+            .collect();
+        let mut counts: Vec<_> = quotas.iter().map(|quota| quota.floor() as usize).collect();
+        let mut remainders: Vec<_> = (0..quotas.len()).collect();
+        remainders.sort_by(|&a, &b| {
+            let (a, b) = (quotas[a].fract(), quotas[b].fract());
+            b.partial_cmp(&a).unwrap_or(Ordering::Equal)
+        });
+        let allotted: usize = counts.iter().sum();
+        for &id in remainders
+            .iter()
+            .cycle()
+            .take(self.population_size.saturating_sub(allotted))
+        {
+            counts[id] += 1;
+        }
+        counts
+    }
+
+    fn fittest_index(&self) -> Option<usize> {
+        (0..self.fitnesses.len()).max_by(|&a, &b| {
+            self.fitnesses[a]
+                .partial_cmp(&self.fitnesses[b])
+                .unwrap_or(Ordering::Equal)
+        })
+    }
+}
@@ -0,0 +1,405 @@
+//! Neuroevolution of feed-forward neural networks with a fixed topology.
+//!
+//! A [`Network`] holds the weights of a network laid out by a
+//! [`Topology`]. Networks can be evolved directly, by wrapping them in an
+//! organism, or by describing the problem as a [`Task`] and evolving
+//! [`Controller`]s, which implement [`Organism`] out of the box: children
+//! are bred by exchanging whole neurons between the parents, and mutated by
+//! perturbing their weights with Gaussian noise.
+//!
+//! ```rust
+//! use ecosystem::{
+//!     neuro::{Activation, Controller, Network, Task, Topology},
+//!     selection::Tournament,
+//!     Ecosystem,
+//! };
+//! use rand::SeedableRng;
+//! use rand_pcg::Pcg64;
+//! use std::sync::Arc;
+//!
+//! // Learn exclusive or, which needs a hidden layer
+//! struct Xor;
+//!
+//! impl Task for Xor {
+//!     type Fitness = f64;
+//!
+//!     fn evaluate(&self, network: &Network) -> f64 {
+//!         let cases = [
+//!             ([0.0, 0.0], 0.0),
+//!             ([0.0, 1.0], 1.0),
+//!             ([1.0, 0.0], 1.0),
+//!             ([1.0, 1.0], 0.0),
+//!         ];
+//!         let error: f64 = cases
+//!             .iter()
+//!             .map(|(inputs, target)| (network.forward(inputs)[0] - target).powi(2))
+//!             .sum();
+//!         -error
+//!     }
+//! }
+//!
+//! let topology = Topology::new(vec![2, 4, 1]).output_activation(Activation::Sigmoid);
+//! let task = Arc::new(Xor);
+//! let mut rng = Pcg64::seed_from_u64(0);
+//! let controllers: Vec<_> = (0..50)
+//!     .map(|_| Controller::new(Network::random(&topology, &mut rng), &task))
+//!     .collect();
+//! let mut ecosystem = Ecosystem::builder()
+//!     .organisms(controllers)
+//!     .mutation_rate(0.2)
+//!     .selection(Tournament { size: 3 })
+//!     .elitism(1)
+//!     .seed(0)
+//!     .build()
+//!     .unwrap();
+//! for _ in 0..200 {
+//!     ecosystem.breed_next_generation()?;
+//! }
+//!
+//! let network = ecosystem.fittest().unwrap().network();
+//! assert!(network.forward(&[0.0, 1.0])[0] > 0.8);
+//! assert!(network.forward(&[1.0, 1.0])[0] < 0.2);
+//! # Ok::<(), ecosystem::EcosystemError>(())
+//! ```
+//!
+//! [`Network`]: struct.Network.html
+//! [`Topology`]: struct.Topology.html
+//! [`Task`]: trait.Task.html
+//! [`Controller`]: struct.Controller.html
+//! [`Organism`]: ../trait.Organism.html
+use crate::{genome::RealVector, Fitness, Organism};
+use rand::Rng;
+use std::sync::Arc;
+
+/// The standard deviation of the noise that controllers' weights are
+/// perturbed by, unless set otherwise.
+pub const DEFAULT_PERTURBATION: f64 = 0.5;
+
+/// A function applied to the weighted sum of a neuron's inputs.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Activation {
+    /// The logistic function, giving values in (0, 1).
+    Sigmoid,
+    /// The hyperbolic tangent, giving values in (-1, 1).
+    Tanh,
+    /// The rectified linear unit, which clamps negative values to zero.
+    Relu,
+    /// The weighted sum itself.
+    Identity,
+}
+
+impl Activation {
+    /// Applies the activation function.
+    pub fn apply(self, x: f64) -> f64 {
+        match self {
+            Activation::Sigmoid => 1.0 / (1.0 + (-x).exp()),
+            Activation::Tanh => x.tanh(),
+            Activation::Relu => x.max(0.0),
+            Activation::Identity => x,
+        }
+    }
+}
+
+/// The layout of a fully-connected feed-forward network: the number of
+/// neurons in each layer, from the inputs to the outputs, and their
+/// activation functions.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Topology {
+    layers: Vec<usize>,
+    activation: Activation,
+    output_activation: Activation,
+}
+
+impl Topology {
+    /// Creates a topology with the given layer sizes, where the first is the
+    /// number of inputs and the last is the number of outputs. The hidden
+    /// and output layers use the hyperbolic tangent as their activation
+    /// function.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there are fewer than two layers.
+    pub fn new(layers: Vec<usize>) -> Self {
+        assert!(
+            layers.len() >= 2,
+            "a network needs at least an input and an output layer"
+        );
+        Self {
+            layers,
+            activation: Activation::Tanh,
+            output_activation: Activation::Tanh,
+        }
+    }
+
+    /// Sets the activation function of the hidden layers.
+    pub fn activation(mut self, activation: Activation) -> Self {
+        self.activation = activation;
+        self
+    }
+
+    /// Sets the activation function of the output layer.
+    pub fn output_activation(mut self, activation: Activation) -> Self {
+        self.output_activation = activation;
+        self
+    }
+
+    /// Returns the sizes of the layers.
+    pub fn layers(&self) -> &[usize] {
+        &self.layers
+    }
+
+    /// Returns the number of inputs.
+    pub fn inputs(&self) -> usize {
+        self.layers[0]
+    }
+
+    /// Returns the number of outputs.
+    pub fn outputs(&self) -> usize {
+        *self.layers.last().unwrap()
+    }
+
+    /// Returns the number of weights, including a bias for every neuron
+    /// outside the input layer.
+    pub fn weight_count(&self) -> usize {
+        self.layers
+            .windows(2)
+            .map(|pair| (pair[0] + 1) * pair[1])
+            .sum()
+    }
+
+    /// Returns the range of the weights of each neuron outside the input
+    /// layer, in order.
+    fn neurons(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        let mut start = 0;
+        self.layers.windows(2).flat_map(move |pair| {
+            let inputs = pair[0] + 1;
+            let first = start;
+            start += inputs * pair[1];
+            (0..pair[1]).map(move |neuron| {
+                let neuron_start = first + neuron * inputs;
+                (neuron_start, neuron_start + inputs)
+            })
+        })
+    }
+}
+
+/// The weights of a feed-forward network with a given [`Topology`].
+///
+/// The weights of each neuron are its bias followed by the weights of its
+/// inputs, and neurons are ordered by layer.
+///
+/// [`Topology`]: struct.Topology.html
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Network {
+    topology: Topology,
+    weights: RealVector,
+}
+
+impl Network {
+    /// Creates a network with the given weights.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of weights does not match the topology.
+    pub fn new(topology: &Topology, weights: Vec<f64>) -> Self {
+        assert_eq!(
+            weights.len(),
+            topology.weight_count(),
+            "the topology needs {} weights",
+            topology.weight_count()
+        );
+        Self {
+            topology: topology.clone(),
+            weights: RealVector::new(weights),
+        }
+    }
+
+    /// Creates a network with weights drawn uniformly from [-1, 1).
+    pub fn random(topology: &Topology, rng: &mut impl Rng) -> Self {
+        let weights = (0..topology.weight_count())
+            .map(|_| rng.gen_range(-1.0, 1.0))
+            .collect();
+        Self::new(topology, weights)
+    }
+
+    /// Returns the topology.
+    pub fn topology(&self) -> &Topology {
+        &self.topology
+    }
+
+    /// Returns the weights.
+    pub fn weights(&self) -> &[f64] {
+        self.weights.genes()
+    }
+
+    /// Computes the outputs of the network for the given inputs.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of inputs does not match the topology.
+    pub fn forward(&self, inputs: &[f64]) -> Vec<f64> {
+        assert_eq!(
+            inputs.len(),
+            self.topology.inputs(),
+            "the network takes {} inputs",
+            self.topology.inputs()
+        );
+        let weights = self.weights.genes();
+        let layers = self.topology.layers.len();
+        let mut values = inputs.to_vec();
+        let mut neurons = self.topology.neurons();
+        for (layer, &size) in self.topology.layers.iter().enumerate().skip(1) {
+            let activation = if layer + 1 == layers {
+                self.topology.output_activation
+            } else {
+                self.topology.activation
+            };
+            values = (0..size)
+                .map(|_| {
+                    let (start, end) = neurons.next().unwrap();
+                    let sum = weights[start]
+                        + weights[start + 1..end]
+                            .iter()
+                            .zip(&values)
+                            .map(|(weight, value)| weight * value)
+                            .sum::<f64>();
+                    activation.apply(sum)
+                })
+                .collect();
+        }
+        values
+    }
+
+    /// Crossover that exchanges whole neurons, with their bias and input
+    /// weights, between the two parents, each with a probability of one half.
+    /// Keeping each neuron's weights together preserves the features it has
+    /// learnt to detect.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the networks have different topologies.
+    pub fn neuron_crossover(&self, other: &Self, rng: &mut impl Rng) -> (Self, Self) {
+        assert_eq!(
+            self.topology, other.topology,
+            "networks with different topologies cannot be combined"
+        );
+        let mut first = self.weights.genes().to_vec();
+        let mut second = other.weights.genes().to_vec();
+        for (start, end) in self.topology.neurons() {
+            if rng.gen_bool(0.5) {
+                first[start..end].swap_with_slice(&mut second[start..end]);
+            }
+        }
+        (
+            Self::new(&self.topology, first),
+            Self::new(&self.topology, second),
+        )
+    }
+
+    /// Adds normally distributed noise, with the given standard deviation,
+    /// to each weight independently with a probability of `rate`.
+    pub fn perturb(&mut self, rate: f64, std_dev: f64, rng: &mut impl Rng) {
+        self.weights.gaussian_mutation(rate, std_dev, rng);
+    }
+}
+
+/// A problem for networks to solve, which gives the fitness of each.
+pub trait Task {
+    /// The type of a network's fitness value.
+    type Fitness: Fitness;
+
+    /// Evaluates how well the network performs the task.
+    fn evaluate(&self, network: &Network) -> Self::Fitness;
+}
+
+/// An organism made of a network that performs a [`Task`].
+///
+/// Children are bred by [neuron crossover] and mutated by [perturbing] each
+/// weight with the ecosystem's mutation rate, by noise with a standard
+/// deviation of [`DEFAULT_PERTURBATION`] unless set otherwise.
+///
+/// [`Task`]: trait.Task.html
+/// [neuron crossover]: struct.Network.html#method.neuron_crossover
+/// [perturbing]: struct.Network.html#method.perturb
+/// [`DEFAULT_PERTURBATION`]: constant.DEFAULT_PERTURBATION.html
+pub struct Controller<T> {
+    network: Network,
+    task: Arc<T>,
+    perturbation: f64,
+}
+
+impl<T: Task> Controller<T> {
+    /// Creates a controller of the given network, which performs the given
+    /// task.
+    pub fn new(network: Network, task: &Arc<T>) -> Self {
+        Self {
+            network,
+            task: Arc::clone(task),
+            perturbation: DEFAULT_PERTURBATION,
+        }
+    }
+
+    /// Sets the standard deviation of the noise that the weights are
+    /// perturbed by, which is inherited by the controller's children.
+    pub fn perturbation(mut self, std_dev: f64) -> Self {
+        self.perturbation = std_dev;
+        self
+    }
+
+    /// Returns the network.
+    pub fn network(&self) -> &Network {
+        &self.network
+    }
+
+    /// Returns the task.
+    pub fn task(&self) -> &Arc<T> {
+        &self.task
+    }
+
+    /// Consumes the controller, returning the network.
+    pub fn into_network(self) -> Network {
+        self.network
+    }
+
+    fn with_network(&self, network: Network) -> Self {
+        Self {
+            network,
+            task: Arc::clone(&self.task),
+            perturbation: self.perturbation,
+        }
+    }
+}
+
+impl<T: Task> Organism for Controller<T> {
+    type Fitness = T::Fitness;
+
+    fn fitness(&self) -> T::Fitness {
+        self.task.evaluate(&self.network)
+    }
+
+    fn breed(&self, other: &Self, rng: &mut impl Rng) -> Self {
+        self.breed_pair(other, rng).0
+    }
+
+    fn breed_pair(&self, other: &Self, rng: &mut impl Rng) -> (Self, Self) {
+        let (first, second) = self.network.neuron_crossover(&other.network, rng);
+        (self.with_network(first), other.with_network(second))
+    }
+
+    fn mutate(&mut self, rate: f64, rng: &mut impl Rng) {
+        self.network.perturb(rate, self.perturbation, rng);
+    }
+}
+
+impl<T> Clone for Controller<T> {
+    fn clone(&self) -> Self {
+        Self {
+            network: self.network.clone(),
+            task: Arc::clone(&self.task),
+            perturbation: self.perturbation,
+        }
+    }
+}
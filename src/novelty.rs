@@ -0,0 +1,259 @@
+//! Evolution driven by the novelty of organisms' behavior.
+//!
+//! In deceptive problems, following the fitness gradient leads away from the
+//! best solutions. Novelty search instead rewards organisms for behaving
+//! differently from those seen before, as described by a behavior
+//! [descriptor], which explores the space of behaviors far more thoroughly.
+//! Novelty can also be blended with fitness, so that the search is drawn
+//! towards good solutions while it explores.
+//!
+//! [descriptor]: trait.Behavior.html
+use crate::parallel::*;
+use crate::{Ecosystem, EcosystemError, Fitness, Organism};
+use rand::Rng;
+
+/// An interface for organisms whose behavior can be described as a point in
+/// space, such as the final position of a simulated robot.
+pub trait Behavior {
+    /// Describes the organism's behavior. Every organism must return the same
+    /// number of values.
+    fn behavior(&self) -> Vec<f64>;
+}
+
+/// An ecosystem evolved by novelty search, along with its archive of past
+/// behaviors.
+///
+/// The *novelty* of an organism is the mean Euclidean distance from its
+/// behavior to the `neighbors` nearest behaviors among the rest of the
+/// population and the archive. In each generation, every organism whose
+/// novelty exceeds the `threshold` has its behavior added to the archive,
+/// so that behaviors seen before become less novel over time.
+///
+/// Parents are chosen by binary tournaments on a score that blends novelty
+/// with fitness (both scaled to the range [0, 1] across the population),
+/// weighted by [`fitness_weight`]. The ecosystem's mutation rate, objective
+/// and elitism are used as usual (with the elites being the fittest
+/// organisms), but its selection strategy and population model are not.
+///
+/// [`fitness_weight`]: #method.set_fitness_weight
+///
+/// # Examples
+///
+/// ```rust
+/// use ecosystem::{
+///     novelty::{Behavior, NoveltySearch},
+///     Ecosystem, Organism,
+/// };
+/// # use rand::Rng;
+/// # struct Guess(f64);
+/// # impl Organism for Guess {
+/// #     type Fitness = f64;
+/// #     fn fitness(&self) -> f64 { unreachable!("only novelty is rewarded") }
+/// #     fn breed(&self, other: &Self, rng: &mut impl Rng) -> Self {
+/// #         Guess(if rng.gen() { self.0 } else { other.0 })
+/// #     }
+/// #     fn mutate(&mut self, rate: f64, rng: &mut impl Rng) {
+/// #         self.0 += rng.gen_range(-rate, rate);
+/// #     }
+/// # }
+///
+/// impl Behavior for Guess {
+///     fn behavior(&self) -> Vec<f64> {
+///         vec![self.0]
+///     }
+/// }
+///
+/// // Every guess starts out the same, with no fitness to guide them
+/// let ecosystem = Ecosystem::builder()
+///     .organisms((0..50).map(|_| Guess(0.0)))
+///     .mutation_rate(1.0)
+///     .build()
+///     .unwrap();
+/// let mut search = NoveltySearch::new(ecosystem, 5, 1.0);
+/// for _ in 0..30 {
+///     search.breed_next_generation()?;
+/// }
+///
+/// // The guesses have spread out in search of new behaviors
+/// let guesses: Vec<_> = search.ecosystem().organisms().map(|guess| guess.0).collect();
+/// let spread = guesses.iter().cloned().fold(f64::NEG_INFINITY, f64::max)
+///     - guesses.iter().cloned().fold(f64::INFINITY, f64::min);
+/// assert!(spread > 10.0);
+/// assert!(!search.archive().is_empty());
+/// # Ok::<(), ecosystem::EcosystemError>(())
+/// ```
+pub struct NoveltySearch<O: Organism> {
+    ecosystem: Ecosystem<O>,
+    archive: Vec<Vec<f64>>,
+    neighbors: usize,
+    threshold: f64,
+    fitness_weight: f64,
+}
+
+impl<O: Organism + Behavior + Send + Sync> NoveltySearch<O> {
+    /// Creates a novelty search over the given ecosystem, measuring novelty
+    /// against the `neighbors` nearest behaviors, and archiving behaviors
+    /// whose novelty exceeds `threshold`.
+    ///
+    /// Only novelty is rewarded at first; see [`set_fitness_weight`].
+    ///
+    /// [`set_fitness_weight`]: #method.set_fitness_weight
+    pub fn new(ecosystem: Ecosystem<O>, neighbors: usize, threshold: f64) -> Self {
+        Self {
+            ecosystem,
+            archive: Vec::new(),
+            neighbors,
+            threshold,
+            fitness_weight: 0.0,
+        }
+    }
+
+    /// Sets how much fitness counts towards each organism's score, from 0.0
+    /// (only novelty counts) to 1.0 (only fitness counts).
+    pub fn set_fitness_weight(&mut self, weight: f64) {
+        self.fitness_weight = weight.clamp(0.0, 1.0);
+    }
+
+    /// Returns the ecosystem being evolved.
+    pub fn ecosystem(&self) -> &Ecosystem<O> {
+        &self.ecosystem
+    }
+
+    /// Consumes the search, returning the ecosystem.
+    pub fn into_ecosystem(self) -> Ecosystem<O> {
+        self.ecosystem
+    }
+
+    /// Returns the archived behaviors, in the order they were added.
+    pub fn archive(&self) -> &[Vec<f64>] {
+        &self.archive
+    }
+
+    /// Returns the novelty of each organism in the population, relative to
+    /// the rest of the population and the archive.
+    pub fn novelties(&self) -> Vec<f64> {
+        let behaviors: Vec<_> = self
+            .ecosystem
+            .population
+            .par_iter()
+            .map(|individual| individual.organism().behavior())
+            .collect();
+        self.novelties_of(&behaviors)
+    }
+
+    /// Breeds the next generation, choosing parents by their novelty (and
+    /// fitness, if it has been given a weight), and archives any
+    /// sufficiently novel behaviors.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the ecosystem contains no organisms.
+    pub fn breed_next_generation(&mut self) -> Result<(), EcosystemError> {
+        let ecosystem = &self.ecosystem;
+        if ecosystem.population.is_empty() {
+            return Err(EcosystemError::EmptyPopulation);
+        }
+
+        let behaviors: Vec<_> = ecosystem
+            .population
+            .par_iter()
+            .map(|individual| individual.organism().behavior())
+            .collect();
+        let novelties = self.novelties_of(&behaviors);
+
+        let mut scores = normalize(&novelties);
+        if self.fitness_weight > 0.0 || ecosystem.elitism > 0 {
+            ecosystem.evaluate_all(&ecosystem.population);
+            let fitnesses: Vec<_> = ecosystem
+                .population
+                .par_iter()
+                .map(|individual| ecosystem.evaluate(individual).to_f64())
+                .collect();
+            let fitnesses = normalize(&ecosystem.objective.weights(fitnesses.into_iter()));
+            for (score, fitness) in scores.iter_mut().zip(fitnesses) {
+                *score = (1.0 - self.fitness_weight) * *score + self.fitness_weight * fitness;
+            }
+        }
+
+        for (behavior, novelty) in behaviors.into_iter().zip(novelties) {
+            if novelty > self.threshold {
+                self.archive.push(behavior);
+            }
+        }
+
+        let ecosystem = &mut self.ecosystem;
+        let size = ecosystem.population.len();
+        let count = ecosystem.offspring_count();
+        let parents: Vec<_> = (0..crate::parent_count(count))
+            .map(|_| {
+                let a = ecosystem.rng.gen_range(0, size);
+                let b = ecosystem.rng.gen_range(0, size);
+                if scores[b] > scores[a] {
+                    b
+                } else {
+                    a
+                }
+            })
+            .collect();
+        let next_generation = ecosystem.breed_offspring(&parents, count);
+
+        let objective = ecosystem.objective;
+        ecosystem.replace_generation(next_generation, |a, b| {
+            objective.compare(b.fitness(), a.fitness())
+        });
+        Ok(())
+    }
+
+    /// Computes the novelty of each of the given behaviors, which belong to
+    /// the current population.
+    fn novelties_of(&self, behaviors: &[Vec<f64>]) -> Vec<f64> {
+        (0..behaviors.len())
+            .into_par_iter()
+            .map(|index| {
+                let behavior = &behaviors[index];
+                let mut distances: Vec<_> = behaviors
+                    .iter()
+                    .enumerate()
+                    .filter(|&(other, _)| other != index)
+                    .map(|(_, other)| other)
+                    .chain(&self.archive)
+                    .map(|other| distance(behavior, other))
+                    .collect();
+                let k = self.neighbors.min(distances.len());
+                if k == 0 {
+                    return 0.0;
+                }
+                if k < distances.len() {
+                    distances.select_nth_unstable_by(k - 1, f64::total_cmp);
+                }
+                distances[..k].iter().sum::<f64>() / k as f64
+            })
+            .collect()
+    }
+}
+
+/// Returns the Euclidean distance between two behaviors.
+fn distance(a: &[f64], b: &[f64]) -> f64 {
+    a.iter()
+        .zip(b)
+        .map(|(a, b)| (a - b).powi(2))
+        .sum::<f64>()
+        .sqrt()
+}
+
+/// Scales the values to the range [0, 1], or to zero if they are all equal.
+fn normalize(values: &[f64]) -> Vec<f64> {
+    let min = values.iter().cloned().fold(f64::INFINITY, f64::min);
+    let max = values.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+    let range = max - min;
+    values
+        .iter()
+        .map(|value| {
+            if range > 0.0 {
+                (value - min) / range
+            } else {
+                0.0
+            }
+        })
+        .collect()
+}
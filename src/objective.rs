@@ -0,0 +1,99 @@
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+
+/// The direction in which fitness values are optimised.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Objective {
+    /// Higher fitness values are better. This is the default.
+    #[default]
+    Maximize,
+    /// Lower fitness values are better, such as when the 'fitness' is really
+    /// a cost or an error.
+    Minimize,
+}
+
+impl Objective {
+    /// Compares two fitness values, with `Ordering::Greater` meaning that `a`
+    /// is better than `b`.
+    ///
+    /// Values that cannot be compared even with themselves, such as NaN, are
+    /// worse than every other value whatever the objective, and equal to
+    /// each other. This keeps the order total, as sorting requires.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ecosystem::{selection::Tournament, Ecosystem, Objective, Organism};
+    /// use std::cmp::Ordering;
+    /// # use rand::Rng;
+    /// # #[derive(Clone, PartialEq)]
+    /// # struct Guess(f64);
+    /// # impl Organism for Guess {
+    /// #     type Fitness = f64;
+    /// #     fn fitness(&self) -> f64 {
+    /// #         // Guesses below zero have no meaningful fitness
+    /// #         if self.0 < 0.0 { f64::NAN } else { -(42.0 - self.0).abs() }
+    /// #     }
+    /// #     fn breed(&self, other: &Self, rng: &mut impl Rng) -> Self {
+    /// #         Guess(if rng.gen() { self.0 } else { other.0 })
+    /// #     }
+    /// #     fn mutate(&mut self, rate: f64, rng: &mut impl Rng) {
+    /// #         self.0 += rng.gen_range(-rate, rate);
+    /// #     }
+    /// # }
+    ///
+    /// assert_eq!(Objective::Maximize.compare(f64::NAN, -1.0), Ordering::Less);
+    /// assert_eq!(Objective::Minimize.compare(f64::NAN, 1.0), Ordering::Less);
+    ///
+    /// // Populations with NaN fitnesses sort and breed as usual
+    /// let mut ecosystem = Ecosystem::builder()
+    ///     .organisms((0..200).map(|i| Guess(f64::from(i) - 100.0)))
+    ///     .selection(Tournament { size: 3 })
+    ///     .elitism(10)
+    ///     .archive(5)
+    ///     .build()
+    ///     .unwrap();
+    /// for _ in 0..20 {
+    ///     ecosystem.breed_next_generation()?;
+    /// }
+    ///
+    /// assert!(!ecosystem.fittest_individual().unwrap().fitness().is_nan());
+    /// # Ok::<(), ecosystem::EcosystemError>(())
+    /// ```
+    pub fn compare<F: PartialOrd>(self, a: F, b: F) -> Ordering {
+        let comparable = |value: &F| value.partial_cmp(value).is_some();
+        match (comparable(&a), comparable(&b)) {
+            (true, true) => {
+                let ordering = a.partial_cmp(&b).unwrap_or(Ordering::Equal);
+                match self {
+                    Objective::Maximize => ordering,
+                    Objective::Minimize => ordering.reverse(),
+                }
+            }
+            (true, false) => Ordering::Greater,
+            (false, true) => Ordering::Less,
+            (false, false) => Ordering::Equal,
+        }
+    }
+
+    /// Converts fitness values into non-negative weights for
+    /// fitness-proportionate selection, with better values receiving larger
+    /// weights.
+    ///
+    /// When maximising, the fitness values are used as they are. When
+    /// minimising, each weight is the distance from the worst fitness value.
+    pub fn weights(self, fitnesses: impl Iterator<Item = f64>) -> Vec<f64> {
+        match self {
+            Objective::Maximize => fitnesses.collect(),
+            Objective::Minimize => {
+                let fitnesses: Vec<_> = fitnesses.collect();
+                let worst = fitnesses.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+                fitnesses
+                    .into_iter()
+                    .map(|fitness| worst - fitness)
+                    .collect()
+            }
+        }
+    }
+}
@@ -0,0 +1,115 @@
+//! Hooks for observing an evolutionary run as it progresses.
+//!
+//! Observers are registered with [`EcosystemBuilder::observer`], and are
+//! notified by [`Ecosystem::run`] as each generation is bred. Any observer can
+//! end the run early by returning `ControlFlow::Break`:
+//!
+//! ```rust
+//! use ecosystem::{
+//!     observer::Observer,
+//!     termination::{MaxGenerations, StopReason},
+//!     Ecosystem, Organism,
+//! };
+//! use std::ops::ControlFlow;
+//! # use rand::Rng;
+//! # struct Guess(f64);
+//! # impl Organism for Guess {
+//! #     type Fitness = f64;
+//! #     fn fitness(&self) -> f64 { 1.0 / (42.0 - self.0).abs() }
+//! #     fn breed(&self, other: &Self, rng: &mut impl Rng) -> Self { Guess((self.0 + other.0) / 2.0) }
+//! #     fn mutate(&mut self, rate: f64, rng: &mut impl Rng) {
+//! #         self.0 += rng.gen_range(-rate, rate);
+//! #     }
+//! # }
+//!
+//! /// Logs each generation, stopping the run after the tenth.
+//! struct Logger;
+//!
+//! impl Observer<Guess> for Logger {
+//!     fn on_generation_end(&mut self, ecosystem: &Ecosystem<Guess>) -> ControlFlow<()> {
+//!         println!("{}: {}", ecosystem.generation(), ecosystem.fittest().unwrap().0);
+//!         if ecosystem.generation() < 10 {
+//!             ControlFlow::Continue(())
+//!         } else {
+//!             ControlFlow::Break(())
+//!         }
+//!     }
+//! }
+//!
+//! let mut ecosystem = Ecosystem::builder()
+//!     .organisms((0..20).map(|i| Guess(f64::from(i) * 5.0 + 0.5)))
+//!     .observer(Logger)
+//!     .build()
+//!     .unwrap();
+//! let summary = ecosystem.run(MaxGenerations(100))?;
+//!
+//! assert_eq!(summary.reason, StopReason::Observer);
+//! assert_eq!(ecosystem.generation(), 10);
+//! # Ok::<(), ecosystem::EcosystemError>(())
+//! ```
+//!
+//! [`EcosystemBuilder::observer`]: ../struct.EcosystemBuilder.html#method.observer
+//! [`Ecosystem::run`]: ../struct.Ecosystem.html#method.run
+use crate::{Ecosystem, Organism};
+use core::ops::ControlFlow;
+#[cfg(feature = "std")]
+use std::sync::{Arc, Mutex, PoisonError};
+
+/// An interface for reacting to the progress of an evolutionary run.
+///
+/// Every method does nothing by default, so only the events of interest need
+/// to be handled. Returning `ControlFlow::Break` from any of them stops the
+/// run once the current event has been delivered to every observer.
+pub trait Observer<O: Organism>: Send + Sync {
+    /// Called before each generation is bred.
+    fn on_generation_start(&mut self, _ecosystem: &Ecosystem<O>) -> ControlFlow<()> {
+        ControlFlow::Continue(())
+    }
+
+    /// Called after each generation is bred.
+    fn on_generation_end(&mut self, _ecosystem: &Ecosystem<O>) -> ControlFlow<()> {
+        ControlFlow::Continue(())
+    }
+
+    /// Called after a generation is bred whose fittest organism is better
+    /// than any seen before during the run (and after
+    /// [`on_generation_end`]).
+    ///
+    /// [`on_generation_end`]: #method.on_generation_end
+    fn on_new_best(&mut self, _ecosystem: &Ecosystem<O>) -> ControlFlow<()> {
+        ControlFlow::Continue(())
+    }
+
+    /// Called once a run has ended, for whatever reason, including an error
+    /// in breeding, so that any resources held for the run can be released.
+    fn on_run_end(&mut self, _ecosystem: &Ecosystem<O>) {}
+}
+
+/// Shared observers can be registered with an ecosystem while remaining
+/// accessible elsewhere, such as for inspecting what they have recorded
+/// once a run has finished.
+#[cfg(feature = "std")]
+impl<O: Organism, T: Observer<O>> Observer<O> for Arc<Mutex<T>> {
+    fn on_generation_start(&mut self, ecosystem: &Ecosystem<O>) -> ControlFlow<()> {
+        lock(self).on_generation_start(ecosystem)
+    }
+
+    fn on_generation_end(&mut self, ecosystem: &Ecosystem<O>) -> ControlFlow<()> {
+        lock(self).on_generation_end(ecosystem)
+    }
+
+    fn on_new_best(&mut self, ecosystem: &Ecosystem<O>) -> ControlFlow<()> {
+        lock(self).on_new_best(ecosystem)
+    }
+
+    fn on_run_end(&mut self, ecosystem: &Ecosystem<O>) {
+        lock(self).on_run_end(ecosystem)
+    }
+}
+
+/// Locks a shared observer, ignoring any panic while it was last held, as an
+/// observer's state remains usable even if an earlier event was cut short.
+#[cfg(feature = "std")]
+fn lock<T>(observer: &Mutex<T>) -> std::sync::MutexGuard<'_, T> {
+    observer.lock().unwrap_or_else(PoisonError::into_inner)
+}
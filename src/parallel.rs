@@ -0,0 +1,109 @@
+//! Parallel iteration over populations, which falls back to ordinary
+//! iteration when the `parallel` feature is disabled.
+#[cfg(feature = "parallel")]
+pub(crate) use rayon::prelude::*;
+#[cfg(feature = "parallel")]
+use rayon::ThreadPool;
+#[cfg(feature = "parallel")]
+use std::sync::Arc;
+
+#[cfg(not(feature = "parallel"))]
+pub(crate) use sequential::*;
+
+/// The thread pool on which an ecosystem does its parallel work, and the
+/// least amount of work handed to each thread at a time.
+#[derive(Clone, Default)]
+pub(crate) struct Parallelism {
+    #[cfg(feature = "parallel")]
+    pub(crate) pool: Option<Arc<ThreadPool>>,
+    #[cfg(feature = "parallel")]
+    pub(crate) min_len: usize,
+}
+
+#[cfg(feature = "parallel")]
+impl Parallelism {
+    /// Runs the given operation in the thread pool, or in rayon's global
+    /// pool if none was given.
+    pub(crate) fn install<R: Send>(&self, op: impl FnOnce() -> R + Send) -> R {
+        match &self.pool {
+            Some(pool) => pool.install(op),
+            None => op(),
+        }
+    }
+
+    /// Returns the least number of items handed to each thread at a time.
+    pub(crate) fn min_len(&self) -> usize {
+        self.min_len.max(1)
+    }
+
+    /// Creates a parallelism on rayon's global thread pool, handing at least
+    /// `min_len` items to each thread at a time.
+    #[cfg(feature = "serde")]
+    pub(crate) fn with_min_len(min_len: usize) -> Self {
+        Self {
+            pool: None,
+            min_len,
+        }
+    }
+}
+
+#[cfg(not(feature = "parallel"))]
+impl Parallelism {
+    pub(crate) fn install<R: Send>(&self, op: impl FnOnce() -> R + Send) -> R {
+        op()
+    }
+
+    pub(crate) fn min_len(&self) -> usize {
+        1
+    }
+
+    #[cfg(feature = "serde")]
+    pub(crate) fn with_min_len(_min_len: usize) -> Self {
+        Self {}
+    }
+}
+
+/// Stand-ins for the parts of rayon's prelude that the crate uses, which
+/// iterate in order on the current thread.
+#[cfg(not(feature = "parallel"))]
+mod sequential {
+    use core::slice;
+
+    pub(crate) trait IntoParallelIterator: IntoIterator + Sized {
+        fn into_par_iter(self) -> Self::IntoIter {
+            self.into_iter()
+        }
+    }
+
+    impl<I: IntoIterator> IntoParallelIterator for I {}
+
+    pub(crate) trait IndexedParallelIterator: Iterator + Sized {
+        fn with_min_len(self, _min: usize) -> Self {
+            self
+        }
+    }
+
+    impl<I: Iterator> IndexedParallelIterator for I {}
+
+    pub(crate) trait ParallelSlice<T> {
+        fn par_iter(&self) -> slice::Iter<'_, T>;
+        #[cfg(feature = "std")]
+        fn par_iter_mut(&mut self) -> slice::IterMut<'_, T>;
+        fn par_chunks(&self, size: usize) -> slice::Chunks<'_, T>;
+    }
+
+    impl<T> ParallelSlice<T> for [T] {
+        fn par_iter(&self) -> slice::Iter<'_, T> {
+            self.iter()
+        }
+
+        #[cfg(feature = "std")]
+        fn par_iter_mut(&mut self) -> slice::IterMut<'_, T> {
+            self.iter_mut()
+        }
+
+        fn par_chunks(&self, size: usize) -> slice::Chunks<'_, T> {
+            self.chunks(size)
+        }
+    }
+}
@@ -0,0 +1,337 @@
+//! Evolution with several competing objectives.
+//!
+//! When no single fitness value can capture what makes an organism good (such
+//! as a model that should be both accurate and small), organisms can instead
+//! implement [`MultiObjective`] and be evolved with
+//! [`Ecosystem::breed_next_generation_nsga2`]. Rather than converging on a
+//! single best organism, the population spreads out along the trade-off
+//! between the objectives.
+//!
+//! [`MultiObjective`]: trait.MultiObjective.html
+//! [`Ecosystem::breed_next_generation_nsga2`]: ../struct.Ecosystem.html#method.breed_next_generation_nsga2
+use crate::parallel::*;
+use crate::{Ecosystem, EcosystemError, Individual, Objective, Organism};
+use alloc::{vec, vec::Vec};
+use core::{cmp::Ordering, sync::atomic};
+use rand::Rng;
+
+/// An interface for organisms that are judged on several objectives at once.
+pub trait MultiObjective {
+    /// Evaluates each of the organism's objectives.
+    ///
+    /// Every organism must return the same number of values, in the same
+    /// order. All of the values are optimised in the direction given by the
+    /// ecosystem's [`Objective`], so objectives that pull the other way
+    /// should be negated.
+    ///
+    /// [`Objective`]: ../enum.Objective.html
+    fn objectives(&self) -> Vec<f64>;
+}
+
+/// Returns whether `a` *dominates* `b`: that is, whether `a` is at least as
+/// good as `b` in every objective, and strictly better in at least one.
+///
+/// # Examples
+///
+/// ```rust
+/// use ecosystem::{pareto::dominates, Objective};
+///
+/// assert!(dominates(&[2.0, 3.0], &[1.0, 3.0], Objective::Maximize));
+/// // Neither point is better in every objective
+/// assert!(!dominates(&[2.0, 1.0], &[1.0, 3.0], Objective::Maximize));
+/// assert!(!dominates(&[1.0, 3.0], &[2.0, 1.0], Objective::Maximize));
+/// ```
+pub fn dominates(a: &[f64], b: &[f64], objective: Objective) -> bool {
+    let mut strictly_better = false;
+    for (&a, &b) in a.iter().zip(b) {
+        match objective.compare(a, b) {
+            Ordering::Less => return false,
+            Ordering::Greater => strictly_better = true,
+            Ordering::Equal => {}
+        }
+    }
+    strictly_better
+}
+
+/// Sorts points into successive non-dominated fronts, returning the indices
+/// of the points in each.
+///
+/// The first front holds the points that no other point dominates, the
+/// second holds those dominated only by points in the first, and so on.
+///
+/// # Examples
+///
+/// ```rust
+/// use ecosystem::{pareto::non_dominated_sort, Objective};
+///
+/// let points: Vec<&[f64]> = vec![&[1.0, 1.0], &[3.0, 1.0], &[2.0, 2.0], &[1.0, 3.0]];
+/// let fronts = non_dominated_sort(&points, Objective::Maximize);
+///
+/// assert_eq!(fronts, vec![vec![1, 2, 3], vec![0]]);
+/// ```
+pub fn non_dominated_sort(points: &[&[f64]], objective: Objective) -> Vec<Vec<usize>> {
+    let mut dominated_by_count = vec![0; points.len()];
+    let mut dominates_list = vec![Vec::new(); points.len()];
+    for a in 0..points.len() {
+        for b in (a + 1)..points.len() {
+            if dominates(points[a], points[b], objective) {
+                dominates_list[a].push(b);
+                dominated_by_count[b] += 1;
+            } else if dominates(points[b], points[a], objective) {
+                dominates_list[b].push(a);
+                dominated_by_count[a] += 1;
+            }
+        }
+    }
+
+    let mut fronts = Vec::new();
+    let mut front: Vec<_> = (0..points.len())
+        .filter(|&point| dominated_by_count[point] == 0)
+        .collect();
+    while !front.is_empty() {
+        let mut next = Vec::new();
+        for &point in &front {
+            for &dominated in &dominates_list[point] {
+                dominated_by_count[dominated] -= 1;
+                if dominated_by_count[dominated] == 0 {
+                    next.push(dominated);
+                }
+            }
+        }
+        next.sort_unstable();
+        fronts.push(core::mem::replace(&mut front, next));
+    }
+    fronts
+}
+
+/// Computes the crowding distance of each point in a front, which measures
+/// how far it is from its neighbors along the front. The distances are
+/// returned in the same order as the front.
+///
+/// The points at either extreme of each objective have an infinite distance,
+/// so that they are always preferred.
+pub fn crowding_distances(points: &[&[f64]], front: &[usize]) -> Vec<f64> {
+    let mut distances = vec![0.0; front.len()];
+    let dimensions = front.first().map_or(0, |&point| points[point].len());
+
+    let mut order: Vec<_> = (0..front.len()).collect();
+    let columns = (0..dimensions).map(|dimension| {
+        front
+            .iter()
+            .map(|&point| points[point][dimension])
+            .collect::<Vec<_>>()
+    });
+    for values in columns {
+        order.sort_by(|&a, &b| values[a].total_cmp(&values[b]));
+
+        let (first, last) = (order[0], order[order.len() - 1]);
+        distances[first] = f64::INFINITY;
+        distances[last] = f64::INFINITY;
+
+        let range = values[last] - values[first];
+        if range > 0.0 {
+            for window in order.windows(3) {
+                distances[window[1]] += (values[window[2]] - values[window[0]]) / range;
+            }
+        }
+    }
+    distances
+}
+
+impl<O: Organism + MultiObjective + Send + Sync> Ecosystem<O> {
+    /// Creates the next generation of organisms using NSGA-II, a
+    /// multi-objective evolutionary algorithm.
+    ///
+    /// Organisms are ranked by the non-dominated front they belong to, and
+    /// within each front by their crowding distance, which favours organisms
+    /// in sparsely populated parts of the front. Parents are chosen by binary
+    /// tournaments on this ranking, and the best-ranked half of the parents
+    /// and children together survive, keeping the population size the same.
+    ///
+    /// The organisms' objective values are evaluated at most once, and count
+    /// towards the ecosystem's [`evaluations`]. Their fitness values are not
+    /// used, nor are the ecosystem's selection strategy, elitism, population
+    /// model or archive.
+    ///
+    /// [`evaluations`]: #method.evaluations
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ecosystem::{pareto::MultiObjective, Ecosystem, Objective, Organism};
+    /// # use rand::Rng;
+    /// # struct Guess(f64);
+    /// # impl Organism for Guess {
+    /// #     type Fitness = f64;
+    /// #     fn fitness(&self) -> f64 { unreachable!("NSGA-II uses the objectives") }
+    /// #     fn breed(&self, other: &Self, rng: &mut impl Rng) -> Self { Guess((self.0 + other.0) / 2.0) }
+    /// #     fn mutate(&mut self, rate: f64, rng: &mut impl Rng) {
+    /// #         self.0 += rng.gen_range(-rate, rate);
+    /// #     }
+    /// # }
+    ///
+    /// // Schaffer's problem: every guess between 0 and 2 is a different
+    /// // trade-off between the two (minimised) objectives
+    /// impl MultiObjective for Guess {
+    ///     fn objectives(&self) -> Vec<f64> {
+    ///         vec![self.0.powi(2), (self.0 - 2.0).powi(2)]
+    ///     }
+    /// }
+    ///
+    /// let mut ecosystem = Ecosystem::builder()
+    ///     .organisms((0..40).map(|i| Guess(f64::from(i) - 20.0)))
+    ///     .mutation_rate(0.1)
+    ///     .objective(Objective::Minimize)
+    ///     .build()
+    ///     .unwrap();
+    /// for _ in 0..30 {
+    ///     ecosystem.breed_next_generation_nsga2()?;
+    /// }
+    ///
+    /// assert!(ecosystem.organisms().all(|guess| guess.0 > -0.5 && guess.0 < 2.5));
+    /// # Ok::<(), ecosystem::EcosystemError>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the ecosystem contains no organisms.
+    pub fn breed_next_generation_nsga2(&mut self) -> Result<(), EcosystemError> {
+        if self.population.is_empty() {
+            return Err(EcosystemError::EmptyPopulation);
+        }
+        let parallelism = self.parallelism.clone();
+        parallelism.install(|| {
+            let size = self.population.len();
+
+            let (ranks, distances) = self.rank_by_dominance();
+            let better = |a: usize, b: usize| {
+                ranks[a] < ranks[b] || (ranks[a] == ranks[b] && distances[a] > distances[b])
+            };
+            let parents: Vec<_> = (0..crate::parent_count(size))
+                .map(|_| {
+                    let a = self.rng.gen_range(0, size);
+                    let b = self.rng.gen_range(0, size);
+                    if better(b, a) {
+                        b
+                    } else {
+                        a
+                    }
+                })
+                .collect();
+            let offspring = self.breed_offspring(&parents, size);
+            self.pass_generation();
+            self.population.extend(offspring);
+
+            let (ranks, distances) = self.rank_by_dominance();
+            let mut order: Vec<_> = (0..self.population.len()).collect();
+            order.sort_by(|&a, &b| {
+                ranks[a]
+                    .cmp(&ranks[b])
+                    .then_with(|| distances[b].total_cmp(&distances[a]))
+            });
+            let mut survives = vec![false; self.population.len()];
+            for &index in &order[..size] {
+                survives[index] = true;
+            }
+            let mut survivors = survives.into_iter();
+            self.population.retain(|_| survivors.next().unwrap());
+
+            self.generation += 1;
+        });
+        Ok(())
+    }
+
+    /// Returns the organisms that no other organism in the population
+    /// dominates: the best trade-offs between the objectives found so far.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ecosystem::{pareto::MultiObjective, Ecosystem, Objective, Organism};
+    /// # use rand::Rng;
+    /// # struct Guess(f64);
+    /// # impl Organism for Guess {
+    /// #     type Fitness = f64;
+    /// #     fn fitness(&self) -> f64 { unreachable!("NSGA-II uses the objectives") }
+    /// #     fn breed(&self, other: &Self, rng: &mut impl Rng) -> Self { Guess((self.0 + other.0) / 2.0) }
+    /// #     fn mutate(&mut self, rate: f64, rng: &mut impl Rng) {
+    /// #         self.0 += rng.gen_range(-rate, rate);
+    /// #     }
+    /// # }
+    /// # impl MultiObjective for Guess {
+    /// #     fn objectives(&self) -> Vec<f64> {
+    /// #         vec![self.0.powi(2), (self.0 - 2.0).powi(2)]
+    /// #     }
+    /// # }
+    ///
+    /// let ecosystem = Ecosystem::builder()
+    ///     .organisms(vec![Guess(-1.0), Guess(0.5), Guess(1.5), Guess(3.0)])
+    ///     .objective(Objective::Minimize)
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// let front: Vec<_> = ecosystem.pareto_front().iter().map(|guess| guess.0).collect();
+    /// assert_eq!(front, vec![0.5, 1.5]);
+    /// ```
+    pub fn pareto_front(&self) -> Vec<&O> {
+        self.fronts().into_iter().next().unwrap_or_default()
+    }
+
+    /// Sorts the organisms into successive non-dominated fronts, best first.
+    ///
+    /// The first front is the [`pareto_front`]; the second holds the
+    /// organisms that would be non-dominated were the first removed, and so
+    /// on. Within each front, organisms are in the order they appear in the
+    /// population.
+    ///
+    /// [`pareto_front`]: #method.pareto_front
+    pub fn fronts(&self) -> Vec<Vec<&O>> {
+        let points = self.objective_values();
+        non_dominated_sort(&points, self.objective)
+            .into_iter()
+            .map(|front| {
+                front
+                    .into_iter()
+                    .map(|index| self.population[index].organism())
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Returns the index of the non-dominated front that each individual
+    /// belongs to, along with its crowding distance within that front.
+    fn rank_by_dominance(&self) -> (Vec<usize>, Vec<f64>) {
+        let points = self.objective_values();
+
+        let mut ranks = vec![0; points.len()];
+        let mut distances = vec![0.0; points.len()];
+        for (rank, front) in non_dominated_sort(&points, self.objective)
+            .into_iter()
+            .enumerate()
+        {
+            for (&index, distance) in front.iter().zip(crowding_distances(&points, &front)) {
+                ranks[index] = rank;
+                distances[index] = distance;
+            }
+        }
+        (ranks, distances)
+    }
+
+    /// Evaluates the objectives of every individual in parallel.
+    fn objective_values(&self) -> Vec<&[f64]> {
+        self.population
+            .par_iter()
+            .map(|individual| self.evaluate_objectives(individual))
+            .collect()
+    }
+
+    /// Evaluates the objectives of an individual if they have not been
+    /// evaluated already, keeping count of the number of evaluations
+    /// performed.
+    pub(crate) fn evaluate_objectives<'a>(&self, individual: &'a Individual<O>) -> &'a [f64] {
+        individual.objectives.get_or_init(|| {
+            self.evaluations.fetch_add(1, atomic::Ordering::Relaxed);
+            individual.organism().objectives()
+        })
+    }
+}
@@ -0,0 +1,438 @@
+use crate::parallel::*;
+use crate::{
+    genome::RealVector,
+    history::History,
+    termination::{RunSummary, Search, Termination},
+    Objective, RealFunction, Statistics,
+};
+use rand::{Rng, SeedableRng};
+use rand_pcg::Pcg64;
+use std::cmp::Ordering;
+
+/// The neighbourhoods through which particles share the best positions they
+/// have found.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Topology {
+    /// Every particle is drawn towards the best position found by the whole
+    /// swarm. This converges quickly, but the swarm can be drawn into a
+    /// local optimum together.
+    #[default]
+    Global,
+    /// The particles form a ring, and each is drawn towards the best
+    /// position found by itself and its two neighbours. Good positions
+    /// spread slowly around the ring, so the swarm explores for longer.
+    Ring,
+}
+
+/// A particle of a [`ParticleSwarm`].
+///
+/// [`ParticleSwarm`]: struct.ParticleSwarm.html
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Particle {
+    position: RealVector,
+    velocity: Vec<f64>,
+    fitness: f64,
+    best_position: RealVector,
+    best_fitness: f64,
+}
+
+impl Particle {
+    /// Returns the particle's current position.
+    pub fn position(&self) -> &RealVector {
+        &self.position
+    }
+
+    /// Returns the particle's current velocity.
+    pub fn velocity(&self) -> &[f64] {
+        &self.velocity
+    }
+
+    /// Returns the fitness of the particle's current position.
+    pub fn fitness(&self) -> f64 {
+        self.fitness
+    }
+
+    /// Returns the best position that the particle has visited.
+    pub fn best_position(&self) -> &RealVector {
+        &self.best_position
+    }
+
+    /// Returns the fitness of the best position that the particle has
+    /// visited.
+    pub fn best_fitness(&self) -> f64 {
+        self.best_fitness
+    }
+}
+
+/// Particle swarm optimisation (PSO), which optimises functions of
+/// real-valued vectors by flying a swarm of particles through the search
+/// space, each drawn towards the best positions found by itself and by its
+/// neighbours.
+///
+/// In every generation, each particle's velocity becomes the sum of three
+/// parts: its previous velocity scaled by the inertia weight, a pull towards
+/// its own best position scaled by the cognitive coefficient, and a pull
+/// towards its neighbourhood's best position scaled by the social
+/// coefficient. Both pulls are also scaled by random numbers drawn afresh
+/// for each variable. Particles then move by their velocity, and stop at the
+/// bounds of the search space, losing their velocity in that direction.
+///
+/// The swarm is created within the given bounds when the first generation is
+/// bred, with random velocities. By default, the inertia weight is 0.7298
+/// and both coefficients are 1.496 (the constriction coefficients of Clerc
+/// and Kennedy, which keep the swarm from exploding), the topology is
+/// global, fitness values are maximised, and the random number generator is
+/// seeded from the operating system. Positions are evaluated in parallel.
+///
+/// # Examples
+///
+/// ```rust
+/// use ecosystem::{
+///     benchmarks::Function,
+///     termination::{MaxGenerations, StopReason, TargetFitness},
+///     Objective, ParticleSwarm, Topology,
+/// };
+///
+/// let function = Function::Sphere;
+/// let bounds = vec![function.bounds(); 10];
+/// let mut swarm = ParticleSwarm::new(bounds, 30, move |point| function.evaluate(point))
+///     .topology(Topology::Ring)
+///     .objective(Objective::Minimize)
+///     .seed(0);
+/// let summary = swarm.run(TargetFitness(1e-8).or(MaxGenerations(2000)));
+///
+/// assert_eq!(summary.reason, StopReason::TargetFitness);
+/// assert!(swarm.best().unwrap().genes().iter().all(|x| x.abs() < 1e-3));
+/// ```
+pub struct ParticleSwarm {
+    fitness: RealFunction,
+    bounds: Vec<(f64, f64)>,
+    swarm_size: usize,
+    inertia: f64,
+    cognitive: f64,
+    social: f64,
+    topology: Topology,
+    objective: Objective,
+    rng: Pcg64,
+    particles: Vec<Particle>,
+    history: Option<History>,
+    generation: u32,
+    evaluations: u64,
+}
+
+impl ParticleSwarm {
+    /// Creates a search for the best input to `fitness` among vectors within
+    /// `bounds`, given as a (lower, upper) pair for each variable, with a
+    /// swarm of `swarm_size` particles.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bounds` is empty, if any bound is infinite or any lower
+    /// bound is greater than its upper bound, or if `swarm_size` is zero.
+    pub fn new<F>(bounds: Vec<(f64, f64)>, swarm_size: usize, fitness: F) -> Self
+    where
+        F: Fn(&[f64]) -> f64 + Send + Sync + 'static,
+    {
+        assert!(!bounds.is_empty(), "there must be at least one variable");
+        assert!(
+            bounds
+                .iter()
+                .all(|(lower, upper)| lower.is_finite() && upper.is_finite() && lower <= upper),
+            "the bounds must be finite, with no lower bound greater than its upper bound"
+        );
+        assert!(swarm_size > 0, "the swarm must hold at least one particle");
+        Self {
+            fitness: Box::new(fitness),
+            bounds,
+            swarm_size,
+            inertia: 0.7298,
+            cognitive: 1.496,
+            social: 1.496,
+            topology: Topology::default(),
+            objective: Objective::default(),
+            rng: Pcg64::from_entropy(),
+            particles: Vec::new(),
+            history: None,
+            generation: 0,
+            evaluations: 0,
+        }
+    }
+
+    /// Sets the inertia weight, by which each particle's velocity is scaled
+    /// before the pulls towards the best positions are added. Larger values
+    /// explore more widely; values of one or more let the swarm diverge.
+    pub fn inertia(mut self, weight: f64) -> Self {
+        self.inertia = weight;
+        self
+    }
+
+    /// Sets the cognitive coefficient, which scales the pull of each
+    /// particle towards the best position it has found itself.
+    pub fn cognitive(mut self, coefficient: f64) -> Self {
+        self.cognitive = coefficient;
+        self
+    }
+
+    /// Sets the social coefficient, which scales the pull of each particle
+    /// towards the best position found by its neighbourhood.
+    pub fn social(mut self, coefficient: f64) -> Self {
+        self.social = coefficient;
+        self
+    }
+
+    /// Sets the neighbourhoods through which particles share their best
+    /// positions.
+    pub fn topology(mut self, topology: Topology) -> Self {
+        self.topology = topology;
+        self
+    }
+
+    /// Sets the direction in which fitness values are optimised.
+    pub fn objective(mut self, objective: Objective) -> Self {
+        self.objective = objective;
+        self
+    }
+
+    /// Records the statistics of every generation, which can then be
+    /// inspected with [`history`].
+    ///
+    /// [`history`]: #method.history
+    pub fn record_history(mut self) -> Self {
+        self.history = Some(History::new());
+        self
+    }
+
+    /// Seeds the random number generator, making the search reproducible.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.rng = Pcg64::seed_from_u64(seed);
+        self
+    }
+
+    /// Returns the particles, which are absent until the first generation is
+    /// bred.
+    pub fn particles(&self) -> &[Particle] {
+        &self.particles
+    }
+
+    /// Returns the current generation number.
+    pub fn generation(&self) -> u32 {
+        self.generation
+    }
+
+    /// Returns the total number of fitness evaluations performed so far.
+    pub fn evaluations(&self) -> u64 {
+        self.evaluations
+    }
+
+    /// Returns the best position found by the swarm so far, or `None` before
+    /// the first generation.
+    pub fn best(&self) -> Option<&RealVector> {
+        self.fittest_particle()
+            .map(|particle| &self.particles[particle].best_position)
+    }
+
+    /// Returns the fitness of the best position found by the swarm so far,
+    /// or `None` before the first generation.
+    pub fn best_fitness(&self) -> Option<f64> {
+        self.fittest_particle()
+            .map(|particle| self.particles[particle].best_fitness)
+    }
+
+    /// Summarises the fitness values of the particles' current positions, or
+    /// returns `None` before the first generation.
+    pub fn statistics(&self) -> Option<Statistics> {
+        Statistics::from_fitnesses(self.particles.iter().map(Particle::fitness).collect())
+    }
+
+    /// Returns the recorded history of the search, if recording was enabled
+    /// with [`record_history`].
+    ///
+    /// [`record_history`]: #method.record_history
+    pub fn history(&self) -> Option<&History> {
+        self.history.as_ref()
+    }
+
+    /// Moves every particle by its velocity, after updating the velocity. The
+    /// swarm is created first, if it has not been already.
+    pub fn next_generation(&mut self) {
+        if self.particles.is_empty() {
+            self.initialise();
+        }
+
+        let guides: Vec<RealVector> = (0..self.particles.len())
+            .map(|particle| {
+                let guide = self.guide(particle);
+                self.particles[guide].best_position.clone()
+            })
+            .collect();
+        let (inertia, cognitive, social) = (self.inertia, self.cognitive, self.social);
+        for (particle, guide) in self.particles.iter_mut().zip(&guides) {
+            for variable in 0..particle.velocity.len() {
+                let position = particle.position.genes()[variable];
+                let own_pull = particle.best_position.genes()[variable] - position;
+                let social_pull = guide.genes()[variable] - position;
+                let velocity = inertia * particle.velocity[variable]
+                    + cognitive * self.rng.gen::<f64>() * own_pull
+                    + social * self.rng.gen::<f64>() * social_pull;
+                // Particles that hit a bound stop there
+                let (lower, upper) = particle.position.bounds()[variable];
+                let moved = position + velocity;
+                particle.velocity[variable] = if (lower..=upper).contains(&moved) {
+                    velocity
+                } else {
+                    0.0
+                };
+                particle.position.set(variable, moved);
+            }
+        }
+        self.evaluate();
+
+        self.generation += 1;
+        if let Some(statistics) = self.statistics() {
+            if let Some(history) = &mut self.history {
+                history.record(self.generation, statistics, self.objective);
+            }
+        }
+    }
+
+    /// Runs the search until the given termination condition is met, which
+    /// is checked before the first generation and after each generation.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ecosystem::{
+    ///     benchmarks::Function,
+    ///     termination::{MaxGenerations, StopReason},
+    ///     Objective, ParticleSwarm,
+    /// };
+    ///
+    /// let function = Function::Rastrigin;
+    /// let bounds = vec![function.bounds(); 2];
+    /// let mut swarm = ParticleSwarm::new(bounds, 20, move |point| function.evaluate(point))
+    ///     .objective(Objective::Minimize)
+    ///     .record_history()
+    ///     .seed(0);
+    /// let summary = swarm.run(MaxGenerations(100));
+    ///
+    /// assert_eq!(summary.reason, StopReason::MaxGenerations);
+    /// // The starting positions are evaluated along with every move
+    /// assert_eq!(summary.evaluations, 20 + 100 * 20);
+    /// assert_eq!(swarm.history().unwrap().records().len(), 100);
+    /// assert!(swarm.particles().iter().all(|particle| {
+    ///     particle.position().genes().iter().all(|x| x.abs() <= 5.12)
+    /// }));
+    /// ```
+    pub fn run<T: Termination<Self>>(&mut self, mut termination: T) -> RunSummary {
+        let (start_generation, start_evaluations) = (self.generation, self.evaluations);
+        let reason = loop {
+            if let Some(reason) = termination.check(self) {
+                break reason;
+            }
+            self.next_generation();
+        };
+        RunSummary {
+            reason,
+            generations: self.generation - start_generation,
+            evaluations: self.evaluations - start_evaluations,
+        }
+    }
+
+    /// Creates the swarm, placing each particle at random within the bounds,
+    /// and giving it a velocity towards another random point.
+    fn initialise(&mut self) {
+        let (bounds, rng) = (&self.bounds, &mut self.rng);
+        self.particles = (0..self.swarm_size)
+            .map(|_| {
+                let position = RealVector::random(bounds, rng);
+                let target = RealVector::random(bounds, rng);
+                let velocity = target
+                    .genes()
+                    .iter()
+                    .zip(position.genes())
+                    .map(|(to, from)| (to - from) / 2.0)
+                    .collect();
+                Particle {
+                    best_position: position.clone(),
+                    position,
+                    velocity,
+                    fitness: f64::NAN,
+                    best_fitness: f64::NAN,
+                }
+            })
+            .collect();
+        self.evaluate();
+    }
+
+    /// Evaluates the particles' current positions in parallel, updating
+    /// their best positions.
+    fn evaluate(&mut self) {
+        let fitness = &self.fitness;
+        let objective = self.objective;
+        self.particles.par_iter_mut().for_each(|particle| {
+            particle.fitness = fitness(particle.position.genes());
+            if particle.best_fitness.is_nan()
+                || objective.compare(particle.fitness, particle.best_fitness) == Ordering::Greater
+            {
+                particle.best_position = particle.position.clone();
+                particle.best_fitness = particle.fitness;
+            }
+        });
+        self.evaluations += self.particles.len() as u64;
+    }
+
+    /// Returns the index of the particle whose best position guides the
+    /// given particle: the best in its neighbourhood.
+    fn guide(&self, particle: usize) -> usize {
+        let len = self.particles.len();
+        match self.topology {
+            Topology::Global => self.fittest_particle().unwrap_or(particle),
+            Topology::Ring => {
+                let objective = self.objective;
+                [(particle + len - 1) % len, particle, (particle + 1) % len]
+                    .iter()
+                    .copied()
+                    .max_by(|&a, &b| {
+                        objective.compare(
+                            self.particles[a].best_fitness,
+                            self.particles[b].best_fitness,
+                        )
+                    })
+                    .unwrap_or(particle)
+            }
+        }
+    }
+
+    /// Returns the index of the particle that has found the best position.
+    fn fittest_particle(&self) -> Option<usize> {
+        let objective = self.objective;
+        (0..self.particles.len()).max_by(|&a, &b| {
+            objective.compare(
+                self.particles[a].best_fitness,
+                self.particles[b].best_fitness,
+            )
+        })
+    }
+}
+
+impl Search for ParticleSwarm {
+    type Fitness = f64;
+
+    fn generation(&self) -> u32 {
+        self.generation
+    }
+
+    fn evaluations(&self) -> u64 {
+        self.evaluations
+    }
+
+    fn objective(&self) -> Objective {
+        self.objective
+    }
+
+    fn best_fitness(&self) -> Option<f64> {
+        self.best_fitness()
+    }
+}
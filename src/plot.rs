@@ -0,0 +1,165 @@
+//! Plotting histories with plotters.
+use crate::history::{History, Record};
+use core::fmt;
+use plotters::{
+    coord::Shift,
+    prelude::{
+        BitMapBackend, ChartBuilder, DrawingArea, DrawingBackend, IntoDrawingArea, LineSeries,
+        PathElement, SVGBackend, SeriesLabelPosition, BLACK, BLUE, RED, WHITE,
+    },
+};
+use std::{error::Error, path::Path};
+
+/// The size of plots, in pixels.
+const SIZE: (u32, u32) = (1024, 640);
+
+impl History {
+    /// Plots the best and mean fitness of each generation, writing the
+    /// chart to the given file. Files ending in `.svg` are written as SVG,
+    /// and those ending in `.png`, `.jpg` or `.bmp` as bitmaps.
+    ///
+    /// Only available with the `plot` feature.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the format isn't supported, or if the chart
+    /// can't be drawn or written.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ecosystem::{termination::MaxGenerations, Ecosystem, Organism};
+    /// # use rand::Rng;
+    /// # struct Guess(f64);
+    /// # impl Organism for Guess {
+    /// #     type Fitness = f64;
+    /// #     fn fitness(&self) -> f64 { 1.0 / (42.0 - self.0).abs() }
+    /// #     fn breed(&self, other: &Self, rng: &mut impl Rng) -> Self { Guess((self.0 + other.0) / 2.0) }
+    /// #     fn mutate(&mut self, rate: f64, rng: &mut impl Rng) {
+    /// #         self.0 += rng.gen_range(-rate, rate);
+    /// #     }
+    /// # }
+    ///
+    /// let mut ecosystem = Ecosystem::builder()
+    ///     .organisms((0..20).map(|i| Guess(f64::from(i) * 5.0 + 0.5)))
+    ///     .record_history()
+    ///     .build()
+    ///     .unwrap();
+    /// ecosystem.run(MaxGenerations(50))?;
+    ///
+    /// let path = std::env::temp_dir().join("fitness.svg");
+    /// ecosystem.history().unwrap().plot(&path)?;
+    /// assert!(std::fs::read_to_string(&path)?.starts_with("<svg"));
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn plot(&self, path: impl AsRef<Path>) -> Result<(), PlotError> {
+        let path = path.as_ref();
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some(extension) if extension.eq_ignore_ascii_case("svg") => {
+                self.draw(SVGBackend::new(path, SIZE).into_drawing_area())
+            }
+            Some(extension) if image_format(extension) => {
+                self.draw(BitMapBackend::new(path, SIZE).into_drawing_area())
+            }
+            _ => Err(PlotError(
+                format!("unsupported image file {}", path.display()).into(),
+            )),
+        }
+    }
+
+    /// Draws the chart on the given drawing area.
+    fn draw<B: DrawingBackend>(&self, area: DrawingArea<B, Shift>) -> Result<(), PlotError>
+    where
+        B::ErrorType: 'static,
+    {
+        let records = self.records();
+        let first = records.first().map_or(0, |record| record.generation);
+        let last = records
+            .last()
+            .map_or(1, |record| record.generation.max(first + 1));
+        let (low, high) = records
+            .iter()
+            .flat_map(|record| [record.best, record.mean])
+            .filter(|fitness| fitness.is_finite())
+            .fold(
+                (f64::INFINITY, f64::NEG_INFINITY),
+                |(low, high), fitness| (low.min(fitness), high.max(fitness)),
+            );
+        let (low, high) = if low > high {
+            (0.0, 1.0)
+        } else {
+            // A margin keeps the curves off the edges of the chart
+            let margin = ((high - low) * 0.05).max(f64::EPSILON.max(high.abs() * 1e-9));
+            (low - margin, high + margin)
+        };
+
+        area.fill(&WHITE).map_err(PlotError::drawing)?;
+        let mut chart = ChartBuilder::on(&area)
+            .caption("Fitness", ("sans-serif", 24))
+            .margin(16)
+            .x_label_area_size(40)
+            .y_label_area_size(64)
+            .build_cartesian_2d(first..last, low..high)
+            .map_err(PlotError::drawing)?;
+        chart
+            .configure_mesh()
+            .x_desc("Generation")
+            .y_desc("Fitness")
+            .draw()
+            .map_err(PlotError::drawing)?;
+
+        for (label, colour, fitness) in [
+            ("Best", RED, (|record| record.best) as fn(&Record) -> f64),
+            ("Mean", BLUE, |record| record.mean),
+        ] {
+            chart
+                .draw_series(LineSeries::new(
+                    records
+                        .iter()
+                        .map(|record| (record.generation, fitness(record))),
+                    colour,
+                ))
+                .map_err(PlotError::drawing)?
+                .label(label)
+                .legend(move |(x, y)| PathElement::new([(x, y), (x + 20, y)], colour));
+        }
+        chart
+            .configure_series_labels()
+            .position(SeriesLabelPosition::LowerRight)
+            .background_style(WHITE)
+            .border_style(BLACK)
+            .draw()
+            .map_err(PlotError::drawing)?;
+        area.present().map_err(PlotError::drawing)
+    }
+}
+
+/// Returns whether the given file extension names a bitmap format that
+/// plots can be written in.
+fn image_format(extension: &str) -> bool {
+    ["png", "jpg", "jpeg", "bmp"]
+        .iter()
+        .any(|format| extension.eq_ignore_ascii_case(format))
+}
+
+/// An error arising from plotting a history.
+#[derive(Debug)]
+pub struct PlotError(Box<dyn Error + Send + Sync>);
+
+impl PlotError {
+    fn drawing(error: impl Error + Send + Sync + 'static) -> Self {
+        PlotError(Box::new(error))
+    }
+}
+
+impl fmt::Display for PlotError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "failed to plot the history: {}", self.0)
+    }
+}
+
+impl Error for PlotError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(self.0.as_ref())
+    }
+}
@@ -0,0 +1,138 @@
+//! Controllers that change the population size as a run progresses.
+//!
+//! A large population explores widely but costs many evaluations per
+//! generation, while a small one refines the best solutions cheaply. A
+//! [`PopulationControl`] registered with
+//! [`EcosystemBuilder::population_control`] chooses the population size
+//! before each generation is bred by [`Ecosystem::breed_next_generation`].
+//!
+//! The population shrinks by retiring its least fit individuals, and grows
+//! by breeding extra children from parents chosen by the selection strategy,
+//! before the generation itself is bred. The size is kept within the bounds
+//! that the population model allows: it always exceeds the number of elites
+//! and the children replaced in each step of a steady-state population, never
+//! exceeds the offspring (plus elites) of a (μ, λ) population, and remains a
+//! whole number of rows of a cellular one.
+//!
+//! [`PopulationControl`]: trait.PopulationControl.html
+//! [`EcosystemBuilder::population_control`]: ../struct.EcosystemBuilder.html#method.population_control
+//! [`Ecosystem::breed_next_generation`]: ../struct.Ecosystem.html#method.breed_next_generation
+use crate::{Ecosystem, Organism};
+use alloc::boxed::Box;
+#[cfg(not(feature = "std"))]
+#[allow(unused_imports)]
+use num_traits::Float;
+
+/// An interface for choosing the population size of each generation.
+pub trait PopulationControl<O: Organism>: Send + Sync {
+    /// Returns the number of organisms to breed the next generation from,
+    /// given the ecosystem as it stands (with every organism evaluated).
+    fn population_size(&mut self, ecosystem: &Ecosystem<O>) -> usize;
+}
+
+/// A population size that changes according to the generation number, such
+/// as one that starts large to explore widely and shrinks to refine the best
+/// solutions.
+///
+/// # Examples
+///
+/// ```rust
+/// use ecosystem::{population::Schedule, Ecosystem, Organism};
+/// # use rand::Rng;
+/// # struct Guess(f64);
+/// # impl Organism for Guess {
+/// #     type Fitness = f64;
+/// #     fn fitness(&self) -> f64 { 1.0 / (42.0 - self.0).abs() }
+/// #     fn breed(&self, other: &Self, rng: &mut impl Rng) -> Self { Guess((self.0 + other.0) / 2.0) }
+/// #     fn mutate(&mut self, rate: f64, rng: &mut impl Rng) {
+/// #         self.0 += rng.gen_range(-rate, rate);
+/// #     }
+/// # }
+///
+/// let schedule = Schedule::SawTooth { max: 100, min: 20, period: 5 };
+/// assert_eq!(schedule.size(0), 100);
+/// assert_eq!(schedule.size(4), 20);
+/// assert_eq!(schedule.size(5), 100);
+///
+/// let mut ecosystem = Ecosystem::builder()
+///     .organisms((0..100).map(|i| Guess(f64::from(i) + 0.5)))
+///     .population_control(Schedule::Linear { start: 100, end: 20, generations: 10 })
+///     .build()
+///     .unwrap();
+/// for _ in 0..20 {
+///     ecosystem.breed_next_generation()?;
+/// }
+///
+/// assert_eq!(ecosystem.individuals().len(), 20);
+/// # Ok::<(), ecosystem::EcosystemError>(())
+/// ```
+pub enum Schedule {
+    /// The same size in every generation.
+    Constant(usize),
+    /// A size that changes linearly from `start` to `end` over the given
+    /// number of generations, and stays at `end` from then on.
+    Linear {
+        /// The size in the first generation.
+        start: usize,
+        /// The size once the given number of generations have been bred.
+        end: usize,
+        /// The number of generations over which the size changes.
+        generations: u32,
+    },
+    /// A size that falls linearly from `max` to `min` over each period of
+    /// generations, then jumps back up to `max`. Each jump brings in a burst
+    /// of new children, renewing the search.
+    SawTooth {
+        /// The size at the start of each period.
+        max: usize,
+        /// The size at the end of each period.
+        min: usize,
+        /// The number of generations in each period.
+        period: u32,
+    },
+    /// A size given by a function of the generation number.
+    Custom(Box<dyn Fn(u32) -> usize + Send + Sync>),
+}
+
+impl Schedule {
+    /// Returns the population size for the given generation.
+    pub fn size(&self, generation: u32) -> usize {
+        match *self {
+            Schedule::Constant(size) => size,
+            Schedule::Linear {
+                start,
+                end,
+                generations,
+            } => {
+                let progress = if generations == 0 {
+                    1.0
+                } else {
+                    (f64::from(generation) / f64::from(generations)).min(1.0)
+                };
+                interpolate(start, end, progress)
+            }
+            Schedule::SawTooth { max, min, period } => {
+                let progress = if period <= 1 {
+                    0.0
+                } else {
+                    f64::from(generation % period) / f64::from(period - 1)
+                };
+                interpolate(max, min, progress)
+            }
+            Schedule::Custom(ref size) => size(generation),
+        }
+    }
+}
+
+impl<O: Organism + Send + Sync> PopulationControl<O> for Schedule {
+    fn population_size(&mut self, ecosystem: &Ecosystem<O>) -> usize {
+        self.size(ecosystem.generation())
+    }
+}
+
+/// Returns the size lying the given fraction of the way from `start` to
+/// `end`, rounded to the nearest whole organism.
+fn interpolate(start: usize, end: usize, progress: f64) -> usize {
+    let size = start as f64 * (1.0 - progress) + end as f64 * progress;
+    size.round() as usize
+}
@@ -0,0 +1,235 @@
+//! Reporting the progress of long runs.
+//!
+//! A [`ProgressReporter`] registered with [`EcosystemBuilder::progress`] is
+//! told how [`Ecosystem::run`] is going after every generation: the
+//! generation number, the best fitness, and, if the termination condition
+//! can say how close the run is to its end (as generation and evaluation
+//! limits can), the fraction of the run completed and an estimate of the
+//! time remaining. Closures taking a [`Progress`] are reporters:
+//!
+//! ```rust
+//! use ecosystem::{progress::Progress, termination::MaxGenerations, Ecosystem, Organism};
+//! # use rand::Rng;
+//! # struct Guess(f64);
+//! # impl Organism for Guess {
+//! #     type Fitness = f64;
+//! #     fn fitness(&self) -> f64 { 1.0 / (42.0 - self.0).abs() }
+//! #     fn breed(&self, other: &Self, rng: &mut impl Rng) -> Self { Guess((self.0 + other.0) / 2.0) }
+//! #     fn mutate(&mut self, rate: f64, rng: &mut impl Rng) {
+//! #         self.0 += rng.gen_range(-rate, rate);
+//! #     }
+//! # }
+//!
+//! let mut ecosystem = Ecosystem::builder()
+//!     .organisms((0..20).map(|i| Guess(f64::from(i) * 5.0 + 0.5)))
+//!     .progress(|progress: &Progress<f64>| {
+//!         if let Some(fraction) = progress.fraction {
+//!             println!("{:.0}% done, best {:?}", fraction * 100.0, progress.best);
+//!         }
+//!     })
+//!     .build()
+//!     .unwrap();
+//! ecosystem.run(MaxGenerations(50))?;
+//! # Ok::<(), ecosystem::EcosystemError>(())
+//! ```
+//!
+//! With the `indicatif` feature enabled, a [`ProgressBar`] draws the
+//! progress in the terminal.
+//!
+//! [`ProgressReporter`]: trait.ProgressReporter.html
+//! [`EcosystemBuilder::progress`]: ../struct.EcosystemBuilder.html#method.progress
+//! [`Ecosystem::run`]: ../struct.Ecosystem.html#method.run
+//! [`Progress`]: struct.Progress.html
+//! [`ProgressBar`]: struct.ProgressBar.html
+use crate::{history::Clock, termination::RunSummary};
+use core::time::Duration;
+
+/// How a run is going, as reported after each generation.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Progress<F> {
+    /// The current generation number.
+    pub generation: u32,
+    /// The number of generations bred since the run began.
+    pub generations: u32,
+    /// The best fitness of the current generation.
+    pub best: Option<F>,
+    /// The fraction of the run completed, from 0 to 1, if the termination
+    /// condition can tell.
+    pub fraction: Option<f64>,
+    /// The time since the run began. Without the `std` feature, or on
+    /// `wasm32-unknown-unknown`, there is no clock to measure it by, and it
+    /// is always zero.
+    pub elapsed: Duration,
+    /// The estimated time until the run ends, extrapolated from the rate of
+    /// progress so far, if the fraction completed is known.
+    pub eta: Option<Duration>,
+}
+
+/// An interface for reporting the progress of a run.
+pub trait ProgressReporter<F>: Send + Sync {
+    /// Called before the first generation of a run is bred.
+    fn start(&mut self, _progress: &Progress<F>) {}
+
+    /// Called after each generation is bred.
+    fn update(&mut self, progress: &Progress<F>);
+
+    /// Called once the run has ended.
+    fn finish(&mut self, _summary: &RunSummary) {}
+}
+
+impl<F, T: FnMut(&Progress<F>) + Send + Sync> ProgressReporter<F> for T {
+    fn update(&mut self, progress: &Progress<F>) {
+        self(progress)
+    }
+}
+
+/// Measures the progress of a single run.
+pub(crate) struct Tracker {
+    clock: Clock,
+    start_generation: u32,
+    start_fraction: Option<f64>,
+}
+
+impl Tracker {
+    pub(crate) fn start(generation: u32, fraction: Option<f64>) -> Self {
+        Self {
+            clock: Clock::starting_at(Duration::ZERO),
+            start_generation: generation,
+            start_fraction: fraction,
+        }
+    }
+
+    pub(crate) fn progress<F>(
+        &self,
+        generation: u32,
+        best: Option<F>,
+        fraction: Option<f64>,
+    ) -> Progress<F> {
+        let elapsed = self.clock.elapsed();
+        let eta = match (self.start_fraction, fraction) {
+            (_, Some(fraction)) if fraction >= 1.0 => Some(Duration::ZERO),
+            (Some(start), Some(fraction)) if fraction > start => {
+                let remaining = (1.0 - fraction) / (fraction - start);
+                Some(elapsed.mul_f64(remaining))
+            }
+            _ => None,
+        };
+        Progress {
+            generation,
+            generations: generation - self.start_generation,
+            best,
+            fraction: fraction.map(|fraction| fraction.clamp(0.0, 1.0)),
+            elapsed,
+            eta,
+        }
+    }
+}
+
+/// A progress bar drawn in the terminal with [indicatif], showing the
+/// fraction of the run completed, the estimated time remaining, the
+/// generation number and the best fitness.
+///
+/// If the termination condition can't tell how close the run is to its end,
+/// a spinner is shown instead of the bar.
+///
+/// # Examples
+///
+/// ```rust
+/// use ecosystem::{progress::ProgressBar, termination::MaxGenerations, Ecosystem, Organism};
+/// # use rand::Rng;
+/// # struct Guess(f64);
+/// # impl Organism for Guess {
+/// #     type Fitness = f64;
+/// #     fn fitness(&self) -> f64 { 1.0 / (42.0 - self.0).abs() }
+/// #     fn breed(&self, other: &Self, rng: &mut impl Rng) -> Self { Guess((self.0 + other.0) / 2.0) }
+/// #     fn mutate(&mut self, rate: f64, rng: &mut impl Rng) {
+/// #         self.0 += rng.gen_range(-rate, rate);
+/// #     }
+/// # }
+///
+/// let mut ecosystem = Ecosystem::builder()
+///     .organisms((0..20).map(|i| Guess(f64::from(i) * 5.0 + 0.5)))
+///     .progress(ProgressBar::new())
+///     .build()
+///     .unwrap();
+/// ecosystem.run(MaxGenerations(100))?;
+/// # Ok::<(), ecosystem::EcosystemError>(())
+/// ```
+///
+/// [indicatif]: https://docs.rs/indicatif
+#[cfg(feature = "indicatif")]
+pub struct ProgressBar {
+    bar: indicatif::ProgressBar,
+}
+
+#[cfg(feature = "indicatif")]
+impl ProgressBar {
+    /// The number of steps that the bar is divided into.
+    const STEPS: u64 = 1000;
+
+    /// Creates a progress bar, drawn to standard error.
+    pub fn new() -> Self {
+        Self::with_bar(indicatif::ProgressBar::new(Self::STEPS))
+    }
+
+    /// Creates a progress bar that draws with the given indicatif bar, such
+    /// as one added to a `MultiProgress`. Its length and style are replaced.
+    pub fn with_bar(bar: indicatif::ProgressBar) -> Self {
+        Self { bar }
+    }
+
+    /// Returns the underlying indicatif bar.
+    pub fn bar(&self) -> &indicatif::ProgressBar {
+        &self.bar
+    }
+}
+
+#[cfg(feature = "indicatif")]
+impl Default for ProgressBar {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "indicatif")]
+impl<F: crate::Fitness + core::fmt::Debug> ProgressReporter<F> for ProgressBar {
+    fn start(&mut self, progress: &Progress<F>) {
+        let template = if progress.fraction.is_some() {
+            "{wide_bar} {percent:>3}% (eta {msg})"
+        } else {
+            "{spinner} {elapsed_precise} {msg}"
+        };
+        // The templates are fixed, so they always parse
+        if let Ok(style) = indicatif::ProgressStyle::with_template(template) {
+            self.bar.set_style(style);
+        }
+        self.bar.set_length(Self::STEPS);
+        self.update(progress);
+    }
+
+    fn update(&mut self, progress: &Progress<F>) {
+        let mut message = alloc::format!("generation {}", progress.generation);
+        if let Some(best) = progress.best {
+            message = alloc::format!("{}, best {:?}", message, best);
+        }
+        match progress.fraction {
+            Some(fraction) => {
+                let eta = progress.eta.map_or_else(
+                    || alloc::string::String::from("?"),
+                    |eta| alloc::format!("{}", indicatif::HumanDuration(eta)),
+                );
+                self.bar.set_message(alloc::format!("{}) {}", eta, message));
+                self.bar
+                    .set_position((fraction * Self::STEPS as f64).round() as u64);
+            }
+            None => {
+                self.bar.set_message(message);
+                self.bar.tick();
+            }
+        }
+    }
+
+    fn finish(&mut self, _summary: &RunSummary) {
+        self.bar.finish();
+    }
+}
@@ -0,0 +1,309 @@
+//! Strategies for choosing the organisms that children replace.
+//!
+//! When only part of the population is replaced in each generation, as in a
+//! [steady-state] population or under a [generation gap], a [`Replacement`]
+//! decides which of the current organisms make way for the new children.
+//! Together with the [selection] strategy, which chooses the parents, it
+//! makes up the survivor selection of the generation loop.
+//!
+//! Besides the simple [`Replace`] policies, [`Crowding`] has each child
+//! replace the organism most similar to it, and [`RestrictedTournament`]
+//! has it do so only if it is fitter, which keeps distinct niches of the
+//! population from crowding each other out.
+//!
+//! [steady-state]: ../enum.PopulationModel.html#variant.SteadyState
+//! [generation gap]: ../struct.EcosystemBuilder.html#method.generation_gap
+//! [`Replacement`]: trait.Replacement.html
+//! [selection]: ../selection/index.html
+//! [`Replace`]: ../enum.Replace.html
+//! [`Crowding`]: struct.Crowding.html
+//! [`RestrictedTournament`]: struct.RestrictedTournament.html
+use crate::{Individual, Objective, Organism, Replace};
+use alloc::vec::Vec;
+use core::cmp::{Ordering, Reverse};
+use rand::{seq::SliceRandom, RngCore};
+
+/// An interface for replacing existing organisms with children.
+pub trait Replacement<O: Organism>: Send + Sync {
+    /// Puts the given children in place of individuals of the population,
+    /// returning the individuals that they displaced, along with any
+    /// children that were not admitted.
+    ///
+    /// Only the individuals whose indices are in `replaceable`, which is
+    /// ordered from least to most fit, may be replaced; the others are the
+    /// elites. Every individual, including every child, has been evaluated.
+    /// Children may also replace children admitted before them.
+    fn replace(
+        &self,
+        population: &mut [Individual<O>],
+        replaceable: &[usize],
+        children: Vec<Individual<O>>,
+        objective: Objective,
+        rng: &mut dyn RngCore,
+    ) -> Vec<Individual<O>>;
+}
+
+impl<O: Organism> Replacement<O> for Replace {
+    fn replace(
+        &self,
+        population: &mut [Individual<O>],
+        replaceable: &[usize],
+        children: Vec<Individual<O>>,
+        _objective: Objective,
+        rng: &mut dyn RngCore,
+    ) -> Vec<Individual<O>> {
+        let count = children.len();
+        let victims: Vec<_> = match self {
+            Replace::Worst => replaceable.iter().take(count).copied().collect(),
+            Replace::Random => replaceable.choose_multiple(rng, count).copied().collect(),
+            Replace::Oldest => {
+                let mut oldest_first = replaceable.to_vec();
+                oldest_first.sort_by_key(|&index| Reverse(population[index].age()));
+                oldest_first.truncate(count);
+                oldest_first
+            }
+        };
+        swap_in(population, victims, children)
+    }
+}
+
+/// Crowding replacement, in which each child replaces the organism most
+/// similar to it among `factor` (the crowding factor) organisms chosen at
+/// random, whatever their fitness.
+///
+/// As children tend to replace organisms of the same niche, niches of the
+/// population survive side by side, rather than the fittest taking over.
+/// Organisms that implement [`Distance`] can pass `O::distance` as the
+/// distance.
+///
+/// [`Distance`]: ../diversity/trait.Distance.html
+///
+/// # Examples
+///
+/// ```rust
+/// use ecosystem::{replacement::Crowding, Ecosystem, Organism, PopulationModel, Replace};
+/// # use rand::Rng;
+/// # struct Guess(f64);
+///
+/// // Equally good peaks at 10 and 30
+/// impl Organism for Guess {
+///     type Fitness = f64;
+///
+///     fn fitness(&self) -> f64 {
+///         let peak = |centre: f64| (5.0 - (self.0 - centre).abs()).max(0.0);
+///         peak(10.0) + peak(30.0)
+///     }
+/// #   fn breed(&self, other: &Self, rng: &mut impl Rng) -> Self {
+/// #       Guess(if rng.gen() { self.0 } else { other.0 })
+/// #   }
+/// #   fn mutate(&mut self, rate: f64, rng: &mut impl Rng) {
+/// #       self.0 += rng.gen_range(-rate, rate);
+/// #   }
+/// }
+///
+/// let mut ecosystem = Ecosystem::builder()
+///     .organisms((0..40).map(|i| Guess(f64::from(i) + 0.5)))
+///     .population_model(PopulationModel::SteadyState {
+///         children: 4,
+///         replace: Replace::Worst,
+///     })
+///     .replacement(Crowding {
+///         distance: |a: &Guess, b: &Guess| (a.0 - b.0).abs(),
+///         factor: 5,
+///     })
+///     .seed(0)
+///     .build()
+///     .unwrap();
+/// for _ in 0..200 {
+///     ecosystem.breed_next_generation()?;
+/// }
+///
+/// // Both peaks are still occupied
+/// let near = |centre: f64| {
+///     ecosystem
+///         .organisms()
+///         .filter(|guess| (guess.0 - centre).abs() < 5.0)
+///         .count()
+/// };
+/// assert!(near(10.0) > 5 && near(30.0) > 5);
+/// # Ok::<(), ecosystem::EcosystemError>(())
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct Crowding<D> {
+    /// Measures the distance between two organisms.
+    pub distance: D,
+    /// The number of organisms that each child is compared with, which is
+    /// at least one.
+    pub factor: usize,
+}
+
+impl<O, D> Replacement<O> for Crowding<D>
+where
+    O: Organism,
+    D: Fn(&O, &O) -> f64 + Send + Sync,
+{
+    fn replace(
+        &self,
+        population: &mut [Individual<O>],
+        replaceable: &[usize],
+        children: Vec<Individual<O>>,
+        _objective: Objective,
+        rng: &mut dyn RngCore,
+    ) -> Vec<Individual<O>> {
+        let mut retired = Vec::new();
+        for child in children {
+            let nearest = nearest(
+                &self.distance,
+                population,
+                replaceable,
+                &child,
+                self.factor,
+                rng,
+            );
+            match nearest {
+                Some(index) => retired.push(core::mem::replace(&mut population[index], child)),
+                None => retired.push(child),
+            }
+        }
+        retired
+    }
+}
+
+/// Restricted tournament selection (RTS), in which each child is compared
+/// with the organism most similar to it among `window` organisms chosen at
+/// random, and replaces it only if the child is fitter.
+///
+/// As with [`Crowding`], children compete within their own niche, so the
+/// population can hold several optima at once rather than converging on
+/// one; unlike it, a niche's best organisms are only ever replaced by
+/// better ones. Larger windows find closer matches, at the cost of more
+/// distance calculations. Organisms that implement [`Distance`] can pass
+/// `O::distance` as the distance.
+///
+/// [`Crowding`]: struct.Crowding.html
+/// [`Distance`]: ../diversity/trait.Distance.html
+///
+/// # Examples
+///
+/// ```rust
+/// use ecosystem::{
+///     replacement::{Replacement, RestrictedTournament},
+///     Individual, Objective,
+/// };
+/// # use ecosystem::Organism;
+/// # use rand::Rng;
+/// # struct Guess(f64);
+/// # impl Organism for Guess {
+/// #     type Fitness = f64;
+/// #     fn fitness(&self) -> f64 {
+/// #         let peak = |centre: f64| (5.0 - (self.0 - centre).abs()).max(0.0);
+/// #         peak(10.0) + peak(30.0)
+/// #     }
+/// #     fn breed(&self, other: &Self, rng: &mut impl Rng) -> Self { Guess(self.0) }
+/// #     fn mutate(&mut self, rate: f64, rng: &mut impl Rng) {}
+/// # }
+///
+/// let rts = RestrictedTournament {
+///     distance: |a: &Guess, b: &Guess| (a.0 - b.0).abs(),
+///     window: 2,
+/// };
+///
+/// // With peaks at 10 and 30, one guess is near the first and one is on
+/// // the second
+/// let mut population = vec![Individual::evaluated(Guess(8.0)), Individual::evaluated(Guess(30.0))];
+/// let children = vec![Individual::evaluated(Guess(9.5)), Individual::evaluated(Guess(28.0))];
+/// let retired = rts.replace(
+///     &mut population,
+///     &[0, 1],
+///     children,
+///     Objective::Maximize,
+///     &mut rand::thread_rng(),
+/// );
+///
+/// // The first child improves on its niche, but the second is turned away
+/// // rather than displacing the better guess on its peak
+/// let guesses: Vec<_> = population.iter().map(|individual| individual.organism().0).collect();
+/// assert_eq!(guesses, vec![9.5, 30.0]);
+/// let retired: Vec<_> = retired.iter().map(|individual| individual.organism().0).collect();
+/// assert_eq!(retired, vec![8.0, 28.0]);
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct RestrictedTournament<D> {
+    /// Measures the distance between two organisms.
+    pub distance: D,
+    /// The number of organisms that each child is compared with, which is
+    /// at least one.
+    pub window: usize,
+}
+
+impl<O, D> Replacement<O> for RestrictedTournament<D>
+where
+    O: Organism,
+    D: Fn(&O, &O) -> f64 + Send + Sync,
+{
+    fn replace(
+        &self,
+        population: &mut [Individual<O>],
+        replaceable: &[usize],
+        children: Vec<Individual<O>>,
+        objective: Objective,
+        rng: &mut dyn RngCore,
+    ) -> Vec<Individual<O>> {
+        let mut retired = Vec::new();
+        for child in children {
+            let nearest = nearest(
+                &self.distance,
+                population,
+                replaceable,
+                &child,
+                self.window,
+                rng,
+            );
+            match nearest {
+                Some(index)
+                    if objective.compare(child.fitness(), population[index].fitness())
+                        == Ordering::Greater =>
+                {
+                    retired.push(core::mem::replace(&mut population[index], child));
+                }
+                _ => retired.push(child),
+            }
+        }
+        retired
+    }
+}
+
+/// Returns the index of the individual most similar to the child among
+/// `count` (at least one) of the replaceable individuals chosen at random,
+/// or `None` if there are none.
+fn nearest<O: Organism>(
+    distance: impl Fn(&O, &O) -> f64,
+    population: &[Individual<O>],
+    replaceable: &[usize],
+    child: &Individual<O>,
+    count: usize,
+    rng: &mut dyn RngCore,
+) -> Option<usize> {
+    let apart = |&index: &usize| distance(child.organism(), population[index].organism());
+    replaceable
+        .choose_multiple(rng, count.max(1))
+        .copied()
+        .min_by(|a, b| apart(a).total_cmp(&apart(b)))
+}
+
+/// Puts each child in place of the individual at the corresponding index,
+/// returning the displaced individuals and any children left over.
+fn swap_in<O: Organism>(
+    population: &mut [Individual<O>],
+    victims: Vec<usize>,
+    mut children: Vec<Individual<O>>,
+) -> Vec<Individual<O>> {
+    let unplaced = children.split_off(victims.len().min(children.len()));
+    let mut retired: Vec<_> = victims
+        .into_iter()
+        .zip(children)
+        .map(|(victim, child)| core::mem::replace(&mut population[victim], child))
+        .collect();
+    retired.extend(unplaced);
+    retired
+}
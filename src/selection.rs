@@ -0,0 +1,1014 @@
+//! Strategies for selecting the organisms that get to breed.
+use crate::parallel::*;
+use crate::{
+    Constrained, ConstraintHandling, EcosystemError, Fitness, Individual, Objective, Organism,
+};
+use alloc::{string::ToString, vec, vec::Vec};
+#[cfg(not(feature = "std"))]
+#[allow(unused_imports)]
+use num_traits::Float;
+use rand::{
+    distributions::{Distribution, WeightedError, WeightedIndex},
+    seq::SliceRandom,
+    Rng, RngCore,
+};
+
+/// An interface for choosing parents from a population.
+///
+/// Strategies pick parents in batches, which allows any per-generation
+/// preparation (such as sorting the population) to be done just once.
+pub trait Selection<O: Organism>: Send + Sync {
+    /// Selects `count` parents from the population, returning their indices.
+    /// Parents are bred in the order they are returned, the first with the
+    /// second, the third with the fourth and so on, so strategies may choose
+    /// each parent's mate.
+    ///
+    /// Fitter individuals are those whose fitness is better with respect to
+    /// the given objective. The same individual may be selected any number
+    /// of times.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if parents are requested from an empty
+    /// population, or if the strategy cannot handle the population's fitness
+    /// values or its own parameters.
+    fn select(
+        &self,
+        population: &[Individual<O>],
+        objective: Objective,
+        count: usize,
+        rng: &mut dyn RngCore,
+    ) -> Result<Vec<usize>, EcosystemError>;
+}
+
+/// Fitness-proportionate selection, in which an organism's chance of being
+/// selected is proportional to its fitness.
+///
+/// This is the default selection strategy. When maximising, it requires
+/// fitness values to be non-negative; when minimising, each organism is
+/// weighted by how much better it is than the worst. The sampling
+/// distribution is built once per generation, so each selection takes
+/// constant time regardless of how skewed the fitness values are.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct FitnessProportionate;
+
+impl<O: Organism> Selection<O> for FitnessProportionate {
+    /// # Errors
+    ///
+    /// An error is returned if any fitness value is negative, infinite or
+    /// NaN.
+    fn select(
+        &self,
+        population: &[Individual<O>],
+        objective: Objective,
+        count: usize,
+        rng: &mut dyn RngCore,
+    ) -> Result<Vec<usize>, EcosystemError> {
+        if count == 0 {
+            return Ok(Vec::new());
+        }
+
+        let weights = objective.weights(
+            population
+                .iter()
+                .map(|individual| individual.fitness().to_f64()),
+        );
+        select_by_weight(weights, count, rng)
+    }
+}
+
+/// Selects `count` organisms, each with a chance proportional to its weight.
+fn select_by_weight(
+    weights: Vec<f64>,
+    count: usize,
+    rng: &mut dyn RngCore,
+) -> Result<Vec<usize>, EcosystemError> {
+    let roulette = Roulette::new(weights)?;
+    Ok((0..count).map(|_| roulette.spin(rng)).collect())
+}
+
+/// Fitness-proportionate selection with *fitness sharing*, which lets
+/// several niches of the population survive side by side, rather than the
+/// whole population converging on a single optimum.
+///
+/// Each organism's weight (as for [`FitnessProportionate`]) is divided by its
+/// *niche count*: the sum, over every organism within `radius` of it
+/// (including itself), of `1 - (d / radius)^alpha`, where `d` is the
+/// `distance` between them. Crowded organisms therefore share their fitness
+/// with their neighbors, and isolated ones are favoured.
+///
+/// The niche counts take a number of distance calculations that grows with
+/// the square of the population size, though they are spread across
+/// threads.
+///
+/// [`FitnessProportionate`]: struct.FitnessProportionate.html
+///
+/// # Examples
+///
+/// ```rust
+/// use ecosystem::{
+///     selection::{FitnessSharing, Selection},
+///     Individual, Objective,
+/// };
+/// # use ecosystem::Organism;
+/// # use rand::Rng;
+/// # struct Guess(f64);
+/// # impl Organism for Guess {
+/// #     type Fitness = f64;
+/// #     fn fitness(&self) -> f64 {
+/// #         let peak = |centre: f64| (5.0 - (self.0 - centre).abs()).max(0.0);
+/// #         peak(10.0) + peak(30.0)
+/// #     }
+/// #     fn breed(&self, other: &Self, rng: &mut impl Rng) -> Self { Guess(self.0) }
+/// #     fn mutate(&mut self, rate: f64, rng: &mut impl Rng) {}
+/// # }
+///
+/// let sharing = FitnessSharing {
+///     distance: |a: &Guess, b: &Guess| (a.0 - b.0).abs(),
+///     radius: 5.0,
+///     alpha: 1.0,
+/// };
+///
+/// // Four guesses crowd the peak at 10, and one has the peak at 30 to itself
+/// let guesses = [10.0, 10.0, 10.0, 10.0, 30.0];
+/// let population: Vec<_> = guesses.iter().map(|&guess| Individual::evaluated(Guess(guess))).collect();
+/// let selected = sharing.select(&population, Objective::Maximize, 1000, &mut rand::thread_rng())?;
+///
+/// // The lone guess is selected about as often as the other four together
+/// let lone = selected.iter().filter(|&&index| index == 4).count();
+/// assert!(lone > 400 && lone < 600);
+/// # Ok::<(), ecosystem::EcosystemError>(())
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct FitnessSharing<D> {
+    /// Measures the distance between two organisms.
+    pub distance: D,
+    /// The distance within which organisms share their fitness.
+    pub radius: f64,
+    /// The shape of the sharing function: 1.0 falls off linearly with
+    /// distance, while larger values share less with all but the closest
+    /// neighbors.
+    pub alpha: f64,
+}
+
+impl<O, D> Selection<O> for FitnessSharing<D>
+where
+    O: Organism + Sync,
+    D: Fn(&O, &O) -> f64 + Send + Sync,
+{
+    /// # Errors
+    ///
+    /// An error is returned if the radius is not positive, or if any
+    /// fitness value is negative, infinite or NaN.
+    fn select(
+        &self,
+        population: &[Individual<O>],
+        objective: Objective,
+        count: usize,
+        rng: &mut dyn RngCore,
+    ) -> Result<Vec<usize>, EcosystemError> {
+        if self.radius.is_nan() || self.radius <= 0.0 {
+            return Err(EcosystemError::InvalidSharingRadius(self.radius));
+        }
+        if count == 0 {
+            return Ok(Vec::new());
+        }
+
+        let weights = objective.weights(
+            population
+                .iter()
+                .map(|individual| individual.fitness().to_f64()),
+        );
+        let weights = population
+            .par_iter()
+            .zip(weights)
+            .map(|(individual, weight)| {
+                let niche_count: f64 = population
+                    .iter()
+                    .map(|other| (self.distance)(individual.organism(), other.organism()))
+                    .filter(|&distance| distance < self.radius)
+                    .map(|distance| 1.0 - (distance / self.radius).powf(self.alpha))
+                    .sum();
+                // An organism is always in its own niche, even if the distance
+                // function disagrees
+                weight / niche_count.max(1.0)
+            })
+            .collect();
+        select_by_weight(weights, count, rng)
+    }
+}
+
+/// Tournament selection, in which each parent is the fittest of a number of
+/// randomly chosen organisms.
+///
+/// Unlike fitness-proportionate selection, this only depends on how
+/// organisms compare to each other, so it continues to work well when fitness
+/// values are nearly uniform or negative. Larger tournaments increase the
+/// selection pressure.
+///
+/// # Examples
+///
+/// ```rust
+/// use ecosystem::selection::Tournament;
+/// # use ecosystem::{Ecosystem, Organism};
+/// # use rand::Rng;
+/// # struct Number(u32);
+/// # impl Organism for Number {
+/// #     type Fitness = f64;
+/// #     fn fitness(&self) -> f64 { f64::from(self.0) }
+/// #     fn breed(&self, other: &Self, rng: &mut impl Rng) -> Self { Number(self.0) }
+/// #     fn mutate(&mut self, rate: f64, rng: &mut impl Rng) {}
+/// # }
+/// # let organisms = (0..10).map(Number);
+///
+/// let ecosystem = Ecosystem::builder()
+///     .organisms(organisms)
+///     .selection(Tournament { size: 3 })
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct Tournament {
+    /// The number of organisms competing in each tournament.
+    pub size: usize,
+}
+
+impl<O: Organism> Selection<O> for Tournament {
+    /// # Errors
+    ///
+    /// An error is returned if the tournament size is zero.
+    fn select(
+        &self,
+        population: &[Individual<O>],
+        objective: Objective,
+        count: usize,
+        rng: &mut dyn RngCore,
+    ) -> Result<Vec<usize>, EcosystemError> {
+        if self.size == 0 {
+            return Err(EcosystemError::InvalidTournamentSize);
+        }
+        if count > 0 && population.is_empty() {
+            return Err(EcosystemError::EmptyPopulation);
+        }
+
+        Ok((0..count)
+            .map(|_| {
+                (0..self.size)
+                    .map(|_| rng.gen_range(0, population.len()))
+                    .max_by(|&a, &b| {
+                        objective.compare(population[a].fitness(), population[b].fitness())
+                    })
+                    .unwrap()
+            })
+            .collect())
+    }
+}
+
+/// Tournament selection for [constrained] organisms, in which each parent is
+/// the best of a number of randomly chosen organisms, taking their
+/// constraint violations into account as given by `handling`.
+///
+/// [constrained]: ../trait.Constrained.html
+///
+/// # Examples
+///
+/// ```rust
+/// use ecosystem::{
+///     selection::ConstrainedTournament, Constrained, ConstraintHandling, Ecosystem, Organism,
+/// };
+/// # use rand::Rng;
+/// # struct Guess(f64);
+/// # impl Organism for Guess {
+/// #     type Fitness = f64;
+/// #     fn fitness(&self) -> f64 { self.0 }
+/// #     fn breed(&self, other: &Self, rng: &mut impl Rng) -> Self { Guess((self.0 + other.0) / 2.0) }
+/// #     fn mutate(&mut self, rate: f64, rng: &mut impl Rng) {
+/// #         self.0 += rng.gen_range(-rate, rate);
+/// #     }
+/// # }
+///
+/// // The largest guess wins, but only guesses up to 42 are allowed
+/// impl Constrained for Guess {
+///     fn violations(&self) -> f64 {
+///         (self.0 - 42.0).max(0.0)
+///     }
+/// }
+///
+/// let mut ecosystem = Ecosystem::builder()
+///     .organisms((0..100).map(|i| Guess(f64::from(i))))
+///     .mutation_rate(0.5)
+///     .selection(ConstrainedTournament {
+///         size: 3,
+///         handling: ConstraintHandling::FeasibilityFirst,
+///     })
+///     .build()
+///     .unwrap();
+/// for _ in 0..30 {
+///     ecosystem.breed_next_generation()?;
+/// }
+///
+/// let best_feasible = ecosystem
+///     .organisms()
+///     .filter(|guess| guess.violations() == 0.0)
+///     .map(|guess| guess.0)
+///     .fold(f64::NEG_INFINITY, f64::max);
+/// assert!(best_feasible > 41.0);
+/// # Ok::<(), ecosystem::EcosystemError>(())
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct ConstrainedTournament {
+    /// The number of organisms competing in each tournament.
+    pub size: usize,
+    /// How constraint violations are weighed against fitness.
+    pub handling: ConstraintHandling,
+}
+
+impl<O: Organism + Constrained> Selection<O> for ConstrainedTournament {
+    /// # Errors
+    ///
+    /// An error is returned if the tournament size is zero.
+    fn select(
+        &self,
+        population: &[Individual<O>],
+        objective: Objective,
+        count: usize,
+        rng: &mut dyn RngCore,
+    ) -> Result<Vec<usize>, EcosystemError> {
+        if self.size == 0 {
+            return Err(EcosystemError::InvalidTournamentSize);
+        }
+        if count > 0 && population.is_empty() {
+            return Err(EcosystemError::EmptyPopulation);
+        }
+
+        let scores: Vec<_> = population
+            .iter()
+            .map(|individual| (individual.fitness(), individual.organism().violations()))
+            .collect();
+        Ok((0..count)
+            .map(|_| {
+                (0..self.size)
+                    .map(|_| rng.gen_range(0, population.len()))
+                    .max_by(|&a, &b| self.handling.compare(objective, scores[a], scores[b]))
+                    .unwrap()
+            })
+            .collect())
+    }
+}
+
+/// An interface for organisms that are judged on a number of test cases,
+/// such as the input and output pairs of a program synthesis problem.
+///
+/// Implementing this allows an organism to be selected by [`Lexicase`]
+/// selection, which rewards organisms that solve cases that others fail,
+/// rather than only those with the best total.
+///
+/// [`Lexicase`]: struct.Lexicase.html
+pub trait TestCases {
+    /// Returns the organism's error on each test case, where zero means that
+    /// it is solved and smaller errors are always better, whatever the
+    /// ecosystem's objective.
+    ///
+    /// Every organism must return the same number of errors, in the same
+    /// order. Unlike [fitness] values, the errors are not cached, but asked
+    /// for afresh each time parents are selected, so organisms whose cases
+    /// are costly to run may want to store their errors.
+    ///
+    /// [fitness]: ../trait.Organism.html#tymethod.fitness
+    fn case_errors(&self) -> Vec<f64>;
+}
+
+/// Lexicase selection, in which each parent is chosen by considering the
+/// test cases one at a time, in a random order, and keeping only the
+/// organisms with the lowest error on each until one remains.
+///
+/// Because every parent is selected with a different ordering of the cases,
+/// organisms that excel on a few hard cases get to breed even if their total
+/// error is poor, which keeps specialists in the population and tends to
+/// solve problems with many cases better than selection on an aggregate
+/// fitness. Organisms' fitness values are ignored, though they are still
+/// used for elitism and to report the fittest organism.
+///
+/// With continuous errors, few organisms tie exactly on any case, so the
+/// `epsilon` tolerance keeps every organism whose error is within it of the
+/// best (epsilon-lexicase selection). A tolerance of zero gives the standard
+/// algorithm.
+///
+/// Each selection takes time proportional to the population size multiplied
+/// by the number of cases in the worst case, though the candidates usually
+/// dwindle after a few cases.
+///
+/// # Examples
+///
+/// ```rust
+/// use ecosystem::{
+///     selection::{Lexicase, TestCases},
+///     Ecosystem, Objective, Organism,
+/// };
+/// # use rand::Rng;
+///
+/// // Guesses at a sequence of digits, each position being a test case
+/// const TARGET: [u8; 8] = [3, 1, 4, 1, 5, 9, 2, 6];
+///
+/// struct Digits(Vec<u8>);
+///
+/// impl TestCases for Digits {
+///     fn case_errors(&self) -> Vec<f64> {
+///         self.0
+///             .iter()
+///             .zip(&TARGET)
+///             .map(|(&digit, &target)| f64::from(digit.max(target) - digit.min(target)))
+///             .collect()
+///     }
+/// }
+///
+/// impl Organism for Digits {
+///     type Fitness = f64;
+///
+///     fn fitness(&self) -> f64 {
+///         self.case_errors().iter().sum()
+///     }
+/// #   fn breed(&self, other: &Self, rng: &mut impl Rng) -> Self {
+/// #       let digits = self.0.iter().zip(&other.0);
+/// #       Digits(digits.map(|(&a, &b)| if rng.gen() { a } else { b }).collect())
+/// #   }
+/// #   fn mutate(&mut self, rate: f64, rng: &mut impl Rng) {
+/// #       for digit in &mut self.0 {
+/// #           if rng.gen::<f64>() < rate {
+/// #               *digit = rng.gen_range(0, 10);
+/// #           }
+/// #       }
+/// #   }
+/// }
+///
+/// let mut ecosystem = Ecosystem::builder()
+///     .organisms((0..50).map(|i| Digits(vec![i % 10; 8])))
+///     .objective(Objective::Minimize)
+///     .mutation_rate(0.05)
+///     .selection(Lexicase { epsilon: 0.0 })
+///     .seed(0)
+///     .build()
+///     .unwrap();
+/// for _ in 0..50 {
+///     ecosystem.breed_next_generation()?;
+/// }
+///
+/// assert_eq!(ecosystem.fittest().unwrap().0, TARGET);
+/// # Ok::<(), ecosystem::EcosystemError>(())
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct Lexicase {
+    /// How far above the lowest error on a case an organism's error may be
+    /// for it to remain a candidate.
+    pub epsilon: f64,
+}
+
+impl<O: Organism + TestCases + Sync> Selection<O> for Lexicase {
+    /// # Errors
+    ///
+    /// An error is returned if the tolerance is negative or NaN, or if the
+    /// organisms have different numbers of test cases.
+    fn select(
+        &self,
+        population: &[Individual<O>],
+        _objective: Objective,
+        count: usize,
+        rng: &mut dyn RngCore,
+    ) -> Result<Vec<usize>, EcosystemError> {
+        if self.epsilon.is_nan() || self.epsilon < 0.0 {
+            return Err(EcosystemError::InvalidLexicaseEpsilon(self.epsilon));
+        }
+        if count == 0 {
+            return Ok(Vec::new());
+        }
+        if population.is_empty() {
+            return Err(EcosystemError::EmptyPopulation);
+        }
+
+        let errors: Vec<_> = population
+            .par_iter()
+            .map(|individual| individual.organism().case_errors())
+            .collect();
+        let cases = errors[0].len();
+        if errors.iter().any(|errors| errors.len() != cases) {
+            return Err(EcosystemError::UnsuitableFitness(
+                "organisms have different numbers of test cases".to_string(),
+            ));
+        }
+
+        let mut order: Vec<_> = (0..cases).collect();
+        let mut selected = Vec::with_capacity(count);
+        for _ in 0..count {
+            order.shuffle(rng);
+            let mut candidates: Vec<_> = (0..population.len()).collect();
+            for &case in &order {
+                if candidates.len() == 1 {
+                    break;
+                }
+                let best = candidates
+                    .iter()
+                    .map(|&candidate| errors[candidate][case])
+                    .fold(f64::INFINITY, f64::min);
+                // NaN errors are never within the tolerance, so a case on
+                // which every candidate has one is skipped
+                let within = |&candidate: &usize| errors[candidate][case] <= best + self.epsilon;
+                if candidates.iter().any(within) {
+                    candidates.retain(within);
+                }
+            }
+            selected.push(*candidates.choose(rng).unwrap());
+        }
+        Ok(selected)
+    }
+}
+
+/// Linear ranking selection, in which an organism's chance of being selected
+/// depends linearly on its rank in the population, rather than on the
+/// magnitude of its fitness.
+///
+/// This stops a single, exceptionally fit organism from dominating
+/// reproduction. The `pressure` ranges from 1.0 (every organism is equally
+/// likely to be chosen) to 2.0 (the least fit organism is never chosen).
+///
+/// # Examples
+///
+/// ```rust
+/// use ecosystem::{selection::{LinearRank, Selection}, Individual, Objective};
+/// # use ecosystem::Organism;
+/// # use rand::Rng;
+/// # struct Number(f64);
+/// # impl Organism for Number {
+/// #     type Fitness = f64;
+/// #     fn fitness(&self) -> f64 { self.0 }
+/// #     fn breed(&self, other: &Self, rng: &mut impl Rng) -> Self { Number(self.0) }
+/// #     fn mutate(&mut self, rate: f64, rng: &mut impl Rng) {}
+/// # }
+///
+/// // One organism is a thousand times fitter than the rest
+/// let population: Vec<_> = [1.0, 1.0, 1.0, 1000.0]
+///     .iter()
+///     .map(|&fitness| Individual::evaluated(Number(fitness)))
+///     .collect();
+/// let selected = LinearRank { pressure: 2.0 }.select(
+///     &population,
+///     Objective::Maximize,
+///     1000,
+///     &mut rand::thread_rng(),
+/// )?;
+///
+/// // The fittest is chosen half of the time, rather than nearly always
+/// let outliers = selected.iter().filter(|&&index| index == 3).count();
+/// assert!(outliers > 400 && outliers < 600);
+/// # Ok::<(), ecosystem::EcosystemError>(())
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct LinearRank {
+    /// The expected number of times the fittest organism is selected, per
+    /// organism in the population.
+    pub pressure: f64,
+}
+
+impl<O: Organism> Selection<O> for LinearRank {
+    /// # Errors
+    ///
+    /// An error is returned if the pressure lies outside of the range [1, 2].
+    fn select(
+        &self,
+        population: &[Individual<O>],
+        objective: Objective,
+        count: usize,
+        rng: &mut dyn RngCore,
+    ) -> Result<Vec<usize>, EcosystemError> {
+        if !(1.0..=2.0).contains(&self.pressure) {
+            return Err(EcosystemError::InvalidSelectionPressure(self.pressure));
+        }
+
+        // A lone organism is the fittest, rather than the least fit
+        let last_rank = population.len().saturating_sub(1);
+        select_by_rank(population, objective, count, rng, |rank| {
+            if last_rank == 0 {
+                return 1.0;
+            }
+            (2.0 - self.pressure) + 2.0 * (self.pressure - 1.0) * rank as f64 / last_rank as f64
+        })
+    }
+}
+
+/// Exponential ranking selection, in which each organism is `base` times as
+/// likely to be chosen as the next fittest.
+///
+/// Smaller bases result in a higher selection pressure.
+///
+/// # Examples
+///
+/// ```rust
+/// use ecosystem::{selection::{ExponentialRank, Selection}, Individual, Objective};
+/// # use ecosystem::Organism;
+/// # use rand::Rng;
+/// # struct Number(f64);
+/// # impl Organism for Number {
+/// #     type Fitness = f64;
+/// #     fn fitness(&self) -> f64 { self.0 }
+/// #     fn breed(&self, other: &Self, rng: &mut impl Rng) -> Self { Number(self.0) }
+/// #     fn mutate(&mut self, rate: f64, rng: &mut impl Rng) {}
+/// # }
+///
+/// // One organism is a thousand times fitter than the rest
+/// let population: Vec<_> = [1.0, 1.0, 1.0, 1000.0]
+///     .iter()
+///     .map(|&fitness| Individual::evaluated(Number(fitness)))
+///     .collect();
+/// let selected = ExponentialRank { base: 0.5 }.select(
+///     &population,
+///     Objective::Maximize,
+///     1000,
+///     &mut rand::thread_rng(),
+/// )?;
+///
+/// // The fittest is chosen with a chance of 8 in 15, as it ranks highest
+/// let outliers = selected.iter().filter(|&&index| index == 3).count();
+/// assert!(outliers > 430 && outliers < 640);
+/// # Ok::<(), ecosystem::EcosystemError>(())
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct ExponentialRank {
+    /// The ratio between the selection weights of adjacently ranked
+    /// organisms, which must lie within (0, 1].
+    pub base: f64,
+}
+
+impl<O: Organism> Selection<O> for ExponentialRank {
+    /// # Errors
+    ///
+    /// An error is returned if the base lies outside of the range (0, 1].
+    fn select(
+        &self,
+        population: &[Individual<O>],
+        objective: Objective,
+        count: usize,
+        rng: &mut dyn RngCore,
+    ) -> Result<Vec<usize>, EcosystemError> {
+        if !(self.base > 0.0 && self.base <= 1.0) {
+            return Err(EcosystemError::InvalidRankBase(self.base));
+        }
+
+        let last_rank = population.len().saturating_sub(1);
+        select_by_rank(population, objective, count, rng, |rank| {
+            self.base.powi((last_rank - rank) as i32)
+        })
+    }
+}
+
+/// Selects `count` organisms, weighting each by a function of its rank (where
+/// the least fit organism has rank 0).
+fn select_by_rank<O: Organism>(
+    population: &[Individual<O>],
+    objective: Objective,
+    count: usize,
+    rng: &mut dyn RngCore,
+    weight: impl Fn(usize) -> f64,
+) -> Result<Vec<usize>, EcosystemError> {
+    if count == 0 {
+        return Ok(Vec::new());
+    }
+
+    let mut ranked: Vec<_> = (0..population.len()).collect();
+    ranked.sort_by(|&a, &b| objective.compare(population[a].fitness(), population[b].fitness()));
+
+    match WeightedIndex::new((0..ranked.len()).map(weight)) {
+        Ok(distribution) => Ok((0..count)
+            .map(|_| ranked[distribution.sample(rng)])
+            .collect()),
+        Err(WeightedError::NoItem) => Err(EcosystemError::EmptyPopulation),
+        Err(error) => Err(EcosystemError::UnsuitableFitness(error.to_string())),
+    }
+}
+
+/// Stochastic universal sampling, which picks every parent with a single
+/// spin of a roulette wheel bearing equally spaced pointers.
+///
+/// The expected number of times each organism is selected is the same as
+/// with fitness-proportionate selection, but the spread around that
+/// expectation is far smaller. Fitness values are weighted in the same way
+/// as for [`FitnessProportionate`].
+///
+/// [`FitnessProportionate`]: struct.FitnessProportionate.html
+///
+/// # Examples
+///
+/// ```rust
+/// use ecosystem::{
+///     selection::{Selection, StochasticUniversal},
+///     EcosystemError, Individual, Objective,
+/// };
+/// # use ecosystem::Organism;
+/// # use rand::Rng;
+/// # struct Number(f64);
+/// # impl Organism for Number {
+/// #     type Fitness = f64;
+/// #     fn fitness(&self) -> f64 { self.0 }
+/// #     fn breed(&self, other: &Self, rng: &mut impl Rng) -> Self { Number(self.0) }
+/// #     fn mutate(&mut self, rate: f64, rng: &mut impl Rng) {}
+/// # }
+///
+/// let population = vec![Individual::evaluated(Number(1.0)), Individual::evaluated(Number(3.0))];
+/// let selected = StochasticUniversal.select(
+///     &population,
+///     Objective::Maximize,
+///     4,
+///     &mut rand::thread_rng(),
+/// )?;
+///
+/// // The selections always match the expected counts exactly
+/// assert_eq!(selected.iter().filter(|&&index| index == 0).count(), 1);
+/// assert_eq!(selected.iter().filter(|&&index| index == 1).count(), 3);
+///
+/// // An infinite fitness, such as `1 / error` gives on an exact hit, leaves
+/// // no proportions to select by
+/// let population = vec![
+///     Individual::evaluated(Number(1.0)),
+///     Individual::evaluated(Number(f64::INFINITY)),
+/// ];
+/// let result = StochasticUniversal.select(
+///     &population,
+///     Objective::Maximize,
+///     4,
+///     &mut rand::thread_rng(),
+/// );
+/// assert!(matches!(result, Err(EcosystemError::UnsuitableFitness(_))));
+/// # Ok::<(), ecosystem::EcosystemError>(())
+/// ```
+#[derive(Clone, Copy, Debug, Default)]
+pub struct StochasticUniversal;
+
+impl<O: Organism> Selection<O> for StochasticUniversal {
+    /// # Errors
+    ///
+    /// An error is returned if any fitness value is negative, infinite or
+    /// NaN.
+    fn select(
+        &self,
+        population: &[Individual<O>],
+        objective: Objective,
+        count: usize,
+        rng: &mut dyn RngCore,
+    ) -> Result<Vec<usize>, EcosystemError> {
+        if count == 0 {
+            return Ok(Vec::new());
+        }
+        if population.is_empty() {
+            return Err(EcosystemError::EmptyPopulation);
+        }
+        let weights = objective.weights(
+            population
+                .iter()
+                .map(|individual| individual.fitness().to_f64()),
+        );
+        check_weights(&weights)?;
+
+        let total_weight: f64 = weights.iter().sum();
+        if total_weight == 0.0 {
+            return Ok((0..count)
+                .map(|_| rng.gen_range(0, population.len()))
+                .collect());
+        }
+
+        let spacing = total_weight / count as f64;
+        let start = rng.gen_range(0.0, spacing);
+
+        let mut selected = Vec::with_capacity(count);
+        let mut index = 0;
+        let mut cumulative_weight = weights[0];
+        for pointer in (0..count).map(|i| start + i as f64 * spacing) {
+            while cumulative_weight <= pointer && index + 1 < population.len() {
+                index += 1;
+                cumulative_weight += weights[index];
+            }
+            selected.push(index);
+        }
+
+        // Adjacent pointers tend to land on the same organism, so the
+        // selections are shuffled to avoid pairing organisms with themselves
+        selected.shuffle(rng);
+        Ok(selected)
+    }
+}
+
+/// NEAT-style speciation, in which organisms are grouped into *species* of
+/// similar organisms that compete mostly among themselves.
+///
+/// The population is divided into species by comparing each organism, in
+/// turn, against the first member of every species found so far; it joins
+/// the first species whose founder is within `threshold` of it, or else
+/// founds a new species. Fitness is shared within each species, so a
+/// species' total share of the parents depends on the average fitness of
+/// its members rather than on its size. This protects new kinds of
+/// organisms, which are often unfit at first, from being crowded out by a
+/// large, established species.
+///
+/// Each mother is chosen according to her shared fitness, and her mate is
+/// chosen by fitness from her own species, except with probability
+/// `interspecies_rate`, in which case he is chosen like her, from the whole
+/// population. Fitness values are weighted in the same way as for
+/// [`FitnessProportionate`].
+///
+/// [`FitnessProportionate`]: struct.FitnessProportionate.html
+///
+/// # Examples
+///
+/// ```rust
+/// use ecosystem::{selection::Speciation, Ecosystem, Individual, Organism};
+/// # use rand::Rng;
+/// # struct Guess(f64);
+/// # impl Organism for Guess {
+/// #     type Fitness = f64;
+/// #     fn fitness(&self) -> f64 {
+/// #         let peak = |centre: f64| (5.0 - (self.0 - centre).abs()).max(0.0);
+/// #         peak(10.0) + peak(30.0)
+/// #     }
+/// #     fn breed(&self, other: &Self, rng: &mut impl Rng) -> Self { Guess((self.0 + other.0) / 2.0) }
+/// #     fn mutate(&mut self, rate: f64, rng: &mut impl Rng) {
+/// #         self.0 += rng.gen_range(-rate, rate);
+/// #     }
+/// # }
+///
+/// let speciation = Speciation {
+///     distance: |a: &Guess, b: &Guess| (a.0 - b.0).abs(),
+///     threshold: 5.0,
+///     interspecies_rate: 0.01,
+/// };
+///
+/// let guesses = [1.0, 2.0, 11.0, 3.0, 12.0];
+/// let population: Vec<_> = guesses.iter().map(|&guess| Individual::evaluated(Guess(guess))).collect();
+/// assert_eq!(speciation.species(&population), vec![vec![0, 1, 3], vec![2, 4]]);
+///
+/// let ecosystem = Ecosystem::builder()
+///     .organisms((0..40).map(|i| Guess(f64::from(i) + 0.5)))
+///     .selection(speciation)
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct Speciation<D> {
+    /// Measures the compatibility distance between two organisms.
+    pub distance: D,
+    /// The distance within which organisms belong to the same species.
+    pub threshold: f64,
+    /// The probability of a mother mating outside of her species.
+    pub interspecies_rate: f64,
+}
+
+impl<D> Speciation<D> {
+    /// Divides the population into species, returning the indices of the
+    /// members of each. Species are ordered by the position of their first
+    /// member in the population.
+    pub fn species<O>(&self, population: &[Individual<O>]) -> Vec<Vec<usize>>
+    where
+        O: Organism,
+        D: Fn(&O, &O) -> f64,
+    {
+        divide_into_species(
+            &[],
+            population,
+            |a, b| (self.distance)(a.organism(), b.organism()),
+            self.threshold,
+        )
+    }
+}
+
+/// Divides `members` into species by comparing each, in turn, against the
+/// founder of every species found so far, returning the indices of the
+/// members of each species. A member joins the first species whose founder
+/// is within `threshold` of it, or else founds a new species.
+///
+/// The first species are those of the given `founders`, which may be left
+/// empty; the first member of any other species is its founder.
+pub(crate) fn divide_into_species<T>(
+    founders: &[&T],
+    members: &[T],
+    distance: impl Fn(&T, &T) -> f64,
+    threshold: f64,
+) -> Vec<Vec<usize>> {
+    let mut founders = founders.to_vec();
+    let mut species = vec![Vec::new(); founders.len()];
+    for (index, member) in members.iter().enumerate() {
+        match founders
+            .iter()
+            .position(|founder| distance(member, founder) < threshold)
+        {
+            Some(id) => species[id].push(index),
+            None => {
+                founders.push(member);
+                species.push(vec![index]);
+            }
+        }
+    }
+    species
+}
+
+impl<O, D> Selection<O> for Speciation<D>
+where
+    O: Organism,
+    D: Fn(&O, &O) -> f64 + Send + Sync,
+{
+    /// # Errors
+    ///
+    /// An error is returned if any fitness value is negative, infinite or
+    /// NaN.
+    fn select(
+        &self,
+        population: &[Individual<O>],
+        objective: Objective,
+        count: usize,
+        rng: &mut dyn RngCore,
+    ) -> Result<Vec<usize>, EcosystemError> {
+        if count == 0 {
+            return Ok(Vec::new());
+        }
+        if population.is_empty() {
+            return Err(EcosystemError::EmptyPopulation);
+        }
+
+        let weights = objective.weights(
+            population
+                .iter()
+                .map(|individual| individual.fitness().to_f64()),
+        );
+        let species = self.species(population);
+        let mut species_of = vec![0; population.len()];
+        let mut shared_weights = vec![0.0; population.len()];
+        for (id, members) in species.iter().enumerate() {
+            for &member in members {
+                species_of[member] = id;
+                shared_weights[member] = weights[member] / members.len() as f64;
+            }
+        }
+
+        let anyone = Roulette::new(shared_weights)?;
+        let within: Vec<_> = species
+            .iter()
+            .map(|members| Roulette::new(members.iter().map(|&member| weights[member]).collect()))
+            .collect::<Result<_, _>>()?;
+
+        let mut selected = Vec::with_capacity(count);
+        while selected.len() < count {
+            let mother = anyone.spin(rng);
+            selected.push(mother);
+            if selected.len() < count {
+                let father = if rng.gen::<f64>() < self.interspecies_rate {
+                    anyone.spin(rng)
+                } else {
+                    let members = &species[species_of[mother]];
+                    members[within[species_of[mother]].spin(rng)]
+                };
+                selected.push(father);
+            }
+        }
+        Ok(selected)
+    }
+}
+
+/// Checks that the given selection weights are non-negative, and finite
+/// both individually and in total, as sampling in proportion to them
+/// requires.
+fn check_weights(weights: &[f64]) -> Result<(), EcosystemError> {
+    if !weights.iter().all(|&weight| weight >= 0.0) {
+        return Err(EcosystemError::UnsuitableFitness(
+            "fitness must be non-negative".to_string(),
+        ));
+    }
+    if !weights.iter().sum::<f64>().is_finite() {
+        return Err(EcosystemError::UnsuitableFitness(
+            "fitness must be finite".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// A roulette wheel that chooses indices with a chance proportional to their
+/// weights, or uniformly if every weight is zero.
+struct Roulette {
+    distribution: Option<WeightedIndex<f64>>,
+    len: usize,
+}
+
+impl Roulette {
+    fn new(weights: Vec<f64>) -> Result<Self, EcosystemError> {
+        check_weights(&weights)?;
+        let len = weights.len();
+        let distribution = match WeightedIndex::new(weights) {
+            Ok(distribution) => Some(distribution),
+            Err(WeightedError::AllWeightsZero) => None,
+            Err(WeightedError::NoItem) => return Err(EcosystemError::EmptyPopulation),
+            Err(error) => return Err(EcosystemError::UnsuitableFitness(error.to_string())),
+        };
+        Ok(Self { distribution, len })
+    }
+
+    fn spin(&self, rng: &mut dyn RngCore) -> usize {
+        match &self.distribution {
+            Some(distribution) => distribution.sample(rng),
+            None => rng.gen_range(0, self.len),
+        }
+    }
+}
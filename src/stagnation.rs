@@ -0,0 +1,155 @@
+use crate::{mutation::Hypermutation, Ecosystem, Generator, Individual, Organism, Setting};
+use alloc::boxed::Box;
+use core::cmp::Ordering;
+use rand::RngCore;
+
+/// How an ecosystem responds when its best fitness stops improving, as set
+/// with [`EcosystemBuilder::on_stagnation`].
+///
+/// [`EcosystemBuilder::on_stagnation`]: struct.EcosystemBuilder.html#method.on_stagnation
+///
+/// # Examples
+///
+/// ```rust
+/// use ecosystem::{Ecosystem, Organism, StagnationResponse};
+/// use rand::Rng;
+/// # struct Guess(f64);
+/// # impl Organism for Guess {
+/// #     type Fitness = f64;
+/// #     fn fitness(&self) -> f64 { 1.0 / (42.0 - self.0).abs() }
+/// #     fn breed(&self, other: &Self, rng: &mut impl Rng) -> Self { Guess((self.0 + other.0) / 2.0) }
+/// #     fn mutate(&mut self, rate: f64, rng: &mut impl Rng) {}
+/// # }
+///
+/// // Without mutation, the guesses soon converge far from the answer
+/// let mut ecosystem = Ecosystem::builder()
+///     .organisms((0..20).map(|i| Guess(f64::from(i) + 500.5)))
+///     .elitism(1)
+///     .on_stagnation(
+///         5,
+///         StagnationResponse::restart(|rng| Guess(rng.gen_range(0.0, 1000.0))),
+///     )
+///     .seed(0)
+///     .build()
+///     .unwrap();
+/// for _ in 0..100 {
+///     ecosystem.breed_next_generation()?;
+/// }
+///
+/// // Restarting has let the search escape
+/// assert!((ecosystem.fittest().unwrap().0 - 42.0).abs() < 10.0);
+/// # Ok::<(), ecosystem::EcosystemError>(())
+/// ```
+pub enum StagnationResponse<O> {
+    /// Replaces every organism other than the elites with one created by the
+    /// given function.
+    Restart(Generator<O>),
+    /// Multiplies the mutation rate by the given factor, keeping it within
+    /// [0, 1]. This has no lasting effect if a [mutation controller] sets
+    /// the rate in each generation.
+    ///
+    /// [mutation controller]: mutation/index.html
+    RaiseMutation(f64),
+    /// Starts a burst of [hypermutation].
+    ///
+    /// [hypermutation]: struct.Ecosystem.html#method.hypermutate
+    Hypermutate(Hypermutation),
+    /// Ends the run, with a reason of [`StopReason::Stagnation`]. This only
+    /// has an effect on runs driven by [`Ecosystem::run`].
+    ///
+    /// [`StopReason::Stagnation`]: termination/enum.StopReason.html#variant.Stagnation
+    /// [`Ecosystem::run`]: struct.Ecosystem.html#method.run
+    Stop,
+}
+
+impl<O> StagnationResponse<O> {
+    /// Creates a response that restarts the search with organisms created by
+    /// the given function.
+    pub fn restart<G>(generate: G) -> Self
+    where
+        G: Fn(&mut dyn RngCore) -> O + Send + Sync + 'static,
+    {
+        StagnationResponse::Restart(Box::new(generate))
+    }
+}
+
+/// Watches an ecosystem's best fitness, responding once it has not improved
+/// for a number of generations.
+pub(crate) struct StagnationMonitor<O: Organism> {
+    pub(crate) generations: u32,
+    pub(crate) response: StagnationResponse<O>,
+    pub(crate) best: Option<O::Fitness>,
+    pub(crate) last_improvement: u32,
+    /// Whether the ecosystem has stagnated since the run last checked, when
+    /// the response is to stop.
+    pub(crate) stalled: bool,
+}
+
+impl<O: Organism> StagnationMonitor<O> {
+    pub(crate) fn new(generations: u32, response: StagnationResponse<O>) -> Self {
+        Self {
+            generations,
+            response,
+            best: None,
+            last_improvement: 0,
+            stalled: false,
+        }
+    }
+}
+
+impl<O: Organism + Send + Sync> Ecosystem<O> {
+    /// Responds to the stagnation of the best fitness, as set with
+    /// [`EcosystemBuilder::on_stagnation`]. This is useful for restoring the
+    /// setting after resuming from a checkpoint.
+    ///
+    /// [`EcosystemBuilder::on_stagnation`]: struct.EcosystemBuilder.html#method.on_stagnation
+    pub fn set_stagnation_response(&mut self, generations: u32, response: StagnationResponse<O>) {
+        self.stagnation = Some(StagnationMonitor::new(generations, response));
+        self.restored(Setting::StagnationResponse);
+    }
+
+    /// Records the current best fitness, if stagnation is being watched for.
+    pub(crate) fn note_best_fitness(&mut self) {
+        let fitness = match &self.stagnation {
+            Some(_) => self.fittest_individual().map(Individual::fitness),
+            None => return,
+        };
+        let (monitor, objective) = (self.stagnation.as_mut().unwrap(), self.objective);
+        if let Some(fitness) = fitness {
+            match monitor.best {
+                Some(best) if objective.compare(fitness, best) != Ordering::Greater => {}
+                _ => {
+                    monitor.best = Some(fitness);
+                    monitor.last_improvement = self.generation;
+                }
+            }
+        }
+    }
+
+    /// Checks whether the best fitness has stagnated, responding if it has.
+    /// Once the response is made, another `generations` generations without
+    /// improvement must pass before the next.
+    pub(crate) fn respond_to_stagnation(&mut self) {
+        self.note_best_fitness();
+        let mut monitor = match self.stagnation.take() {
+            Some(monitor) => monitor,
+            None => return,
+        };
+
+        if self.generation.saturating_sub(monitor.last_improvement) >= monitor.generations {
+            monitor.last_improvement = self.generation;
+            match &monitor.response {
+                StagnationResponse::Restart(generate) => {
+                    self.replace_least_fit(self.population.len(), generate);
+                }
+                StagnationResponse::RaiseMutation(factor) => {
+                    self.mutation_rate = (self.mutation_rate * factor).clamp(0.0, 1.0);
+                }
+                StagnationResponse::Hypermutate(hypermutation) => self.hypermutate(*hypermutation),
+                StagnationResponse::Stop => monitor.stalled = true,
+            }
+        }
+
+        self.stagnation = Some(monitor);
+    }
+}
@@ -0,0 +1,60 @@
+use alloc::vec::Vec;
+#[cfg(not(feature = "std"))]
+#[allow(unused_imports)]
+use num_traits::Float;
+
+/// A summary of the fitness values in a generation.
+///
+/// The fitness values are summarised through their scalar view (see
+/// [`Fitness::to_f64`]), regardless of the ecosystem's objective; `max` is
+/// the best value when maximising, and `min` the best when minimising.
+///
+/// [`Fitness::to_f64`]: trait.Fitness.html#tymethod.to_f64
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Statistics {
+    /// The lowest fitness value.
+    pub min: f64,
+    /// The highest fitness value.
+    pub max: f64,
+    /// The arithmetic mean of the fitness values.
+    pub mean: f64,
+    /// The median fitness value, which is the mean of the two middle values
+    /// when there is an even number of them.
+    pub median: f64,
+    /// The (population) standard deviation of the fitness values.
+    pub std_dev: f64,
+}
+
+impl Statistics {
+    /// Summarises the given fitness values, returning `None` if there are
+    /// none.
+    pub(crate) fn from_fitnesses(mut fitnesses: Vec<f64>) -> Option<Self> {
+        if fitnesses.is_empty() {
+            return None;
+        }
+
+        let count = fitnesses.len() as f64;
+        let mean = fitnesses.iter().sum::<f64>() / count;
+        let variance = fitnesses
+            .iter()
+            .map(|fitness| (fitness - mean).powi(2))
+            .sum::<f64>()
+            / count;
+
+        fitnesses.sort_by(f64::total_cmp);
+        let middle = fitnesses.len() / 2;
+        let median = if fitnesses.len().is_multiple_of(2) {
+            (fitnesses[middle - 1] + fitnesses[middle]) / 2.0
+        } else {
+            fitnesses[middle]
+        };
+
+        Some(Self {
+            min: fitnesses[0],
+            max: fitnesses[fitnesses.len() - 1],
+            mean,
+            median,
+            std_dev: variance.sqrt(),
+        })
+    }
+}
@@ -0,0 +1,199 @@
+//! Surrogate-assisted evaluation, for fitness functions too expensive to run
+//! on every child.
+//!
+//! A [`Surrogate`] registered with [`EcosystemBuilder::surrogate`] is a cheap
+//! model of the fitness function. The ecosystem breeds several candidate
+//! children for every child it needs, estimates their fitness with the
+//! surrogate, and keeps only the most promising, so the true fitness
+//! function is only run on children that are likely to be worth it. The
+//! surrogate is shown the true fitness of every child that is evaluated, so
+//! that models that learn can improve as the search goes on.
+//!
+//! Closures that estimate an organism's fitness are surrogates too, though
+//! ones that do not learn:
+//!
+//! ```rust
+//! use ecosystem::{Ecosystem, Organism};
+//! # use rand::Rng;
+//! # struct Guess(f64);
+//! # impl Organism for Guess {
+//! #     type Fitness = f64;
+//! #     fn fitness(&self) -> f64 { 1.0 / (42.0 - self.0).abs() }
+//! #     fn breed(&self, other: &Self, rng: &mut impl Rng) -> Self { Guess((self.0 + other.0) / 2.0) }
+//! #     fn mutate(&mut self, rate: f64, rng: &mut impl Rng) {
+//! #         self.0 += rng.gen_range(-rate, rate);
+//! #     }
+//! # }
+//!
+//! let guesses = || (0..20).map(|i| Guess(f64::from(i) * 5.0 + 0.5));
+//! let mut screened = Ecosystem::builder()
+//!     .organisms(guesses())
+//!     .mutation_rate(1.0)
+//!     // The distance from the answer, only to the nearest whole number
+//!     .surrogate(|guess: &Guess| -(42.0 - guess.0).abs().round(), 5)
+//!     .seed(0)
+//!     .build()
+//!     .unwrap();
+//! let mut unscreened = Ecosystem::builder()
+//!     .organisms(guesses())
+//!     .mutation_rate(1.0)
+//!     .seed(0)
+//!     .build()
+//!     .unwrap();
+//! for _ in 0..10 {
+//!     screened.breed_next_generation()?;
+//!     unscreened.breed_next_generation()?;
+//! }
+//!
+//! // Only the children that were kept were evaluated, so both ecosystems
+//! // ran the true fitness function equally often
+//! assert_eq!(screened.evaluations(), unscreened.evaluations());
+//! let mean = |ecosystem: &Ecosystem<Guess>| {
+//!     ecosystem.organisms().map(|guess| (42.0 - guess.0).abs()).sum::<f64>() / 20.0
+//! };
+//! assert!(mean(&screened) < mean(&unscreened));
+//! # Ok::<(), ecosystem::EcosystemError>(())
+//! ```
+//!
+//! [`Surrogate`]: trait.Surrogate.html
+//! [`EcosystemBuilder::surrogate`]: ../struct.EcosystemBuilder.html#method.surrogate
+use crate::{diversity::Distance, Organism};
+use alloc::{collections::VecDeque, vec::Vec};
+
+/// An interface for cheap models of an expensive fitness function.
+pub trait Surrogate<O: Organism>: Send + Sync {
+    /// Estimates the fitness of the given organism. Estimates only need to
+    /// rank organisms in the right order, not match their true fitness.
+    fn estimate(&self, organism: &O) -> f64;
+
+    /// Learns from an organism whose true fitness has been evaluated. This
+    /// does nothing by default.
+    fn learn(&mut self, _organism: &O, _fitness: f64) {}
+}
+
+impl<O, F> Surrogate<O> for F
+where
+    O: Organism,
+    F: Fn(&O) -> f64 + Send + Sync,
+{
+    fn estimate(&self, organism: &O) -> f64 {
+        self(organism)
+    }
+}
+
+/// A surrogate that estimates an organism's fitness as the mean fitness of
+/// the `k` nearest organisms that it has learned from, weighted by the
+/// inverse of their distance.
+///
+/// Only the `capacity` most recently learned organisms are remembered, so
+/// the model follows the population as it moves. Until it has learned from
+/// any organisms, every estimate is zero, and candidates are kept in the
+/// order they were bred.
+///
+/// # Examples
+///
+/// ```rust
+/// use ecosystem::{diversity::Distance, surrogate::NearestNeighbors, Ecosystem, Organism};
+/// # use rand::Rng;
+/// # #[derive(Clone)]
+/// # struct Guess(f64);
+/// # impl Organism for Guess {
+/// #     type Fitness = f64;
+/// #     fn fitness(&self) -> f64 { 1.0 / (42.0 - self.0).abs() }
+/// #     fn breed(&self, other: &Self, rng: &mut impl Rng) -> Self { Guess((self.0 + other.0) / 2.0) }
+/// #     fn mutate(&mut self, rate: f64, rng: &mut impl Rng) {
+/// #         self.0 += rng.gen_range(-rate, rate);
+/// #     }
+/// # }
+///
+/// impl Distance for Guess {
+///     fn distance(&self, other: &Self) -> f64 {
+///         (self.0 - other.0).abs()
+///     }
+/// }
+///
+/// let mut ecosystem = Ecosystem::builder()
+///     .organisms((0..20).map(|i| Guess(f64::from(i) * 5.0 + 0.5)))
+///     .mutation_rate(1.0)
+///     .surrogate(NearestNeighbors::new(3, 200), 4)
+///     .build()
+///     .unwrap();
+/// for _ in 0..20 {
+///     ecosystem.breed_next_generation()?;
+/// }
+///
+/// // Four candidates were bred for every child, but only the children
+/// // themselves were evaluated (those of the last generation have yet to
+/// // be)
+/// assert_eq!(ecosystem.evaluations(), 20 + 19 * 20);
+/// # Ok::<(), ecosystem::EcosystemError>(())
+/// ```
+#[derive(Clone, Debug)]
+pub struct NearestNeighbors<O> {
+    k: usize,
+    capacity: usize,
+    samples: VecDeque<(O, f64)>,
+}
+
+impl<O> NearestNeighbors<O> {
+    /// Creates a model that averages over the `k` nearest of the `capacity`
+    /// most recently learned organisms.
+    pub fn new(k: usize, capacity: usize) -> Self {
+        Self {
+            k,
+            capacity,
+            samples: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    /// Returns the number of organisms that the model remembers.
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    /// Returns whether the model has yet to learn from any organisms.
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+}
+
+impl<O> Surrogate<O> for NearestNeighbors<O>
+where
+    O: Organism + Distance + Clone + Send + Sync,
+{
+    fn estimate(&self, organism: &O) -> f64 {
+        let mut neighbors: Vec<_> = self
+            .samples
+            .iter()
+            .map(|(sample, fitness)| (organism.distance(sample), *fitness))
+            .collect();
+        let k = self.k.min(neighbors.len());
+        if k == 0 {
+            return 0.0;
+        }
+        if k < neighbors.len() {
+            neighbors.select_nth_unstable_by(k - 1, |a, b| a.0.total_cmp(&b.0));
+        }
+        let neighbors = &neighbors[..k];
+        // An organism that has been learned already is estimated exactly
+        if let Some(&(_, fitness)) = neighbors.iter().find(|(distance, _)| *distance == 0.0) {
+            return fitness;
+        }
+        let total_weight: f64 = neighbors.iter().map(|(distance, _)| distance.recip()).sum();
+        neighbors
+            .iter()
+            .map(|(distance, fitness)| fitness / distance)
+            .sum::<f64>()
+            / total_weight
+    }
+
+    fn learn(&mut self, organism: &O, fitness: f64) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.samples.len() == self.capacity {
+            self.samples.pop_front();
+        }
+        self.samples.push_back((organism.clone(), fitness));
+    }
+}
@@ -0,0 +1,329 @@
+//! Streaming the progress of a run to WebSocket clients.
+//!
+//! The [`Telemetry`] observer serves a WebSocket endpoint, and sends every
+//! connected client a JSON [`Message`] as each generation is bred, so that a
+//! dashboard in a browser can follow a run on a remote machine as it
+//! happens. Each message is an object whose `type` is one of:
+//!
+//! - `generation`, with the statistics of each generation once it has been
+//!   bred: `{"type": "generation", "generation": 12, "evaluations": 1300,
+//!   "elapsed": 0.84, "best": 0.98, "mean": 0.61, "median": 0.64,
+//!   "std_dev": 0.17}`, where `elapsed` is in seconds since the run began.
+//! - `best`, with a new best organism as serialised by serde:
+//!   `{"type": "best", "generation": 12, "fitness": 0.98, "organism": ...}`.
+//!   Clients that connect partway through a run are sent the latest one
+//!   straight away.
+//! - `end`, once the run has ended: `{"type": "end", "generation": 100,
+//!   "evaluations": 10100}`.
+//!
+//! In a browser, the messages can be read with:
+//!
+//! ```js
+//! const socket = new WebSocket("ws://evolution-host:9001");
+//! socket.onmessage = (event) => {
+//!     const message = JSON.parse(event.data);
+//!     if (message.type === "generation") chart.push(message.generation, message.best);
+//! };
+//! ```
+//!
+//! Messages are sent from a background thread, so slow clients never hold up
+//! the run; if they fall far enough behind, generation messages are dropped
+//! rather than queued without limit.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use ecosystem::{
+//!     telemetry::{Message, Telemetry},
+//!     termination::MaxGenerations,
+//!     Ecosystem, Organism,
+//! };
+//! use serde::Serialize;
+//! # use rand::Rng;
+//! # #[derive(Serialize)]
+//! # struct Guess(f64);
+//! # impl Organism for Guess {
+//! #     type Fitness = f64;
+//! #     fn fitness(&self) -> f64 { 1.0 / (42.0 - self.0).abs() }
+//! #     fn breed(&self, other: &Self, rng: &mut impl Rng) -> Self { Guess((self.0 + other.0) / 2.0) }
+//! #     fn mutate(&mut self, rate: f64, rng: &mut impl Rng) {
+//! #         self.0 += rng.gen_range(-rate, rate);
+//! #     }
+//! # }
+//!
+//! let telemetry = Telemetry::bind("127.0.0.1:0")?;
+//! let address = telemetry.local_addr();
+//! let mut ecosystem = Ecosystem::builder()
+//!     .organisms((0..20).map(|i| Guess(f64::from(i) * 5.0 + 0.5)))
+//!     .observer(telemetry)
+//!     .build()
+//!     .unwrap();
+//!
+//! // A client, such as a dashboard, following the run
+//! let (mut client, _) = tungstenite::connect(format!("ws://{}", address)).unwrap();
+//! # std::thread::sleep(std::time::Duration::from_millis(100));
+//! ecosystem.run(MaxGenerations(10))?;
+//!
+//! let mut generations = 0;
+//! loop {
+//!     let text = client.read().unwrap().into_text().unwrap();
+//!     match serde_json::from_str::<Message>(&text).unwrap() {
+//!         Message::Generation { .. } => generations += 1,
+//!         Message::Best { .. } => {}
+//!         Message::End { generation, .. } => {
+//!             assert_eq!(generation, 10);
+//!             break;
+//!         }
+//!     }
+//! }
+//! assert_eq!(generations, 10);
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+//!
+//! [`Telemetry`]: struct.Telemetry.html
+//! [`Message`]: enum.Message.html
+use crate::{history::Clock, observer::Observer, Ecosystem, Fitness, Objective, Organism};
+use core::{ops::ControlFlow, time::Duration};
+use serde::{Deserialize, Serialize};
+use std::{
+    io,
+    net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver, SyncSender},
+        Arc,
+    },
+    thread,
+};
+use tungstenite::WebSocket;
+
+/// The number of messages that can wait to be sent before generation
+/// messages start being dropped.
+const QUEUE_LENGTH: usize = 1024;
+
+/// A message sent to the clients of a [`Telemetry`] endpoint, as a JSON
+/// object tagged by its `type`.
+///
+/// [`Telemetry`]: struct.Telemetry.html
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Message {
+    /// The statistics of a generation that has just been bred.
+    Generation {
+        /// The generation number.
+        generation: u32,
+        /// The number of fitness evaluations performed so far.
+        evaluations: u64,
+        /// The seconds elapsed since the run began.
+        elapsed: f64,
+        /// The best fitness of the generation.
+        best: f64,
+        /// The mean fitness of the generation.
+        mean: f64,
+        /// The median fitness of the generation.
+        median: f64,
+        /// The standard deviation of the generation's fitness values.
+        std_dev: f64,
+    },
+    /// An organism that is fitter than any seen before in the run.
+    Best {
+        /// The generation in which it was found.
+        generation: u32,
+        /// Its fitness.
+        fitness: f64,
+        /// The organism, as serialised by serde.
+        organism: serde_json::Value,
+    },
+    /// The run has ended.
+    End {
+        /// The final generation number.
+        generation: u32,
+        /// The total number of fitness evaluations performed.
+        evaluations: u64,
+    },
+}
+
+/// Work for the thread that sends messages to the clients.
+enum Command {
+    /// A client has connected.
+    Connect(Box<WebSocket<TcpStream>>),
+    /// A message, already encoded, is to be sent to every client.
+    Send(String, bool),
+}
+
+/// An [observer] that streams the progress of a run to WebSocket clients.
+///
+/// [observer]: ../observer/index.html
+pub struct Telemetry {
+    address: SocketAddr,
+    commands: SyncSender<Command>,
+    closed: Arc<AtomicBool>,
+    clock: Option<Clock>,
+}
+
+impl Telemetry {
+    /// Listens for WebSocket clients on the given address. Binding to port
+    /// 0 picks a free port, which can be found with [`local_addr`].
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the address can't be bound.
+    ///
+    /// [`local_addr`]: #method.local_addr
+    pub fn bind(address: impl ToSocketAddrs) -> io::Result<Self> {
+        let listener = TcpListener::bind(address)?;
+        let address = listener.local_addr()?;
+        let (commands, queue) = mpsc::sync_channel(QUEUE_LENGTH);
+        let closed = Arc::new(AtomicBool::new(false));
+
+        let connections = commands.clone();
+        let closing = Arc::clone(&closed);
+        thread::spawn(move || accept(listener, connections, closing));
+        thread::spawn(move || broadcast(queue));
+        Ok(Self {
+            address,
+            commands,
+            closed,
+            clock: None,
+        })
+    }
+
+    /// Returns the address on which clients are accepted.
+    pub fn local_addr(&self) -> SocketAddr {
+        self.address
+    }
+
+    /// Queues a message for every client. If the queue is full, generation
+    /// messages are dropped, while the rarer messages that clients can't do
+    /// without are waited for.
+    fn send(&self, message: &Message) {
+        let Ok(text) = serde_json::to_string(message) else {
+            return;
+        };
+        let best = matches!(message, Message::Best { .. });
+        let command = Command::Send(text, best);
+        // Failing to send means that the sending thread has gone, and with it
+        // every client
+        if let Message::Generation { .. } = message {
+            let _ = self.commands.try_send(command);
+        } else {
+            let _ = self.commands.send(command);
+        }
+    }
+}
+
+impl<O> Observer<O> for Telemetry
+where
+    O: Organism + Serialize + Send + Sync,
+{
+    fn on_generation_start(&mut self, _ecosystem: &Ecosystem<O>) -> ControlFlow<()> {
+        if self.clock.is_none() {
+            self.clock = Some(Clock::starting_at(Duration::ZERO));
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn on_generation_end(&mut self, ecosystem: &Ecosystem<O>) -> ControlFlow<()> {
+        if let Some(statistics) = ecosystem.statistics() {
+            let elapsed = self.clock.as_ref().map_or(Duration::ZERO, Clock::elapsed);
+            self.send(&Message::Generation {
+                generation: ecosystem.generation(),
+                evaluations: ecosystem.evaluations(),
+                elapsed: elapsed.as_secs_f64(),
+                best: match ecosystem.objective() {
+                    Objective::Maximize => statistics.max,
+                    Objective::Minimize => statistics.min,
+                },
+                mean: statistics.mean,
+                median: statistics.median,
+                std_dev: statistics.std_dev,
+            });
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn on_new_best(&mut self, ecosystem: &Ecosystem<O>) -> ControlFlow<()> {
+        if let Some(fittest) = ecosystem.fittest_individual() {
+            if let Ok(organism) = serde_json::to_value(fittest.organism()) {
+                self.send(&Message::Best {
+                    generation: ecosystem.generation(),
+                    fitness: fittest.fitness().to_f64(),
+                    organism,
+                });
+            }
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn on_run_end(&mut self, ecosystem: &Ecosystem<O>) {
+        self.clock = None;
+        self.send(&Message::End {
+            generation: ecosystem.generation(),
+            evaluations: ecosystem.evaluations(),
+        });
+    }
+}
+
+impl Drop for Telemetry {
+    fn drop(&mut self) {
+        // The accepting thread is woken with a connection of its own, so that
+        // it sees that the endpoint has closed
+        self.closed.store(true, Ordering::SeqCst);
+        let _ = TcpStream::connect(self.address);
+    }
+}
+
+/// Accepts clients until the endpoint is closed, handing each to the
+/// sending thread once its handshake is complete.
+fn accept(listener: TcpListener, connections: SyncSender<Command>, closed: Arc<AtomicBool>) {
+    for stream in listener.incoming() {
+        if closed.load(Ordering::SeqCst) {
+            return;
+        }
+        let Ok(stream) = stream else {
+            continue;
+        };
+        if let Ok(client) = tungstenite::accept(stream) {
+            if connections
+                .send(Command::Connect(Box::new(client)))
+                .is_err()
+            {
+                return;
+            }
+        }
+    }
+}
+
+/// Sends each queued message to every client, dropping those that can no
+/// longer be reached, until the endpoint is closed.
+fn broadcast(queue: Receiver<Command>) {
+    let mut clients: Vec<Box<WebSocket<TcpStream>>> = Vec::new();
+    let mut latest_best = None;
+    for command in queue {
+        match command {
+            Command::Connect(mut client) => {
+                let caught_up = match &latest_best {
+                    Some(best) => client.send(tungstenite::Message::text(String::clone(best))),
+                    None => Ok(()),
+                };
+                if caught_up.is_ok() {
+                    clients.push(client);
+                }
+            }
+            Command::Send(text, best) => {
+                clients.retain_mut(|client| {
+                    client
+                        .send(tungstenite::Message::text(text.clone()))
+                        .is_ok()
+                });
+                if best {
+                    latest_best = Some(text);
+                }
+            }
+        }
+    }
+    for mut client in clients {
+        let _ = client.close(None);
+        let _ = client.flush();
+    }
+}
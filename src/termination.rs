@@ -0,0 +1,402 @@
+//! Conditions for deciding when an evolutionary run should stop.
+//!
+//! The built-in conditions can be combined with their `or` and `and`
+//! methods, and any conditions can be combined with [`Or`] and [`And`]:
+//!
+//! ```rust
+//! use ecosystem::termination::{MaxGenerations, Stagnation, TargetFitness, Termination};
+//! # use ecosystem::Organism;
+//! # use rand::Rng;
+//! # struct Guess(f64);
+//! # impl Organism for Guess {
+//! #     type Fitness = f64;
+//! #     fn fitness(&self) -> f64 { 0.0 }
+//! #     fn breed(&self, other: &Self, rng: &mut impl Rng) -> Self { Guess(self.0) }
+//! #     fn mutate(&mut self, rate: f64, rng: &mut impl Rng) {}
+//! # }
+//!
+//! // Stop once a good enough solution is found, or after 1000 generations
+//! // without any improvement
+//! let termination = TargetFitness(0.99).or(MaxGenerations(1000).and(Stagnation::new(50)));
+//! # fn check<T: Termination<ecosystem::Ecosystem<Guess>>>(termination: T) {}
+//! # check(termination);
+//! ```
+//!
+//! Conditions are checked against a [`Search`], which is implemented by
+//! [`Ecosystem`] and by the other optimisers in this crate, so the same
+//! conditions can end any of their runs.
+//!
+//! [`Or`]: struct.Or.html
+//! [`And`]: struct.And.html
+//! [`Search`]: trait.Search.html
+//! [`Ecosystem`]: ../struct.Ecosystem.html
+use crate::{diversity::Distance, Ecosystem, Fitness, Individual, Objective, Organism};
+use alloc::{string::String, vec::Vec};
+use core::cmp::Ordering;
+
+/// The progress of an evolutionary run, as seen by termination conditions.
+pub trait Search {
+    /// The type of the fitness values being optimised.
+    type Fitness: Fitness;
+
+    /// Returns the current generation number.
+    fn generation(&self) -> u32;
+
+    /// Returns the total number of fitness evaluations performed so far.
+    fn evaluations(&self) -> u64;
+
+    /// Returns the direction in which fitness values are optimised.
+    fn objective(&self) -> Objective;
+
+    /// Returns the best fitness value of the search, or `None` if nothing
+    /// has been evaluated yet.
+    fn best_fitness(&self) -> Option<Self::Fitness>;
+}
+
+impl<O: Organism + Send + Sync> Search for Ecosystem<O> {
+    type Fitness = O::Fitness;
+
+    fn generation(&self) -> u32 {
+        self.generation()
+    }
+
+    fn evaluations(&self) -> u64 {
+        self.evaluations()
+    }
+
+    fn objective(&self) -> Objective {
+        self.objective()
+    }
+
+    /// Returns the fitness of the fittest organism in the current
+    /// generation.
+    fn best_fitness(&self) -> Option<O::Fitness> {
+        self.fittest_individual().map(Individual::fitness)
+    }
+}
+
+/// An interface for conditions that end an evolutionary run of a search
+/// `S`, such as an [`Ecosystem`].
+///
+/// [`Ecosystem`]: ../struct.Ecosystem.html
+pub trait Termination<S: ?Sized> {
+    /// Checks the state of the search, returning the reason for stopping if
+    /// the run should stop.
+    ///
+    /// This is called once before the first generation is bred, and once
+    /// after every generation thereafter.
+    fn check(&mut self, search: &S) -> Option<StopReason>;
+
+    /// Returns the fraction of the run completed, from 0 to 1, or `None` if
+    /// the condition can't tell how close the run is to its end.
+    ///
+    /// This is used to report progress, and to estimate how long a run has
+    /// left. Only limits on resources, such as generations and evaluations,
+    /// can tell; conditions on the fitness of the search can't.
+    fn progress(&self, _search: &S) -> Option<f64> {
+        None
+    }
+}
+
+/// The reason that an evolutionary run stopped.
+#[derive(Clone, Debug, PartialEq)]
+pub enum StopReason {
+    /// The target fitness was reached.
+    TargetFitness,
+    /// The maximum number of generations was reached.
+    MaxGenerations,
+    /// The budget of fitness evaluations was used up.
+    MaxEvaluations,
+    /// The best fitness stopped improving.
+    Stagnation,
+    /// The population converged, losing its diversity.
+    Converged,
+    /// An [observer] requested that the run stop.
+    ///
+    /// [observer]: ../observer/trait.Observer.html
+    Observer,
+    /// Several conditions were met together.
+    All(Vec<StopReason>),
+    /// A user-defined condition was met.
+    Custom(String),
+}
+
+/// A summary of an evolutionary run.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RunSummary {
+    /// The reason that the run stopped.
+    pub reason: StopReason,
+    /// The number of generations bred during the run.
+    pub generations: u32,
+    /// The number of fitness evaluations performed during the run.
+    pub evaluations: u64,
+}
+
+/// Stops once the best fitness is at least as good as the given target.
+#[derive(Clone, Copy, Debug)]
+pub struct TargetFitness<F>(pub F);
+
+impl<S: Search<Fitness = F> + ?Sized, F: Fitness> Termination<S> for TargetFitness<F> {
+    fn check(&mut self, search: &S) -> Option<StopReason> {
+        let best = search.best_fitness()?;
+        if search.objective().compare(best, self.0) != Ordering::Less {
+            Some(StopReason::TargetFitness)
+        } else {
+            None
+        }
+    }
+}
+
+/// Stops once the search reaches the given generation number.
+#[derive(Clone, Copy, Debug)]
+pub struct MaxGenerations(pub u32);
+
+impl<S: Search + ?Sized> Termination<S> for MaxGenerations {
+    fn check(&mut self, search: &S) -> Option<StopReason> {
+        if search.generation() >= self.0 {
+            Some(StopReason::MaxGenerations)
+        } else {
+            None
+        }
+    }
+
+    fn progress(&self, search: &S) -> Option<f64> {
+        Some(fraction(f64::from(search.generation()), f64::from(self.0)))
+    }
+}
+
+/// Stops once the search has performed at least the given number of
+/// fitness evaluations.
+///
+/// Evaluation counts are the usual basis for comparing different
+/// algorithms, as the work done in a generation varies between them. The
+/// condition is only checked between generations, so a run may overshoot
+/// the budget by up to a generation's worth of evaluations. (An
+/// [`Ecosystem`] evaluates each generation's children when it breeds the
+/// next, or when they are first inspected, so its count lags a generation
+/// behind.)
+///
+/// [`Ecosystem`]: ../struct.Ecosystem.html
+///
+/// # Examples
+///
+/// ```rust
+/// use ecosystem::{
+///     benchmarks::Function,
+///     termination::{MaxEvaluations, StopReason},
+///     Cmaes, DifferentialEvolution, Objective,
+/// };
+///
+/// // Two optimisers given the same budget on the same problem
+/// let function = Function::Sphere;
+/// let mut cmaes = Cmaes::new(vec![3.0; 5], 2.0, move |point| function.evaluate(point))
+///     .objective(Objective::Minimize)
+///     .seed(0);
+/// let mut de = DifferentialEvolution::new(vec![function.bounds(); 5], 25, move |point| {
+///     function.evaluate(point)
+/// })
+/// .objective(Objective::Minimize)
+/// .seed(0);
+///
+/// let summary = cmaes.run(MaxEvaluations(2000));
+/// assert_eq!(summary.reason, StopReason::MaxEvaluations);
+/// assert!(summary.evaluations >= 2000);
+/// let summary = de.run(MaxEvaluations(2000));
+/// assert_eq!(summary.reason, StopReason::MaxEvaluations);
+/// // The initial population, then 79 generations of 25 trial vectors
+/// assert_eq!(summary.evaluations, 25 + 79 * 25);
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct MaxEvaluations(pub u64);
+
+impl<S: Search + ?Sized> Termination<S> for MaxEvaluations {
+    fn check(&mut self, search: &S) -> Option<StopReason> {
+        if search.evaluations() >= self.0 {
+            Some(StopReason::MaxEvaluations)
+        } else {
+            None
+        }
+    }
+
+    fn progress(&self, search: &S) -> Option<f64> {
+        Some(fraction(search.evaluations() as f64, self.0 as f64))
+    }
+}
+
+/// Returns the fraction of a limit that has been used, from 0 to 1.
+pub(crate) fn fraction(used: f64, limit: f64) -> f64 {
+    if limit > 0.0 {
+        (used / limit).min(1.0)
+    } else {
+        1.0
+    }
+}
+
+/// Stops once the best fitness has not improved for the given number of
+/// generations.
+#[derive(Clone, Debug)]
+pub struct Stagnation<F> {
+    generations: u32,
+    best: Option<F>,
+    last_improvement: u32,
+}
+
+impl<F> Stagnation<F> {
+    /// Creates a condition that stops after `generations` generations
+    /// without improvement.
+    pub fn new(generations: u32) -> Self {
+        Self {
+            generations,
+            best: None,
+            last_improvement: 0,
+        }
+    }
+}
+
+impl<S: Search<Fitness = F> + ?Sized, F: Fitness> Termination<S> for Stagnation<F> {
+    fn check(&mut self, search: &S) -> Option<StopReason> {
+        let fitness = search.best_fitness()?;
+        match self.best {
+            Some(best) if search.objective().compare(fitness, best) != Ordering::Greater => {}
+            _ => {
+                self.best = Some(fitness);
+                self.last_improvement = search.generation();
+            }
+        }
+
+        if search.generation().saturating_sub(self.last_improvement) >= self.generations {
+            Some(StopReason::Stagnation)
+        } else {
+            None
+        }
+    }
+}
+
+/// Stops once the mean distance between the organisms falls below the given
+/// threshold, as the population has then converged.
+///
+/// Measuring the mean distance takes a number of distance calculations that
+/// grows with the square of the population size.
+///
+/// # Examples
+///
+/// ```rust
+/// use ecosystem::{
+///     diversity::Distance,
+///     termination::{Convergence, MaxGenerations, StopReason},
+///     Ecosystem, Organism,
+/// };
+/// # use rand::Rng;
+/// # struct Guess(f64);
+/// # impl Organism for Guess {
+/// #     type Fitness = f64;
+/// #     fn fitness(&self) -> f64 { 1.0 / (42.0 - self.0).abs() }
+/// #     fn breed(&self, other: &Self, rng: &mut impl Rng) -> Self { Guess((self.0 + other.0) / 2.0) }
+/// #     fn mutate(&mut self, rate: f64, rng: &mut impl Rng) {}
+/// # }
+///
+/// impl Distance for Guess {
+///     fn distance(&self, other: &Self) -> f64 {
+///         (self.0 - other.0).abs()
+///     }
+/// }
+///
+/// let mut ecosystem = Ecosystem::builder()
+///     .organisms((0..20).map(|i| Guess(f64::from(i) * 5.0 + 0.5)))
+///     .build()
+///     .unwrap();
+/// let summary = ecosystem.run(Convergence(0.1).or(MaxGenerations(100)))?;
+///
+/// assert_eq!(summary.reason, StopReason::Converged);
+/// # Ok::<(), ecosystem::EcosystemError>(())
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct Convergence(pub f64);
+
+impl<O: Organism + Distance + Send + Sync> Termination<Ecosystem<O>> for Convergence {
+    fn check(&mut self, ecosystem: &Ecosystem<O>) -> Option<StopReason> {
+        if ecosystem.mean_distance() < self.0 {
+            Some(StopReason::Converged)
+        } else {
+            None
+        }
+    }
+}
+
+/// Stops when either of two conditions is met.
+#[derive(Clone, Debug)]
+pub struct Or<A, B>(pub A, pub B);
+
+impl<S: ?Sized, A: Termination<S>, B: Termination<S>> Termination<S> for Or<A, B> {
+    fn check(&mut self, search: &S) -> Option<StopReason> {
+        // Both conditions are checked, so that stateful ones stay up to date
+        let first = self.0.check(search);
+        let second = self.1.check(search);
+        first.or(second)
+    }
+
+    fn progress(&self, search: &S) -> Option<f64> {
+        // The run ends with whichever condition is met first
+        match (self.0.progress(search), self.1.progress(search)) {
+            (Some(first), Some(second)) => Some(first.max(second)),
+            (first, second) => first.or(second),
+        }
+    }
+}
+
+/// Stops when two conditions are met at the same time.
+#[derive(Clone, Debug)]
+pub struct And<A, B>(pub A, pub B);
+
+impl<S: ?Sized, A: Termination<S>, B: Termination<S>> Termination<S> for And<A, B> {
+    fn check(&mut self, search: &S) -> Option<StopReason> {
+        match (self.0.check(search), self.1.check(search)) {
+            (Some(first), Some(second)) => {
+                let mut reasons = Vec::new();
+                for reason in [first, second] {
+                    match reason {
+                        StopReason::All(nested) => reasons.extend(nested),
+                        reason => reasons.push(reason),
+                    }
+                }
+                Some(StopReason::All(reasons))
+            }
+            _ => None,
+        }
+    }
+
+    fn progress(&self, search: &S) -> Option<f64> {
+        // The run only ends once both conditions are met
+        Some(self.0.progress(search)?.min(self.1.progress(search)?))
+    }
+}
+
+macro_rules! impl_combinators {
+    ($($condition:ident $(<$($param:ident),*>)?),*) => {
+        $(
+            impl$(<$($param),*>)? $condition$(<$($param),*>)? {
+                /// Combines this condition with another, stopping when either
+                /// is met.
+                pub fn or<T>(self, other: T) -> Or<Self, T> {
+                    Or(self, other)
+                }
+
+                /// Combines this condition with another, stopping only when
+                /// both are met at the same time.
+                pub fn and<T>(self, other: T) -> And<Self, T> {
+                    And(self, other)
+                }
+            }
+        )*
+    };
+}
+
+impl_combinators!(
+    TargetFitness<F>,
+    MaxGenerations,
+    MaxEvaluations,
+    Stagnation<F>,
+    Convergence,
+    Or<A, B>,
+    And<A, B>
+);
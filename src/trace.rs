@@ -0,0 +1,110 @@
+//! Instrumentation of the evolution loop with [tracing] spans and events,
+//! which compiles to nothing when the `tracing` feature is disabled.
+//!
+//! Each call to [`Ecosystem::breed_next_generation`] runs in a `generation`
+//! span, within which the `evaluate`, `select` and `breed` spans time the
+//! phases of breeding. An `evaluated` event carries the fitness statistics
+//! of each generation once it has been evaluated, and a `bred` event the
+//! number of evaluations and the time taken once the next generation has
+//! been bred. Events are emitted at the `INFO` level and the spans of the
+//! phases at the `DEBUG` level, all with the target `ecosystem`.
+//!
+//! [tracing]: https://docs.rs/tracing
+//! [`Ecosystem::breed_next_generation`]: ../struct.Ecosystem.html#method.breed_next_generation
+use crate::{Objective, Statistics};
+use core::time::Duration;
+
+#[cfg(feature = "tracing")]
+pub(crate) use tracing::span::EnteredSpan as Entered;
+
+/// A stand-in for an entered span, which does nothing.
+#[cfg(not(feature = "tracing"))]
+pub(crate) struct Entered;
+
+/// Enters the span covering the breeding of a generation from the current
+/// one.
+pub(crate) fn generation(generation: u32) -> Entered {
+    #[cfg(feature = "tracing")]
+    return tracing::info_span!(target: "ecosystem", "generation", generation).entered();
+    #[cfg(not(feature = "tracing"))]
+    {
+        let _ = generation;
+        Entered
+    }
+}
+
+/// Enters the span covering the evaluation of a group of individuals.
+pub(crate) fn evaluate(individuals: usize) -> Entered {
+    #[cfg(feature = "tracing")]
+    return tracing::debug_span!(target: "ecosystem", "evaluate", individuals).entered();
+    #[cfg(not(feature = "tracing"))]
+    {
+        let _ = individuals;
+        Entered
+    }
+}
+
+/// Enters the span covering the selection of parents.
+pub(crate) fn select(parents: usize) -> Entered {
+    #[cfg(feature = "tracing")]
+    return tracing::debug_span!(target: "ecosystem", "select", parents).entered();
+    #[cfg(not(feature = "tracing"))]
+    {
+        let _ = parents;
+        Entered
+    }
+}
+
+/// Enters the span covering the breeding of children from their parents.
+pub(crate) fn breed(children: usize) -> Entered {
+    #[cfg(feature = "tracing")]
+    return tracing::debug_span!(target: "ecosystem", "breed", children).entered();
+    #[cfg(not(feature = "tracing"))]
+    {
+        let _ = children;
+        Entered
+    }
+}
+
+/// Reports the fitness statistics of a generation that has been evaluated.
+/// The statistics are only gathered if the event will be recorded.
+pub(crate) fn evaluated(
+    generation: u32,
+    objective: Objective,
+    statistics: impl FnOnce() -> Option<Statistics>,
+) {
+    #[cfg(feature = "tracing")]
+    if tracing::enabled!(target: "ecosystem", tracing::Level::INFO) {
+        if let Some(statistics) = statistics() {
+            let best = match objective {
+                Objective::Maximize => statistics.max,
+                Objective::Minimize => statistics.min,
+            };
+            tracing::info!(
+                target: "ecosystem",
+                generation,
+                best,
+                mean = statistics.mean,
+                std_dev = statistics.std_dev,
+                "evaluated",
+            );
+        }
+    }
+    #[cfg(not(feature = "tracing"))]
+    let _ = (generation, objective, statistics);
+}
+
+/// Reports that a generation has been bred, with the total number of
+/// evaluations so far and the time that breeding it took.
+pub(crate) fn bred(generation: u32, evaluations: u64, elapsed: Duration) {
+    #[cfg(feature = "tracing")]
+    tracing::info!(
+        target: "ecosystem",
+        generation,
+        evaluations,
+        elapsed_ms = elapsed.as_secs_f64() * 1000.0,
+        "bred",
+    );
+    #[cfg(not(feature = "tracing"))]
+    let _ = (generation, evaluations, elapsed);
+}
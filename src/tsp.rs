@@ -0,0 +1,576 @@
+//! The travelling salesman problem (TSP): finding the shortest tour that
+//! visits every city once and returns to the start.
+//!
+//! A [`DistanceMatrix`] holds the distances between the cities, and can be
+//! built from coordinates or loaded from a [TSPLIB] file. A [`Tour`] is an
+//! organism that visits the cities in the order of a [`Permutation`], bred
+//! by order crossover and mutated by reversing part of the route. Its
+//! fitness is the length of the tour, so ecosystems of tours should
+//! minimise. Tours can also be improved by [`two_opt`] local search, which
+//! removes every crossing from a route.
+//!
+//! ```rust
+//! use ecosystem::{
+//!     selection::Tournament,
+//!     tsp::{DistanceMatrix, Tour},
+//!     Ecosystem, Objective, Organism,
+//! };
+//! use rand::SeedableRng;
+//! use rand_pcg::Pcg64;
+//! use std::{f64::consts::PI, sync::Arc};
+//!
+//! // Twenty cities on a circle, whose shortest tour goes around it
+//! let cities: Vec<_> = (0..20)
+//!     .map(|city| {
+//!         let angle = 2.0 * PI * city as f64 / 20.0;
+//!         (angle.cos(), angle.sin())
+//!     })
+//!     .collect();
+//! let distances = Arc::new(DistanceMatrix::from_coordinates(&cities));
+//! let shortest = 20.0 * distances.distance(0, 1);
+//!
+//! let mut rng = Pcg64::seed_from_u64(0);
+//! let mut ecosystem = Ecosystem::builder()
+//!     .organisms((0..100).map(|_| Tour::random(&distances, &mut rng)))
+//!     .objective(Objective::Minimize)
+//!     .selection(Tournament { size: 3 })
+//!     .mutation_rate(0.3)
+//!     .elitism(1)
+//!     .seed(0)
+//!     .build()
+//!     .unwrap();
+//! for _ in 0..100 {
+//!     ecosystem.breed_next_generation()?;
+//! }
+//! let mut tour = ecosystem.fittest().unwrap().clone();
+//! assert!(tour.length() < 1.5 * shortest);
+//!
+//! // Without crossings, a tour of cities on a circle goes around it
+//! tour.two_opt();
+//! assert!((tour.length() - shortest).abs() < 1e-9);
+//! # Ok::<(), ecosystem::EcosystemError>(())
+//! ```
+//!
+//! [`DistanceMatrix`]: struct.DistanceMatrix.html
+//! [TSPLIB]: http://comopt.ifi.uni-heidelberg.de/software/TSPLIB95/
+//! [`Tour`]: struct.Tour.html
+//! [`Permutation`]: ../genome/struct.Permutation.html
+//! [`two_opt`]: struct.Tour.html#method.two_opt
+use crate::{genome::Permutation, Organism};
+use rand::Rng;
+use std::{error::Error, fmt, fs, io, path::Path, sync::Arc};
+
+/// The distances between every pair of cities, which need not be
+/// symmetric.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DistanceMatrix {
+    size: usize,
+    distances: Vec<f64>,
+}
+
+impl DistanceMatrix {
+    /// Creates a matrix from its rows, where `rows[a][b]` is the distance
+    /// from city `a` to city `b`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the matrix is not square.
+    pub fn new(rows: Vec<Vec<f64>>) -> Self {
+        let size = rows.len();
+        assert!(
+            rows.iter().all(|row| row.len() == size),
+            "the matrix must be square"
+        );
+        Self {
+            size,
+            distances: rows.into_iter().flatten().collect(),
+        }
+    }
+
+    /// Creates a matrix of the Euclidean distances between cities at the
+    /// given (x, y) coordinates.
+    pub fn from_coordinates(cities: &[(f64, f64)]) -> Self {
+        Self::from_fn(cities.len(), |a, b| {
+            let (dx, dy) = (cities[a].0 - cities[b].0, cities[a].1 - cities[b].1);
+            (dx * dx + dy * dy).sqrt()
+        })
+    }
+
+    /// Parses a symmetric or asymmetric travelling salesman problem in the
+    /// TSPLIB format.
+    ///
+    /// Cities may be given by coordinates, with the `EUC_2D`, `CEIL_2D`,
+    /// `ATT` or `GEO` edge weight types, whose distances are rounded to
+    /// integers as TSPLIB defines them, or by an `EXPLICIT` matrix in the
+    /// `FULL_MATRIX`, `UPPER_ROW`, `LOWER_ROW`, `UPPER_DIAG_ROW` or
+    /// `LOWER_DIAG_ROW` format.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the text is malformed, or describes a
+    /// different kind of problem or an unsupported edge weight type or
+    /// format.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ecosystem::tsp::DistanceMatrix;
+    ///
+    /// let text = "\
+    /// NAME: triangle
+    /// TYPE: TSP
+    /// DIMENSION: 3
+    /// EDGE_WEIGHT_TYPE: EXPLICIT
+    /// EDGE_WEIGHT_FORMAT: UPPER_ROW
+    /// EDGE_WEIGHT_SECTION
+    /// 3 4
+    /// 5
+    /// EOF
+    /// ";
+    /// let distances = DistanceMatrix::from_tsplib(text).unwrap();
+    /// assert_eq!(distances.distance(0, 2), 4.0);
+    /// assert_eq!(distances.distance(2, 1), 5.0);
+    /// assert_eq!(distances.tour_length(&[0, 1, 2]), 12.0);
+    /// ```
+    pub fn from_tsplib(text: &str) -> Result<Self, TsplibError> {
+        let mut dimension = None;
+        let mut edge_weight_type = None;
+        let mut edge_weight_format = None;
+        let mut cities = Vec::new();
+        let mut weights = Vec::new();
+        let mut lines = text
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .peekable();
+        while let Some(line) = lines.next() {
+            let keyword = line.split(':').next().unwrap().trim();
+            match keyword {
+                "EOF" => break,
+                "NODE_COORD_SECTION" => {
+                    let dimension = dimension.ok_or_else(|| missing("DIMENSION"))?;
+                    for _ in 0..dimension {
+                        let line = lines.next().ok_or_else(|| {
+                            TsplibError::Syntax("too few cities in NODE_COORD_SECTION".into())
+                        })?;
+                        let numbers = parse_numbers(line)?;
+                        match numbers[..] {
+                            [_, x, y] => cities.push((x, y)),
+                            _ => {
+                                return Err(TsplibError::Syntax(format!(
+                                    "expected a city number and two coordinates, found '{}'",
+                                    line
+                                )))
+                            }
+                        }
+                    }
+                }
+                "EDGE_WEIGHT_SECTION" => {
+                    let dimension = dimension.ok_or_else(|| missing("DIMENSION"))?;
+                    let format = edge_weight_format.as_deref().unwrap_or("FULL_MATRIX");
+                    let count = weight_count(format, dimension)?;
+                    while weights.len() < count {
+                        let line = lines.next().ok_or_else(|| {
+                            TsplibError::Syntax("too few weights in EDGE_WEIGHT_SECTION".into())
+                        })?;
+                        weights.extend(parse_numbers(line)?);
+                    }
+                }
+                // Only the distances matter, so other sections are skipped
+                "DISPLAY_DATA_SECTION" | "FIXED_EDGES_SECTION" => {
+                    while lines.peek().is_some_and(|line| {
+                        line.starts_with(|c: char| c.is_ascii_digit() || c == '-')
+                    }) {
+                        lines.next();
+                    }
+                }
+                _ => {
+                    let value = match line.split_once(':') {
+                        Some((_, value)) => value.trim(),
+                        None => {
+                            return Err(TsplibError::Syntax(format!(
+                                "expected a 'KEY: value' line, found '{}'",
+                                line
+                            )))
+                        }
+                    };
+                    match keyword {
+                        "TYPE" if value != "TSP" && value != "ATSP" => {
+                            return Err(TsplibError::Unsupported(format!("TYPE: {}", value)))
+                        }
+                        "DIMENSION" => {
+                            dimension = Some(value.parse().map_err(|_| {
+                                TsplibError::Syntax(format!("invalid dimension '{}'", value))
+                            })?)
+                        }
+                        "EDGE_WEIGHT_TYPE" => edge_weight_type = Some(value.to_string()),
+                        "EDGE_WEIGHT_FORMAT" => edge_weight_format = Some(value.to_string()),
+                        _ => {}
+                    }
+                }
+            }
+        }
+
+        let dimension = dimension.ok_or_else(|| missing("DIMENSION"))?;
+        let edge_weight_type = edge_weight_type.ok_or_else(|| missing("EDGE_WEIGHT_TYPE"))?;
+        if edge_weight_type == "EXPLICIT" {
+            let format = edge_weight_format.as_deref().unwrap_or("FULL_MATRIX");
+            if weights.is_empty() && dimension > 0 {
+                return Err(missing("EDGE_WEIGHT_SECTION"));
+            }
+            return Ok(Self::from_explicit(format, dimension, &weights));
+        }
+
+        if cities.len() != dimension {
+            return Err(missing("NODE_COORD_SECTION"));
+        }
+        let distance: fn((f64, f64), (f64, f64)) -> f64 = match edge_weight_type.as_str() {
+            "EUC_2D" => |a, b| euclidean(a, b).round(),
+            "CEIL_2D" => |a, b| euclidean(a, b).ceil(),
+            "ATT" => pseudo_euclidean,
+            "GEO" => geographical,
+            _ => {
+                return Err(TsplibError::Unsupported(format!(
+                    "EDGE_WEIGHT_TYPE: {}",
+                    edge_weight_type
+                )))
+            }
+        };
+        Ok(Self::from_fn(dimension, |a, b| {
+            if a == b {
+                0.0
+            } else {
+                distance(cities[a], cities[b])
+            }
+        }))
+    }
+
+    /// Reads a problem from a TSPLIB file, as [`from_tsplib`] parses it.
+    ///
+    /// [`from_tsplib`]: #method.from_tsplib
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the file could not be read, or if it could
+    /// not be parsed.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, TsplibError> {
+        Self::from_tsplib(&fs::read_to_string(path)?)
+    }
+
+    /// Returns the number of cities.
+    pub fn len(&self) -> usize {
+        self.size
+    }
+
+    /// Returns whether there are no cities.
+    pub fn is_empty(&self) -> bool {
+        self.size == 0
+    }
+
+    /// Returns the distance from one city to another.
+    ///
+    /// # Panics
+    ///
+    /// Panics if either city is out of bounds.
+    pub fn distance(&self, from: usize, to: usize) -> f64 {
+        assert!(from < self.size && to < self.size, "no such city");
+        self.distances[from * self.size + to]
+    }
+
+    /// Returns the length of the tour that visits the cities in the given
+    /// order and returns to the first.
+    pub fn tour_length(&self, order: &[usize]) -> f64 {
+        if order.is_empty() {
+            return 0.0;
+        }
+        let closing = self.distance(order[order.len() - 1], order[0]);
+        order
+            .windows(2)
+            .map(|pair| self.distance(pair[0], pair[1]))
+            .sum::<f64>()
+            + closing
+    }
+
+    fn from_fn(size: usize, distance: impl Fn(usize, usize) -> f64) -> Self {
+        let distances = (0..size)
+            .flat_map(|a| (0..size).map(move |b| (a, b)))
+            .map(|(a, b)| distance(a, b))
+            .collect();
+        Self { size, distances }
+    }
+
+    /// Fills a matrix from the weights of an explicit TSPLIB matrix, whose
+    /// number has already been checked against the format.
+    fn from_explicit(format: &str, size: usize, weights: &[f64]) -> Self {
+        if format == "FULL_MATRIX" {
+            return Self {
+                size,
+                distances: weights[..size * size].to_vec(),
+            };
+        }
+        let entries: Vec<(usize, usize)> = match format {
+            "UPPER_ROW" => (0..size)
+                .flat_map(|a| (a + 1..size).map(move |b| (a, b)))
+                .collect(),
+            "LOWER_ROW" => (0..size)
+                .flat_map(|a| (0..a).map(move |b| (a, b)))
+                .collect(),
+            "UPPER_DIAG_ROW" => (0..size)
+                .flat_map(|a| (a..size).map(move |b| (a, b)))
+                .collect(),
+            _ => (0..size)
+                .flat_map(|a| (0..=a).map(move |b| (a, b)))
+                .collect(),
+        };
+        let mut matrix = Self {
+            size,
+            distances: vec![0.0; size * size],
+        };
+        for (&(a, b), &weight) in entries.iter().zip(weights) {
+            matrix.distances[a * size + b] = weight;
+            matrix.distances[b * size + a] = weight;
+        }
+        matrix
+    }
+}
+
+/// Returns the number of weights in an explicit TSPLIB matrix of the given
+/// format.
+fn weight_count(format: &str, size: usize) -> Result<usize, TsplibError> {
+    match format {
+        "FULL_MATRIX" => Ok(size * size),
+        "UPPER_ROW" | "LOWER_ROW" => Ok(size * size.saturating_sub(1) / 2),
+        "UPPER_DIAG_ROW" | "LOWER_DIAG_ROW" => Ok(size * (size + 1) / 2),
+        _ => Err(TsplibError::Unsupported(format!(
+            "EDGE_WEIGHT_FORMAT: {}",
+            format
+        ))),
+    }
+}
+
+fn parse_numbers(line: &str) -> Result<Vec<f64>, TsplibError> {
+    line.split_whitespace()
+        .map(|token| {
+            token
+                .parse()
+                .map_err(|_| TsplibError::Syntax(format!("invalid number '{}'", token)))
+        })
+        .collect()
+}
+
+fn missing(section: &str) -> TsplibError {
+    TsplibError::Syntax(format!("missing {}", section))
+}
+
+fn euclidean(a: (f64, f64), b: (f64, f64)) -> f64 {
+    ((a.0 - b.0).powi(2) + (a.1 - b.1).powi(2)).sqrt()
+}
+
+/// The pseudo-Euclidean distance of TSPLIB's `ATT` problems.
+fn pseudo_euclidean(a: (f64, f64), b: (f64, f64)) -> f64 {
+    let distance = (((a.0 - b.0).powi(2) + (a.1 - b.1).powi(2)) / 10.0).sqrt();
+    let rounded = distance.round();
+    if rounded < distance {
+        rounded + 1.0
+    } else {
+        rounded
+    }
+}
+
+/// The distance in kilometres between two places on an idealised Earth,
+/// given by their latitude and longitude in degrees and minutes (so that
+/// 16.47 is 16 degrees and 47 minutes), as TSPLIB's `GEO` problems define
+/// it.
+fn geographical(a: (f64, f64), b: (f64, f64)) -> f64 {
+    // TSPLIB defines these distances with pi truncated to six places
+    #[allow(clippy::approx_constant)]
+    const PI: f64 = 3.141592;
+    const RADIUS: f64 = 6378.388;
+    let radians = |degrees_minutes: f64| {
+        let degrees = degrees_minutes.trunc();
+        let minutes = degrees_minutes - degrees;
+        PI * (degrees + 5.0 * minutes / 3.0) / 180.0
+    };
+    let (latitude_a, longitude_a) = (radians(a.0), radians(a.1));
+    let (latitude_b, longitude_b) = (radians(b.0), radians(b.1));
+    let q1 = (longitude_a - longitude_b).cos();
+    let q2 = (latitude_a - latitude_b).cos();
+    let q3 = (latitude_a + latitude_b).cos();
+    (RADIUS * (0.5 * ((1.0 + q1) * q2 - (1.0 - q1) * q3)).acos() + 1.0).trunc()
+}
+
+/// An error arising while loading a TSPLIB problem.
+#[derive(Debug)]
+pub enum TsplibError {
+    /// The file could not be read.
+    Io(io::Error),
+    /// The text was malformed.
+    Syntax(String),
+    /// The text described a kind of problem, edge weight type or format, or
+    /// section that is not supported.
+    Unsupported(String),
+}
+
+impl fmt::Display for TsplibError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TsplibError::Io(error) => write!(f, "failed to read the problem: {}", error),
+            TsplibError::Syntax(reason) => write!(f, "malformed problem: {}", reason),
+            TsplibError::Unsupported(what) => write!(f, "unsupported problem: {}", what),
+        }
+    }
+}
+
+impl Error for TsplibError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            TsplibError::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for TsplibError {
+    fn from(error: io::Error) -> Self {
+        TsplibError::Io(error)
+    }
+}
+
+/// A tour that visits the cities of a [`DistanceMatrix`] in the order of a
+/// permutation, whose fitness is its length.
+///
+/// Children are bred by [order crossover], which keeps runs of cities in the
+/// order the parents visit them, and mutated by [inversion], which reverses
+/// part of the route with the ecosystem's mutation rate.
+///
+/// [`DistanceMatrix`]: struct.DistanceMatrix.html
+/// [order crossover]: ../genome/struct.Permutation.html#method.order_crossover
+/// [inversion]: ../genome/struct.Permutation.html#method.inversion_mutation
+pub struct Tour {
+    order: Permutation,
+    distances: Arc<DistanceMatrix>,
+}
+
+impl Tour {
+    /// Creates a tour that visits the cities in the given order.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the order does not visit every city of the matrix.
+    pub fn new(order: Permutation, distances: &Arc<DistanceMatrix>) -> Self {
+        assert_eq!(
+            order.len(),
+            distances.len(),
+            "the tour must visit every city"
+        );
+        Self {
+            order,
+            distances: Arc::clone(distances),
+        }
+    }
+
+    /// Creates a tour that visits the cities in a random order.
+    pub fn random(distances: &Arc<DistanceMatrix>, rng: &mut impl Rng) -> Self {
+        Self::new(Permutation::random(distances.len(), rng), distances)
+    }
+
+    /// Returns the order in which the cities are visited.
+    pub fn order(&self) -> &Permutation {
+        &self.order
+    }
+
+    /// Returns the distances between the cities.
+    pub fn distances(&self) -> &Arc<DistanceMatrix> {
+        &self.distances
+    }
+
+    /// Returns the length of the tour.
+    pub fn length(&self) -> f64 {
+        self.distances.tour_length(self.order.as_slice())
+    }
+
+    /// Improves the tour by 2-opt local search, returning how much shorter
+    /// it became.
+    ///
+    /// Each move replaces two links of the tour with the two that reconnect
+    /// it the other way, by reversing the route between them. The first
+    /// move that shortens the tour is made, until there are none left, so
+    /// the tour ends with no crossings. Each pass over the moves takes time
+    /// proportional to the square of the number of cities. The distances
+    /// are assumed to be symmetric.
+    pub fn two_opt(&mut self) -> f64 {
+        let n = self.order.len();
+        let before = self.length();
+        let mut improved = true;
+        while improved {
+            improved = false;
+            for i in 0..n.saturating_sub(1) {
+                for j in i + 2..n {
+                    let order = self.order.as_slice();
+                    let (a, b) = (order[i], order[i + 1]);
+                    let (c, d) = (order[j], order[(j + 1) % n]);
+                    if a == d {
+                        continue;
+                    }
+                    let change = self.distances.distance(a, c) + self.distances.distance(b, d)
+                        - self.distances.distance(a, b)
+                        - self.distances.distance(c, d);
+                    if change < -1e-10 {
+                        self.order.reverse(i + 1, j + 1);
+                        improved = true;
+                    }
+                }
+            }
+        }
+        before - self.length()
+    }
+
+    fn with_order(&self, order: Permutation) -> Self {
+        Self {
+            order,
+            distances: Arc::clone(&self.distances),
+        }
+    }
+}
+
+impl Organism for Tour {
+    type Fitness = f64;
+
+    fn fitness(&self) -> f64 {
+        self.length()
+    }
+
+    fn breed(&self, other: &Self, rng: &mut impl Rng) -> Self {
+        self.breed_pair(other, rng).0
+    }
+
+    fn breed_pair(&self, other: &Self, rng: &mut impl Rng) -> (Self, Self) {
+        let (first, second) = self.order.order_crossover(&other.order, rng);
+        (self.with_order(first), other.with_order(second))
+    }
+
+    fn mutate(&mut self, rate: f64, rng: &mut impl Rng) {
+        self.order.inversion_mutation(rate, rng);
+    }
+}
+
+impl Clone for Tour {
+    fn clone(&self) -> Self {
+        self.with_order(self.order.clone())
+    }
+}
+
+impl fmt::Debug for Tour {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Tour").field("order", &self.order).finish()
+    }
+}
+
+/// Tours are equal if they visit the cities in the same order, whatever
+/// their distances.
+impl PartialEq for Tour {
+    fn eq(&self, other: &Self) -> bool {
+        self.order == other.order
+    }
+}